@@ -0,0 +1,416 @@
+//! Checks that an engine follows the UCI protocol strictly: the `uci` handshake, `isready`,
+//! a `go depth 5` search, `stop` of a `go infinite` search and `quit`. Every line of the engine
+//! is parsed and a pass/fail report with the offending lines is printed.
+//!
+//! Run it with `cargo run --example conformance -- <path to the engine or ws:// URL>`.
+//! The exit code is 0 if every check passes, 1 if any fails and 2 if the engine can't be reached.
+
+use std::{fmt::Display, time::Duration};
+
+use remote_stockfish_client::RemoteChessEngine;
+use uci_beyond::{
+    engine_commands::{AnyEngineCommand, IdCommand},
+    gui_command_responses::GoEvent,
+    util::{Connection, IoConnection},
+};
+
+/// How long the engine may stay silent while an answer is expected.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long `go infinite` searches before `stop`.
+const INFINITE_SEARCH: Duration = Duration::from_millis(200);
+
+/// A line of the engine that breaks the protocol.
+#[derive(Debug)]
+struct Offense {
+    line: String,
+    reason: String,
+}
+
+/// The outcome of a single check.
+#[derive(Debug)]
+struct CheckReport {
+    name: &'static str,
+    offenses: Vec<Offense>,
+    /// Why the check couldn't complete, e.g. a timeout or an error of the connection.
+    failure: Option<String>,
+}
+
+impl CheckReport {
+    fn passed(&self) -> bool {
+        self.offenses.is_empty() && self.failure.is_none()
+    }
+}
+
+/// The outcomes of all the checks, in order.
+#[derive(Debug, Default)]
+struct Report {
+    checks: Vec<CheckReport>,
+}
+
+impl Report {
+    fn add(&mut self, name: &'static str, offenses: Vec<Offense>, result: Result<(), String>) {
+        self.checks.push(CheckReport {
+            name,
+            offenses,
+            failure: result.err(),
+        });
+    }
+
+    fn passed(&self) -> bool {
+        self.checks.iter().all(CheckReport::passed)
+    }
+
+    fn exit_code(&self) -> i32 {
+        if self.passed() { 0 } else { 1 }
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for check in &self.checks {
+            let verdict = if check.passed() { "PASS" } else { "FAIL" };
+            writeln!(f, "{verdict} {}", check.name)?;
+            for offense in &check.offenses {
+                writeln!(f, "    `{}`: {}", offense.line, offense.reason)?;
+            }
+            if let Some(failure) = &check.failure {
+                writeln!(f, "    {failure}")?;
+            }
+        }
+        let passed = self.checks.iter().filter(|check| check.passed()).count();
+        writeln!(f, "{passed}/{} checks passed", self.checks.len())
+    }
+}
+
+fn offend(offenses: &mut Vec<Offense>, line: &str, reason: impl Display) {
+    offenses.push(Offense {
+        line: line.to_string(),
+        reason: reason.to_string(),
+    });
+}
+
+async fn send<C: Connection>(connection: &mut C, line: &str) -> Result<(), String> {
+    connection
+        .send_raw_line(line)
+        .await
+        .map_err(|e| format!("failed to send `{line}`: {e:?}"))
+}
+
+/// Reads the next non-empty line, which must arrive in time.
+async fn next_line<C: Connection>(
+    connection: &mut C,
+    timeout: Duration,
+    expected: &str,
+) -> Result<String, String> {
+    loop {
+        match tokio::time::timeout(timeout, connection.read_raw_line()).await {
+            Ok(Ok(Some(line))) if line.trim().is_empty() => continue,
+            Ok(Ok(Some(line))) => return Ok(line),
+            Ok(Ok(None)) => {
+                return Err(format!("the engine closed its output before `{expected}`"));
+            }
+            Ok(Err(e)) => return Err(format!("the connection failed before `{expected}`: {e:?}")),
+            Err(_elapsed) => return Err(format!("no `{expected}` within {timeout:?}")),
+        }
+    }
+}
+
+/// `uci`: the banner, if any, then `id name` and `id author`, the options with unique names and `uciok`.
+async fn handshake<C: Connection>(
+    connection: &mut C,
+    timeout: Duration,
+    offenses: &mut Vec<Offense>,
+) -> Result<(), String> {
+    send(connection, "uci").await?;
+
+    let (mut name, mut author) = (false, false);
+    let mut options = Vec::<String>::new();
+    loop {
+        let line = next_line(connection, timeout, "uciok").await?;
+        let started = name || author || !options.is_empty();
+        match line.parse::<AnyEngineCommand>() {
+            Err(e) => offend(offenses, &line, e),
+            // The banner, e.g. of Stockfish, comes before the id block
+            Ok(AnyEngineCommand::Other(_)) if !started => (),
+            Ok(AnyEngineCommand::Id(id)) => {
+                let seen = match id {
+                    IdCommand::Name(_) => &mut name,
+                    IdCommand::Author(_) => &mut author,
+                };
+                if *seen {
+                    offend(offenses, &line, "repeated `id` line");
+                } else if !options.is_empty() {
+                    offend(offenses, &line, "`id` after the options");
+                }
+                *seen = true;
+            }
+            Ok(AnyEngineCommand::Option(option)) => {
+                let option_name = option.0.name();
+                if options.iter().any(|o| o.eq_ignore_ascii_case(option_name)) {
+                    offend(offenses, &line, "repeated option");
+                } else {
+                    options.push(option_name.to_string());
+                }
+            }
+            Ok(AnyEngineCommand::UciOk(_)) => break,
+            Ok(_) => offend(offenses, &line, "unexpected before `uciok`"),
+        }
+    }
+
+    match (name, author) {
+        (false, _) => Err("no `id name` before `uciok`".to_string()),
+        (_, false) => Err("no `id author` before `uciok`".to_string()),
+        (true, true) => Ok(()),
+    }
+}
+
+/// `isready`: `readyok`, with nothing but `info` lines before it.
+async fn is_ready<C: Connection>(
+    connection: &mut C,
+    timeout: Duration,
+    offenses: &mut Vec<Offense>,
+) -> Result<(), String> {
+    send(connection, "isready").await?;
+    loop {
+        let line = next_line(connection, timeout, "readyok").await?;
+        match line.parse::<AnyEngineCommand>() {
+            Ok(AnyEngineCommand::ReadyOk(_)) => return Ok(()),
+            Ok(AnyEngineCommand::Info(_)) => (),
+            Ok(_) => offend(offenses, &line, "unexpected before `readyok`"),
+            Err(e) => offend(offenses, &line, e),
+        }
+    }
+}
+
+/// Reads the search up to `bestmove`, every line being an `info` or the `bestmove`.
+async fn read_search<C: Connection>(
+    connection: &mut C,
+    timeout: Duration,
+    offenses: &mut Vec<Offense>,
+) -> Result<(), String> {
+    loop {
+        let line = next_line(connection, timeout, "bestmove").await?;
+        match line.parse::<GoEvent>() {
+            Ok(GoEvent::BestMove(_)) => return Ok(()),
+            Ok(_) => (),
+            Err(e) => offend(offenses, &line, e),
+        }
+    }
+}
+
+/// `go depth 5` from the start position, after `ucinewgame`.
+async fn search<C: Connection>(
+    connection: &mut C,
+    timeout: Duration,
+    offenses: &mut Vec<Offense>,
+) -> Result<(), String> {
+    send(connection, "ucinewgame").await?;
+    is_ready(connection, timeout, offenses).await?;
+    send(connection, "position startpos").await?;
+    send(connection, "go depth 5").await?;
+    read_search(connection, timeout, offenses).await
+}
+
+/// `go infinite`, which must not end before `stop`, and then `stop`, which must end it with `bestmove`.
+async fn stop<C: Connection>(
+    connection: &mut C,
+    timeout: Duration,
+    offenses: &mut Vec<Offense>,
+) -> Result<(), String> {
+    send(connection, "position startpos moves e2e4").await?;
+    send(connection, "go infinite").await?;
+
+    let deadline = tokio::time::Instant::now() + INFINITE_SEARCH;
+    loop {
+        let line = match tokio::time::timeout_at(deadline, connection.read_raw_line()).await {
+            Ok(Ok(Some(line))) => line,
+            Ok(Ok(None)) => return Err("the engine closed its output before `stop`".to_string()),
+            Ok(Err(e)) => return Err(format!("the connection failed before `stop`: {e:?}")),
+            Err(_elapsed) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.parse::<GoEvent>() {
+            Ok(GoEvent::BestMove(_)) => {
+                offend(offenses, &line, "`bestmove` of `go infinite` before `stop`");
+                return Ok(());
+            }
+            Ok(_) => (),
+            Err(e) => offend(offenses, &line, e),
+        }
+    }
+
+    send(connection, "stop").await?;
+    read_search(connection, timeout, offenses).await
+}
+
+/// `quit`: the engine closes its output without writing anything.
+async fn quit<C: Connection>(
+    connection: &mut C,
+    timeout: Duration,
+    offenses: &mut Vec<Offense>,
+) -> Result<(), String> {
+    send(connection, "quit").await?;
+    loop {
+        match tokio::time::timeout(timeout, connection.read_raw_line()).await {
+            Ok(Ok(Some(line))) => offend(offenses, &line, "output after `quit`"),
+            // A closed connection is a closed output too
+            Ok(Ok(None) | Err(_)) => return Ok(()),
+            Err(_elapsed) => return Err(format!("still running {timeout:?} after `quit`")),
+        }
+    }
+}
+
+/// Runs the checks in order. A failed check doesn't stop the next ones.
+async fn run<C: Connection>(connection: &mut C, timeout: Duration) -> Report {
+    let mut report = Report::default();
+
+    let mut offenses = Vec::new();
+    let result = handshake(connection, timeout, &mut offenses).await;
+    report.add("handshake", offenses, result);
+
+    let mut offenses = Vec::new();
+    let result = is_ready(connection, timeout, &mut offenses).await;
+    report.add("isready", offenses, result);
+
+    let mut offenses = Vec::new();
+    let result = search(connection, timeout, &mut offenses).await;
+    report.add("go depth 5", offenses, result);
+
+    let mut offenses = Vec::new();
+    let result = stop(connection, timeout, &mut offenses).await;
+    report.add("stop", offenses, result);
+
+    let mut offenses = Vec::new();
+    let result = quit(connection, timeout, &mut offenses).await;
+    report.add("quit", offenses, result);
+
+    report
+}
+
+#[tokio::main]
+async fn main() {
+    let Some(engine) = std::env::args().nth(1) else {
+        eprintln!("Usage: conformance <path to the engine or ws:// URL>");
+        std::process::exit(2);
+    };
+
+    let report = if engine.starts_with("ws://") || engine.starts_with("wss://") {
+        match RemoteChessEngine::new(engine.as_str()).connect().await {
+            Ok(mut connection) => run(&mut connection, DEFAULT_TIMEOUT).await,
+            Err(e) => {
+                eprintln!("Failed to connect to {engine}: {e}");
+                std::process::exit(2);
+            }
+        }
+    } else {
+        match IoConnection::spawn(tokio::process::Command::new(&engine).kill_on_drop(true)) {
+            Ok(mut connection) => run(&mut connection, DEFAULT_TIMEOUT).await,
+            Err(e) => {
+                eprintln!("Failed to start {engine}: {e}");
+                std::process::exit(2);
+            }
+        }
+    };
+
+    print!("{report}");
+    std::process::exit(report.exit_code());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uci_beyond::fixtures::FakeEngine;
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// A small engine. The broken one gets the protocol wrong in every check but `isready`.
+    fn fake_engine(broken: bool) -> FakeEngine {
+        FakeEngine::new(move |line| {
+            Some(match (line, broken) {
+                ("uci", false) => {
+                    "id name Fake 1.0\nid author Somebody\n\noption name Hash type spin default 16 min 1 max 1024\nuciok\n"
+                }
+                ("uci", true) => {
+                    "id name Fake 1.0\noption name Threads type spin default 1 min 1 max 64\noption name Hash type spinny default 16\nid author Somebody\nuciok\n"
+                }
+                ("isready", _) => "readyok\n",
+                ("go depth 5", false) => {
+                    "info depth 1 seldepth 1 multipv 1 score cp 20 nodes 20 nps 20000 time 1 pv e2e4\nbestmove e2e4 ponder e7e5\n"
+                }
+                ("go depth 5", true) => {
+                    "info depth 1 seldepth 1 multipv 1 score cp 20 nodes 20 nps 20000 time 1 pv e2e4\nthinking...\nbestmove e2e4\n"
+                }
+                ("go infinite", _) => {
+                    "info depth 1 seldepth 1 multipv 1 score cp -20 nodes 20 nps 20000 time 1 pv e7e5\n"
+                }
+                // The broken one never stops
+                ("stop", false) => "bestmove e7e5\n",
+                ("quit", true) => "bye\n",
+                _ => "",
+            })
+        })
+        .with_greeting("Fake 1.0 by Somebody\n")
+    }
+
+    async fn run_against_fake(broken: bool) -> Report {
+        let (mut connection, engine) = fake_engine(broken).connect();
+
+        let report = run(&mut connection, TIMEOUT).await;
+        engine.await.unwrap();
+        report
+    }
+
+    #[tokio::test]
+    async fn test_conformance_of_fake_engine() {
+        let report = run_against_fake(false).await;
+        assert!(report.passed(), "{report}");
+        assert_eq!(report.exit_code(), 0);
+        assert_eq!(
+            report.to_string(),
+            "PASS handshake\nPASS isready\nPASS go depth 5\nPASS stop\nPASS quit\n5/5 checks passed\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conformance_of_broken_engine() {
+        let report = run_against_fake(true).await;
+        assert_eq!(report.exit_code(), 1);
+
+        let failed = report
+            .checks
+            .iter()
+            .filter(|check| !check.passed())
+            .map(|check| check.name)
+            .collect::<Vec<_>>();
+        assert_eq!(failed, ["handshake", "go depth 5", "stop", "quit"]);
+
+        let handshake = &report.checks[0];
+        assert_eq!(
+            handshake
+                .offenses
+                .iter()
+                .map(|offense| offense.line.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "option name Hash type spinny default 16",
+                "id author Somebody"
+            ]
+        );
+        assert_eq!(handshake.offenses[1].reason, "`id` after the options");
+        assert_eq!(report.checks[2].offenses[0].line, "thinking...");
+        assert_eq!(
+            report.checks[3].failure.as_deref(),
+            Some("no `bestmove` within 500ms")
+        );
+        assert_eq!(report.checks[4].offenses[0].line, "bye");
+
+        let rendered = report.to_string();
+        assert!(
+            rendered.contains("FAIL handshake\n    `option name Hash type spinny default 16`: ")
+        );
+        assert!(rendered.ends_with("1/5 checks passed\n"));
+    }
+}
@@ -0,0 +1,576 @@
+//! An interactive console for a UCI engine: the lines typed on stdin are parsed as GUI commands and sent,
+//! while the lines of the engine are parsed and printed, with the options as a table and the `info depth`
+//! lines updated in place. Ctrl-C sends `stop` and `quit` before exiting.
+//!
+//! Run it with `cargo run --example uci_repl -- [flags] <path to the engine, ws:// URL or tcp://host:port>`.
+//!
+//! * `--raw` sends the lines that fail to parse as they are, after reporting the error.
+//! * `--crlf` ends the printed lines with `\r\n` instead of `\n`.
+//! * `--no-flush` flushes stdout only when the engine falls silent, e.g. when piping a long search to a file.
+//! * `--plain` disables the colors and the in-place updates, which is the default when stdout isn't a terminal.
+//!
+//! The commands may also be piped, e.g. `printf 'uci\ngo depth 10\n' | cargo run --example uci_repl -- stockfish`.
+//! At the end of the input, `quit` is sent once the engine falls silent.
+
+use std::{
+    future::Future,
+    io::{IsTerminal as _, Write},
+    time::Duration,
+};
+
+use remote_stockfish_client::{RemoteChessEngine, TcpChessEngine};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt as _};
+use uci_beyond::{
+    engine_commands::{AnyEngineCommand, DepthInfoCommand, InfoCommand},
+    gui_commands::AnyGuiCommand,
+    util::{Connection, IoConnection},
+};
+
+/// How long the engine stays silent before stdout is flushed with `--no-flush` and, at the end
+/// of the input, before `quit` is sent.
+const DEFAULT_QUIET: Duration = Duration::from_millis(500);
+
+/// The line ending of the printed lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Newline {
+    Lf,
+    CrLf,
+}
+
+impl Newline {
+    fn as_str(self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// When stdout is flushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flush {
+    /// After every printed line, for typing the commands.
+    EveryLine,
+    /// When the engine falls silent, and before exiting.
+    WhenQuiet,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    /// Send the lines that fail to parse as they are.
+    raw: bool,
+    newline: Newline,
+    flush: Flush,
+    /// Color the output with ANSI escapes.
+    color: bool,
+    /// Update the `info depth` lines in place rather than printing each of them.
+    live: bool,
+    /// See [`DEFAULT_QUIET`].
+    quiet: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            raw: false,
+            newline: Newline::Lf,
+            flush: Flush::EveryLine,
+            color: false,
+            live: false,
+            quiet: DEFAULT_QUIET,
+        }
+    }
+}
+
+/// The error that ends the console.
+#[derive(Debug)]
+enum ReplError<E> {
+    /// The connection to the engine failed.
+    Engine(E),
+    /// Reading the commands or printing failed.
+    Io(std::io::Error),
+}
+
+impl<E: std::fmt::Debug> std::fmt::Display for ReplError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplError::Engine(e) => write!(f, "The connection to the engine failed: {e:?}"),
+            ReplError::Io(e) => write!(f, "Failed to read the commands or to print: {e}"),
+        }
+    }
+}
+
+const BOLD: &str = "1";
+const DIM: &str = "2";
+const RED: &str = "31";
+const GREEN: &str = "32";
+const YELLOW: &str = "33";
+const CYAN: &str = "36";
+
+/// Prints the output of the console according to the [`Config`].
+struct Printer<W> {
+    out: W,
+    config: Config,
+    /// An `info depth` line is printed without its line ending, to be overwritten.
+    live_line: bool,
+    /// The last printed line is an option, so the next one continues the table.
+    in_options: bool,
+}
+
+impl<W: Write> Printer<W> {
+    fn new(out: W, config: Config) -> Self {
+        Self {
+            out,
+            config,
+            live_line: false,
+            in_options: false,
+        }
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.config.color {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn line(&mut self, text: &str) -> std::io::Result<()> {
+        let newline = self.config.newline.as_str();
+        if self.live_line {
+            self.live_line = false;
+            write!(self.out, "{newline}")?;
+        }
+        write!(self.out, "{text}{newline}")?;
+        if self.config.flush == Flush::EveryLine {
+            self.out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Prints the line over the previous `info depth` line, if any.
+    fn live(&mut self, text: &str) -> std::io::Result<()> {
+        if !self.config.live {
+            return self.line(text);
+        }
+        // Back to the start of the line and erase it
+        write!(self.out, "\r\x1b[2K{text}")?;
+        self.live_line = true;
+        // The line is useless unless it is seen as it changes
+        self.out.flush()
+    }
+
+    fn error(&mut self, text: &str) -> std::io::Result<()> {
+        let text = self.paint(RED, text);
+        self.line(&text)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+
+    fn engine_line(&mut self, line: &str) -> std::io::Result<()> {
+        let cmd = match line.parse::<AnyEngineCommand>() {
+            Ok(cmd) => cmd,
+            Err(e) => return self.error(&format!("{line}  <- {e}")),
+        };
+
+        let in_options = std::mem::replace(&mut self.in_options, false);
+        match cmd {
+            AnyEngineCommand::Option(cmd) => {
+                if !in_options {
+                    let header =
+                        self.paint(BOLD, &format!("{:<24} {:<7} declaration", "option", "type"));
+                    self.line(&header)?;
+                }
+                self.in_options = true;
+                let option = cmd.0;
+                let row = format!(
+                    "{} {} {}",
+                    self.paint(CYAN, &format!("{:<24}", option.name())),
+                    self.paint(YELLOW, &format!("{:<7}", option.r#type().to_string())),
+                    option.typed_data(),
+                );
+                self.line(row.trim_end())
+            }
+            AnyEngineCommand::Info(InfoCommand::Depth(info)) => {
+                let summary = self.depth_summary(&info);
+                self.live(&summary)
+            }
+            AnyEngineCommand::Info(InfoCommand::String(text)) => {
+                let text = self.paint(DIM, &format!("info string {text}"));
+                self.line(&text)
+            }
+            AnyEngineCommand::BestMove(cmd) => {
+                let text = self.paint(GREEN, &cmd.to_string());
+                self.line(&text)
+            }
+            _ => self.line(line),
+        }
+    }
+
+    /// The fields of the line that are present, e.g. `depth 5 score cp 20 nodes 120 pv e2e4 e7e5`.
+    fn depth_summary(&self, info: &DepthInfoCommand) -> String {
+        let mut fields = Vec::new();
+        if let Some(depth) = info.depth {
+            fields.push(self.paint(BOLD, &format!("depth {depth}")));
+        }
+        if let Some(multipv) = info.multipv {
+            fields.push(format!("multipv {multipv}"));
+        }
+        if let Some(score) = &info.score {
+            fields.push(self.paint(YELLOW, &format!("score {score}")));
+        }
+        if let Some(nodes) = info.nodes {
+            fields.push(format!("nodes {nodes}"));
+        }
+        if let Some(nps) = info.nps {
+            fields.push(format!("nps {nps}"));
+        }
+        if let Some(time) = info.time {
+            fields.push(format!("time {time}"));
+        }
+        if !info.pv.is_empty() {
+            let pv = info
+                .pv
+                .iter()
+                .map(|mv| mv.0.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            fields.push(format!("pv {pv}"));
+        }
+        fields.join(" ")
+    }
+}
+
+/// What the console is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// The next command or the next line of the engine.
+    Running,
+    /// The input has ended, so `quit` is sent once the engine falls silent.
+    InputClosed,
+    /// `quit` has been sent, so the console exits when the engine closes the connection or falls silent.
+    Quitting,
+}
+
+/// Sends the commands read from `input` and prints the lines of the engine to `output` until `quit`,
+/// the end of the connection or `interrupt`, which sends `stop` and `quit`.
+async fn run<C, I, W>(
+    connection: &mut C,
+    input: I,
+    output: W,
+    config: Config,
+    interrupt: impl Future<Output = ()>,
+) -> Result<(), ReplError<C::Err>>
+where
+    C: Connection,
+    I: AsyncBufRead + Unpin,
+    W: Write,
+{
+    let quiet = config.quiet;
+    let raw = config.raw;
+    let mut printer = Printer::new(output, config);
+    let mut commands = input.lines();
+    let mut state = State::Running;
+    let mut interrupt = std::pin::pin!(interrupt);
+    let mut interrupted = false;
+
+    loop {
+        tokio::select! {
+            line = commands.next_line(), if state == State::Running => {
+                let Some(line) = line.map_err(ReplError::Io)? else {
+                    state = State::InputClosed;
+                    continue;
+                };
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let sent = match line.parse::<AnyGuiCommand>() {
+                    Ok(cmd) => {
+                        if matches!(cmd, AnyGuiCommand::Quit(_)) {
+                            state = State::Quitting;
+                        }
+                        Some(cmd.to_string())
+                    }
+                    Err(e) => {
+                        printer
+                            .error(&format!("Failed to parse `{line}`: {e}"))
+                            .map_err(ReplError::Io)?;
+                        if raw {
+                            Some(line.to_string())
+                        } else {
+                            printer
+                                .line("Not sent. Pass --raw to send such lines as they are.")
+                                .map_err(ReplError::Io)?;
+                            None
+                        }
+                    }
+                };
+                if let Some(sent) = sent {
+                    connection.send_raw_line(&sent).await.map_err(ReplError::Engine)?;
+                }
+            }
+            line = connection.read_raw_line() => {
+                match line.map_err(ReplError::Engine)? {
+                    Some(line) => printer.engine_line(&line).map_err(ReplError::Io)?,
+                    None => {
+                        if state != State::Quitting {
+                            printer.error("The engine has closed the connection.").map_err(ReplError::Io)?;
+                        }
+                        break;
+                    }
+                }
+            }
+            () = &mut interrupt, if !interrupted => {
+                interrupted = true;
+                printer.line("Interrupted, sending `stop` and `quit`").map_err(ReplError::Io)?;
+                connection.send_raw_line("stop").await.map_err(ReplError::Engine)?;
+                connection.send_raw_line("quit").await.map_err(ReplError::Engine)?;
+                state = State::Quitting;
+            }
+            () = tokio::time::sleep(quiet) => {
+                printer.flush().map_err(ReplError::Io)?;
+                match state {
+                    State::Running => (),
+                    State::InputClosed => {
+                        connection.send_raw_line("quit").await.map_err(ReplError::Engine)?;
+                        state = State::Quitting;
+                    }
+                    // The engine keeps the connection open after `quit`, e.g. a remote one
+                    State::Quitting => break,
+                }
+            }
+        }
+    }
+    printer.flush().map_err(ReplError::Io)
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: uci_repl [--raw] [--crlf] [--no-flush] [--plain] <path to the engine, ws:// URL or tcp://host:port>"
+    );
+    std::process::exit(2);
+}
+
+#[tokio::main]
+async fn main() {
+    let terminal = std::io::stdout().is_terminal();
+    let mut config = Config {
+        color: terminal,
+        live: terminal,
+        ..Config::default()
+    };
+    let mut engine = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--raw" => config.raw = true,
+            "--crlf" => config.newline = Newline::CrLf,
+            "--no-flush" => config.flush = Flush::WhenQuiet,
+            "--plain" => {
+                config.color = false;
+                config.live = false;
+            }
+            flag if flag.starts_with("--") => usage(),
+            _ if engine.is_some() => usage(),
+            _ => engine = Some(arg),
+        }
+    }
+    let Some(engine) = engine else { usage() };
+
+    let input = tokio::io::BufReader::new(tokio::io::stdin());
+    let output = std::io::stdout().lock();
+    let interrupt = async {
+        // Without the handler, Ctrl-C kills the console as usual
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+
+    let result = if engine.starts_with("ws://") || engine.starts_with("wss://") {
+        match RemoteChessEngine::new(engine.as_str()).connect().await {
+            Ok(mut connection) => run(&mut connection, input, output, config, interrupt)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(format!("Failed to connect to {engine}: {e}")),
+        }
+    } else if let Some(addr) = engine.strip_prefix("tcp://") {
+        match TcpChessEngine::new(addr).connect().await {
+            Ok(mut connection) => run(&mut connection, input, output, config, interrupt)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(format!("Failed to connect to {engine}: {e}")),
+        }
+    } else {
+        match IoConnection::spawn(tokio::process::Command::new(&engine).kill_on_drop(true)) {
+            Ok(mut connection) => run(&mut connection, input, output, config, interrupt)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(format!("Failed to start {engine}: {e}")),
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::io::BufReader;
+    use uci_beyond::fixtures::FakeEngine;
+
+    const QUIET: Duration = Duration::from_millis(100);
+
+    /// A small engine.
+    fn fake_engine() -> FakeEngine {
+        FakeEngine::new(|line| {
+            Some(match line {
+                "uci" => {
+                    "id name Fake 1.0\nid author Somebody\noption name Hash type spin default 16 min 1 max 1024\noption name Ponder type check default false\nuciok\n"
+                }
+                "isready" => "readyok\n",
+                "go depth 5" => {
+                    "info depth 1 score cp 20 nodes 20 pv e2e4\ninfo depth 2 score cp 15 nodes 80 pv e2e4 e7e5\nbestmove e2e4 ponder e7e5\n"
+                }
+                "go infinite" => "info depth 1 score cp 20 nodes 20 pv e2e4\n",
+                "stop" => "bestmove e2e4\n",
+                _ => "",
+            })
+        })
+    }
+
+    /// Runs the console with the commands against the fake engine, returning the output and the lines received by the engine.
+    async fn run_script(
+        commands: &'static str,
+        config: Config,
+        interrupt: impl Future<Output = ()>,
+    ) -> (String, Vec<String>) {
+        let (mut connection, engine) = fake_engine().connect();
+
+        let mut output = Vec::new();
+        run(
+            &mut connection,
+            commands.as_bytes(),
+            &mut output,
+            config,
+            interrupt,
+        )
+        .await
+        .unwrap();
+        let received = engine.await.unwrap();
+
+        (String::from_utf8(output).unwrap(), received)
+    }
+
+    fn config() -> Config {
+        Config {
+            quiet: QUIET,
+            ..Config::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repl_session() {
+        let (output, received) = run_script(
+            "uci\n\nisready\nuci now\ngo depth 5\nquit\n",
+            config(),
+            std::future::pending(),
+        )
+        .await;
+
+        // The malformed `uci now` isn't sent
+        assert_eq!(received, ["uci", "isready", "go depth 5", "quit"]);
+
+        // The errors are printed as soon as the command is read, so possibly amid the lines of the engine
+        let (errors, lines): (Vec<_>, Vec<_>) = output
+            .lines()
+            .partition(|line| line.starts_with("Failed to parse") || line.starts_with("Not sent."));
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("Failed to parse `uci now`: "));
+        assert_eq!(
+            lines,
+            [
+                "id name Fake 1.0",
+                "id author Somebody",
+                "option                   type    declaration",
+                "Hash                     spin    default 16 min 1 max 1024",
+                "Ponder                   check   default false",
+                "uciok",
+                "readyok",
+                "depth 1 score cp 20 nodes 20 pv e2e4",
+                "depth 2 score cp 15 nodes 80 pv e2e4 e7e5",
+                "bestmove e2e4 ponder e7e5",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repl_raw_crlf_and_end_of_input() {
+        let config = Config {
+            raw: true,
+            newline: Newline::CrLf,
+            flush: Flush::WhenQuiet,
+            ..config()
+        };
+        let (output, received) =
+            run_script("uci now\nisready\n", config, std::future::pending()).await;
+
+        // `quit` follows the end of the input
+        assert_eq!(received, ["uci now", "isready", "quit"]);
+        assert!(output.ends_with("readyok\r\n"));
+        assert!(!output.contains("Not sent."));
+    }
+
+    #[tokio::test]
+    async fn test_repl_live_depth_lines() {
+        let config = Config {
+            live: true,
+            ..config()
+        };
+        let (output, _) = run_script("go depth 5\nquit\n", config, std::future::pending()).await;
+
+        assert_eq!(
+            output,
+            "\r\x1b[2Kdepth 1 score cp 20 nodes 20 pv e2e4\
+             \r\x1b[2Kdepth 2 score cp 15 nodes 80 pv e2e4 e7e5\n\
+             bestmove e2e4 ponder e7e5\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repl_interrupt() {
+        let (mut connection, engine) = fake_engine().connect();
+
+        // The input stays open, as a terminal would
+        let (input, _keep_open) = tokio::io::duplex(64);
+        let (sender, interrupted) = tokio::sync::oneshot::channel::<()>();
+        let mut output = Vec::new();
+        let repl = run(
+            &mut connection,
+            BufReader::new(input),
+            &mut output,
+            config(),
+            async {
+                interrupted.await.unwrap();
+            },
+        );
+        let search = async {
+            tokio::time::sleep(QUIET).await;
+            sender.send(()).unwrap();
+        };
+        let (result, ()) = tokio::join!(repl, search);
+        result.unwrap();
+
+        assert_eq!(engine.await.unwrap(), ["stop", "quit"]);
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output,
+            "Interrupted, sending `stop` and `quit`\nbestmove e2e4\n"
+        );
+    }
+}
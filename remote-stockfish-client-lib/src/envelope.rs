@@ -0,0 +1,283 @@
+//! The envelope mode of the remote protocol, see [`ENVELOPE_SUBPROTOCOL`].
+
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::HeaderValue;
+use uci_beyond::transcript::Direction;
+
+/// The WebSocket subprotocol of the envelope mode, where each UCI line travels in a binary [`Envelope`]
+/// that carries its direction, channel and sequence number.
+///
+/// The mode is negotiated during the WebSocket handshake: the client asks for the subprotocol
+/// in `Sec-WebSocket-Protocol` and uses the envelopes only if the server accepts it, e.g. with
+/// [`accept_envelope_subprotocol`]. Otherwise, the lines travel as plain text messages.
+pub const ENVELOPE_SUBPROTOCOL: &str = "uci-envelope.v1";
+
+/// The size of the fields before the line.
+const HEADER_LEN: usize = 1 + 2 + 8 + 4;
+
+/// A single UCI line of the envelope mode.
+///
+/// The frame of an envelope is the concatenation of
+///
+/// | Field     | Size     | Value                                                |
+/// |-----------|----------|------------------------------------------------------|
+/// | direction | 1 byte   | `0` to the engine, `1` from the engine               |
+/// | channel   | 2 bytes  | big-endian, multiplexes several engines on a socket  |
+/// | sequence  | 8 bytes  | big-endian, per channel and direction, from `0`      |
+/// | length    | 4 bytes  | big-endian length of the line in bytes               |
+/// | line      | `length` | UTF-8, without the line terminator                   |
+///
+/// A binary message holds one or more frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub direction: Direction,
+    pub channel: u16,
+    /// The number of the lines sent before this one in the same channel and direction.
+    pub sequence: u64,
+    pub line: String,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    #[error("The envelope is truncated: {needed} bytes expected, {available} available.")]
+    Truncated { needed: usize, available: usize },
+    #[error("Invalid direction byte: {0}.")]
+    InvalidDirection(u8),
+    #[error("The line of the envelope isn't valid UTF-8: {0}")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+    /// An envelope of the channel was lost, duplicated or reordered.
+    #[error("Envelope {found} of channel {channel} received while {expected} was expected.")]
+    OutOfSequence {
+        channel: u16,
+        expected: u64,
+        found: u64,
+    },
+    #[error("The envelope goes in the wrong direction: {0:?}.")]
+    UnexpectedDirection(Direction),
+    /// A text message in the envelope mode, where every line travels in an envelope of a binary message.
+    #[error("A text message was received in the envelope mode.")]
+    TextMessage,
+}
+
+impl Envelope {
+    /// Appends the frame of the envelope to the buffer.
+    pub fn encode_into(&self, buf: &mut Vec<u8>) {
+        let direction = match self.direction {
+            Direction::ToEngine => 0,
+            Direction::FromEngine => 1,
+        };
+        buf.reserve(HEADER_LEN + self.line.len());
+        buf.push(direction);
+        buf.extend_from_slice(&self.channel.to_be_bytes());
+        buf.extend_from_slice(&self.sequence.to_be_bytes());
+        let len = u32::try_from(self.line.len()).expect("A UCI line is shorter than 4 GiB");
+        buf.extend_from_slice(&len.to_be_bytes());
+        buf.extend_from_slice(self.line.as_bytes());
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.encode_into(&mut buf);
+        buf
+    }
+
+    /// Decodes all the frames of a binary message.
+    pub fn decode_all(mut bytes: &[u8]) -> Result<Vec<Envelope>, EnvelopeError> {
+        let mut envelopes = Vec::new();
+        while !bytes.is_empty() {
+            let (envelope, rest) = Self::decode(bytes)?;
+            envelopes.push(envelope);
+            bytes = rest;
+        }
+        Ok(envelopes)
+    }
+
+    /// Decodes the first frame, returning the bytes after it.
+    pub fn decode(bytes: &[u8]) -> Result<(Envelope, &[u8]), EnvelopeError> {
+        let truncated = |needed| EnvelopeError::Truncated {
+            needed,
+            available: bytes.len(),
+        };
+        if bytes.len() < HEADER_LEN {
+            return Err(truncated(HEADER_LEN));
+        }
+        let direction = match bytes[0] {
+            0 => Direction::ToEngine,
+            1 => Direction::FromEngine,
+            other => return Err(EnvelopeError::InvalidDirection(other)),
+        };
+        let channel = u16::from_be_bytes([bytes[1], bytes[2]]);
+        let sequence = u64::from_be_bytes(bytes[3..11].try_into().unwrap());
+        let len = u32::from_be_bytes(bytes[11..15].try_into().unwrap()) as usize;
+        let end = HEADER_LEN + len;
+        if bytes.len() < end {
+            return Err(truncated(end));
+        }
+        let line = std::str::from_utf8(&bytes[HEADER_LEN..end])?.to_string();
+        let envelope = Envelope {
+            direction,
+            channel,
+            sequence,
+            line,
+        };
+        Ok((envelope, &bytes[end..]))
+    }
+}
+
+/// The numbering of the envelopes of one channel in one direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EnvelopeSequence {
+    direction: Direction,
+    channel: u16,
+    next: u64,
+}
+
+impl EnvelopeSequence {
+    pub(crate) fn new(direction: Direction, channel: u16) -> Self {
+        Self {
+            direction,
+            channel,
+            next: 0,
+        }
+    }
+
+    /// Wraps the line into the next envelope.
+    pub(crate) fn wrap(&mut self, line: &str) -> Envelope {
+        let envelope = Envelope {
+            direction: self.direction,
+            channel: self.channel,
+            sequence: self.next,
+            line: line.to_string(),
+        };
+        self.next += 1;
+        envelope
+    }
+
+    /// Checks that the envelope is the next one, returning its line,
+    /// or `None` if the envelope belongs to another channel.
+    pub(crate) fn unwrap(&mut self, envelope: Envelope) -> Result<Option<String>, EnvelopeError> {
+        if envelope.channel != self.channel {
+            return Ok(None);
+        }
+        if envelope.direction != self.direction {
+            return Err(EnvelopeError::UnexpectedDirection(envelope.direction));
+        }
+        if envelope.sequence != self.next {
+            return Err(EnvelopeError::OutOfSequence {
+                channel: self.channel,
+                expected: self.next,
+                found: envelope.sequence,
+            });
+        }
+        self.next += 1;
+        Ok(Some(envelope.line))
+    }
+}
+
+/// Whether the `Sec-WebSocket-Protocol` header lists the [`ENVELOPE_SUBPROTOCOL`].
+fn lists_envelope_subprotocol(header: Option<&HeaderValue>) -> bool {
+    header
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|protocols| {
+            protocols
+                .split(',')
+                .any(|protocol| protocol.trim() == ENVELOPE_SUBPROTOCOL)
+        })
+}
+
+/// The handshake callback of a server, e.g. for [`tokio_tungstenite::accept_hdr_async`],
+/// that accepts the envelope mode whenever the client asks for it.
+///
+/// The server is expected to use the envelope mode if the response has the
+/// [`ENVELOPE_SUBPROTOCOL`] in `Sec-WebSocket-Protocol`, see [`is_envelope_response`].
+pub fn accept_envelope_subprotocol(
+    request: &Request,
+    mut response: Response,
+) -> Result<Response, ErrorResponse> {
+    if lists_envelope_subprotocol(request.headers().get("Sec-WebSocket-Protocol")) {
+        response.headers_mut().insert(
+            "Sec-WebSocket-Protocol",
+            HeaderValue::from_static(ENVELOPE_SUBPROTOCOL),
+        );
+    }
+    Ok(response)
+}
+
+/// Whether the handshake response accepted the envelope mode.
+pub fn is_envelope_response<B>(response: &tungstenite::http::Response<B>) -> bool {
+    lists_envelope_subprotocol(response.headers().get("Sec-WebSocket-Protocol"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(sequence: u64, line: &str) -> Envelope {
+        Envelope {
+            direction: Direction::FromEngine,
+            channel: 7,
+            sequence,
+            line: line.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut bytes = envelope(0, "id name Fake").encode();
+        envelope(1, "").encode_into(&mut bytes);
+        envelope(2, "uciok").encode_into(&mut bytes);
+        assert_eq!(
+            bytes.len(),
+            3 * HEADER_LEN + "id name Fake".len() + "uciok".len()
+        );
+        assert_eq!(
+            Envelope::decode_all(&bytes).unwrap(),
+            [
+                envelope(0, "id name Fake"),
+                envelope(1, ""),
+                envelope(2, "uciok")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let bytes = envelope(0, "readyok").encode();
+        assert_eq!(
+            Envelope::decode_all(&bytes[..bytes.len() - 1]),
+            Err(EnvelopeError::Truncated {
+                needed: bytes.len(),
+                available: bytes.len() - 1,
+            })
+        );
+        let mut invalid = bytes.clone();
+        invalid[0] = 2;
+        assert_eq!(
+            Envelope::decode_all(&invalid),
+            Err(EnvelopeError::InvalidDirection(2))
+        );
+    }
+
+    #[test]
+    fn test_sequence_detects_loss() {
+        let mut sequence = EnvelopeSequence::new(Direction::FromEngine, 7);
+        assert_eq!(
+            sequence.unwrap(envelope(0, "readyok")),
+            Ok(Some("readyok".to_string()))
+        );
+        // Another channel is skipped without affecting the sequence
+        let other = Envelope {
+            channel: 8,
+            ..envelope(5, "bestmove e2e4")
+        };
+        assert_eq!(sequence.unwrap(other), Ok(None));
+        assert_eq!(
+            sequence.unwrap(envelope(2, "bestmove e2e4")),
+            Err(EnvelopeError::OutOfSequence {
+                channel: 7,
+                expected: 1,
+                found: 2,
+            })
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+/// The error of a [`RemoteChessEngineConnection`](crate::RemoteChessEngineConnection)
+/// or a [`TcpChessEngineConnection`](crate::TcpChessEngineConnection).
+///
+/// It converts into [`anyhow::Error`] with `?`, and can be recovered from it with
+/// [`anyhow::Error::downcast_ref`].
+#[derive(thiserror::Error, Debug)]
+pub enum RemoteEngineError {
+    /// The connection to the engine couldn't be established, e.g. the TCP connection was refused.
+    #[error("Failed to connect to the engine: {0}")]
+    Connect(#[source] tungstenite::Error),
+    /// The established connection failed, e.g. it was reset.
+    ///
+    /// The errors of a raw TCP or TLS connection are [`tungstenite::Error::Io`].
+    #[error("Transport error: {0}")]
+    Transport(#[from] tungstenite::Error),
+    /// The engine answered the command with output that can't be parsed.
+    #[error("Unexpected response to `{command}`: {message}")]
+    Protocol { command: String, message: String },
+    /// The engine closed the connection before the response was complete.
+    #[error("The engine closed the connection")]
+    Closed,
+    /// The connection wasn't established within the deadline set by the caller, e.g. with
+    /// [`RemoteChessEngine::with_connect_timeout`](crate::RemoteChessEngine::with_connect_timeout).
+    #[error("The engine didn't respond within {0:?}")]
+    Timeout(Duration),
+    /// The engine sent a binary message that isn't valid UTF-8.
+    #[error("The engine sent a message that isn't valid UTF-8: {0}")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+    /// The engine sent an invalid envelope in the envelope mode, or an envelope was lost.
+    #[error("Envelope error: {0}")]
+    Envelope(#[from] crate::EnvelopeError),
+}
+
+/// Whether the WebSocket error is transient, i.e. a transient I/O error (see
+/// [`is_transient_io_error`](uci_beyond::util::is_transient_io_error)) or a full write buffer.
+pub fn is_transient_ws_error(error: &tungstenite::Error) -> bool {
+    match error {
+        tungstenite::Error::Io(e) => uci_beyond::util::is_transient_io_error(e),
+        tungstenite::Error::WriteBufferFull(_) => true,
+        _ => false,
+    }
+}
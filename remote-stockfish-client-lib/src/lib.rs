@@ -1,33 +1,66 @@
-use anyhow::Context;
+use std::collections::VecDeque;
+use std::fmt::{Display, Write as _};
+use std::sync::Arc;
+use std::time::Duration;
+
 use async_trait::async_trait;
 use futures_util::stream::StreamExt as _;
 use futures_util::stream::{SplitSink, SplitStream};
+use tokio::sync::Mutex;
 use tokio_tungstenite::connect_async;
 use tungstenite::Utf8Bytes;
 use tungstenite::protocol::Message;
+use uci_beyond::gui_command_responses::GoEvent;
 use uci_beyond::gui_commands::UciCommandTrait;
 use uci_beyond::model::MoveString;
-use uci_beyond::util::{AsyncReadable, StringStreamReader};
+use uci_beyond::transcript::Direction;
+use uci_beyond::util::{
+    AsyncReadable, Clock as _, ConnectionDiagnostics, LineCountingReader, ResponseHandle,
+    RetryPolicy, StringStreamReader, SystemClock, TranscriptLog,
+};
+
+use crate::envelope::EnvelopeSequence;
+
+mod envelope;
+mod error;
+mod message_writer;
+mod pool;
+mod tcp;
+
+pub use envelope::{
+    ENVELOPE_SUBPROTOCOL, Envelope, EnvelopeError, accept_envelope_subprotocol,
+    is_envelope_response,
+};
+pub use error::{RemoteEngineError, is_transient_ws_error};
+pub use message_writer::MessageWriter;
+pub use pool::{
+    DEFAULT_REINITIALIZE_BACKOFF_INITIAL, DEFAULT_REINITIALIZE_BACKOFF_MAX, EnginePool,
+    PooledConnection, PooledEngine,
+};
+pub use tcp::{TcpChessEngine, TcpChessEngineConnection};
 
 pub struct RemoteChessEngine<R>
 where
     R: tungstenite::client::IntoClientRequest + Unpin,
 {
     request: R,
+    envelope_channel: Option<u16>,
+    connect_timeout: Option<Duration>,
 }
 
+type WebSocketStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+type WebSocketRead = SplitStream<WebSocketStream>;
+
 pub struct RemoteChessEngineConnection {
-    write: SplitSink<
-        tokio_tungstenite::WebSocketStream<
-            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-        >,
-        tokio_tungstenite::tungstenite::protocol::Message,
-    >,
-    read: SplitStream<
-        tokio_tungstenite::WebSocketStream<
-            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-        >,
-    >,
+    write: MessageWriter<SplitSink<WebSocketStream, Message>>,
+    /// Shared with the [`ResponseHandle`]s of the responses expected later.
+    incoming: Arc<Mutex<Incoming>>,
+    /// Shared with the read half, which records the received lines.
+    diagnostics: Arc<std::sync::Mutex<ConnectionDiagnostics>>,
+    clock: SystemClock,
+    /// The channel of the envelope mode, if the server accepted it.
+    envelope_channel: Option<u16>,
 }
 
 pub enum PositionEvaluation {
@@ -57,7 +90,7 @@ pub enum MoveEvaluation {
 
 #[async_trait(?Send)]
 impl uci_beyond::util::Connection for RemoteChessEngineConnection {
-    type Err = anyhow::Error;
+    type Err = RemoteEngineError;
 
     async fn send<C>(
         &mut self,
@@ -67,91 +100,245 @@ impl uci_beyond::util::Connection for RemoteChessEngineConnection {
         C: UciCommandTrait,
         C::Response: AsyncReadable,
     {
-        use futures_util::SinkExt as _;
+        self.write_line(cmd.to_string()).await?;
 
-        let cmd: String = cmd.to_string();
-        let cmd: Utf8Bytes = Utf8Bytes::try_from(cmd)?;
-        self.write.send(Message::Text(cmd)).await?;
+        // The failure is left to the caller, with the lines read so far in `last_response_lines`
+        read_response::<C::Response>(&self.incoming).await
+    }
 
-        let Self { read, write: _ } = self;
+    async fn send_expecting_later_response<C, T>(
+        &mut self,
+        cmd: C,
+    ) -> Result<ResponseHandle<T, Self::Err>, Self::Err>
+    where
+        C: UciCommandTrait,
+        T: AsyncReadable + 'static,
+    {
+        self.write_line(cmd.to_string()).await?;
 
-        let read = read.flat_map(|msg| {
-            match msg {
-                Ok(tungstenite::Message::Text(text)) => {
-                    // eprintln!("=== WebSocket Text Message Received ===");
-                    // eprintln!("Length: {} bytes", text.len());
-                    // eprintln!("Content: {:?}", text);
-                    // eprintln!("======================================");
+        let incoming = Arc::clone(&self.incoming);
+        Ok(ResponseHandle::new(async move {
+            read_response::<T>(&incoming).await
+        }))
+    }
 
-                    // Split text by newlines to handle multiple UCI lines in one WebSocket message
-                    // This handles both \n (Linux) and \r\n (Windows) line endings
-                    // Also trim trailing whitespace to handle trailing spaces in UCI option lines
-                    let mut lines: Vec<String> = text
-                        .split('\n')
-                        .map(|line| line.trim_end().to_string())
-                        .collect();
-
-                    // Remove the last empty element if text ends with \n
-                    // (split always creates a trailing empty string after a final separator)
-                    if text.ends_with('\n') && lines.last().map_or(false, |s| s.is_empty()) {
-                        lines.pop();
-                    }
+    async fn send_raw_line(&mut self, line: &str) -> Result<(), Self::Err> {
+        self.write_line(line.to_string()).await
+    }
 
-                    // Filter out command echoes from pseudo-TTY (script command echoes input)
-                    // UCI commands we send: uci, isready, position, go, stop, quit, setoption, ucinewgame
-                    lines.retain(|line| {
-                        let trimmed = line.trim();
-                        // eprintln!("DEBUG: Checking line: {:?} (trimmed: {:?})", line, trimmed);
-                        // Skip empty lines that would be command echoes or actual UCI commands
-                        // Valid UCI responses start with: id, option, uciok, readyok, bestmove, info
-                        if trimmed.is_empty() {
-                            // eprintln!("DEBUG: Keeping empty line");
-                            true // Keep empty lines (they're structural in UCI protocol)
-                        } else if trimmed == "uci"
-                            || trimmed == "isready"
-                            || trimmed == "quit"
-                            || trimmed == "stop"
-                            || trimmed == "ucinewgame"
-                            || trimmed.starts_with("position ")
-                            || trimmed.starts_with("go ")
-                            || trimmed.starts_with("setoption ")
-                        {
-                            // eprintln!("Filtering out command echo: {:?}", trimmed);
-                            false // Filter out command echoes
-                        } else {
-                            // eprintln!("DEBUG: Keeping response line");
-                            true // Keep actual UCI responses
-                        }
-                    });
+    async fn read_raw_line(&mut self) -> Result<Option<String>, Self::Err> {
+        Ok(self.incoming.lock().await.next_line().await?)
+    }
+
+    fn last_response_lines(&self) -> Option<usize> {
+        self.diagnostics.lock().unwrap().last_response_lines()
+    }
+}
 
-                    eprintln!("Split into {} lines: {:?}", lines.len(), lines);
+/// Copies the handshake request, which isn't [`Clone`].
+fn clone_request(
+    request: &tungstenite::handshake::client::Request,
+) -> tungstenite::handshake::client::Request {
+    let mut copy = tungstenite::handshake::client::Request::new(());
+    *copy.method_mut() = request.method().clone();
+    *copy.uri_mut() = request.uri().clone();
+    *copy.version_mut() = request.version();
+    *copy.headers_mut() = request.headers().clone();
+    copy
+}
 
-                    let results: Vec<Result<String, tungstenite::Error>> =
-                        lines.into_iter().map(Ok).collect();
-                    futures::stream::iter(results)
+/// Reads the response from the shared read half, recording the number of lines consumed
+/// (including the line at which parsing failed, if it did) in the diagnostics.
+///
+/// The read half is locked one line at a time, so a response that never reads
+/// (e.g. `()` of `stop`) doesn't wait for a response being read elsewhere.
+async fn read_response<T>(
+    incoming: &Arc<Mutex<Incoming>>,
+) -> Result<Result<T, T::Err>, RemoteEngineError>
+where
+    T: AsyncReadable,
+{
+    let lines = futures::stream::unfold(Arc::clone(incoming), |incoming| async move {
+        let line = incoming.lock().await.next_line().await.transpose()?;
+        Some((line, incoming))
+    });
+
+    // eprintln!("=== Starting to parse response ===");
+    let mut reader = LineCountingReader::new(StringStreamReader::new(Box::pin(lines)));
+    let response = T::read_from(&mut reader).await;
+    let lines_consumed = reader.lines_consumed();
+    {
+        let mut incoming = incoming.lock().await;
+        incoming
+            .diagnostics
+            .lock()
+            .unwrap()
+            .record_response_lines(lines_consumed);
+        // The line the parser peeked at belongs to whatever is read next
+        if let Some(line) = reader.into_inner().into_peeked_line() {
+            incoming.pending.push_front(line);
+        }
+    }
+    // eprintln!("=== Finished parsing response ===");
+
+    match response {
+        Ok(Some(response)) => Ok(response),
+        Ok(None) => Err(RemoteEngineError::Closed),
+        Err(e) => Err(e),
+    }
+}
+
+/// The read half of the connection.
+struct Incoming {
+    read: WebSocketRead,
+    /// The lines of the received messages that haven't been read yet.
+    pending: VecDeque<String>,
+    diagnostics: Arc<std::sync::Mutex<ConnectionDiagnostics>>,
+    clock: SystemClock,
+    /// The numbering of the received envelopes in the envelope mode.
+    envelopes: Option<EnvelopeSequence>,
+}
+
+impl Incoming {
+    /// Reads the next line, starting with the lines left over from the previously received messages.
+    async fn next_line(&mut self) -> Result<Option<String>, RemoteEngineError> {
+        loop {
+            if let Some(line) = self.pending.pop_front() {
+                return Ok(Some(line));
+            }
+            match self.read.next().await {
+                Some(Ok(tungstenite::Message::Text(text))) if self.envelopes.is_none() => {
+                    // eprintln!("=== WebSocket Text Message Received ===");
+                    // eprintln!("Length: {} bytes", text.len());
+                    // eprintln!("Content: {:?}", text);
+                    // eprintln!("======================================");
+                    let lines = split_message(&text);
+                    self.record_received(lines.iter().map(String::as_str));
+                    self.pending.extend(lines);
+                }
+                Some(Ok(tungstenite::Message::Binary(bytes))) if self.envelopes.is_some() => {
+                    let lines = self.unwrap_envelopes(&bytes)?;
+                    self.record_received(lines.iter().map(String::as_str));
+                    self.pending.extend(lines);
                 }
-                Ok(other_msg) => {
+                Some(Ok(tungstenite::Message::Text(_))) => {
+                    let e = EnvelopeError::TextMessage;
+                    self.diagnostics
+                        .lock()
+                        .unwrap()
+                        .record_error(&e, self.clock.now());
+                    return Err(e.into());
+                }
+                Some(Ok(other_msg)) => {
                     eprintln!("=== WebSocket Non-Text Message: {:?} ===", other_msg);
-                    futures::stream::iter(vec![])
                 }
-                Err(e) => {
+                Some(Err(e)) => {
                     eprintln!("=== WebSocket Error: {:?} ===", e);
-                    futures::stream::iter(vec![Err(e)])
+                    self.diagnostics
+                        .lock()
+                        .unwrap()
+                        .record_error(&e, self.clock.now());
+                    return Err(e.into());
+                }
+                None => {
+                    self.diagnostics.lock().unwrap().record_closed();
+                    return Ok(None);
                 }
             }
+        }
+    }
+
+    /// Unwraps the lines of the channel from the envelopes of a binary message.
+    fn unwrap_envelopes(&mut self, bytes: &[u8]) -> Result<Vec<String>, RemoteEngineError> {
+        let sequence = self
+            .envelopes
+            .as_mut()
+            .expect("Only the envelope mode unwraps envelopes");
+        let res = Envelope::decode_all(bytes).and_then(|envelopes| {
+            envelopes
+                .into_iter()
+                .filter_map(|envelope| sequence.unwrap(envelope).transpose())
+                .collect::<Result<Vec<_>, _>>()
         });
+        res.map_err(|e| {
+            self.diagnostics
+                .lock()
+                .unwrap()
+                .record_error(&e, self.clock.now());
+            e.into()
+        })
+    }
+}
 
-        // eprintln!("=== Starting to parse response ===");
-        let mut reader = StringStreamReader::new(read);
-        let response = C::Response::read_from(&mut reader)
-            .await?
-            .context("A command expected")?;
-        // eprintln!("=== Finished parsing response ===");
+impl Incoming {
+    fn record_received<'a>(&self, lines: impl IntoIterator<Item = &'a str>) {
+        let mut diagnostics = self.diagnostics.lock().unwrap();
+        for line in lines {
+            diagnostics.record_received(line, self.clock.now());
+        }
+    }
+}
+
+/// Splits a WebSocket text message into UCI lines, dropping the echoes of the sent commands.
+fn split_message(text: &str) -> Vec<String> {
+    // Split text by newlines to handle multiple UCI lines in one WebSocket message
+    // This handles both \n (Linux) and \r\n (Windows) line endings
+    // Also trim trailing whitespace to handle trailing spaces in UCI option lines
+    let mut lines: Vec<String> = text
+        .split('\n')
+        .map(|line| line.trim_end().to_string())
+        .collect();
+
+    // Remove the last empty element if text ends with \n
+    // (split always creates a trailing empty string after a final separator)
+    if text.ends_with('\n') && lines.last().map_or(false, |s| s.is_empty()) {
+        lines.pop();
+    }
 
-        match response {
-            Ok(resp) => return Ok(Ok(resp)),
-            Err(e) => return Ok(Err(e)),
+    // Filter out command echoes from pseudo-TTY (script command echoes input)
+    // UCI commands we send: uci, isready, position, go, stop, quit, setoption, ucinewgame
+    lines.retain(|line| {
+        let trimmed = line.trim();
+        // eprintln!("DEBUG: Checking line: {:?} (trimmed: {:?})", line, trimmed);
+        // Skip empty lines that would be command echoes or actual UCI commands
+        // Valid UCI responses start with: id, option, uciok, readyok, bestmove, info
+        if trimmed.is_empty() {
+            // eprintln!("DEBUG: Keeping empty line");
+            true // Keep empty lines (they're structural in UCI protocol)
+        } else if trimmed == "uci"
+            || trimmed == "isready"
+            || trimmed == "quit"
+            || trimmed == "stop"
+            || trimmed == "ucinewgame"
+            || trimmed.starts_with("position ")
+            || trimmed.starts_with("go ")
+            || trimmed.starts_with("setoption ")
+        {
+            // eprintln!("Filtering out command echo: {:?}", trimmed);
+            false // Filter out command echoes
+        } else {
+            // eprintln!("DEBUG: Keeping response line");
+            true // Keep actual UCI responses
         }
+    });
+
+    eprintln!("Split into {} lines: {:?}", lines.len(), lines);
+    lines
+}
+
+#[async_trait(?Send)]
+impl PooledConnection for RemoteChessEngineConnection {
+    async fn read_search(&mut self) -> anyhow::Result<Vec<GoEvent>> {
+        pool::read_go_events(self).await
+    }
+
+    async fn quit(&mut self) -> anyhow::Result<()> {
+        use uci_beyond::gui_commands::QuitCommand;
+        use uci_beyond::util::Connection as _;
+
+        self.send(QuitCommand).await??;
+        self.close_gracefully().await?;
+        Ok(())
     }
 }
 
@@ -160,45 +347,256 @@ where
     R: tungstenite::client::IntoClientRequest + Unpin,
 {
     pub fn new(request: R) -> Self {
-        Self { request }
+        Self {
+            request,
+            envelope_channel: None,
+            connect_timeout: None,
+        }
+    }
+
+    /// Fails with [`RemoteEngineError::Timeout`] if the connection isn't established within the `timeout`,
+    /// incl. the WebSocket handshake, e.g. with a server that accepts the TCP connection and never answers.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Asks the server for the envelope mode (see [`ENVELOPE_SUBPROTOCOL`]), where each line
+    /// travels in a binary [`Envelope`] of the channel with a sequence number, so that a lost line is detected
+    /// as [`EnvelopeError::OutOfSequence`].
+    ///
+    /// If the server doesn't accept the mode during the handshake, the connection falls back to text messages,
+    /// see [`RemoteChessEngineConnection::envelope_channel`].
+    pub fn with_envelope_mode(mut self, channel: u16) -> Self {
+        self.envelope_channel = Some(channel);
+        self
     }
 
     /// Note: when connecting to stockfish, it greets you with
     /// `Stockfish 17.1 by the Stockfish developers (see AUTHORS file)\n`
-    pub async fn connect(self) -> anyhow::Result<RemoteChessEngineConnection> {
-        let (ws_stream, _) = connect_async(self.request).await?;
+    pub async fn connect(self) -> Result<RemoteChessEngineConnection, RemoteEngineError> {
+        let timeout = self.connect_timeout;
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.connect_without_timeout())
+                .await
+                .unwrap_or(Err(RemoteEngineError::Timeout(timeout))),
+            None => self.connect_without_timeout().await,
+        }
+    }
+
+    async fn connect_without_timeout(
+        self,
+    ) -> Result<RemoteChessEngineConnection, RemoteEngineError> {
+        let request = self
+            .request
+            .into_client_request()
+            .map_err(RemoteEngineError::Connect)?;
+        let (ws_stream, envelope_channel) = match self.envelope_channel {
+            None => {
+                let (ws_stream, _) = connect_async(request)
+                    .await
+                    .map_err(RemoteEngineError::Connect)?;
+                (ws_stream, None)
+            }
+            Some(channel) => {
+                let plain = clone_request(&request);
+                let mut request = request;
+                request.headers_mut().insert(
+                    "Sec-WebSocket-Protocol",
+                    tungstenite::http::HeaderValue::from_static(ENVELOPE_SUBPROTOCOL),
+                );
+                match connect_async(request).await {
+                    Ok((ws_stream, response)) => {
+                        let accepted = is_envelope_response(&response);
+                        (ws_stream, accepted.then_some(channel))
+                    }
+                    // tungstenite fails the handshake if the server ignored the subprotocol
+                    Err(tungstenite::Error::Protocol(
+                        tungstenite::error::ProtocolError::SecWebSocketSubProtocolError(
+                            tungstenite::error::SubProtocolError::NoSubProtocol,
+                        ),
+                    )) => {
+                        let (ws_stream, _) = connect_async(plain)
+                            .await
+                            .map_err(RemoteEngineError::Connect)?;
+                        (ws_stream, None)
+                    }
+                    Err(e) => return Err(RemoteEngineError::Connect(e)),
+                }
+            }
+        };
+
         let (write, read) = ws_stream.split();
-        Ok(RemoteChessEngineConnection { write, read })
+        let mut write = MessageWriter::new(write);
+        if let Some(channel) = envelope_channel {
+            write = write.with_envelope_channel(channel);
+        }
+        let diagnostics = Arc::new(std::sync::Mutex::new(ConnectionDiagnostics::default()));
+        let clock = SystemClock::default();
+        Ok(RemoteChessEngineConnection {
+            write,
+            incoming: Arc::new(Mutex::new(Incoming {
+                read,
+                pending: VecDeque::new(),
+                diagnostics: Arc::clone(&diagnostics),
+                clock,
+                envelopes: envelope_channel
+                    .map(|channel| EnvelopeSequence::new(Direction::FromEngine, channel)),
+            })),
+            diagnostics,
+            clock,
+            envelope_channel,
+        })
     }
 }
 
 impl RemoteChessEngineConnection {
-    pub async fn next_message(&mut self) -> anyhow::Result<String> {
-        let res = self.read.next().await;
-        if let Some(Ok(msg)) = res {
-            if let tungstenite::Message::Text(text) = msg {
-                return Ok(text.to_string());
+    /// The channel of the envelope mode, or `None` if the lines travel as text messages,
+    /// e.g. because the server didn't accept the mode (see [`RemoteChessEngine::with_envelope_mode`]).
+    pub fn envelope_channel(&self) -> Option<u16> {
+        self.envelope_channel
+    }
+
+    /// The snapshot of what the connection has been doing lately, e.g. the last lines and the last error.
+    pub fn diagnostics(&self) -> ConnectionDiagnostics {
+        self.diagnostics.lock().unwrap().clone()
+    }
+
+    /// Writes every line sent or received from now on to the log, incl. the raw lines
+    /// and the lines of [`send_batch`](Self::send_batch).
+    pub fn with_transcript(self, transcript: TranscriptLog) -> Self {
+        self.diagnostics.lock().unwrap().set_transcript(transcript);
+        self
+    }
+
+    /// Retries the failed writes according to the policy, e.g. [`ExponentialRetry`](uci_beyond::util::ExponentialRetry)
+    /// with [`is_transient_ws_error`]. The default is [`NoRetry`](uci_beyond::util::NoRetry).
+    ///
+    /// A message is never sent twice: once it was queued, only flushing it is retried.
+    pub fn with_retry_policy<P>(mut self, policy: P) -> Self
+    where
+        P: RetryPolicy<tungstenite::Error> + Send + Sync + 'static,
+    {
+        self.write = self.write.with_retry_policy(policy);
+        self
+    }
+
+    /// Sends the commands in a single WebSocket message, a line per command, e.g. the `setoption` commands
+    /// of a configuration, instead of a message per command.
+    ///
+    /// The commands are displayed straight into the buffer of the message. Nothing is read, so the responses,
+    /// if any, are left for [`read_raw_line`](uci_beyond::util::Connection::read_raw_line). No message is sent
+    /// for no commands.
+    pub async fn send_batch(&mut self, cmds: &[&dyn Display]) -> Result<(), RemoteEngineError> {
+        if cmds.is_empty() {
+            return Ok(());
+        }
+        let mut text = String::new();
+        for cmd in cmds {
+            write!(text, "{cmd}").expect("Writing to a String doesn't fail");
+            text.truncate(text.trim_end_matches(['\r', '\n']).len());
+            text.push('\n');
+        }
+        self.write_text(Utf8Bytes::from(text)).await
+    }
+
+    /// Sends the line as a WebSocket text message, without the trailing newline.
+    async fn write_line(&mut self, mut line: String) -> Result<(), RemoteEngineError> {
+        line.truncate(line.trim_end_matches(['\r', '\n']).len());
+        self.write_text(Utf8Bytes::from(line)).await
+    }
+
+    /// Sends the lines as a single message, recording them as sent.
+    async fn write_text(&mut self, text: Utf8Bytes) -> Result<(), RemoteEngineError> {
+        // The clone shares the bytes
+        let res = self.write.write_batch(text.clone()).await;
+        let mut diagnostics = self.diagnostics.lock().unwrap();
+        match res {
+            Ok(()) => {
+                let mut lines: Vec<&str> = text.lines().collect();
+                if lines.is_empty() {
+                    lines.push("");
+                }
+                for line in lines {
+                    diagnostics.record_sent(line, self.clock.now());
+                }
+                Ok(())
+            }
+            Err(e) => {
+                diagnostics.record_error(&e, self.clock.now());
+                Err(e.into())
             }
         }
-        Err(anyhow::anyhow!("No message received"))
     }
 
-    pub async fn skip_message(&mut self) -> anyhow::Result<()> {
-        if let Some(Ok(_msg)) = self.read.next().await {
+    /// Receives the next WebSocket text message as is.
+    ///
+    /// Note: the message bypasses the lines left over by the previous responses,
+    /// use [`read_raw_line`](uci_beyond::util::Connection::read_raw_line) to read them in order.
+    ///
+    /// A binary message is accepted if it is valid UTF-8. In the envelope mode, the lines of the envelopes
+    /// of a binary message are joined with `\n`.
+    pub async fn next_message(&mut self) -> Result<String, RemoteEngineError> {
+        let mut incoming = self.incoming.lock().await;
+        let res = loop {
+            match incoming.read.next().await {
+                Some(Ok(Message::Binary(bytes))) if incoming.envelopes.is_some() => {
+                    // The errors of the envelopes are recorded as they are unwrapped
+                    let lines = incoming.unwrap_envelopes(&bytes)?;
+                    if !lines.is_empty() {
+                        incoming.record_received(lines.iter().map(String::as_str));
+                        return Ok(lines.join("\n"));
+                    }
+                }
+                Some(Ok(Message::Text(_))) if incoming.envelopes.is_some() => {
+                    break Err(EnvelopeError::TextMessage.into());
+                }
+                Some(Ok(Message::Text(text))) => break Ok(text.to_string()),
+                Some(Ok(Message::Binary(bytes))) => {
+                    break std::str::from_utf8(&bytes)
+                        .map(str::to_string)
+                        .map_err(RemoteEngineError::from);
+                }
+                Some(Ok(Message::Close(_))) | None => break Err(RemoteEngineError::Closed),
+                // Pings and pongs are answered by tungstenite
+                Some(Ok(_)) => (),
+                Some(Err(e)) => break Err(e.into()),
+            }
+        };
+        match &res {
+            Ok(msg) => incoming.record_received(msg.lines()),
+            Err(RemoteEngineError::Closed) => incoming.diagnostics.lock().unwrap().record_closed(),
+            Err(e) => incoming
+                .diagnostics
+                .lock()
+                .unwrap()
+                .record_error(e, self.clock.now()),
+        }
+        res
+    }
+
+    /// Skips the next message, e.g. the engine's greeting.
+    ///
+    /// Skipping the greeting before sending `uci` is optional: the default
+    /// [`GreetingPolicy`](uci_beyond::gui_command_responses::GreetingPolicy) keeps it as
+    /// [`UciResponse::greeting`](uci_beyond::gui_command_responses::UciResponse::greeting).
+    pub async fn skip_message(&mut self) -> Result<(), RemoteEngineError> {
+        if let Some(Ok(_msg)) = self.incoming.lock().await.read.next().await {
             // println!("Skipped message: {:?}", msg);
         }
         Ok(())
     }
 
     // Ideally, this should be an async drop but Rust does not support that yet.
-    pub async fn close_gracefully(&mut self) -> anyhow::Result<()> {
+    pub async fn close_gracefully(&mut self) -> Result<(), RemoteEngineError> {
         use futures_util::SinkExt as _;
         use tungstenite::protocol::CloseFrame;
         use tungstenite::protocol::frame::coding::CloseCode;
 
-        let reason = Utf8Bytes::try_from("Normal closure")?;
+        let reason = Utf8Bytes::from_static("Normal closure");
 
         self.write
+            .get_mut()
             .send(Message::Close(Some(CloseFrame {
                 code: CloseCode::Normal,
                 reason,
@@ -221,7 +619,7 @@ impl RemoteChessEngineConnection {
 
         // Set position
         let mut position_cmd = PositionCommand::from_fen(fen);
-        position_cmd.moves = moves;
+        position_cmd.moves = moves.into();
         let _ = self.send(position_cmd).await??;
 
         // Start search with depth 20
@@ -248,7 +646,7 @@ impl RemoteChessEngineConnection {
                                     let principal_variation: Vec<MoveString> = parts
                                         [pv_index + 1..]
                                         .iter()
-                                        .map(|&s| MoveString(s.to_string()))
+                                        .map(|&s| MoveString::from(s))
                                         .collect();
                                     last_evaluation = Some(PositionEvaluation::Undecided {
                                         principal_variation,
@@ -262,7 +660,7 @@ impl RemoteChessEngineConnection {
                                     let principal_variation: Vec<MoveString> = parts
                                         [pv_index + 1..]
                                         .iter()
-                                        .map(|&s| MoveString(s.to_string()))
+                                        .map(|&s| MoveString::from(s))
                                         .collect();
                                     last_evaluation = Some(PositionEvaluation::Mate {
                                         principal_variation,
@@ -347,6 +745,7 @@ mod tests {
     use tokio::io::AsyncBufReadExt as _;
 
     use tokio::io::BufReader;
+    use tokio::sync::Mutex;
     use tokio_tungstenite::connect_async;
     use tungstenite::Message;
 
@@ -386,6 +785,491 @@ mod tests {
         Ok(())
     }
 
+    /// Serves a single WebSocket connection on a free local port. For each expected line,
+    /// the server answers with the scripted message, which may hold several lines.
+    /// The server closes the connection at the end of the script.
+    async fn serve_script(
+        script: Vec<(&'static str, &'static str)>,
+    ) -> anyhow::Result<(String, tokio::task::JoinHandle<()>)> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            for (expected, reply) in script {
+                let msg = ws.next().await.unwrap().unwrap();
+                assert_eq!(msg.to_text().unwrap(), expected);
+                if !reply.is_empty() {
+                    ws.send(Message::Text(reply.to_string().into()))
+                        .await
+                        .unwrap();
+                }
+            }
+            // The client may have closed the connection already
+            let _ = ws.close(None).await;
+        });
+        Ok((url, server))
+    }
+
+    #[tokio::test]
+    async fn test_raw_lines_interleave_with_typed_commands() -> anyhow::Result<()> {
+        use uci_beyond::gui_commands::{IsReadyCommand, UciCommand};
+        use uci_beyond::util::Connection as _;
+
+        let (url, server) = serve_script(vec![
+            (
+                "uci",
+                "id name Fake\nid author Nobody\n\noption name Hash type spin default 16 min 1 max 1024\nuciok\ninfo string after uciok\n",
+            ),
+            ("vendorcmd 1", "vendor reply 1\nvendor reply 2\n"),
+            ("isready", "readyok\n"),
+        ])
+        .await?;
+        let log = SharedLog::default();
+        let mut connection = RemoteChessEngine::new(url)
+            .connect()
+            .await?
+            .with_transcript(TranscriptLog::new(log.clone()));
+
+        let res = connection.send(UciCommand).await??;
+        assert_eq!(res.id_block.name, "Fake");
+        // The rest of the message that held `uciok` is not lost
+        assert_eq!(
+            connection.read_raw_line().await?.as_deref(),
+            Some("info string after uciok")
+        );
+
+        // The trailing newline is handled by the writer
+        connection.send_raw_line("vendorcmd 1\n").await?;
+        assert_eq!(
+            connection.read_raw_line().await?.as_deref(),
+            Some("vendor reply 1")
+        );
+
+        let Ok(()) = connection.send(IsReadyCommand).await?;
+        assert_eq!(
+            connection.read_raw_line().await?.as_deref(),
+            Some("vendor reply 2")
+        );
+        assert_eq!(
+            connection.read_raw_line().await?.as_deref(),
+            Some("readyok")
+        );
+
+        // The transcript has the raw lines in order with the typed ones
+        let log = String::from_utf8(log.0.lock().unwrap().clone())?;
+        let lines = uci_beyond::transcript::parse_transcript(&log)?
+            .into_iter()
+            .map(|entry| (entry.direction, entry.line))
+            .collect::<Vec<_>>();
+        let expected = [
+            (Direction::ToEngine, "uci"),
+            (Direction::FromEngine, "id name Fake"),
+            (Direction::FromEngine, "id author Nobody"),
+            (Direction::FromEngine, ""),
+            (
+                Direction::FromEngine,
+                "option name Hash type spin default 16 min 1 max 1024",
+            ),
+            (Direction::FromEngine, "uciok"),
+            (Direction::FromEngine, "info string after uciok"),
+            (Direction::ToEngine, "vendorcmd 1"),
+            (Direction::FromEngine, "vendor reply 1"),
+            (Direction::FromEngine, "vendor reply 2"),
+            (Direction::ToEngine, "isready"),
+            (Direction::FromEngine, "readyok"),
+        ]
+        .map(|(direction, line)| (direction, line.to_string()));
+        assert_eq!(lines, expected);
+
+        server.await?;
+        Ok(())
+    }
+
+    /// A [`TranscriptLog`] writer whose bytes the test can look at.
+    #[derive(Clone, Default)]
+    struct SharedLog(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_response_lines_sharing_a_message() -> anyhow::Result<()> {
+        use uci_beyond::engine_commands::ReadyOkCommand;
+        use uci_beyond::gui_commands::{IsReadyCommand, UciCommand};
+        use uci_beyond::util::Connection as _;
+
+        // The greeting, the response to `uci` and the first line of the next response arrive in one message
+        let (url, server) = serve_script(vec![
+            (
+                "uci",
+                "Fake 1.0 by Nobody\nid name Fake\nid author Nobody\n\nuciok\nreadyok\n",
+            ),
+            ("isready", ""),
+        ])
+        .await?;
+        let mut connection = RemoteChessEngine::new(url).connect().await?;
+
+        let res = connection.send(UciCommand).await??;
+        assert_eq!(res.greeting, ["Fake 1.0 by Nobody"]);
+        assert_eq!(res.id_block.name, "Fake");
+
+        let readyok = connection
+            .send_expecting_later_response::<_, ReadyOkCommand>(IsReadyCommand)
+            .await?;
+        readyok.await??;
+
+        server.await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_in_one_frame() -> anyhow::Result<()> {
+        use uci_beyond::gui_commands::{IsReadyCommand, SetOptionCommand};
+        use uci_beyond::util::Connection as _;
+
+        // Receives the frames until `isready` and answers it
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut frames = Vec::new();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let done = text.as_str() == "isready";
+                frames.push(text.to_string());
+                if done {
+                    ws.send(Message::Text("readyok".into())).await.unwrap();
+                    break;
+                }
+            }
+            let _ = ws.close(None).await;
+            frames
+        });
+        let mut connection = RemoteChessEngine::new(url).connect().await?;
+
+        let configuration: Vec<_> = (1..=20)
+            .map(|i| SetOptionCommand::Hash { value: 16 * i })
+            .collect();
+        let cmds: Vec<&dyn std::fmt::Display> = configuration
+            .iter()
+            .map(|cmd| cmd as &dyn std::fmt::Display)
+            .collect();
+        connection.send_batch(&cmds).await?;
+        let Ok(()) = connection.send(IsReadyCommand).await?;
+        assert_eq!(
+            connection.read_raw_line().await?.as_deref(),
+            Some("readyok")
+        );
+
+        let frames = server.await?;
+        assert_eq!(frames.len(), 2);
+        let lines: Vec<_> = frames[0].lines().collect();
+        assert_eq!(lines.len(), 20);
+        assert_eq!(lines[0], "setoption name Hash value 16");
+        assert_eq!(lines[19], "setoption name Hash value 320");
+        // Every command is recorded as sent
+        assert_eq!(connection.diagnostics().commands_sent("setoption"), 20);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_closed_connection_error() -> anyhow::Result<()> {
+        use uci_beyond::gui_commands::UciCommand;
+        use uci_beyond::util::Connection as _;
+
+        let (url, server) = serve_script(vec![("uci", "")]).await?;
+        let mut connection = RemoteChessEngine::new(url).connect().await?;
+
+        let res = connection.send(UciCommand).await;
+        assert!(matches!(res, Err(RemoteEngineError::Closed)), "{res:?}");
+        assert!(matches!(
+            connection.next_message().await,
+            Err(RemoteEngineError::Closed)
+        ));
+
+        server.await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_failure_error() -> anyhow::Result<()> {
+        use uci_beyond::gui_commands::GoCommand;
+        use uci_beyond::util::Connection as _;
+
+        let (url, server) =
+            serve_script(vec![("go depth 1", "info depth one\nbestmove e2e4\n")]).await?;
+        let mut connection = RemoteChessEngine::new(url).connect().await?;
+
+        let Ok(()) = connection.send(GoCommand::for_depth(1)).await?;
+        let err = connection.read_search().await.unwrap_err();
+        // The error is still typed behind `anyhow::Error`
+        match err.downcast_ref::<RemoteEngineError>() {
+            Some(RemoteEngineError::Protocol { command, .. }) => assert_eq!(command, "go"),
+            _ => panic!("Unexpected error: {err:?}"),
+        }
+
+        server.await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics() -> anyhow::Result<()> {
+        use uci_beyond::gui_commands::{IsReadyCommand, UciCommand};
+        use uci_beyond::transcript::Direction;
+        use uci_beyond::util::{Connection as _, ConnectionState};
+
+        let (url, server) = serve_script(vec![
+            ("uci", "id name Fake\nid author Nobody\n\nuciok\n"),
+            ("isready", "readyok\n"),
+            ("isready", ""),
+        ])
+        .await?;
+        let mut connection = RemoteChessEngine::new(url).connect().await?;
+
+        connection.send(UciCommand).await??;
+        let Ok(()) = connection.send(IsReadyCommand).await?;
+        assert_eq!(
+            connection.read_raw_line().await?.as_deref(),
+            Some("readyok")
+        );
+
+        let diagnostics = connection.diagnostics();
+        assert_eq!(diagnostics.state(), ConnectionState::Open);
+        assert_eq!(diagnostics.commands_sent("uci"), 1);
+        assert_eq!(diagnostics.commands_sent("isready"), 1);
+        assert_eq!(diagnostics.lines_received(), 5);
+        assert_eq!(diagnostics.last_sent().unwrap().line, "isready");
+        assert_eq!(diagnostics.last_received().unwrap().line, "readyok");
+        let directions = diagnostics
+            .recent_lines()
+            .map(|line| line.direction)
+            .collect::<Vec<_>>();
+        assert_eq!(directions.first(), Some(&Direction::ToEngine));
+        assert_eq!(directions.len(), 7);
+
+        // The server closes the connection instead of answering
+        let Ok(()) = connection.send(IsReadyCommand).await?;
+        assert_eq!(connection.read_raw_line().await?, None);
+        let diagnostics = connection.diagnostics();
+        assert_eq!(diagnostics.commands_sent("isready"), 2);
+        assert_eq!(diagnostics.state(), ConnectionState::Closed);
+
+        server.await?;
+        Ok(())
+    }
+
+    const UCI_REPLY: [&str; 5] = [
+        "id name Fake",
+        "id author Nobody",
+        "",
+        "option name Hash type spin default 16 min 1 max 1024",
+        "uciok",
+    ];
+
+    /// Answers `uci` like a minimal engine, accepting the envelope mode if `envelopes`, and returns
+    /// whether the envelope mode was used.
+    async fn serve_uci(envelopes: bool) -> anyhow::Result<(String, tokio::task::JoinHandle<bool>)> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut negotiated = false;
+            let callback = |request: &Request, response: Response| -> Result<_, ErrorResponse> {
+                if !envelopes {
+                    return Ok(response);
+                }
+                let response = accept_envelope_subprotocol(request, response)?;
+                negotiated = is_envelope_response(&response);
+                Ok(response)
+            };
+            let mut ws = tokio_tungstenite::accept_hdr_async(stream, callback)
+                .await
+                .unwrap();
+
+            let msg = ws.next().await.unwrap().unwrap();
+            if negotiated {
+                let envelopes = Envelope::decode_all(&msg.into_data()).unwrap();
+                assert_eq!(envelopes.len(), 1);
+                assert_eq!(envelopes[0].line, "uci");
+                let mut sequence =
+                    EnvelopeSequence::new(Direction::FromEngine, envelopes[0].channel);
+                // Several envelopes in one message
+                let mut reply = Vec::new();
+                for line in UCI_REPLY {
+                    sequence.wrap(line).encode_into(&mut reply);
+                }
+                ws.send(Message::Binary(reply.into())).await.unwrap();
+            } else {
+                assert_eq!(msg.to_text().unwrap(), "uci");
+                let reply = UCI_REPLY.join("\n") + "\n";
+                ws.send(Message::Text(reply.into())).await.unwrap();
+            }
+            let _ = ws.close(None).await;
+            negotiated
+        });
+        Ok((url, server))
+    }
+
+    async fn uci_exchange(engine: RemoteChessEngine<String>) -> anyhow::Result<Option<u16>> {
+        use uci_beyond::gui_commands::UciCommand;
+        use uci_beyond::util::Connection as _;
+
+        let mut connection = engine.connect().await?;
+        let response = connection.send(UciCommand).await?.unwrap();
+        assert_eq!(response.id_block.name, "Fake");
+        assert!(response.option_block.hash.is_some());
+        Ok(connection.envelope_channel())
+    }
+
+    #[tokio::test]
+    async fn test_uci_exchange_in_plain_mode() -> anyhow::Result<()> {
+        let (url, server) = serve_uci(false).await?;
+        let channel = uci_exchange(RemoteChessEngine::new(url)).await?;
+        assert_eq!(channel, None);
+        assert!(!server.await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_uci_exchange_in_envelope_mode() -> anyhow::Result<()> {
+        let (url, server) = serve_uci(true).await?;
+        let channel = uci_exchange(RemoteChessEngine::new(url).with_envelope_mode(3)).await?;
+        assert_eq!(channel, Some(3));
+        assert!(server.await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_envelope_mode_falls_back_to_plain() -> anyhow::Result<()> {
+        // The server ignores the subprotocol, so the client connects again without it
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            // The failed handshake
+            let _ = tokio_tungstenite::accept_async(stream).await;
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let msg = ws.next().await.unwrap().unwrap();
+            assert_eq!(msg.to_text().unwrap(), "uci");
+            let reply = UCI_REPLY.join("\n") + "\n";
+            ws.send(Message::Text(reply.into())).await.unwrap();
+            let _ = ws.close(None).await;
+        });
+
+        let channel = uci_exchange(RemoteChessEngine::new(url).with_envelope_mode(3)).await?;
+        assert_eq!(channel, None);
+        server.await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_envelope_mode_does_not_fall_back_on_other_errors() -> anyhow::Result<()> {
+        use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+        // The server switches protocols without `Upgrade: websocket`, which isn't a rejection of the subprotocol
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                request.push(stream.read_u8().await.unwrap());
+            }
+            stream
+                .write_all(b"HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\n\r\n")
+                .await
+                .unwrap();
+            // No second attempt without the subprotocol
+            tokio::time::timeout(std::time::Duration::from_millis(50), listener.accept())
+                .await
+                .is_err()
+        });
+
+        let res = RemoteChessEngine::new(url)
+            .with_envelope_mode(3)
+            .connect()
+            .await;
+        assert!(matches!(
+            res,
+            Err(RemoteEngineError::Connect(tungstenite::Error::Protocol(_)))
+        ));
+        assert!(server.await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_text_message_in_envelope_mode() -> anyhow::Result<()> {
+        use uci_beyond::gui_commands::UciCommand;
+        use uci_beyond::util::Connection as _;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_hdr_async(stream, accept_envelope_subprotocol)
+                .await
+                .unwrap();
+            let _uci = ws.next().await.unwrap().unwrap();
+            let reply = UCI_REPLY.join("\n") + "\n";
+            ws.send(Message::Text(reply.into())).await.unwrap();
+            let _ = ws.close(None).await;
+        });
+
+        let mut connection = RemoteChessEngine::new(url)
+            .with_envelope_mode(3)
+            .connect()
+            .await?;
+        assert_eq!(connection.envelope_channel(), Some(3));
+        let res = connection.send(UciCommand).await;
+        assert!(matches!(
+            res,
+            Err(RemoteEngineError::Envelope(EnvelopeError::TextMessage))
+        ));
+        assert!(connection.diagnostics().last_error().is_some());
+        server.await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_error() {
+        // Nothing listens on the port of a dropped listener
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let res = RemoteChessEngine::new(url).connect().await;
+        assert!(matches!(res, Err(RemoteEngineError::Connect(_))));
+    }
+
+    #[tokio::test]
+    async fn test_connect_timeout() -> anyhow::Result<()> {
+        // The server accepts the TCP connection but never answers the handshake
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move { listener.accept().await });
+
+        let timeout = Duration::from_millis(50);
+        let res = RemoteChessEngine::new(url)
+            .with_connect_timeout(timeout)
+            .connect()
+            .await;
+        assert!(matches!(res, Err(RemoteEngineError::Timeout(t)) if t == timeout));
+        server.await??;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_uci_command() -> anyhow::Result<()> {
         use uci_beyond::gui_commands::UciCommand;
@@ -440,7 +1324,7 @@ mod tests {
         let engine = RemoteChessEngine::new("ws://127.0.0.1:9002");
         let mut connection = engine.connect().await?;
         connection.skip_message().await?;
-        let fen = uci_beyond::model::FenString("6k1/5ppp/8/8/8/6Q1/5PPP/6K1 w - - 0 1".to_string());
+        let fen = uci_beyond::model::FenString::from("6k1/5ppp/8/8/8/6Q1/5PPP/6K1 w - - 0 1");
         let eval = connection.evaluate_position(fen).await?;
 
         match eval {
@@ -480,7 +1364,7 @@ mod tests {
         let fen = uci_beyond::model::FenString(
             "r1bqkbnr/pppppppp/n7/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string(),
         );
-        let mv = MoveString("e2e4".to_string());
+        let mv = MoveString::from("e2e4");
         let eval = connection.evaluate_move(fen, mv).await?;
         match eval {
             MoveEvaluation::Best => {
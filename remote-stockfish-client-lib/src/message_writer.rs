@@ -0,0 +1,319 @@
+use async_trait::async_trait;
+use tungstenite::Utf8Bytes;
+use tungstenite::protocol::Message;
+use uci_beyond::transcript::Direction;
+use uci_beyond::util::{AsyncWritable, NoRetry, RetryPolicy};
+
+use crate::envelope::EnvelopeSequence;
+
+/// The [`AsyncWritable`] over a WebSocket sink, e.g. the write half of a connection to an engine
+/// served with `websocat --text`, that sends each line as a text message without the line terminator,
+/// or as a binary message with an [`Envelope`](crate::Envelope) in the envelope mode.
+///
+/// A failed send is retried according to the [`RetryPolicy`]. A message is never sent twice:
+/// once it was queued, only flushing it is retried.
+pub struct MessageWriter<S> {
+    sink: S,
+    retry_policy: Box<dyn RetryPolicy<tungstenite::Error> + Send + Sync>,
+    envelopes: Option<EnvelopeSequence>,
+}
+
+impl<S> MessageWriter<S>
+where
+    S: futures_util::Sink<Message, Error = tungstenite::Error> + Unpin,
+{
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            retry_policy: Box::new(NoRetry),
+            envelopes: None,
+        }
+    }
+
+    /// Retries the failed sends according to the policy, e.g. [`ExponentialRetry`](uci_beyond::util::ExponentialRetry)
+    /// with [`is_transient_ws_error`](crate::is_transient_ws_error). The default is [`NoRetry`].
+    pub fn with_retry_policy<P>(mut self, policy: P) -> Self
+    where
+        P: RetryPolicy<tungstenite::Error> + Send + Sync + 'static,
+    {
+        self.retry_policy = Box::new(policy);
+        self
+    }
+
+    /// Wraps each line into an [`Envelope`](crate::Envelope) of the channel, numbered from `0`.
+    pub fn with_envelope_channel(mut self, channel: u16) -> Self {
+        self.envelopes = Some(EnvelopeSequence::new(Direction::ToEngine, channel));
+        self
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// The sink, e.g. to send a close frame.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+
+    /// Sends the lines as a single message, e.g. a burst of `setoption` commands, to save the overhead
+    /// and the latency of a message per line.
+    ///
+    /// The lines are sent as a text message joined with `\n`, without copying the text, or as a binary message
+    /// with an [`Envelope`](crate::Envelope) per line in the envelope mode. The trailing line terminators are dropped.
+    pub async fn write_batch(&mut self, text: Utf8Bytes) -> Result<(), tungstenite::Error> {
+        let message = self.batch_message(text);
+        send_with_retries(&mut self.sink, message, self.retry_policy.as_ref()).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<S> AsyncWritable for MessageWriter<S>
+where
+    S: futures_util::Sink<Message, Error = tungstenite::Error> + Unpin,
+{
+    type Error = tungstenite::Error;
+
+    async fn write_lines(&mut self, text: &str) -> Result<(), Self::Error> {
+        let mut lines: Vec<&str> = text.lines().collect();
+        if lines.is_empty() {
+            lines.push("");
+        }
+        for line in lines {
+            let message = self.message(line);
+            send_with_retries(&mut self.sink, message, self.retry_policy.as_ref()).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<S> MessageWriter<S> {
+    fn message(&mut self, line: &str) -> Message {
+        match &mut self.envelopes {
+            Some(sequence) => Message::Binary(sequence.wrap(line).encode().into()),
+            None => Message::Text(Utf8Bytes::from(line.to_string())),
+        }
+    }
+
+    fn batch_message(&mut self, text: Utf8Bytes) -> Message {
+        let len = text.trim_end_matches(['\r', '\n']).len();
+        match &mut self.envelopes {
+            Some(sequence) => {
+                let mut buf = Vec::new();
+                for line in text[..len].lines() {
+                    sequence.wrap(line).encode_into(&mut buf);
+                }
+                Message::Binary(buf.into())
+            }
+            None if len == text.len() => Message::Text(text),
+            // Slicing the shared bytes doesn't copy them
+            None => Message::Text(
+                Utf8Bytes::try_from(bytes::Bytes::from(text).slice(..len))
+                    .expect("The text is cut at a line terminator"),
+            ),
+        }
+    }
+}
+
+/// Sends the message, retrying the failed attempts according to the policy.
+///
+/// Queuing the message and flushing it are retried separately, so that a queued message isn't queued again.
+async fn send_with_retries<S>(
+    sink: &mut S,
+    message: Message,
+    policy: &(dyn RetryPolicy<tungstenite::Error> + Send + Sync),
+) -> Result<(), tungstenite::Error>
+where
+    S: futures_util::Sink<Message, Error = tungstenite::Error> + Unpin,
+{
+    use futures_util::SinkExt as _;
+
+    let mut queued = false;
+    let mut failures = 0;
+    loop {
+        let res = if queued {
+            sink.flush().await
+        } else {
+            sink.feed(message.clone()).await
+        };
+        match res {
+            Ok(()) if queued => return Ok(()),
+            Ok(()) => {
+                queued = true;
+                failures = 0;
+            }
+            Err(e) => {
+                failures += 1;
+                let Some(delay) = policy.retry_after(&e, failures) else {
+                    return Err(e);
+                };
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use uci_beyond::engine_commands::ReadyOkCommand;
+    use uci_beyond::util::ExponentialRetry;
+
+    use super::*;
+    use crate::{Envelope, is_transient_ws_error};
+
+    /// A sink that isn't ready for the first `failures` messages.
+    struct FlakySink {
+        sent: Vec<Message>,
+        failures: usize,
+    }
+
+    impl futures_util::Sink<Message> for FlakySink {
+        type Error = tungstenite::Error;
+
+        fn poll_ready(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                let e = std::io::Error::from(std::io::ErrorKind::WouldBlock);
+                return std::task::Poll::Ready(Err(tungstenite::Error::Io(e)));
+            }
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn start_send(
+            mut self: std::pin::Pin<&mut Self>,
+            item: Message,
+        ) -> Result<(), Self::Error> {
+            self.sent.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    fn flaky(failures: usize) -> FlakySink {
+        FlakySink {
+            sent: Vec::new(),
+            failures,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retries() {
+        let message = || Message::Text("isready".into());
+
+        let mut sink = flaky(1);
+        let res = send_with_retries(&mut sink, message(), &NoRetry).await;
+        assert!(matches!(res, Err(tungstenite::Error::Io(_))));
+        assert!(sink.sent.is_empty());
+
+        let policy = ExponentialRetry::new(
+            2,
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+            is_transient_ws_error,
+        );
+        let mut sink = flaky(1);
+        send_with_retries(&mut sink, message(), &policy)
+            .await
+            .unwrap();
+        assert_eq!(sink.sent, [message()]);
+    }
+
+    #[tokio::test]
+    async fn test_write_lines_as_messages() {
+        let mut writer = MessageWriter::new(flaky(0));
+        writer.write_command(&ReadyOkCommand).await.unwrap();
+        writer
+            .write_lines("id name Fake\r\nid author Nobody\n\nuciok\n")
+            .await
+            .unwrap();
+        let sent: Vec<_> = writer
+            .into_inner()
+            .sent
+            .into_iter()
+            .map(|message| message.into_text().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            sent,
+            ["readyok", "id name Fake", "id author Nobody", "", "uciok"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_lines_as_envelopes() {
+        let mut writer = MessageWriter::new(flaky(0)).with_envelope_channel(3);
+        writer.write_lines("ucinewgame\nisready").await.unwrap();
+        let envelopes: Vec<_> = writer
+            .into_inner()
+            .sent
+            .into_iter()
+            .flat_map(|message| Envelope::decode_all(&message.into_data()).unwrap())
+            .collect();
+        assert_eq!(
+            envelopes,
+            [
+                Envelope {
+                    direction: Direction::ToEngine,
+                    channel: 3,
+                    sequence: 0,
+                    line: "ucinewgame".to_string(),
+                },
+                Envelope {
+                    direction: Direction::ToEngine,
+                    channel: 3,
+                    sequence: 1,
+                    line: "isready".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_batch() {
+        let mut writer = MessageWriter::new(flaky(0));
+        writer
+            .write_batch(Utf8Bytes::from_static("ucinewgame\nisready\n"))
+            .await
+            .unwrap();
+        let sent = writer.into_inner().sent;
+        assert_eq!(sent, [Message::Text("ucinewgame\nisready".into())]);
+
+        let mut writer = MessageWriter::new(flaky(0)).with_envelope_channel(1);
+        writer.write_lines("uci").await.unwrap();
+        writer
+            .write_batch(Utf8Bytes::from_static("ucinewgame\nisready"))
+            .await
+            .unwrap();
+        let sent = writer.into_inner().sent;
+        assert_eq!(sent.len(), 2);
+        let sequences: Vec<_> = Envelope::decode_all(&sent[1].clone().into_data())
+            .unwrap()
+            .into_iter()
+            .map(|envelope| (envelope.sequence, envelope.line))
+            .collect();
+        assert_eq!(
+            sequences,
+            [(1, "ucinewgame".to_string()), (2, "isready".to_string())]
+        );
+    }
+}
@@ -0,0 +1,724 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::FutureExt as _;
+use futures_util::future::LocalBoxFuture;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::Semaphore;
+use uci_beyond::capabilities::EngineCapabilities;
+use uci_beyond::gui_command_responses::{GoEvent, GoResponse};
+use uci_beyond::gui_commands::{GoCommand, PositionCommand, QuitCommand, UciCommand};
+use uci_beyond::util::{Backoff, Clock, Connection, IoConnection, SystemClock};
+
+use crate::RemoteEngineError;
+
+/// A [`Connection`] that can be managed by an [`EnginePool`].
+///
+/// The errors of the connection, e.g. [`RemoteEngineError`](crate::RemoteEngineError),
+/// are reported by the pool as [`anyhow::Error`].
+#[async_trait(?Send)]
+pub trait PooledConnection: Connection<Err: Into<anyhow::Error>> {
+    /// Reads the output of the search started with `go` up to and including `bestmove`.
+    async fn read_search(&mut self) -> anyhow::Result<Vec<GoEvent>>;
+
+    /// Sends `quit` and closes the connection.
+    async fn quit(&mut self) -> anyhow::Result<()>;
+}
+
+/// A local engine, e.g. one spawned with [`IoConnection::spawn`].
+#[async_trait(?Send)]
+impl<R, W> PooledConnection for IoConnection<R, W>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    async fn read_search(&mut self) -> anyhow::Result<Vec<GoEvent>> {
+        read_go_events(self).await
+    }
+
+    async fn quit(&mut self) -> anyhow::Result<()> {
+        self.send(QuitCommand).await??;
+        Ok(())
+    }
+}
+
+/// Reads the raw lines of a search up to and including `bestmove`, skipping the lines that aren't
+/// [`GoEvent`]s, e.g. the echoes of the sent commands.
+pub(crate) async fn read_go_events<C>(connection: &mut C) -> anyhow::Result<Vec<GoEvent>>
+where
+    C: Connection<Err: Into<anyhow::Error>>,
+{
+    let mut events = Vec::new();
+    loop {
+        let line = connection
+            .read_raw_line()
+            .await
+            .map_err(Into::<anyhow::Error>::into)?
+            .ok_or(RemoteEngineError::Closed)?;
+        if line.is_empty() {
+            continue;
+        }
+        match line.parse::<GoEvent>() {
+            Ok(GoEvent::BestMove(bestmove)) => {
+                events.push(GoEvent::BestMove(bestmove));
+                return Ok(events);
+            }
+            Ok(event) => events.push(event),
+            // E.g. echoes of the sent commands
+            Err(uci_beyond::command::parsing::Error::UnexpectedCommand(_)) => (),
+            Err(e) => {
+                return Err(RemoteEngineError::Protocol {
+                    command: "go".to_string(),
+                    message: format!("Failed to parse `{line}`: {e:?}"),
+                }
+                .into());
+            }
+        }
+    }
+}
+
+type Connector<C> = Box<dyn Fn(usize) -> LocalBoxFuture<'static, anyhow::Result<C>>>;
+
+/// The first delay between the failed re-initializations of an engine, see [`EnginePool::with_reinitialize_backoff`].
+pub const DEFAULT_REINITIALIZE_BACKOFF_INITIAL: Duration = Duration::from_millis(100);
+/// The longest delay between the failed re-initializations of an engine.
+pub const DEFAULT_REINITIALIZE_BACKOFF_MAX: Duration = Duration::from_secs(10);
+
+struct Slot<C> {
+    index: usize,
+    connection: C,
+}
+
+/// A fixed-size pool of engine connections for analyzing several positions in parallel.
+///
+/// Engines are handed out in the order they were requested, either with [`checkout`](Self::checkout)
+/// or with [`analyze_on_any`](Self::analyze_on_any), so a connection is never used by two analyses at once.
+///
+/// Dropping the pool sends `quit` to the idle engines on a best-effort basis, i.e. without waiting for
+/// the engines that can't accept it immediately. Use [`shutdown`](Self::shutdown) to wait for all of them.
+///
+/// After a failed re-initialization, the next one of the same engine waits for a [`Backoff`],
+/// see [`with_reinitialize_backoff`](Self::with_reinitialize_backoff).
+pub struct EnginePool<C>
+where
+    C: PooledConnection,
+{
+    connector: Connector<C>,
+    idle: Mutex<VecDeque<Slot<C>>>,
+    available: Semaphore,
+    capabilities: Mutex<Vec<EngineCapabilities>>,
+    backoffs: Mutex<Vec<Backoff<Box<dyn Clock>>>>,
+}
+
+/// An engine checked out of an [`EnginePool`]. It's checked back in when dropped.
+pub struct PooledEngine<'a, C>
+where
+    C: PooledConnection,
+{
+    pool: &'a EnginePool<C>,
+    slot: Option<Slot<C>>,
+}
+
+impl<C> EnginePool<C>
+where
+    C: PooledConnection,
+{
+    /// Creates a pool of `size` engines.
+    ///
+    /// `connect` is called with the index of the engine, so that the engines of the pool can differ,
+    /// and again whenever the connection to the engine has to be re-initialized.
+    pub async fn new<F, Fut>(size: usize, connect: F) -> anyhow::Result<Self>
+    where
+        F: Fn(usize) -> Fut + 'static,
+        Fut: Future<Output = anyhow::Result<C>> + 'static,
+    {
+        let connector: Connector<C> = Box::new(move |index| connect(index).boxed_local());
+
+        let mut idle = VecDeque::with_capacity(size);
+        let mut capabilities = Vec::with_capacity(size);
+        for index in 0..size {
+            let (connection, caps) = Self::initialize(&connector, index).await?;
+            idle.push_back(Slot { index, connection });
+            capabilities.push(caps);
+        }
+
+        let pool = Self {
+            connector,
+            idle: Mutex::new(idle),
+            available: Semaphore::new(size),
+            capabilities: Mutex::new(capabilities),
+            backoffs: Mutex::new(Vec::new()),
+        };
+        Ok(pool.with_reinitialize_backoff(
+            SystemClock::default(),
+            DEFAULT_REINITIALIZE_BACKOFF_INITIAL,
+            DEFAULT_REINITIALIZE_BACKOFF_MAX,
+        ))
+    }
+
+    /// Replaces the backoff between the failed re-initializations of each engine. The delay starts at `initial`,
+    /// doubles after every consecutive failure and is capped at `max`, as measured by the `clock`.
+    ///
+    /// The default is [`DEFAULT_REINITIALIZE_BACKOFF_INITIAL`] up to [`DEFAULT_REINITIALIZE_BACKOFF_MAX`].
+    pub fn with_reinitialize_backoff<K>(self, clock: K, initial: Duration, max: Duration) -> Self
+    where
+        K: Clock + Clone + 'static,
+    {
+        let backoffs = (0..self.size())
+            .map(|_| Backoff::new(Box::new(clock.clone()) as Box<dyn Clock>, initial, max))
+            .collect();
+        *self.backoffs.lock().unwrap() = backoffs;
+        self
+    }
+
+    /// The time left until the engine with the given index may be re-initialized after a failed re-initialization.
+    pub fn reinitialize_delay(&self, index: usize) -> Option<Duration> {
+        self.backoffs
+            .lock()
+            .unwrap()
+            .get(index)
+            .map(Backoff::remaining)
+    }
+
+    async fn initialize(
+        connector: &Connector<C>,
+        index: usize,
+    ) -> anyhow::Result<(C, EngineCapabilities)> {
+        let mut connection = connector(index).await?;
+        let response = connection
+            .send(UciCommand)
+            .await
+            .map_err(Into::<anyhow::Error>::into)?
+            .map_err(|e| anyhow::anyhow!("Failed to parse the response to `uci`: {e:?}"))?;
+        Ok((connection, EngineCapabilities::from(response)))
+    }
+
+    /// The number of engines in the pool.
+    pub fn size(&self) -> usize {
+        self.capabilities.lock().unwrap().len()
+    }
+
+    /// The capabilities advertised by the engine with the given index.
+    pub fn capabilities(&self, index: usize) -> Option<EngineCapabilities> {
+        self.capabilities.lock().unwrap().get(index).cloned()
+    }
+
+    /// Waits until an engine is available and checks it out.
+    pub async fn checkout(&self) -> PooledEngine<'_, C> {
+        // The semaphore is fair, so the engines are handed out in the order they were requested
+        self.available
+            .acquire()
+            .await
+            .expect("The semaphore is never closed")
+            .forget();
+
+        let slot = self
+            .idle
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("A permit is only available when an engine is idle");
+
+        PooledEngine {
+            pool: self,
+            slot: Some(slot),
+        }
+    }
+
+    /// Checks the engine back in. Same as dropping it.
+    pub fn checkin(&self, engine: PooledEngine<'_, C>) {
+        drop(engine);
+    }
+
+    fn return_slot(&self, slot: Slot<C>) {
+        self.idle.lock().unwrap().push_back(slot);
+        self.available.add_permits(1);
+    }
+
+    /// Analyzes the position on the first available engine.
+    ///
+    /// If the analysis fails, the connection to the engine is re-initialized before the engine is checked in.
+    /// If that fails too, the error of the analysis has the error of the re-initialization as its context.
+    pub async fn analyze_on_any(
+        &self,
+        position: PositionCommand,
+        go: GoCommand,
+    ) -> anyhow::Result<GoResponse> {
+        let mut engine = self.checkout().await;
+
+        let res = engine.analyze(position, go).await;
+        if let Err(e) = res {
+            return match engine.reinitialize().await {
+                Ok(()) => Err(e),
+                Err(reinit) => Err(e.context(format!(
+                    "Failed to re-initialize engine {} afterwards: {reinit:#}",
+                    engine.index()
+                ))),
+            };
+        }
+        res
+    }
+
+    /// Sends `quit` to all engines and waits for them to accept it.
+    pub async fn shutdown(self) -> anyhow::Result<()> {
+        let slots: Vec<_> = self.idle.lock().unwrap().drain(..).collect();
+
+        let mut res = Ok(());
+        for mut slot in slots {
+            if let Err(e) = slot.connection.quit().await {
+                res = res.and(Err(e));
+            }
+        }
+        res
+    }
+}
+
+impl<C> Drop for EnginePool<C>
+where
+    C: PooledConnection,
+{
+    fn drop(&mut self) {
+        // Async drop is not available, so the engines that can't quit right away are just dropped
+        let idle = self.idle.get_mut().unwrap_or_else(|e| e.into_inner());
+        for slot in idle.iter_mut() {
+            let _ = slot.connection.quit().now_or_never();
+        }
+    }
+}
+
+impl<C> PooledEngine<'_, C>
+where
+    C: PooledConnection,
+{
+    /// The index of the engine in the pool.
+    pub fn index(&self) -> usize {
+        self.slot().index
+    }
+
+    /// The capabilities advertised by the engine.
+    pub fn capabilities(&self) -> EngineCapabilities {
+        self.pool
+            .capabilities(self.index())
+            .expect("The index of a pooled engine is within the pool")
+    }
+
+    /// Sets up the position, starts the search, and waits for `bestmove`.
+    pub async fn analyze(
+        &mut self,
+        position: PositionCommand,
+        go: GoCommand,
+    ) -> anyhow::Result<GoResponse> {
+        let connection = &mut self.slot_mut().connection;
+        connection
+            .send(position)
+            .await
+            .map_err(Into::<anyhow::Error>::into)??;
+        connection
+            .send(go)
+            .await
+            .map_err(Into::<anyhow::Error>::into)??;
+        let events = connection.read_search().await?;
+        Ok(GoResponse::from_events(&events))
+    }
+
+    /// Replaces the connection to the engine with a new one, e.g. after an error.
+    ///
+    /// If the previous re-initialization of the engine failed, waits for the [`EnginePool::reinitialize_delay`] first.
+    pub async fn reinitialize(&mut self) -> anyhow::Result<()> {
+        let index = self.index();
+        let delay = self.pool.backoffs.lock().unwrap()[index].remaining();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+
+        let res = EnginePool::initialize(&self.pool.connector, index).await;
+        {
+            let mut backoffs = self.pool.backoffs.lock().unwrap();
+            match res {
+                Ok(_) => backoffs[index].succeeded(),
+                Err(_) => backoffs[index].failed(),
+            }
+        }
+
+        let (connection, caps) = res?;
+        self.slot_mut().connection = connection;
+        self.pool.capabilities.lock().unwrap()[index] = caps;
+        Ok(())
+    }
+
+    fn slot(&self) -> &Slot<C> {
+        self.slot.as_ref().expect("The slot is only taken on drop")
+    }
+
+    fn slot_mut(&mut self) -> &mut Slot<C> {
+        self.slot.as_mut().expect("The slot is only taken on drop")
+    }
+}
+
+impl<C> std::ops::Deref for PooledEngine<'_, C>
+where
+    C: PooledConnection,
+{
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.slot().connection
+    }
+}
+
+impl<C> std::ops::DerefMut for PooledEngine<'_, C>
+where
+    C: PooledConnection,
+{
+    fn deref_mut(&mut self) -> &mut C {
+        &mut self.slot_mut().connection
+    }
+}
+
+impl<C> Drop for PooledEngine<'_, C>
+where
+    C: PooledConnection,
+{
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot.take() {
+            self.pool.return_slot(slot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    use tokio::task::JoinHandle;
+    use uci_beyond::fixtures::{DuplexConnection, FakeEngine};
+    use uci_beyond::gui_commands::StopCommand;
+    use uci_beyond::model;
+
+    const INFO: &str =
+        "info depth 1 seldepth 1 multipv 1 score cp 20 nodes 20 nps 20000 time 1 pv e2e4\n";
+
+    /// What happened to a fake engine, shared with the test.
+    #[derive(Default)]
+    struct FakeEngineLog {
+        commands: Vec<String>,
+        /// Makes the engine exit on the next `go`, as if it crashed.
+        fail_next_search: bool,
+    }
+
+    /// An engine that answers `uci` and replies to `go` with a one-line search.
+    ///
+    /// `go infinite` is only answered with `bestmove` after `stop`.
+    fn fake_engine(
+        name: String,
+        log: Arc<Mutex<FakeEngineLog>>,
+    ) -> (DuplexConnection, JoinHandle<Vec<String>>) {
+        let mut searching = false;
+        FakeEngine::new(move |line| {
+            let mut log = log.lock().unwrap();
+            log.commands.push(line.to_string());
+            Some(match line {
+                "uci" => format!(
+                    "id name {name}\n\
+                     id author Nobody\n\
+                     \n\
+                     option name Hash type spin default 16 min 1 max 1024\n\
+                     uciok\n"
+                ),
+                "go infinite" => {
+                    searching = true;
+                    INFO.to_string()
+                }
+                "stop" if searching => {
+                    searching = false;
+                    "bestmove e2e4\n".to_string()
+                }
+                _ if line.starts_with("go") => {
+                    if std::mem::take(&mut log.fail_next_search) {
+                        return None;
+                    }
+                    format!("{INFO}bestmove e2e4\n")
+                }
+                _ => String::new(),
+            })
+        })
+        .connect()
+    }
+
+    /// The engines started by the pool, which return the commands they have received once they exit.
+    type Engines = Rc<RefCell<Vec<JoinHandle<Vec<String>>>>>;
+
+    fn fake_engines(
+        n: usize,
+    ) -> (
+        Vec<Arc<Mutex<FakeEngineLog>>>,
+        Engines,
+        impl Fn(usize) -> std::future::Ready<anyhow::Result<DuplexConnection>> + 'static,
+    ) {
+        let logs: Vec<_> = (0..n).map(|_| Arc::default()).collect();
+        let engines = Engines::default();
+        let connect = {
+            let logs = logs.clone();
+            let engines = Rc::clone(&engines);
+            move |index: usize| {
+                let (connection, engine) =
+                    fake_engine(format!("Fake {index}"), Arc::clone(&logs[index]));
+                engines.borrow_mut().push(engine);
+                std::future::ready(Ok(connection))
+            }
+        };
+        (logs, engines, connect)
+    }
+
+    /// Waits for the engines to exit and returns the last command each of them has received.
+    async fn last_commands(engines: &Engines) -> Vec<Option<String>> {
+        let engines = engines.take();
+        let mut last = Vec::new();
+        for engine in engines {
+            last.push(engine.await.unwrap().pop());
+        }
+        last
+    }
+
+    fn startpos() -> PositionCommand {
+        PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parallel_analyses_do_not_interleave() {
+        let (logs, engines, connect) = fake_engines(2);
+        let pool = EnginePool::new(2, connect).await.unwrap();
+
+        let analyses = (0..6).map(|_| pool.analyze_on_any(startpos(), GoCommand::for_depth(1)));
+        let results = futures_util::future::join_all(analyses).await;
+
+        for res in results {
+            let res = res.unwrap();
+            let best_move = res.search_summary.best_move.unwrap().best_move;
+            assert_eq!(best_move, model::MoveString::from("e2e4"));
+        }
+
+        let mut searches = 0;
+        for log in &logs {
+            let log = log.lock().unwrap();
+            // uci, then position/go pairs
+            assert_eq!(log.commands[0], "uci");
+            for pair in log.commands[1..].chunks(2) {
+                assert!(pair[0].starts_with("position"));
+                assert!(pair[1].starts_with("go"));
+            }
+            searches += log.commands[1..].len() / 2;
+            // Both engines were used
+            assert!(log.commands.len() > 1);
+        }
+        assert_eq!(searches, 6);
+
+        pool.shutdown().await.unwrap();
+        assert_eq!(
+            last_commands(&engines).await,
+            [Some("quit".to_string()), Some("quit".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_per_engine_capabilities() {
+        let (_logs, _engines, connect) = fake_engines(2);
+        let pool = EnginePool::new(2, connect).await.unwrap();
+
+        assert_eq!(pool.size(), 2);
+        assert_eq!(pool.capabilities(0).unwrap().name(), "Fake 0");
+        assert_eq!(pool.capabilities(1).unwrap().name(), "Fake 1");
+        assert!(pool.capabilities(2).is_none());
+
+        let engine = pool.checkout().await;
+        assert_eq!(
+            engine.capabilities().name(),
+            format!("Fake {}", engine.index())
+        );
+        assert!(engine.capabilities().option("hash").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_reinitialize_errored_connection() {
+        let connects = Rc::new(Cell::new(0));
+        let (logs, _engines, connect) = fake_engines(1);
+        let connect = {
+            let connects = Rc::clone(&connects);
+            move |index: usize| {
+                connects.set(connects.get() + 1);
+                connect(index)
+            }
+        };
+        let pool = EnginePool::new(1, connect).await.unwrap();
+        assert_eq!(connects.get(), 1);
+
+        logs[0].lock().unwrap().fail_next_search = true;
+        assert!(
+            pool.analyze_on_any(startpos(), GoCommand::for_depth(1))
+                .await
+                .is_err()
+        );
+        assert_eq!(connects.get(), 2);
+
+        // The new connection starts with a fresh `uci` handshake
+        assert_eq!(logs[0].lock().unwrap().commands.last().unwrap(), "uci");
+        assert!(
+            pool.analyze_on_any(startpos(), GoCommand::for_depth(1))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_reinitialization_is_reported() {
+        let connects = Rc::new(Cell::new(0));
+        let (logs, _engines, connect) = fake_engines(1);
+        let connect = {
+            let connects = Rc::clone(&connects);
+            move |index: usize| {
+                connects.set(connects.get() + 1);
+                if connects.get() > 1 {
+                    return std::future::ready(Err(anyhow::anyhow!("The engine is gone")));
+                }
+                connect(index)
+            }
+        };
+        let pool = EnginePool::new(1, connect).await.unwrap();
+
+        logs[0].lock().unwrap().fail_next_search = true;
+        let e = pool
+            .analyze_on_any(startpos(), GoCommand::for_depth(1))
+            .await
+            .unwrap_err();
+        assert_eq!(connects.get(), 2);
+        assert_eq!(
+            format!("{e:#}"),
+            "Failed to re-initialize engine 0 afterwards: The engine is gone: The engine closed the connection"
+        );
+        assert!(matches!(
+            e.root_cause().downcast_ref(),
+            Some(RemoteEngineError::Closed)
+        ));
+    }
+
+    /// A clock that only moves when told to.
+    #[derive(Clone, Default)]
+    struct FakeClock(Rc<Cell<Duration>>);
+
+    impl FakeClock {
+        fn advance(&self, by: Duration) {
+            self.0.set(self.0.get() + by);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> uci_beyond::util::Timestamp {
+            uci_beyond::util::Timestamp::from_monotonic(self.0.get())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reinitialize_with_backoff() {
+        let connects = Rc::new(Cell::new(0));
+        let reachable = Rc::new(Cell::new(true));
+        let (logs, _engines, connect) = fake_engines(1);
+        let connect = {
+            let connects = Rc::clone(&connects);
+            let reachable = Rc::clone(&reachable);
+            move |index: usize| {
+                connects.set(connects.get() + 1);
+                if !reachable.get() {
+                    return std::future::ready(Err(anyhow::anyhow!("The engine is gone")));
+                }
+                connect(index)
+            }
+        };
+        let clock = FakeClock::default();
+        let pool = EnginePool::new(1, connect)
+            .await
+            .unwrap()
+            .with_reinitialize_backoff(
+                clock.clone(),
+                Duration::from_millis(100),
+                Duration::from_millis(150),
+            );
+        assert_eq!(pool.reinitialize_delay(0), Some(Duration::ZERO));
+        assert_eq!(pool.reinitialize_delay(1), None);
+
+        reachable.set(false);
+        let mut engine = pool.checkout().await;
+        assert!(engine.reinitialize().await.is_err());
+        assert_eq!(connects.get(), 2);
+        assert_eq!(pool.reinitialize_delay(0), Some(Duration::from_millis(100)));
+        clock.advance(Duration::from_millis(40));
+        assert_eq!(pool.reinitialize_delay(0), Some(Duration::from_millis(60)));
+
+        // The next attempt waits for the backoff rather than connecting right away
+        let attempt = tokio::time::timeout(Duration::from_millis(20), engine.reinitialize()).await;
+        assert!(attempt.is_err());
+        assert_eq!(connects.get(), 2);
+
+        clock.advance(Duration::from_millis(60));
+        assert!(engine.reinitialize().await.is_err());
+        assert_eq!(connects.get(), 3);
+        assert_eq!(pool.reinitialize_delay(0), Some(Duration::from_millis(150)));
+
+        reachable.set(true);
+        clock.advance(Duration::from_millis(150));
+        engine.reinitialize().await.unwrap();
+        assert_eq!(connects.get(), 4);
+        assert_eq!(pool.reinitialize_delay(0), Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_quit_on_drop() {
+        let (_logs, engines, connect) = fake_engines(3);
+        let pool = EnginePool::new(3, connect).await.unwrap();
+
+        drop(pool);
+
+        assert_eq!(
+            last_commands(&engines).await,
+            vec![Some("quit".to_string()); 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_go_infinite_while_the_search_is_read() {
+        let (mut connection, engine) = fake_engine("Fake".to_string(), Arc::default());
+
+        let go_infinite = GoCommand {
+            indefinite: true,
+            ..Default::default()
+        };
+        let search = connection
+            .send_expecting_later_response::<_, GoResponse>(go_infinite)
+            .await
+            .unwrap();
+        let stop = async {
+            // Let the search be read in the meantime
+            tokio::task::yield_now().await;
+            connection.send(StopCommand).await
+        };
+        let (response, stopped) = futures_util::future::join(search, stop).await;
+
+        let Ok(()) = stopped.unwrap();
+        let response = response.unwrap().unwrap();
+        assert_eq!(response.search_summary.depth, Some(1));
+        assert_eq!(
+            response.search_summary.best_move.map(|b| b.best_move),
+            Some(model::MoveString::from("e2e4"))
+        );
+        drop(connection);
+        assert_eq!(engine.await.unwrap(), ["go infinite", "stop"]);
+    }
+}
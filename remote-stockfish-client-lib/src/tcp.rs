@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use tokio::io::{AsyncRead, AsyncWrite};
+use uci_beyond::{
+    gui_commands::UciCommandTrait,
+    util::{
+        AsyncReadable, Connection, ConnectionDiagnostics, IoConnection, ResponseHandle,
+        TranscriptLog,
+    },
+};
+
+use crate::RemoteEngineError;
+
+type TcpRead = Box<dyn AsyncRead + Unpin + Send>;
+type TcpWrite = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// An engine served over a raw TCP socket, one UCI line per `\n`, e.g. by the engine servers of lichess-bot,
+/// rather than over WebSockets (see [`RemoteChessEngine`](crate::RemoteChessEngine)).
+pub struct TcpChessEngine {
+    /// `host:port`.
+    addr: String,
+    connect_timeout: Option<std::time::Duration>,
+    #[cfg(feature = "tls")]
+    tls: Option<(
+        tokio_rustls::rustls::pki_types::ServerName<'static>,
+        std::sync::Arc<tokio_rustls::rustls::ClientConfig>,
+    )>,
+}
+
+/// The [`Connection`] to a [`TcpChessEngine`].
+///
+/// The errors of the socket are [`RemoteEngineError::Transport`] with [`tungstenite::Error::Io`],
+/// except for the end of the stream in the middle of a response, which is [`RemoteEngineError::Closed`].
+pub struct TcpChessEngineConnection {
+    inner: IoConnection<TcpRead, TcpWrite>,
+}
+
+impl TcpChessEngine {
+    /// The engine at `host:port`, e.g. `127.0.0.1:9003`.
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            connect_timeout: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// Fails with [`RemoteEngineError::Timeout`] if the connection isn't established within the `timeout`,
+    /// incl. the TLS handshake.
+    pub fn with_connect_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Connects over TLS, verifying the certificate of the server for the name.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(
+        mut self,
+        server_name: tokio_rustls::rustls::pki_types::ServerName<'static>,
+        config: std::sync::Arc<tokio_rustls::rustls::ClientConfig>,
+    ) -> Self {
+        self.tls = Some((server_name, config));
+        self
+    }
+
+    /// Connects to the engine. A refused connection, or a failed TLS handshake, is [`RemoteEngineError::Connect`].
+    pub async fn connect(self) -> Result<TcpChessEngineConnection, RemoteEngineError> {
+        let timeout = self.connect_timeout;
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.connect_without_timeout())
+                .await
+                .unwrap_or(Err(RemoteEngineError::Timeout(timeout))),
+            None => self.connect_without_timeout().await,
+        }
+    }
+
+    async fn connect_without_timeout(self) -> Result<TcpChessEngineConnection, RemoteEngineError> {
+        let stream = tokio::net::TcpStream::connect(&self.addr)
+            .await
+            .map_err(connect_error)?;
+        // UCI lines are small and latency matters more than throughput
+        stream.set_nodelay(true).map_err(connect_error)?;
+
+        #[cfg(feature = "tls")]
+        if let Some((server_name, config)) = self.tls {
+            let stream = tokio_rustls::TlsConnector::from(config)
+                .connect(server_name, stream)
+                .await
+                .map_err(connect_error)?;
+            let (read, write) = tokio::io::split(stream);
+            return Ok(TcpChessEngineConnection::new(
+                Box::new(read),
+                Box::new(write),
+            ));
+        }
+
+        let (read, write) = stream.into_split();
+        Ok(TcpChessEngineConnection::new(
+            Box::new(read),
+            Box::new(write),
+        ))
+    }
+}
+
+fn connect_error(e: std::io::Error) -> RemoteEngineError {
+    RemoteEngineError::Connect(tungstenite::Error::Io(e))
+}
+
+/// Maps the error of the established connection, e.g. a reset, to the typed variants.
+fn transport_error(e: std::io::Error) -> RemoteEngineError {
+    match e.kind() {
+        std::io::ErrorKind::UnexpectedEof => RemoteEngineError::Closed,
+        _ => RemoteEngineError::Transport(tungstenite::Error::Io(e)),
+    }
+}
+
+impl TcpChessEngineConnection {
+    fn new(read: TcpRead, write: TcpWrite) -> Self {
+        Self {
+            inner: IoConnection::new(read, write),
+        }
+    }
+
+    /// The snapshot of what the connection has been doing lately, e.g. the last lines and the last error.
+    pub fn diagnostics(&self) -> ConnectionDiagnostics {
+        self.inner.diagnostics()
+    }
+
+    /// Writes every line sent or received from now on to the log, incl. the raw lines.
+    pub fn with_transcript(mut self, transcript: TranscriptLog) -> Self {
+        self.inner = self.inner.with_transcript(transcript);
+        self
+    }
+}
+
+#[async_trait(?Send)]
+impl Connection for TcpChessEngineConnection {
+    type Err = RemoteEngineError;
+
+    async fn send<C>(
+        &mut self,
+        cmd: C,
+    ) -> Result<Result<C::Response, <C::Response as AsyncReadable>::Err>, Self::Err>
+    where
+        C: UciCommandTrait,
+        C::Response: AsyncReadable,
+    {
+        self.inner.send(cmd).await.map_err(transport_error)
+    }
+
+    async fn send_expecting_later_response<C, T>(
+        &mut self,
+        cmd: C,
+    ) -> Result<ResponseHandle<T, Self::Err>, Self::Err>
+    where
+        C: UciCommandTrait,
+        T: AsyncReadable + 'static,
+    {
+        let response = self
+            .inner
+            .send_expecting_later_response::<C, T>(cmd)
+            .await
+            .map_err(transport_error)?;
+        Ok(ResponseHandle::new(async move {
+            response.await.map_err(transport_error)
+        }))
+    }
+
+    async fn send_raw_line(&mut self, line: &str) -> Result<(), Self::Err> {
+        self.inner
+            .send_raw_line(line)
+            .await
+            .map_err(transport_error)
+    }
+
+    async fn read_raw_line(&mut self) -> Result<Option<String>, Self::Err> {
+        self.inner.read_raw_line().await.map_err(transport_error)
+    }
+
+    fn last_response_lines(&self) -> Option<usize> {
+        self.inner.last_response_lines()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader};
+    use uci_beyond::gui_commands::{IsReadyCommand, UciCommand};
+
+    use super::*;
+
+    /// Serves one connection with a scripted handshake: the banner, then the answers to `uci` and `isready`.
+    async fn scripted_engine(listener: tokio::net::TcpListener) -> Vec<String> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read, mut write) = stream.into_split();
+        write.write_all(b"Fake 1.0 by Somebody\n").await.unwrap();
+
+        let mut received = Vec::new();
+        let mut lines = BufReader::new(read).lines();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let reply = match line.as_str() {
+                "uci" => {
+                    "id name Fake 1.0\nid author Somebody\noption name Hash type spin default 16 min 1 max 1024\nuciok\n"
+                }
+                "isready" => "readyok\n",
+                "quit" => {
+                    received.push(line);
+                    break;
+                }
+                _ => "",
+            };
+            received.push(line);
+            write.write_all(reply.as_bytes()).await.unwrap();
+        }
+        received
+    }
+
+    #[tokio::test]
+    async fn test_tcp_handshake() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(scripted_engine(listener));
+
+        let mut connection = TcpChessEngine::new(addr.to_string())
+            .connect()
+            .await
+            .unwrap();
+        let handshake = connection.send(UciCommand).await.unwrap().unwrap();
+        assert_eq!(handshake.id_block.name, "Fake 1.0");
+        let Ok(()) = connection.send(IsReadyCommand).await.unwrap();
+        assert_eq!(
+            connection.read_raw_line().await.unwrap().as_deref(),
+            Some("readyok")
+        );
+        connection.send_raw_line("quit").await.unwrap();
+
+        assert_eq!(server.await.unwrap(), ["uci", "isready", "quit"]);
+        assert_eq!(connection.diagnostics().commands_sent("uci"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connection_refused() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let error = TcpChessEngine::new(addr.to_string())
+            .connect()
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error,
+            RemoteEngineError::Connect(tungstenite::Error::Io(e))
+                if e.kind() == std::io::ErrorKind::ConnectionRefused
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tcp_reset_mid_stream() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.readable().await.unwrap();
+            stream.write_all(b"id name Fake 1.0\n").await.unwrap();
+            // Closing the socket with the `uci` line unread resets the connection instead of closing it gracefully
+            drop(stream);
+        });
+
+        let mut connection = TcpChessEngine::new(addr.to_string())
+            .connect()
+            .await
+            .unwrap();
+        let result = connection.send(UciCommand).await;
+        server.await.unwrap();
+        assert!(
+            matches!(
+                &result,
+                Err(RemoteEngineError::Transport(tungstenite::Error::Io(e)))
+                    if e.kind() == std::io::ErrorKind::ConnectionReset
+            ),
+            "{:?}",
+            result.map(|_| ())
+        );
+        // The error isn't reported as one of a WebSocket
+        let message = result.err().unwrap().to_string();
+        assert!(message.starts_with("Transport error: "), "{message}");
+    }
+}
@@ -0,0 +1,86 @@
+//! A minimal UCI engine that answers `uci`, `isready` and `quit`, ignoring the other commands.
+//!
+//! Run it with `cargo run --example echo_engine` and type the commands, or point a GUI at it.
+
+use tokio::io::{AsyncRead, AsyncWrite, BufReader};
+use uci_beyond::{
+    engine_commands::{IdBlock, ReadyOkCommand, UciOkCommand, UciOptionBlockBuilder},
+    gui_command_responses::UciResponse,
+    gui_commands::AnyGuiCommand,
+    util::{AsyncReadable, AsyncWritable as _, LineWriter},
+};
+
+/// Answers the commands read from `reader` until `quit` or the end of the input.
+async fn serve<R, W>(reader: R, writer: W) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin,
+{
+    let mut reader = BufReader::new(reader);
+    let mut writer = LineWriter::new(writer);
+
+    while let Some(cmd) = AnyGuiCommand::read_from(&mut reader).await? {
+        match cmd {
+            Ok(AnyGuiCommand::Uci(_)) => {
+                let response = UciResponse {
+                    id_block: IdBlock {
+                        name: "Echo 0.1".to_string(),
+                        author: "the uci-beyond developers".to_string(),
+                    },
+                    option_block: UciOptionBlockBuilder::default(),
+                    uciok: UciOkCommand,
+                    greeting: Vec::new(),
+                    repeated_options: Vec::new(),
+                };
+                response.write_to(&mut writer).await?;
+            }
+            Ok(AnyGuiCommand::IsReady(_)) => {
+                writer.write_command(&ReadyOkCommand).await?;
+            }
+            Ok(AnyGuiCommand::Quit(_)) => break,
+            Ok(_) => (),
+            Err(e) => eprintln!("Failed to parse the command: {e:?}"),
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    serve(tokio::io::stdin(), tokio::io::stdout()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uci_beyond::{
+        gui_commands::{IsReadyCommand, UciCommand},
+        util::{Connection as _, IoConnection},
+    };
+
+    #[tokio::test]
+    async fn test_echo_engine_over_duplex() {
+        let (gui, engine) = tokio::io::duplex(1024);
+        let (gui_read, gui_write) = tokio::io::split(gui);
+        let (engine_read, engine_write) = tokio::io::split(engine);
+
+        let talk = async {
+            let mut connection = IoConnection::new(gui_read, gui_write);
+            let response = connection.send(UciCommand).await.unwrap().unwrap();
+            assert_eq!(response.id_block.name, "Echo 0.1");
+            assert!(response.option_block.options().is_empty());
+
+            connection.send_raw_line("ucinewgame").await.unwrap();
+            connection.send(IsReadyCommand).await.unwrap().unwrap();
+            assert_eq!(
+                connection.read_raw_line().await.unwrap().as_deref(),
+                Some("readyok")
+            );
+
+            connection.send_raw_line("quit").await.unwrap();
+        };
+        let (served, ()) = futures::future::join(serve(engine_read, engine_write), talk).await;
+        served.unwrap();
+    }
+}
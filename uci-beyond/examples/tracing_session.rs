@@ -0,0 +1,47 @@
+//! Prints the spans of a [`Session`] with their durations, i.e. the `uci` handshake,
+//! every `setoption` and the search.
+//!
+//! Run it with `cargo run --example tracing_session --features tracing -- <path to the engine>`.
+//! The engine defaults to `stockfish`. Raise the maximum level below to `TRACE`
+//! to also see every line received.
+
+use tracing_subscriber::fmt::format::FmtSpan;
+use uci_beyond::{
+    gui_commands::{GoCommand, SetOptionCommand},
+    model::FenString,
+    session::Session,
+    util::IoConnection,
+};
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        // The closing event of a span carries its duration
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
+
+    let engine = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "stockfish".to_string());
+    let connection = IoConnection::spawn(tokio::process::Command::new(engine).kill_on_drop(true))?;
+    let mut session = Session::new(connection);
+
+    if let Err(e) = session.initialize(None).await? {
+        eprintln!("The engine doesn't speak UCI: {e}");
+        return Ok(());
+    }
+    session
+        .configure(vec![
+            SetOptionCommand::Threads { value: 2 },
+            SetOptionCommand::Hash { value: 64 },
+        ])
+        .await?;
+
+    let startpos = FenString::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    session
+        .evaluate_positions([startpos], &GoCommand::for_depth(12))
+        .await
+        .map_err(std::io::Error::other)?;
+    Ok(())
+}
@@ -0,0 +1,318 @@
+//! The module for [`AnalysisCache`], i.e. the results of the searches kept by
+//! [`Session::analyze_cached`](crate::session::Session::analyze_cached), e.g. for stepping back and forth through a game.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    gui_command_responses::SearchSummary,
+    gui_commands::{GoCommand, PositionCommand, SetOptionCommand},
+    model,
+};
+
+/// The default capacity of [`AnalysisCache`].
+pub const DEFAULT_ANALYSIS_CACHE_CAPACITY: usize = 256;
+
+/// The FEN of the start position, so that `position startpos` and the same position given as FEN share the entries.
+const STARTPOS_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Which fields of the FEN [`AnalysisCache`] tells the positions apart by, besides the first four
+/// (the piece placement, the side to move, the castling rights and the en passant square).
+///
+/// The default ignores both move counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FenNormalization {
+    /// Keep the halfmove clock, e.g. when the analysis depends on the fifty-move rule.
+    pub keep_halfmove_clock: bool,
+    /// Keep the fullmove number.
+    pub keep_fullmove_number: bool,
+}
+
+impl FenNormalization {
+    /// Drops the ignored fields and collapses the whitespace between the kept ones.
+    pub fn normalize(&self, fen: &model::FenString) -> String {
+        fen.0
+            .split_whitespace()
+            .enumerate()
+            .filter(|(i, _)| match i {
+                4 => self.keep_halfmove_clock,
+                5 => self.keep_fullmove_number,
+                _ => true,
+            })
+            .map(|(_, field)| field)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// What identifies a search in an [`AnalysisCache`] (see [`AnalysisCache::key`]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnalysisKey {
+    /// The normalized FEN of the root position followed by the moves, if any, e.g. `... moves e2e4`.
+    pub position: String,
+    /// The `go` command, i.e. the limits of the search and the searched moves.
+    pub go: String,
+    /// The last `setoption` command of every option that may change the results, by option name.
+    pub options: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    summary: SearchSummary,
+    last_used: u64,
+}
+
+/// A least recently used cache of [`SearchSummary`]s keyed by [`AnalysisKey`].
+///
+/// The cache follows the options that may change the results of a search, e.g. `MultiPV` or `Skill Level`,
+/// through [`AnalysisCache::observe_setoption`], so changing them makes the following lookups miss.
+/// The options that only change the speed or the output of the search, i.e. `Threads`, `Hash`, `NumaPolicy`,
+/// `Clear Hash`, `Ponder`, `UCI_ShowWDL` and `Debug Log File`, are ignored. Custom options are always followed.
+///
+/// The positions are compared by their root and moves, so a position reached by different moves
+/// is cached separately for each of them. Eviction scans the entries, which is fine for a few thousand of them.
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    capacity: usize,
+    normalization: FenNormalization,
+    options: BTreeMap<String, String>,
+    entries: HashMap<AnalysisKey, Entry>,
+    /// Incremented on every lookup and insertion, for the LRU order.
+    tick: u64,
+}
+
+impl Default for AnalysisCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_ANALYSIS_CACHE_CAPACITY)
+    }
+}
+
+impl AnalysisCache {
+    /// Creates a cache of at most `capacity` searches with the default [`FenNormalization`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "The capacity of the analysis cache must be positive"
+        );
+        Self {
+            capacity,
+            normalization: FenNormalization::default(),
+            options: BTreeMap::new(),
+            entries: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn with_normalization(mut self, normalization: FenNormalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn normalization(&self) -> FenNormalization {
+        self.normalization
+    }
+
+    /// The number of cached searches.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forgets the cached searches, but not the options.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Records the option sent to the engine if it may change the results of a search.
+    pub fn observe_setoption(&mut self, cmd: &SetOptionCommand) {
+        if affects_results(cmd) {
+            self.options.insert(cmd.name().to_string(), cmd.to_string());
+        }
+    }
+
+    /// The key of the search with the options observed so far.
+    pub fn key(&self, position: &PositionCommand, go: &GoCommand) -> AnalysisKey {
+        let mut normalized = match &position.startpos {
+            model::Position::StartPos => self
+                .normalization
+                .normalize(&model::FenString(STARTPOS_FEN.to_string())),
+            model::Position::Fen(fen) => self.normalization.normalize(fen),
+        };
+        if !position.moves.is_empty() {
+            normalized.push_str(&format!(" moves {}", position.moves));
+        }
+        AnalysisKey {
+            position: normalized,
+            go: go.to_string(),
+            options: self.options.clone(),
+        }
+    }
+
+    /// The cached search, if any, which becomes the most recently used one.
+    pub fn get(&mut self, key: &AnalysisKey) -> Option<&SearchSummary> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.tick;
+        Some(&entry.summary)
+    }
+
+    /// Caches the search, evicting the least recently used one if the cache is full.
+    pub fn insert(&mut self, key: AnalysisKey, summary: SearchSummary) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        let entry = Entry {
+            summary,
+            last_used: self.tick,
+        };
+        self.entries.insert(key, entry);
+    }
+}
+
+/// Whether the option may change the results of a search, as opposed to only its speed or its output.
+fn affects_results(cmd: &SetOptionCommand) -> bool {
+    !matches!(
+        cmd,
+        SetOptionCommand::Threads { .. }
+            | SetOptionCommand::Hash { .. }
+            | SetOptionCommand::NumaPolicy { .. }
+            | SetOptionCommand::ClearHash
+            | SetOptionCommand::Ponder { .. }
+            | SetOptionCommand::UCIShowWDL { .. }
+            | SetOptionCommand::DebugLogFile { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(fen: &str) -> PositionCommand {
+        PositionCommand::from_fen(model::FenString(fen.to_string()))
+    }
+
+    fn summary(depth: u32) -> SearchSummary {
+        SearchSummary {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fen_normalization() {
+        let fen = model::FenString::from("8/8/8/8/8/8/8/K1k5  w - - 12 40");
+        assert_eq!(
+            FenNormalization::default().normalize(&fen),
+            "8/8/8/8/8/8/8/K1k5 w - -"
+        );
+        let keep_halfmove_clock = FenNormalization {
+            keep_halfmove_clock: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            keep_halfmove_clock.normalize(&fen),
+            "8/8/8/8/8/8/8/K1k5 w - - 12"
+        );
+    }
+
+    #[test]
+    fn test_hits_ignore_move_counters() {
+        let mut cache = AnalysisCache::new(4);
+        let go = GoCommand::for_depth(10);
+
+        let startpos = PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList::default(),
+        };
+        cache.insert(cache.key(&startpos, &go), summary(10));
+
+        let same = position("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3 7");
+        assert_eq!(cache.get(&cache.key(&same, &go)), Some(&summary(10)));
+
+        // Other limits are another search
+        assert_eq!(
+            cache.get(&cache.key(&same, &GoCommand::for_depth(12))),
+            None
+        );
+
+        let mut cache = cache.with_normalization(FenNormalization {
+            keep_halfmove_clock: true,
+            keep_fullmove_number: true,
+        });
+        assert_eq!(cache.get(&cache.key(&same, &go)), None);
+    }
+
+    #[test]
+    fn test_misses_after_option_changes() {
+        let mut cache = AnalysisCache::default();
+        let root = position("8/8/8/8/8/8/8/K1k5 w - - 0 1");
+        let go = GoCommand::for_depth(10);
+        cache.insert(cache.key(&root, &go), summary(10));
+
+        // The options that don't change the results are ignored
+        cache.observe_setoption(&SetOptionCommand::Threads { value: 8 });
+        cache.observe_setoption(&SetOptionCommand::Hash { value: 256 });
+        assert!(cache.get(&cache.key(&root, &go)).is_some());
+
+        cache.observe_setoption(&SetOptionCommand::MultiPV { value: 3 });
+        assert_eq!(cache.get(&cache.key(&root, &go)), None);
+        cache.insert(cache.key(&root, &go), summary(11));
+
+        cache.observe_setoption(&SetOptionCommand::Custom {
+            name: "Contempt".to_string(),
+            value: Some("10".to_string()),
+        });
+        assert_eq!(cache.get(&cache.key(&root, &go)), None);
+        cache.insert(cache.key(&root, &go), summary(12));
+
+        // Setting an option back hits the search made with the same values
+        cache.observe_setoption(&SetOptionCommand::MultiPV { value: 1 });
+        assert_eq!(cache.get(&cache.key(&root, &go)), None);
+        cache.observe_setoption(&SetOptionCommand::MultiPV { value: 3 });
+        assert_eq!(cache.get(&cache.key(&root, &go)), Some(&summary(12)));
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = AnalysisCache::new(2);
+        let go = GoCommand::for_depth(10);
+        let a = cache.key(&position("8/8/8/8/8/8/8/K1k5 w - - 0 1"), &go);
+        let b = cache.key(&position("8/8/8/8/8/8/8/K2k4 w - - 0 1"), &go);
+        let c = cache.key(&position("8/8/8/8/8/8/8/K3k3 w - - 0 1"), &go);
+
+        cache.insert(a.clone(), summary(1));
+        cache.insert(b.clone(), summary(2));
+        // `a` becomes the most recently used one
+        assert!(cache.get(&a).is_some());
+
+        cache.insert(c.clone(), summary(3));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&b), None);
+        assert_eq!(cache.get(&a), Some(&summary(1)));
+        assert_eq!(cache.get(&c), Some(&summary(3)));
+
+        // Replacing a cached search doesn't evict another one
+        cache.insert(c.clone(), summary(4));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&a), Some(&summary(1)));
+        assert_eq!(cache.get(&c), Some(&summary(4)));
+    }
+}
@@ -0,0 +1,201 @@
+//! The module for [`EngineCapabilities`], i.e. what the engine advertised about itself in response to the `uci` command.
+
+use std::path::Path;
+
+use crate::{
+    engine_commands::{EngineName, IdBlock, NnueNetKind, UciOptionBlockBuilder},
+    engine_quirks::EngineFamily,
+    gui_command_responses::{InfoStringBlock, UciResponse},
+    model,
+    options::UciOption,
+};
+
+/// The identity and the options advertised by the engine in response to the `uci` command.
+#[derive(Debug, Clone)]
+pub struct EngineCapabilities {
+    pub id: IdBlock,
+    pub options: UciOptionBlockBuilder,
+}
+
+impl EngineCapabilities {
+    pub fn name(&self) -> &str {
+        &self.id.name
+    }
+
+    pub fn author(&self) -> &str {
+        &self.id.author
+    }
+
+    /// The family and the version of the engine, see [`IdBlock::parsed_name`].
+    pub fn parsed_name(&self) -> EngineName {
+        self.id.parsed_name()
+    }
+
+    /// The engine family in the builtin quirk database, see [`Quirks::for_id_block`](crate::engine_quirks::Quirks::for_id_block).
+    pub fn family(&self) -> EngineFamily {
+        EngineFamily::from_id_name(&self.parsed_name().family)
+    }
+
+    /// Finds the advertised option by its name. As per the UCI protocol, the name is case-insensitive.
+    pub fn option(&self, name: &str) -> Option<UciOption> {
+        self.options.option(name)
+    }
+
+    /// The largest number of search threads, i.e. the maximum of `Threads`, or `None` if the engine
+    /// doesn't advertise the option, e.g. a minimal engine without any options.
+    pub fn max_threads(&self) -> Option<u32> {
+        self.options.threads.as_ref().map(|spin| spin.max)
+    }
+
+    /// The network file configured with `EvalFile` for [`NnueNetKind::Big`]
+    /// or with `EvalFileSmall` for [`NnueNetKind::Small`], i.e. the advertised default.
+    pub fn configured_net(&self, kind: NnueNetKind) -> Option<&model::UciString> {
+        match kind {
+            NnueNetKind::Big => self.options.eval_file.as_ref(),
+            NnueNetKind::Small => self.options.eval_file_small.as_ref(),
+            NnueNetKind::Unknown => None,
+        }
+    }
+
+    /// The networks reported before a search (see [`InfoStringBlock::nnue_nets`]) that are not
+    /// the configured ones, e.g. because the configured file failed to load and the engine fell back
+    /// to its embedded network.
+    ///
+    /// The networks are compared by their file names, so a configured path matches the reported name.
+    pub fn nnue_net_mismatches(&self, info: &InfoStringBlock) -> Vec<NnueNetMismatch> {
+        info.nnue_nets()
+            .filter_map(|(kind, net)| {
+                let configured = self.configured_net(kind)?;
+                (file_name(&configured.0) != file_name(&net.name)).then(|| NnueNetMismatch {
+                    kind,
+                    configured: configured.0.clone(),
+                    loaded: net.name.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A network reported by the engine that is not the one configured with `EvalFile` or `EvalFileSmall`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NnueNetMismatch {
+    pub kind: NnueNetKind,
+    pub configured: String,
+    pub loaded: String,
+}
+
+/// The file name of the path, e.g. of an `EvalFile`, or the path itself if it has none.
+pub(crate) fn file_name(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path)
+}
+
+impl From<UciResponse> for EngineCapabilities {
+    fn from(response: UciResponse) -> Self {
+        let UciResponse {
+            id_block,
+            option_block,
+            uciok: _,
+            greeting: _,
+            repeated_options: _,
+        } = response;
+
+        EngineCapabilities {
+            id: id_block,
+            options: option_block,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        fixtures::{GO_DEPTH5_TRANSCRIPT, GO_INFINITE_STOPPED_TRANSCRIPT, STOCKFISH_UCI_RESPONSE},
+        gui_command_responses::{GoEvent, GreetingPolicy},
+        util::AsyncReadable,
+    };
+
+    async fn stockfish_capabilities() -> EngineCapabilities {
+        let mut reader = tokio::io::BufReader::new(STOCKFISH_UCI_RESPONSE.as_bytes());
+        UciResponse::read_with(&mut reader, GreetingPolicy::Strict)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+            .into()
+    }
+
+    fn info_string_block(transcript: &str) -> InfoStringBlock {
+        let events = transcript
+            .lines()
+            .map(|line| line.parse::<GoEvent>())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        InfoStringBlock::from_events(&events)
+    }
+
+    #[tokio::test]
+    async fn test_nnue_net_mismatches() {
+        let mut capabilities = stockfish_capabilities().await;
+        assert!(
+            capabilities
+                .nnue_net_mismatches(&info_string_block(GO_DEPTH5_TRANSCRIPT))
+                .is_empty()
+        );
+
+        capabilities.options.eval_file_small =
+            Some(model::UciString::from("/nets/nn-37f18f62d772.nnue"));
+        assert!(
+            capabilities
+                .nnue_net_mismatches(&info_string_block(GO_DEPTH5_TRANSCRIPT))
+                .is_empty()
+        );
+
+        // An older engine loads a single network
+        assert_eq!(
+            capabilities.nnue_net_mismatches(&info_string_block(GO_INFINITE_STOPPED_TRANSCRIPT)),
+            [NnueNetMismatch {
+                kind: NnueNetKind::Big,
+                configured: "nn-1c0000000000.nnue".to_string(),
+                loaded: "nn-ad9b42354671.nnue".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_without_options() {
+        let mut reader = tokio::io::BufReader::new("id name X\nid author Y\nuciok\n".as_bytes());
+        let capabilities: EngineCapabilities = UciResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+            .into();
+
+        assert_eq!(capabilities.name(), "X");
+        assert_eq!(capabilities.max_threads(), None);
+        assert_eq!(capabilities.option("Hash"), None);
+        assert_eq!(capabilities.configured_net(NnueNetKind::Big), None);
+        assert!(
+            capabilities
+                .nnue_net_mismatches(&info_string_block(GO_DEPTH5_TRANSCRIPT))
+                .is_empty()
+        );
+
+        assert_eq!(stockfish_capabilities().await.max_threads(), Some(1024));
+    }
+
+    #[tokio::test]
+    async fn test_parsed_name() {
+        let capabilities = stockfish_capabilities().await;
+        let name = capabilities.parsed_name();
+        assert_eq!(name.family, "Stockfish");
+        assert_eq!(name.version.map(|v| v.to_string()).as_deref(), Some("17.1"));
+        assert_eq!(name.build_meta, None);
+        assert_eq!(capabilities.family(), EngineFamily::Stockfish);
+    }
+}
@@ -1,7 +1,62 @@
+use crate::{
+    engine_commands::{
+        BestMoveCommand, IdCommand, InfoCommand, OptionCommand, ReadyOkCommand, UciOkCommand,
+    },
+    gui_commands::{
+        IsReadyCommand, PonderHitCommand, QuitCommand, SetOptionCommand, StopCommand, UciCommand,
+        UciNewGameCommand,
+    },
+};
+
+/// The names of the commands sent by the engine, i.e. the [`Command::NAME`]s of the
+/// [`engine_commands`](crate::engine_commands), e.g. for dispatching a line with [`command_name`].
+pub const ENGINE_COMMAND_NAMES: &[&str] = &[
+    IdCommand::NAME,
+    OptionCommand::NAME,
+    UciOkCommand::NAME,
+    ReadyOkCommand::NAME,
+    InfoCommand::NAME,
+    BestMoveCommand::NAME,
+];
+
+/// The names of the commands sent by the GUI, i.e. the [`Command::NAME`]s of the
+/// [`gui_commands`](crate::gui_commands) that are parsed, so not `go` and `position` yet.
+pub const GUI_COMMAND_NAMES: &[&str] = &[
+    UciCommand::NAME,
+    IsReadyCommand::NAME,
+    UciNewGameCommand::NAME,
+    SetOptionCommand::NAME,
+    StopCommand::NAME,
+    PonderHitCommand::NAME,
+    QuitCommand::NAME,
+];
+
+/// The name of the command on the line, i.e. its first token, if it is one of the `names`,
+/// e.g. [`ENGINE_COMMAND_NAMES`] or [`GUI_COMMAND_NAMES`].
+///
+/// Unlike a prefix match, `ucinewgame` is not `uci` and `stopped` is no command.
+pub fn command_name(line: &str, names: &[&'static str]) -> Option<&'static str> {
+    let first_token = first_token(line)?;
+    names.iter().copied().find(|name| *name == first_token)
+}
+
+fn first_token(line: &str) -> Option<&str> {
+    line.split_whitespace().next()
+}
+
 pub trait Command {
     type ParsingError;
 
     const NAME: &'static str;
+
+    /// Whether the line is this command, i.e. its first token is [`Command::NAME`],
+    /// e.g. to peek at a line before deciding which reader gets it.
+    ///
+    /// Unlike `line.starts_with(Self::NAME)`, `optionset ...` is not an `option` line.
+    fn matches_line(line: &str) -> bool {
+        first_token(line) == Some(Self::NAME)
+    }
+
     fn parse_cmd_name(s: &str) -> Result<&str, parsing::Error<Self::ParsingError>> {
         if s.is_empty() {
             return Err(parsing::Error::UnexpectedEof);
@@ -20,24 +75,77 @@ pub trait Command {
 }
 
 pub mod parsing {
-    #[derive(thiserror::Error, Debug)]
+    //! The errors of parsing the commands.
+    //!
+    //! The messages are lowercase phrases without a final period that quote the tokens with `'`.
+    //! An error wrapping another one appends its message after `: `, and the expected values follow
+    //! in parentheses, so the message of the whole tree reads as a chain, e.g. with [`Error::in_command`]:
+    //!
+    //! ```text
+    //! failed to parse 'option' command: in type clause: unknown option type 'spinn' (expected one of: spin, string, button, check, combo)
+    //! ```
+
+    use std::fmt::Display;
+
+    use crate::command::Command;
+
+    #[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+    #[non_exhaustive]
     pub enum Error<E> {
-        #[error("Unexpected end of file")]
+        #[error("unexpected end of input")]
         UnexpectedEof,
-        #[error("Unexpected end of tokens")]
+        #[error("unexpected end of tokens")]
         UnexpectedEndOfTokens,
-        #[error("Unexpected command")]
+        #[error("unexpected command '{0}'")]
         UnexpectedCommand(String),
-        #[error("Unexpected format")]
+        #[error("unexpected format")]
         UnexpectedFormat,
         /// This error happens when [`crate::util::handle_next_line`]
-        #[error("Unexpected peek output")]
+        #[error("unexpected peek output")]
         UnexpectedPeekOutput,
-        #[error("Custom parsing error: {0}")]
+        #[error("{0}")]
         CustomError(E),
     }
 
+    /// The [`Error`] of parsing the command with the name, see [`Error::in_command`].
+    #[derive(Debug, Clone, Copy)]
+    pub struct InCommand<'a, E> {
+        name: &'static str,
+        error: &'a Error<E>,
+    }
+
+    impl<E: Display> Display for InCommand<'_, E> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "failed to parse '{}' command: {}", self.name, self.error)
+        }
+    }
+
+    /// Lists the expected values for the messages, e.g. `spin, string, button`.
+    pub(crate) fn one_of<I>(values: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        values
+            .into_iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     impl<E> Error<E> {
+        /// The error as the error of parsing the command `C`, whose name prefixes the message, e.g.
+        /// `failed to parse 'option' command: empty option name`.
+        pub fn in_command<C>(&self) -> InCommand<'_, E>
+        where
+            C: Command<ParsingError = E>,
+        {
+            InCommand {
+                name: C::NAME,
+                error: self,
+            }
+        }
+
         pub fn map_custom<F, O>(self, f: F) -> Error<O>
         where
             F: FnOnce(E) -> O,
@@ -63,3 +171,89 @@ pub mod parsing {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        engine_commands::AnyEngineCommandKind,
+        gui_commands::{AnyGuiCommand, AnyGuiCommandParsingError},
+    };
+
+    fn assert_unique(names: &[&str]) {
+        for (i, name) in names.iter().enumerate() {
+            assert!(!names[i + 1..].contains(name), "`{name}` is repeated");
+        }
+    }
+
+    #[test]
+    fn test_command_names() {
+        assert_unique(ENGINE_COMMAND_NAMES);
+        assert_unique(GUI_COMMAND_NAMES);
+
+        for name in ["id", "option", "uciok", "readyok", "info", "bestmove"] {
+            assert!(ENGINE_COMMAND_NAMES.contains(&name), "`{name}` is missing");
+            assert_ne!(
+                AnyEngineCommandKind::of_line(name),
+                AnyEngineCommandKind::Other
+            );
+        }
+        for name in [
+            "uci",
+            "isready",
+            "ucinewgame",
+            "setoption",
+            "stop",
+            "ponderhit",
+            "quit",
+        ] {
+            assert!(GUI_COMMAND_NAMES.contains(&name), "`{name}` is missing");
+            assert!(!matches!(
+                name.parse::<AnyGuiCommand>(),
+                Ok(AnyGuiCommand::Other(_))
+            ));
+        }
+        assert_eq!(ENGINE_COMMAND_NAMES.len(), 6);
+        assert_eq!(GUI_COMMAND_NAMES.len(), 7);
+    }
+
+    #[test]
+    fn test_prefix_collisions() {
+        assert_eq!(command_name("uci", GUI_COMMAND_NAMES), Some("uci"));
+        assert_eq!(
+            command_name("ucinewgame", GUI_COMMAND_NAMES),
+            Some("ucinewgame")
+        );
+        assert!(!UciCommand::matches_line("ucinewgame"));
+        assert!(UciNewGameCommand::matches_line(" ucinewgame\r\n"));
+        assert_eq!(
+            "ucinewgame".parse::<AnyGuiCommand>().unwrap(),
+            AnyGuiCommand::UciNewGame(UciNewGameCommand)
+        );
+
+        assert_eq!(command_name("stop now", GUI_COMMAND_NAMES), Some("stop"));
+        assert_eq!(command_name("stopped", GUI_COMMAND_NAMES), None);
+        assert!(!StopCommand::matches_line("stopped"));
+        assert_eq!(
+            "stopped".parse::<AnyGuiCommand>().unwrap(),
+            AnyGuiCommand::Other("stopped".to_string())
+        );
+        assert!(matches!(
+            "stop now".parse::<AnyGuiCommand>(),
+            Err(parsing::Error::CustomError(
+                AnyGuiCommandParsingError::NoArgumentsCommandParsingError(_)
+            ))
+        ));
+
+        assert_eq!(command_name("", ENGINE_COMMAND_NAMES), None);
+        assert_eq!(
+            AnyEngineCommandKind::of_line("uciokay"),
+            AnyEngineCommandKind::Other
+        );
+        assert_eq!(
+            AnyEngineCommandKind::of_line("information"),
+            AnyEngineCommandKind::Other
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::{fmt::Display, str::FromStr};
+
+use async_trait::async_trait;
+use kinded::Kinded;
+
+use crate::{
+    command::{self, Command as _},
+    engine_commands::{
+        BestMoveCommand, BestMoveCommandParsingError, IdCommand, IdCommandParsingError,
+        InfoCommand, InfoCommandParsingError, OptionCommand, OptionCommandParsingError,
+        ReadyOkCommand, UciOkCommand,
+    },
+    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
+};
+
+/// Any command sent by the engine, as read by a GUI, e.g. to dispatch the lines of several
+/// commands in flight (see [`Router`](crate::util::Router)).
+#[derive(Debug, Clone, PartialEq, Eq, Kinded)]
+#[kinded(kind = AnyEngineCommandKind)]
+pub enum AnyEngineCommand {
+    Id(IdCommand),
+    Option(OptionCommand),
+    UciOk(UciOkCommand),
+    ReadyOk(ReadyOkCommand),
+    Info(InfoCommand),
+    BestMove(BestMoveCommand),
+    /// A command that isn't parsed yet or an unknown one, e.g. the banner of the engine, as received.
+    Other(String),
+}
+
+impl AnyEngineCommandKind {
+    /// The kind of the command on the line, judging by its name only, so a malformed line
+    /// is still told apart, e.g. `info depth x` is an [`AnyEngineCommandKind::Info`].
+    pub fn of_line(line: &str) -> Self {
+        let line = line.trim();
+        match command::command_name(line, command::ENGINE_COMMAND_NAMES) {
+            Some(IdCommand::NAME) => AnyEngineCommandKind::Id,
+            Some(OptionCommand::NAME) => AnyEngineCommandKind::Option,
+            // Unlike the others, `uciok` and `readyok` take no arguments
+            Some(UciOkCommand::NAME) if line == UciOkCommand::NAME => AnyEngineCommandKind::UciOk,
+            Some(ReadyOkCommand::NAME) if line == ReadyOkCommand::NAME => {
+                AnyEngineCommandKind::ReadyOk
+            }
+            Some(InfoCommand::NAME) => AnyEngineCommandKind::Info,
+            Some(BestMoveCommand::NAME) => AnyEngineCommandKind::BestMove,
+            _ => AnyEngineCommandKind::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum AnyEngineCommandParsingError {
+    #[error("IdCommand parsing error: {0}")]
+    IdCommandParsingError(IdCommandParsingError),
+    #[error("OptionCommand parsing error: {0}")]
+    OptionCommandParsingError(OptionCommandParsingError),
+    #[error("InfoCommand parsing error: {0}")]
+    InfoCommandParsingError(InfoCommandParsingError),
+    #[error("BestMoveCommand parsing error: {0}")]
+    BestMoveCommandParsingError(BestMoveCommandParsingError),
+}
+
+impl Display for AnyEngineCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnyEngineCommand::Id(cmd) => write!(f, "{cmd}"),
+            AnyEngineCommand::Option(cmd) => write!(f, "{cmd}"),
+            AnyEngineCommand::UciOk(cmd) => write!(f, "{cmd}"),
+            AnyEngineCommand::ReadyOk(cmd) => write!(f, "{cmd}"),
+            AnyEngineCommand::Info(cmd) => write!(f, "{cmd}"),
+            AnyEngineCommand::BestMove(cmd) => write!(f, "{cmd}"),
+            AnyEngineCommand::Other(line) => write!(f, "{line}"),
+        }
+    }
+}
+
+impl FromStr for AnyEngineCommand {
+    type Err = command::parsing::Error<AnyEngineCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use AnyEngineCommandParsingError::{
+            BestMoveCommandParsingError, IdCommandParsingError, InfoCommandParsingError,
+            OptionCommandParsingError,
+        };
+
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(command::parsing::Error::UnexpectedEof);
+        }
+
+        match AnyEngineCommandKind::of_line(s) {
+            AnyEngineCommandKind::Id => s
+                .parse()
+                .map(AnyEngineCommand::Id)
+                .map_err(|e| e.map_custom(IdCommandParsingError)),
+            AnyEngineCommandKind::Option => s
+                .parse()
+                .map(AnyEngineCommand::Option)
+                .map_err(|e| e.map_custom(OptionCommandParsingError)),
+            AnyEngineCommandKind::UciOk => Ok(AnyEngineCommand::UciOk(UciOkCommand)),
+            AnyEngineCommandKind::ReadyOk => Ok(AnyEngineCommand::ReadyOk(ReadyOkCommand)),
+            AnyEngineCommandKind::Info => s
+                .parse()
+                .map(AnyEngineCommand::Info)
+                .map_err(|e| e.map_custom(InfoCommandParsingError)),
+            AnyEngineCommandKind::BestMove => s
+                .parse()
+                .map(AnyEngineCommand::BestMove)
+                .map_err(|e| e.map_custom(BestMoveCommandParsingError)),
+            AnyEngineCommandKind::Other => Ok(AnyEngineCommand::Other(s.to_string())),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for AnyEngineCommand {
+    type Err = command::parsing::Error<AnyEngineCommandParsingError>;
+
+    /// Reads the next command, skipping the empty lines.
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        loop {
+            let f = |line: &str| -> LineHandlerOutcome<Option<AnyEngineCommand>, Self::Err> {
+                if line.trim().is_empty() {
+                    return LineHandlerOutcome::Read(None);
+                }
+                match line.parse() {
+                    Ok(cmd) => LineHandlerOutcome::Read(Some(cmd)),
+                    Err(e) => LineHandlerOutcome::Error(e),
+                }
+            };
+
+            match handle_next_line(reader, f).await? {
+                Some(LineHandlerOutcome::Read(Some(cmd))) => return Ok(Some(Ok(cmd))),
+                Some(LineHandlerOutcome::Read(None)) => continue,
+                Some(LineHandlerOutcome::Error(e)) => return Ok(Some(Err(e))),
+                Some(LineHandlerOutcome::Peeked) => {
+                    return command::parsing::Error::UnexpectedPeekOutput.wrap();
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_any_engine_command() {
+        let input = "Stockfish 17.1 by the Stockfish developers (see AUTHORS file)\n\
+            id name Stockfish 17.1\n\
+            \n\
+            option name Hash type spin default 16 min 1 max 33554432\n\
+            uciok\n\
+            readyok\n\
+            info depth 1 seldepth 1 multipv 1 score cp 20 nodes 20 nps 20000 time 1 pv e2e4\n\
+            bestmove\n\
+            bestmove e2e4 ponder e7e5\n";
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+
+        let mut commands = Vec::new();
+        while let Some(cmd) = AnyEngineCommand::read_from(&mut reader).await.unwrap() {
+            commands.push(cmd);
+        }
+
+        let kinds = commands
+            .iter()
+            .map(|cmd| cmd.as_ref().map(AnyEngineCommand::kind).ok())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                Some(AnyEngineCommandKind::Other),
+                Some(AnyEngineCommandKind::Id),
+                Some(AnyEngineCommandKind::Option),
+                Some(AnyEngineCommandKind::UciOk),
+                Some(AnyEngineCommandKind::ReadyOk),
+                Some(AnyEngineCommandKind::Info),
+                None,
+                Some(AnyEngineCommandKind::BestMove),
+            ]
+        );
+        // A malformed line of a known command is an error rather than `Other`
+        assert!(commands[6].is_err());
+        assert_eq!(
+            commands[7].as_ref().unwrap().to_string(),
+            "bestmove e2e4 ponder e7e5"
+        );
+    }
+
+    #[test]
+    fn test_kind_of_malformed_line() {
+        assert_eq!(
+            AnyEngineCommandKind::of_line("bestmove"),
+            AnyEngineCommandKind::BestMove
+        );
+        assert_eq!(
+            AnyEngineCommandKind::of_line("optionset name Hash"),
+            AnyEngineCommandKind::Other
+        );
+        assert_eq!(
+            AnyEngineCommandKind::of_line(" readyok\r"),
+            AnyEngineCommandKind::ReadyOk
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::{fmt::Display, str::FromStr};
+
+use async_trait::async_trait;
+
+use crate::{
+    command::{self, Command as _},
+    model,
+    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
+};
+
+/// <https://backscattering.de/chess/uci/#engine-bestmove>
+///
+/// The engine has stopped searching and found the move `best_move` best in this position.
+/// The engine can send the move it likes to ponder on.
+///
+/// ```text
+/// bestmove e2e4 ponder d7d6
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BestMoveCommand {
+    pub best_move: model::MoveString,
+    pub ponder: Option<model::MoveString>,
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+#[non_exhaustive]
+pub enum BestMoveCommandParsingError {
+    #[error("Expected `ponder` token, found `{0}`.")]
+    PonderTokenExpected(String),
+    #[error("Unexpected trailing tokens: `{0}`.")]
+    UnexpectedTrailingTokens(String),
+}
+
+impl command::Command for BestMoveCommand {
+    type ParsingError = BestMoveCommandParsingError;
+
+    const NAME: &'static str = "bestmove";
+}
+
+impl Display for BestMoveCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bestmove {}", self.best_move)?;
+        if let Some(ponder) = &self.ponder {
+            write!(f, " ponder {ponder}")?;
+        }
+        Ok(())
+    }
+}
+
+impl BestMoveCommand {
+    /// Whether the engine has no move to play, i.e. `bestmove (none)` as sent by Stockfish for
+    /// a checkmate or a stalemate, or the null move `bestmove 0000` of some other engines.
+    pub fn is_none(&self) -> bool {
+        matches!(self.best_move.0.as_str(), "(none)" | "0000")
+    }
+}
+
+impl FromStr for BestMoveCommand {
+    type Err = command::parsing::Error<BestMoveCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = BestMoveCommand::parse_cmd_name(s)?;
+
+        let mut tokens = s.split_whitespace();
+
+        let best_move = tokens
+            .next()
+            .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?;
+
+        let ponder = match tokens.next() {
+            None => None,
+            Some("ponder") => Some(
+                tokens
+                    .next()
+                    .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?,
+            ),
+            Some(token) => {
+                return Err(command::parsing::Error::CustomError(
+                    BestMoveCommandParsingError::PonderTokenExpected(token.to_string()),
+                ));
+            }
+        };
+
+        if let Some(token) = tokens.next() {
+            return Err(command::parsing::Error::CustomError(
+                BestMoveCommandParsingError::UnexpectedTrailingTokens(token.to_string()),
+            ));
+        }
+
+        Ok(BestMoveCommand {
+            best_move: model::MoveString(best_move.to_string()),
+            ponder: ponder.map(model::MoveString::from),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for BestMoveCommand {
+    type Err = command::parsing::Error<BestMoveCommandParsingError>;
+
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let f =
+            |line: &str| -> LineHandlerOutcome<BestMoveCommand, <BestMoveCommand as FromStr>::Err> {
+                match line.parse::<BestMoveCommand>() {
+                    Ok(cmd) => LineHandlerOutcome::Read(cmd),
+                    Err(e) => LineHandlerOutcome::Error(e),
+                }
+            };
+
+        match handle_next_line(reader, f).await? {
+            Some(LineHandlerOutcome::Read(cmd)) => Ok(Some(Ok(cmd))),
+            Some(LineHandlerOutcome::Error(e)) => Ok(Some(Err(e))),
+            Some(LineHandlerOutcome::Peeked) => {
+                return command::parsing::Error::UnexpectedPeekOutput.wrap();
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bestmove_command() {
+        let cmd = "bestmove e2e4 ponder d7d6\n"
+            .parse::<BestMoveCommand>()
+            .expect("Failed to parse BestMoveCommand");
+
+        assert_eq!(cmd.best_move, model::MoveString::from("e2e4"));
+        assert_eq!(cmd.ponder, Some(model::MoveString::from("d7d6")));
+
+        let cmd = "bestmove f1b5"
+            .parse::<BestMoveCommand>()
+            .expect("Failed to parse BestMoveCommand");
+
+        assert_eq!(cmd.best_move, model::MoveString::from("f1b5"));
+        assert_eq!(cmd.ponder, None);
+    }
+
+    #[test]
+    fn test_display_bestmove_command() {
+        let cmd = BestMoveCommand {
+            best_move: model::MoveString::from("e2e4"),
+            ponder: Some(model::MoveString::from("e7e6")),
+        };
+
+        assert_eq!(cmd.to_string(), "bestmove e2e4 ponder e7e6");
+    }
+
+    #[test]
+    fn test_parse_malformed_bestmove_command() {
+        assert!("bestmove".parse::<BestMoveCommand>().is_err());
+        assert!(
+            "bestmove e2e4 pondr d7d6"
+                .parse::<BestMoveCommand>()
+                .is_err()
+        );
+        assert!("bestmove e2e4 ponder".parse::<BestMoveCommand>().is_err());
+        assert!(
+            "bestmove e2e4 ponder d7d6 e2e4"
+                .parse::<BestMoveCommand>()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt::Display;
+
+use crate::engine_commands::IdBlock;
+
+/// The `id name` of the engine split into the family, the version and the rest, e.g.
+/// `Stockfish`, `17.1` and none for `Stockfish 17.1`, or `Lc0`, `0.32.0` and `dev+git.abc123` for `Lc0 v0.32.0-dev+git.abc123`.
+///
+/// See [`IdBlock::parsed_name`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EngineName {
+    /// The words before the version, e.g. `Komodo Dragon`, or the whole name if it has no version.
+    pub family: String,
+    pub version: Option<Version>,
+    /// What follows the version, e.g. `64 POPCNT` of `Stockfish 11 64 POPCNT`, or the build of
+    /// an unversioned development build, e.g. `dev-20240817-abcdef` of `Stockfish dev-20240817-abcdef`.
+    pub build_meta: Option<String>,
+}
+
+/// The version of an engine, with one to three numbers, e.g. `17`, `17.1` or `0.31.2`.
+///
+/// The versions are ordered by the numbers, with a missing number before any other, so `17 < 17.0 < 17.1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    pub major: u64,
+    pub minor: Option<u64>,
+    pub patch: Option<u64>,
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.major)?;
+        if let Some(minor) = self.minor {
+            write!(f, ".{minor}")?;
+        }
+        if let Some(patch) = self.patch {
+            write!(f, ".{patch}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Version {
+    /// Reads the version at the start of the token, with an optional `v`, e.g. `v0.31.2-rc1`,
+    /// and returns the rest of the token, e.g. `-rc1`.
+    fn parse_prefix(token: &str) -> Option<(Version, &str)> {
+        let digits = token.strip_prefix(['v', 'V']).unwrap_or(token);
+        let mut numbers = [None; 3];
+        let mut rest = digits;
+        for (i, number) in numbers.iter_mut().enumerate() {
+            let tail = if i == 0 {
+                rest
+            } else {
+                match rest.strip_prefix('.') {
+                    Some(tail) if tail.starts_with(|c: char| c.is_ascii_digit()) => tail,
+                    _ => break,
+                }
+            };
+            let len = tail
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(tail.len());
+            *number = Some(tail[..len].parse().ok()?);
+            rest = &tail[len..];
+        }
+        let [Some(major), minor, patch] = numbers else {
+            return None;
+        };
+        Some((
+            Version {
+                major,
+                minor,
+                patch,
+            },
+            rest,
+        ))
+    }
+}
+
+impl EngineName {
+    /// Splits the name at the first word after the family that starts with a version, e.g. `17.1` or `v0.31.2`,
+    /// or with `dev`, as in the development builds of Stockfish.
+    ///
+    /// It never fails: a name without either is the family as a whole.
+    pub fn parse(name: &str) -> Self {
+        let words = name.split_whitespace().collect::<Vec<_>>();
+        for (i, word) in words.iter().enumerate().skip(1) {
+            let family = words[..i].join(" ");
+            let rest = &words[i + 1..];
+            if let Some((version, suffix)) = Version::parse_prefix(word) {
+                let suffix = suffix.trim_start_matches(['-', '+', '_', '.']);
+                let build_meta = std::iter::once(suffix)
+                    .chain(rest.iter().copied())
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                return EngineName {
+                    family,
+                    version: Some(version),
+                    build_meta: (!build_meta.is_empty()).then_some(build_meta),
+                };
+            }
+            if is_dev_build(word) {
+                return EngineName {
+                    family,
+                    version: None,
+                    build_meta: Some(words[i..].join(" ")),
+                };
+            }
+        }
+        EngineName {
+            family: name.trim().to_string(),
+            version: None,
+            build_meta: None,
+        }
+    }
+}
+
+/// `dev`, `dev-20240817-abcdef` or `dev_...`, but not e.g. `Devil`.
+fn is_dev_build(word: &str) -> bool {
+    word.get(..3)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("dev"))
+        && !word[3..].starts_with(|c: char| c.is_ascii_alphabetic())
+}
+
+impl IdBlock {
+    /// The `id name` split into the family, the version and the rest. See [`EngineName::parse`].
+    pub fn parsed_name(&self) -> EngineName {
+        EngineName::parse(&self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(major: u64, minor: Option<u64>, patch: Option<u64>) -> Option<Version> {
+        Some(Version {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    #[test]
+    fn test_parse_engine_names() {
+        let cases = [
+            (
+                "Stockfish 17.1",
+                "Stockfish",
+                version(17, Some(1), None),
+                None,
+            ),
+            ("Stockfish 16", "Stockfish", version(16, None, None), None),
+            (
+                "Stockfish dev-20240817-abcdef",
+                "Stockfish",
+                None,
+                Some("dev-20240817-abcdef"),
+            ),
+            (
+                "Stockfish 11 64 POPCNT",
+                "Stockfish",
+                version(11, None, None),
+                Some("64 POPCNT"),
+            ),
+            ("Lc0 v0.31.2", "Lc0", version(0, Some(31), Some(2)), None),
+            (
+                "Lc0 v0.32.0-dev+git.abc123",
+                "Lc0",
+                version(0, Some(32), Some(0)),
+                Some("dev+git.abc123"),
+            ),
+            (
+                "Komodo Dragon 3.3",
+                "Komodo Dragon",
+                version(3, Some(3), None),
+                None,
+            ),
+            (
+                "Ethereal 14.25 (PEXT)",
+                "Ethereal",
+                version(14, Some(25), None),
+                Some("(PEXT)"),
+            ),
+            ("Berserk 13", "Berserk", version(13, None, None), None),
+            (
+                "Arasan 24.2.2",
+                "Arasan",
+                version(24, Some(2), Some(2)),
+                None,
+            ),
+            ("Seer v2.8.0", "Seer", version(2, Some(8), Some(0)), None),
+            (
+                "RubiChess 20240817",
+                "RubiChess",
+                version(20240817, None, None),
+                None,
+            ),
+            (
+                "Obsidian 13.0",
+                "Obsidian",
+                version(13, Some(0), None),
+                None,
+            ),
+            ("Weiss 2.0a", "Weiss", version(2, Some(0), None), Some("a")),
+            ("Leela Chess Zero", "Leela Chess Zero", None, None),
+            ("MyEngine", "MyEngine", None, None),
+            ("", "", None, None),
+        ];
+
+        for (name, family, version, build_meta) in cases {
+            assert_eq!(
+                EngineName::parse(name),
+                EngineName {
+                    family: family.to_string(),
+                    version,
+                    build_meta: build_meta.map(str::to_string),
+                },
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_version_order_and_display() {
+        let v17 = version(17, None, None).unwrap();
+        let v17_0 = version(17, Some(0), None).unwrap();
+        let v17_1 = version(17, Some(1), None).unwrap();
+        assert!(v17 < v17_0 && v17_0 < v17_1);
+        assert!(version(9, Some(99), None).unwrap() < v17);
+
+        assert_eq!(v17.to_string(), "17");
+        assert_eq!(version(0, Some(31), Some(2)).unwrap().to_string(), "0.31.2");
+    }
+
+    #[test]
+    fn test_parse_versions_of_odd_words() {
+        // The first word is always the family, even if it looks like a version
+        assert_eq!(EngineName::parse("4ku 2.0").family, "4ku");
+        // Devil is a name rather than a development build
+        assert_eq!(EngineName::parse("Devil Chess").family, "Devil Chess");
+        // A number too big for a version
+        assert_eq!(
+            EngineName::parse("Engine 99999999999999999999").version,
+            None
+        );
+        assert_eq!(
+            EngineName::parse("Engine 1.2.3.4").build_meta.as_deref(),
+            Some("4")
+        );
+    }
+}
@@ -7,16 +7,17 @@ use variants_data_struct::VariantsDataStruct;
 
 use crate::command;
 use crate::command::Command;
-use crate::util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line};
-
-// TODO: reimplement parsing using better abstractions
+use crate::util::{
+    AsyncReadable, LineHandlerOutcome, ReadBlockError, StreamingLineReader, handle_next_line,
+    read_block,
+};
 
 /// <https://backscattering.de/chess/uci/#engine-id>
-#[derive(VariantsDataStruct, Debug, Kinded)]
+#[derive(VariantsDataStruct, Debug, Clone, PartialEq, Eq, Kinded)]
 #[variants_data_struct(
     name=IdBlock,
     attrs(
-        #[derive(Debug)]
+        #[derive(Debug, PartialEq, Eq, Clone)]
         #[optional_struct]
         /// The block of [`IdCommand`]s sent by the engine to identify itself.
     )
@@ -33,18 +34,27 @@ pub enum IdCommand {
     Author(String),
 }
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum IdCommandParsingError {
     #[error("Wrong field: `{0}`.")]
     WrongField(String),
+    /// The field is present but has no value, e.g. `id name`.
+    #[error("Empty value of the field: {0}.")]
+    EmptyValue(IdCommandKind),
 }
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum IdBlockParsingError {
     #[error("Command error: {0:?}")]
     CommandError(#[from] command::parsing::Error<IdCommandParsingError>),
-    #[error("Repeated field: {0}")]
-    RepeatedField(IdCommandKind),
+    #[error("Repeated field: {field} (first: `{first}`, second: `{second}`)")]
+    RepeatedField {
+        field: IdCommandKind,
+        first: String,
+        second: String,
+    },
     #[error("Incomplete IdBlock")]
     IncompleteBlock,
 }
@@ -70,24 +80,42 @@ impl std::fmt::Display for IdCommand {
     }
 }
 
+impl std::fmt::Display for IdBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", IdCommand::Name(self.name.clone()))?;
+        write!(f, "{}", IdCommand::Author(self.author.clone()))
+    }
+}
+
 impl FromStr for IdCommand {
     type Err = command::parsing::Error<IdCommandParsingError>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = IdCommand::parse_cmd_name(s)?;
+        let s = s.trim_end_matches(['\r', '\n']);
+
+        // The value is the rest of the line verbatim, e.g. `My name  is Bond ` or `author unknown`
+        let (field, value) = s.split_once(' ').unwrap_or((s, ""));
+
+        let kind = match field {
+            "name" => IdCommandKind::Name,
+            "author" => IdCommandKind::Author,
+            _ => {
+                return Err(command::parsing::Error::CustomError(
+                    IdCommandParsingError::WrongField(field.to_string()),
+                ));
+            }
+        };
 
-        let (field, value) = s
-            .split_once(' ')
-            .ok_or(command::parsing::Error::UnexpectedFormat)?;
-
-        let value = value.trim_end();
+        if value.is_empty() {
+            return Err(command::parsing::Error::CustomError(
+                IdCommandParsingError::EmptyValue(kind),
+            ));
+        }
 
-        match field {
-            "name" => Ok(IdCommand::Name(value.to_string())),
-            "author" => Ok(IdCommand::Author(value.to_string())),
-            _ => Err(command::parsing::Error::CustomError(
-                IdCommandParsingError::WrongField(field.to_string()),
-            )),
+        match kind {
+            IdCommandKind::Name => Ok(IdCommand::Name(value.to_string())),
+            IdCommandKind::Author => Ok(IdCommand::Author(value.to_string())),
         }
     }
 }
@@ -127,51 +155,43 @@ impl AsyncReadable for IdBlock {
     where
         R: StreamingLineReader,
     {
-        let mut opt_id_block = OptionalIdBlock::default();
-        let mut i = 0;
-
-        loop {
-            let cmd: Option<Result<IdCommand, <IdCommand as FromStr>::Err>> =
-                IdCommand::read_from(reader).await?;
-
-            let Some(cmd) = cmd else {
-                if i == 0 {
-                    // No IdCommands were read; return None
-                    return Ok(None);
-                }
-                // EOF reached before completing the block
-                return IdBlockParsingError::IncompleteBlock.wrap();
-            };
-
-            let cmd = match cmd {
-                Ok(cmd) => cmd,
-                Err(e) => {
-                    return IdBlockParsingError::from(e).wrap();
+        let fold = |b: &mut OptionalIdBlock, cmd: IdCommand| match cmd {
+            IdCommand::Name(name) => {
+                if let Some(first) = &b.name {
+                    return Err(IdBlockParsingError::RepeatedField {
+                        field: IdCommandKind::Name,
+                        first: first.clone(),
+                        second: name,
+                    });
                 }
-            };
-
-            match cmd {
-                IdCommand::Name(name) => {
-                    if opt_id_block.name.is_some() {
-                        return IdBlockParsingError::RepeatedField(IdCommandKind::Name).wrap();
-                    }
-                    opt_id_block.name = Some(name);
-                }
-                IdCommand::Author(author) => {
-                    if opt_id_block.author.is_some() {
-                        return IdBlockParsingError::RepeatedField(IdCommandKind::Author).wrap();
-                    }
-                    opt_id_block.author = Some(author);
+                b.name = Some(name);
+                Ok(())
+            }
+            IdCommand::Author(author) => {
+                if let Some(first) = &b.author {
+                    return Err(IdBlockParsingError::RepeatedField {
+                        field: IdCommandKind::Author,
+                        first: first.clone(),
+                        second: author,
+                    });
                 }
+                b.author = Some(author);
+                Ok(())
             }
+        };
 
-            // When OptionalIdBlock becomes complete, convert it to IdBlock.
-            opt_id_block = match opt_id_block.try_into() {
-                Ok(id_block) => return Ok(Some(Ok(id_block))),
-                Err(e) => e,
-            };
+        let opt_id_block = match read_block(reader, fold).await? {
+            Some(Ok(opt_id_block)) => opt_id_block,
+            Some(Err(ReadBlockError::Parsing(e))) => return IdBlockParsingError::from(e).wrap(),
+            Some(Err(ReadBlockError::Fold(e))) => return e.wrap(),
+            None => return Ok(None),
+        };
 
-            i += 1;
+        let id_block: Result<IdBlock, _> = opt_id_block.try_into();
+        match id_block {
+            Ok(id_block) => Ok(Some(Ok(id_block))),
+            // The block ended before all fields were read
+            Err(_) => IdBlockParsingError::IncompleteBlock.wrap(),
         }
     }
 }
@@ -252,4 +272,222 @@ mod tests {
             } if name == "Stockfish 17.1" && author == "the Stockfish developers (see AUTHORS file)"
         );
     }
+
+    #[tokio::test]
+    async fn test_id_block_leaves_the_next_line() {
+        let input = "id name Stockfish 17.1\n\
+               id author the Stockfish developers (see AUTHORS file)\n\
+               \n\
+               option name Threads type spin default 1 min 1 max 1024\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        IdBlock::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        let next_line = crate::util::read_line(&mut reader).await.unwrap();
+        assert_eq!(next_line.as_deref(), Some(""));
+    }
+
+    #[tokio::test]
+    async fn test_id_block_errors() {
+        use assert_matches::assert_matches;
+
+        async fn read(
+            input: &str,
+        ) -> Option<Result<IdBlock, command::parsing::Error<IdBlockParsingError>>> {
+            let mut reader = tokio::io::BufReader::new(input.as_bytes());
+            IdBlock::read_from(&mut reader).await.unwrap()
+        }
+
+        assert_matches!(read("").await, None);
+        assert_matches!(read("uciok\n").await, None);
+        assert_matches!(
+            read("id name A\nid name B\n").await,
+            Some(Err(command::parsing::Error::CustomError(
+                IdBlockParsingError::RepeatedField {
+                    field: IdCommandKind::Name,
+                    first,
+                    second,
+                }
+            ))) if first == "A" && second == "B"
+        );
+        assert_matches!(
+            read("id name A\n").await,
+            Some(Err(command::parsing::Error::CustomError(
+                IdBlockParsingError::IncompleteBlock
+            )))
+        );
+        assert_matches!(
+            read("id name A\n\nid author B\n").await,
+            Some(Err(command::parsing::Error::CustomError(
+                IdBlockParsingError::IncompleteBlock
+            )))
+        );
+        assert_matches!(
+            read("id version 1\n").await,
+            Some(Err(command::parsing::Error::CustomError(
+                IdBlockParsingError::CommandError(_)
+            )))
+        );
+    }
+
+    #[test]
+    fn test_parse_id_command_values_verbatim() {
+        use assert_matches::assert_matches;
+
+        assert_matches!(
+            "id name My name is Bond".parse::<IdCommand>(),
+            Ok(IdCommand::Name(name)) if name == "My name is Bond"
+        );
+        assert_matches!(
+            "id name Fake  Engine  2.0 \n".parse::<IdCommand>(),
+            Ok(IdCommand::Name(name)) if name == "Fake  Engine  2.0 "
+        );
+        assert_matches!(
+            "id author author unknown".parse::<IdCommand>(),
+            Ok(IdCommand::Author(author)) if author == "author unknown"
+        );
+        assert_matches!(
+            "id name author of the engine".parse::<IdCommand>(),
+            Ok(IdCommand::Name(name)) if name == "author of the engine"
+        );
+
+        let name = IdCommand::Name("Fake  Engine".to_string());
+        assert_matches!(
+            name.to_string().parse::<IdCommand>(),
+            Ok(IdCommand::Name(parsed)) if parsed == "Fake  Engine"
+        );
+    }
+
+    #[test]
+    fn test_parse_id_command_empty_value() {
+        use assert_matches::assert_matches;
+
+        for line in ["id name", "id name ", "id name\r\n"] {
+            assert_matches!(
+                line.parse::<IdCommand>(),
+                Err(command::parsing::Error::CustomError(
+                    IdCommandParsingError::EmptyValue(IdCommandKind::Name)
+                )),
+                "{line:?}"
+            );
+        }
+        assert_matches!(
+            "id author".parse::<IdCommand>(),
+            Err(command::parsing::Error::CustomError(
+                IdCommandParsingError::EmptyValue(IdCommandKind::Author)
+            ))
+        );
+        assert_matches!(
+            "id version".parse::<IdCommand>(),
+            Err(command::parsing::Error::CustomError(
+                IdCommandParsingError::WrongField(field)
+            )) if field == "version"
+        );
+    }
+
+    /// The reader of [`IdBlock`] before it was ported onto [`read_block`], kept as the reference of the port.
+    async fn legacy_read_id_block<R>(
+        reader: &mut R,
+    ) -> Result<Option<Result<IdBlock, command::parsing::Error<IdBlockParsingError>>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let mut opt_id_block = OptionalIdBlock::default();
+        let mut i = 0;
+
+        loop {
+            let Some(cmd) = IdCommand::read_from(reader).await? else {
+                if i == 0 {
+                    return Ok(None);
+                }
+                return IdBlockParsingError::IncompleteBlock.wrap();
+            };
+            let cmd = match cmd {
+                Ok(cmd) => cmd,
+                Err(e) => return IdBlockParsingError::from(e).wrap(),
+            };
+
+            match cmd {
+                IdCommand::Name(name) => {
+                    if let Some(first) = &opt_id_block.name {
+                        return IdBlockParsingError::RepeatedField {
+                            field: IdCommandKind::Name,
+                            first: first.clone(),
+                            second: name,
+                        }
+                        .wrap();
+                    }
+                    opt_id_block.name = Some(name);
+                }
+                IdCommand::Author(author) => {
+                    if let Some(first) = &opt_id_block.author {
+                        return IdBlockParsingError::RepeatedField {
+                            field: IdCommandKind::Author,
+                            first: first.clone(),
+                            second: author,
+                        }
+                        .wrap();
+                    }
+                    opt_id_block.author = Some(author);
+                }
+            }
+
+            opt_id_block = match opt_id_block.try_into() {
+                Ok(id_block) => return Ok(Some(Ok(id_block))),
+                Err(e) => e,
+            };
+            i += 1;
+        }
+    }
+
+    /// The outcomes of the ported and of the legacy reader: the read block and the line left for the next reader.
+    async fn outcomes(input: &str) -> [(String, Option<String>); 2] {
+        use crate::util::read_line;
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let ported = IdBlock::read_from(&mut reader).await.unwrap();
+        let ported = (format!("{ported:?}"), read_line(&mut reader).await.unwrap());
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let legacy = legacy_read_id_block(&mut reader).await.unwrap();
+        let legacy = (format!("{legacy:?}"), read_line(&mut reader).await.unwrap());
+
+        [ported, legacy]
+    }
+
+    #[tokio::test]
+    async fn test_ported_id_block_reader_is_equivalent() {
+        use crate::fixtures::{LC0_UCI_RESPONSE, STOCKFISH_UCI_RESPONSE};
+
+        for input in [
+            STOCKFISH_UCI_RESPONSE,
+            LC0_UCI_RESPONSE,
+            "",
+            "id author B\nid name A\n",
+            "id name A\n",
+            "id name A\nid name B\n",
+            "id name A\nid version 1\n",
+        ] {
+            let [ported, legacy] = outcomes(input).await;
+            assert_eq!(ported, legacy, "{input:?}");
+        }
+
+        // The port differs on purpose where the block is followed by another command: its line ends the block
+        // and is left for the next reader rather than consumed as a malformed `id`
+        for input in ["uciok\n", "id name A\nuciok\n"] {
+            let [ported, legacy] = outcomes(input).await;
+            assert_eq!(ported.1.as_deref(), Some("uciok"), "{input:?}");
+            assert!(legacy.0.contains("CommandError"), "{input:?}");
+            assert_eq!(legacy.1, None, "{input:?}");
+        }
+        // ... and the block goes on after both fields, so a repeated field isn't left for the next reader
+        let [ported, legacy] = outcomes("id name A\nid author B\nid name C\n").await;
+        assert!(ported.0.contains("RepeatedField"));
+        assert!(legacy.0.starts_with("Some(Ok("));
+        assert_eq!(legacy.1.as_deref(), Some("id name C"));
+    }
 }
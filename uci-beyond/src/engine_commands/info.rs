@@ -1,19 +1,40 @@
-use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
+use std::{fmt::Display, str::FromStr};
 
-use crate::command;
+use crate::{command, model};
 
+/// <https://backscattering.de/chess/uci/#engine-info>
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InfoCommand {
+    /// ```text
+    /// info string NNUE evaluation using nn-ad9b42354671.nnue enabled
+    /// ```
     String(String),
+    /// ```text
+    /// info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 6666 hashfull 0 tbhits 0 time 3 pv e2e4
+    /// ```
     Depth(DepthInfoCommand),
+    /// ```text
+    /// info refutation d1h5 g6h5
+    /// ```
+    Refutation(RefutationInfoCommand),
+    /// ```text
+    /// info currline 1 e2e4 e7e5
+    /// ```
+    CurrLine(CurrLineInfoCommand),
 }
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Clone)]
+#[non_exhaustive]
 pub enum InfoCommandParsingError {
     #[error("Unexpected token. Expected `{expected}`, found `{found}`.")]
     UnexpectedToken {
         expected: &'static str,
         found: String,
     },
+    #[error("Invalid value for `{key}`: `{found}`.")]
+    InvalidValue { key: &'static str, found: String },
+    #[error("Score parsing error: {0}")]
+    ScoreParsingError(model::ScoreParsingError),
 }
 
 impl command::Command for InfoCommand {
@@ -22,6 +43,162 @@ impl command::Command for InfoCommand {
     const NAME: &'static str = "info";
 }
 
+impl Display for InfoCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InfoCommand::String(s) => write!(f, "info string {s}"),
+            InfoCommand::Depth(depth_info) => write!(f, "{depth_info}"),
+            InfoCommand::Refutation(refutation) => write!(f, "{refutation}"),
+            InfoCommand::CurrLine(currline) => write!(f, "{currline}"),
+        }
+    }
+}
+
+impl FromStr for InfoCommand {
+    type Err = command::parsing::Error<InfoCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use crate::command::Command as _;
+
+        let rest = InfoCommand::parse_cmd_name(s)?.trim_start();
+
+        match rest.split_whitespace().next() {
+            Some("string") => {
+                let text = rest["string".len()..].trim();
+                Ok(InfoCommand::String(text.to_string()))
+            }
+            // Only a line of its own is a refutation or a current line, otherwise the line reports the search
+            // and the token is kept among the unknown ones (see `DepthInfoCommand::refutation`)
+            Some("refutation") => Ok(InfoCommand::Refutation(s.parse()?)),
+            Some("currline") => Ok(InfoCommand::CurrLine(s.parse()?)),
+            _ => Ok(InfoCommand::Depth(s.parse()?)),
+        }
+    }
+}
+
+/// The move refuted by a line, sent by the engines with `UCI_ShowRefutations`, e.g. that `Qh5` is refuted by `gxh5`:
+///
+/// ```text
+/// info refutation d1h5 g6h5
+/// ```
+///
+/// The refutation line is empty if the engine found no refutation for the move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefutationInfoCommand {
+    pub refuted_move: model::MoveString,
+    pub refutation_line: Vec<model::MoveString>,
+}
+
+impl RefutationInfoCommand {
+    /// Parses the value of the `refutation` token, i.e. the refuted move followed by the refutation line.
+    fn parse_value(value: &str) -> Result<Self, command::parsing::Error<InfoCommandParsingError>> {
+        let mut moves = value.split_whitespace().map(model::MoveString::from);
+        let refuted_move = moves
+            .next()
+            .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?;
+        Ok(RefutationInfoCommand {
+            refuted_move,
+            refutation_line: moves.collect(),
+        })
+    }
+}
+
+impl Display for RefutationInfoCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "info refutation {}", self.refuted_move)?;
+        for m in &self.refutation_line {
+            write!(f, " {m}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RefutationInfoCommand {
+    type Err = command::parsing::Error<InfoCommandParsingError>;
+
+    /// Parses a line of its own, whose moves extend to the end of the line.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use crate::command::Command as _;
+
+        let rest = InfoCommand::parse_cmd_name(s)?.trim_start();
+        let value = strip_token(rest, "refutation")?;
+        RefutationInfoCommand::parse_value(value)
+    }
+}
+
+/// The line the engine is calculating, sent by the engines with `UCI_ShowCurrLine`, e.g.
+///
+/// ```text
+/// info currline 1 e2e4 e7e5
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrLineInfoCommand {
+    /// The number of the CPU calculating the line, starting from 1. A single-CPU engine may omit it.
+    pub cpu: Option<u32>,
+    pub line: Vec<model::MoveString>,
+}
+
+impl CurrLineInfoCommand {
+    /// Parses the value of the `currline` token, i.e. the optional CPU number followed by the line.
+    fn parse_value(value: &str) -> Self {
+        let mut tokens = value.split_whitespace().peekable();
+        // A move is never a number, so a leading number is the CPU
+        let cpu = tokens.next_if(|token| token.parse::<u32>().is_ok());
+        CurrLineInfoCommand {
+            cpu: cpu.and_then(|cpu| cpu.parse().ok()),
+            line: tokens.map(model::MoveString::from).collect(),
+        }
+    }
+}
+
+impl Display for CurrLineInfoCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "info currline")?;
+        if let Some(cpu) = self.cpu {
+            write!(f, " {cpu}")?;
+        }
+        for m in &self.line {
+            write!(f, " {m}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CurrLineInfoCommand {
+    type Err = command::parsing::Error<InfoCommandParsingError>;
+
+    /// Parses a line of its own, whose moves extend to the end of the line.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use crate::command::Command as _;
+
+        let rest = InfoCommand::parse_cmd_name(s)?.trim_start();
+        let value = strip_token(rest, "currline")?;
+        Ok(CurrLineInfoCommand::parse_value(value))
+    }
+}
+
+/// Strips the expected first token of `s`, returning the rest.
+fn strip_token<'a>(
+    s: &'a str,
+    expected: &'static str,
+) -> Result<&'a str, command::parsing::Error<InfoCommandParsingError>> {
+    let token = s
+        .split_whitespace()
+        .next()
+        .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?;
+    if token != expected {
+        return Err(command::parsing::Error::CustomError(
+            InfoCommandParsingError::UnexpectedToken {
+                expected,
+                found: token.to_string(),
+            },
+        ));
+    }
+    Ok(s[token.len()..].trim())
+}
+
+/// The free-text `info string` lines, some of which are recognized and parsed into structured data.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StringInfoCommand {
     /// ```text
     /// info string Available processors: 0-7
@@ -31,7 +208,51 @@ pub enum StringInfoCommand {
     /// info string Using 1 thread
     /// ```
     UsingThreads(UsingThreadsInfoCommand),
+    /// ```text
+    /// info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))
+    /// ```
     NnueEvaluation(NnueEvaluationInfoCommand),
+    /// Any other text, e.g.
+    ///
+    /// ```text
+    /// info string Failed to open tablebases
+    /// ```
+    Other(String),
+}
+
+impl Display for StringInfoCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StringInfoCommand::AvailableProcessors(cmd) => write!(f, "{cmd}"),
+            StringInfoCommand::UsingThreads(cmd) => write!(f, "{cmd}"),
+            StringInfoCommand::NnueEvaluation(cmd) => write!(f, "{cmd}"),
+            StringInfoCommand::Other(text) => write!(f, "info string {text}"),
+        }
+    }
+}
+
+impl FromStr for StringInfoCommand {
+    type Err = command::parsing::Error<InfoCommandParsingError>;
+
+    /// Tries the structured parsers first and falls back to [`StringInfoCommand::Other`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(cmd) = s.parse::<AvailableProcessorsInfoCommand>() {
+            return Ok(StringInfoCommand::AvailableProcessors(cmd));
+        }
+        if let Ok(cmd) = s.parse::<UsingThreadsInfoCommand>() {
+            return Ok(StringInfoCommand::UsingThreads(cmd));
+        }
+        if let Ok(cmd) = s.parse::<NnueEvaluationInfoCommand>() {
+            return Ok(StringInfoCommand::NnueEvaluation(cmd));
+        }
+
+        match s.parse::<InfoCommand>()? {
+            InfoCommand::String(text) => Ok(StringInfoCommand::Other(text)),
+            InfoCommand::Depth(_) | InfoCommand::Refutation(_) | InfoCommand::CurrLine(_) => {
+                Err(command::parsing::Error::UnexpectedFormat)
+            }
+        }
+    }
 }
 
 /// Metadata about an NNUE network used in evaluation. Appears in
@@ -39,10 +260,70 @@ pub enum StringInfoCommand {
 /// ```text
 /// info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))
 /// ```
+///
+/// Older versions of Stockfish print
+///
+/// ```text
+/// info string NNUE evaluation using nn-ad9b42354671.nnue enabled
+/// ```
+///
+/// and some builds omit the architecture, e.g.
+///
+/// ```text
+/// info string NNUE evaluation using nn-1c0000000000.nnue (133MiB)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NnueEvaluationInfoCommand {
+    /// The file name of the network. May contain spaces.
     pub name: String,
-    pub size_mib: u32,
-    pub architecture: NnueNetworkArchitecture,
+    pub size_mib: Option<u32>,
+    /// Only present if `size_mib` is present.
+    pub architecture: Option<NnueNetworkArchitecture>,
+    /// Whether the line ends with `enabled`.
+    pub enabled: bool,
+}
+
+/// Which of the networks of the engine an [`NnueEvaluationInfoCommand`] reports.
+///
+/// Since Stockfish 16.1, the engine loads a big and a small network, configured with the
+/// `EvalFile` and `EvalFileSmall` options respectively, and reports both before a search.
+/// Older engines load a single network, configured with `EvalFile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NnueNetKind {
+    /// The network configured with `EvalFile`.
+    Big,
+    /// The network configured with `EvalFileSmall`.
+    Small,
+    /// The engine reported more networks than it is known to load.
+    Unknown,
+}
+
+impl NnueEvaluationInfoCommand {
+    /// Classifies the networks reported before a search, in the order they were reported.
+    ///
+    /// A single network is the big one. Of two networks, the one with more hidden neurons
+    /// (or, without the architectures, the larger one) is the big one, and if that is unknown,
+    /// the first one is, as Stockfish reports the big network first.
+    pub fn classify(evaluations: &[&NnueEvaluationInfoCommand]) -> Vec<NnueNetKind> {
+        match evaluations {
+            [_] => vec![NnueNetKind::Big],
+            [first, second] if first.is_smaller_than(second) => {
+                vec![NnueNetKind::Small, NnueNetKind::Big]
+            }
+            [_, _] => vec![NnueNetKind::Big, NnueNetKind::Small],
+            _ => vec![NnueNetKind::Unknown; evaluations.len()],
+        }
+    }
+
+    fn is_smaller_than(&self, other: &NnueEvaluationInfoCommand) -> bool {
+        match (&self.architecture, &other.architecture) {
+            (Some(this), Some(other)) => this.hidden_neurons < other.hidden_neurons,
+            _ => matches!(
+                (self.size_mib, other.size_mib),
+                (Some(this), Some(other)) if this < other
+            ),
+        }
+    }
 }
 
 /// The structure representing the architecture of an NNUE network,
@@ -51,6 +332,7 @@ pub struct NnueEvaluationInfoCommand {
 /// ```text
 /// info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NnueNetworkArchitecture {
     /// number of input features (material + piece–square features)
     pub inputs_features: u32,
@@ -62,6 +344,7 @@ pub struct NnueNetworkArchitecture {
 
 /// Dimensions of the output head of an NNUE network,
 /// i.e. the last three numbers in [`NnueNetworkArchitecture`].
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NnueNetowrkHeadDimensions(pub [u32; 3]);
 
 /// The
@@ -85,6 +368,7 @@ pub struct NnueNetowrkHeadDimensions(pub [u32; 3]);
 /// info depth 5 seldepth 7 multipv 1 score cp 58 nodes 609 nps 87000 hashfull 0 tbhits 0 time 7 pv e2e4
 /// bestmove e2e4 ponder d7d6
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UsingThreadsInfoCommand {
     pub threads: u32,
 }
@@ -100,21 +384,85 @@ impl Display for UsingThreadsInfoCommand {
     }
 }
 
+impl FromStr for UsingThreadsInfoCommand {
+    type Err = command::parsing::Error<InfoCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use crate::command::Command as _;
+
+        let s = InfoCommand::parse_cmd_name(s)?;
+        let mut tokens = s.split_whitespace();
+
+        for expected in ["string", "Using"] {
+            let token = tokens
+                .next()
+                .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?;
+            if token != expected {
+                return Err(command::parsing::Error::CustomError(
+                    InfoCommandParsingError::UnexpectedToken {
+                        expected,
+                        found: token.to_string(),
+                    },
+                ));
+            }
+        }
+
+        let threads_str = tokens
+            .next()
+            .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?;
+        let threads = threads_str.parse::<u32>().map_err(|_| {
+            command::parsing::Error::CustomError(InfoCommandParsingError::InvalidValue {
+                key: "threads",
+                found: threads_str.to_string(),
+            })
+        })?;
+
+        match tokens.next() {
+            Some("thread" | "threads") => {}
+            Some(token) => {
+                return Err(command::parsing::Error::CustomError(
+                    InfoCommandParsingError::UnexpectedToken {
+                        expected: "threads",
+                        found: token.to_string(),
+                    },
+                ));
+            }
+            None => return Err(command::parsing::Error::UnexpectedEndOfTokens),
+        }
+
+        if tokens.next().is_some() {
+            return Err(command::parsing::Error::UnexpectedFormat);
+        }
+
+        Ok(UsingThreadsInfoCommand { threads })
+    }
+}
+
 /// ```text
 /// info string Available processors: 0-7
 /// ```
+///
+/// On multi-socket or restricted-affinity systems, the processors can be listed as
+/// a comma-separated list of indices and ranges, e.g.
+///
+/// ```text
+/// info string Available processors: 0-15,32-47
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AvailableProcessorsInfoCommand {
-    pub processors: RangeInclusive<u32>,
+    pub processors: model::CpuSet,
+}
+
+impl AvailableProcessorsInfoCommand {
+    /// The total number of available processors.
+    pub fn total(&self) -> u32 {
+        self.processors.total()
+    }
 }
 
 impl Display for AvailableProcessorsInfoCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "info string Available processors: {}-{}",
-            self.processors.start(),
-            self.processors.end()
-        )
+        write!(f, "info string Available processors: {}", self.processors)
     }
 }
 
@@ -173,33 +521,31 @@ impl FromStr for AvailableProcessorsInfoCommand {
 
         let s = s[processors_token.len()..].trim_start();
 
-        let (beg, end) = s
-            .split_once('-')
-            .ok_or(command::parsing::Error::UnexpectedFormat)?;
-
-        let beg = beg
-            .trim_end()
-            .parse::<u32>()
-            .map_err(|_| command::parsing::Error::UnexpectedFormat)?;
-
-        let end = end
-            .trim()
-            .parse::<u32>()
-            .map_err(|_| command::parsing::Error::UnexpectedFormat)?;
+        let processors = s.parse::<model::CpuSet>().map_err(|_| {
+            command::parsing::Error::CustomError(InfoCommandParsingError::InvalidValue {
+                key: "processors",
+                found: s.trim_end().to_string(),
+            })
+        })?;
 
-        Ok(AvailableProcessorsInfoCommand {
-            processors: beg..=end,
-        })
+        Ok(AvailableProcessorsInfoCommand { processors })
     }
 }
 
 impl Display for NnueEvaluationInfoCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "info string NNUE evaluation using {} ({}MiB, {})",
-            self.name, self.size_mib, self.architecture
-        )
+        write!(f, "info string NNUE evaluation using {}", self.name)?;
+        if let Some(size_mib) = self.size_mib {
+            write!(f, " ({size_mib}MiB")?;
+            if let Some(architecture) = &self.architecture {
+                write!(f, ", {architecture}")?;
+            }
+            write!(f, ")")?;
+        }
+        if self.enabled {
+            write!(f, " enabled")?;
+        }
+        Ok(())
     }
 }
 
@@ -274,60 +620,70 @@ impl FromStr for NnueEvaluationInfoCommand {
 
         let s = s[using_token.len()..].trim_start();
 
-        // Parse the filename (e.g., "nn-1c0000000000.nnue")
-        let name_end = s
-            .find(' ')
-            .ok_or(command::parsing::Error::UnexpectedFormat)?;
-        let name = s[..name_end].to_string();
-
-        let s = s[name_end..].trim_start();
-
-        // Parse "(133MiB, (22528, 3072, 15, 32, 1))"
-        if !s.starts_with('(') {
-            return Err(command::parsing::Error::UnexpectedFormat);
-        }
+        // The file name may contain spaces, so the line is parsed from the right.
+        let mut s = s.trim_end();
 
-        let s = &s[1..];
-
-        // Parse size "133MiB"
-        let size_end = s
-            .find("MiB")
-            .ok_or(command::parsing::Error::UnexpectedFormat)?;
-        let size_mib = s[..size_end]
-            .trim()
-            .parse::<u32>()
-            .map_err(|_| command::parsing::Error::UnexpectedFormat)?;
-
-        let s = &s[size_end + 3..].trim_start();
-
-        // Expect comma
-        if !s.starts_with(',') {
-            return Err(command::parsing::Error::UnexpectedFormat);
-        }
+        let enabled = match s.strip_suffix("enabled") {
+            Some(rest) if rest.ends_with(char::is_whitespace) => {
+                s = rest.trim_end();
+                true
+            }
+            _ => false,
+        };
 
-        let s = s[1..].trim_start();
+        let (name, size_mib, architecture) = match s.strip_suffix(')') {
+            Some(inner) => {
+                // Find the opening parenthesis matching the last closing one,
+                // e.g. the first one in "(133MiB, (22528, 3072, 15, 32, 1))"
+                let mut depth = 1;
+                let open_paren = inner
+                    .char_indices()
+                    .rev()
+                    .find(|&(_, c)| {
+                        match c {
+                            ')' => depth += 1,
+                            '(' => depth -= 1,
+                            _ => {}
+                        }
+                        depth == 0
+                    })
+                    .map(|(i, _)| i)
+                    .ok_or(command::parsing::Error::UnexpectedFormat)?;
+
+                let name = inner[..open_paren].trim_end();
+                let inner = &inner[open_paren + 1..];
+
+                // Parse "133MiB" and, optionally, ", (22528, 3072, 15, 32, 1)"
+                let (size_str, architecture_str) = match inner.split_once(',') {
+                    Some((size_str, architecture_str)) => (size_str, Some(architecture_str)),
+                    None => (inner, None),
+                };
+
+                let size_mib = size_str
+                    .trim()
+                    .strip_suffix("MiB")
+                    .ok_or(command::parsing::Error::UnexpectedFormat)?
+                    .parse::<u32>()
+                    .map_err(|_| command::parsing::Error::UnexpectedFormat)?;
+
+                let architecture = architecture_str
+                    .map(|s| s.trim().parse::<NnueNetworkArchitecture>())
+                    .transpose()?;
+
+                (name, Some(size_mib), architecture)
+            }
+            None => (s, None, None),
+        };
 
-        // Parse architecture "(22528, 3072, 15, 32, 1))"
-        // Find the extent of the architecture substring (including outer closing paren)
-        if !s.starts_with('(') {
-            return Err(command::parsing::Error::UnexpectedFormat);
+        if name.is_empty() {
+            return Err(command::parsing::Error::UnexpectedEndOfTokens);
         }
 
-        // Find the first closing parenthesis (end of the architecture tuple)
-        let close_paren = s
-            .find(')')
-            .ok_or(command::parsing::Error::UnexpectedFormat)?;
-
-        // Extract substring for architecture parsing: "(22528, 3072, 15, 32, 1))"
-        // Include both closing parens (one for tuple, one for outer context)
-        let arch_slice = &s[..=close_paren];
-
-        let architecture = arch_slice.parse::<NnueNetworkArchitecture>()?;
-
         Ok(NnueEvaluationInfoCommand {
-            name,
+            name: name.to_string(),
             size_mib,
             architecture,
+            enabled,
         })
     }
 }
@@ -399,8 +755,353 @@ impl Display for NnueNetowrkHeadDimensions {
     }
 }
 
-/// TODO: Implement parsing for info command responses.
-pub struct DepthInfoCommand;
+/// The `info` command carrying search statistics, e.g.
+///
+/// ```text
+/// info depth 5 seldepth 7 multipv 1 score cp 58 nodes 609 nps 87000 hashfull 0 tbhits 0 time 7 pv e2e4
+/// ```
+///
+/// Every field is optional because engines are free to send any subset of them.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DepthInfoCommand {
+    /// search depth in plies
+    pub depth: Option<u32>,
+    /// selective search depth in plies
+    pub seldepth: Option<u32>,
+    /// the number of the principal variation in multi PV mode, starting from 1
+    pub multipv: Option<u32>,
+    /// the score from the engine's point of view
+    pub score: Option<model::Score>,
+    /// the number of nodes searched
+    pub nodes: Option<u64>,
+    /// the number of nodes searched per second
+    pub nps: Option<u64>,
+    /// the hash is x permill full
+    pub hashfull: Option<u32>,
+    /// the number of positions found in the endgame tablebases
+    pub tbhits: Option<u64>,
+    /// the time searched in ms
+    pub time: Option<u64>,
+    /// the move currently being searched
+    pub currmove: Option<model::MoveString>,
+    /// the number of the move currently being searched, starting from 1
+    pub currmovenumber: Option<u32>,
+    /// the best line found
+    pub pv: Vec<model::MoveString>,
+    /// The tokens that are not modeled by this struct together with their values, in order of appearance, e.g.
+    /// `("refutation", Some("d1h5 g6h5"))` or `("lowerbound", None)`.
+    ///
+    /// See [`InfoTokenArity`] for how the extent of their values is determined.
+    pub unknown_tokens: Vec<(String, Option<String>)>,
+    /// The free text of the trailing `string` token, i.e. the rest of the line after it, e.g.
+    /// `TB hit rate low` of `info depth 10 score cp 30 string TB hit rate low`.
+    ///
+    /// Unlike the `info string ...` lines (see [`InfoCommand::String`]), the line also reports the search.
+    pub string: Option<String>,
+}
+
+/// The number of values following a token of the `info` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoTokenArity {
+    /// The token is followed by exactly this many values, e.g. `wdl 958 41 1`.
+    /// Flags such as `lowerbound` take zero values.
+    Fixed(usize),
+    /// The token is followed by the values up to the next known token, e.g. `refutation d1h5 g6h5`.
+    ///
+    /// This is also the heuristic used for tokens that are not known at all.
+    UntilNextToken,
+    /// The token is followed by the rest of the line, e.g. `string`.
+    ToEndOfLine,
+}
+
+/// The tokens of the `info` command known to the parser.
+///
+/// <https://backscattering.de/chess/uci/#engine-info>
+pub const KNOWN_INFO_TOKENS: &[(&str, InfoTokenArity)] = &[
+    ("depth", InfoTokenArity::Fixed(1)),
+    ("seldepth", InfoTokenArity::Fixed(1)),
+    ("time", InfoTokenArity::Fixed(1)),
+    ("nodes", InfoTokenArity::Fixed(1)),
+    ("pv", InfoTokenArity::UntilNextToken),
+    ("multipv", InfoTokenArity::Fixed(1)),
+    ("score", InfoTokenArity::Fixed(2)),
+    ("lowerbound", InfoTokenArity::Fixed(0)),
+    ("upperbound", InfoTokenArity::Fixed(0)),
+    ("wdl", InfoTokenArity::Fixed(3)),
+    ("currmove", InfoTokenArity::Fixed(1)),
+    ("currmovenumber", InfoTokenArity::Fixed(1)),
+    ("hashfull", InfoTokenArity::Fixed(1)),
+    ("nps", InfoTokenArity::Fixed(1)),
+    ("tbhits", InfoTokenArity::Fixed(1)),
+    ("sbhits", InfoTokenArity::Fixed(1)),
+    ("cpuload", InfoTokenArity::Fixed(1)),
+    ("string", InfoTokenArity::ToEndOfLine),
+    ("refutation", InfoTokenArity::UntilNextToken),
+    ("currline", InfoTokenArity::UntilNextToken),
+];
+
+impl InfoTokenArity {
+    /// Returns the arity of a known `info` token or `None` if the token is unknown.
+    pub fn of(token: &str) -> Option<Self> {
+        KNOWN_INFO_TOKENS
+            .iter()
+            .find(|(known, _)| *known == token)
+            .map(|(_, arity)| *arity)
+    }
+
+    /// Splits `s` into the value of a token with this arity and the rest.
+    ///
+    /// The returned value is empty if the token takes no values.
+    fn split_value(
+        self,
+        s: &str,
+    ) -> Result<(&str, &str), command::parsing::Error<InfoCommandParsingError>> {
+        debug_assert_eq!(s, s.trim_start());
+
+        let end = match self {
+            InfoTokenArity::Fixed(n) => {
+                let mut end = 0;
+                for _ in 0..n {
+                    let rest = &s[end..];
+                    let trimmed = rest.trim_start();
+                    let token = trimmed
+                        .split_whitespace()
+                        .next()
+                        .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?;
+                    end += rest.len() - trimmed.len() + token.len();
+                }
+                end
+            }
+            InfoTokenArity::UntilNextToken => {
+                let mut end = 0;
+                loop {
+                    let rest = &s[end..];
+                    let trimmed = rest.trim_start();
+                    let Some(token) = trimmed.split_whitespace().next() else {
+                        break;
+                    };
+                    if InfoTokenArity::of(token).is_some() {
+                        break;
+                    }
+                    end += rest.len() - trimmed.len() + token.len();
+                }
+                end
+            }
+            InfoTokenArity::ToEndOfLine => s.len(),
+        };
+
+        Ok((s[..end].trim_end(), s[end..].trim_start()))
+    }
+}
+
+impl DepthInfoCommand {
+    /// The `refutation` embedded in the line among the [`unknown_tokens`](Self::unknown_tokens), if any, e.g.
+    /// `info depth 3 refutation d1h5 g6h5 nodes 100`. Its moves extend to the next known token.
+    pub fn refutation(&self) -> Option<RefutationInfoCommand> {
+        self.unknown_token("refutation")
+            .and_then(|value| RefutationInfoCommand::parse_value(value).ok())
+    }
+
+    /// The `currline` embedded in the line among the [`unknown_tokens`](Self::unknown_tokens), if any, e.g.
+    /// `info depth 3 currline 1 e2e4 e7e5 nodes 100`. Its moves extend to the next known token.
+    pub fn currline(&self) -> Option<CurrLineInfoCommand> {
+        self.unknown_token("currline")
+            .map(CurrLineInfoCommand::parse_value)
+    }
+
+    fn unknown_token(&self, name: &str) -> Option<&str> {
+        self.unknown_tokens
+            .iter()
+            .find(|(token, _)| token == name)
+            .map(|(_, value)| value.as_deref().unwrap_or_default())
+    }
+
+    /// The principal variation in SAN, played from the position the engine searched.
+    ///
+    /// Fails with [`model::SanConversionError::IllegalMove`] if the line is stale for the position.
+    #[cfg(feature = "board")]
+    pub fn pv_to_san(
+        &self,
+        root: &crate::gui_commands::PositionCommand,
+    ) -> Result<Vec<String>, model::SanConversionError> {
+        root.to_san(&self.pv)
+    }
+
+    fn parse_value<T: FromStr>(
+        key: &'static str,
+        s: &str,
+    ) -> Result<(T, &str), command::parsing::Error<InfoCommandParsingError>> {
+        debug_assert_eq!(s, s.trim_start());
+
+        let value_str = s
+            .split_whitespace()
+            .next()
+            .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?;
+
+        let value = value_str.parse::<T>().map_err(|_| {
+            command::parsing::Error::CustomError(InfoCommandParsingError::InvalidValue {
+                key,
+                found: value_str.to_string(),
+            })
+        })?;
+
+        Ok((value, s[value_str.len()..].trim_start()))
+    }
+}
+
+impl Display for DepthInfoCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "info")?;
+        if let Some(depth) = self.depth {
+            write!(f, " depth {depth}")?;
+        }
+        if let Some(seldepth) = self.seldepth {
+            write!(f, " seldepth {seldepth}")?;
+        }
+        if let Some(multipv) = self.multipv {
+            write!(f, " multipv {multipv}")?;
+        }
+        if let Some(score) = self.score {
+            write!(f, " score {score}")?;
+        }
+        if let Some(nodes) = self.nodes {
+            write!(f, " nodes {nodes}")?;
+        }
+        if let Some(nps) = self.nps {
+            write!(f, " nps {nps}")?;
+        }
+        if let Some(hashfull) = self.hashfull {
+            write!(f, " hashfull {hashfull}")?;
+        }
+        if let Some(tbhits) = self.tbhits {
+            write!(f, " tbhits {tbhits}")?;
+        }
+        if let Some(time) = self.time {
+            write!(f, " time {time}")?;
+        }
+        if let Some(currmove) = &self.currmove {
+            write!(f, " currmove {currmove}")?;
+        }
+        if let Some(currmovenumber) = self.currmovenumber {
+            write!(f, " currmovenumber {currmovenumber}")?;
+        }
+        if !self.pv.is_empty() {
+            write!(f, " pv")?;
+            for m in &self.pv {
+                write!(f, " {m}")?;
+            }
+        }
+        for (token, value) in &self.unknown_tokens {
+            write!(f, " {token}")?;
+            if let Some(value) = value {
+                write!(f, " {value}")?;
+            }
+        }
+        // The text extends to the end of the line, so it must be the last token
+        if let Some(string) = &self.string {
+            write!(f, " string")?;
+            if !string.is_empty() {
+                write!(f, " {string}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DepthInfoCommand {
+    type Err = command::parsing::Error<InfoCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use crate::command::Command as _;
+
+        let mut s = InfoCommand::parse_cmd_name(s)?.trim_start();
+        let mut cmd = DepthInfoCommand::default();
+
+        while let Some(token) = s.split_whitespace().next() {
+            s = s[token.len()..].trim_start();
+
+            match token {
+                "depth" => {
+                    let (depth, rest) = Self::parse_value("depth", s)?;
+                    cmd.depth = Some(depth);
+                    s = rest;
+                }
+                "seldepth" => {
+                    let (seldepth, rest) = Self::parse_value("seldepth", s)?;
+                    cmd.seldepth = Some(seldepth);
+                    s = rest;
+                }
+                "multipv" => {
+                    let (multipv, rest) = Self::parse_value("multipv", s)?;
+                    cmd.multipv = Some(multipv);
+                    s = rest;
+                }
+                "score" => {
+                    let (score, rest) = model::Score::parse(s)
+                        .map_err(|e| e.map_custom(InfoCommandParsingError::ScoreParsingError))?;
+                    cmd.score = Some(score);
+                    s = rest;
+                }
+                "nodes" => {
+                    let (nodes, rest) = Self::parse_value("nodes", s)?;
+                    cmd.nodes = Some(nodes);
+                    s = rest;
+                }
+                "nps" => {
+                    let (nps, rest) = Self::parse_value("nps", s)?;
+                    cmd.nps = Some(nps);
+                    s = rest;
+                }
+                "hashfull" => {
+                    let (hashfull, rest) = Self::parse_value("hashfull", s)?;
+                    cmd.hashfull = Some(hashfull);
+                    s = rest;
+                }
+                "tbhits" => {
+                    let (tbhits, rest) = Self::parse_value("tbhits", s)?;
+                    cmd.tbhits = Some(tbhits);
+                    s = rest;
+                }
+                "time" => {
+                    let (time, rest) = Self::parse_value("time", s)?;
+                    cmd.time = Some(time);
+                    s = rest;
+                }
+                "currmove" => {
+                    let (currmove, rest) = Self::parse_value::<String>("currmove", s)?;
+                    cmd.currmove = Some(model::MoveString(currmove));
+                    s = rest;
+                }
+                "currmovenumber" => {
+                    let (currmovenumber, rest) = Self::parse_value("currmovenumber", s)?;
+                    cmd.currmovenumber = Some(currmovenumber);
+                    s = rest;
+                }
+                "pv" => {
+                    // The principal variation extends to the next known token.
+                    let (pv, rest) = InfoTokenArity::UntilNextToken.split_value(s)?;
+                    cmd.pv = pv.split_whitespace().map(model::MoveString::from).collect();
+                    s = rest;
+                }
+                "string" => {
+                    // The text extends to the end of the line, whatever tokens it contains.
+                    let (text, rest) = InfoTokenArity::ToEndOfLine.split_value(s)?;
+                    cmd.string = Some(text.to_string());
+                    s = rest;
+                }
+                _ => {
+                    let arity = InfoTokenArity::of(token).unwrap_or(InfoTokenArity::UntilNextToken);
+                    let (value, rest) = arity.split_value(s)?;
+                    let value = (!value.is_empty()).then(|| value.to_string());
+                    cmd.unknown_tokens.push((token.to_string(), value));
+                    s = rest;
+                }
+            }
+        }
+
+        Ok(cmd)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -413,18 +1114,47 @@ mod tests {
             .parse::<AvailableProcessorsInfoCommand>()
             .expect("Failed to parse AvailableProcessorsInfoCommand");
 
-        assert_eq!(*cmd.processors.start(), 0);
-        assert_eq!(*cmd.processors.end(), 7);
+        assert_eq!(cmd.processors, model::CpuSet(vec![0..=7]));
+        assert_eq!(cmd.total(), 8);
     }
 
     #[test]
     fn test_display_available_processors_info_command() {
-        let cmd = AvailableProcessorsInfoCommand { processors: 0..=7 };
+        let cmd = AvailableProcessorsInfoCommand {
+            processors: model::CpuSet(vec![0..=7]),
+        };
 
         let s = format!("{cmd}");
         assert_eq!(s, "info string Available processors: 0-7");
     }
 
+    #[test]
+    fn test_parse_multi_range_available_processors_info_command() {
+        let s = "info string Available processors: 0-15,32-47";
+        let cmd = s
+            .parse::<AvailableProcessorsInfoCommand>()
+            .expect("Failed to parse AvailableProcessorsInfoCommand");
+
+        assert_eq!(cmd.processors, model::CpuSet(vec![0..=15, 32..=47]));
+        assert_eq!(cmd.total(), 32);
+        assert_eq!(cmd.to_string(), s);
+
+        let s = "info string Available processors: 0,2,4-7";
+        let cmd = s
+            .parse::<AvailableProcessorsInfoCommand>()
+            .expect("Failed to parse AvailableProcessorsInfoCommand");
+
+        assert_eq!(cmd.processors, model::CpuSet(vec![0..=0, 2..=2, 4..=7]));
+        assert_eq!(cmd.total(), 6);
+        assert_eq!(cmd.to_string(), s);
+
+        assert!(
+            "info string Available processors: 0-x"
+                .parse::<AvailableProcessorsInfoCommand>()
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_parse_nnue_evaluation_info_command() {
         let s = "info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))";
@@ -433,22 +1163,61 @@ mod tests {
             .expect("Failed to parse NnueEvaluationInfoCommand");
 
         assert_eq!(cmd.name, "nn-1c0000000000.nnue");
-        assert_eq!(cmd.size_mib, 133);
-        assert_eq!(cmd.architecture.inputs_features, 22528);
-        assert_eq!(cmd.architecture.hidden_neurons, 3072);
-        assert_eq!(cmd.architecture.additional_layers_dimensions.0, [15, 32, 1]);
+        assert_eq!(cmd.size_mib, Some(133));
+        let architecture = cmd.architecture.expect("Expected architecture");
+        assert_eq!(architecture.inputs_features, 22528);
+        assert_eq!(architecture.hidden_neurons, 3072);
+        assert_eq!(architecture.additional_layers_dimensions.0, [15, 32, 1]);
+        assert!(!cmd.enabled);
+    }
+
+    #[test]
+    fn test_parse_nnue_evaluation_info_command_with_enabled_suffix() {
+        let s = "info string NNUE evaluation using nn-ad9b42354671.nnue enabled";
+        let cmd = s
+            .parse::<NnueEvaluationInfoCommand>()
+            .expect("Failed to parse NnueEvaluationInfoCommand");
+
+        assert_eq!(cmd.name, "nn-ad9b42354671.nnue");
+        assert_eq!(cmd.size_mib, None);
+        assert!(cmd.architecture.is_none());
+        assert!(cmd.enabled);
+        assert_eq!(cmd.to_string(), s);
+    }
+
+    #[test]
+    fn test_parse_nnue_evaluation_info_command_without_architecture() {
+        let s = "info string NNUE evaluation using my networks/big net.nnue (133MiB)";
+        let cmd = s
+            .parse::<NnueEvaluationInfoCommand>()
+            .expect("Failed to parse NnueEvaluationInfoCommand");
+
+        assert_eq!(cmd.name, "my networks/big net.nnue");
+        assert_eq!(cmd.size_mib, Some(133));
+        assert!(cmd.architecture.is_none());
+        assert!(!cmd.enabled);
+        assert_eq!(cmd.to_string(), s);
+
+        let s = "info string NNUE evaluation using big net.nnue (6MiB, (22528, 128, 15, 32, 1))";
+        let cmd = s
+            .parse::<NnueEvaluationInfoCommand>()
+            .expect("Failed to parse NnueEvaluationInfoCommand");
+
+        assert_eq!(cmd.name, "big net.nnue");
+        assert_eq!(cmd.to_string(), s);
     }
 
     #[test]
     fn test_display_nnue_evaluation_info_command() {
         let cmd = NnueEvaluationInfoCommand {
             name: "nn-1c0000000000.nnue".to_string(),
-            size_mib: 133,
-            architecture: NnueNetworkArchitecture {
+            size_mib: Some(133),
+            architecture: Some(NnueNetworkArchitecture {
                 inputs_features: 22528,
                 hidden_neurons: 3072,
                 additional_layers_dimensions: NnueNetowrkHeadDimensions([15, 32, 1]),
-            },
+            }),
+            enabled: false,
         };
 
         let s = format!("{cmd}");
@@ -481,4 +1250,240 @@ mod tests {
         let s = format!("{arch}");
         assert_eq!(s, "(22528, 3072, 15, 32, 1)");
     }
+
+    #[test]
+    fn test_parse_refutation_info_command() {
+        // The example of the UCI specification: `Qh5` is refuted by `gxh5`
+        let line = "info refutation d1h5 g6h5";
+        let cmd = line.parse::<InfoCommand>().unwrap();
+        assert_eq!(
+            cmd,
+            InfoCommand::Refutation(RefutationInfoCommand {
+                refuted_move: model::MoveString::from("d1h5"),
+                refutation_line: vec![model::MoveString::from("g6h5")],
+            })
+        );
+        assert_eq!(cmd.to_string(), line);
+
+        // No refutation found, and the moves extend to the end of the line whatever they look like
+        let cmd = "info refutation d1h5"
+            .parse::<RefutationInfoCommand>()
+            .unwrap();
+        assert!(cmd.refutation_line.is_empty());
+        let cmd = "info refutation e2e4 e7e5 depth 3"
+            .parse::<RefutationInfoCommand>()
+            .unwrap();
+        assert_eq!(cmd.refutation_line.len(), 3);
+
+        assert!("info refutation".parse::<InfoCommand>().is_err());
+        assert!(matches!(
+            "info currline e2e4".parse::<RefutationInfoCommand>(),
+            Err(command::parsing::Error::CustomError(
+                InfoCommandParsingError::UnexpectedToken {
+                    expected: "refutation",
+                    ..
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_parse_currline_line() {
+        // The example of the UCI specification, with and without the CPU number
+        let line = "info currline 1 e2e4 e7e5";
+        let cmd = line.parse::<InfoCommand>().unwrap();
+        assert_eq!(
+            cmd,
+            InfoCommand::CurrLine(CurrLineInfoCommand {
+                cpu: Some(1),
+                line: vec![
+                    model::MoveString::from("e2e4"),
+                    model::MoveString::from("e7e5")
+                ],
+            })
+        );
+        assert_eq!(cmd.to_string(), line);
+
+        let line = "info currline e2e4 e7e5";
+        let cmd = line.parse::<CurrLineInfoCommand>().unwrap();
+        assert_eq!(cmd.cpu, None);
+        assert_eq!(cmd.line.len(), 2);
+        assert_eq!(cmd.to_string(), line);
+    }
+
+    #[test]
+    fn test_parse_currline_info_command() {
+        let cmd = "info depth 3 currline 1 e2e4 e7e5 nodes 100"
+            .parse::<InfoCommand>()
+            .expect("Failed to parse InfoCommand");
+        let InfoCommand::Depth(cmd) = cmd else {
+            panic!("Expected InfoCommand::Depth, got {cmd:?}");
+        };
+
+        assert_eq!(cmd.depth, Some(3));
+        assert_eq!(cmd.nodes, Some(100));
+        assert_eq!(
+            cmd.unknown_tokens,
+            vec![("currline".to_string(), Some("1 e2e4 e7e5".to_string()))]
+        );
+        // Embedded mid-line, the moves end at the next known token
+        assert_eq!(
+            cmd.currline(),
+            Some(CurrLineInfoCommand {
+                cpu: Some(1),
+                line: vec![
+                    model::MoveString::from("e2e4"),
+                    model::MoveString::from("e7e5")
+                ],
+            })
+        );
+        assert_eq!(cmd.refutation(), None);
+
+        let cmd: DepthInfoCommand = "info depth 3 refutation d1h5 g6h5 score cp 20"
+            .parse()
+            .unwrap();
+        assert_eq!(cmd.score, Some(model::Score::Cp(20)));
+        assert_eq!(
+            cmd.refutation().unwrap().refutation_line,
+            [model::MoveString::from("g6h5")]
+        );
+    }
+
+    #[test]
+    fn test_parse_bound_and_wdl_info_command() {
+        let cmd = "info depth 10 score cp 35 lowerbound wdl 120 850 30 nodes 5000 pv e2e4 e7e5"
+            .parse::<InfoCommand>()
+            .expect("Failed to parse InfoCommand");
+        let InfoCommand::Depth(cmd) = cmd else {
+            panic!("Expected InfoCommand::Depth, got {cmd:?}");
+        };
+
+        assert_eq!(cmd.score, Some(model::Score::Cp(35)));
+        assert_eq!(cmd.nodes, Some(5000));
+        assert_eq!(cmd.pv.len(), 2);
+        assert_eq!(
+            cmd.unknown_tokens,
+            vec![
+                ("lowerbound".to_string(), None),
+                ("wdl".to_string(), Some("120 850 30".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_vendor_specific_info_command() {
+        let cmd = "info depth 7 ebf 1.87 tbhits 0 pv e2e4 hashfull 12"
+            .parse::<InfoCommand>()
+            .expect("Failed to parse InfoCommand");
+        let InfoCommand::Depth(cmd) = cmd else {
+            panic!("Expected InfoCommand::Depth, got {cmd:?}");
+        };
+
+        assert_eq!(cmd.depth, Some(7));
+        assert_eq!(cmd.tbhits, Some(0));
+        assert_eq!(cmd.pv, vec![model::MoveString::from("e2e4")]);
+        assert_eq!(cmd.hashfull, Some(12));
+        assert_eq!(
+            cmd.unknown_tokens,
+            vec![("ebf".to_string(), Some("1.87".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_display_unknown_info_tokens() {
+        let line = "info depth 4 score cp 20 pv e2e4 upperbound refutation d1h5 g6h5";
+        let cmd = line
+            .parse::<InfoCommand>()
+            .expect("Failed to parse InfoCommand");
+
+        assert_eq!(cmd.to_string(), line);
+    }
+
+    #[test]
+    fn test_parse_trailing_string_token() {
+        let line = "info depth 10 score cp 30 string TB hit rate low";
+        let InfoCommand::Depth(cmd) = line.parse::<InfoCommand>().unwrap() else {
+            panic!("Expected InfoCommand::Depth");
+        };
+        assert_eq!(cmd.depth, Some(10));
+        assert_eq!(cmd.score, Some(model::Score::Cp(30)));
+        assert_eq!(cmd.string.as_deref(), Some("TB hit rate low"));
+        assert!(cmd.unknown_tokens.is_empty());
+        assert_eq!(cmd.to_string(), line);
+
+        // The known tokens in the text aren't parsed, and the principal variation stops before it
+        let InfoCommand::Depth(cmd) = "info depth 12 pv e2e4 e7e5 string depth 3  nodes  pv a  b"
+            .parse::<InfoCommand>()
+            .unwrap()
+        else {
+            panic!("Expected InfoCommand::Depth");
+        };
+        assert_eq!(cmd.depth, Some(12));
+        assert_eq!(cmd.nodes, None);
+        assert_eq!(cmd.pv.len(), 2);
+        assert_eq!(cmd.string.as_deref(), Some("depth 3  nodes  pv a  b"));
+
+        // The text is written after the unknown tokens
+        let cmd = DepthInfoCommand {
+            depth: Some(1),
+            string: Some("hello".to_string()),
+            unknown_tokens: vec![("lowerbound".to_string(), None)],
+            ..Default::default()
+        };
+        assert_eq!(cmd.to_string(), "info depth 1 lowerbound string hello");
+
+        let InfoCommand::Depth(cmd) = "info depth 1 string".parse::<InfoCommand>().unwrap() else {
+            panic!("Expected InfoCommand::Depth");
+        };
+        assert_eq!(cmd.string.as_deref(), Some(""));
+        assert_eq!(cmd.to_string(), "info depth 1 string");
+    }
+
+    #[test]
+    fn test_parse_string_info_command() {
+        let cmd = "info string Using 4 threads"
+            .parse::<StringInfoCommand>()
+            .expect("Failed to parse StringInfoCommand");
+        assert_eq!(
+            cmd,
+            StringInfoCommand::UsingThreads(UsingThreadsInfoCommand { threads: 4 })
+        );
+
+        let cmd = "info string Available processors: 0-7"
+            .parse::<StringInfoCommand>()
+            .expect("Failed to parse StringInfoCommand");
+        assert!(matches!(cmd, StringInfoCommand::AvailableProcessors(_)));
+
+        let cmd = "info string Failed to open tablebases"
+            .parse::<StringInfoCommand>()
+            .expect("Failed to parse StringInfoCommand");
+        assert_eq!(
+            cmd,
+            StringInfoCommand::Other("Failed to open tablebases".to_string())
+        );
+        assert_eq!(cmd.to_string(), "info string Failed to open tablebases");
+
+        assert!("info depth 1".parse::<StringInfoCommand>().is_err());
+    }
+
+    #[cfg(feature = "board")]
+    #[test]
+    fn test_multipv_lines_to_san() {
+        let root = crate::gui_commands::PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList::default(),
+        };
+        let lines = [
+            "info depth 10 multipv 1 score cp 30 pv e2e4 e7e5 g1f3",
+            "info depth 10 multipv 2 score cp 25 pv d2d4 g8f6 c2c4",
+        ];
+        let san: Vec<Vec<String>> = lines
+            .iter()
+            .map(|line| match line.parse::<InfoCommand>().unwrap() {
+                InfoCommand::Depth(info) => info.pv_to_san(&root).unwrap(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(san, [["e4", "e5", "Nf3"], ["d4", "Nf6", "c4"]]);
+    }
 }
@@ -1,14 +1,49 @@
+mod any_engine_command;
+mod bestmove;
+mod engine_name;
 mod id;
 mod info;
 mod option;
+mod readyok;
 mod uciok;
 
+pub use any_engine_command::{
+    AnyEngineCommand, AnyEngineCommandKind, AnyEngineCommandParsingError,
+};
+pub use bestmove::{BestMoveCommand, BestMoveCommandParsingError};
+pub use engine_name::{EngineName, Version};
 pub use id::{IdBlock, IdBlockParsingError, IdCommand, IdCommandParsingError};
 pub use info::{
-    AvailableProcessorsInfoCommand, NnueEvaluationInfoCommand, UsingThreadsInfoCommand,
+    AvailableProcessorsInfoCommand, CurrLineInfoCommand, DepthInfoCommand, InfoCommand,
+    InfoCommandParsingError, InfoTokenArity, KNOWN_INFO_TOKENS, NnueEvaluationInfoCommand,
+    NnueNetKind, NnueNetowrkHeadDimensions, NnueNetworkArchitecture, RefutationInfoCommand,
+    StringInfoCommand, UsingThreadsInfoCommand,
 };
 pub use option::{
-    OptionBlockParsingError, OptionCommand, OptionCommandParsingError, UciOptionBlock,
-    UciOptionBlockBuilder,
+    ChangedOption, OptionBlockDiff, OptionBlockParsingError, OptionCommand,
+    OptionCommandParsingError, PartialUciOptionBlock, TypeClause, TypeClauseParsingError,
+    UciOptionBlock, UciOptionBlockBuilder,
 };
+pub use readyok::{ReadyOkCommand, ReadyOkCommandParsingError};
 pub use uciok::{UciOkCommand, UciOkCommandParsingError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command, test_util::assert_data};
+
+    #[test]
+    fn test_commands_are_send_sync_clone() {
+        assert_data::<AnyEngineCommand>();
+        assert_data::<command::parsing::Error<AnyEngineCommandParsingError>>();
+        assert_data::<IdBlock>();
+        assert_data::<IdBlockParsingError>();
+        assert_data::<OptionCommand>();
+        assert_data::<UciOptionBlockBuilder>();
+        assert_data::<OptionBlockParsingError>();
+        assert_data::<InfoCommand>();
+        assert_data::<BestMoveCommand>();
+        assert_data::<command::parsing::Error<UciOkCommandParsingError>>();
+        assert_data::<command::parsing::Error<ReadyOkCommandParsingError>>();
+    }
+}
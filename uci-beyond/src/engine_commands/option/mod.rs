@@ -1,16 +1,22 @@
 use std::str::FromStr;
 
 use crate::{
-    command, options,
+    command,
+    engine_quirks::Quirks,
+    model, options,
     util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
 };
 
+mod option_block_diff;
 mod type_clause;
 mod uci_option_block;
 
 use async_trait::async_trait;
+pub use option_block_diff::{ChangedOption, OptionBlockDiff};
 pub use type_clause::{TypeClause, TypeClauseParsingError};
-pub use uci_option_block::{OptionBlockParsingError, UciOptionBlock, UciOptionBlockBuilder};
+pub use uci_option_block::{
+    OptionBlockParsingError, PartialUciOptionBlock, UciOptionBlock, UciOptionBlockBuilder,
+};
 
 /// <https://backscattering.de/chess/uci/#engine-option>
 ///
@@ -19,23 +25,133 @@ pub use uci_option_block::{OptionBlockParsingError, UciOptionBlock, UciOptionBlo
 /// ```text
 /// option name Hash type spin default 1 min 1 max 128
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OptionCommand(pub options::UciOption);
 
-#[derive(Debug)]
+/// The error of parsing an `option` line, e.g. for telling the user of a GUI why an option of the engine is ignored.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum OptionCommandParsingError {
     /// The `name` token was expected. Encountered something else.
+    #[error("expected 'name' after 'option', found '{0}'")]
     NameTokenExpected(String),
+    /// Another clause, e.g. `type`, comes before the `name` clause, or a data clause, e.g. `default`,
+    /// comes before the `type` clause, which is accepted only with [`Quirks::reordered_option_clauses`].
+    #[error("the '{0}' clause is out of place, 'name' comes first and is followed by 'type'")]
+    OutOfPlaceClause(String),
+    /// The `name` token is immediately followed by the `type` token.
+    #[error("empty option name")]
+    EmptyOptionName,
+    #[error("in type clause: {0}")]
     TypeClauseParsingError(TypeClauseParsingError),
+    /// A standard option is advertised with another type, e.g. `Hash` as a string.
+    #[error("'{}' must be of type '{}', found '{found}'", option_kind.name(), option_kind.r#type())]
     UnexpectedUciType {
         option_kind: options::UciOptionKind,
         found: options::UciOptionType,
     },
+    #[error("in option data: {0}")]
     KnownUciOptionDataParsingError(options::typed_uci_option_data::KnownUciOptionDataParsingError),
+    #[error("invalid option: {0}")]
     UciOptionFromPartsError(options::UciOptionFromPartsError),
+    #[error("unexpected trailing tokens '{0}'")]
     UnexpectedTrailingTokens(String),
 }
 
 impl OptionCommand {
+    /// Creates an `option ... type spin` command, e.g.
+    ///
+    /// ```text
+    /// option name Hash type spin default 16 min 1 max 33554432
+    /// ```
+    ///
+    /// Fails if the option is a standard one of a different type or if `default` is not within `[min, max]`.
+    pub fn spin(
+        name: impl Into<options::UciOptionNameInfo>,
+        default: u32,
+        min: u32,
+        max: u32,
+    ) -> Result<Self, options::UciOptionFromPartsError> {
+        let spin = options::Spin { default, min, max };
+        if !(min..=max).contains(&default) {
+            return Err(options::UciOptionFromPartsError::InvalidSpinBounds(spin));
+        }
+        Self::from_parts(name, options::TypedUciOptionData::Spin(spin.into()))
+    }
+
+    /// Creates an `option ... type check` command, e.g.
+    ///
+    /// ```text
+    /// option name Ponder type check default false
+    /// ```
+    pub fn check(
+        name: impl Into<options::UciOptionNameInfo>,
+        default: bool,
+    ) -> Result<Self, options::UciOptionFromPartsError> {
+        Self::from_parts(
+            name,
+            options::TypedUciOptionData::Check(model::Check(default)),
+        )
+    }
+
+    /// Creates an `option ... type string` command, e.g.
+    ///
+    /// ```text
+    /// option name SyzygyPath type string default <empty>
+    /// ```
+    pub fn string(
+        name: impl Into<options::UciOptionNameInfo>,
+        default: impl Into<String>,
+    ) -> Result<Self, options::UciOptionFromPartsError> {
+        Self::from_parts(
+            name,
+            options::TypedUciOptionData::String(model::UciString(default.into())),
+        )
+    }
+
+    /// Creates an `option ... type button` command, e.g.
+    ///
+    /// ```text
+    /// option name Clear Hash type button
+    /// ```
+    pub fn button(
+        name: impl Into<options::UciOptionNameInfo>,
+    ) -> Result<Self, options::UciOptionFromPartsError> {
+        Self::from_parts(name, options::TypedUciOptionData::Button)
+    }
+
+    /// Creates an `option ... type combo` command, e.g.
+    ///
+    /// ```text
+    /// option name Style type combo default Normal var Solid var Normal var Risky
+    /// ```
+    ///
+    /// The vars are listed in the given order. Fails if `default` is not one of `vars`.
+    pub fn combo<S>(
+        name: impl Into<options::UciOptionNameInfo>,
+        default: impl Into<String>,
+        vars: impl IntoIterator<Item = S>,
+    ) -> Result<Self, options::UciOptionFromPartsError>
+    where
+        S: Into<String>,
+    {
+        let combo = options::Combo {
+            default: model::UciString(default.into()),
+            vars: vars
+                .into_iter()
+                .map(|var| model::UciString(var.into()))
+                .collect(),
+        };
+        Self::from_parts(name, options::TypedUciOptionData::Combo(combo))
+    }
+
+    fn from_parts(
+        name: impl Into<options::UciOptionNameInfo>,
+        typed_data: options::TypedUciOptionData,
+    ) -> Result<Self, options::UciOptionFromPartsError> {
+        options::UciOption::from_parts(name.into(), typed_data).map(OptionCommand)
+    }
+
     fn parse_name_token(
         s: &str,
     ) -> Result<&str, command::parsing::Error<OptionCommandParsingError>> {
@@ -45,6 +161,11 @@ impl OptionCommand {
             return Err(command::parsing::Error::UnexpectedEndOfTokens);
         };
 
+        if CLAUSE_KEYWORDS.contains(&name_token) {
+            return Err(command::parsing::Error::CustomError(
+                OptionCommandParsingError::OutOfPlaceClause(name_token.to_string()),
+            ));
+        }
         if name_token != "name" {
             return Err(command::parsing::Error::CustomError(
                 OptionCommandParsingError::NameTokenExpected(name_token.to_string()),
@@ -54,51 +175,51 @@ impl OptionCommand {
         Ok(s.trim_start_matches("name").trim_start())
     }
 
+    /// Splits the name of the option from the `type` clause that follows it.
+    ///
+    /// The name may contain the token `type`, e.g. `Book Type File`, so it extends to the last `type`
+    /// token followed by a UCI type, as most GUIs assume. If no `type` token is followed by a UCI type,
+    /// the name ends at the first one, so that the type clause fails to parse.
+    ///
+    /// This is ambiguous when a value contains a type clause, e.g. the string default of
+    /// `option name Foo type string default type check`, which is split as the name `Foo type string default`.
     fn parse_name_info(
-        mut s: &str,
+        s: &str,
     ) -> Result<
         (options::UciOptionNameInfo, &str),
         command::parsing::Error<OptionCommandParsingError>,
     > {
         debug_assert_eq!(s, s.trim_start());
-        let orig = s;
-
-        let mut tokens = s.split_whitespace();
-
-        loop {
-            debug_assert_eq!(s, s.trim_start());
-
-            let Some(token) = tokens.next() else {
-                return Err(command::parsing::Error::UnexpectedEndOfTokens);
-            };
 
-            if token == "type" {
-                s = s.trim_start();
-                break;
+        let mut first_type_token = None;
+        let mut last_type_clause = None;
+        for start in token_starts(s) {
+            if s[start..].split_whitespace().next() != Some("type") {
+                continue;
+            }
+            first_type_token.get_or_insert(start);
+            if TypeClause::parse_clause(&s[start..]).is_ok() {
+                last_type_clause = Some(start);
             }
-
-            s = s.trim_start_matches(token).trim_start();
         }
+        let Some(split) = last_type_clause.or(first_type_token) else {
+            return Err(command::parsing::Error::UnexpectedEndOfTokens);
+        };
 
-        debug_assert_eq!(s, s.trim_start());
-
-        let option_kind = orig.trim_end_matches(s).trim_end();
+        let (option_kind, s) = (s[..split].trim_end(), &s[split..]);
 
-        let s = s.trim_start_matches(option_kind).trim_start();
+        if option_kind.is_empty() {
+            return Err(command::parsing::Error::CustomError(
+                OptionCommandParsingError::EmptyOptionName,
+            ));
+        }
 
-        let option_kind: options::UciOptionKind = match option_kind.parse() {
-            Ok(kind) => kind,
-            Err(e) => {
-                return Ok((
-                    options::UciOptionNameInfo::Custom {
-                        name: option_kind.to_string(),
-                    },
-                    s,
-                ));
-            }
+        let name_info = match option_kind.parse::<options::UciOptionKind>() {
+            Ok(kind) => options::UciOptionNameInfo::Standard(kind),
+            Err(unknown) => options::UciOptionNameInfo::Custom(unknown),
         };
 
-        Ok((options::UciOptionNameInfo::Standard(option_kind), s))
+        Ok((name_info, s))
     }
 
     fn validate_uci_type(
@@ -128,6 +249,68 @@ impl OptionCommand {
     }
 }
 
+/// The byte offsets of the whitespace-separated tokens of the string.
+fn token_starts(s: &str) -> impl Iterator<Item = usize> + '_ {
+    s.char_indices()
+        .filter(|&(i, c)| {
+            !c.is_whitespace() && s[..i].chars().next_back().is_none_or(char::is_whitespace)
+        })
+        .map(|(i, _)| i)
+}
+
+/// The keywords that start the clauses of an `option` line other than the `name` clause.
+const CLAUSE_KEYWORDS: [&str; 5] = ["type", "default", "min", "max", "var"];
+
+/// The first whitespace-separated token at the byte offset.
+fn token_at(s: &str, start: usize) -> &str {
+    s[start..].split_whitespace().next().unwrap_or_default()
+}
+
+/// The keyword of the first clause out of place in the `option` line, without the `option` token, i.e. a clause
+/// before the `name` clause or a clause other than `type` after it, if any.
+///
+/// A name with a clause keyword, e.g. `Use min depth`, counts as out of place too, so this only tells why
+/// a line failed to parse.
+fn out_of_place_clause(s: &str) -> Option<&str> {
+    let mut keywords = token_starts(s)
+        .map(|start| token_at(s, start))
+        .filter(|&token| token == "name" || CLAUSE_KEYWORDS.contains(&token));
+    match s.split_whitespace().next()? {
+        "name" => keywords.nth(1).filter(|&keyword| keyword != "type"),
+        token if CLAUSE_KEYWORDS.contains(&token) => Some(token),
+        _ => None,
+    }
+}
+
+/// Rearranges the clauses of an `option` line, without the `option` token, into the order `name`, `type` and data,
+/// e.g. `type spin name Hash default 16 min 1 max 1024` into `name Hash type spin default 16 min 1 max 1024`.
+///
+/// The name clause extends to the next clause keyword or to the end of the line, and the type clause is
+/// the `type` token with the token that follows it. Returns `None` without a `name` or a `type` token.
+fn reorder_clauses(s: &str) -> Option<String> {
+    let name_start = token_starts(s).find(|&start| token_at(s, start) == "name")?;
+    let name_end = token_starts(s)
+        .filter(|&start| start > name_start)
+        .find(|&start| CLAUSE_KEYWORDS.contains(&token_at(s, start)))
+        .unwrap_or(s.len());
+    let name_clause = s[name_start..name_end].trim_end();
+    let rest = format!("{}{}", &s[..name_start], &s[name_end..]);
+
+    let starts = token_starts(&rest).collect::<Vec<_>>();
+    let type_index = starts
+        .iter()
+        .position(|&start| token_at(&rest, start) == "type")?;
+    let type_end = starts.get(type_index + 2).copied().unwrap_or(rest.len());
+    let type_clause = rest[starts[type_index]..type_end].trim_end();
+    let data = format!("{}{}", &rest[..starts[type_index]], &rest[type_end..]);
+
+    Some(
+        format!("{name_clause} {type_clause} {}", data.trim())
+            .trim_end()
+            .to_string(),
+    )
+}
+
 impl command::Command for OptionCommand {
     type ParsingError = OptionCommandParsingError;
 
@@ -140,18 +323,15 @@ impl std::fmt::Display for OptionCommand {
 
         let name = uci_option.name();
         let r#type = uci_option.r#type();
+        write!(f, "option name {name} type {type}")?;
 
-        match uci_option.r#type() {
-            options::UciOptionType::Button => {
-                write!(f, "option name {name} type {type}")
-            }
-            options::UciOptionType::Check
-            | options::UciOptionType::String
-            | options::UciOptionType::Spin
-            | options::UciOptionType::Combo => {
-                write!(f, "option name {name} type {type} {uci_option}")
-            }
+        // The data is empty for the buttons, whether standard or custom, so it is
+        // checked rather than the type, leaving no trailing space either way
+        let data = uci_option.to_string();
+        if !data.is_empty() {
+            write!(f, " {data}")?;
         }
+        Ok(())
     }
 }
 
@@ -159,22 +339,69 @@ impl FromStr for OptionCommand {
     type Err = command::parsing::Error<OptionCommandParsingError>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OptionCommand::parse_with(s, &Quirks::default())
+    }
+}
+
+impl OptionCommand {
+    /// Parses the command with the leniencies of the [`Quirks`], e.g. for a string default with spaces.
+    pub fn parse_with(
+        s: &str,
+        quirks: &Quirks,
+    ) -> Result<Self, command::parsing::Error<OptionCommandParsingError>> {
         use crate::command::Command as _;
 
         let s = OptionCommand::parse_cmd_name(s)?;
 
+        // The line is reordered only when it fails to parse as it is, so that a name
+        // with a clause keyword, e.g. `Use min depth`, is kept whole
+        let error = match OptionCommand::parse_clauses(s, quirks) {
+            Ok(cmd) => return Ok(cmd),
+            Err(e) => e,
+        };
+        match out_of_place_clause(s) {
+            Some(_) if quirks.reordered_option_clauses => match reorder_clauses(s) {
+                Some(reordered) => OptionCommand::parse_clauses(&reordered, quirks),
+                None => Err(error),
+            },
+            Some(clause) => Err(command::parsing::Error::CustomError(
+                OptionCommandParsingError::OutOfPlaceClause(clause.to_string()),
+            )),
+            None => Err(error),
+        }
+    }
+
+    /// Parses the clauses of the command, without the `option` token, in the standard order.
+    fn parse_clauses(
+        s: &str,
+        quirks: &Quirks,
+    ) -> Result<Self, command::parsing::Error<OptionCommandParsingError>> {
         // TODO: consider defining a "name clause"
         let s = OptionCommand::parse_name_token(s)?;
         let (name_info, s) = match OptionCommand::parse_name_info(s) {
             Ok(res) => res,
             Err(e) => return Err(e),
         };
+        let name_info = match name_info {
+            options::UciOptionNameInfo::Custom(options::UnknownUciOptionKind {
+                case_insensitive_match: Some(kind),
+                ..
+            }) if quirks.case_insensitive_option_names => {
+                options::UciOptionNameInfo::Standard(kind)
+            }
+            name_info => name_info,
+        };
 
         let (TypeClause { uci_type }, s) = TypeClause::parse_clause(s)?;
 
         OptionCommand::validate_uci_type(&name_info, uci_type)?;
 
-        let (typed_data, s) = options::TypedUciOptionData::parse_for_type(uci_type, s)?;
+        let (typed_data, s) = match uci_type {
+            options::UciOptionType::String if quirks.string_defaults_to_eol => {
+                options::TypedUciOptionData::parse_string_to_end_of_line(s)?
+            }
+            uci_type => options::TypedUciOptionData::parse_for_type(uci_type, s)?,
+        };
 
         let uci_option = options::UciOption::from_parts(name_info, typed_data)?;
 
@@ -243,7 +470,7 @@ impl From<options::UciOptionFromPartsError> for command::parsing::Error<OptionCo
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{model, options::UciOption};
+    use crate::{fixtures::STOCKFISH_OPTION_BLOCK, options::UciOption};
 
     #[test]
     fn test_imitate_stockfish_output() {
@@ -323,10 +550,10 @@ mod test {
                 max: 7,
             }),
             UciOption::EvalFile {
-                default: model::UciString("nn-1c0000000000.nnue".to_string()),
+                default: model::UciString::from("nn-1c0000000000.nnue"),
             },
             UciOption::EvalFileSmall {
-                default: model::UciString("nn-37f18f62d772.nnue".to_string()),
+                default: model::UciString::from("nn-37f18f62d772.nnue"),
             },
         ]
         .map(OptionCommand)
@@ -334,57 +561,38 @@ mod test {
             writeln!(&mut buf, "{cmd}").unwrap();
         }
 
-        let expect = r#"option name Debug Log File type string default <empty>
-option name NumaPolicy type string default auto
-option name Threads type spin default 1 min 1 max 1024
-option name Hash type spin default 16 min 1 max 33554432
-option name Clear Hash type button
-option name Ponder type check default false
-option name MultiPV type spin default 1 min 1 max 256
-option name Skill Level type spin default 20 min 0 max 20
-option name Move Overhead type spin default 10 min 0 max 5000
-option name nodestime type spin default 0 min 0 max 10000
-option name UCI_Chess960 type check default false
-option name UCI_LimitStrength type check default false
-option name UCI_Elo type spin default 1320 min 1320 max 3190
-option name UCI_ShowWDL type check default false
-option name SyzygyPath type string default <empty>
-option name SyzygyProbeDepth type spin default 1 min 1 max 100
-option name Syzygy50MoveRule type check default true
-option name SyzygyProbeLimit type spin default 7 min 0 max 7
-option name EvalFile type string default nn-1c0000000000.nnue
-option name EvalFileSmall type string default nn-37f18f62d772.nnue
-"#;
-
-        assert_eq!(buf, expect);
+        assert_eq!(buf, STOCKFISH_OPTION_BLOCK);
     }
 
     #[test]
-    fn test_parse_option_commands() {
-        let cmd_strs = [
-            "option name Debug Log File type string default <empty>",
-            "option name NumaPolicy type string default auto",
-            "option name Threads type spin default 1 min 1 max 1024",
-            "option name Hash type spin default 16 min 1 max 33554432",
-            "option name Clear Hash type button",
-            "option name Ponder type check default false",
-            "option name MultiPV type spin default 1 min 1 max 256",
-            "option name Skill Level type spin default 20 min 0 max 20",
-            "option name Move Overhead type spin default 10 min 0 max 5000",
-            "option name nodestime type spin default 0 min 0 max 10000",
-            "option name UCI_Chess960 type check default false",
-            "option name UCI_LimitStrength type check default false",
-            "option name UCI_Elo type spin default 1320 min 1320 max 3190",
-            "option name UCI_ShowWDL type check default false",
-            "option name SyzygyPath type string default <empty>",
-            "option name SyzygyProbeDepth type spin default 1 min 1 max 100",
-            "option name Syzygy50MoveRule type check default true",
-            "option name SyzygyProbeLimit type spin default 7 min 0 max 7",
-            "option name EvalFile type string default nn-1c0000000000.nnue",
-            "option name EvalFileSmall type string default nn-37f18f62d772.nnue",
-        ];
+    fn test_imitate_stockfish_output_from_block() {
+        use std::fmt::Write as _;
+
+        let mut block = options::UciOptionBlock::stockfish_defaults();
+        let mut buf = String::new();
+        for option in block.iter_canonical() {
+            writeln!(&mut buf, "{}", OptionCommand(option)).unwrap();
+        }
+        assert_eq!(buf, STOCKFISH_OPTION_BLOCK);
+
+        // The custom options follow the standard ones in the order of declaration
+        for name in ["Zeta", "Alpha"] {
+            block.custom.insert(
+                name.to_string(),
+                options::TypedUciOptionData::Check(model::Check(false)),
+            );
+        }
+        let names = block
+            .iter_canonical()
+            .map(|option| option.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names[..2], ["Debug Log File", "NumaPolicy"]);
+        assert_eq!(names[names.len() - 3..], ["EvalFileSmall", "Zeta", "Alpha"]);
+    }
 
-        for cmd_str in cmd_strs {
+    #[test]
+    fn test_parse_option_commands() {
+        for cmd_str in STOCKFISH_OPTION_BLOCK.lines() {
             let option_cmd = match OptionCommand::from_str(cmd_str) {
                 Ok(cmd) => cmd,
                 Err(e) => panic!("Failed to parse OptionCommand from `{cmd_str}`: {e:?}"),
@@ -409,28 +617,7 @@ option name EvalFileSmall type string default nn-37f18f62d772.nnue
     async fn test_parse_option_commands_async() {
         use tokio::io::BufReader;
 
-        let input = b"option name Debug Log File type string default <empty>\n\
-        option name NumaPolicy type string default auto\n\
-        option name Threads type spin default 1 min 1 max 1024\n\
-        option name Hash type spin default 16 min 1 max 33554432\n\
-        option name Clear Hash type button\n\
-        option name Ponder type check default false\n\
-        option name MultiPV type spin default 1 min 1 max 256\n\
-        option name Skill Level type spin default 20 min 0 max 20\n\
-        option name Move Overhead type spin default 10 min 0 max 5000\n\
-        option name nodestime type spin default 0 min 0 max 10000\n\
-        option name UCI_Chess960 type check default false\n\
-        option name UCI_LimitStrength type check default false\n\
-        option name UCI_Elo type spin default 1320 min 1320 max 3190\n\
-        option name UCI_ShowWDL type check default false\n\
-        option name SyzygyPath type string default <empty>\n\
-        option name SyzygyProbeDepth type spin default 1 min 1 max 100\n\
-        option name Syzygy50MoveRule type check default true\n\
-        option name SyzygyProbeLimit type spin default 7 min 0 max 7\n\
-        option name EvalFile type string default nn-1c0000000000.nnue\n\
-        option name EvalFileSmall type string default nn-37f18f62d772.nnue\n";
-
-        let cursor = std::io::Cursor::new(input);
+        let cursor = std::io::Cursor::new(STOCKFISH_OPTION_BLOCK.as_bytes());
         let mut reader = BufReader::new(cursor);
 
         let expected = vec![
@@ -505,10 +692,10 @@ option name EvalFileSmall type string default nn-37f18f62d772.nnue
                 max: 7,
             }),
             UciOption::EvalFile {
-                default: model::UciString("nn-1c0000000000.nnue".to_string()),
+                default: model::UciString::from("nn-1c0000000000.nnue"),
             },
             UciOption::EvalFileSmall {
-                default: model::UciString("nn-37f18f62d772.nnue".to_string()),
+                default: model::UciString::from("nn-37f18f62d772.nnue"),
             },
         ];
 
@@ -528,4 +715,370 @@ option name EvalFileSmall type string default nn-37f18f62d772.nnue
         let res = OptionCommand::read_from(&mut reader).await;
         assert!(matches!(res, Ok(None)));
     }
+
+    #[test]
+    fn test_option_command_constructors() {
+        let cmd = OptionCommand::spin(options::UciOptionKind::Hash, 16, 1, 33_554_432)
+            .expect("Failed to create spin option");
+        assert_eq!(
+            cmd.to_string(),
+            "option name Hash type spin default 16 min 1 max 33554432"
+        );
+
+        let cmd = OptionCommand::spin("Threads", 1, 1, 1024).expect("Failed to create spin option");
+        assert!(matches!(cmd.0, UciOption::Threads(_)));
+
+        let cmd = OptionCommand::check("Ponder", false).expect("Failed to create check option");
+        assert_eq!(
+            cmd.to_string(),
+            "option name Ponder type check default false"
+        );
+
+        let cmd = OptionCommand::string(options::UciOptionKind::SyzygyPath, "")
+            .expect("Failed to create string option");
+        assert_eq!(
+            cmd.to_string(),
+            "option name SyzygyPath type string default <empty>"
+        );
+
+        let cmd = OptionCommand::button("Clear Hash").expect("Failed to create button option");
+        assert_eq!(cmd.to_string(), "option name Clear Hash type button");
+
+        let cmd = OptionCommand::combo("Style", "Normal", ["Solid", "Normal", "Risky"])
+            .expect("Failed to create combo option");
+        assert_eq!(
+            cmd.to_string(),
+            "option name Style type combo default Normal var Solid var Normal var Risky"
+        );
+        assert_eq!(cmd.to_string().parse::<OptionCommand>().unwrap(), cmd);
+    }
+
+    #[test]
+    fn test_option_command_constructors_validate() {
+        assert!(matches!(
+            OptionCommand::check(options::UciOptionKind::Hash, true),
+            Err(options::UciOptionFromPartsError::UciOptionDataTypeMismatchError(_))
+        ));
+        assert!(matches!(
+            OptionCommand::spin("Threads", 0, 1, 1024),
+            Err(options::UciOptionFromPartsError::InvalidSpinBounds(_))
+        ));
+        assert!(matches!(
+            OptionCommand::combo("Style", "Wild", ["Solid", "Normal"]),
+            Err(options::UciOptionFromPartsError::InvalidComboDefault(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_option_command_empty_name() {
+        assert!(matches!(
+            "option name  type spin default 1 min 1 max 128".parse::<OptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                OptionCommandParsingError::EmptyOptionName
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_parse_option_command_missing_name_token() {
+        assert!(matches!(
+            "option Hash type spin default 1 min 1 max 128".parse::<OptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                OptionCommandParsingError::NameTokenExpected(token)
+            )) if token == "Hash"
+        ));
+        assert!(matches!(
+            "option type spin default 1 min 1 max 128".parse::<OptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                OptionCommandParsingError::OutOfPlaceClause(clause)
+            )) if clause == "type"
+        ));
+    }
+
+    #[test]
+    fn test_parse_name_info_keeps_unknown_kind() {
+        let (name_info, s) =
+            OptionCommand::parse_name_info("hash type spin default 16 min 1 max 1024").unwrap();
+        assert_eq!(s, "type spin default 16 min 1 max 1024");
+        assert!(matches!(
+            name_info,
+            options::UciOptionNameInfo::Custom(options::UnknownUciOptionKind {
+                name,
+                case_insensitive_match: Some(options::UciOptionKind::Hash),
+            }) if name == "hash"
+        ));
+
+        let cmd: OptionCommand = "option name Contempt type spin default 0 min 0 max 100"
+            .parse()
+            .unwrap_or_else(|e| panic!("Failed to parse OptionCommand: {e:?}"));
+        assert_eq!(cmd.0.name(), "Contempt");
+    }
+
+    #[test]
+    fn test_parse_spin_without_bounds() {
+        let line = "option name Selectivity type spin default 2";
+        let cmd: OptionCommand = line.parse().unwrap();
+        assert_eq!(
+            cmd.0,
+            UciOption::Custom {
+                name: "Selectivity".to_string(),
+                typed_data: options::TypedUciOptionData::Spin(options::PartialSpin {
+                    default: 2,
+                    min: None,
+                    max: None,
+                }),
+            }
+        );
+        assert_eq!(cmd.to_string(), line);
+
+        let cmd: OptionCommand = "option name Contempt type spin max 100 default 0 min -100"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            cmd.to_string(),
+            "option name Contempt type spin default 0 min -100 max 100"
+        );
+
+        // The standard options need the bounds
+        assert!(matches!(
+            "option name Hash type spin default 16".parse::<OptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                OptionCommandParsingError::UciOptionFromPartsError(
+                    options::UciOptionFromPartsError::SpinConversionError(
+                        options::SpinConversionError::MissingBounds(_)
+                    )
+                )
+            ))
+        ));
+        let cmd: OptionCommand = "option name Hash type spin max 1024 min 1 default 16"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            cmd.0,
+            UciOption::Hash(options::Spin {
+                default: 16,
+                min: 1,
+                max: 1024
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_name_containing_type() {
+        let line = "option name Book Type File type string default <empty>";
+        let cmd: OptionCommand = line.parse().unwrap();
+        assert_eq!(
+            cmd.0,
+            UciOption::Custom {
+                name: "Book Type File".to_string(),
+                typed_data: options::TypedUciOptionData::String(model::UciString(String::new())),
+            }
+        );
+        assert_eq!(cmd.to_string(), line);
+
+        let cmd: OptionCommand = "option name Book type File type string default <empty>"
+            .parse()
+            .unwrap();
+        assert_eq!(cmd.0.name(), "Book type File");
+
+        let cmd: OptionCommand = "option name type type check default true".parse().unwrap();
+        assert_eq!(cmd.0.name(), "type");
+
+        // The first `type` token is the type clause when none of them is followed by a UCI type
+        assert!(matches!(
+            "option name Book Type type number default 1".parse::<OptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                OptionCommandParsingError::TypeClauseParsingError(
+                    TypeClauseParsingError::UnknownType(_)
+                )
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_custom_options_round_trip() {
+        let cases = [
+            (
+                "option name Reset Learning type button",
+                options::TypedUciOptionData::Button,
+            ),
+            (
+                "option name Own Book type check default false",
+                options::TypedUciOptionData::Check(model::Check(false)),
+            ),
+            (
+                "option name Book File type string default book.bin",
+                options::TypedUciOptionData::String(model::UciString::from("book.bin")),
+            ),
+            (
+                "option name Book File type string default <empty>",
+                options::TypedUciOptionData::String(model::UciString(String::new())),
+            ),
+        ];
+        for (line, typed_data) in cases {
+            let cmd = OptionCommand(UciOption::Custom {
+                name: line
+                    .strip_prefix("option name ")
+                    .and_then(|rest| rest.split(" type ").next())
+                    .unwrap()
+                    .to_string(),
+                typed_data,
+            });
+            assert_eq!(cmd.to_string(), line);
+            assert_eq!(line.parse::<OptionCommand>().unwrap(), cmd);
+        }
+
+        // A standard button
+        let clear_hash = OptionCommand(UciOption::ClearHash);
+        assert_eq!(clear_hash.to_string(), "option name Clear Hash type button");
+        assert_eq!(
+            clear_hash.to_string().parse::<OptionCommand>().unwrap(),
+            clear_hash
+        );
+    }
+
+    #[test]
+    fn test_parsing_error_messages() {
+        let cases = [
+            (
+                "option name Hash type string default 16",
+                "'Hash' must be of type 'spin', found 'string'",
+            ),
+            (
+                "option type spin",
+                "the 'type' clause is out of place, 'name' comes first and is followed by 'type'",
+            ),
+            (
+                "option nme Hash type spin",
+                "expected 'name' after 'option', found 'nme'",
+            ),
+            (
+                "option name Foo type spinn default 1",
+                "in type clause: unknown option type 'spinn' (expected one of: spin, string, button, check, combo)",
+            ),
+            (
+                "option name Foo type spin default x",
+                "in option data: in spin: invalid value 'x': invalid digit found in string",
+            ),
+            (
+                "option name Ponder type check default yes",
+                "in option data: invalid check value 'yes' (expected one of: true, false)",
+            ),
+            (
+                "option name Foo type spin default 1 min 0 max 2 var x",
+                "unexpected trailing tokens 'var x'",
+            ),
+            ("id name Foo", "unexpected command 'id name Foo'"),
+        ];
+        for (line, message) in cases {
+            let e = line.parse::<OptionCommand>().unwrap_err();
+            assert_eq!(e.to_string(), message);
+            assert_eq!(
+                e.in_command::<OptionCommand>().to_string(),
+                format!("failed to parse 'option' command: {message}")
+            );
+        }
+
+        let e = OptionBlockParsingError::RepeatedOption {
+            kind_or_name: "Hash".to_string(),
+            first: "option name Hash type spin default 16 min 1 max 1024".to_string(),
+            second: "option name Hash type spin default 32 min 1 max 2048".to_string(),
+        };
+        assert_eq!(
+            e.to_string(),
+            "repeated option 'Hash': 'option name Hash type spin default 16 min 1 max 1024' \
+             and then 'option name Hash type spin default 32 min 1 max 2048'"
+        );
+    }
+
+    #[test]
+    fn test_parse_option_command_with_quirks() {
+        use crate::engine_quirks::EngineFamily;
+
+        let komodo = Quirks::for_family(EngineFamily::Komodo);
+
+        let line = "option name hash type spin default 16 min 1 max 1024";
+        let cmd = OptionCommand::parse_with(line, &komodo).unwrap();
+        assert!(matches!(cmd.0, UciOption::Hash(_)));
+        let cmd: OptionCommand = line.parse().unwrap();
+        assert!(matches!(cmd.0, UciOption::Custom { .. }));
+
+        let line = "option name Book File type string default books/main book.bin";
+        let cmd = OptionCommand::parse_with(line, &komodo).unwrap();
+        assert_eq!(
+            cmd.0,
+            UciOption::Custom {
+                name: "Book File".to_string(),
+                typed_data: options::TypedUciOptionData::String(model::UciString(
+                    "books/main book.bin".to_string()
+                )),
+            }
+        );
+        assert!(line.parse::<OptionCommand>().is_err());
+
+        let line = "option name Book File type string default <empty>";
+        let cmd = OptionCommand::parse_with(line, &komodo).unwrap();
+        assert_eq!(cmd.to_string(), line);
+    }
+
+    #[test]
+    fn test_parse_reordered_option_clauses() {
+        use crate::fixtures::REORDERED_OPTION_CLAUSES;
+
+        let lenient = Quirks {
+            reordered_option_clauses: true,
+            ..Default::default()
+        };
+        let parsed = REORDERED_OPTION_CLAUSES
+            .lines()
+            .map(|line| {
+                OptionCommand::parse_with(line, &lenient)
+                    .unwrap()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parsed,
+            [
+                "option name Threads type spin default 1 min 1 max 512",
+                "option name Ponder type check default false",
+                "option name Hash type spin default 16 min 1 max 1024",
+                "option name Move Overhead type spin default 10 min 0 max 5000",
+                "option name Debug Log File type string default <empty>",
+                "option name Clear Hash type button",
+            ]
+        );
+
+        let out_of_place = REORDERED_OPTION_CLAUSES
+            .lines()
+            .map(|line| match line.parse::<OptionCommand>() {
+                Err(command::parsing::Error::CustomError(
+                    OptionCommandParsingError::OutOfPlaceClause(clause),
+                )) => Some(clause),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            out_of_place,
+            [
+                Some("type".to_string()),
+                Some("type".to_string()),
+                Some("default".to_string()),
+                // The data clause before the type clause is out of place too
+                Some("default".to_string()),
+                Some("type".to_string()),
+                Some("type".to_string()),
+            ]
+        );
+
+        // The lines in the standard order are parsed as before, even if the name has a clause keyword
+        for line in [
+            "option name Book Type File type string default book.bin",
+            "option name Use min depth type check default true",
+        ] {
+            let cmd = OptionCommand::parse_with(line, &lenient).unwrap();
+            assert_eq!(cmd, line.parse().unwrap());
+            assert_eq!(cmd.to_string(), line);
+        }
+    }
 }
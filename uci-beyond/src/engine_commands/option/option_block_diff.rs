@@ -0,0 +1,172 @@
+use std::fmt::Display;
+
+use kinded::Kinded as _;
+
+use crate::{
+    engine_commands::{OptionCommand, UciOptionBlockBuilder},
+    options::{TypedUciOptionData, UciOption},
+};
+
+/// An option advertised by both blocks with different declarations, e.g. a new `max` of `Threads`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedOption {
+    /// The name as advertised by the newer block.
+    pub name: String,
+    pub old: TypedUciOptionData,
+    pub new: TypedUciOptionData,
+}
+
+/// The difference between the options advertised by two builds of an engine, see [`UciOptionBlockBuilder::compare`].
+///
+/// The options are matched by their names, case-insensitively as per the UCI protocol.
+///
+/// The [`Display`] implementation renders a diff with a line per option, e.g.
+///
+/// ```text
+/// + option name Frobnicate type check default false
+/// - option name nodestime type spin default 0 min 0 max 10000
+/// ~ Threads: type spin default 1 min 1 max 512 -> type spin default 1 min 1 max 1024
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OptionBlockDiff {
+    /// The options that only the newer block advertises, in the order of [`UciOptionBlockBuilder::options`].
+    pub added: Vec<UciOption>,
+    /// The options that only the baseline advertises, in the order of [`UciOptionBlockBuilder::options`].
+    pub removed: Vec<UciOption>,
+    pub changed: Vec<ChangedOption>,
+}
+
+impl OptionBlockDiff {
+    /// Whether the blocks advertise the same options.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The declaration of the option after its name, e.g. `type spin default 1 min 1 max 1024`.
+fn declaration(data: &TypedUciOptionData) -> String {
+    format!("type {} {data}", data.kind())
+        .trim_end()
+        .to_string()
+}
+
+impl Display for OptionBlockDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for option in &self.added {
+            writeln!(f, "+ {}", OptionCommand(option.clone()))?;
+        }
+        for option in &self.removed {
+            writeln!(f, "- {}", OptionCommand(option.clone()))?;
+        }
+        for ChangedOption { name, old, new } in &self.changed {
+            writeln!(f, "~ {name}: {} -> {}", declaration(old), declaration(new))?;
+        }
+        Ok(())
+    }
+}
+
+impl UciOptionBlockBuilder {
+    /// Compares the options with the ones of the `baseline`, e.g. of the engine build that a configuration
+    /// was pinned against, to detect the changed defaults and bounds.
+    pub fn compare(&self, baseline: &UciOptionBlockBuilder) -> OptionBlockDiff {
+        let (new, old) = (self.options(), baseline.options());
+        let find = |options: &[UciOption], name: &str| {
+            options
+                .iter()
+                .find(|option| option.name().eq_ignore_ascii_case(name))
+                .cloned()
+        };
+
+        let mut diff = OptionBlockDiff::default();
+        for option in &new {
+            match find(&old, option.name()) {
+                None => diff.added.push(option.clone()),
+                Some(first) => {
+                    let (old, new) = (first.typed_data(), option.typed_data());
+                    if old != new {
+                        diff.changed.push(ChangedOption {
+                            name: option.name().to_string(),
+                            old,
+                            new,
+                        });
+                    }
+                }
+            }
+        }
+        diff.removed = old
+            .iter()
+            .filter(|option| find(&new, option.name()).is_none())
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{fixtures::STOCKFISH_OPTION_BLOCK, options::Spin, util::AsyncReadable};
+
+    async fn stockfish_options() -> UciOptionBlockBuilder {
+        let mut reader = tokio::io::BufReader::new(STOCKFISH_OPTION_BLOCK.as_bytes());
+        UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_compare_option_blocks() {
+        let baseline = stockfish_options().await;
+        assert!(baseline.compare(&baseline).is_empty());
+
+        let mut modified = baseline.clone();
+        modified.threads = Some(Spin {
+            default: 1,
+            min: 1,
+            max: 512,
+        });
+        modified.nodestime = None;
+        modified.custom.insert(
+            "Frobnicate".to_string(),
+            TypedUciOptionData::Check(crate::model::Check(false)),
+        );
+
+        let diff = modified.compare(&baseline);
+        assert_eq!(
+            diff.added,
+            [UciOption::Custom {
+                name: "Frobnicate".to_string(),
+                typed_data: TypedUciOptionData::Check(crate::model::Check(false)),
+            }]
+        );
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name(), "nodestime");
+        assert_eq!(
+            diff.changed,
+            [ChangedOption {
+                name: "Threads".to_string(),
+                old: UciOption::Threads(baseline.threads.clone().unwrap()).typed_data(),
+                new: UciOption::Threads(modified.threads.clone().unwrap()).typed_data(),
+            }]
+        );
+
+        assert_eq!(
+            diff.to_string(),
+            "+ option name Frobnicate type check default false\n\
+             - option name nodestime type spin default 0 min 0 max 10000\n\
+             ~ Threads: type spin default 1 min 1 max 1024 -> type spin default 1 min 1 max 512\n"
+        );
+
+        // The other way around
+        let diff = baseline.compare(&modified);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(
+            diff.changed[0].new,
+            UciOption::Threads(baseline.threads.unwrap()).typed_data()
+        );
+    }
+}
@@ -7,11 +7,15 @@ pub struct TypeClause {
     pub uci_type: options::UciOptionType,
 }
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum TypeClauseParsingError {
     /// The `type` token was expected. Encountered something else.
+    #[error("expected 'type', found '{0}'")]
     TypeTokenExpected(String),
+    #[error("{0}")]
     UnknownType(options::UnknownUciOptionType),
+    #[error("missing type after 'type'")]
     UnexpectedEol,
 }
 
@@ -3,18 +3,26 @@ use async_trait::async_trait;
 use crate::{
     command,
     engine_commands::{self, OptionCommand},
-    options::UciOption,
-    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
+    engine_quirks::Quirks,
+    options::{UciOption, UciOptionKind, ordering::canonical_position},
+    util::{AsyncReadable, ReadBlockError, StreamingLineReader, read_block_with},
 };
 
 // UciOptionBlock is defined there because the UciOption enum is in the options module
 pub use crate::options::{UciOptionBlock, UciOptionBlockBuilder};
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum OptionBlockParsingError {
+    #[error("{0}")]
     CommandErrorParsingError(engine_commands::OptionCommandParsingError),
-    EmptyLineWasNotFoundUntilEndOfStream,
-    RepeatedOption,
+    /// The option was advertised twice. `first` and `second` are the two `option` lines.
+    #[error("repeated option '{kind_or_name}': '{first}' and then '{second}'")]
+    RepeatedOption {
+        kind_or_name: String,
+        first: String,
+        second: String,
+    },
 }
 
 impl OptionBlockParsingError {
@@ -33,183 +41,480 @@ impl AsyncReadable for UciOptionBlockBuilder {
     where
         R: StreamingLineReader,
     {
-        let mut i = 0;
-        let mut b = UciOptionBlockBuilder::default();
+        UciOptionBlockBuilder::read_with(reader, &Quirks::default()).await
+    }
+}
 
-        loop {
-            let opt = handle_next_line(reader, |line: &str| {
-                if !line.trim_start().starts_with("option") {
-                    return LineHandlerOutcome::Peeked;
-                }
-                match line.parse::<OptionCommand>() {
-                    Ok(cmd) => LineHandlerOutcome::Read(cmd),
-                    Err(e) => LineHandlerOutcome::Error(e),
-                }
-            })
-            .await?;
+impl UciOptionBlockBuilder {
+    /// Reads the block with the leniencies of the [`Quirks`] (see [`OptionCommand::parse_with`]).
+    ///
+    /// With [`Quirks::skip_unknown_lines`], the `option` lines that can't be parsed are skipped.
+    /// With [`Quirks::collect_repeated_options`], the repeated options are dropped;
+    /// see [`read_collecting_repeated`](Self::read_collecting_repeated) to keep them.
+    pub async fn read_with<R>(
+        reader: &mut R,
+        quirks: &Quirks,
+    ) -> Result<Option<Result<Self, <Self as AsyncReadable>::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        Self::read_collecting_repeated(reader, quirks, &mut Vec::new()).await
+    }
 
-            let Some(outcome) = opt else {
-                return OptionBlockParsingError::EmptyLineWasNotFoundUntilEndOfStream.wrap();
-            };
+    /// The same as [`read_with`](Self::read_with), but with [`Quirks::collect_repeated_options`],
+    /// the repeated options are appended to `repeated`.
+    pub async fn read_collecting_repeated<R>(
+        reader: &mut R,
+        quirks: &Quirks,
+        repeated: &mut Vec<(UciOptionKind, UciOption)>,
+    ) -> Result<Option<Result<Self, <Self as AsyncReadable>::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let mut block = UciOptionBlockBuilder::default();
+        let mut is_empty = true;
 
-            let cmd: OptionCommand = match outcome {
-                LineHandlerOutcome::Peeked => {
-                    if i == 0 {
-                        // No OptionCommands were read; return None
-                        return Ok(None);
-                    } else {
-                        // End of OptionBlock - return the builder with whatever options were found
-                        return Ok(Some(Ok(b)));
-                    }
+        loop {
+            let parse = |line: &str| OptionCommand::parse_with(line, quirks);
+            let fold =
+                |(): &mut (), cmd: OptionCommand| block.add_with(cmd.0, quirks, &mut *repeated);
+
+            match read_block_with(reader, parse, fold).await? {
+                Some(Ok(())) => return Ok(Some(Ok(block))),
+                Some(Err(ReadBlockError::Parsing(_))) if quirks.skip_unknown_lines => {
+                    is_empty = false;
                 }
-                LineHandlerOutcome::Read(cmd) => cmd,
-                LineHandlerOutcome::Error(e) => {
+                Some(Err(ReadBlockError::Parsing(e))) => {
                     return e.map_custom(OptionBlockParsingError::from).wrap();
                 }
-            };
+                Some(Err(ReadBlockError::Fold(e))) => return e.wrap(),
+                None => return Ok((!is_empty).then_some(Ok(block))),
+            }
+        }
+    }
 
-            let uci_option: UciOption = cmd.0;
+    /// Adds the advertised option. If it was already advertised, it is appended to `repeated`
+    /// with [`Quirks::collect_repeated_options`] and fails otherwise.
+    pub(crate) fn add_with(
+        &mut self,
+        option: UciOption,
+        quirks: &Quirks,
+        repeated: &mut Vec<(UciOptionKind, UciOption)>,
+    ) -> Result<(), OptionBlockParsingError> {
+        if let Some(kind) = option.kind().filter(|_| quirks.collect_repeated_options) {
+            if self.advertised(&option).is_some() {
+                repeated.push((kind, option));
+                return Ok(());
+            }
+        }
+        self.add(option)
+    }
 
-            match uci_option {
-                UciOption::ClearHash => {
-                    if b.clear_hash.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.clear_hash = Some(());
-                }
-                UciOption::DebugLogFile { default } => {
-                    if b.debug_log_file.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.debug_log_file = Some(default);
-                }
-                UciOption::EvalFile { default } => {
-                    if b.eval_file.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.eval_file = Some(default);
-                }
-                UciOption::EvalFileSmall { default } => {
-                    if b.eval_file_small.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.eval_file_small = Some(default);
-                }
-                UciOption::Hash(spin) => {
-                    if b.hash.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.hash = Some(spin);
-                }
-                UciOption::MoveOverhead(spin) => {
-                    if b.move_overhead.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.move_overhead = Some(spin);
-                }
-                UciOption::MultiPV(spin) => {
-                    if b.multi_pv.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.multi_pv = Some(spin);
-                }
-                UciOption::Nodestime(spin) => {
-                    if b.nodestime.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.nodestime = Some(spin);
-                }
-                UciOption::NumaPolicy { default } => {
-                    if b.numa_policy.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.numa_policy = Some(default);
-                }
-                UciOption::Ponder { default } => {
-                    if b.ponder.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.ponder = Some(default);
-                }
-                UciOption::SkillLevel(spin) => {
-                    if b.skill_level.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.skill_level = Some(spin);
-                }
-                UciOption::Syzygy50MoveRule { default } => {
-                    if b.syzygy_50_move_rule.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.syzygy_50_move_rule = Some(default);
-                }
-                UciOption::SyzygyPath { default } => {
-                    if b.syzygy_path.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.syzygy_path = Some(default);
-                }
-                UciOption::SyzygyProbeDepth(spin) => {
-                    if b.syzygy_probe_depth.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.syzygy_probe_depth = Some(spin);
-                }
-                UciOption::SyzygyProbeLimit(spin) => {
-                    if b.syzygy_probe_limit.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.syzygy_probe_limit = Some(spin);
-                }
-                UciOption::Threads(spin) => {
-                    if b.threads.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.threads = Some(spin);
-                }
-                UciOption::UCIChess960 { default } => {
-                    if b.uci_chess_960.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.uci_chess_960 = Some(default);
-                }
-                UciOption::UCIElo(spin) => {
-                    if b.uci_elo.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.uci_elo = Some(spin);
-                }
-                UciOption::UCILimitStrength { default } => {
-                    if b.uci_limit_strength.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.uci_limit_strength = Some(default);
-                }
-                UciOption::UCIShowWDL { default } => {
-                    if b.uci_show_wdl.is_some() {
-                        return OptionBlockParsingError::RepeatedOption.wrap();
-                    };
-                    b.uci_show_wdl = Some(default);
-                }
-                UciOption::Custom { name, typed_data } => {
-                    use std::collections::hash_map::Entry::{Occupied, Vacant};
-
-                    match b.custom.entry(name) {
-                        Occupied(_) => {
-                            return OptionBlockParsingError::RepeatedOption.wrap();
-                        }
-                        Vacant(e) => {
-                            e.insert(typed_data);
-                        }
-                    }
-                }
-            };
+    /// Adds the advertised option, failing if it was already advertised.
+    pub(crate) fn add(&mut self, option: UciOption) -> Result<(), OptionBlockParsingError> {
+        if let Some(first) = self.advertised(&option) {
+            return Err(OptionBlockParsingError::RepeatedOption {
+                kind_or_name: option.name().to_string(),
+                first: OptionCommand(first).to_string(),
+                second: OptionCommand(option).to_string(),
+            });
+        }
 
-            i += 1;
+        let b = self;
+
+        match option {
+            UciOption::ClearHash => {
+                b.clear_hash = Some(());
+            }
+            UciOption::DebugLogFile { default } => {
+                b.debug_log_file = Some(default);
+            }
+            UciOption::EvalFile { default } => {
+                b.eval_file = Some(default);
+            }
+            UciOption::EvalFileSmall { default } => {
+                b.eval_file_small = Some(default);
+            }
+            UciOption::Hash(spin) => {
+                b.hash = Some(spin);
+            }
+            UciOption::MoveOverhead(spin) => {
+                b.move_overhead = Some(spin);
+            }
+            UciOption::MultiPV(spin) => {
+                b.multi_pv = Some(spin);
+            }
+            UciOption::Nodestime(spin) => {
+                b.nodestime = Some(spin);
+            }
+            UciOption::NumaPolicy { default } => {
+                b.numa_policy = Some(default);
+            }
+            UciOption::Ponder { default } => {
+                b.ponder = Some(default);
+            }
+            UciOption::SkillLevel(spin) => {
+                b.skill_level = Some(spin);
+            }
+            UciOption::Syzygy50MoveRule { default } => {
+                b.syzygy_50_move_rule = Some(default);
+            }
+            UciOption::SyzygyPath { default } => {
+                b.syzygy_path = Some(default);
+            }
+            UciOption::SyzygyProbeDepth(spin) => {
+                b.syzygy_probe_depth = Some(spin);
+            }
+            UciOption::SyzygyProbeLimit(spin) => {
+                b.syzygy_probe_limit = Some(spin);
+            }
+            UciOption::Threads(spin) => {
+                b.threads = Some(spin);
+            }
+            UciOption::UCIChess960 { default } => {
+                b.uci_chess_960 = Some(default);
+            }
+            UciOption::UCIElo(spin) => {
+                b.uci_elo = Some(spin);
+            }
+            UciOption::UCILimitStrength { default } => {
+                b.uci_limit_strength = Some(default);
+            }
+            UciOption::UCIShowWDL { default } => {
+                b.uci_show_wdl = Some(default);
+            }
+            UciOption::Custom { name, typed_data } => {
+                b.custom.insert(name, typed_data);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The option advertised earlier under the name of `option`, looked up in its own field
+    /// rather than among all of the [`options`](Self::options).
+    fn advertised(&self, option: &UciOption) -> Option<UciOption> {
+        let b = self;
+
+        match option {
+            UciOption::ClearHash => b.clear_hash.map(|()| UciOption::ClearHash),
+            UciOption::DebugLogFile { .. } => b
+                .debug_log_file
+                .clone()
+                .map(|default| UciOption::DebugLogFile { default }),
+            UciOption::EvalFile { .. } => b
+                .eval_file
+                .clone()
+                .map(|default| UciOption::EvalFile { default }),
+            UciOption::EvalFileSmall { .. } => b
+                .eval_file_small
+                .clone()
+                .map(|default| UciOption::EvalFileSmall { default }),
+            UciOption::Hash(_) => b.hash.clone().map(UciOption::Hash),
+            UciOption::MoveOverhead(_) => b.move_overhead.clone().map(UciOption::MoveOverhead),
+            UciOption::MultiPV(_) => b.multi_pv.clone().map(UciOption::MultiPV),
+            UciOption::Nodestime(_) => b.nodestime.clone().map(UciOption::Nodestime),
+            UciOption::NumaPolicy { .. } => b
+                .numa_policy
+                .clone()
+                .map(|default| UciOption::NumaPolicy { default }),
+            UciOption::Ponder { .. } => b
+                .ponder
+                .clone()
+                .map(|default| UciOption::Ponder { default }),
+            UciOption::SkillLevel(_) => b.skill_level.clone().map(UciOption::SkillLevel),
+            UciOption::Syzygy50MoveRule { .. } => b
+                .syzygy_50_move_rule
+                .clone()
+                .map(|default| UciOption::Syzygy50MoveRule { default }),
+            UciOption::SyzygyPath { .. } => b
+                .syzygy_path
+                .clone()
+                .map(|default| UciOption::SyzygyPath { default }),
+            UciOption::SyzygyProbeDepth(_) => b
+                .syzygy_probe_depth
+                .clone()
+                .map(UciOption::SyzygyProbeDepth),
+            UciOption::SyzygyProbeLimit(_) => b
+                .syzygy_probe_limit
+                .clone()
+                .map(UciOption::SyzygyProbeLimit),
+            UciOption::Threads(_) => b.threads.clone().map(UciOption::Threads),
+            UciOption::UCIChess960 { .. } => b
+                .uci_chess_960
+                .clone()
+                .map(|default| UciOption::UCIChess960 { default }),
+            UciOption::UCIElo(_) => b.uci_elo.clone().map(UciOption::UCIElo),
+            UciOption::UCILimitStrength { .. } => b
+                .uci_limit_strength
+                .clone()
+                .map(|default| UciOption::UCILimitStrength { default }),
+            UciOption::UCIShowWDL { .. } => b
+                .uci_show_wdl
+                .clone()
+                .map(|default| UciOption::UCIShowWDL { default }),
+            UciOption::Custom { name, .. } => {
+                b.custom.get(name).map(|typed_data| UciOption::Custom {
+                    name: name.clone(),
+                    typed_data: typed_data.clone(),
+                })
+            }
+        }
+    }
+
+    /// The options advertised by the engine: the standard ones in the
+    /// [`CANONICAL_ORDER`](crate::options::ordering::CANONICAL_ORDER) followed by the custom ones
+    /// in the order of declaration.
+    pub fn options(&self) -> Vec<UciOption> {
+        let b = self;
+        let mut options = Vec::new();
+
+        options.extend(b.threads.clone().map(UciOption::Threads));
+        options.extend(b.hash.clone().map(UciOption::Hash));
+        options.extend(b.multi_pv.clone().map(UciOption::MultiPV));
+        options.extend(
+            b.numa_policy
+                .clone()
+                .map(|default| UciOption::NumaPolicy { default }),
+        );
+        options.extend(b.clear_hash.map(|()| UciOption::ClearHash));
+        options.extend(
+            b.ponder
+                .clone()
+                .map(|default| UciOption::Ponder { default }),
+        );
+        options.extend(
+            b.eval_file
+                .clone()
+                .map(|default| UciOption::EvalFile { default }),
+        );
+        options.extend(
+            b.eval_file_small
+                .clone()
+                .map(|default| UciOption::EvalFileSmall { default }),
+        );
+        options.extend(
+            b.uci_chess_960
+                .clone()
+                .map(|default| UciOption::UCIChess960 { default }),
+        );
+        options.extend(
+            b.uci_show_wdl
+                .clone()
+                .map(|default| UciOption::UCIShowWDL { default }),
+        );
+        options.extend(
+            b.uci_limit_strength
+                .clone()
+                .map(|default| UciOption::UCILimitStrength { default }),
+        );
+        options.extend(b.uci_elo.clone().map(UciOption::UCIElo));
+        options.extend(b.skill_level.clone().map(UciOption::SkillLevel));
+        options.extend(
+            b.syzygy_path
+                .clone()
+                .map(|default| UciOption::SyzygyPath { default }),
+        );
+        options.extend(
+            b.syzygy_probe_depth
+                .clone()
+                .map(UciOption::SyzygyProbeDepth),
+        );
+        options.extend(
+            b.syzygy_50_move_rule
+                .clone()
+                .map(|default| UciOption::Syzygy50MoveRule { default }),
+        );
+        options.extend(
+            b.syzygy_probe_limit
+                .clone()
+                .map(UciOption::SyzygyProbeLimit),
+        );
+        options.extend(b.move_overhead.clone().map(UciOption::MoveOverhead));
+        options.extend(b.nodestime.clone().map(UciOption::Nodestime));
+        options.extend(
+            b.debug_log_file
+                .clone()
+                .map(|default| UciOption::DebugLogFile { default }),
+        );
+
+        options.sort_by_key(|option| option.kind().map(canonical_position));
+        options.extend(b.custom.iter().map(|(name, typed_data)| UciOption::Custom {
+            name: name.clone(),
+            typed_data: typed_data.clone(),
+        }));
+
+        options
+    }
+
+    /// Finds the advertised option by its name. As per the UCI protocol, the name is case-insensitive.
+    pub fn option(&self, name: &str) -> Option<UciOption> {
+        self.options()
+            .into_iter()
+            .find(|option| option.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The strengths for a `UCI_Elo` picker: the bounds advertised by the engine and the multiples
+    /// of 200 between them, or `None` if the engine doesn't advertise `UCI_Elo`.
+    pub fn elo_presets(&self) -> Option<Vec<i64>> {
+        self.uci_elo
+            .as_ref()
+            .map(|spin| spin.stepped_by(200).collect())
+    }
+
+    /// The levels for a `Skill Level` picker: the bounds advertised by the engine and the multiples
+    /// of 5 between them, or `None` if the engine doesn't advertise `Skill Level`.
+    pub fn skill_level_presets(&self) -> Option<Vec<i64>> {
+        self.skill_level
+            .as_ref()
+            .map(|spin| spin.stepped_by(5).collect())
+    }
+
+    /// Builds the block, taking the options that the engine didn't advertise from `defaults`,
+    /// e.g. from [`UciOptionBlock::stockfish_defaults`].
+    ///
+    /// The custom options of `defaults` are added unless the engine advertised an option with the same name.
+    pub fn build_with_defaults(&self, defaults: &UciOptionBlock) -> UciOptionBlock {
+        let b = self.clone();
+        let d = defaults;
+
+        let mut custom = b.custom;
+        for (name, typed_data) in d.custom.iter() {
+            if !custom.contains_key(name) {
+                custom.insert(name.clone(), typed_data.clone());
+            }
+        }
+
+        UciOptionBlock {
+            threads: b.threads.unwrap_or_else(|| d.threads.clone()),
+            hash: b.hash.unwrap_or_else(|| d.hash.clone()),
+            multi_pv: b.multi_pv.unwrap_or_else(|| d.multi_pv.clone()),
+            numa_policy: b.numa_policy.unwrap_or_else(|| d.numa_policy.clone()),
+            clear_hash: (),
+            ponder: b.ponder.unwrap_or_else(|| d.ponder.clone()),
+            eval_file: b.eval_file.unwrap_or_else(|| d.eval_file.clone()),
+            eval_file_small: b
+                .eval_file_small
+                .unwrap_or_else(|| d.eval_file_small.clone()),
+            uci_chess_960: b.uci_chess_960.unwrap_or_else(|| d.uci_chess_960.clone()),
+            uci_show_wdl: b.uci_show_wdl.unwrap_or_else(|| d.uci_show_wdl.clone()),
+            uci_limit_strength: b
+                .uci_limit_strength
+                .unwrap_or_else(|| d.uci_limit_strength.clone()),
+            uci_elo: b.uci_elo.unwrap_or_else(|| d.uci_elo.clone()),
+            skill_level: b.skill_level.unwrap_or_else(|| d.skill_level.clone()),
+            syzygy_path: b.syzygy_path.unwrap_or_else(|| d.syzygy_path.clone()),
+            syzygy_probe_depth: b
+                .syzygy_probe_depth
+                .unwrap_or_else(|| d.syzygy_probe_depth.clone()),
+            syzygy_50_move_rule: b
+                .syzygy_50_move_rule
+                .unwrap_or_else(|| d.syzygy_50_move_rule.clone()),
+            syzygy_probe_limit: b
+                .syzygy_probe_limit
+                .unwrap_or_else(|| d.syzygy_probe_limit.clone()),
+            move_overhead: b.move_overhead.unwrap_or_else(|| d.move_overhead.clone()),
+            nodestime: b.nodestime.unwrap_or_else(|| d.nodestime.clone()),
+            debug_log_file: b.debug_log_file.unwrap_or_else(|| d.debug_log_file.clone()),
+            custom,
+        }
+    }
+
+    /// Freezes the advertised options as they are, without requiring every standard option.
+    pub fn build_partial(&self) -> PartialUciOptionBlock {
+        PartialUciOptionBlock {
+            builder: self.clone(),
         }
     }
 }
 
+impl UciOptionBlock {
+    /// The options of the block: the standard ones in the [`CANONICAL_ORDER`](crate::options::ordering::CANONICAL_ORDER)
+    /// followed by the custom ones in the order of declaration, i.e. the order Stockfish advertises them in.
+    ///
+    /// ```
+    /// use uci_beyond::{engine_commands::OptionCommand, options::UciOptionBlock};
+    ///
+    /// let mut lines = UciOptionBlock::stockfish_defaults()
+    ///     .iter_canonical()
+    ///     .map(|option| OptionCommand(option).to_string());
+    /// assert_eq!(
+    ///     lines.next().unwrap(),
+    ///     "option name Debug Log File type string default <empty>"
+    /// );
+    /// ```
+    pub fn iter_canonical(&self) -> impl Iterator<Item = UciOption> {
+        let builder = UciOptionBlockBuilder {
+            threads: Some(self.threads.clone()),
+            hash: Some(self.hash.clone()),
+            multi_pv: Some(self.multi_pv.clone()),
+            numa_policy: Some(self.numa_policy.clone()),
+            clear_hash: Some(()),
+            ponder: Some(self.ponder.clone()),
+            eval_file: Some(self.eval_file.clone()),
+            eval_file_small: Some(self.eval_file_small.clone()),
+            uci_chess_960: Some(self.uci_chess_960.clone()),
+            uci_show_wdl: Some(self.uci_show_wdl.clone()),
+            uci_limit_strength: Some(self.uci_limit_strength.clone()),
+            uci_elo: Some(self.uci_elo.clone()),
+            skill_level: Some(self.skill_level.clone()),
+            syzygy_path: Some(self.syzygy_path.clone()),
+            syzygy_probe_depth: Some(self.syzygy_probe_depth.clone()),
+            syzygy_50_move_rule: Some(self.syzygy_50_move_rule.clone()),
+            syzygy_probe_limit: Some(self.syzygy_probe_limit.clone()),
+            move_overhead: Some(self.move_overhead.clone()),
+            nodestime: Some(self.nodestime.clone()),
+            debug_log_file: Some(self.debug_log_file.clone()),
+            custom: self.custom.clone(),
+        };
+        builder.options().into_iter()
+    }
+}
+
+/// The options advertised by an engine that may lack some of the standard ones,
+/// e.g. for a snapshot of the capabilities of an engine (see [`UciOptionBlockBuilder::build_partial`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialUciOptionBlock {
+    builder: UciOptionBlockBuilder,
+}
+
+impl PartialUciOptionBlock {
+    /// See [`UciOptionBlockBuilder::options`].
+    pub fn options(&self) -> Vec<UciOption> {
+        self.builder.options()
+    }
+
+    /// See [`UciOptionBlockBuilder::option`].
+    pub fn option(&self, name: &str) -> Option<UciOption> {
+        self.builder.option(name)
+    }
+
+    /// The standard option of the kind, if advertised.
+    pub fn get(&self, kind: UciOptionKind) -> Option<UciOption> {
+        self.options()
+            .into_iter()
+            .find(|option| option.kind() == Some(kind))
+    }
+
+    /// The standard options that weren't advertised.
+    pub fn missing(&self) -> Vec<UciOptionKind> {
+        use strum::IntoEnumIterator as _;
+
+        UciOptionKind::iter()
+            .filter(|&kind| self.get(kind).is_none())
+            .collect()
+    }
+
+    /// See [`UciOptionBlockBuilder::build_with_defaults`].
+    pub fn with_defaults(&self, defaults: &UciOptionBlock) -> UciOptionBlock {
+        self.builder.build_with_defaults(defaults)
+    }
+
+    pub fn into_builder(self) -> UciOptionBlockBuilder {
+        self.builder
+    }
+}
+
 impl From<engine_commands::OptionCommandParsingError> for OptionBlockParsingError {
     fn from(err: engine_commands::OptionCommandParsingError) -> Self {
         OptionBlockParsingError::CommandErrorParsingError(err)
@@ -220,34 +525,34 @@ impl From<engine_commands::OptionCommandParsingError> for OptionBlockParsingErro
 mod tests {
     use super::*;
 
-    use crate::{model, options};
+    use crate::{
+        command::Command as _,
+        fixtures::{COMBO_OPTION_BLOCK, STOCKFISH_OPTION_BLOCK},
+        model, options,
+    };
 
     #[tokio::test]
-    async fn test_parse_uci_option_block() {
-        let input = "option name Debug Log File type string default <empty>\n\
-           option name NumaPolicy type string default auto\n\
-           option name Threads type spin default 1 min 1 max 1024\n\
-           option name Hash type spin default 16 min 1 max 33554432\n\
-           option name Clear Hash type button\n\
-           option name Ponder type check default false\n\
-           option name MultiPV type spin default 1 min 1 max 256\n\
-           option name Skill Level type spin default 20 min 0 max 20\n\
-           option name Move Overhead type spin default 10 min 0 max 5000\n\
-           option name nodestime type spin default 0 min 0 max 10000\n\
-           option name UCI_Chess960 type check default false\n\
-           option name UCI_LimitStrength type check default false\n\
-           option name UCI_Elo type spin default 1320 min 1320 max 3190\n\
-           option name UCI_ShowWDL type check default false\n\
-           option name SyzygyPath type string default <empty>\n\
-           option name SyzygyProbeDepth type spin default 1 min 1 max 100\n\
-           option name Syzygy50MoveRule type check default true\n\
-           option name SyzygyProbeLimit type spin default 7 min 0 max 7\n\
-           option name EvalFile type string default nn-1c0000000000.nnue\n\
-           option name EvalFileSmall type string default nn-37f18f62d772.nnue\n\
-           \n\
-           \n";
+    async fn test_stockfish_presets() {
+        let mut reader = tokio::io::BufReader::new(STOCKFISH_OPTION_BLOCK.as_bytes());
+        let block = UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
 
-        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        // `UCI_Elo` of Stockfish is within [1320, 3190]
+        let mut elo = vec![1320];
+        elo.extend((1400..=3000).step_by(200));
+        elo.push(3190);
+        assert_eq!(block.elo_presets(), Some(elo));
+        assert_eq!(block.skill_level_presets(), Some(vec![0, 5, 10, 15, 20]));
+
+        assert_eq!(UciOptionBlockBuilder::default().elo_presets(), None);
+    }
+
+    #[tokio::test]
+    async fn test_parse_uci_option_block() {
+        let mut reader = tokio::io::BufReader::new(STOCKFISH_OPTION_BLOCK.as_bytes());
         let uci_option_block_builder = UciOptionBlockBuilder::read_from(&mut reader)
             .await
             .unwrap()
@@ -346,11 +651,336 @@ mod tests {
         );
         assert_eq!(
             uci_option_block.eval_file,
-            model::UciString("nn-1c0000000000.nnue".to_string())
+            model::UciString::from("nn-1c0000000000.nnue")
         );
         assert_eq!(
             uci_option_block.eval_file_small,
-            model::UciString("nn-37f18f62d772.nnue".to_string())
+            model::UciString::from("nn-37f18f62d772.nnue")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_advertised_options() {
+        let input = "option name MultiPV type spin default 1 min 1 max 256\n\
+           option name My Custom type check default true\n\
+           option name Threads type spin default 1 min 1 max 1024\n\
+           \n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let builder = UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        let names: Vec<String> = builder
+            .options()
+            .iter()
+            .map(|option| option.name().to_string())
+            .collect();
+        assert_eq!(names, ["Threads", "MultiPV", "My Custom"]);
+
+        assert_eq!(
+            builder.option("multipv"),
+            Some(UciOption::MultiPV(options::Spin {
+                default: 1,
+                min: 1,
+                max: 256,
+            }))
+        );
+        assert_eq!(builder.option("Hash"), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_combo_option() {
+        let mut reader = tokio::io::BufReader::new(COMBO_OPTION_BLOCK.as_bytes());
+        let builder = UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(builder.options().len(), 3);
+
+        let Some(options::TypedUciOptionData::Combo(combo)) = builder
+            .option("Personality")
+            .map(|option| option.typed_data())
+        else {
+            panic!("Expected a combo");
+        };
+        assert_eq!(combo.default, model::UciString::from("Default"));
+        assert_eq!(
+            combo.vars,
+            ["Default", "Aggressive", "Defensive", "Human"].map(model::UciString::from)
         );
     }
+
+    #[tokio::test]
+    async fn test_read_uci_option_block_until_eof() {
+        let input = "option name Threads type spin default 1 min 1 max 1024\n\
+           option name Ponder type check default false\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let builder = UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(builder.options().len(), 2);
+        assert_eq!(builder.ponder, Some(model::Check(false)));
+
+        let mut reader = tokio::io::BufReader::new(&b""[..]);
+        assert!(
+            UciOptionBlockBuilder::read_from(&mut reader)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_option_block_matches_whole_command_name() {
+        let input = "option name Threads type spin default 1 min 1 max 1024\n\
+           \t option name Ponder type check default false\n\
+           optionset name Hash type spin default 16 min 1 max 33554432\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let builder = UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(builder.options().len(), 2);
+        assert_eq!(builder.ponder, Some(model::Check(false)));
+
+        // The line that ended the block is left for the next reader
+        assert_eq!(
+            crate::util::read_line(&mut reader)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("optionset name Hash type spin default 16 min 1 max 33554432")
+        );
+
+        assert!(OptionCommand::matches_line("option name Hash"));
+        assert!(OptionCommand::matches_line("  option"));
+        assert!(!OptionCommand::matches_line("optionset name Hash"));
+        assert!(!OptionCommand::matches_line(""));
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_option_block_followed_by_uciok() {
+        let input = "option name Threads type spin default 1 min 1 max 1024\n\
+           uciok\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let builder = UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(builder.options().len(), 1);
+
+        // `uciok` is left for the next reader
+        let uciok = engine_commands::UciOkCommand::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(uciok.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_option_block_errors() {
+        async fn read(
+            input: &str,
+        ) -> Option<Result<UciOptionBlockBuilder, command::parsing::Error<OptionBlockParsingError>>>
+        {
+            let mut reader = tokio::io::BufReader::new(input.as_bytes());
+            UciOptionBlockBuilder::read_from(&mut reader).await.unwrap()
+        }
+
+        match read(
+            "option name Hash type spin default 16 min 1 max 1024\n\
+             option name Hash type spin default 16 min 1 max 2048\n",
+        )
+        .await
+        {
+            Some(Err(command::parsing::Error::CustomError(
+                OptionBlockParsingError::RepeatedOption {
+                    kind_or_name,
+                    first,
+                    second,
+                },
+            ))) => {
+                assert_eq!(kind_or_name, "Hash");
+                assert_eq!(
+                    first,
+                    "option name Hash type spin default 16 min 1 max 1024"
+                );
+                assert_eq!(
+                    second,
+                    "option name Hash type spin default 16 min 1 max 2048"
+                );
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        match read(
+            "option name Contempt type spin default 0 min 0 max 100\n\
+             option name Contempt type check default false\n",
+        )
+        .await
+        {
+            Some(Err(command::parsing::Error::CustomError(
+                OptionBlockParsingError::RepeatedOption {
+                    kind_or_name,
+                    second,
+                    ..
+                },
+            ))) => {
+                assert_eq!(kind_or_name, "Contempt");
+                assert_eq!(second, "option name Contempt type check default false");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(matches!(
+            read("option name Hash type spin default 16 min 1\n").await,
+            Some(Err(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_build_truncated_block() {
+        let input = "option name Threads type spin default 1 min 1 max 512\n\
+           option name Hash type spin default 64 min 1 max 1024\n\
+           option name Analyse Mode type check default false\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let builder = UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        // Without defaults
+        assert!(UciOptionBlock::try_from(builder.clone()).is_err());
+        let partial = builder.build_partial();
+        assert_eq!(
+            partial.get(UciOptionKind::Hash),
+            Some(UciOption::Hash(options::Spin {
+                default: 64,
+                min: 1,
+                max: 1024,
+            }))
+        );
+        assert_eq!(partial.missing().len(), 18);
+        assert!(!partial.missing().contains(&UciOptionKind::Threads));
+        assert!(partial.missing().contains(&UciOptionKind::EvalFileSmall));
+
+        // With defaults
+        let defaults = UciOptionBlock::stockfish_defaults();
+        let block = builder.build_with_defaults(&defaults);
+        assert_eq!(block.threads.max, 512);
+        assert_eq!(block.hash.default, 64);
+        assert_eq!(block.eval_file_small, defaults.eval_file_small);
+        assert_eq!(block.numa_policy, model::NumaPolicy::Auto);
+        assert!(block.custom.contains_key("Analyse Mode"));
+        assert_eq!(block, partial.with_defaults(&defaults));
+
+        // A complete block doesn't need the defaults
+        let mut reader = tokio::io::BufReader::new(STOCKFISH_OPTION_BLOCK.as_bytes());
+        let builder = UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let mut other_defaults = defaults.clone();
+        other_defaults.threads.max = 1;
+        assert_eq!(builder.build_with_defaults(&other_defaults), defaults);
+        assert!(builder.build_partial().missing().is_empty());
+    }
+
+    /// The loop of the reader of [`UciOptionBlockBuilder`] before it was ported onto [`read_block_with`],
+    /// kept as the reference of the port. The options are added as by the ported reader.
+    async fn legacy_read_option_block<R>(
+        reader: &mut R,
+    ) -> Result<
+        Option<Result<UciOptionBlockBuilder, command::parsing::Error<OptionBlockParsingError>>>,
+        R::Error,
+    >
+    where
+        R: StreamingLineReader,
+    {
+        use crate::util::{LineHandlerOutcome, handle_next_line};
+
+        let mut i = 0;
+        let mut b = UciOptionBlockBuilder::default();
+
+        loop {
+            let opt = handle_next_line(reader, |line: &str| {
+                if !line.trim_start().starts_with("option") {
+                    return LineHandlerOutcome::Peeked;
+                }
+                match line.parse::<OptionCommand>() {
+                    Ok(cmd) => LineHandlerOutcome::Read(cmd),
+                    Err(e) => LineHandlerOutcome::Error(e),
+                }
+            })
+            .await?;
+
+            let cmd = match opt {
+                None | Some(LineHandlerOutcome::Peeked) => return Ok((i > 0).then_some(Ok(b))),
+                Some(LineHandlerOutcome::Read(cmd)) => cmd,
+                Some(LineHandlerOutcome::Error(e)) => {
+                    return e.map_custom(OptionBlockParsingError::from).wrap();
+                }
+            };
+            if let Err(e) = b.add(cmd.0) {
+                return e.wrap();
+            }
+            i += 1;
+        }
+    }
+
+    /// The outcomes of the ported and of the legacy reader: the read block and the line left for the next reader.
+    async fn outcomes(input: &str) -> [(String, Option<String>); 2] {
+        use crate::util::read_line;
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let ported = UciOptionBlockBuilder::read_from(&mut reader).await.unwrap();
+        let ported = (format!("{ported:?}"), read_line(&mut reader).await.unwrap());
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let legacy = legacy_read_option_block(&mut reader).await.unwrap();
+        let legacy = (format!("{legacy:?}"), read_line(&mut reader).await.unwrap());
+
+        [ported, legacy]
+    }
+
+    #[tokio::test]
+    async fn test_ported_option_block_reader_is_equivalent() {
+        use crate::fixtures::{REORDERED_OPTION_CLAUSES, STOCKFISH_UCI_RESPONSE};
+
+        let with_uciok = format!("{STOCKFISH_OPTION_BLOCK}uciok\n");
+        for input in [
+            STOCKFISH_OPTION_BLOCK,
+            &with_uciok,
+            STOCKFISH_UCI_RESPONSE,
+            COMBO_OPTION_BLOCK,
+            REORDERED_OPTION_CLAUSES,
+            "",
+            "\n",
+            "option name Hash type spin default 16 min 1 max 1024\n\
+             option name hash type spin default 16 min 1 max 1024\n",
+            "option name Hash type spinn default 16\nuciok\n",
+        ] {
+            let [ported, legacy] = outcomes(input).await;
+            assert_eq!(ported, legacy, "{input:?}");
+        }
+
+        // The port differs on purpose for a line of another command that starts with `option`:
+        // it ends the block rather than failing to parse
+        let [ported, legacy] = outcomes("optionset name Hash value 16\n").await;
+        assert_eq!(ported.0, "None");
+        assert_eq!(ported.1.as_deref(), Some("optionset name Hash value 16"));
+        assert!(legacy.0.starts_with("Some(Err("));
+    }
 }
@@ -0,0 +1,86 @@
+use std::fmt::Display;
+
+use async_trait::async_trait;
+
+use crate::{
+    command::{self, Command as _},
+    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
+};
+
+/// <https://backscattering.de/chess/uci/#engine-readyok>
+///
+/// This must be sent when the engine has received an `isready` command and has processed all input and is ready to accept new commands now.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ReadyOkCommand;
+
+impl Display for ReadyOkCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
+
+impl command::Command for ReadyOkCommand {
+    type ParsingError = ReadyOkCommandParsingError;
+
+    const NAME: &'static str = "readyok";
+}
+
+/// `readyok` has no arguments, so the only way to fail is to see another command, which is reported as
+/// [`command::parsing::Error::UnexpectedCommand`].
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ReadyOkCommandParsingError {}
+
+#[async_trait(?Send)]
+impl AsyncReadable for ReadyOkCommand {
+    type Err = command::parsing::Error<ReadyOkCommandParsingError>;
+
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let f = |line: &str| {
+            if line.trim() == ReadyOkCommand::NAME {
+                LineHandlerOutcome::Read(ReadyOkCommand)
+            } else {
+                LineHandlerOutcome::Error(command::parsing::Error::UnexpectedCommand(
+                    line.to_string(),
+                ))
+            }
+        };
+
+        match handle_next_line(reader, f).await? {
+            Some(LineHandlerOutcome::Read(cmd)) => Ok(Some(Ok(cmd))),
+            Some(LineHandlerOutcome::Error(e)) => Ok(Some(Err(e))),
+            Some(LineHandlerOutcome::Peeked) => {
+                return command::parsing::Error::UnexpectedPeekOutput.wrap();
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_readyok_command() {
+        let input = "info string NNUE evaluation using nn-1c0000000000.nnue\nreadyok\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+
+        let err = ReadyOkCommand::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(err, command::parsing::Error::UnexpectedCommand(_)));
+
+        let cmd = ReadyOkCommand::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(cmd, ReadyOkCommand);
+    }
+}
@@ -3,20 +3,26 @@ use std::fmt::Display;
 use async_trait::async_trait;
 
 use crate::{
-    command,
+    command::{self, Command as _},
     util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
 };
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct UciOkCommand;
 
 impl Display for UciOkCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "uciok")
+        write!(f, "{}", Self::NAME)
     }
 }
 
-#[derive(Debug, thiserror::Error)]
+impl command::Command for UciOkCommand {
+    type ParsingError = UciOkCommandParsingError;
+
+    const NAME: &'static str = "uciok";
+}
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
 #[error("UciOkCommand parsing error. Unexpected input: {0}")]
 pub struct UciOkCommandParsingError(String);
 
@@ -35,7 +41,7 @@ impl AsyncReadable for UciOkCommand {
                 if trimmed.is_empty() {
                     // Skip empty lines
                     LineHandlerOutcome::Read(None)
-                } else if trimmed == "uciok" {
+                } else if trimmed == UciOkCommand::NAME {
                     LineHandlerOutcome::Read(Some(UciOkCommand))
                 } else {
                     LineHandlerOutcome::Error(command::parsing::Error::CustomError(
@@ -0,0 +1,166 @@
+//! The module for [`Quirks`], i.e. the parser leniencies needed by particular engines,
+//! selected from the engine name in the [`IdBlock`] via a small builtin database (see [`EngineFamily`]).
+
+use crate::engine_commands::IdBlock;
+
+/// The engines known to the builtin quirk database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineFamily {
+    Stockfish,
+    /// [Leela Chess Zero](https://lczero.org/).
+    Lc0,
+    Komodo,
+    Ethereal,
+    /// Any other engine. It gets the strict [`Quirks::default`].
+    Unknown,
+}
+
+impl EngineFamily {
+    /// Identifies the engine by its `id name`, e.g. `Lc0 v0.31.2` or `Stockfish 17.1`.
+    pub fn from_id_name(name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        let first_word = name.split_whitespace().next().unwrap_or_default();
+
+        if first_word.starts_with("stockfish") {
+            EngineFamily::Stockfish
+        } else if first_word == "lc0" || name.contains("leela") {
+            EngineFamily::Lc0
+        } else if first_word.starts_with("komodo") || first_word.starts_with("dragon") {
+            EngineFamily::Komodo
+        } else if first_word.starts_with("ethereal") {
+            EngineFamily::Ethereal
+        } else {
+            EngineFamily::Unknown
+        }
+    }
+}
+
+/// The parser leniencies for an engine that doesn't follow the UCI protocol to the letter.
+///
+/// The default is strict, i.e. no leniencies, which is what Stockfish needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Quirks {
+    /// Skip the lines that can't be parsed instead of failing, e.g. the diagnostic output
+    /// that Lc0 prints with `VerboseMoveStats` or the text between the options and `uciok`.
+    pub skip_unknown_lines: bool,
+    /// The default of a `string` option extends to the end of the line, e.g.
+    /// `option name WeightsFile type string default <autodiscover> or a path`.
+    ///
+    /// Strictly, the default is a single token.
+    pub string_defaults_to_eol: bool,
+    /// Match the names of the standard options case-insensitively, e.g. `hash` as `Hash`.
+    ///
+    /// Strictly, such options are custom ones (see
+    /// [`UnknownUciOptionKind::case_insensitive_match`](crate::options::UnknownUciOptionKind::case_insensitive_match)).
+    pub case_insensitive_option_names: bool,
+    /// Keep an option advertised again, e.g. the second `EvalFile` of the forks that advertise one per net,
+    /// in [`UciResponse::repeated_options`](crate::gui_command_responses::UciResponse::repeated_options)
+    /// instead of failing. Only the standard options are kept; a repeated custom option still fails.
+    ///
+    /// Strictly, an option is advertised once.
+    pub collect_repeated_options: bool,
+    /// Accept the clauses of an `option` line in any order, e.g. `option type spin name Threads default 1 min 1 max 512`.
+    /// A line is reordered only when it fails to parse in the standard order, so a name with a clause keyword,
+    /// e.g. `Use min depth`, is kept as long as the clauses are in order. The name of a reordered line extends
+    /// to the next clause keyword (`type`, `default`, `min`, `max` or `var`) rather than to the last type clause,
+    /// so it can't contain one.
+    ///
+    /// Strictly, `name` comes first and is followed by `type`.
+    pub reordered_option_clauses: bool,
+    /// The engine acknowledges `stop` with a line of its own before `bestmove`, e.g. the
+    /// `info string stop acknowledged` of the hosted services that multiplex several clients on one engine.
+    /// The acknowledgment is kept in [`StopResponse::acknowledgment`](crate::gui_command_responses::StopResponse::acknowledgment)
+    /// and routed only to the stopped search (see [`Router::stop`](crate::util::Router::stop)).
+    ///
+    /// Strictly, `stop` is only answered with the end of the search.
+    pub ack_before_bestmove: bool,
+}
+
+impl Quirks {
+    /// The quirks of the engine family according to the builtin database.
+    pub fn for_family(family: EngineFamily) -> Self {
+        match family {
+            EngineFamily::Stockfish | EngineFamily::Unknown => Quirks::default(),
+            EngineFamily::Lc0 => Quirks {
+                skip_unknown_lines: true,
+                string_defaults_to_eol: true,
+                case_insensitive_option_names: false,
+                collect_repeated_options: false,
+                reordered_option_clauses: false,
+                ack_before_bestmove: false,
+            },
+            EngineFamily::Komodo => Quirks {
+                skip_unknown_lines: false,
+                string_defaults_to_eol: true,
+                case_insensitive_option_names: true,
+                collect_repeated_options: false,
+                reordered_option_clauses: false,
+                ack_before_bestmove: false,
+            },
+            EngineFamily::Ethereal => Quirks {
+                skip_unknown_lines: false,
+                string_defaults_to_eol: false,
+                case_insensitive_option_names: true,
+                collect_repeated_options: false,
+                reordered_option_clauses: false,
+                ack_before_bestmove: false,
+            },
+        }
+    }
+
+    /// The quirks of the engine identified by the family of the `id name` of the [`IdBlock`]
+    /// (see [`IdBlock::parsed_name`]).
+    pub fn for_id_block(id_block: &IdBlock) -> Self {
+        Self::for_family(EngineFamily::from_id_name(&id_block.parsed_name().family))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id_block(name: &str) -> IdBlock {
+        IdBlock {
+            name: name.to_string(),
+            author: "Somebody".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_engine_family_from_id_name() {
+        assert_eq!(
+            EngineFamily::from_id_name("Stockfish 17.1"),
+            EngineFamily::Stockfish
+        );
+        assert_eq!(EngineFamily::from_id_name("Lc0 v0.31.2"), EngineFamily::Lc0);
+        assert_eq!(
+            EngineFamily::from_id_name("Komodo Dragon 3.3"),
+            EngineFamily::Komodo
+        );
+        assert_eq!(
+            EngineFamily::from_id_name("Ethereal 14.25"),
+            EngineFamily::Ethereal
+        );
+        assert_eq!(
+            EngineFamily::from_id_name("MyEngine 1.0"),
+            EngineFamily::Unknown
+        );
+    }
+
+    #[test]
+    fn test_lc0_quirks() {
+        let quirks = Quirks::for_id_block(&id_block("Lc0 v0.31.2"));
+        assert!(quirks.skip_unknown_lines);
+        assert!(quirks.string_defaults_to_eol);
+        assert!(!quirks.case_insensitive_option_names);
+
+        assert_eq!(
+            Quirks::for_id_block(&id_block("Stockfish 17.1")),
+            Quirks::default()
+        );
+        assert_eq!(
+            Quirks::for_id_block(&id_block("Lc0 v0.32.0-dev+git.abc123")),
+            quirks
+        );
+    }
+}
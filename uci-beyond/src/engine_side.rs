@@ -0,0 +1,311 @@
+//! The module for writing engines rather than GUIs with the crate, e.g. [`OptionRegistry`] for the options
+//! of the engine, along with the readers of the [`gui_commands`](crate::gui_commands), e.g.
+//! [`AnyGuiCommand`](crate::gui_commands::AnyGuiCommand).
+
+use crate::{
+    engine_commands::{OptionCommand, UciOptionBlockBuilder},
+    gui_commands::SetOptionCommand,
+    options::{OptionState, OptionStateError, OptionValue},
+};
+
+/// The callback of an option of an [`OptionRegistry`], which receives the checked value of a `setoption`.
+///
+/// The error, e.g. for a net that fails to load, is reported as [`OptionRegistryError::Rejected`].
+pub type OptionHandler = Box<dyn FnMut(OptionValue) -> Result<(), String> + Send>;
+
+/// A problem found by [`OptionRegistry::register`] or [`OptionRegistry::apply`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OptionRegistryError {
+    #[error("The option `{name}` is already registered.")]
+    AlreadyRegistered { name: String },
+    /// The `setoption` doesn't fit the declaration of the option, so the handler isn't called.
+    #[error("{0}")]
+    Invalid(OptionStateError),
+    #[error("The option `{name}` rejected the value: {message}")]
+    Rejected { name: String, message: String },
+}
+
+impl From<OptionStateError> for OptionRegistryError {
+    fn from(err: OptionStateError) -> Self {
+        OptionRegistryError::Invalid(err)
+    }
+}
+
+/// The options declared by an engine, each with the handler of its `setoption` commands.
+///
+/// A `setoption` is checked against the declaration of the option, e.g. that a spin is within its bounds,
+/// before it reaches the handler, and the value is kept only if the handler accepts it.
+///
+/// ```
+/// use uci_beyond::{
+///     engine_commands::OptionCommand, engine_side::OptionRegistry, gui_commands::SetOptionCommand,
+///     options::OptionValue,
+/// };
+///
+/// let mut registry = OptionRegistry::new();
+/// registry
+///     .register(OptionCommand::spin("Hash", 16, 1, 1024).unwrap(), |value| {
+///         println!("resizing the hash table to {value:?}");
+///         Ok(())
+///     })
+///     .unwrap();
+///
+/// let cmd: SetOptionCommand = "setoption name Hash value 64".parse().unwrap();
+/// registry.apply(&cmd).unwrap();
+/// assert_eq!(registry.value("Hash"), Some(&OptionValue::Spin(64)));
+///
+/// let cmd: SetOptionCommand = "setoption name Hash value 4096".parse().unwrap();
+/// assert!(registry.apply(&cmd).is_err());
+/// assert_eq!(
+///     registry.render_block(),
+///     "option name Hash type spin default 16 min 1 max 1024\n"
+/// );
+/// ```
+#[derive(Default)]
+pub struct OptionRegistry {
+    declarations: UciOptionBlockBuilder,
+    state: OptionState,
+    handlers: Vec<(String, OptionHandler)>,
+}
+
+impl OptionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares the option, e.g. made with [`OptionCommand::spin`], with the handler of its `setoption` commands.
+    ///
+    /// Fails if an option with the same name is already registered. As per the UCI protocol, the names
+    /// are case-insensitive, so `hash` is the same option as `Hash`.
+    pub fn register<F>(
+        &mut self,
+        OptionCommand(option): OptionCommand,
+        handler: F,
+    ) -> Result<(), OptionRegistryError>
+    where
+        F: FnMut(OptionValue) -> Result<(), String> + Send + 'static,
+    {
+        let name = option.name().to_string();
+        let registered = self
+            .handlers
+            .iter()
+            .any(|(registered, _)| registered.eq_ignore_ascii_case(&name));
+        if registered || self.declarations.add(option.clone()).is_err() {
+            return Err(OptionRegistryError::AlreadyRegistered { name });
+        }
+        self.state.push(option);
+        self.handlers.push((name, Box::new(handler)));
+        Ok(())
+    }
+
+    /// Checks the `setoption` against the declaration of the option and hands the value over to its handler.
+    ///
+    /// As per the UCI protocol, the name is case-insensitive. The value is kept only if the handler accepts it.
+    pub fn apply(&mut self, cmd: &SetOptionCommand) -> Result<(), OptionRegistryError> {
+        let value = self.state.check(cmd)?;
+        let (name, handler) = self
+            .handlers
+            .iter_mut()
+            .find(|(name, _)| name.eq_ignore_ascii_case(cmd.name()))
+            .expect("every checked option has a handler");
+        handler(value).map_err(|message| OptionRegistryError::Rejected {
+            name: name.clone(),
+            message,
+        })?;
+        self.state.apply(cmd)?;
+        Ok(())
+    }
+
+    /// The current value of the option, starting with its default.
+    pub fn value(&self, name: &str) -> Option<&OptionValue> {
+        self.state.get(name)
+    }
+
+    /// The `option` lines for the response to `uci`, each terminated with a newline, in the order of
+    /// [`UciOptionBlockBuilder::options`].
+    pub fn render_block(&self) -> String {
+        self.declarations
+            .options()
+            .into_iter()
+            .map(|option| format!("{}\n", OptionCommand(option)))
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for OptionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OptionRegistry")
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn setoption(line: &str) -> SetOptionCommand {
+        line.parse().unwrap()
+    }
+
+    #[test]
+    fn test_register_and_apply_options() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str| {
+            let received = Arc::clone(&received);
+            move |value: OptionValue| -> Result<(), String> {
+                received.lock().unwrap().push((name, value));
+                Ok(())
+            }
+        };
+
+        let mut registry = OptionRegistry::new();
+        registry
+            .register(
+                OptionCommand::spin("Threads", 1, 1, 512).unwrap(),
+                record("Threads"),
+            )
+            .unwrap();
+        registry
+            .register(
+                OptionCommand::check("Ponder", false).unwrap(),
+                record("Ponder"),
+            )
+            .unwrap();
+        registry
+            .register(
+                OptionCommand::button("Clear Hash").unwrap(),
+                record("Clear Hash"),
+            )
+            .unwrap();
+        registry
+            .register(
+                OptionCommand::combo("Style", "Normal", ["Normal", "Solid", "Risky"]).unwrap(),
+                record("Style"),
+            )
+            .unwrap();
+        registry
+            .register(
+                OptionCommand::spin("Selectivity", 2, 0, 10).unwrap(),
+                |value| match value {
+                    OptionValue::Spin(value) if value > 5 => {
+                        Err(format!("{value} is too selective"))
+                    }
+                    _ => Ok(()),
+                },
+            )
+            .unwrap();
+
+        registry
+            .apply(&setoption("setoption name Threads value 8"))
+            .unwrap();
+        registry
+            .apply(&setoption("setoption name Ponder value true"))
+            .unwrap();
+        registry
+            .apply(&setoption("setoption name Clear Hash"))
+            .unwrap();
+        registry
+            .apply(&setoption("setoption name style value risky"))
+            .unwrap();
+        assert_eq!(
+            *received.lock().unwrap(),
+            [
+                ("Threads", OptionValue::Spin(8)),
+                ("Ponder", OptionValue::Check(true)),
+                ("Clear Hash", OptionValue::Button { presses: 1 }),
+                ("Style", OptionValue::Combo("Risky".to_string())),
+            ]
+        );
+        assert_eq!(registry.value("Threads"), Some(&OptionValue::Spin(8)));
+
+        assert_eq!(
+            registry.render_block(),
+            "option name Threads type spin default 1 min 1 max 512\n\
+             option name Clear Hash type button\n\
+             option name Ponder type check default false\n\
+             option name Style type combo default Normal var Normal var Solid var Risky\n\
+             option name Selectivity type spin default 2 min 0 max 10\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_invalid_setoptions() {
+        let calls = Arc::new(Mutex::new(0));
+        let count = {
+            let calls = Arc::clone(&calls);
+            move |_: OptionValue| -> Result<(), String> {
+                *calls.lock().unwrap() += 1;
+                Ok(())
+            }
+        };
+
+        let mut registry = OptionRegistry::new();
+        registry
+            .register(OptionCommand::spin("Hash", 16, 1, 1024).unwrap(), count)
+            .unwrap();
+        registry
+            .register(
+                OptionCommand::spin("Selectivity", 2, 0, 10).unwrap(),
+                |_| Err("not now".to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(
+            registry.register(OptionCommand::check("Selectivity", true).unwrap(), |_| Ok(
+                ()
+            )),
+            Err(OptionRegistryError::AlreadyRegistered {
+                name: "Selectivity".to_string()
+            })
+        );
+        // The names are case-insensitive, for the standard options and the custom ones alike
+        assert_eq!(
+            registry.register(
+                OptionCommand::spin("selectivity", 1, 0, 5).unwrap(),
+                |_| Ok(())
+            ),
+            Err(OptionRegistryError::AlreadyRegistered {
+                name: "selectivity".to_string()
+            })
+        );
+        assert_eq!(
+            registry.register(
+                OptionCommand::spin("HASH", 16, 1, 1024).unwrap(),
+                |_| Ok(())
+            ),
+            Err(OptionRegistryError::AlreadyRegistered {
+                name: "HASH".to_string()
+            })
+        );
+        assert_eq!(registry.render_block().lines().count(), 2);
+        assert!(matches!(
+            registry.apply(&setoption("setoption name Hash value 4096")),
+            Err(OptionRegistryError::Invalid(OptionStateError::OutOfRange {
+                value: 4096,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            registry.apply(&setoption("setoption name Threads value 4")),
+            Err(OptionRegistryError::Invalid(
+                OptionStateError::UnknownOption { .. }
+            ))
+        ));
+        assert_eq!(*calls.lock().unwrap(), 0);
+        assert_eq!(registry.value("Hash"), Some(&OptionValue::Spin(16)));
+
+        // The value rejected by the handler isn't kept
+        assert_eq!(
+            registry.apply(&setoption("setoption name Selectivity value 3")),
+            Err(OptionRegistryError::Rejected {
+                name: "Selectivity".to_string(),
+                message: "not now".to_string()
+            })
+        );
+        assert_eq!(registry.value("Selectivity"), Some(&OptionValue::Spin(2)));
+    }
+}
@@ -0,0 +1,195 @@
+//! The module with the engine transcripts shared by the tests of this crate and of its users,
+//! and the [`FakeEngine`] that plays a scripted engine over a [`tokio::io::duplex`].
+//!
+//! Every line, including the last one, is terminated with `\n`, so the fixtures can be fed to a
+//! [`StreamingLineReader`](crate::util::StreamingLineReader) as they are, e.g.
+//!
+//! ```ignore
+//! let mut reader = tokio::io::BufReader::new(STOCKFISH_UCI_RESPONSE.as_bytes());
+//! ```
+
+mod fake_engine;
+
+pub use fake_engine::{DuplexConnection, FakeEngine};
+
+macro_rules! stockfish_option_block {
+    () => {
+        "option name Debug Log File type string default <empty>\n\
+option name NumaPolicy type string default auto\n\
+option name Threads type spin default 1 min 1 max 1024\n\
+option name Hash type spin default 16 min 1 max 33554432\n\
+option name Clear Hash type button\n\
+option name Ponder type check default false\n\
+option name MultiPV type spin default 1 min 1 max 256\n\
+option name Skill Level type spin default 20 min 0 max 20\n\
+option name Move Overhead type spin default 10 min 0 max 5000\n\
+option name nodestime type spin default 0 min 0 max 10000\n\
+option name UCI_Chess960 type check default false\n\
+option name UCI_LimitStrength type check default false\n\
+option name UCI_Elo type spin default 1320 min 1320 max 3190\n\
+option name UCI_ShowWDL type check default false\n\
+option name SyzygyPath type string default <empty>\n\
+option name SyzygyProbeDepth type spin default 1 min 1 max 100\n\
+option name Syzygy50MoveRule type check default true\n\
+option name SyzygyProbeLimit type spin default 7 min 0 max 7\n\
+option name EvalFile type string default nn-1c0000000000.nnue\n\
+option name EvalFileSmall type string default nn-37f18f62d772.nnue\n"
+    };
+}
+
+/// The banner that Stockfish 17.1 prints as soon as it starts, before any command.
+pub const STOCKFISH_GREETING: &str =
+    "Stockfish 17.1 by the Stockfish developers (see AUTHORS file)\n";
+
+/// The options advertised by Stockfish 17.1, i.e. every standard option.
+pub const STOCKFISH_OPTION_BLOCK: &str = stockfish_option_block!();
+
+/// The response of Stockfish 17.1 to `uci`, without the [`STOCKFISH_GREETING`].
+pub const STOCKFISH_UCI_RESPONSE: &str = concat!(
+    "id name Stockfish 17.1\n",
+    "id author the Stockfish developers (see AUTHORS file)\n",
+    "\n",
+    stockfish_option_block!(),
+    "uciok\n",
+);
+
+/// The output of Stockfish 17.1 for `go depth 5` from the start position.
+pub const GO_DEPTH5_TRANSCRIPT: &str = "info string Available processors: 0-7
+info string Using 1 thread
+info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))
+info string NNUE evaluation using nn-37f18f62d772.nnue (6MiB, (22528, 128, 15, 32, 1))
+info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 6666 hashfull 0 tbhits 0 time 3 pv e2e4
+info depth 2 seldepth 3 multipv 1 score cp 34 nodes 45 nps 11250 hashfull 0 tbhits 0 time 4 pv e2e4
+info depth 3 seldepth 4 multipv 1 score cp 42 nodes 72 nps 14400 hashfull 0 tbhits 0 time 5 pv e2e4
+info depth 4 seldepth 7 multipv 1 score cp 39 nodes 512 nps 85333 hashfull 0 tbhits 0 time 6 pv g1f3 d7d5 d2d4
+info depth 5 seldepth 7 multipv 1 score cp 58 nodes 609 nps 87000 hashfull 0 tbhits 0 time 7 pv e2e4
+bestmove e2e4 ponder d7d6
+";
+
+/// The output of Stockfish for `go infinite` from the start position, followed by `stop` after depth 13.
+pub const GO_INFINITE_STOPPED_TRANSCRIPT: &str = "info string NNUE evaluation using nn-ad9b42354671.nnue enabled
+info depth 1 seldepth 1 multipv 1 score cp 18 nodes 20 nps 4000 hashfull 0 tbhits 0 time 5 pv e2e4
+info depth 2 seldepth 2 multipv 1 score cp 46 nodes 66 nps 11000 hashfull 0 tbhits 0 time 6 pv d2d4
+info depth 3 seldepth 2 multipv 1 score cp 51 nodes 120 nps 20000 hashfull 0 tbhits 0 time 6 pv e2e4
+info depth 4 seldepth 2 multipv 1 score cp 58 nodes 144 nps 18000 hashfull 0 tbhits 0 time 8 pv d2d4
+info depth 5 seldepth 2 multipv 1 score cp 58 nodes 174 nps 15818 hashfull 0 tbhits 0 time 11 pv d2d4 a7a6
+info depth 6 seldepth 7 multipv 1 score cp 34 nodes 1303 nps 81437 hashfull 0 tbhits 0 time 16 pv e2e4 c7c5 g1f3 b8c6 c2c3
+info depth 7 seldepth 6 multipv 1 score cp 29 nodes 3126 nps 120230 hashfull 1 tbhits 0 time 26 pv d2d4 g8f6 e2e3 d7d5 c2c4 d5c4
+info depth 8 seldepth 7 multipv 1 score cp 26 nodes 5791 nps 152394 hashfull 4 tbhits 0 time 38 pv g1f3 g8f6 d2d4 d7d5 e2e3
+info depth 9 seldepth 9 multipv 1 score cp 31 nodes 8541 nps 174306 hashfull 5 tbhits 0 time 49 pv g1f3 c7c5 e2e4 e7e6 d2d4 c5d4 f3d4
+info depth 10 seldepth 13 multipv 1 score cp 25 nodes 20978 nps 209780 hashfull 10 tbhits 0 time 100 pv e2e4 c7c5 g1f3 b8c6 f1c4 e7e6 e1g1 g8f6
+info depth 11 seldepth 13 multipv 1 score cp 32 nodes 29040 nps 220000 hashfull 14 tbhits 0 time 132 pv e2e4 c7c5 c2c3 g8f6 e4e5 f6d5 d2d4
+info depth 12 seldepth 14 multipv 1 score cp 38 nodes 41207 nps 242394 hashfull 18 tbhits 0 time 170 pv e2e4 e7e6 d2d4 d7d5 b1c3 d5e4 c3e4
+info depth 13 seldepth 14 multipv 1 score cp 38 nodes 45531 nps 247451 hashfull 21 tbhits 0 time 184 pv e2e4 e7e6 d2d4 d7d5 b1c3 d5e4 c3e4
+bestmove e2e4 ponder e7e6
+";
+
+/// An abridged Lc0-style response to `uci`, with a string default that contains spaces and
+/// a diagnostic line among the options.
+///
+/// It is parsed only with the [`Quirks`](crate::engine_quirks::Quirks) of Lc0.
+pub const LC0_UCI_RESPONSE: &str = "id name Lc0 v0.31.2
+id author The LCZero Authors.
+
+option name WeightsFile type string default <autodiscover> or a path
+option name Threads type spin default 0 min 0 max 128
+Loading weights file from: /usr/share/lc0/weights.pb.gz
+option name MultiPV type spin default 1 min 1 max 500
+uciok
+";
+
+/// An option block with a `combo` option, e.g. from Komodo.
+pub const COMBO_OPTION_BLOCK: &str = "option name Threads type spin default 1 min 1 max 1024
+option name Personality type combo default Default var Default var Aggressive var Defensive var Human
+option name Hash type spin default 16 min 1 max 33554432
+";
+
+/// The `option` lines with their clauses out of the standard order, one of them with the name last.
+///
+/// They are parsed only with [`Quirks::reordered_option_clauses`](crate::engine_quirks::Quirks::reordered_option_clauses).
+pub const REORDERED_OPTION_CLAUSES: &str = "option type spin name Threads default 1 min 1 max 512
+option type check default false name Ponder
+option default 16 min 1 max 1024 type spin name Hash
+option name Move Overhead default 10 min 0 max 5000 type spin
+option type string default <empty> name Debug Log File
+option type button name Clear Hash
+";
+
+/// A `Debug Log File` of Stockfish 17.1 for a short game: the `uci` handshake and two searches.
+///
+/// The commands sent to the engine are prefixed with `>> ` and its output with `<< `
+/// (see [`parse_transcript`](crate::transcript::parse_transcript)).
+pub const STOCKFISH_DEBUG_LOG: &str = ">> uci
+<< Stockfish 17.1 by the Stockfish developers (see AUTHORS file)
+<< id name Stockfish 17.1
+<< id author the Stockfish developers (see AUTHORS file)
+<< 
+<< option name Threads type spin default 1 min 1 max 1024
+<< option name Hash type spin default 16 min 1 max 33554432
+<< option name MultiPV type spin default 1 min 1 max 256
+<< uciok
+>> setoption name Hash value 64
+>> isready
+<< readyok
+>> ucinewgame
+>> position startpos
+>> go depth 3
+<< info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))
+<< info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 6666 hashfull 0 tbhits 0 time 3 pv e2e4
+<< info depth 2 seldepth 3 multipv 1 score cp 34 nodes 45 nps 11250 hashfull 0 tbhits 0 time 4 pv e2e4
+<< info depth 3 seldepth 4 multipv 1 score cp 42 nodes 72 nps 14400 hashfull 0 tbhits 0 time 5 pv e2e4
+<< bestmove e2e4 ponder e7e5
+>> position startpos moves e2e4 e7e5
+>> go movetime 100
+<< info depth 1 seldepth 2 multipv 1 score cp 30 nodes 31 nps 10333 hashfull 0 tbhits 0 time 3 pv g1f3
+<< info depth 2 seldepth 3 multipv 1 score cp 38 nodes 88 nps 22000 hashfull 0 tbhits 0 time 4 pv g1f3 b8c6
+<< bestmove g1f3 ponder b8c6
+>> quit
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        engine_quirks::{EngineFamily, Quirks},
+        gui_command_responses::{GoEvent, GreetingPolicy, UciResponse},
+        util::AsyncReadable,
+    };
+
+    #[tokio::test]
+    async fn test_uci_responses_parse() {
+        let input = format!("{STOCKFISH_GREETING}{STOCKFISH_UCI_RESPONSE}");
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let response = UciResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.option_block.options().len(), 20);
+
+        let mut reader = tokio::io::BufReader::new(LC0_UCI_RESPONSE.as_bytes());
+        let response = UciResponse::read_with_quirks(
+            &mut reader,
+            GreetingPolicy::Strict,
+            Some(Quirks::for_family(EngineFamily::Lc0)),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+        assert_eq!(response.option_block.options().len(), 3);
+    }
+
+    #[test]
+    fn test_go_transcripts_parse() {
+        for transcript in [GO_DEPTH5_TRANSCRIPT, GO_INFINITE_STOPPED_TRANSCRIPT] {
+            let events = transcript
+                .lines()
+                .map(|line| line.parse::<GoEvent>())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert!(matches!(events.last(), Some(GoEvent::BestMove(_))));
+        }
+    }
+}
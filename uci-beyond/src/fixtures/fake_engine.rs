@@ -0,0 +1,117 @@
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncRead, AsyncWrite, AsyncWriteExt as _, BufReader},
+    task::JoinHandle,
+};
+
+use crate::util::IoConnection;
+
+/// The size of the buffer of the [`tokio::io::duplex`] between a [`FakeEngine`] and its connection.
+const DUPLEX_BUFFER_SIZE: usize = 4096;
+
+/// The [`IoConnection`] to a [`FakeEngine`], see [`FakeEngine::connect`].
+pub type DuplexConnection = IoConnection<
+    tokio::io::ReadHalf<tokio::io::DuplexStream>,
+    tokio::io::WriteHalf<tokio::io::DuplexStream>,
+>;
+
+type Script = Box<dyn FnMut(&str) -> Option<String> + Send>;
+
+/// An engine played by a script in the tests, which replies to every line it receives.
+///
+/// The script returns the reply to the line, i.e. the lines to write, each terminated with `\n`, or nothing
+/// for an empty reply. `None` makes the engine exit as if it crashed, i.e. close its output. The engine
+/// also exits after `quit`, once the reply to it is written.
+///
+/// ```ignore
+/// let (mut connection, engine) = FakeEngine::new(|line| {
+///     Some(match line {
+///         "uci" => "id name Fake 1.0\nid author Nobody\n\nuciok\n",
+///         _ => "",
+///     })
+/// })
+/// .connect();
+///
+/// let response = connection.send(UciCommand).await??;
+/// assert_eq!(response.id_block.name, "Fake 1.0");
+/// connection.send_raw_line("quit").await?;
+/// assert_eq!(engine.await?, ["uci", "quit"]);
+/// ```
+pub struct FakeEngine {
+    script: Script,
+    greeting: Option<String>,
+    answered_pings: Option<usize>,
+}
+
+impl FakeEngine {
+    pub fn new<F, T>(mut script: F) -> Self
+    where
+        F: FnMut(&str) -> Option<T> + Send + 'static,
+        T: Into<String>,
+    {
+        Self {
+            script: Box::new(move |line| script(line).map(Into::into)),
+            greeting: None,
+            answered_pings: None,
+        }
+    }
+
+    /// Writes the greeting before reading any line, e.g. [`STOCKFISH_GREETING`](crate::fixtures::STOCKFISH_GREETING).
+    pub fn with_greeting(mut self, greeting: impl Into<String>) -> Self {
+        self.greeting = Some(greeting.into());
+        self
+    }
+
+    /// Answers `isready` only `n` times, e.g. to play an engine that hangs in the middle of a search.
+    ///
+    /// The script isn't asked for the reply to the next ones, which are still received.
+    pub fn answering_pings(mut self, n: usize) -> Self {
+        self.answered_pings = Some(n);
+        self
+    }
+
+    /// Plays the engine on the streams until `quit`, the exit of the script or the end of the input,
+    /// and returns the received lines.
+    pub async fn serve<R, W>(mut self, reader: R, mut writer: W) -> Vec<String>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut received = Vec::new();
+        if let Some(greeting) = &self.greeting
+            && writer.write_all(greeting.as_bytes()).await.is_err()
+        {
+            return received;
+        }
+
+        // The connection may be dropped at any time, which ends the engine like the end of its input
+        let mut lines = BufReader::new(reader).lines();
+        let mut pings = 0;
+        while let Ok(Some(line)) = lines.next_line().await {
+            received.push(line.clone());
+            if line == "isready" {
+                pings += 1;
+                if self.answered_pings.is_some_and(|answered| pings > answered) {
+                    continue;
+                }
+            }
+            let Some(reply) = (self.script)(&line) else {
+                break;
+            };
+            if writer.write_all(reply.as_bytes()).await.is_err() || line == "quit" {
+                break;
+            }
+        }
+        received
+    }
+
+    /// Spawns the engine on one end of a [`tokio::io::duplex`] and connects to the other end.
+    ///
+    /// The task returns the lines received by the engine once it has exited.
+    pub fn connect(self) -> (DuplexConnection, JoinHandle<Vec<String>>) {
+        let (gui, engine) = tokio::io::duplex(DUPLEX_BUFFER_SIZE);
+        let (gui_read, gui_write) = tokio::io::split(gui);
+        let (engine_read, engine_write) = tokio::io::split(engine);
+        let engine = tokio::spawn(self.serve(engine_read, engine_write));
+        (IoConnection::new(gui_read, gui_write), engine)
+    }
+}
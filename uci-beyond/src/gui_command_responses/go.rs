@@ -1,21 +1,143 @@
-use crate::engine_commands::{
-    AvailableProcessorsInfoCommand, NnueEvaluationInfoCommand, UsingThreadsInfoCommand,
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{
+    command::{self, Command as _},
+    engine_commands::{
+        AvailableProcessorsInfoCommand, BestMoveCommand, BestMoveCommandParsingError,
+        CurrLineInfoCommand, InfoCommand, InfoCommandParsingError, NnueEvaluationInfoCommand,
+        NnueNetKind, RefutationInfoCommand, StringInfoCommand, UsingThreadsInfoCommand,
+    },
+    model,
+    options::OptionSnapshot,
+    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, Timestamp, handle_next_line},
 };
 
-// Ideally, GoCommandResponse should be an enum to support different implementations.
-// However, implementing multiple go command response types is not a priority right now.
-// Therefore, we use a type alias for the basic implementation.
-pub type GoCommandResponse = BasicGoCommandResponse;
+#[deprecated(note = "renamed to `GoResponse`")]
+pub type GoCommandResponse = GoResponse;
 
-/// The temporary basic implementation of a go command response.
+#[deprecated(note = "renamed to `GoResponse`")]
+pub type BasicGoCommandResponse = GoResponse;
+
+/// The response to `go`, i.e. the [`GoEvent`]s of the search up to and including `bestmove`.
 ///
-/// It is deliberately incomplete to cover only the parts of the response
-/// that are currently needed.
+/// The events are kept as they were read, and the [`InfoStringBlock`] and the [`SearchSummary`]
+/// are derived from them, so only the summary can be changed.
 ///
-/// Eventually, this should be replaced with a more complete implementation
-/// that can handle all aspects of a go command response.
-pub struct BasicGoCommandResponse {
+/// ```
+/// use uci_beyond::gui_command_responses::{GoEvent, GoResponse, SearchOutcome};
+///
+/// let events = [
+///     "info depth 1 score cp 17 nodes 20 time 3 pv e2e4",
+///     "bestmove e2e4",
+/// ]
+/// .map(|line| line.parse::<GoEvent>().unwrap());
+/// let response = GoResponse::from_events(&events);
+///
+/// assert_eq!(response.events().len(), 2);
+/// assert_eq!(response.search_summary.depth, Some(1));
+/// assert_eq!(response.outcome(), Some(SearchOutcome::BestMove));
+/// assert_eq!(response.clone(), response);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoResponse {
     info_string_block: InfoStringBlock,
+    events: Vec<GoEvent>,
+    raw_lines: Vec<String>,
+    pub search_summary: SearchSummary,
+}
+
+impl GoResponse {
+    pub fn from_events(events: &[GoEvent]) -> Self {
+        let raw_lines = events.iter().map(GoEvent::to_string).collect();
+        Self::from_lines(events.to_vec(), raw_lines)
+    }
+
+    fn from_lines(events: Vec<GoEvent>, raw_lines: Vec<String>) -> Self {
+        GoResponse {
+            info_string_block: InfoStringBlock::from_events(&events),
+            search_summary: SearchSummary::from_events(events.iter().cloned()),
+            events,
+            raw_lines,
+        }
+    }
+
+    pub fn info_string_block(&self) -> &InfoStringBlock {
+        &self.info_string_block
+    }
+
+    /// How the search ended, see [`SearchSummary::outcome`].
+    pub fn outcome(&self) -> Option<SearchOutcome> {
+        self.search_summary.outcome()
+    }
+
+    /// All events of the search up to and including `bestmove`.
+    pub fn events(&self) -> &[GoEvent] {
+        &self.events
+    }
+
+    /// The lines of the search as the engine sent them, without the line terminators.
+    ///
+    /// Unlike the [`Display`] of the response, they keep the original spacing and order of the tokens.
+    /// For a response made [`from_events`](Self::from_events), they are the displayed events.
+    pub fn raw_lines(&self) -> &[String] {
+        &self.raw_lines
+    }
+}
+
+/// Writes the events line by line, as the engine sends them in reply to `go`.
+///
+/// The lines are normalized, e.g. the tokens of the `info` lines are written in the canonical order
+/// (see [`GoResponse::raw_lines`] for the lines as received).
+impl Display for GoResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for event in &self.events {
+            writeln!(f, "{event}")?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for GoResponse {
+    type Err = <GoEvent as AsyncReadable>::Err;
+
+    /// Reads the [`GoEvent`]s up to and including `bestmove`.
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let mut events = Vec::new();
+        let mut raw_lines = Vec::new();
+        loop {
+            let f = |line: &str| -> LineHandlerOutcome<(GoEvent, String), Self::Err> {
+                match line.parse::<GoEvent>() {
+                    Ok(event) => {
+                        let line = line.trim_end_matches(['\r', '\n']).to_string();
+                        LineHandlerOutcome::Read((event, line))
+                    }
+                    Err(e) => LineHandlerOutcome::Error(e),
+                }
+            };
+
+            match handle_next_line(reader, f).await? {
+                Some(LineHandlerOutcome::Read((event, line))) => {
+                    let is_last = matches!(event, GoEvent::BestMove(_));
+                    events.push(event);
+                    raw_lines.push(line);
+                    if is_last {
+                        return Ok(Some(Ok(Self::from_lines(events, raw_lines))));
+                    }
+                }
+                Some(LineHandlerOutcome::Error(e)) => return Ok(Some(Err(e))),
+                Some(LineHandlerOutcome::Peeked) => {
+                    return command::parsing::Error::UnexpectedPeekOutput.wrap();
+                }
+                None => return Ok(None),
+            }
+        }
+    }
 }
 
 /// "Info string" block of a go command response.
@@ -43,8 +165,721 @@ pub struct BasicGoCommandResponse {
 /// info depth 5 seldepth 7 multipv 1 score cp 58 nodes 609 nps 87000 hashfull 0 tbhits 0 time 7 pv e2e4
 /// bestmove e2e4 ponder d7d6
 /// ```
+///
+/// The lines are kept in the order they were received, including the ones
+/// that are not recognized (e.g. `info string Failed to open tablebases`).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct InfoStringBlock {
-    available_processors: AvailableProcessorsInfoCommand,
-    used_threads: UsingThreadsInfoCommand,
-    nnue_evaluations: Vec<NnueEvaluationInfoCommand>,
+    lines: Vec<StringInfoCommand>,
+}
+
+impl InfoStringBlock {
+    pub fn from_events<'a, I>(events: I) -> Self
+    where
+        I: IntoIterator<Item = &'a GoEvent>,
+    {
+        let mut block = InfoStringBlock::default();
+        for event in events {
+            block.update(event);
+        }
+        block
+    }
+
+    /// Appends the `info string` line carried by the [`GoEvent`], if any.
+    pub fn update(&mut self, event: &GoEvent) {
+        if let GoEvent::Info(InfoCommand::String(text)) = event {
+            self.push(text);
+        }
+    }
+
+    /// Appends the text of an `info string` line, i.e. the part after `info string`.
+    pub fn push(&mut self, text: &str) {
+        let line = format!("info string {text}");
+        let cmd = line
+            .parse::<StringInfoCommand>()
+            .unwrap_or_else(|_| StringInfoCommand::Other(text.to_string()));
+        self.lines.push(cmd);
+    }
+
+    /// All `info string` lines in the order they were received.
+    pub fn lines(&self) -> &[StringInfoCommand] {
+        &self.lines
+    }
+
+    pub fn available_processors(&self) -> Option<&AvailableProcessorsInfoCommand> {
+        self.lines.iter().find_map(|line| match line {
+            StringInfoCommand::AvailableProcessors(cmd) => Some(cmd),
+            _ => None,
+        })
+    }
+
+    pub fn used_threads(&self) -> Option<&UsingThreadsInfoCommand> {
+        self.lines.iter().find_map(|line| match line {
+            StringInfoCommand::UsingThreads(cmd) => Some(cmd),
+            _ => None,
+        })
+    }
+
+    pub fn nnue_evaluations(&self) -> impl Iterator<Item = &NnueEvaluationInfoCommand> {
+        self.lines.iter().filter_map(|line| match line {
+            StringInfoCommand::NnueEvaluation(cmd) => Some(cmd),
+            _ => None,
+        })
+    }
+
+    /// The reported networks with their [`NnueNetKind`] (see [`NnueEvaluationInfoCommand::classify`]).
+    pub fn nnue_nets(&self) -> impl Iterator<Item = (NnueNetKind, &NnueEvaluationInfoCommand)> {
+        let evaluations: Vec<_> = self.nnue_evaluations().collect();
+        NnueEvaluationInfoCommand::classify(&evaluations)
+            .into_iter()
+            .zip(evaluations)
+    }
+
+    /// The network configured with `EvalFile`, i.e. the only one of the older engines.
+    pub fn big_net(&self) -> Option<&NnueEvaluationInfoCommand> {
+        self.nnue_net(NnueNetKind::Big)
+    }
+
+    /// The network configured with `EvalFileSmall`.
+    pub fn small_net(&self) -> Option<&NnueEvaluationInfoCommand> {
+        self.nnue_net(NnueNetKind::Small)
+    }
+
+    fn nnue_net(&self, kind: NnueNetKind) -> Option<&NnueEvaluationInfoCommand> {
+        self.nnue_nets()
+            .find_map(|(net_kind, net)| (net_kind == kind).then_some(net))
+    }
+
+    /// The text of the lines that are not recognized.
+    pub fn other(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().filter_map(|line| match line {
+            StringInfoCommand::Other(text) => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The unrecognized lines that look like warnings or errors, e.g.
+    ///
+    /// ```text
+    /// info string Failed to open tablebases
+    /// ```
+    ///
+    /// This is a heuristic based on the presence of `Failed` or `ERROR` in the text.
+    pub fn warnings(&self) -> impl Iterator<Item = &str> {
+        self.other()
+            .filter(|text| text.contains("Failed") || text.contains("ERROR"))
+    }
+}
+
+/// Writes the `info string` lines in the order they were received, one per line.
+impl Display for InfoStringBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A single line of the engine output in response to the `go` command.
+///
+/// A search can be consumed as a stream of [`GoEvent`]s, which ends with [`GoEvent::BestMove`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GoEvent {
+    /// Any `info` line but the refutations and the current lines, which are events of their own.
+    Info(InfoCommand),
+    /// `info refutation ...`, e.g. for showing the refutations of the moves in an analysis GUI.
+    Refutation(RefutationInfoCommand),
+    /// `info currline ...`, e.g. for showing what each CPU is calculating.
+    CurrLine(CurrLineInfoCommand),
+    BestMove(BestMoveCommand),
+}
+
+impl Display for GoEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoEvent::Info(info) => write!(f, "{info}"),
+            GoEvent::Refutation(refutation) => write!(f, "{refutation}"),
+            GoEvent::CurrLine(currline) => write!(f, "{currline}"),
+            GoEvent::BestMove(best_move) => write!(f, "{best_move}"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+#[non_exhaustive]
+pub enum GoEventParsingError {
+    #[error("InfoCommand parsing error: {0}")]
+    InfoCommandParsingError(InfoCommandParsingError),
+    #[error("BestMoveCommand parsing error: {0}")]
+    BestMoveCommandParsingError(BestMoveCommandParsingError),
+}
+
+impl FromStr for GoEvent {
+    type Err = command::parsing::Error<GoEventParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err(command::parsing::Error::UnexpectedEof);
+        }
+
+        if InfoCommand::matches_line(s) {
+            s.parse::<InfoCommand>()
+                .map(|info| match info {
+                    InfoCommand::Refutation(refutation) => GoEvent::Refutation(refutation),
+                    InfoCommand::CurrLine(currline) => GoEvent::CurrLine(currline),
+                    info => GoEvent::Info(info),
+                })
+                .map_err(|e| e.map_custom(GoEventParsingError::InfoCommandParsingError))
+        } else if BestMoveCommand::matches_line(s) {
+            s.parse::<BestMoveCommand>()
+                .map(GoEvent::BestMove)
+                .map_err(|e| e.map_custom(GoEventParsingError::BestMoveCommandParsingError))
+        } else {
+            Err(command::parsing::Error::UnexpectedCommand(s.to_string()))
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for GoEvent {
+    type Err = command::parsing::Error<GoEventParsingError>;
+
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let f = |line: &str| -> LineHandlerOutcome<GoEvent, <GoEvent as FromStr>::Err> {
+            match line.parse::<GoEvent>() {
+                Ok(event) => LineHandlerOutcome::Read(event),
+                Err(e) => LineHandlerOutcome::Error(e),
+            }
+        };
+
+        match handle_next_line(reader, f).await? {
+            Some(LineHandlerOutcome::Read(event)) => Ok(Some(Ok(event))),
+            Some(LineHandlerOutcome::Error(e)) => Ok(Some(Err(e))),
+            Some(LineHandlerOutcome::Peeked) => {
+                return command::parsing::Error::UnexpectedPeekOutput.wrap();
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Aggregate statistics of a search computed from the stream of [`GoEvent`]s.
+///
+/// Only the first principal variation (`multipv 1`) is taken into account,
+/// so the summary of a MultiPV search describes its best line.
+///
+/// The summary remains valid for searches interrupted with `stop`:
+/// it then describes the last iteration reported by the engine.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchSummary {
+    /// The maximum search depth reached.
+    pub depth: Option<u32>,
+    /// The maximum selective search depth reached.
+    pub seldepth: Option<u32>,
+    /// The total number of nodes searched.
+    pub nodes: Option<u64>,
+    /// The effective nodes per second.
+    ///
+    /// When the engine omits `nps`, it is computed from `nodes` and `time`.
+    pub nps: Option<u64>,
+    /// The total number of tablebase hits.
+    pub tbhits: Option<u64>,
+    /// The peak hash table usage in permill.
+    pub hashfull: Option<u32>,
+    /// The elapsed search time in ms.
+    pub time: Option<u64>,
+    /// The final evaluation from the engine's point of view.
+    pub score: Option<model::Score>,
+    /// The final principal variation.
+    pub pv: Vec<model::MoveString>,
+    /// The best move, if the search has completed.
+    pub best_move: Option<BestMoveCommand>,
+    /// The options that differed from their defaults when `go` was sent, if the search was made
+    /// by a [`Session`](crate::session::Session).
+    pub options: Option<OptionSnapshot>,
+}
+
+impl SearchSummary {
+    pub fn from_events<I>(events: I) -> Self
+    where
+        I: IntoIterator<Item = GoEvent>,
+    {
+        let mut summary = SearchSummary::default();
+        for event in events {
+            summary.update(&event);
+        }
+        summary
+    }
+
+    /// Folds a single [`GoEvent`] into the summary.
+    ///
+    /// Useful when the events are consumed one by one as they arrive.
+    pub fn update(&mut self, event: &GoEvent) {
+        let info = match event {
+            GoEvent::Info(InfoCommand::Depth(info)) => info,
+            GoEvent::Info(_) | GoEvent::Refutation(_) | GoEvent::CurrLine(_) => return,
+            GoEvent::BestMove(best_move) => {
+                self.best_move = Some(best_move.clone());
+                return;
+            }
+        };
+
+        if info.multipv.is_some_and(|multipv| multipv != 1) {
+            return;
+        }
+
+        self.depth = self.depth.max(info.depth);
+        self.seldepth = self.seldepth.max(info.seldepth);
+        self.hashfull = self.hashfull.max(info.hashfull);
+
+        if info.nodes.is_some() {
+            self.nodes = info.nodes;
+        }
+        if info.tbhits.is_some() {
+            self.tbhits = info.tbhits;
+        }
+        if info.time.is_some() {
+            self.time = info.time;
+        }
+        if info.score.is_some() {
+            self.score = info.score;
+        }
+        if !info.pv.is_empty() {
+            self.pv = info.pv.clone();
+        }
+
+        self.nps = match (info.nps, self.nodes, self.time) {
+            (Some(nps), _, _) => Some(nps),
+            // A count of nodes too large for nodes per millisecond keeps the last nps
+            (None, Some(nodes), Some(time)) if time > 0 => nodes
+                .checked_mul(1000)
+                .map(|nodes| nodes / time)
+                .or(self.nps),
+            (None, _, _) => self.nps,
+        };
+    }
+
+    /// How the search ended, or `None` until `bestmove` is received.
+    ///
+    /// When the position is already over, Stockfish doesn't search and answers with
+    ///
+    /// ```text
+    /// info depth 0 score mate 0
+    /// bestmove (none)
+    /// ```
+    ///
+    /// or with `score cp 0` for a stalemate, which is reported as [`SearchOutcome::TerminalPosition`]
+    /// instead of a best move with an empty principal variation.
+    pub fn outcome(&self) -> Option<SearchOutcome> {
+        let best_move = self.best_move.as_ref()?;
+        if best_move.is_none() {
+            let mated = self.score == Some(model::Score::Mate(0));
+            Some(SearchOutcome::TerminalPosition { mated })
+        } else {
+            Some(SearchOutcome::BestMove)
+        }
+    }
+
+    /// Checks the engine-reported `time` against the time measured by the GUI between sending `go`
+    /// (`started`) and receiving the last event (`finished`).
+    ///
+    /// The measured time also includes the transport latency, so the `tolerance` should account for it.
+    /// A summary without `time` is always consistent.
+    pub fn validate_elapsed(
+        &self,
+        started: Timestamp,
+        finished: Timestamp,
+        tolerance: Duration,
+    ) -> Result<(), ElapsedMismatch> {
+        let Some(time) = self.time else {
+            return Ok(());
+        };
+        let reported = Duration::from_millis(time);
+        let measured = finished.duration_since(started);
+        if reported.abs_diff(measured) > tolerance {
+            return Err(ElapsedMismatch { reported, measured });
+        }
+        Ok(())
+    }
+}
+
+/// How a search ended (see [`SearchSummary::outcome`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchOutcome {
+    /// The engine found a move to play, i.e. [`SearchSummary::best_move`].
+    BestMove,
+    /// The position has no legal moves, so there was nothing to search, e.g. to resign or to claim the result.
+    TerminalPosition {
+        /// The side to move is checkmated, as opposed to stalemated.
+        mated: bool,
+    },
+}
+
+/// The engine-reported search time disagrees with the measured one (see [`SearchSummary::validate_elapsed`]).
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("The engine reported {reported:?} of search time, but {measured:?} was measured")]
+pub struct ElapsedMismatch {
+    pub reported: Duration,
+    pub measured: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{GO_DEPTH5_TRANSCRIPT, GO_INFINITE_STOPPED_TRANSCRIPT};
+
+    fn parse_events(transcript: &str) -> Vec<GoEvent> {
+        transcript
+            .lines()
+            .map(|line| match line.parse::<GoEvent>() {
+                Ok(event) => event,
+                Err(e) => panic!("Failed to parse GoEvent from `{line}`: {e:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_search_summary_go_depth() {
+        let summary = SearchSummary::from_events(parse_events(GO_DEPTH5_TRANSCRIPT));
+
+        assert_eq!(
+            summary,
+            SearchSummary {
+                depth: Some(5),
+                seldepth: Some(7),
+                nodes: Some(609),
+                nps: Some(87000),
+                tbhits: Some(0),
+                hashfull: Some(0),
+                time: Some(7),
+                score: Some(model::Score::Cp(58)),
+                pv: vec![model::MoveString::from("e2e4")],
+                best_move: Some(BestMoveCommand {
+                    best_move: model::MoveString::from("e2e4"),
+                    ponder: Some(model::MoveString::from("d7d6")),
+                }),
+                options: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_search_summary_interrupted_by_stop() {
+        let summary = SearchSummary::from_events(parse_events(GO_INFINITE_STOPPED_TRANSCRIPT));
+
+        assert_eq!(summary.depth, Some(13));
+        assert_eq!(summary.seldepth, Some(14));
+        assert_eq!(summary.nodes, Some(45531));
+        assert_eq!(summary.nps, Some(247451));
+        assert_eq!(summary.tbhits, Some(0));
+        assert_eq!(summary.hashfull, Some(21));
+        assert_eq!(summary.time, Some(184));
+        assert_eq!(summary.score, Some(model::Score::Cp(38)));
+        assert_eq!(
+            summary.pv,
+            ["e2e4", "e7e6", "d2d4", "d7d5", "b1c3", "d5e4", "c3e4"].map(model::MoveString::from)
+        );
+        assert_eq!(
+            summary.best_move.map(|b| b.best_move),
+            Some(model::MoveString::from("e2e4"))
+        );
+    }
+
+    #[test]
+    fn test_search_summary_multipv() {
+        let transcript = "info depth 10 seldepth 12 multipv 1 score cp 30 nodes 5000 nps 100000 hashfull 3 tbhits 0 time 50 pv e2e4 e7e5
+info depth 10 seldepth 15 multipv 2 score cp 20 nodes 5000 nps 100000 hashfull 3 tbhits 0 time 50 pv d2d4 d7d5
+info depth 10 seldepth 11 multipv 3 score mate -5 nodes 5000 nps 100000 hashfull 3 tbhits 0 time 50 pv f2f3";
+
+        let summary = SearchSummary::from_events(parse_events(transcript));
+
+        assert_eq!(summary.seldepth, Some(12));
+        assert_eq!(summary.score, Some(model::Score::Cp(30)));
+        assert_eq!(summary.pv, ["e2e4", "e7e5"].map(model::MoveString::from));
+        assert_eq!(summary.best_move, None);
+    }
+
+    #[test]
+    fn test_search_summary_without_nps() {
+        let transcript = "info depth 1 score cp 20 nodes 400 time 2 pv e2e4
+info depth 2 score cp 25 nodes 3000 time 4 pv e2e4 e7e5
+bestmove e2e4";
+
+        let summary = SearchSummary::from_events(parse_events(transcript));
+
+        assert_eq!(summary.nodes, Some(3000));
+        assert_eq!(summary.time, Some(4));
+        assert_eq!(summary.nps, Some(750_000));
+        assert_eq!(summary.seldepth, None);
+        assert_eq!(summary.hashfull, None);
+
+        // Neither a huge count of nodes nor a zero time overflows or divides by zero
+        let transcript = "info depth 1 score cp 20 nodes 18446744073709551615 time 2000 pv e2e4
+info depth 2 score cp 25 nodes 5000 time 0 pv e2e4 e7e5
+bestmove e2e4";
+
+        let summary = SearchSummary::from_events(parse_events(transcript));
+
+        assert_eq!(summary.nodes, Some(5000));
+        assert_eq!(summary.time, Some(0));
+        assert_eq!(summary.nps, None);
+    }
+
+    #[tokio::test]
+    async fn test_read_go_command_response() {
+        let transcript = "info string Using 1 thread
+info depth 1 seldepth 1 multipv 1 score cp 18 nodes 20 nps 4000 hashfull 0 tbhits 0 time 5 pv e2e4
+bestmove e2e4 ponder e7e6
+readyok
+";
+        let mut reader = tokio::io::BufReader::new(transcript.as_bytes());
+
+        let response = GoResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.info_string_block().lines().len(), 1);
+        assert_eq!(response.search_summary.depth, Some(1));
+        assert_eq!(
+            response.search_summary.best_move.map(|b| b.best_move),
+            Some(model::MoveString::from("e2e4"))
+        );
+
+        // A search cut off before `bestmove` reads as the end of input
+        let mut reader = tokio::io::BufReader::new(&b"info string Using 1 thread\n"[..]);
+        assert!(GoResponse::read_from(&mut reader).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_refutation_and_currline_events() {
+        let transcript =
+            "info depth 3 seldepth 3 score cp 20 nodes 300 currline 1 e2e4 e7e5 time 2 pv e2e4
+info currline 1 e2e4 e7e5 g1f3
+info currline 2 d2d4 d7d5
+info refutation d1h5 g6h5
+bestmove e2e4
+";
+        let mut reader = tokio::io::BufReader::new(transcript.as_bytes());
+        let response = GoResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let events = response.events();
+        // The current line embedded in the `info depth` line doesn't break it
+        assert!(matches!(&events[0], GoEvent::Info(InfoCommand::Depth(_))));
+        assert_eq!(
+            events[0].to_string(),
+            "info depth 3 seldepth 3 score cp 20 nodes 300 time 2 pv e2e4 currline 1 e2e4 e7e5"
+        );
+        assert!(matches!(
+            &events[1],
+            GoEvent::CurrLine(CurrLineInfoCommand { cpu: Some(1), line }) if line.len() == 3
+        ));
+        assert!(matches!(
+            &events[2],
+            GoEvent::CurrLine(CurrLineInfoCommand { cpu: Some(2), .. })
+        ));
+        assert!(matches!(
+            &events[3],
+            GoEvent::Refutation(RefutationInfoCommand { refuted_move, .. }) if refuted_move.0 == "d1h5"
+        ));
+        assert_eq!(events[3].to_string(), "info refutation d1h5 g6h5");
+        assert_eq!(response.search_summary.depth, Some(3));
+        assert_eq!(response.search_summary.pv.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_terminal_position_outcome() {
+        let checkmate = "info depth 0 score mate 0
+bestmove (none)
+";
+        let mut reader = tokio::io::BufReader::new(checkmate.as_bytes());
+        let response = GoResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            response.outcome(),
+            Some(SearchOutcome::TerminalPosition { mated: true })
+        );
+        assert_eq!(response.search_summary.depth, Some(0));
+        assert!(response.search_summary.pv.is_empty());
+
+        let stalemate = "info depth 0 score cp 0
+bestmove (none)
+";
+        let mut reader = tokio::io::BufReader::new(stalemate.as_bytes());
+        let response = GoResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            response.outcome(),
+            Some(SearchOutcome::TerminalPosition { mated: false })
+        );
+
+        let events = parse_events(GO_DEPTH5_TRANSCRIPT);
+        let summary = SearchSummary::from_events(events.iter().cloned());
+        assert_eq!(summary.outcome(), Some(SearchOutcome::BestMove));
+        // Without `bestmove`, the search isn't over
+        let summary = SearchSummary::from_events(events[..events.len() - 1].iter().cloned());
+        assert_eq!(summary.outcome(), None);
+    }
+
+    #[tokio::test]
+    async fn test_go_command_response_display_round_trip() {
+        for transcript in [GO_DEPTH5_TRANSCRIPT, GO_INFINITE_STOPPED_TRANSCRIPT] {
+            let mut reader = tokio::io::BufReader::new(transcript.as_bytes());
+            let response = GoResponse::read_from(&mut reader)
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert_eq!(response.to_string(), transcript);
+            assert_eq!(response.raw_lines(), transcript.lines().collect::<Vec<_>>());
+
+            let displayed = response.to_string();
+            let mut reader = tokio::io::BufReader::new(displayed.as_bytes());
+            let reparsed = GoResponse::read_from(&mut reader)
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert_eq!(reparsed.events(), response.events());
+            assert_eq!(reparsed.search_summary, response.search_summary);
+        }
+
+        // The tokens out of the canonical order are normalized, but the raw lines keep them
+        let transcript = "info string Using 1 thread\n\
+info depth 1 score cp 17 seldepth 2 pv e2e4\n\
+bestmove e2e4\n";
+        let mut reader = tokio::io::BufReader::new(transcript.as_bytes());
+        let response = GoResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            response.to_string(),
+            "info string Using 1 thread\n\
+info depth 1 seldepth 2 score cp 17 pv e2e4\n\
+bestmove e2e4\n"
+        );
+        assert_eq!(response.raw_lines(), transcript.lines().collect::<Vec<_>>());
+        assert_eq!(
+            response.info_string_block().to_string(),
+            "info string Using 1 thread\n"
+        );
+    }
+
+    #[test]
+    fn test_info_string_block_nnue_nets() {
+        let block = InfoStringBlock::from_events(&parse_events(GO_DEPTH5_TRANSCRIPT));
+        assert_eq!(
+            block.big_net().map(|net| net.name.as_str()),
+            Some("nn-1c0000000000.nnue")
+        );
+        assert_eq!(
+            block.small_net().map(|net| net.name.as_str()),
+            Some("nn-37f18f62d772.nnue")
+        );
+
+        // The small network reported first is still recognized by its size
+        let mut block = InfoStringBlock::default();
+        block.push("NNUE evaluation using small.nnue (6MiB)");
+        block.push("NNUE evaluation using big.nnue (133MiB)");
+        assert_eq!(
+            block.big_net().map(|net| net.name.as_str()),
+            Some("big.nnue")
+        );
+        assert_eq!(
+            block.small_net().map(|net| net.name.as_str()),
+            Some("small.nnue")
+        );
+
+        // Older engines load a single network
+        let block = InfoStringBlock::from_events(&parse_events(GO_INFINITE_STOPPED_TRANSCRIPT));
+        assert_eq!(
+            block.big_net().map(|net| net.name.as_str()),
+            Some("nn-ad9b42354671.nnue")
+        );
+        assert!(block.small_net().is_none());
+    }
+
+    #[test]
+    fn test_info_string_block_mixed_lines() {
+        let transcript = "info string Available processors: 0-7
+info string Failed to open tablebases
+info string Using 2 threads
+info string classical evaluation enabled
+info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))
+info string ERROR: NNUE network nn-37f18f62d772.nnue was not loaded
+info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 6666 hashfull 0 tbhits 0 time 3 pv e2e4
+bestmove e2e4";
+
+        let events = parse_events(transcript);
+        let block = InfoStringBlock::from_events(&events);
+
+        assert_eq!(block.lines().len(), 6);
+        assert_eq!(block.available_processors().map(|cmd| cmd.total()), Some(8));
+        assert_eq!(block.used_threads().map(|cmd| cmd.threads), Some(2));
+        assert_eq!(block.nnue_evaluations().count(), 1);
+        assert_eq!(
+            block.other().collect::<Vec<_>>(),
+            [
+                "Failed to open tablebases",
+                "classical evaluation enabled",
+                "ERROR: NNUE network nn-37f18f62d772.nnue was not loaded",
+            ]
+        );
+        assert_eq!(
+            block.warnings().collect::<Vec<_>>(),
+            [
+                "Failed to open tablebases",
+                "ERROR: NNUE network nn-37f18f62d772.nnue was not loaded",
+            ]
+        );
+        assert_eq!(
+            block.lines()[1].to_string(),
+            "info string Failed to open tablebases"
+        );
+    }
+
+    #[test]
+    fn test_search_summary_validate_elapsed() {
+        let summary = SearchSummary::from_events(parse_events(
+            "info depth 5 seldepth 7 multipv 1 score cp 58 nodes 609 nps 87000 time 1000 pv e2e4",
+        ));
+        let started = Timestamp::from_monotonic(Duration::from_secs(10));
+        let tolerance = Duration::from_millis(50);
+
+        let finished = Timestamp::from_monotonic(Duration::from_millis(11_030));
+        assert_eq!(
+            summary.validate_elapsed(started, finished, tolerance),
+            Ok(())
+        );
+
+        let finished = Timestamp::from_monotonic(Duration::from_millis(10_500));
+        assert_eq!(
+            summary.validate_elapsed(started, finished, tolerance),
+            Err(ElapsedMismatch {
+                reported: Duration::from_millis(1000),
+                measured: Duration::from_millis(500),
+            })
+        );
+
+        assert_eq!(
+            SearchSummary::default().validate_elapsed(started, finished, tolerance),
+            Ok(())
+        );
+    }
 }
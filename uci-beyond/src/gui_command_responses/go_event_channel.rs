@@ -0,0 +1,296 @@
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use crate::{
+    engine_commands::{DepthInfoCommand, InfoCommand},
+    gui_command_responses::GoEvent,
+    util::{AsyncReadable, StreamingLineReader},
+};
+
+/// The default capacity of [`go_event_channel`].
+pub const DEFAULT_GO_EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// What [`GoEventSender::send`] does when the channel is full, i.e. when the consumer is slower than the engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait for the consumer, which in turn stops reading the engine output until there is room.
+    #[default]
+    Block,
+    /// Merge the progress-only events (see [`is_progress_only`]) that don't fit into the channel into one,
+    /// keeping the latest value of every field, e.g. of `hashfull` and `currmove`.
+    ///
+    /// The other events are never dropped: they wait for room in the channel as with [`OverflowPolicy::Block`],
+    /// after the merged progress event that precedes them.
+    CoalesceProgress,
+}
+
+/// Creates a bounded channel of [`GoEvent`]s, e.g. for a task that reads a search and a consumer that renders it.
+///
+/// Use [`forward_go_events`] to feed the sender from the engine output.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn go_event_channel(
+    capacity: usize,
+    policy: OverflowPolicy,
+) -> (GoEventSender, mpsc::Receiver<GoEvent>) {
+    let (sender, receiver) = mpsc::channel(capacity);
+    let sender = GoEventSender {
+        sender,
+        policy,
+        pending: None,
+    };
+    (sender, receiver)
+}
+
+/// The sending half of [`go_event_channel`].
+#[derive(Debug)]
+pub struct GoEventSender {
+    sender: mpsc::Sender<GoEvent>,
+    policy: OverflowPolicy,
+    /// The merged progress-only events that didn't fit into the channel yet.
+    pending: Option<DepthInfoCommand>,
+}
+
+/// The receiver of the [`go_event_channel`] was dropped.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("The receiver of the go events was dropped")]
+pub struct ReceiverDropped;
+
+impl GoEventSender {
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Sends the event according to the [`OverflowPolicy`].
+    pub async fn send(&mut self, event: GoEvent) -> Result<(), ReceiverDropped> {
+        let progress = match (self.policy, event) {
+            (OverflowPolicy::CoalesceProgress, GoEvent::Info(InfoCommand::Depth(info)))
+                if is_progress_only(&info) =>
+            {
+                info
+            }
+            (_, event) => {
+                self.flush().await?;
+                return self.sender.send(event).await.map_err(|_| ReceiverDropped);
+            }
+        };
+
+        let progress = match self.pending.take() {
+            Some(pending) => merge_progress(pending, progress),
+            None => progress,
+        };
+        match self
+            .sender
+            .try_send(GoEvent::Info(InfoCommand::Depth(progress)))
+        {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(GoEvent::Info(InfoCommand::Depth(progress)))) => {
+                self.pending = Some(progress);
+                Ok(())
+            }
+            Err(TrySendError::Full(_)) => unreachable!("The event is the one sent above"),
+            Err(TrySendError::Closed(_)) => Err(ReceiverDropped),
+        }
+    }
+
+    /// Waits until the merged progress event, if any, is sent.
+    pub async fn flush(&mut self) -> Result<(), ReceiverDropped> {
+        if let Some(progress) = self.pending.take() {
+            self.sender
+                .send(GoEvent::Info(InfoCommand::Depth(progress)))
+                .await
+                .map_err(|_| ReceiverDropped)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the `info` line only reports the progress of the search, e.g.
+///
+/// ```text
+/// info depth 20 currmove e2e4 currmovenumber 1
+/// info nodes 1000000 nps 2000000 hashfull 12 tbhits 0 time 500
+/// ```
+///
+/// Such lines can be merged without losing a principal variation or a score.
+pub fn is_progress_only(info: &DepthInfoCommand) -> bool {
+    info.score.is_none()
+        && info.pv.is_empty()
+        && info.multipv.is_none()
+        && info.unknown_tokens.is_empty()
+        && info.string.is_none()
+}
+
+/// Merges two progress-only events, the fields of the `newer` one taking precedence.
+fn merge_progress(older: DepthInfoCommand, newer: DepthInfoCommand) -> DepthInfoCommand {
+    DepthInfoCommand {
+        depth: newer.depth.or(older.depth),
+        seldepth: newer.seldepth.or(older.seldepth),
+        multipv: None,
+        score: None,
+        nodes: newer.nodes.or(older.nodes),
+        nps: newer.nps.or(older.nps),
+        hashfull: newer.hashfull.or(older.hashfull),
+        tbhits: newer.tbhits.or(older.tbhits),
+        time: newer.time.or(older.time),
+        // `currmovenumber` describes `currmove`, so they are taken from the same event
+        currmove: newer.currmove.clone().or(older.currmove.clone()),
+        currmovenumber: if newer.currmove.is_some() {
+            newer.currmovenumber
+        } else {
+            newer.currmovenumber.or(older.currmovenumber)
+        },
+        pv: Vec::new(),
+        unknown_tokens: Vec::new(),
+        string: None,
+    }
+}
+
+/// Reads the [`GoEvent`]s up to and including `bestmove` and sends them, e.g. from a spawned task.
+///
+/// Stops early without an error if the receiver is dropped. When the output ends before `bestmove`,
+/// the sender is dropped, which closes the channel.
+pub async fn forward_go_events<R>(
+    reader: &mut R,
+    mut sender: GoEventSender,
+) -> Result<Result<(), <GoEvent as AsyncReadable>::Err>, R::Error>
+where
+    R: StreamingLineReader,
+{
+    loop {
+        let event = match GoEvent::read_from(reader).await? {
+            Some(Ok(event)) => event,
+            Some(Err(e)) => return Ok(Err(e)),
+            None => break,
+        };
+        let is_last = matches!(event, GoEvent::BestMove(_));
+        if sender.send(event).await.is_err() || is_last {
+            break;
+        }
+    }
+    // Deliver the progress that is still pending when the output ends abruptly
+    let _ = sender.flush().await;
+    Ok(Ok(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 100 progress-only lines, with a principal variation every 25 lines, followed by `bestmove`.
+    fn transcript() -> String {
+        let mut transcript = String::new();
+        for i in 1..=100 {
+            transcript.push_str(&format!(
+                "info depth {} currmove e2e4 currmovenumber {i} hashfull {i}\n",
+                i / 25 + 1
+            ));
+            if i % 25 == 0 {
+                transcript.push_str(&format!(
+                    "info depth {} seldepth {} multipv 1 score cp {i} nodes {i} nps 1000 time {i} pv e2e4\n",
+                    i / 25,
+                    i / 25
+                ));
+            }
+        }
+        transcript.push_str("bestmove e2e4\n");
+        transcript
+    }
+
+    /// Forwards the transcript to a consumer that yields to the forwarder after every event.
+    async fn forward_to_slow_consumer(policy: OverflowPolicy) -> Vec<GoEvent> {
+        let transcript = transcript();
+        let mut reader = tokio::io::BufReader::new(transcript.as_bytes());
+        let (sender, mut receiver) = go_event_channel(2, policy);
+
+        let consume = async {
+            let mut events = Vec::new();
+            while let Some(event) = receiver.recv().await {
+                events.push(event);
+                tokio::task::yield_now().await;
+            }
+            events
+        };
+        let (forwarded, events) =
+            futures::future::join(forward_go_events(&mut reader, sender), consume).await;
+        forwarded.unwrap().unwrap();
+        events
+    }
+
+    fn hashfull(event: &GoEvent) -> Option<u32> {
+        match event {
+            GoEvent::Info(InfoCommand::Depth(info)) => info.hashfull,
+            _ => None,
+        }
+    }
+
+    fn scores(events: &[GoEvent]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                GoEvent::Info(InfoCommand::Depth(info)) => info.score.map(|s| s.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_block_delivers_every_event() {
+        let events = forward_to_slow_consumer(OverflowPolicy::Block).await;
+        assert_eq!(events.len(), 105);
+        assert_eq!(
+            events.iter().filter_map(hashfull).collect::<Vec<_>>(),
+            (1..=100).collect::<Vec<_>>()
+        );
+        assert!(matches!(events.last(), Some(GoEvent::BestMove(_))));
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_progress_keeps_the_latest_progress_and_every_pv() {
+        let events = forward_to_slow_consumer(OverflowPolicy::CoalesceProgress).await;
+        assert!(events.len() < 105, "{} events", events.len());
+
+        // The principal variations are never dropped and keep their order
+        assert_eq!(scores(&events), ["cp 25", "cp 50", "cp 75", "cp 100"]);
+
+        // The progress only moves forward and its latest value arrives before `bestmove`
+        let hashfull: Vec<u32> = events.iter().filter_map(hashfull).collect();
+        assert!(hashfull.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(hashfull.last(), Some(&100));
+        assert!(matches!(events.last(), Some(GoEvent::BestMove(_))));
+
+        // The progress preceding a principal variation is delivered before it
+        let before_pv = events
+            .iter()
+            .position(|event| scores(std::slice::from_ref(event)) == ["cp 25"])
+            .unwrap();
+        assert_eq!(hashfull(&events[before_pv - 1]), Some(25));
+    }
+
+    #[test]
+    fn test_merge_progress() {
+        let older: DepthInfoCommand = "info depth 5 currmove e2e4 currmovenumber 3 hashfull 10"
+            .parse::<InfoCommand>()
+            .map(|info| match info {
+                InfoCommand::Depth(info) => info,
+                _ => unreachable!(),
+            })
+            .unwrap();
+        let newer = DepthInfoCommand {
+            hashfull: Some(12),
+            nodes: Some(1000),
+            ..Default::default()
+        };
+
+        let merged = merge_progress(older, newer);
+        assert_eq!(merged.depth, Some(5));
+        assert_eq!(merged.hashfull, Some(12));
+        assert_eq!(merged.nodes, Some(1000));
+        assert_eq!(
+            merged.currmove,
+            Some(crate::model::MoveString::from("e2e4"))
+        );
+        assert_eq!(merged.currmovenumber, Some(3));
+    }
+}
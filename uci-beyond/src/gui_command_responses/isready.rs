@@ -0,0 +1,143 @@
+use std::{convert::Infallible, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::{
+    command,
+    engine_commands::ReadyOkCommand,
+    gui_command_responses::InfoStringBlock,
+    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
+};
+
+/// The response to `isready`, i.e. `readyok` and the `info string` lines printed before it,
+/// e.g. `info string Using 4 threads` after `setoption name Threads value 4`.
+///
+/// The other lines before `readyok` are skipped, since an engine may still be printing
+/// the output of the previous command.
+///
+/// ```
+/// use uci_beyond::{gui_command_responses::IsReadyResponse, util::AsyncReadable};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let output = "info string Using 4 threads\nreadyok\n";
+/// let mut reader = tokio::io::BufReader::new(output.as_bytes());
+/// let response = IsReadyResponse::read_from(&mut reader)
+///     .await
+///     .unwrap()
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(response.info_string_block.used_threads().unwrap().threads, 4);
+/// assert_eq!(response.to_string(), output);
+/// assert_eq!(response.clone(), response);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsReadyResponse {
+    pub info_string_block: InfoStringBlock,
+    pub readyok: ReadyOkCommand,
+}
+
+/// Writes the `info string` lines and `readyok`, each terminated with a newline.
+impl Display for IsReadyResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.info_string_block)?;
+        writeln!(f, "{}", self.readyok)
+    }
+}
+
+enum Line {
+    ReadyOk,
+    InfoString(String),
+    Other,
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for IsReadyResponse {
+    type Err = command::parsing::Error<Infallible>;
+
+    /// Reads the lines up to and including `readyok`. The end of the output before it is
+    /// [`command::parsing::Error::UnexpectedEof`].
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let mut response = IsReadyResponse {
+            info_string_block: InfoStringBlock::default(),
+            readyok: ReadyOkCommand,
+        };
+        let mut is_first = true;
+        loop {
+            let f = |line: &str| -> LineHandlerOutcome<Line, Self::Err> {
+                let line = line.trim();
+                if line == "readyok" {
+                    LineHandlerOutcome::Read(Line::ReadyOk)
+                } else if let Some(text) = line.strip_prefix("info string ") {
+                    LineHandlerOutcome::Read(Line::InfoString(text.to_string()))
+                } else {
+                    LineHandlerOutcome::Read(Line::Other)
+                }
+            };
+
+            match handle_next_line(reader, f).await? {
+                Some(LineHandlerOutcome::Read(Line::ReadyOk)) => return Ok(Some(Ok(response))),
+                Some(LineHandlerOutcome::Read(Line::InfoString(text))) => {
+                    response.info_string_block.push(&text);
+                }
+                Some(LineHandlerOutcome::Read(Line::Other)) => {}
+                Some(LineHandlerOutcome::Error(e)) => return Ok(Some(Err(e))),
+                Some(LineHandlerOutcome::Peeked) => {
+                    return command::parsing::Error::UnexpectedPeekOutput.wrap();
+                }
+                None if is_first => return Ok(None),
+                None => return command::parsing::Error::UnexpectedEof.wrap(),
+            }
+            is_first = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(
+        output: &str,
+    ) -> Option<Result<IsReadyResponse, command::parsing::Error<Infallible>>> {
+        let mut reader = tokio::io::BufReader::new(output.as_bytes());
+        IsReadyResponse::read_from(&mut reader).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_is_ready_response() {
+        let response = read(
+            "info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))\n\
+             info depth 1 score cp 17 pv e2e4\n\
+             readyok\n",
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(response.info_string_block.lines().len(), 1);
+        assert!(response.info_string_block.big_net().is_some());
+
+        let response = read("readyok\n").await.unwrap().unwrap();
+        assert_eq!(
+            response,
+            IsReadyResponse {
+                info_string_block: InfoStringBlock::default(),
+                readyok: ReadyOkCommand,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_is_ready_response_eof() {
+        assert!(read("").await.is_none());
+        assert!(matches!(
+            read("info string Using 1 thread\n").await,
+            Some(Err(command::parsing::Error::UnexpectedEof))
+        ));
+    }
+}
@@ -1,4 +1,75 @@
-mod go;
-mod uci;
+//! The responses of the engine to the commands of the GUI, one module per command, e.g.
+//! [`UciResponse`] in [`uci`] for `uci`. The responses are re-exported here.
 
-pub use uci::{UciCommandResponse, UciCommandResponseParsingError};
+pub mod go;
+mod go_event_channel;
+pub mod isready;
+mod search_progress;
+pub mod stop;
+pub mod uci;
+
+#[cfg(feature = "stockfish-ext")]
+pub mod stockfish;
+
+#[allow(deprecated)]
+pub use go::{BasicGoCommandResponse, GoCommandResponse};
+pub use go::{
+    ElapsedMismatch, GoEvent, GoEventParsingError, GoResponse, InfoStringBlock, SearchOutcome,
+    SearchSummary,
+};
+pub use go_event_channel::{
+    DEFAULT_GO_EVENT_CHANNEL_CAPACITY, GoEventSender, OverflowPolicy, ReceiverDropped,
+    forward_go_events, go_event_channel, is_progress_only,
+};
+pub use isready::IsReadyResponse;
+pub use search_progress::{SearchProgress, SearchProgressEstimator};
+pub use stop::StopResponse;
+pub(crate) use uci::ReadUsage;
+pub use uci::{
+    DEFAULT_MAX_GREETING_LINES, DEFAULT_MAX_OPTION_LINES, DEFAULT_MAX_RESPONSE_BYTES,
+    GreetingPolicy, PartialUciResponse, ReadLimitExceeded, ReadLimits, ReadOptions, UciResponse,
+    UciResponseParsingError,
+};
+#[allow(deprecated)]
+pub use uci::{PartialUciCommandResponse, UciCommandResponse, UciCommandResponseParsingError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command, test_util::assert_data};
+
+    #[test]
+    fn test_responses_are_send_sync_clone() {
+        assert_data::<UciResponse>();
+        assert_data::<PartialUciResponse>();
+        assert_data::<command::parsing::Error<UciResponseParsingError>>();
+        assert_data::<GoResponse>();
+        assert_data::<IsReadyResponse>();
+        assert_data::<StopResponse>();
+        assert_data::<SearchSummary>();
+        assert_data::<InfoStringBlock>();
+        assert_data::<GoEvent>();
+        assert_data::<command::parsing::Error<GoEventParsingError>>();
+        assert_data::<SearchProgress>();
+        assert_data::<ReadLimitExceeded>();
+    }
+
+    #[cfg(feature = "stockfish-ext")]
+    #[test]
+    fn test_stockfish_responses_are_send_sync_clone() {
+        use stockfish::{
+            BenchResponse, BenchResponseParsingError, CompilerResponse,
+            CompilerResponseParsingError, DisplayResponse, DisplayResponseParsingError,
+            EvalResponse, EvalResponseParsingError,
+        };
+
+        assert_data::<BenchResponse>();
+        assert_data::<command::parsing::Error<BenchResponseParsingError>>();
+        assert_data::<CompilerResponse>();
+        assert_data::<command::parsing::Error<CompilerResponseParsingError>>();
+        assert_data::<DisplayResponse>();
+        assert_data::<command::parsing::Error<DisplayResponseParsingError>>();
+        assert_data::<EvalResponse>();
+        assert_data::<command::parsing::Error<EvalResponseParsingError>>();
+    }
+}
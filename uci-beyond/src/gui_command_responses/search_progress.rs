@@ -0,0 +1,215 @@
+use crate::{
+    engine_commands::InfoCommand, gui_command_responses::GoEvent, gui_commands::GoCommand,
+};
+
+/// The estimated progress of a search, see [`SearchProgressEstimator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchProgress {
+    /// The fraction of the search that is done, from 0.0 to 1.0.
+    Determinate(f64),
+    /// The search has no limit the progress can be estimated against, e.g. `go infinite`.
+    Indeterminate,
+}
+
+/// Estimates the progress of a search from the limits of its [`GoCommand`] and the stream of [`GoEvent`]s, e.g. for a progress bar.
+///
+/// - With `movetime`, the progress is linear in the reported `time`.
+/// - With `nodes`, the progress is the fraction of the node limit that was searched.
+/// - With `depth`, the effort to complete an iteration is assumed to grow by the branching factor with every ply,
+///   so reaching depth `d` of `D` is reported as `branching_factor^(d - D)`.
+///
+/// When several limits are set, the search ends at the first one reached, so the most advanced estimate is reported.
+/// Searches limited only by the clock, by `mate`, or not at all (`go infinite`) are [`SearchProgress::Indeterminate`].
+///
+/// The estimate never decreases and is 1.0 once `bestmove` arrives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchProgressEstimator {
+    movetime: Option<u32>,
+    nodes: Option<u32>,
+    depth: Option<u32>,
+    branching_factor: f64,
+    progress: f64,
+}
+
+impl SearchProgressEstimator {
+    /// The default effective branching factor, i.e. the growth of the effort per ply of depth.
+    pub const DEFAULT_BRANCHING_FACTOR: f64 = 2.0;
+
+    pub fn new(go: &GoCommand) -> Self {
+        let is_limited = !go.indefinite && !go.ponder;
+        Self {
+            movetime: go.movetime.filter(|_| is_limited),
+            nodes: go.nodes.filter(|_| is_limited),
+            depth: go.depth.filter(|_| is_limited),
+            branching_factor: Self::DEFAULT_BRANCHING_FACTOR,
+            progress: 0.0,
+        }
+    }
+
+    /// Sets the branching factor used for depth-limited searches. Values below 1.0 are treated as 1.0.
+    pub fn with_branching_factor(mut self, branching_factor: f64) -> Self {
+        self.branching_factor = branching_factor.max(1.0);
+        self
+    }
+
+    fn is_determinate(&self) -> bool {
+        self.movetime.is_some() || self.nodes.is_some() || self.depth.is_some()
+    }
+
+    /// Folds a single [`GoEvent`] into the estimate.
+    pub fn update(&mut self, event: &GoEvent) {
+        let info = match event {
+            GoEvent::Info(InfoCommand::Depth(info)) => info,
+            GoEvent::Info(_) | GoEvent::Refutation(_) | GoEvent::CurrLine(_) => return,
+            GoEvent::BestMove(_) => {
+                self.progress = 1.0;
+                return;
+            }
+        };
+
+        let by_time = self
+            .movetime
+            .zip(info.time)
+            .map(|(movetime, time)| ratio(time as f64, movetime as f64));
+        let by_nodes = self
+            .nodes
+            .zip(info.nodes)
+            .map(|(limit, nodes)| ratio(nodes as f64, limit as f64));
+        let by_depth = self.depth.zip(info.depth).map(|(limit, depth)| {
+            if depth >= limit {
+                1.0
+            } else {
+                self.branching_factor.powi(depth as i32 - limit as i32)
+            }
+        });
+
+        for estimate in [by_time, by_nodes, by_depth].into_iter().flatten() {
+            self.progress = self.progress.max(estimate.clamp(0.0, 1.0));
+        }
+    }
+
+    pub fn estimate(&self) -> SearchProgress {
+        if self.is_determinate() || self.progress == 1.0 {
+            SearchProgress::Determinate(self.progress)
+        } else {
+            SearchProgress::Indeterminate
+        }
+    }
+}
+
+/// `part / whole`, treating a zero `whole` as already reached.
+fn ratio(part: f64, whole: f64) -> f64 {
+    if whole == 0.0 { 1.0 } else { part / whole }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimates(go: &GoCommand, transcript: &str) -> Vec<SearchProgress> {
+        let mut estimator = SearchProgressEstimator::new(go);
+        transcript
+            .lines()
+            .map(|line| {
+                let event: GoEvent = line.parse().expect("Failed to parse GoEvent");
+                estimator.update(&event);
+                estimator.estimate()
+            })
+            .collect()
+    }
+
+    fn determinate(estimates: &[SearchProgress]) -> Vec<f64> {
+        estimates
+            .iter()
+            .map(|estimate| match estimate {
+                SearchProgress::Determinate(progress) => *progress,
+                SearchProgress::Indeterminate => panic!("Expected a determinate estimate"),
+            })
+            .collect()
+    }
+
+    fn assert_monotonic(progress: &[f64]) {
+        assert!(
+            progress.windows(2).all(|w| w[0] <= w[1]),
+            "Not monotonic: {progress:?}"
+        );
+    }
+
+    #[test]
+    fn test_progress_movetime() {
+        let transcript = "info string Using 1 thread
+info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 20000 time 250 pv e2e4
+info depth 2 seldepth 3 multipv 1 score cp 34 nodes 45 nps 11250 time 500 pv e2e4
+info depth 3 seldepth 4 multipv 1 score cp 42 nodes 72 nps 14400 time 400 pv e2e4
+info depth 4 seldepth 7 multipv 1 score cp 39 nodes 512 nps 85333 time 1200 pv g1f3
+bestmove g1f3";
+
+        let progress = determinate(&estimates(
+            &GoCommand::for_movetime(std::time::Duration::from_secs(1)),
+            transcript,
+        ));
+
+        assert_eq!(progress, [0.0, 0.25, 0.5, 0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_progress_nodes() {
+        let transcript =
+            "info depth 1 seldepth 2 multipv 1 score cp 17 nodes 100 nps 20000 time 1 pv e2e4
+info depth 2 seldepth 3 multipv 1 score cp 34 nodes 300 nps 11250 time 2 pv e2e4
+info depth 3 seldepth 4 multipv 1 score cp 42 nodes 800 nps 14400 time 3 pv e2e4
+bestmove e2e4";
+        let go = GoCommand {
+            nodes: Some(1000),
+            ..Default::default()
+        };
+
+        let progress = determinate(&estimates(&go, transcript));
+
+        assert_eq!(progress, [0.1, 0.3, 0.8, 1.0]);
+    }
+
+    #[test]
+    fn test_progress_depth() {
+        let transcript =
+            "info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 20000 time 1 pv e2e4
+info depth 2 seldepth 3 multipv 1 score cp 34 nodes 45 nps 11250 time 4 pv e2e4
+info depth 3 seldepth 4 multipv 1 score cp 42 nodes 72 nps 14400 time 5 pv e2e4
+info depth 3 seldepth 5 multipv 2 score cp 12 nodes 80 nps 14400 time 5 pv d2d4
+info depth 4 seldepth 7 multipv 1 score cp 39 nodes 512 nps 85333 time 6 pv g1f3 d7d5 d2d4
+bestmove g1f3 ponder d7d5";
+
+        let progress = determinate(&estimates(&GoCommand::for_depth(4), transcript));
+        assert_monotonic(&progress);
+        assert_eq!(progress, [0.125, 0.25, 0.5, 0.5, 1.0, 1.0]);
+
+        let mut estimator =
+            SearchProgressEstimator::new(&GoCommand::for_depth(4)).with_branching_factor(4.0);
+        estimator.update(&transcript.lines().nth(2).unwrap().parse().unwrap());
+        assert_eq!(estimator.estimate(), SearchProgress::Determinate(0.25));
+    }
+
+    #[test]
+    fn test_progress_infinite() {
+        let transcript =
+            "info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 20000 time 1 pv e2e4
+info depth 2 seldepth 3 multipv 1 score cp 34 nodes 45 nps 11250 time 4 pv e2e4
+bestmove e2e4";
+        let go = GoCommand {
+            indefinite: true,
+            depth: Some(1),
+            ..Default::default()
+        };
+
+        let estimates = estimates(&go, transcript);
+
+        assert_eq!(
+            estimates,
+            [
+                SearchProgress::Indeterminate,
+                SearchProgress::Indeterminate,
+                SearchProgress::Determinate(1.0),
+            ]
+        );
+    }
+}
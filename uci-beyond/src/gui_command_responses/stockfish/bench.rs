@@ -0,0 +1,242 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+
+use crate::{
+    command::{self, Command as _},
+    engine_commands::BestMoveCommand,
+    model,
+    util::{AsyncReadable, StreamingLineReader, read_line},
+};
+
+/// The response to [`BenchCommand`](crate::gui_commands::stockfish::BenchCommand).
+///
+/// ```text
+/// Position: 1/50 (rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1)
+/// info string Available processors: 0-7
+/// ...
+/// info depth 13 seldepth 17 multipv 1 score cp 33 nodes 18210 nps 1011666 hashfull 8 tbhits 0 time 18 pv e2e4 e7e5
+/// bestmove e2e4 ponder e7e5
+///
+/// Position: 2/50 (r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10)
+/// ...
+/// ===========================
+/// Total time (ms) : 1906
+/// Nodes searched  : 2030154
+/// Nodes/second    : 1065138
+/// ```
+///
+/// The search output of each position is skipped, except for its `bestmove`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchResponse {
+    /// The benchmarked positions in the order they were searched.
+    pub positions: Vec<BenchPosition>,
+    pub total_time_ms: u64,
+    pub nodes_searched: u64,
+    pub nodes_per_second: u64,
+}
+
+/// A position searched by `bench`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchPosition {
+    pub fen: model::FenString,
+    /// `None` for the limit types that don't search, e.g. `eval` or `perft`.
+    pub best_move: Option<BestMoveCommand>,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum BenchResponseParsingError {
+    #[error("Invalid position line: `{0}`.")]
+    InvalidPosition(String),
+    #[error("Invalid summary line: `{0}`.")]
+    InvalidSummary(String),
+    #[error("The output ended before the summary.")]
+    IncompleteResponse,
+}
+
+impl BenchResponseParsingError {
+    fn wrap<RR>(self) -> Result<Option<Result<BenchResponse, command::parsing::Error<Self>>>, RR> {
+        command::parsing::Error::from(self).wrap()
+    }
+}
+
+/// Parses `1/50 (rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1)`, following `Position:`.
+fn parse_position(rest: &str) -> Option<model::FenString> {
+    let (_counter, fen) = rest.trim().split_once(' ')?;
+    let fen = fen.strip_prefix('(')?.strip_suffix(')')?;
+    Some(model::FenString(fen.to_string()))
+}
+
+/// Parses the value of a summary line, e.g. ` 1906` in `Total time (ms) : 1906`.
+fn parse_summary_value(value: &str, line: &str) -> Result<u64, BenchResponseParsingError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| BenchResponseParsingError::InvalidSummary(line.to_string()))
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for BenchResponse {
+    type Err = command::parsing::Error<BenchResponseParsingError>;
+
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let mut positions: Vec<BenchPosition> = Vec::new();
+        let mut total_time_ms = None;
+        let mut nodes_searched = None;
+        let mut is_first_line = true;
+
+        // The block ends with the `Nodes/second` line of the summary
+        loop {
+            let Some(line) = read_line(reader).await? else {
+                if is_first_line {
+                    return Ok(None);
+                }
+                return BenchResponseParsingError::IncompleteResponse.wrap();
+            };
+            is_first_line = false;
+
+            if let Some(rest) = line.strip_prefix("Position:") {
+                let Some(fen) = parse_position(rest) else {
+                    return BenchResponseParsingError::InvalidPosition(line).wrap();
+                };
+                positions.push(BenchPosition {
+                    fen,
+                    best_move: None,
+                });
+            } else if BestMoveCommand::matches_line(&line) {
+                // The bestmove lines of the search are the only ones kept
+                if let (Some(position), Ok(best_move)) =
+                    (positions.last_mut(), BestMoveCommand::from_str(&line))
+                {
+                    position.best_move = Some(best_move);
+                }
+            } else if let Some((key, value)) = line.split_once(':') {
+                let res = match key.trim() {
+                    "Total time (ms)" => parse_summary_value(value, &line).map(|v| {
+                        total_time_ms = Some(v);
+                    }),
+                    "Nodes searched" => parse_summary_value(value, &line).map(|v| {
+                        nodes_searched = Some(v);
+                    }),
+                    "Nodes/second" => {
+                        let nodes_per_second = match parse_summary_value(value, &line) {
+                            Ok(v) => v,
+                            Err(e) => return e.wrap(),
+                        };
+                        let (Some(total_time_ms), Some(nodes_searched)) =
+                            (total_time_ms, nodes_searched)
+                        else {
+                            return BenchResponseParsingError::InvalidSummary(line).wrap();
+                        };
+                        return Ok(Some(Ok(BenchResponse {
+                            positions,
+                            total_time_ms,
+                            nodes_searched,
+                            nodes_per_second,
+                        })));
+                    }
+                    _ => Ok(()),
+                };
+                if let Err(e) = res {
+                    return e.wrap();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `bench 16 1 5 positions.fen depth` in the format of Stockfish 17.1, with a FEN file of three positions.
+    ///
+    /// The transcript is synthetic rather than captured, but its summary agrees with the searches,
+    /// e.g. the nodes searched are the sum of the nodes of the last `info` of every position.
+    const BENCH: &str = "
+Position: 1/3 (rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1)
+info string Available processors: 0-7
+info string Using 1 thread
+info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))
+info string NNUE evaluation using nn-37f18f62d772.nnue (6MiB, (22528, 128, 15, 32, 1))
+info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 10000 hashfull 0 tbhits 0 time 2 pv e2e4
+info depth 2 seldepth 3 multipv 1 score cp 34 nodes 45 nps 15000 hashfull 0 tbhits 0 time 3 pv e2e4
+info depth 3 seldepth 4 multipv 1 score cp 42 nodes 72 nps 18000 hashfull 0 tbhits 0 time 4 pv e2e4
+info depth 4 seldepth 7 multipv 1 score cp 39 nodes 512 nps 102400 hashfull 0 tbhits 0 time 5 pv g1f3 d7d5 d2d4
+info depth 5 seldepth 7 multipv 1 score cp 58 nodes 609 nps 101500 hashfull 0 tbhits 0 time 6 pv e2e4
+bestmove e2e4
+
+Position: 2/3 (r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10)
+info depth 1 seldepth 2 multipv 1 score cp 5 nodes 99 nps 49500 hashfull 0 tbhits 0 time 2 pv e2a6
+info depth 2 seldepth 4 multipv 1 score cp 5 nodes 290 nps 96666 hashfull 0 tbhits 0 time 3 pv e2a6 b4c3
+info depth 3 seldepth 6 multipv 1 score cp 5 nodes 1050 nps 262500 hashfull 0 tbhits 0 time 4 pv e2a6 b4c3 d2c3
+info depth 4 seldepth 6 multipv 1 score cp 5 nodes 1812 nps 362400 hashfull 0 tbhits 0 time 5 pv e2a6 b4c3 d2c3 h3g2
+info depth 5 seldepth 8 multipv 1 score cp 5 nodes 3251 nps 464428 hashfull 1 tbhits 0 time 7 pv e2a6 b4c3 d2c3 h3g2 f3g2
+bestmove e2a6 ponder b4c3
+
+Position: 3/3 (8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 11)
+info depth 1 seldepth 1 multipv 1 score cp 56 nodes 17 nps 17000 hashfull 0 tbhits 0 time 1 pv b4b1
+info depth 2 seldepth 2 multipv 1 score cp 64 nodes 48 nps 24000 hashfull 0 tbhits 0 time 2 pv e2e4
+info depth 3 seldepth 3 multipv 1 score cp 64 nodes 103 nps 51500 hashfull 0 tbhits 0 time 2 pv e2e4 f4e3
+info depth 4 seldepth 4 multipv 1 score cp 64 nodes 196 nps 65333 hashfull 0 tbhits 0 time 3 pv e2e4 f4e3 g2g3
+info depth 5 seldepth 5 multipv 1 score cp 64 nodes 296 nps 74000 hashfull 0 tbhits 0 time 4 pv e2e4 f4e3 g2g3 h4g4
+bestmove e2e4 ponder f4e3
+
+===========================
+Total time (ms) : 13
+Nodes searched  : 4156
+Nodes/second    : 319692
+readyok
+";
+
+    #[tokio::test]
+    async fn test_read_bench_response() {
+        let mut reader = tokio::io::BufReader::new(BENCH.as_bytes());
+        let response = BenchResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(response.total_time_ms, 13);
+        assert_eq!(response.nodes_searched, 4156);
+        assert_eq!(response.nodes_per_second, 319692);
+
+        assert_eq!(response.positions.len(), 3);
+        assert_eq!(
+            response.positions[1].fen,
+            model::FenString(
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10".to_string()
+            )
+        );
+        assert_eq!(
+            response.positions[1].best_move,
+            Some("bestmove e2a6 ponder b4c3".parse().unwrap())
+        );
+
+        // The reader stops at the summary
+        let rest = read_line(&mut reader).await.unwrap();
+        assert_eq!(rest.as_deref(), Some("readyok"));
+    }
+
+    #[tokio::test]
+    async fn test_read_incomplete_bench_response() {
+        let input = "Position: 1/1 (8/8/8/8/8/8/8/K6k w - - 0 1)\nbestmove a1a2\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let err = BenchResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            command::parsing::Error::CustomError(BenchResponseParsingError::IncompleteResponse)
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+
+use crate::{
+    command,
+    util::{AsyncReadable, StreamingLineReader, read_block_until},
+};
+
+/// The response to [`CompilerCommand`](crate::gui_commands::stockfish::CompilerCommand).
+///
+/// ```text
+/// Compiled by                : g++ (GNUC) 14.2.0 on Linux
+/// Compilation architecture   : x86-64-avx2
+/// Compilation settings       : 64bit BMI2 AVX2 SSE41 SSSE3 SSE2 POPCNT
+/// Compiler __VERSION__ macro : 14.2.0
+///
+/// ```
+///
+/// The block ends with an empty line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompilerResponse {
+    /// The lines of the block, without the empty line that ends it.
+    pub lines: Vec<String>,
+    /// The compiler, if it's recognizable from the `Compiled by` line.
+    pub compiler: Option<CompilerInfo>,
+}
+
+/// The compiler that built the engine, e.g. `g++` `14.2.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompilerInfo {
+    pub name: String,
+    pub version: String,
+}
+
+impl CompilerInfo {
+    /// Parses the value of the `Compiled by` line, e.g. `g++ (GNUC) 14.2.0 on Linux` or `clang++ 18.1.3 on Linux`.
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.rsplit_once(" on ").map_or(s, |(compiler, _os)| compiler);
+
+        let mut tokens = s.split_whitespace();
+        let name = tokens.next()?;
+        let version = tokens
+            .rev()
+            .find(|token| token.starts_with(|c: char| c.is_ascii_digit()))?;
+
+        Some(CompilerInfo {
+            name: name.to_string(),
+            version: version.to_string(),
+        })
+    }
+}
+
+impl CompilerResponse {
+    /// The value of the line with the given key, e.g. `x86-64-avx2` for `Compilation architecture`.
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim() == key).then(|| v.trim())
+        })
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum CompilerResponseParsingError {
+    #[error("The output ended before the empty line.")]
+    IncompleteResponse,
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for CompilerResponse {
+    type Err = command::parsing::Error<CompilerResponseParsingError>;
+
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let mut lines = match read_block_until(reader, |line| line.trim().is_empty()).await? {
+            Some(Ok(lines)) => lines,
+            Some(Err(_)) => {
+                return command::parsing::Error::from(
+                    CompilerResponseParsingError::IncompleteResponse,
+                )
+                .wrap();
+            }
+            None => return Ok(None),
+        };
+        lines.pop();
+
+        let mut response = CompilerResponse {
+            lines,
+            compiler: None,
+        };
+        response.compiler = response.value("Compiled by").and_then(CompilerInfo::parse);
+
+        Ok(Some(Ok(response)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `compiler` with Stockfish 17.1.
+    const COMPILER: &str = "Compiled by                : g++ (GNUC) 14.2.0 on Linux
+Compilation architecture   : x86-64-avx2
+Compilation settings       : 64bit BMI2 AVX2 SSE41 SSSE3 SSE2 POPCNT
+Compiler __VERSION__ macro : 14.2.0
+
+readyok
+";
+
+    #[tokio::test]
+    async fn test_read_compiler_response() {
+        let mut reader = tokio::io::BufReader::new(COMPILER.as_bytes());
+        let response = CompilerResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(response.lines.len(), 4);
+        assert_eq!(
+            response.compiler,
+            Some(CompilerInfo {
+                name: "g++".to_string(),
+                version: "14.2.0".to_string(),
+            })
+        );
+        assert_eq!(
+            response.value("Compilation architecture"),
+            Some("x86-64-avx2")
+        );
+
+        let rest = crate::util::read_line(&mut reader).await.unwrap();
+        assert_eq!(rest.as_deref(), Some("readyok"));
+    }
+
+    #[test]
+    fn test_parse_compiler_info() {
+        assert_eq!(
+            CompilerInfo::parse("clang++ 18.1.3 on Linux"),
+            Some(CompilerInfo {
+                name: "clang++".to_string(),
+                version: "18.1.3".to_string(),
+            })
+        );
+        assert_eq!(
+            CompilerInfo::parse("Unknown compiler on unknown system"),
+            None
+        );
+    }
+}
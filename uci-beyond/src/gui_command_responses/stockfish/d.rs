@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+
+use crate::{
+    command, model,
+    util::{AsyncReadable, StreamingLineReader, read_block_until},
+};
+
+/// The response to [`DisplayCommand`](crate::gui_commands::stockfish::DisplayCommand).
+///
+/// ```text
+///  +---+---+---+---+---+---+---+---+
+///  | r | n | b | q | k | b | n | r | 8
+///  +---+---+---+---+---+---+---+---+
+/// ...
+///  | R | N | B | Q | K | B | N | R | 1
+///  +---+---+---+---+---+---+---+---+
+///    a   b   c   d   e   f   g   h
+///
+/// Fen: rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
+/// Key: 8F8F01D4562F59FB
+/// Checkers:
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayResponse {
+    /// The non-empty lines of the ASCII art board, as printed.
+    pub board: Vec<String>,
+    pub fen: model::FenString,
+    /// The Zobrist hash key of the position.
+    pub key: u64,
+    /// The squares of the pieces giving check, e.g. `e2`.
+    pub checkers: Vec<String>,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum DisplayResponseParsingError {
+    #[error("Invalid key: `{0}`.")]
+    InvalidKey(String),
+    #[error("The `Fen:` line is missing.")]
+    MissingFen,
+    #[error("The `Key:` line is missing.")]
+    MissingKey,
+    #[error("The output ended before the `Checkers:` line.")]
+    IncompleteResponse,
+}
+
+impl DisplayResponseParsingError {
+    fn wrap<RR>(
+        self,
+    ) -> Result<Option<Result<DisplayResponse, command::parsing::Error<Self>>>, RR> {
+        command::parsing::Error::from(self).wrap()
+    }
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for DisplayResponse {
+    type Err = command::parsing::Error<DisplayResponseParsingError>;
+
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let lines = match read_block_until(reader, |line| line.starts_with("Checkers:")).await? {
+            Some(Ok(lines)) => lines,
+            Some(Err(_)) => return DisplayResponseParsingError::IncompleteResponse.wrap(),
+            None => return Ok(None),
+        };
+
+        let mut board = Vec::new();
+        let mut fen = None;
+        let mut key = None;
+        let mut checkers = Vec::new();
+
+        for line in lines {
+            if let Some(rest) = line.strip_prefix("Fen:") {
+                fen = Some(model::FenString(rest.trim().to_string()));
+            } else if let Some(rest) = line.strip_prefix("Key:") {
+                match u64::from_str_radix(rest.trim(), 16) {
+                    Ok(k) => key = Some(k),
+                    Err(_) => {
+                        return DisplayResponseParsingError::InvalidKey(rest.trim().to_string())
+                            .wrap();
+                    }
+                }
+            } else if let Some(rest) = line.strip_prefix("Checkers:") {
+                checkers = rest.split_whitespace().map(str::to_string).collect();
+            } else if !line.trim().is_empty() {
+                board.push(line);
+            }
+        }
+
+        let Some(fen) = fen else {
+            return DisplayResponseParsingError::MissingFen.wrap();
+        };
+        let Some(key) = key else {
+            return DisplayResponseParsingError::MissingKey.wrap();
+        };
+
+        Ok(Some(Ok(DisplayResponse {
+            board,
+            fen,
+            key,
+            checkers,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `d` after `position startpos moves e2e4 d7d5 f1b5` in the format of Stockfish 17.1.
+    ///
+    /// The transcript is synthetic rather than captured: the board, the FEN and the checkers
+    /// are those of the position, but the key is made up.
+    const CHECK_DISPLAY: &str = "
+ +---+---+---+---+---+---+---+---+
+ | r | n | b | q | k | b | n | r | 8
+ +---+---+---+---+---+---+---+---+
+ | p | p | p |   | p | p | p | p | 7
+ +---+---+---+---+---+---+---+---+
+ |   |   |   |   |   |   |   |   | 6
+ +---+---+---+---+---+---+---+---+
+ |   | B |   | p |   |   |   |   | 5
+ +---+---+---+---+---+---+---+---+
+ |   |   |   |   | P |   |   |   | 4
+ +---+---+---+---+---+---+---+---+
+ |   |   |   |   |   |   |   |   | 3
+ +---+---+---+---+---+---+---+---+
+ | P | P | P | P |   | P | P | P | 2
+ +---+---+---+---+---+---+---+---+
+ | R | N | B | Q | K |   | N | R | 1
+ +---+---+---+---+---+---+---+---+
+   a   b   c   d   e   f   g   h
+
+Fen: rnbqkbnr/ppp1pppp/8/1B1p4/4P3/8/PPPP1PPP/RNBQK1NR b KQkq - 1 2
+Key: 5C4F2A9D0B1E3F86
+Checkers: b5 
+";
+
+    #[tokio::test]
+    async fn test_read_display_response() {
+        let mut reader = tokio::io::BufReader::new(CHECK_DISPLAY.as_bytes());
+        let response = DisplayResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(response.board.len(), 18);
+        assert_eq!(response.board[1], " | r | n | b | q | k | b | n | r | 8");
+        assert_eq!(
+            response.fen,
+            model::FenString(
+                "rnbqkbnr/ppp1pppp/8/1B1p4/4P3/8/PPPP1PPP/RNBQK1NR b KQkq - 1 2".to_string()
+            )
+        );
+        assert_eq!(response.key, 0x5C4F_2A9D_0B1E_3F86);
+        assert_eq!(response.checkers, ["b5"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_display_response_without_checkers() {
+        let input = "Fen: rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n\
+Key: 8F8F01D4562F59FB\n\
+Checkers: \n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let response = DisplayResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert!(response.board.is_empty());
+        assert_eq!(response.key, 0x8F8F_01D4_562F_59FB);
+        assert!(response.checkers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_display_response_with_invalid_key() {
+        let input = "Fen: 8/8/8/8/8/8/8/8 w - - 0 1\nKey: XYZ\nCheckers: \n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let err = DisplayResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            command::parsing::Error::CustomError(DisplayResponseParsingError::InvalidKey(_))
+        ));
+    }
+}
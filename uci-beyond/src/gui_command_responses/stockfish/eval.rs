@@ -0,0 +1,291 @@
+use async_trait::async_trait;
+
+use crate::{
+    command,
+    util::{AsyncReadable, StreamingLineReader, read_block_until},
+};
+
+use super::parse_pawns_as_cp;
+
+/// The response to [`EvalCommand`](crate::gui_commands::stockfish::EvalCommand).
+///
+/// ```text
+///  NNUE network contributions (White to move)
+/// +------------+------------+------------+------------+
+/// |   Bucket   |  Material  | Positional |   Total    |
+/// |            |   (PSQT)   |  (Layers)  |            |
+/// +------------+------------+------------+------------+
+/// |  0         |     0.00   |  -  0.67   |  -  0.67   |
+/// ...
+/// |  7         |     0.00   |  +  0.09   |  +  0.09   | <-- this bucket is used
+/// +------------+------------+------------+------------+
+///
+/// NNUE evaluation        +0.09 (white side)
+/// Final evaluation       +0.21 (white side) [with scaled NNUE, ...]
+/// ```
+///
+/// The table of NNUE-derived piece values that precedes it is skipped. All values are from White's point of view.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EvalResponse {
+    /// The contributions of the NNUE buckets, in the order they were printed.
+    pub nnue_contributions: Vec<NnueBucketContribution>,
+    /// The raw NNUE evaluation in centipawns. `None` if the side to move is in check.
+    pub nnue_evaluation_cp: Option<i32>,
+    /// The final evaluation in centipawns. `None` if the side to move is in check.
+    pub final_evaluation_cp: Option<i32>,
+}
+
+impl EvalResponse {
+    /// The bucket used for the evaluation of the position.
+    pub fn used_bucket(&self) -> Option<&NnueBucketContribution> {
+        self.nnue_contributions.iter().find(|c| c.used)
+    }
+}
+
+/// A row of the "NNUE network contributions" table printed by `eval`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NnueBucketContribution {
+    pub bucket: u32,
+    /// The material term (PSQT) in centipawns.
+    pub material_cp: i32,
+    /// The positional term (layers) in centipawns.
+    pub positional_cp: i32,
+    /// The total in centipawns.
+    pub total_cp: i32,
+    /// Whether the bucket is the one used for the current position.
+    pub used: bool,
+}
+
+impl NnueBucketContribution {
+    /// Parses a row of the table. Returns `Ok(None)` for the header rows.
+    fn parse_row(line: &str) -> Result<Option<Self>, EvalResponseParsingError> {
+        let cells: Vec<&str> = line.split('|').collect();
+
+        let Some(Ok(bucket)) = cells.get(1).map(|cell| cell.trim().parse::<u32>()) else {
+            return Ok(None);
+        };
+
+        let err = || EvalResponseParsingError::InvalidBucketRow(line.to_string());
+        let [material_cp, positional_cp, total_cp] = [2, 3, 4].map(|i| {
+            cells
+                .get(i)
+                .and_then(|cell| parse_pawns_as_cp(cell))
+                .ok_or_else(err)
+        });
+
+        Ok(Some(NnueBucketContribution {
+            bucket,
+            material_cp: material_cp?,
+            positional_cp: positional_cp?,
+            total_cp: total_cp?,
+            used: cells.get(5).is_some_and(|cell| cell.contains("<--")),
+        }))
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum EvalResponseParsingError {
+    #[error("Invalid row of the NNUE network contributions table: `{0}`.")]
+    InvalidBucketRow(String),
+    #[error("Invalid evaluation: `{0}`.")]
+    InvalidEvaluation(String),
+    #[error("The output ended before the final evaluation.")]
+    IncompleteResponse,
+}
+
+impl EvalResponseParsingError {
+    fn wrap<RR>(self) -> Result<Option<Result<EvalResponse, command::parsing::Error<Self>>>, RR> {
+        command::parsing::Error::from(self).wrap()
+    }
+}
+
+/// Parses the value following `NNUE evaluation` or `Final evaluation`, e.g. `       +0.21 (white side)`
+/// or `: none (in check)`.
+fn parse_evaluation(rest: &str, line: &str) -> Result<Option<i32>, EvalResponseParsingError> {
+    let value = rest
+        .trim_start_matches(':')
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| EvalResponseParsingError::InvalidEvaluation(line.to_string()))?;
+
+    if value == "none" {
+        return Ok(None);
+    }
+
+    parse_pawns_as_cp(value)
+        .map(Some)
+        .ok_or_else(|| EvalResponseParsingError::InvalidEvaluation(line.to_string()))
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for EvalResponse {
+    type Err = command::parsing::Error<EvalResponseParsingError>;
+
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let lines =
+            match read_block_until(reader, |line| line.starts_with("Final evaluation")).await? {
+                Some(Ok(lines)) => lines,
+                Some(Err(_)) => return EvalResponseParsingError::IncompleteResponse.wrap(),
+                None => return Ok(None),
+            };
+
+        let mut response = EvalResponse::default();
+        let mut in_contributions = false;
+
+        for line in &lines {
+            if let Some(rest) = line.strip_prefix("Final evaluation") {
+                match parse_evaluation(rest, line) {
+                    Ok(cp) => response.final_evaluation_cp = cp,
+                    Err(e) => return e.wrap(),
+                }
+            } else if let Some(rest) = line.strip_prefix("NNUE evaluation") {
+                match parse_evaluation(rest, line) {
+                    Ok(cp) => response.nnue_evaluation_cp = cp,
+                    Err(e) => return e.wrap(),
+                }
+            } else if line.contains("NNUE network contributions") {
+                in_contributions = true;
+            } else if in_contributions && line.starts_with('|') {
+                match NnueBucketContribution::parse_row(line) {
+                    Ok(Some(row)) => response.nnue_contributions.push(row),
+                    Ok(None) => (),
+                    Err(e) => return e.wrap(),
+                }
+            }
+        }
+
+        Ok(Some(Ok(response)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `eval` in the start position in the format of Stockfish 17.1.
+    ///
+    /// The transcript is synthetic rather than captured: the layout follows Stockfish 17.1,
+    /// but the values are made up.
+    const STARTPOS_EVAL: &str = "
+ NNUE derived piece values:
++-------+-------+-------+-------+-------+-------+-------+-------+
+|   r   |   n   |   b   |   q   |   k   |   b   |   n   |   r   |
+| -0.21 | -0.41 | -0.46 | -1.24 |       | -0.45 | -0.40 | -0.21 |
++-------+-------+-------+-------+-------+-------+-------+-------+
+|   p   |   p   |   p   |   p   |   p   |   p   |   p   |   p   |
+| -0.05 | -0.09 | -0.11 | -0.17 | -0.16 | -0.12 | -0.09 | -0.05 |
++-------+-------+-------+-------+-------+-------+-------+-------+
+|       |       |       |       |       |       |       |       |
+|       |       |       |       |       |       |       |       |
++-------+-------+-------+-------+-------+-------+-------+-------+
+|       |       |       |       |       |       |       |       |
+|       |       |       |       |       |       |       |       |
++-------+-------+-------+-------+-------+-------+-------+-------+
+|       |       |       |       |       |       |       |       |
+|       |       |       |       |       |       |       |       |
++-------+-------+-------+-------+-------+-------+-------+-------+
+|       |       |       |       |       |       |       |       |
+|       |       |       |       |       |       |       |       |
++-------+-------+-------+-------+-------+-------+-------+-------+
+|   P   |   P   |   P   |   P   |   P   |   P   |   P   |   P   |
+| +0.05 | +0.09 | +0.11 | +0.17 | +0.16 | +0.12 | +0.09 | +0.05 |
++-------+-------+-------+-------+-------+-------+-------+-------+
+|   R   |   N   |   B   |   Q   |   K   |   B   |   N   |   R   |
+| +0.21 | +0.41 | +0.46 | +1.24 |       | +0.45 | +0.40 | +0.21 |
++-------+-------+-------+-------+-------+-------+-------+-------+
+
+ NNUE network contributions (White to move)
++------------+------------+------------+------------+
+|   Bucket   |  Material  | Positional |   Total    |
+|            |   (PSQT)   |  (Layers)  |            |
++------------+------------+------------+------------+
+|  0         |     0.00   |  -  0.67   |  -  0.67   |
+|  1         |     0.00   |  +  0.10   |  +  0.10   |
+|  2         |     0.00   |  +  0.05   |  +  0.05   |
+|  3         |     0.00   |  +  0.02   |  +  0.02   |
+|  4         |     0.00   |  +  0.04   |  +  0.04   |
+|  5         |     0.00   |  +  0.05   |  +  0.05   |
+|  6         |     0.00   |  +  0.07   |  +  0.07   |
+|  7         |     0.00   |  +  0.09   |  +  0.09   | <-- this bucket is used
++------------+------------+------------+------------+
+
+NNUE evaluation        +0.09 (white side)
+Final evaluation       +0.21 (white side) [with scaled NNUE, optimism, ...]
+readyok
+";
+
+    #[tokio::test]
+    async fn test_read_eval_response() {
+        let mut reader = tokio::io::BufReader::new(STARTPOS_EVAL.as_bytes());
+        let response = EvalResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(response.nnue_contributions.len(), 8);
+        assert_eq!(
+            response.nnue_contributions[0],
+            NnueBucketContribution {
+                bucket: 0,
+                material_cp: 0,
+                positional_cp: -67,
+                total_cp: -67,
+                used: false,
+            }
+        );
+        assert_eq!(
+            response.used_bucket(),
+            Some(&NnueBucketContribution {
+                bucket: 7,
+                material_cp: 0,
+                positional_cp: 9,
+                total_cp: 9,
+                used: true,
+            })
+        );
+        assert_eq!(response.nnue_evaluation_cp, Some(9));
+        assert_eq!(response.final_evaluation_cp, Some(21));
+
+        // The reader stops at the final evaluation
+        let rest = crate::util::read_line(&mut reader).await.unwrap();
+        assert_eq!(rest.as_deref(), Some("readyok"));
+    }
+
+    #[tokio::test]
+    async fn test_read_eval_response_in_check() {
+        let input = "\nFinal evaluation: none (in check)\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let response = EvalResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert!(response.nnue_contributions.is_empty());
+        assert_eq!(response.nnue_evaluation_cp, None);
+        assert_eq!(response.final_evaluation_cp, None);
+    }
+
+    #[tokio::test]
+    async fn test_read_incomplete_eval_response() {
+        let input = "\n NNUE network contributions (White to move)\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let err = EvalResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            command::parsing::Error::CustomError(EvalResponseParsingError::IncompleteResponse)
+        ));
+    }
+}
@@ -0,0 +1,37 @@
+//! The module for the responses to the non-standard commands supported by Stockfish,
+//! see [`gui_commands::stockfish`](crate::gui_commands::stockfish).
+//!
+//! Unlike UCI responses, these are human-readable blocks of lines, so each of them is read
+//! with [`read_block_until`](crate::util::read_block_until) and the line that ends the block,
+//! or line by line with [`read_line`](crate::util::read_line) when the block is large, as in the case of `bench`.
+
+mod bench;
+mod compiler;
+mod d;
+mod eval;
+
+pub use bench::{BenchPosition, BenchResponse, BenchResponseParsingError};
+pub use compiler::{CompilerInfo, CompilerResponse, CompilerResponseParsingError};
+pub use d::{DisplayResponse, DisplayResponseParsingError};
+pub use eval::{EvalResponse, EvalResponseParsingError, NnueBucketContribution};
+
+/// Parses a value in pawns, as printed by Stockfish (e.g. `+0.18` or `-  0.60`), into centipawns.
+fn parse_pawns_as_cp(s: &str) -> Option<i32> {
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let pawns: f64 = s.parse().ok()?;
+    Some((pawns * 100.0).round() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pawns_as_cp() {
+        assert_eq!(parse_pawns_as_cp("+0.18"), Some(18));
+        assert_eq!(parse_pawns_as_cp("-  0.60"), Some(-60));
+        assert_eq!(parse_pawns_as_cp("0.00"), Some(0));
+        assert_eq!(parse_pawns_as_cp("+ 12.34"), Some(1234));
+        assert_eq!(parse_pawns_as_cp("none"), None);
+    }
+}
@@ -0,0 +1,281 @@
+use std::fmt::Display;
+
+use async_trait::async_trait;
+
+use crate::{
+    command,
+    engine_commands::{AnyEngineCommandKind, BestMoveCommand},
+    engine_quirks::Quirks,
+    gui_command_responses::{GoEvent, SearchSummary},
+    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
+};
+
+/// The response to `stop` during a search, i.e. the events the engine sends before `bestmove`
+/// as it winds the search down, e.g. the final `info` line, and `bestmove` itself.
+///
+/// The events sent before `stop` belong to the [`GoResponse`](crate::gui_command_responses::GoResponse)
+/// of the search, so this is only the tail of it.
+///
+/// ```
+/// use uci_beyond::{gui_command_responses::StopResponse, model::MoveString, util::AsyncReadable};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let output = "info depth 24 score cp 31 nodes 1500000 time 1200 pv e2e4 e7e5\n\
+///     bestmove e2e4 ponder e7e5\n";
+/// let mut reader = tokio::io::BufReader::new(output.as_bytes());
+/// let response = StopResponse::read_from(&mut reader)
+///     .await
+///     .unwrap()
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(response.events.len(), 1);
+/// assert_eq!(response.best_move.best_move, MoveString::from("e2e4"));
+/// assert_eq!(response.search_summary().depth, Some(24));
+/// assert_eq!(response.clone(), response);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StopResponse {
+    /// The line acknowledging `stop`, read with [`Quirks::ack_before_bestmove`], e.g.
+    /// `info string stop acknowledged`. It isn't one of the events.
+    pub acknowledgment: Option<String>,
+    /// The events before `bestmove`, which is never one of them.
+    pub events: Vec<GoEvent>,
+    pub best_move: BestMoveCommand,
+}
+
+impl StopResponse {
+    /// The summary of the events after `stop`, including the best move.
+    ///
+    /// The depth and the statistics are those of the last `info` line after `stop`, if any, so they may
+    /// be missing even though the search reported them before.
+    pub fn search_summary(&self) -> SearchSummary {
+        SearchSummary::from_events(
+            self.events
+                .iter()
+                .cloned()
+                .chain([GoEvent::BestMove(self.best_move.clone())]),
+        )
+    }
+
+    /// Whether the line acknowledges `stop` (see [`Quirks::ack_before_bestmove`]), i.e. it is
+    /// an `info string` line or a line of no known command that mentions `stop`, in any case.
+    pub fn is_acknowledgment(line: &str) -> bool {
+        let line = line.trim();
+        let text = match AnyEngineCommandKind::of_line(line) {
+            AnyEngineCommandKind::Info => match line.strip_prefix("info string ") {
+                Some(text) => text,
+                None => return false,
+            },
+            AnyEngineCommandKind::Other => line,
+            _ => return false,
+        };
+        text.to_ascii_lowercase().contains("stop")
+    }
+
+    /// Reads the response with the leniencies of the [`Quirks`]:
+    ///
+    /// * with [`Quirks::ack_before_bestmove`], the first acknowledgment (see [`is_acknowledgment`](Self::is_acknowledgment))
+    ///   is kept in [`acknowledgment`](Self::acknowledgment) rather than among the events;
+    /// * with [`Quirks::skip_unknown_lines`], the lines that can't be parsed as [`GoEvent`]s are skipped.
+    pub async fn read_with<R>(
+        reader: &mut R,
+        quirks: &Quirks,
+    ) -> Result<Option<Result<Self, <Self as AsyncReadable>::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let mut acknowledgment = None;
+        let mut events = Vec::new();
+        let mut is_first = true;
+        loop {
+            let expects_acknowledgment = quirks.ack_before_bestmove && acknowledgment.is_none();
+            let f = |line: &str| -> LineHandlerOutcome<Line, <Self as AsyncReadable>::Err> {
+                let line = line.trim();
+                if expects_acknowledgment && StopResponse::is_acknowledgment(line) {
+                    return LineHandlerOutcome::Read(Line::Acknowledgment(line.to_string()));
+                }
+                match line.parse::<GoEvent>() {
+                    Ok(event) => LineHandlerOutcome::Read(Line::Event(event)),
+                    Err(_) if quirks.skip_unknown_lines => LineHandlerOutcome::Read(Line::Skipped),
+                    Err(e) => LineHandlerOutcome::Error(e),
+                }
+            };
+
+            match handle_next_line(reader, f).await? {
+                Some(LineHandlerOutcome::Read(Line::Event(GoEvent::BestMove(best_move)))) => {
+                    return Ok(Some(Ok(StopResponse {
+                        acknowledgment,
+                        events,
+                        best_move,
+                    })));
+                }
+                Some(LineHandlerOutcome::Read(Line::Event(event))) => events.push(event),
+                Some(LineHandlerOutcome::Read(Line::Acknowledgment(line))) => {
+                    acknowledgment = Some(line);
+                }
+                Some(LineHandlerOutcome::Read(Line::Skipped)) => {}
+                Some(LineHandlerOutcome::Error(e)) => return Ok(Some(Err(e))),
+                Some(LineHandlerOutcome::Peeked) => {
+                    return command::parsing::Error::UnexpectedPeekOutput.wrap();
+                }
+                None if is_first => return Ok(None),
+                None => return command::parsing::Error::UnexpectedEof.wrap(),
+            }
+            is_first = false;
+        }
+    }
+}
+
+/// Writes the acknowledgment, if any, and the events line by line, as the engine sends them in reply to `stop`.
+impl Display for StopResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(acknowledgment) = &self.acknowledgment {
+            writeln!(f, "{acknowledgment}")?;
+        }
+        for event in &self.events {
+            writeln!(f, "{event}")?;
+        }
+        writeln!(f, "{}", self.best_move)
+    }
+}
+
+enum Line {
+    Event(GoEvent),
+    Acknowledgment(String),
+    Skipped,
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for StopResponse {
+    type Err = <GoEvent as AsyncReadable>::Err;
+
+    /// Reads the [`GoEvent`]s up to and including `bestmove` strictly, see [`StopResponse::read_with`].
+    /// The end of the output before it is [`command::parsing::Error::UnexpectedEof`].
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        StopResponse::read_with(reader, &Quirks::default()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model;
+
+    #[tokio::test]
+    async fn test_read_stop_response() {
+        let output = "info depth 12 seldepth 18 score cp 25 nodes 91000 time 150 pv d2d4 d7d5\n\
+                      bestmove d2d4 ponder d7d5\n";
+        let mut reader = tokio::io::BufReader::new(output.as_bytes());
+        let response = StopResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.events.len(), 1);
+        assert_eq!(
+            response.best_move.ponder,
+            Some(model::MoveString::from("d7d5"))
+        );
+
+        let summary = response.search_summary();
+        assert_eq!(summary.depth, Some(12));
+        assert_eq!(summary.best_move.as_ref(), Some(&response.best_move));
+
+        let written = response.to_string();
+        let mut reader = tokio::io::BufReader::new(written.as_bytes());
+        assert_eq!(
+            StopResponse::read_from(&mut reader)
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap(),
+            response
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_stop_response_without_info() {
+        let mut reader = tokio::io::BufReader::new("bestmove e2e4\n".as_bytes());
+        let response = StopResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(response.events.is_empty());
+        assert_eq!(response.best_move.ponder, None);
+    }
+
+    #[tokio::test]
+    async fn test_read_stop_response_eof() {
+        let mut reader = tokio::io::BufReader::new("".as_bytes());
+        assert!(
+            StopResponse::read_from(&mut reader)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let mut reader = tokio::io::BufReader::new("info depth 3 pv e2e4\n".as_bytes());
+        assert!(matches!(
+            StopResponse::read_from(&mut reader).await.unwrap(),
+            Some(Err(command::parsing::Error::UnexpectedEof))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_stop_response_of_multiplexed_service() {
+        // A hosted service sharing one engine between several clients acknowledges `stop` on its own
+        let output = "info string stop acknowledged\n\
+                      info depth 18 seldepth 25 score cp 34 nodes 2400000 time 900 pv g1f3 d7d5\n\
+                      session 7f3a detached\n\
+                      bestmove g1f3 ponder d7d5\n";
+        let quirks = Quirks {
+            ack_before_bestmove: true,
+            skip_unknown_lines: true,
+            ..Default::default()
+        };
+        let mut reader = tokio::io::BufReader::new(output.as_bytes());
+        let response = StopResponse::read_with(&mut reader, &quirks)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            response.acknowledgment.as_deref(),
+            Some("info string stop acknowledged")
+        );
+        assert_eq!(response.events.len(), 1);
+        assert_eq!(
+            response.best_move.best_move,
+            model::MoveString::from("g1f3")
+        );
+        assert_eq!(response.search_summary().depth, Some(18));
+
+        // Strictly, the acknowledgment is an event and the vendor line is an error
+        let mut reader = tokio::io::BufReader::new(output.as_bytes());
+        assert!(matches!(
+            StopResponse::read_from(&mut reader).await.unwrap(),
+            Some(Err(command::parsing::Error::UnexpectedCommand(_)))
+        ));
+    }
+
+    #[test]
+    fn test_is_acknowledgment() {
+        assert!(StopResponse::is_acknowledgment(
+            "info string stop acknowledged\n"
+        ));
+        assert!(StopResponse::is_acknowledgment("STOP OK"));
+        assert!(!StopResponse::is_acknowledgment(
+            "info string Using 4 threads"
+        ));
+        assert!(!StopResponse::is_acknowledgment("bestmove e2e4"));
+        assert!(!StopResponse::is_acknowledgment("info depth 3 pv e2e4"));
+    }
+}
@@ -1,23 +1,354 @@
-use async_trait::async_trait;
+use std::{
+    convert::Infallible,
+    fmt::Display,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
 
 use crate::{
-    command,
+    command::{self, Command as _},
     engine_commands::{
-        IdBlock, IdBlockParsingError, OptionBlockParsingError, UciOkCommand,
-        UciOkCommandParsingError, UciOptionBlockBuilder,
+        IdBlock, IdBlockParsingError, IdCommand, OptionBlockParsingError, OptionCommand,
+        UciOkCommand, UciOkCommandParsingError, UciOptionBlockBuilder,
+    },
+    engine_quirks::Quirks,
+    options::{UciOption, UciOptionKind},
+    util::{
+        AsyncReadable, AsyncWritable, FanOutObserver, LineHandlerOutcome, LineObserver,
+        StreamingLineReader, handle_next_line,
     },
-    util::{AsyncReadable, LineHandlerOutcome, handle_next_line},
 };
+use async_trait::async_trait;
+
+/// The default limit of the greeting lines skipped by [`GreetingPolicy::Skip`].
+pub const DEFAULT_MAX_GREETING_LINES: usize = 4;
+
+/// How [`UciResponse::read_with`] treats the lines before the id block.
+///
+/// Stockfish greets with a banner (e.g. `Stockfish 17.1 by the Stockfish developers (see AUTHORS file)`)
+/// as soon as it starts. A connection that didn't skip it would otherwise fail to parse the id block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GreetingPolicy {
+    /// Skip up to `max_lines` lines that don't start with `id`, keeping them as
+    /// [`UciResponse::greeting`].
+    Skip { max_lines: usize },
+    /// Expect the id block right away, e.g. for engine conformance testing.
+    Strict,
+}
+
+impl Default for GreetingPolicy {
+    fn default() -> Self {
+        GreetingPolicy::Skip {
+            max_lines: DEFAULT_MAX_GREETING_LINES,
+        }
+    }
+}
+
+/// The default of [`ReadLimits::max_option_lines`].
+pub const DEFAULT_MAX_OPTION_LINES: usize = 4096;
+
+/// The default of [`ReadLimits::max_bytes`].
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 1 << 20;
+
+/// The bounds on reading a [`UciResponse`], so that a buggy or malicious engine that
+/// keeps advertising options can't make the reader loop forever or allocate without bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+    pub max_option_lines: usize,
+    /// The total size of the lines of the response, without the line terminators.
+    pub max_bytes: usize,
+    /// The time to read the whole response. Unlimited if `None`.
+    pub deadline: Option<Duration>,
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self {
+        ReadLimits {
+            max_option_lines: DEFAULT_MAX_OPTION_LINES,
+            max_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            deadline: None,
+        }
+    }
+}
+
+impl ReadLimits {
+    fn check(&self, usage: ReadUsage) -> Result<(), ReadLimitExceeded> {
+        if usage.option_lines > self.max_option_lines {
+            Err(ReadLimitExceeded::OptionLines(self.max_option_lines))
+        } else if usage.bytes > self.max_bytes {
+            Err(ReadLimitExceeded::Bytes(self.max_bytes))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The [`ReadLimits`] that was exceeded, with its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ReadLimitExceeded {
+    #[error("More than {0} option lines")]
+    OptionLines(usize),
+    #[error("More than {0} bytes")]
+    Bytes(usize),
+    #[error("The response took longer than {0:?}")]
+    Deadline(Duration),
+}
+
+/// How [`UciResponse::read_with_options`] reads the response.
+///
+/// Since it holds the observer, it isn't [`Copy`], and two options are equal only if they share the observer.
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    pub greeting: GreetingPolicy,
+    /// The leniencies for the engine. When `None`, they are selected from the id block
+    /// (see [`Quirks::for_id_block`]) and apply to the options and the lines before `uciok`.
+    pub quirks: Option<Quirks>,
+    pub limits: ReadLimits,
+    /// The observer of the lines of the response. The observer of the reader, if any, is reported to as well.
+    pub observer: Option<Arc<dyn LineObserver>>,
+}
+
+impl ReadOptions {
+    pub fn with_observer(mut self, observer: Arc<dyn LineObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+}
+
+impl PartialEq for ReadOptions {
+    fn eq(&self, other: &Self) -> bool {
+        let same_observer = match (&self.observer, &other.observer) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        self.greeting == other.greeting
+            && self.quirks == other.quirks
+            && self.limits == other.limits
+            && same_observer
+    }
+}
+
+impl Eq for ReadOptions {}
+
+/// What was read of a response so far, checked against the [`ReadLimits`].
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ReadUsage {
+    option_lines: usize,
+    bytes: usize,
+}
+
+impl ReadUsage {
+    /// Counts the line, failing if that exceeds the limits.
+    pub(crate) fn read_line(
+        &mut self,
+        line: &str,
+        limits: &ReadLimits,
+    ) -> Result<(), ReadLimitExceeded> {
+        let usage = self.with_line(line);
+        limits.check(usage)?;
+        *self = usage;
+        Ok(())
+    }
+
+    fn with_line(self, line: &str) -> Self {
+        ReadUsage {
+            option_lines: self.option_lines + usize::from(OptionCommand::matches_line(line)),
+            bytes: self.bytes + line.trim_end_matches(['\r', '\n']).len(),
+        }
+    }
+}
+
+/// The reader that ends the input at the line that would exceed the [`ReadLimits`],
+/// recording which one was exceeded.
+struct LimitedReader<'r, R> {
+    inner: &'r mut R,
+    limits: ReadLimits,
+    usage: ReadUsage,
+    /// The usage with the line returned by `next_line`, taken over once the line is consumed.
+    pending: Option<ReadUsage>,
+    exceeded: Option<ReadLimitExceeded>,
+    observer: Option<Arc<dyn LineObserver>>,
+}
+
+impl<R> crate::util::streaming_line_reader::sealed::Sealed for LimitedReader<'_, R> where
+    R: StreamingLineReader
+{
+}
+
+impl<R> StreamingLineReader for LimitedReader<'_, R>
+where
+    R: StreamingLineReader,
+{
+    type Error = R::Error;
 
-#[derive(Debug)]
-pub struct UciCommandResponse {
+    const AUTO_CONSUMING: bool = R::AUTO_CONSUMING;
+
+    type Line<'a>
+        = R::Line<'a>
+    where
+        Self: 'a;
+
+    fn next_line<'a>(
+        self: &'a mut Self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<Self::Line<'a>>, Self::Error>> {
+        let LimitedReader {
+            inner,
+            limits,
+            usage,
+            pending,
+            exceeded,
+            observer: _,
+        } = self;
+        if exceeded.is_some() {
+            return Poll::Ready(Ok(None));
+        }
+
+        match inner.next_line(cx) {
+            Poll::Ready(Ok(Some(line))) => {
+                let next = usage.with_line(line.as_ref());
+                if let Err(e) = limits.check(next) {
+                    *exceeded = Some(e);
+                    return Poll::Ready(Ok(None));
+                }
+                // Auto-consuming readers consume the line as soon as it is returned
+                if R::AUTO_CONSUMING {
+                    *usage = next;
+                } else {
+                    *pending = Some(next);
+                }
+                Poll::Ready(Ok(Some(line)))
+            }
+            poll => poll,
+        }
+    }
+
+    fn consume_line_manually(&mut self, line_len: usize) {
+        self.inner.consume_line_manually(line_len);
+        if let Some(usage) = self.pending.take() {
+            self.usage = usage;
+        }
+    }
+
+    fn observer(&self) -> Option<Arc<dyn LineObserver>> {
+        self.observer.clone()
+    }
+}
+
+/// The response to `uci`, i.e. the id block, the options and `uciok`.
+///
+/// ```
+/// use uci_beyond::{gui_command_responses::UciResponse, util::AsyncReadable};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let output = "id name MyEngine 1.0\nid author Me\n\n\
+///     option name Hash type spin default 16 min 1 max 1024\n\nuciok\n";
+/// let mut reader = tokio::io::BufReader::new(output.as_bytes());
+/// let response = UciResponse::read_from(&mut reader)
+///     .await
+///     .unwrap()
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(response.id_block.name, "MyEngine 1.0");
+/// assert_eq!(response.option_block.options().len(), 1);
+/// assert!(response.greeting.is_empty());
+/// assert_eq!(response.clone(), response);
+/// # }
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UciResponse {
     pub id_block: IdBlock,
     pub option_block: UciOptionBlockBuilder,
     pub uciok: UciOkCommand,
+    /// The lines skipped before the id block (see [`GreetingPolicy`]).
+    pub greeting: Vec<String>,
+    /// The options advertised again, in the order of advertisement. Always empty
+    /// without [`Quirks::collect_repeated_options`], since a repeated option fails the reading.
+    pub repeated_options: Vec<(UciOptionKind, UciOption)>,
+}
+
+/// What was read of a [`UciResponse`] before the reading failed,
+/// see [`UciResponse::read_partial_from`].
+///
+/// This still tells who the engine is and what it supports when, for instance,
+/// the connection drops before `uciok`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialUciResponse {
+    /// The lines skipped before the id block (see [`GreetingPolicy`]).
+    pub greeting: Vec<String>,
+    /// `None` if the reading failed before the end of the id block.
+    pub id_block: Option<IdBlock>,
+    /// The options read before the failure, or `None` if the reading failed before the option block.
+    pub option_block: Option<UciOptionBlockBuilder>,
+    /// See [`UciResponse::repeated_options`].
+    pub repeated_options: Vec<(UciOptionKind, UciOption)>,
+    /// What prevented the response from being read.
+    pub error: command::parsing::Error<UciResponseParsingError>,
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum UciCommandResponseParsingError {
+#[deprecated(note = "renamed to `UciResponse`")]
+pub type UciCommandResponse = UciResponse;
+
+#[deprecated(note = "renamed to `PartialUciResponse`")]
+pub type PartialUciCommandResponse = PartialUciResponse;
+
+#[deprecated(note = "renamed to `UciResponseParsingError`")]
+pub type UciCommandResponseParsingError = UciResponseParsingError;
+
+/// The parts of the response read so far, kept outside of the reading future
+/// so that they survive the [`ReadLimits::deadline`].
+#[derive(Debug, Default)]
+struct ReadProgress {
+    greeting: Vec<String>,
+    id_block: Option<IdBlock>,
+    option_block: Option<UciOptionBlockBuilder>,
+    repeated_options: Vec<(UciOptionKind, UciOption)>,
+}
+
+impl UciResponse {
+    /// Writes the response the way the engine sends it in reply to the `uci` command, i.e.
+    /// the id block, an empty line, the options, an empty line, and `uciok`.
+    ///
+    /// The output is accepted by [`UciResponse::read_from`] as long as the greeting
+    /// fits into [`DEFAULT_MAX_GREETING_LINES`].
+    ///
+    /// The options are normalized rather than kept in the positions of the engine: they are written
+    /// in the order of [`UciOptionBlockBuilder::options`], followed by the
+    /// [`repeated_options`](Self::repeated_options). Read back with [`Quirks::collect_repeated_options`],
+    /// the output gives the same response.
+    pub async fn write_to<W>(&self, w: &mut W) -> Result<(), W::Error>
+    where
+        W: AsyncWritable,
+    {
+        w.write_command(self).await
+    }
+}
+
+/// See [`UciResponse::write_to`] for the order of the options.
+impl Display for UciResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.greeting {
+            writeln!(f, "{line}")?;
+        }
+        writeln!(f, "{}", self.id_block)?;
+        writeln!(f)?;
+        for option in self.option_block.options() {
+            writeln!(f, "{}", OptionCommand(option))?;
+        }
+        for (_, option) in &self.repeated_options {
+            writeln!(f, "{}", OptionCommand(option.clone()))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{}", self.uciok)
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UciResponseParsingError {
     #[error("IdBlock parsing error: {0:?}")]
     IdBlockParsingError(IdBlockParsingError),
     // That's the behavior of Stockfish. It's kinda hacky but I don't know how to do better for now.
@@ -29,93 +360,298 @@ pub enum UciCommandResponseParsingError {
     UciOkCommandParsingError(UciOkCommandParsingError),
     #[error("Incomplete UCI command response.")]
     IncompleteResponse,
+    #[error("Read limit exceeded: {0}")]
+    LimitExceeded(ReadLimitExceeded),
 }
 
-impl UciCommandResponseParsingError {
-    fn wrap<RR>(
-        self,
-    ) -> Result<Option<Result<UciCommandResponse, command::parsing::Error<Self>>>, RR> {
+impl UciResponseParsingError {
+    fn wrap<RR>(self) -> Result<Option<Result<UciResponse, command::parsing::Error<Self>>>, RR> {
         command::parsing::Error::from(self).wrap()
     }
 }
 
 #[async_trait(?Send)]
-impl AsyncReadable for UciCommandResponse {
-    type Err = command::parsing::Error<UciCommandResponseParsingError>;
+impl AsyncReadable for UciResponse {
+    type Err = command::parsing::Error<UciResponseParsingError>;
 
+    /// Reads the response with the default [`GreetingPolicy`].
     async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
     where
-        R: crate::util::StreamingLineReader,
+        R: StreamingLineReader,
+    {
+        Self::read_with(reader, GreetingPolicy::default()).await
+    }
+}
+
+impl UciResponse {
+    /// Reads the response, treating the lines before the id block according to the [`GreetingPolicy`].
+    pub async fn read_with<R>(
+        reader: &mut R,
+        policy: GreetingPolicy,
+    ) -> Result<Option<Result<Self, command::parsing::Error<UciResponseParsingError>>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        Self::read_with_quirks(reader, policy, Some(Quirks::default())).await
+    }
+
+    /// Reads the response with the leniencies of the [`Quirks`], e.g. for Lc0.
+    ///
+    /// When `quirks` is `None`, they are selected from the id block (see [`Quirks::for_id_block`])
+    /// and apply to the options and the lines before `uciok`.
+    pub async fn read_with_quirks<R>(
+        reader: &mut R,
+        policy: GreetingPolicy,
+        quirks: Option<Quirks>,
+    ) -> Result<Option<Result<Self, command::parsing::Error<UciResponseParsingError>>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let options = ReadOptions {
+            greeting: policy,
+            quirks,
+            limits: ReadLimits::default(),
+            observer: None,
+        };
+        Self::read_with_options(reader, options).await
+    }
+
+    /// Reads the response according to the [`ReadOptions`].
+    ///
+    /// Fails with [`UciResponseParsingError::LimitExceeded`] as soon as one of the
+    /// [`ReadLimits`] is exceeded. The line that would exceed it is left unread.
+    pub async fn read_with_options<R>(
+        reader: &mut R,
+        options: ReadOptions,
+    ) -> Result<Option<Result<Self, command::parsing::Error<UciResponseParsingError>>>, R::Error>
+    where
+        R: StreamingLineReader,
     {
+        Self::read_tracked(reader, options, &mut ReadProgress::default()).await
+    }
+
+    /// The same as [`read_from`](AsyncReadable::read_from), but on failure returns the parts
+    /// of the response read before it rather than only the error.
+    ///
+    /// The errors of the reader itself are returned as they are.
+    pub async fn read_partial_from<R>(
+        reader: &mut R,
+    ) -> Result<Result<Self, PartialUciResponse>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let options = ReadOptions {
+            quirks: Some(Quirks::default()),
+            ..Default::default()
+        };
+        Self::read_partial_with_options(reader, options).await
+    }
+
+    /// The same as [`read_partial_from`](Self::read_partial_from), but according to the [`ReadOptions`].
+    pub async fn read_partial_with_options<R>(
+        reader: &mut R,
+        options: ReadOptions,
+    ) -> Result<Result<Self, PartialUciResponse>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let mut progress = ReadProgress::default();
+        let error = match Self::read_tracked(reader, options, &mut progress).await? {
+            Some(Ok(response)) => return Ok(Ok(response)),
+            Some(Err(e)) => e,
+            None => UciResponseParsingError::IncompleteResponse.into(),
+        };
+        let ReadProgress {
+            greeting,
+            id_block,
+            option_block,
+            repeated_options,
+        } = progress;
+        Ok(Err(PartialUciResponse {
+            greeting,
+            id_block,
+            option_block,
+            repeated_options,
+            error,
+        }))
+    }
+
+    /// Reads the response, recording its parts in `progress` as they are read.
+    async fn read_tracked<R>(
+        reader: &mut R,
+        options: ReadOptions,
+        progress: &mut ReadProgress,
+    ) -> Result<Option<Result<Self, command::parsing::Error<UciResponseParsingError>>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let observer = FanOutObserver::combine(reader.observer(), options.observer);
+        let mut reader = LimitedReader {
+            inner: reader,
+            limits: options.limits,
+            usage: ReadUsage::default(),
+            pending: None,
+            exceeded: None,
+            observer,
+        };
+
+        let read = Self::read_limited(&mut reader, options.greeting, options.quirks, progress);
+        let response = match options.limits.deadline {
+            Some(deadline) => match tokio::time::timeout(deadline, read).await {
+                Ok(response) => response?,
+                Err(_) => {
+                    let e = ReadLimitExceeded::Deadline(deadline);
+                    return UciResponseParsingError::LimitExceeded(e).wrap();
+                }
+            },
+            None => read.await?,
+        };
+
+        // The input ended early because of the limit, whatever the parser made of it
+        match reader.exceeded {
+            Some(e) => UciResponseParsingError::LimitExceeded(e).wrap(),
+            None => Ok(response),
+        }
+    }
+
+    async fn read_limited<R>(
+        reader: &mut R,
+        policy: GreetingPolicy,
+        quirks: Option<Quirks>,
+        progress: &mut ReadProgress,
+    ) -> Result<Option<Result<Self, command::parsing::Error<UciResponseParsingError>>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        let max_greeting_lines = match policy {
+            GreetingPolicy::Skip { max_lines } => max_lines,
+            GreetingPolicy::Strict => 0,
+        };
+        let greeting = &mut progress.greeting;
+        while greeting.len() < max_greeting_lines {
+            let f = |line: &str| -> LineHandlerOutcome<String, Infallible> {
+                if IdCommand::matches_line(line) {
+                    LineHandlerOutcome::Peeked
+                } else {
+                    LineHandlerOutcome::Read(line.trim_end().to_string())
+                }
+            };
+            match handle_next_line(reader, f).await? {
+                Some(LineHandlerOutcome::Read(line)) => greeting.push(line),
+                Some(LineHandlerOutcome::Peeked) => break,
+                Some(LineHandlerOutcome::Error(never)) => match never {},
+                None => return UciResponseParsingError::IncompleteResponse.wrap(),
+            }
+        }
+
         let id_block = match IdBlock::read_from(reader).await? {
-            Some(Ok(block)) => block,
+            Some(Ok(block)) => progress.id_block.insert(block),
             Some(Err(e)) => {
                 return e
-                    .map_custom(UciCommandResponseParsingError::IdBlockParsingError)
+                    .map_custom(UciResponseParsingError::IdBlockParsingError)
                     .wrap();
             }
             None => {
-                return UciCommandResponseParsingError::IncompleteResponse.wrap();
+                return UciResponseParsingError::IncompleteResponse.wrap();
             }
         };
 
         match handle_next_line(
             reader,
-            |line: &str| -> LineHandlerOutcome<(), UciCommandResponseParsingError> {
-                if line.trim().is_empty() {
-                    LineHandlerOutcome::Read(())
-                } else {
-                    LineHandlerOutcome::Error(
-                        UciCommandResponseParsingError::ExpectedEmptyLineAfterIdBlock,
-                    )
+            |line: &str| -> LineHandlerOutcome<(), UciResponseParsingError> {
+                match line.trim() {
+                    "" => LineHandlerOutcome::Read(()),
+                    // A minimal engine without options may send `uciok` right after the id block
+                    "uciok" => LineHandlerOutcome::Peeked,
+                    _ => LineHandlerOutcome::Error(
+                        UciResponseParsingError::ExpectedEmptyLineAfterIdBlock,
+                    ),
                 }
             },
         )
         .await?
         {
-            Some(LineHandlerOutcome::Read(())) => (),
+            // An empty option block is read before `uciok` below
+            Some(LineHandlerOutcome::Read(())) | Some(LineHandlerOutcome::Peeked) => (),
             Some(LineHandlerOutcome::Error(e)) => {
                 return e.wrap();
             }
-            Some(LineHandlerOutcome::Peeked) => {
-                return command::parsing::Error::UnexpectedPeekOutput.wrap();
-            }
             None => {
-                return UciCommandResponseParsingError::IncompleteResponse.wrap();
+                return UciResponseParsingError::IncompleteResponse.wrap();
             }
         };
 
-        let option_block_builder = match UciOptionBlockBuilder::read_from(reader).await? {
-            Some(Ok(builder)) => builder,
-            Some(Err(e)) => {
-                return e
-                    .map_custom(UciCommandResponseParsingError::OptionBlockParsingError)
-                    .wrap();
-            }
-            None => {
-                return UciCommandResponseParsingError::IncompleteResponse.wrap();
-            }
-        };
+        let quirks = quirks.unwrap_or_else(|| Quirks::for_id_block(id_block));
 
         // Since all UCI options are optional, we just use the builder directly
-        let option_block = option_block_builder;
+        let option_block = progress
+            .option_block
+            .insert(UciOptionBlockBuilder::default());
+        loop {
+            let repeated = &mut progress.repeated_options;
+            match UciOptionBlockBuilder::read_collecting_repeated(reader, &quirks, repeated).await?
+            {
+                Some(Ok(builder)) => {
+                    for option in builder.options() {
+                        if let Err(e) = option_block.add_with(option, &quirks, repeated) {
+                            return UciResponseParsingError::OptionBlockParsingError(e).wrap();
+                        }
+                    }
+                }
+                Some(Err(e)) => {
+                    return e
+                        .map_custom(UciResponseParsingError::OptionBlockParsingError)
+                        .wrap();
+                }
+                // No options. Whether the response is complete is decided by `uciok` below.
+                None => (),
+            };
+
+            if !quirks.skip_unknown_lines {
+                break;
+            }
+            // Skip the line that ended the options unless it is `uciok`, and continue with the options after it
+            let f = |line: &str| -> LineHandlerOutcome<(), Infallible> {
+                if line.trim() == "uciok" {
+                    LineHandlerOutcome::Peeked
+                } else {
+                    LineHandlerOutcome::Read(())
+                }
+            };
+            match handle_next_line(reader, f).await? {
+                Some(LineHandlerOutcome::Read(())) => continue,
+                Some(LineHandlerOutcome::Error(never)) => match never {},
+                Some(LineHandlerOutcome::Peeked) | None => break,
+            }
+        }
 
         let uciok = match UciOkCommand::read_from(reader).await? {
             Some(Ok(cmd)) => cmd,
             Some(Err(e)) => {
                 return e
-                    .map_custom(UciCommandResponseParsingError::UciOkCommandParsingError)
+                    .map_custom(UciResponseParsingError::UciOkCommandParsingError)
                     .wrap();
             }
             None => {
-                return UciCommandResponseParsingError::IncompleteResponse.wrap();
+                return UciResponseParsingError::IncompleteResponse.wrap();
             }
         };
 
-        Ok(Some(Ok(UciCommandResponse {
+        let ReadProgress {
+            greeting,
+            id_block: Some(id_block),
+            option_block: Some(option_block),
+            repeated_options,
+        } = std::mem::take(progress)
+        else {
+            unreachable!("The id and option blocks are read before `uciok`");
+        };
+        Ok(Some(Ok(UciResponse {
             id_block,
             option_block,
             uciok,
+            greeting,
+            repeated_options,
         })))
     }
 }
@@ -124,38 +660,149 @@ impl AsyncReadable for UciCommandResponse {
 mod tests {
     use super::*;
 
-    use crate::{model, options::Spin};
+    use futures::StreamExt as _;
+
+    use crate::{
+        fixtures::{STOCKFISH_GREETING, STOCKFISH_OPTION_BLOCK, STOCKFISH_UCI_RESPONSE},
+        model,
+        options::Spin,
+        util::{CountingObserver, LineWriter, StringStreamReader},
+    };
 
     #[tokio::test]
-    async fn test_read_uci_command_response() {
-        let input = "id name Stockfish 17.1\n\
-id author the Stockfish developers (see AUTHORS file)\n\
+    async fn test_uci_command_response_round_trip() {
+        let mut option_block = UciOptionBlockBuilder::default();
+        option_block.debug_log_file = Some(model::UciString(String::new()));
+        option_block.numa_policy = Some(model::NumaPolicy::Auto);
+        option_block.threads = Some(Spin {
+            default: 1,
+            min: 1,
+            max: 1024,
+        });
+        option_block.clear_hash = Some(());
+        option_block.ponder = Some(model::Check(false));
+        option_block.custom.insert(
+            "Aggressiveness".to_string(),
+            crate::options::TypedUciOptionData::Spin(
+                Spin {
+                    default: 100,
+                    min: 0,
+                    max: 200,
+                }
+                .into(),
+            ),
+        );
+
+        let response = UciResponse {
+            id_block: IdBlock {
+                name: "Stockfish 17.1".to_string(),
+                author: "the Stockfish developers (see AUTHORS file)".to_string(),
+            },
+            option_block,
+            uciok: UciOkCommand,
+            greeting: vec![
+                "Stockfish 17.1 by the Stockfish developers (see AUTHORS file)".to_string(),
+            ],
+            repeated_options: Vec::new(),
+        };
+
+        let mut writer = LineWriter::new(Vec::new());
+        response.write_to(&mut writer).await.unwrap();
+        let buf = writer.into_inner();
+        assert_eq!(
+            String::from_utf8(buf.clone()).unwrap(),
+            response.to_string()
+        );
+
+        let mut reader = tokio::io::BufReader::new(buf.as_slice());
+        let parsed = UciResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(parsed, response);
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_command_response_without_blank_line_before_uciok() {
+        let input = "id name Fake\n\
+id author Nobody\n\
 \n\
-option name Debug Log File type string default <empty>\n\
-option name NumaPolicy type string default auto\n\
 option name Threads type spin default 1 min 1 max 1024\n\
-option name Hash type spin default 16 min 1 max 33554432\n\
-option name Clear Hash type button\n\
-option name Ponder type check default false\n\
-option name MultiPV type spin default 1 min 1 max 256\n\
-option name Skill Level type spin default 20 min 0 max 20\n\
-option name Move Overhead type spin default 10 min 0 max 5000\n\
-option name nodestime type spin default 0 min 0 max 10000\n\
-option name UCI_Chess960 type check default false\n\
-option name UCI_LimitStrength type check default false\n\
-option name UCI_Elo type spin default 1320 min 1320 max 3190\n\
-option name UCI_ShowWDL type check default false\n\
-option name SyzygyPath type string default <empty>\n\
-option name SyzygyProbeDepth type spin default 1 min 1 max 100\n\
-option name Syzygy50MoveRule type check default true\n\
-option name SyzygyProbeLimit type spin default 7 min 0 max 7\n\
-option name EvalFile type string default nn-1c0000000000.nnue\n\
-option name EvalFileSmall type string default nn-37f18f62d772.nnue\n\
+uciok\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let response = UciResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.option_block.options().len(), 1);
+
+        let input = "id name Fake\n\
+id author Nobody\n\
 \n\
 uciok\n";
 
         let mut reader = tokio::io::BufReader::new(input.as_bytes());
-        let uci_command_response = UciCommandResponse::read_from(&mut reader)
+        let response = UciResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(response.option_block.options().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_command_response_without_options() {
+        for input in [
+            "id name X\nid author Y\n\nuciok\n",
+            "id name X\nid author Y\nuciok\n",
+        ] {
+            let mut reader = tokio::io::BufReader::new(input.as_bytes());
+            let response = UciResponse::read_from(&mut reader)
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap_or_else(|e| panic!("{input:?}: {e:?}"));
+            assert_eq!(response.id_block.name, "X");
+            assert_eq!(response.id_block.author, "Y");
+            assert_eq!(response.option_block, UciOptionBlockBuilder::default());
+
+            let mut reader = tokio::io::BufReader::new(input.as_bytes());
+            let response = UciResponse::read_with(&mut reader, GreetingPolicy::Strict)
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert!(response.option_block.options().is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_command_response_requires_uciok() {
+        let input = "id name Fake\n\
+id author Nobody\n\
+\n\
+option name Threads type spin default 1 min 1 max 1024\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        assert!(matches!(
+            UciResponse::read_from(&mut reader).await.unwrap(),
+            Some(Err(command::parsing::Error::CustomError(
+                UciResponseParsingError::IncompleteResponse
+            )))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_command_response() {
+        // With an empty line before `uciok`, which some engines send
+        let input = STOCKFISH_UCI_RESPONSE.replace("uciok\n", "\nuciok\n");
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let uci_command_response = UciResponse::read_from(&mut reader)
             .await
             .unwrap()
             .unwrap()
@@ -273,11 +920,329 @@ uciok\n";
         );
         assert_eq!(
             uci_command_response.option_block.eval_file,
-            Some(model::UciString("nn-1c0000000000.nnue".to_string()))
+            Some(model::UciString::from("nn-1c0000000000.nnue"))
         );
         assert_eq!(
             uci_command_response.option_block.eval_file_small,
-            Some(model::UciString("nn-37f18f62d772.nnue".to_string()))
+            Some(model::UciString::from("nn-37f18f62d772.nnue"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_command_response_after_greeting() {
+        let input = format!("{STOCKFISH_GREETING}{STOCKFISH_UCI_RESPONSE}");
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let response = UciResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            response.greeting,
+            ["Stockfish 17.1 by the Stockfish developers (see AUTHORS file)"]
+        );
+        assert_eq!(response.id_block.name, "Stockfish 17.1");
+        assert_eq!(response.option_block.options().len(), 20);
+        // The options are written in the order of Stockfish
+        assert!(response.to_string().contains(STOCKFISH_OPTION_BLOCK));
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        assert!(matches!(
+            UciResponse::read_with(&mut reader, GreetingPolicy::Strict)
+                .await
+                .unwrap(),
+            Some(Err(_))
+        ));
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        assert!(matches!(
+            UciResponse::read_with(&mut reader, GreetingPolicy::Skip { max_lines: 0 })
+                .await
+                .unwrap(),
+            Some(Err(_))
+        ));
+    }
+
+    /// An engine that never stops advertising options after its id block.
+    fn endless_options() -> StringStreamReader<
+        std::io::Error,
+        impl futures::Stream<Item = std::io::Result<String>> + Unpin + Send,
+    > {
+        let header = ["id name Fake", "id author Nobody", ""].map(String::from);
+        let options = (0..).map(|i| format!("option name Custom {i} type check default false"));
+        let lines = header.into_iter().chain(options).map(Ok);
+        StringStreamReader::new(futures::stream::iter(lines))
+    }
+
+    /// The response of a Stockfish 15-era build, with a single net.
+    const SINGLE_EVAL_FILE_RESPONSE: &str = "id name Stockfish 15.1
+id author the Stockfish developers (see AUTHORS file)
+
+option name Threads type spin default 1 min 1 max 1024
+option name Hash type spin default 16 min 1 max 33554432
+option name EvalFile type string default nn-ad9b42354671.nnue
+uciok
+";
+
+    /// The response of a fork that advertises `EvalFile` once per net.
+    const DUPLICATED_EVAL_FILE_RESPONSE: &str = "id name Stockfish 16 fork
+id author the Stockfish developers (see AUTHORS file)
+
+option name Threads type spin default 1 min 1 max 1024
+option name EvalFile type string default nn-5af11540bbfe.nnue
+option name EvalFile type string default nn-baff1ede1f90.nnue
+option name Hash type spin default 16 min 1 max 33554432
+uciok
+";
+
+    #[tokio::test]
+    async fn test_read_single_eval_file() {
+        let mut reader = tokio::io::BufReader::new(SINGLE_EVAL_FILE_RESPONSE.as_bytes());
+        let response = UciResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            response.option_block.eval_file,
+            Some(model::UciString::from("nn-ad9b42354671.nnue"))
+        );
+        assert!(response.option_block.eval_file_small.is_none());
+        assert!(response.repeated_options.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_duplicated_eval_file() {
+        // Strictly, the second `EvalFile` fails the reading
+        let mut reader = tokio::io::BufReader::new(DUPLICATED_EVAL_FILE_RESPONSE.as_bytes());
+        let response = UciResponse::read_from(&mut reader).await;
+        assert!(matches!(
+            response.unwrap().unwrap(),
+            Err(command::parsing::Error::CustomError(
+                UciResponseParsingError::OptionBlockParsingError(
+                    OptionBlockParsingError::RepeatedOption { .. }
+                )
+            ))
+        ));
+
+        let quirks = Quirks {
+            collect_repeated_options: true,
+            ..Default::default()
+        };
+        let mut reader = tokio::io::BufReader::new(DUPLICATED_EVAL_FILE_RESPONSE.as_bytes());
+        let response =
+            UciResponse::read_with_quirks(&mut reader, GreetingPolicy::Strict, Some(quirks))
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+        assert_eq!(
+            response.option_block.eval_file,
+            Some(model::UciString::from("nn-5af11540bbfe.nnue"))
+        );
+        // The options after the repeated one are read as well
+        assert!(response.option_block.hash.is_some());
+        assert_eq!(
+            response.repeated_options,
+            [(
+                UciOptionKind::EvalFile,
+                UciOption::EvalFile {
+                    default: model::UciString::from("nn-baff1ede1f90.nnue")
+                }
+            )]
+        );
+
+        // The repeated option is written after the others, which reads back the same
+        let written = response.to_string();
+        assert!(
+            written.ends_with(
+                "option name EvalFile type string default nn-baff1ede1f90.nnue\n\nuciok\n"
+            )
+        );
+        let mut reader = tokio::io::BufReader::new(written.as_bytes());
+        let reread =
+            UciResponse::read_with_quirks(&mut reader, GreetingPolicy::Strict, Some(quirks))
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+        assert_eq!(reread, response);
+    }
+
+    /// Reads the partial response from the first `lines` lines of the greeting and the fixture.
+    async fn read_cut_response(lines: usize) -> PartialUciResponse {
+        let input: String = format!("{STOCKFISH_GREETING}{STOCKFISH_UCI_RESPONSE}")
+            .split_inclusive('\n')
+            .take(lines)
+            .collect();
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        UciResponse::read_partial_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn test_read_partial_uci_command_response() {
+        // In the id block
+        let partial = read_cut_response(2).await;
+        assert_eq!(
+            partial,
+            PartialUciResponse {
+                greeting: vec![STOCKFISH_GREETING.trim_end().to_string()],
+                id_block: None,
+                option_block: None,
+                repeated_options: Vec::new(),
+                error: command::parsing::Error::CustomError(
+                    UciResponseParsingError::IdBlockParsingError(
+                        IdBlockParsingError::IncompleteBlock
+                    )
+                ),
+            }
         );
+        assert_eq!(read_cut_response(2).await, partial);
+        assert_ne!(read_cut_response(9).await, partial);
+
+        // In the option block
+        let partial = read_cut_response(9).await;
+        assert_eq!(partial.id_block.unwrap().name, "Stockfish 17.1");
+        let option_block = partial.option_block.unwrap();
+        assert_eq!(option_block.options().len(), 5);
+        assert!(option_block.hash.is_some());
+        assert!(matches!(
+            partial.error,
+            command::parsing::Error::CustomError(UciResponseParsingError::IncompleteResponse)
+        ));
+
+        // Right before `uciok`
+        let partial = read_cut_response(24).await;
+        assert_eq!(
+            partial.id_block.unwrap().author,
+            "the Stockfish developers (see AUTHORS file)"
+        );
+        assert_eq!(partial.option_block.unwrap().options().len(), 20);
+        assert!(matches!(
+            partial.error,
+            command::parsing::Error::CustomError(UciResponseParsingError::IncompleteResponse)
+        ));
+
+        let input = format!("{STOCKFISH_GREETING}{STOCKFISH_UCI_RESPONSE}");
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let response = UciResponse::read_partial_from(&mut reader).await;
+        assert!(response.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_command_response_observer() {
+        let observer = Arc::new(CountingObserver::new());
+        let options = ReadOptions::default().with_observer(observer.clone());
+        let input = format!("{STOCKFISH_UCI_RESPONSE}readyok\n");
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let response = UciResponse::read_with_options(&mut reader, options).await;
+        assert!(matches!(response.unwrap().unwrap(), Ok(_)));
+
+        // Every line up to `uciok` is reported once, including the peeked ones
+        let lines = STOCKFISH_UCI_RESPONSE.lines();
+        assert_eq!(observer.lines(), lines.clone().count());
+        let parsed = lines
+            .filter(|line| line.starts_with("id ") || line.starts_with("option "))
+            .count();
+        assert_eq!(observer.parsed(), parsed);
+        assert_eq!(observer.errors(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_command_response_observers_of_reader_and_options() {
+        let of_reader = Arc::new(CountingObserver::new());
+        let of_options = Arc::new(CountingObserver::new());
+        let options = ReadOptions::default().with_observer(of_options.clone());
+        let input = format!("{STOCKFISH_UCI_RESPONSE}readyok\n");
+        let mut reader = crate::util::ObservedReader::new(
+            tokio::io::BufReader::new(input.as_bytes()),
+            of_reader.clone(),
+        );
+        let response = UciResponse::read_with_options(&mut reader, options).await;
+        assert!(matches!(response.unwrap().unwrap(), Ok(_)));
+
+        // Neither observer replaces the other
+        let lines = STOCKFISH_UCI_RESPONSE.lines().count();
+        for observer in [&of_reader, &of_options] {
+            assert_eq!(observer.lines(), lines);
+            assert!(observer.parsed() > 0);
+            assert_eq!(observer.errors(), 0);
+        }
+        assert_eq!(of_reader.parsed(), of_options.parsed());
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_command_response_limits() {
+        let limits = ReadLimits {
+            max_option_lines: 100,
+            ..Default::default()
+        };
+        let options = ReadOptions {
+            limits,
+            ..Default::default()
+        };
+        let response = UciResponse::read_with_options(&mut endless_options(), options).await;
+        assert!(matches!(
+            response.unwrap().unwrap(),
+            Err(command::parsing::Error::CustomError(
+                UciResponseParsingError::LimitExceeded(ReadLimitExceeded::OptionLines(100))
+            ))
+        ));
+
+        let limits = ReadLimits {
+            max_bytes: 1000,
+            ..Default::default()
+        };
+        let options = ReadOptions {
+            limits,
+            ..Default::default()
+        };
+        let response = UciResponse::read_with_options(&mut endless_options(), options).await;
+        assert!(matches!(
+            response.unwrap().unwrap(),
+            Err(command::parsing::Error::CustomError(
+                UciResponseParsingError::LimitExceeded(ReadLimitExceeded::Bytes(1000))
+            ))
+        ));
+
+        // The limits don't get in the way of a regular response
+        let mut reader = tokio::io::BufReader::new(STOCKFISH_UCI_RESPONSE.as_bytes());
+        let limits = ReadLimits {
+            max_option_lines: 20,
+            ..Default::default()
+        };
+        let options = ReadOptions {
+            limits,
+            ..Default::default()
+        };
+        let response = UciResponse::read_with_options(&mut reader, options).await;
+        assert!(matches!(response.unwrap().unwrap(), Ok(_)));
+    }
+
+    #[tokio::test]
+    async fn test_read_uci_command_response_deadline() {
+        let lines = futures::stream::iter(["id name Fake".to_string()].map(Ok))
+            .chain(futures::stream::pending::<std::io::Result<String>>());
+        let mut reader = StringStreamReader::new(lines);
+        let deadline = Duration::from_millis(10);
+        let options = ReadOptions {
+            limits: ReadLimits {
+                deadline: Some(deadline),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let response = UciResponse::read_with_options(&mut reader, options).await;
+        assert!(matches!(
+            response.unwrap().unwrap(),
+            Err(command::parsing::Error::CustomError(
+                UciResponseParsingError::LimitExceeded(ReadLimitExceeded::Deadline(d))
+            )) if d == deadline
+        ));
     }
 }
@@ -0,0 +1,216 @@
+use std::{fmt::Display, str::FromStr};
+
+use async_trait::async_trait;
+
+use crate::{
+    command::{self, Command as _},
+    gui_commands::{
+        IsReadyCommand, NoArgumentsCommandParsingError, PonderHitCommand, QuitCommand,
+        SetOptionCommand, SetOptionCommandParsingError, StopCommand, UciCommand, UciNewGameCommand,
+    },
+    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
+};
+
+/// Any command sent by the GUI, as read by an engine, e.g. in its main loop:
+///
+/// ```ignore
+/// while let Some(cmd) = AnyGuiCommand::read_from(&mut stdin).await? {
+///     match cmd {
+///         Ok(AnyGuiCommand::Quit(_)) => break,
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnyGuiCommand {
+    Uci(UciCommand),
+    IsReady(IsReadyCommand),
+    UciNewGame(UciNewGameCommand),
+    SetOption(SetOptionCommand),
+    Stop(StopCommand),
+    PonderHit(PonderHitCommand),
+    Quit(QuitCommand),
+    /// A command that isn't parsed yet (e.g. `position` and `go`) or an unknown one, as received.
+    ///
+    /// As per the UCI protocol, the engine should ignore the commands it doesn't know.
+    Other(String),
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum AnyGuiCommandParsingError {
+    #[error("Command without arguments parsing error: {0}")]
+    NoArgumentsCommandParsingError(NoArgumentsCommandParsingError),
+    #[error("SetOptionCommand parsing error: {0}")]
+    SetOptionCommandParsingError(SetOptionCommandParsingError),
+}
+
+impl Display for AnyGuiCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnyGuiCommand::Uci(cmd) => write!(f, "{cmd}"),
+            AnyGuiCommand::IsReady(cmd) => write!(f, "{cmd}"),
+            AnyGuiCommand::UciNewGame(cmd) => write!(f, "{cmd}"),
+            AnyGuiCommand::SetOption(cmd) => write!(f, "{cmd}"),
+            AnyGuiCommand::Stop(cmd) => write!(f, "{cmd}"),
+            AnyGuiCommand::PonderHit(cmd) => write!(f, "{cmd}"),
+            AnyGuiCommand::Quit(cmd) => write!(f, "{cmd}"),
+            AnyGuiCommand::Other(line) => write!(f, "{line}"),
+        }
+    }
+}
+
+impl FromStr for AnyGuiCommand {
+    type Err = command::parsing::Error<AnyGuiCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use AnyGuiCommandParsingError::{
+            NoArgumentsCommandParsingError, SetOptionCommandParsingError,
+        };
+
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(command::parsing::Error::UnexpectedEof);
+        }
+
+        match command::command_name(s, command::GUI_COMMAND_NAMES) {
+            Some(UciCommand::NAME) => s
+                .parse()
+                .map(AnyGuiCommand::Uci)
+                .map_err(|e| e.map_custom(NoArgumentsCommandParsingError)),
+            Some(IsReadyCommand::NAME) => s
+                .parse()
+                .map(AnyGuiCommand::IsReady)
+                .map_err(|e| e.map_custom(NoArgumentsCommandParsingError)),
+            Some(UciNewGameCommand::NAME) => s
+                .parse()
+                .map(AnyGuiCommand::UciNewGame)
+                .map_err(|e| e.map_custom(NoArgumentsCommandParsingError)),
+            Some(SetOptionCommand::NAME) => s
+                .parse()
+                .map(AnyGuiCommand::SetOption)
+                .map_err(|e| e.map_custom(SetOptionCommandParsingError)),
+            Some(StopCommand::NAME) => s
+                .parse()
+                .map(AnyGuiCommand::Stop)
+                .map_err(|e| e.map_custom(NoArgumentsCommandParsingError)),
+            Some(PonderHitCommand::NAME) => s
+                .parse()
+                .map(AnyGuiCommand::PonderHit)
+                .map_err(|e| e.map_custom(NoArgumentsCommandParsingError)),
+            Some(QuitCommand::NAME) => s
+                .parse()
+                .map(AnyGuiCommand::Quit)
+                .map_err(|e| e.map_custom(NoArgumentsCommandParsingError)),
+            _ => Ok(AnyGuiCommand::Other(s.to_string())),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl AsyncReadable for AnyGuiCommand {
+    type Err = command::parsing::Error<AnyGuiCommandParsingError>;
+
+    /// Reads the next command, skipping the empty lines.
+    async fn read_from<R>(reader: &mut R) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+    where
+        R: StreamingLineReader,
+    {
+        loop {
+            let f = |line: &str| -> LineHandlerOutcome<Option<AnyGuiCommand>, Self::Err> {
+                if line.trim().is_empty() {
+                    return LineHandlerOutcome::Read(None);
+                }
+                match line.parse() {
+                    Ok(cmd) => LineHandlerOutcome::Read(Some(cmd)),
+                    Err(e) => LineHandlerOutcome::Error(e),
+                }
+            };
+
+            match handle_next_line(reader, f).await? {
+                Some(LineHandlerOutcome::Read(Some(cmd))) => return Ok(Some(Ok(cmd))),
+                Some(LineHandlerOutcome::Read(None)) => continue,
+                Some(LineHandlerOutcome::Error(e)) => return Ok(Some(Err(e))),
+                Some(LineHandlerOutcome::Peeked) => {
+                    return command::parsing::Error::UnexpectedPeekOutput.wrap();
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_any_gui_command() {
+        let input = "uci\n\
+            \n\
+            setoption name Hash value 128\n\
+            position startpos moves e2e4\n\
+            isready\r\n\
+            stop now\n\
+            quit\n";
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+
+        let mut commands = Vec::new();
+        while let Some(cmd) = AnyGuiCommand::read_from(&mut reader).await.unwrap() {
+            commands.push(cmd);
+        }
+
+        assert_eq!(commands.len(), 6);
+        assert_eq!(
+            commands[0].as_ref().unwrap(),
+            &AnyGuiCommand::Uci(UciCommand)
+        );
+        assert_eq!(
+            commands[1].as_ref().unwrap(),
+            &AnyGuiCommand::SetOption(SetOptionCommand::Hash { value: 128 })
+        );
+        assert_eq!(
+            commands[2].as_ref().unwrap(),
+            &AnyGuiCommand::Other("position startpos moves e2e4".to_string())
+        );
+        assert_eq!(
+            commands[3].as_ref().unwrap(),
+            &AnyGuiCommand::IsReady(IsReadyCommand)
+        );
+        assert!(matches!(
+            commands[4],
+            Err(command::parsing::Error::CustomError(
+                AnyGuiCommandParsingError::NoArgumentsCommandParsingError(_)
+            ))
+        ));
+        assert_eq!(
+            commands[5].as_ref().unwrap(),
+            &AnyGuiCommand::Quit(QuitCommand)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_individual_gui_commands() {
+        let input = "setoption name Threads value 4\nisready\n";
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+
+        let cmd = SetOptionCommand::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(cmd, SetOptionCommand::Threads { value: 4 });
+
+        // Like the engine commands, the line of another command is consumed
+        assert!(matches!(
+            UciCommand::read_from(&mut reader).await.unwrap().unwrap(),
+            Err(command::parsing::Error::UnexpectedCommand(_))
+        ));
+        assert!(
+            IsReadyCommand::read_from(&mut reader)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -1,6 +1,9 @@
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
-use crate::{gui_commands::UciCommandTrait, model};
+use crate::{
+    gui_commands::{PositionCommand, UciCommandTrait},
+    model,
+};
 
 /// Start calculating on the current position set up with the position command.
 /// There are a number of parameters that can follow this command and all will be sent in the same string.
@@ -34,10 +37,11 @@ use crate::{gui_commands::UciCommandTrait, model};
 /// ```
 ///
 /// </details>
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GoCommand {
     /// Restrict search to these moves only.
     /// Example: After `position startpos` and `go infinite searchmoves e2e4 d2d4` the engine will only search the two moves e2e4 and d2d4 in the initial position.
-    pub searchmoves: Vec<model::MoveString>,
+    pub searchmoves: model::MoveList,
     /// Start searching in pondering mode. It won't exit the search in ponder mode, even if it's mate!
     /// This means that the last move sent in in the position string is the ponder move.
     /// The engine can do what it wants to do, but after a `ponderhit` command it will execute the suggested move to ponder on.
@@ -73,7 +77,7 @@ pub struct GoCommand {
 impl Default for GoCommand {
     fn default() -> Self {
         Self {
-            searchmoves: vec![],
+            searchmoves: model::MoveList::default(),
             ponder: false,
             wtime: None,
             btime: None,
@@ -90,14 +94,156 @@ impl Default for GoCommand {
     }
 }
 
+impl GoCommand {
+    /// Creates a `go` command from the state of a chess clock, e.g.
+    ///
+    /// ```text
+    /// go wtime 300000 btime 295000 winc 2000 binc 2000
+    /// ```
+    ///
+    /// Durations are sent in milliseconds, saturating at [`u32::MAX`]. Zero increments and
+    /// `moves_to_go` are omitted. The time left for `side_to_move` is sent as at least 1 ms
+    /// because some engines treat `wtime 0` as the absence of a time limit.
+    pub fn from_clock(clock: &model::Clock, side_to_move: model::Color) -> Self {
+        use model::Color;
+
+        let time = |color: Color| {
+            let ms = model::duration_to_ms(clock.time(color));
+            if color == side_to_move { ms.max(1) } else { ms }
+        };
+        let inc = |color: Color| Some(model::duration_to_ms(clock.inc(color))).filter(|&ms| ms > 0);
+
+        Self {
+            wtime: Some(time(Color::White)),
+            btime: Some(time(Color::Black)),
+            winc: inc(Color::White),
+            binc: inc(Color::Black),
+            movestogo: clock.moves_to_go.filter(|&moves| moves > 0),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a `go movetime` command, saturating at [`u32::MAX`] milliseconds.
+    pub fn for_movetime(movetime: Duration) -> Self {
+        Self {
+            movetime: Some(model::duration_to_ms(movetime)),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a `go depth` command.
+    pub fn for_depth(depth: u32) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+}
+
+/// The combination of parameters of a [`GoCommand`] that engines don't handle predictably.
+///
+/// See [`GoCommand::validate`].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoCommandValidationError {
+    /// `perft` is a debugging command rather than a search, e.g. `go perft 5 depth 10`.
+    #[error("`perft` can't be combined with `{0}`")]
+    PerftWithOtherParameters(&'static str),
+    /// `infinite` searches until `stop`, so a limit is ignored or cuts the search short,
+    /// depending on the engine, e.g. `go infinite depth 10`.
+    #[error("`infinite` can't be combined with `{0}`")]
+    InfiniteWithLimit(&'static str),
+    /// The limit is zero, e.g. `go depth 0` or `go movetime 0`.
+    #[error("`{0}` must be greater than zero")]
+    ZeroLimit(&'static str),
+    /// `ponder` searches the last move of the position, so the position must have at least one move.
+    #[error("`ponder` requires the position to have at least one move")]
+    PonderWithoutMoves,
+}
+
+impl GoCommand {
+    /// The time, node and depth limits of the search, in the order of [`Display`].
+    fn limits(&self) -> [(&'static str, Option<u32>); 9] {
+        [
+            ("wtime", self.wtime),
+            ("btime", self.btime),
+            ("winc", self.winc),
+            ("binc", self.binc),
+            ("movestogo", self.movestogo),
+            ("depth", self.depth),
+            ("nodes", self.nodes),
+            ("mate", self.mate),
+            ("movetime", self.movetime),
+        ]
+    }
+
+    /// Checks that the parameters can be sent together, reporting the first violation:
+    ///
+    /// * `perft` excludes all the other parameters;
+    /// * `infinite` excludes the time, node and depth limits;
+    /// * `depth`, `nodes`, `mate`, `movetime`, `movestogo` and `perft` are greater than zero.
+    ///
+    /// The time left on the clocks and the increments may be zero.
+    /// Use [`GoCommand::validate_for`] to check `ponder` against the position too.
+    pub fn validate(&self) -> Result<(), GoCommandValidationError> {
+        use GoCommandValidationError as E;
+
+        let set_limit = self
+            .limits()
+            .into_iter()
+            .find_map(|(name, limit)| limit.map(|_| name));
+
+        if self.perft.is_some() {
+            let other = [
+                ("searchmoves", !self.searchmoves.is_empty()),
+                ("ponder", self.ponder),
+                ("infinite", self.indefinite),
+            ]
+            .into_iter()
+            .find_map(|(name, set)| set.then_some(name))
+            .or(set_limit);
+            if let Some(other) = other {
+                return Err(E::PerftWithOtherParameters(other));
+            }
+        }
+        if self.indefinite
+            && let Some(limit) = set_limit
+        {
+            return Err(E::InfiniteWithLimit(limit));
+        }
+
+        let positive_limits = [
+            ("movestogo", self.movestogo),
+            ("depth", self.depth),
+            ("nodes", self.nodes),
+            ("mate", self.mate),
+            ("movetime", self.movetime),
+            ("perft", self.perft),
+        ];
+        if let Some((name, _)) = positive_limits
+            .into_iter()
+            .find(|(_, limit)| *limit == Some(0))
+        {
+            return Err(E::ZeroLimit(name));
+        }
+        Ok(())
+    }
+
+    /// Like [`GoCommand::validate`], but also checks that `ponder` has a move to ponder on
+    /// in the position the search starts from.
+    pub fn validate_for(&self, position: &PositionCommand) -> Result<(), GoCommandValidationError> {
+        self.validate()?;
+        if self.ponder && position.moves.is_empty() {
+            return Err(GoCommandValidationError::PonderWithoutMoves);
+        }
+        Ok(())
+    }
+}
+
 impl Display for GoCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "go")?;
         if !self.searchmoves.is_empty() {
-            write!(f, " searchmoves")?;
-            for m in &self.searchmoves {
-                write!(f, " {m}")?;
-            }
+            write!(f, " searchmoves {}", self.searchmoves)?;
         }
         if self.ponder {
             write!(f, " ponder")?;
@@ -201,4 +347,168 @@ mod tests {
         };
         assert_eq!(cmd.to_string(), "go ponder movetime 1000");
     }
+
+    #[test]
+    fn test_go_from_clock() {
+        let clock = model::Clock {
+            white: Duration::from_secs(300),
+            black: Duration::from_millis(295_500),
+            white_inc: Duration::from_secs(2),
+            black_inc: Duration::from_secs(2),
+            moves_to_go: None,
+        };
+        assert_eq!(
+            GoCommand::from_clock(&clock, model::Color::White).to_string(),
+            "go wtime 300000 btime 295500 winc 2000 binc 2000"
+        );
+
+        let clock = model::Clock {
+            white: Duration::from_secs(60),
+            black: Duration::ZERO,
+            moves_to_go: Some(12),
+            ..Default::default()
+        };
+        assert_eq!(
+            GoCommand::from_clock(&clock, model::Color::Black).to_string(),
+            "go wtime 60000 btime 1 movestogo 12"
+        );
+    }
+
+    #[test]
+    fn test_go_from_clock_saturates() {
+        let clock = model::Clock {
+            white: Duration::from_secs(u64::MAX),
+            black: Duration::from_millis(u64::from(u32::MAX) + 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            GoCommand::from_clock(&clock, model::Color::White).to_string(),
+            "go wtime 4294967295 btime 4294967295"
+        );
+        assert_eq!(
+            GoCommand::for_movetime(Duration::from_secs(u64::MAX)).to_string(),
+            "go movetime 4294967295"
+        );
+    }
+
+    #[test]
+    fn test_go_for_movetime_and_depth() {
+        assert_eq!(
+            GoCommand::for_movetime(Duration::from_millis(1500)).to_string(),
+            "go movetime 1500"
+        );
+        assert_eq!(GoCommand::for_depth(20).to_string(), "go depth 20");
+    }
+
+    #[test]
+    fn test_go_validate() {
+        use GoCommandValidationError as E;
+
+        assert_eq!(GoCommand::for_depth(20).validate(), Ok(()));
+        assert_eq!(
+            GoCommand {
+                indefinite: true,
+                searchmoves: model::MoveList(vec![model::MoveString::from("e2e4")]),
+                ..Default::default()
+            }
+            .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            GoCommand {
+                perft: Some(5),
+                ..Default::default()
+            }
+            .validate(),
+            Ok(())
+        );
+        // The clock of the side not to move may run out
+        assert_eq!(
+            GoCommand {
+                wtime: Some(1000),
+                btime: Some(0),
+                winc: Some(0),
+                ..Default::default()
+            }
+            .validate(),
+            Ok(())
+        );
+
+        assert_eq!(
+            GoCommand {
+                perft: Some(5),
+                depth: Some(10),
+                indefinite: true,
+                ..Default::default()
+            }
+            .validate(),
+            Err(E::PerftWithOtherParameters("infinite"))
+        );
+        assert_eq!(
+            GoCommand {
+                perft: Some(5),
+                wtime: Some(1000),
+                ..Default::default()
+            }
+            .validate(),
+            Err(E::PerftWithOtherParameters("wtime"))
+        );
+        assert_eq!(
+            GoCommand {
+                indefinite: true,
+                nodes: Some(1000),
+                ..Default::default()
+            }
+            .validate(),
+            Err(E::InfiniteWithLimit("nodes"))
+        );
+        assert_eq!(
+            GoCommand::for_depth(0).validate(),
+            Err(E::ZeroLimit("depth"))
+        );
+        assert_eq!(
+            GoCommand::for_movetime(Duration::ZERO).validate(),
+            Err(E::ZeroLimit("movetime"))
+        );
+        assert_eq!(
+            GoCommand {
+                perft: Some(0),
+                ..Default::default()
+            }
+            .validate(),
+            Err(E::ZeroLimit("perft"))
+        );
+        assert_eq!(
+            E::PerftWithOtherParameters("depth").to_string(),
+            "`perft` can't be combined with `depth`"
+        );
+    }
+
+    #[test]
+    fn test_go_validate_for_position() {
+        let startpos = PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList::default(),
+        };
+        let after_e4 = PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList(vec![model::MoveString::from("e2e4")]),
+        };
+        let ponder = GoCommand {
+            ponder: true,
+            movetime: Some(1000),
+            ..Default::default()
+        };
+
+        assert_eq!(ponder.validate(), Ok(()));
+        assert_eq!(
+            ponder.validate_for(&startpos),
+            Err(GoCommandValidationError::PonderWithoutMoves)
+        );
+        assert_eq!(ponder.validate_for(&after_e4), Ok(()));
+        assert_eq!(
+            GoCommand::for_depth(0).validate_for(&after_e4),
+            Err(GoCommandValidationError::ZeroLimit("depth"))
+        );
+    }
 }
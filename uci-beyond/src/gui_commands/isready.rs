@@ -1,6 +1,9 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use crate::gui_commands::UciCommandTrait;
+use crate::{
+    command,
+    gui_commands::{NoArgumentsCommandParsingError, UciCommandTrait, parse_no_arguments},
+};
 
 /// This is used to synchronize the engine with the GUI.
 // When the GUI has sent a command or multiple commands that can take some time to complete, this command can be used to wait for the engine to be ready again or to ping the engine to find out if it is still alive.
@@ -9,6 +12,7 @@ use crate::gui_commands::UciCommandTrait;
 // This command will always be answered with `readyok` and can be sent also when the engine is calculating in which case the engine will also immediately answer with `readyok` without stopping the search.
 ///
 /// See in Stockfish UCI documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#isready>.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct IsReadyCommand;
 
 impl Display for IsReadyCommand {
@@ -17,6 +21,20 @@ impl Display for IsReadyCommand {
     }
 }
 
+impl command::Command for IsReadyCommand {
+    type ParsingError = NoArgumentsCommandParsingError;
+
+    const NAME: &'static str = "isready";
+}
+
+impl FromStr for IsReadyCommand {
+    type Err = command::parsing::Error<NoArgumentsCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_no_arguments::<Self>(s).map(|()| IsReadyCommand)
+    }
+}
+
 impl UciCommandTrait for IsReadyCommand {
     type Response = ();
 }
@@ -9,10 +9,19 @@
 //!
 //! [UCI]: https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html
 
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
+use async_trait::async_trait;
+
+use crate::{
+    command,
+    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
+};
+
+mod any_gui_command;
 mod go;
 mod isready;
+mod ponderhit;
 mod position;
 mod quit;
 mod setoption;
@@ -20,11 +29,16 @@ mod stop;
 mod uci;
 mod ucinewgame;
 
-pub use go::GoCommand;
+#[cfg(feature = "stockfish-ext")]
+pub mod stockfish;
+
+pub use any_gui_command::{AnyGuiCommand, AnyGuiCommandParsingError};
+pub use go::{GoCommand, GoCommandValidationError};
 pub use isready::IsReadyCommand;
+pub use ponderhit::PonderHitCommand;
 pub use position::PositionCommand;
 pub use quit::QuitCommand;
-pub use setoption::SetOptionCommand;
+pub use setoption::{SetOptionCommand, SetOptionCommandParsingError, ValueTokenPolicy};
 pub use stop::StopCommand;
 pub use uci::UciCommand;
 pub use ucinewgame::UciNewGameCommand;
@@ -34,4 +48,139 @@ pub trait UciCommandTrait: Display + Send {
     type Response: std::fmt::Debug;
 }
 
-// TODO: consider adding non-standard commands (e.g. for Stockfish)
+/// The error of parsing a command without arguments, e.g. `stop`.
+///
+/// Seeing another command is reported as [`command::parsing::Error::UnexpectedCommand`].
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NoArgumentsCommandParsingError {
+    /// The command is followed by other tokens, e.g. `stop now`.
+    #[error("Unexpected trailing tokens: `{0}`")]
+    UnexpectedTrailingTokens(String),
+}
+
+/// Parses the line of a command without arguments, accepting trailing whitespace.
+fn parse_no_arguments<C>(
+    s: &str,
+) -> Result<(), command::parsing::Error<NoArgumentsCommandParsingError>>
+where
+    C: command::Command<ParsingError = NoArgumentsCommandParsingError>,
+{
+    let s = s.trim_end();
+    if s.is_empty() {
+        return Err(command::parsing::Error::UnexpectedEof);
+    }
+    match s.strip_prefix(C::NAME) {
+        Some("") => Ok(()),
+        Some(rest) if rest.starts_with(char::is_whitespace) => Err(
+            NoArgumentsCommandParsingError::UnexpectedTrailingTokens(rest.trim_start().to_string())
+                .into(),
+        ),
+        // E.g. `stopped` for `stop`
+        Some(_) | None => Err(command::parsing::Error::UnexpectedCommand(s.to_string())),
+    }
+}
+
+/// Reads a single-line command with its [`FromStr`], e.g. on the engine side.
+///
+/// As with the engine commands, the line is consumed even if it fails to parse.
+async fn read_command<R, C>(reader: &mut R) -> Result<Option<Result<C, C::Err>>, R::Error>
+where
+    R: StreamingLineReader,
+    C: FromStr + Send,
+    C::Err: Send,
+{
+    let f = |line: &str| match line.parse::<C>() {
+        Ok(cmd) => LineHandlerOutcome::Read(cmd),
+        Err(e) => LineHandlerOutcome::Error(e),
+    };
+
+    match handle_next_line(reader, f).await? {
+        Some(LineHandlerOutcome::Read(cmd)) => Ok(Some(Ok(cmd))),
+        Some(LineHandlerOutcome::Error(e)) => Ok(Some(Err(e))),
+        Some(LineHandlerOutcome::Peeked) => unreachable!("The line is never peeked at"),
+        None => Ok(None),
+    }
+}
+
+/// Implements [`AsyncReadable`] with [`read_command`] for the commands that implement [`FromStr`].
+macro_rules! impl_async_readable {
+    ($($cmd:ty),* $(,)?) => {
+        $(
+            #[async_trait(?Send)]
+            impl AsyncReadable for $cmd {
+                type Err = <$cmd as FromStr>::Err;
+
+                async fn read_from<R>(
+                    reader: &mut R,
+                ) -> Result<Option<Result<Self, Self::Err>>, R::Error>
+                where
+                    R: StreamingLineReader,
+                {
+                    read_command(reader).await
+                }
+            }
+        )*
+    };
+}
+
+impl_async_readable!(
+    UciCommand,
+    IsReadyCommand,
+    UciNewGameCommand,
+    SetOptionCommand,
+    StopCommand,
+    PonderHitCommand,
+    QuitCommand,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<C>(cmd: C)
+    where
+        C: FromStr<Err = command::parsing::Error<NoArgumentsCommandParsingError>>
+            + UciCommandTrait
+            + PartialEq
+            + std::fmt::Debug,
+    {
+        let name = cmd.to_string();
+        let cases: [(String, Result<(), Option<&str>>); 6] = [
+            (name.clone(), Ok(())),
+            (format!("{name}  \t"), Ok(())),
+            (format!("{name}\r\n"), Ok(())),
+            (format!("{name} now"), Err(Some("now"))),
+            (format!("{name}  now  please "), Err(Some("now  please"))),
+            (format!("{name}ed"), Err(None)),
+        ];
+
+        for (line, expected) in cases {
+            match (line.parse::<C>(), expected) {
+                (Ok(parsed), Ok(())) => assert_eq!(parsed, cmd),
+                (
+                    Err(command::parsing::Error::CustomError(
+                        NoArgumentsCommandParsingError::UnexpectedTrailingTokens(tokens),
+                    )),
+                    Err(Some(expected)),
+                ) => assert_eq!(tokens, expected),
+                (Err(command::parsing::Error::UnexpectedCommand(_)), Err(None)) => (),
+                (parsed, expected) => panic!("{line:?}: {parsed:?}, expected {expected:?}"),
+            }
+        }
+        assert!(matches!(
+            "".parse::<C>(),
+            Err(command::parsing::Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn test_parse_commands_without_arguments() {
+        check(UciCommand);
+        check(IsReadyCommand);
+        check(UciNewGameCommand);
+        check(StopCommand);
+        check(PonderHitCommand);
+        check(QuitCommand);
+    }
+}
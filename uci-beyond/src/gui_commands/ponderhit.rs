@@ -0,0 +1,37 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    command,
+    gui_commands::{NoArgumentsCommandParsingError, UciCommandTrait, parse_no_arguments},
+};
+
+/// The user has played the expected move. This will be sent if the engine was told to ponder on the same move
+/// the user has played. The engine should continue searching but switch from pondering to normal search.
+///
+/// See in Stockfish UCI documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#ponderhit>.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PonderHitCommand;
+
+impl Display for PonderHitCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ponderhit")
+    }
+}
+
+impl command::Command for PonderHitCommand {
+    type ParsingError = NoArgumentsCommandParsingError;
+
+    const NAME: &'static str = "ponderhit";
+}
+
+impl FromStr for PonderHitCommand {
+    type Err = command::parsing::Error<NoArgumentsCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_no_arguments::<Self>(s).map(|()| PonderHitCommand)
+    }
+}
+
+impl UciCommandTrait for PonderHitCommand {
+    type Response = ();
+}
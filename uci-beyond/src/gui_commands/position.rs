@@ -6,9 +6,10 @@ use crate::{gui_commands::UciCommandTrait, model};
 /// If the game was played from the start position the string `startpos` must be sent.
 ///
 /// See in Stockfish UCI documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#position>.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PositionCommand {
     pub startpos: model::Position,
-    pub moves: Vec<model::MoveString>,
+    pub moves: model::MoveList,
 }
 
 impl PositionCommand {
@@ -16,19 +17,28 @@ impl PositionCommand {
     pub fn from_fen(fen: model::FenString) -> Self {
         Self {
             startpos: model::Position::Fen(fen),
-            moves: Vec::new(),
+            moves: model::MoveList::default(),
         }
     }
+
+    /// Converts the moves played from the position after the moves of the command to SAN,
+    /// e.g. a principal variation (see [`DepthInfoCommand::pv_to_san`](crate::engine_commands::DepthInfoCommand::pv_to_san)).
+    ///
+    /// See [`model::Position::to_san`].
+    #[cfg(feature = "board")]
+    pub fn to_san(
+        &self,
+        moves: &[model::MoveString],
+    ) -> Result<Vec<String>, model::SanConversionError> {
+        model::san::moves_to_san(self.moves.play(&self.startpos)?, moves)
+    }
 }
 
 impl Display for PositionCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "position {}", self.startpos)?;
         if !self.moves.is_empty() {
-            write!(f, " moves")?;
-            for m in &self.moves {
-                write!(f, " {}", m.0)?;
-            }
+            write!(f, " moves {}", self.moves)?;
         }
         Ok(())
     }
@@ -37,3 +47,45 @@ impl Display for PositionCommand {
 impl UciCommandTrait for PositionCommand {
     type Response = ();
 }
+
+#[cfg(all(test, feature = "board"))]
+mod tests {
+    use super::*;
+
+    fn moves(s: &str) -> model::MoveList {
+        s.split_whitespace().collect()
+    }
+
+    #[test]
+    fn test_position_command_to_san() {
+        let position = PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: moves("e2e4 e7e5"),
+        };
+        assert_eq!(
+            position.to_san(&moves("g1f3 b8c6")).unwrap(),
+            ["Nf3", "Nc6"]
+        );
+
+        // The principal variation of the previous position is stale
+        assert_eq!(
+            position.to_san(&moves("e7e5")),
+            Err(model::SanConversionError::IllegalMove {
+                ply: 0,
+                mv: model::MoveString::from("e7e5")
+            })
+        );
+
+        let position = PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: moves("e2e4 e2e4"),
+        };
+        assert_eq!(
+            position.to_san(&[]),
+            Err(model::SanConversionError::IllegalPositionMove {
+                ply: 1,
+                mv: model::MoveString::from("e2e4")
+            })
+        );
+    }
+}
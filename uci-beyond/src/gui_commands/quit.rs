@@ -1,10 +1,14 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use crate::gui_commands::UciCommandTrait;
+use crate::{
+    command,
+    gui_commands::{NoArgumentsCommandParsingError, UciCommandTrait, parse_no_arguments},
+};
 
 /// Quit the program as soon as possible.
 ///
 /// See in Stockfish UCI documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#quit>.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct QuitCommand;
 
 impl Display for QuitCommand {
@@ -13,6 +17,20 @@ impl Display for QuitCommand {
     }
 }
 
+impl command::Command for QuitCommand {
+    type ParsingError = NoArgumentsCommandParsingError;
+
+    const NAME: &'static str = "quit";
+}
+
+impl FromStr for QuitCommand {
+    type Err = command::parsing::Error<NoArgumentsCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_no_arguments::<Self>(s).map(|()| QuitCommand)
+    }
+}
+
 impl UciCommandTrait for QuitCommand {
     type Response = ();
 }
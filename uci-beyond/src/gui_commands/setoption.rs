@@ -1,6 +1,6 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use crate::{gui_commands::UciCommandTrait, model};
+use crate::{command, gui_commands::UciCommandTrait, model, options::UciOptionKind};
 
 /// This is sent to the engine when the user wants to change the internal parameters of the engine. For the button type no value is needed.
 /// One string will be sent for each parameter and this will only be sent when the engine is waiting.
@@ -8,27 +8,91 @@ use crate::{gui_commands::UciCommandTrait, model};
 /// See in Stockfish UCI documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#setoption>.
 ///
 /// Also see [options::UciOption](crate::options::UciOption).
+///
+/// # The `value` token ambiguity
+///
+/// UCI has no escaping, so both the name and the value may contain the token `value`, e.g.
+/// `setoption name My value option value 3` is either the option `My` set to `option value 3`
+/// or the option `My value option` set to `3`. [`ValueTokenPolicy`] decides how the parser splits
+/// such lines. The default, [`ValueTokenPolicy::FirstValueToken`], is the behavior of Stockfish,
+/// under which the [`Display`] output round-trips unless the name itself contains the token `value`.
+///
+/// # String values
+///
+/// The values of the standard string options (`EvalFile`, `EvalFileSmall` and `Debug Log File`) are written
+/// and parsed as [`model::UciString`] tokens, i.e. the empty value as `<empty>` and a literal `<empty>`
+/// as `\<empty>`. The values of the custom options are kept verbatim since their type is unknown.
+/// Consecutive whitespace in a value, including around it, doesn't survive parsing.
+///
+/// There is a variant per standard option, so the enum is non-exhaustive like [`UciOption`](crate::options::UciOption).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SetOptionCommand {
-    Threads { value: u32 },
-    Hash { value: u32 },
-    MultiPV { value: u32 },
-    NumaPolicy { value: model::NumaPolicy },
+    Threads {
+        value: u32,
+    },
+    Hash {
+        value: u32,
+    },
+    MultiPV {
+        value: u32,
+    },
+    NumaPolicy {
+        value: model::NumaPolicy,
+    },
     ClearHash,
-    Ponder { value: bool },
-    EvalFile { value: String },
-    EvalFileSmall { value: String },
-    UCIChess960 { value: bool },
-    UCIShowWDL { value: bool },
-    UCILimitStrength { value: bool },
-    UCIElo { value: u32 },
-    SkillLevel { value: u32 },
-    SyzygyPath { value: Option<String> },
-    SyzygyProbeDepth { value: u32 },
-    Syzygy50MoveRule { value: bool },
-    SyzygyProbeLimit { value: u32 },
-    MoveOverhead { value: u32 },
-    Nodestime { value: u32 },
-    DebugLogFile { value: String },
+    Ponder {
+        value: bool,
+    },
+    EvalFile {
+        value: String,
+    },
+    EvalFileSmall {
+        value: String,
+    },
+    UCIChess960 {
+        value: bool,
+    },
+    UCIShowWDL {
+        value: bool,
+    },
+    UCILimitStrength {
+        value: bool,
+    },
+    UCIElo {
+        value: u32,
+    },
+    SkillLevel {
+        value: u32,
+    },
+    SyzygyPath {
+        value: model::SyzygyPathSpec,
+    },
+    SyzygyProbeDepth {
+        value: u32,
+    },
+    Syzygy50MoveRule {
+        value: bool,
+    },
+    SyzygyProbeLimit {
+        value: u32,
+    },
+    MoveOverhead {
+        value: u32,
+    },
+    Nodestime {
+        value: u32,
+    },
+    DebugLogFile {
+        value: String,
+    },
+    /// An option that is not known to this crate, e.g. an engine-specific one.
+    ///
+    /// `value` is `None` for buttons.
+    Custom {
+        name: String,
+        value: Option<String>,
+    },
 }
 
 impl Display for SetOptionCommand {
@@ -50,10 +114,10 @@ impl Display for SetOptionCommand {
                 write!(f, "Ponder value {value}")
             }
             SetOptionCommand::EvalFile { value } => {
-                write!(f, "EvalFile value {value}")
+                write!(f, "EvalFile value {}", model::UciString(value.clone()))
             }
             SetOptionCommand::EvalFileSmall { value } => {
-                write!(f, "EvalFileSmall value {value}")
+                write!(f, "EvalFileSmall value {}", model::UciString(value.clone()))
             }
             SetOptionCommand::UCIChess960 { value } => {
                 write!(f, "UCI_Chess960 value {value}")
@@ -71,11 +135,7 @@ impl Display for SetOptionCommand {
                 write!(f, "Skill Level value {value}")
             }
             SetOptionCommand::SyzygyPath { value } => {
-                let val_str = match value {
-                    Some(v) => v.as_str(),
-                    None => "<empty>",
-                };
-                write!(f, "SyzygyPath value {val_str}")
+                write!(f, "SyzygyPath value {value}")
             }
             SetOptionCommand::SyzygyProbeDepth { value } => {
                 write!(f, "SyzygyProbeDepth value {value}")
@@ -87,19 +147,412 @@ impl Display for SetOptionCommand {
                 write!(f, "SyzygyProbeLimit value {value}")
             }
             SetOptionCommand::MoveOverhead { value } => {
-                write!(f, "MoveOverhead value {value}")
+                write!(f, "Move Overhead value {value}")
             }
             SetOptionCommand::Nodestime { value } => {
                 write!(f, "nodestime value {value}")
             }
             SetOptionCommand::DebugLogFile { value } => {
-                write!(f, "DebugLogFile value {value}")
+                write!(
+                    f,
+                    "Debug Log File value {}",
+                    model::UciString(value.clone())
+                )
+            }
+            SetOptionCommand::Custom { name, value } => {
+                write!(f, "{name}")?;
+                if let Some(value) = value {
+                    write!(f, " value {value}")?;
+                }
+                Ok(())
             }
         }
     }
 }
 
+/// How [`SetOptionCommand::parse_with`] splits the name from the value when either contains the token `value`
+/// (see [`SetOptionCommand`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueTokenPolicy {
+    /// The name ends at the first `value` token, so the value may contain `value` but the name may not.
+    ///
+    /// This is the behavior of Stockfish (see `Engine::set_option` in
+    /// [engine.cpp](https://github.com/official-stockfish/Stockfish/blob/master/src/engine.cpp)).
+    #[default]
+    FirstValueToken,
+    /// The name ends at the last `value` token, so the name may contain `value` but the value may not.
+    ///
+    /// This is what several GUIs assume when they display option names.
+    LastValueToken,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SetOptionCommandParsingError {
+    #[error("Expected `name` after `setoption`.")]
+    NameTokenExpected,
+    #[error("Empty option name.")]
+    EmptyOptionName,
+    #[error("Missing value of `{0}`.")]
+    MissingValue(&'static str),
+    #[error("Unexpected value of the button `{0}`.")]
+    UnexpectedValue(&'static str),
+    #[error("Invalid value of `{name}`: `{value}`.")]
+    InvalidValue { name: &'static str, value: String },
+}
+
+impl command::Command for SetOptionCommand {
+    type ParsingError = SetOptionCommandParsingError;
+
+    const NAME: &'static str = "setoption";
+}
+
+impl SetOptionCommand {
+    /// The kind of the standard option, or `None` for [`SetOptionCommand::Custom`].
+    pub fn kind(&self) -> Option<UciOptionKind> {
+        match self {
+            SetOptionCommand::Threads { .. } => Some(UciOptionKind::Threads),
+            SetOptionCommand::Hash { .. } => Some(UciOptionKind::Hash),
+            SetOptionCommand::MultiPV { .. } => Some(UciOptionKind::MultiPV),
+            SetOptionCommand::NumaPolicy { .. } => Some(UciOptionKind::NumaPolicy),
+            SetOptionCommand::ClearHash => Some(UciOptionKind::ClearHash),
+            SetOptionCommand::Ponder { .. } => Some(UciOptionKind::Ponder),
+            SetOptionCommand::EvalFile { .. } => Some(UciOptionKind::EvalFile),
+            SetOptionCommand::EvalFileSmall { .. } => Some(UciOptionKind::EvalFileSmall),
+            SetOptionCommand::UCIChess960 { .. } => Some(UciOptionKind::UCIChess960),
+            SetOptionCommand::UCIShowWDL { .. } => Some(UciOptionKind::UCIShowWDL),
+            SetOptionCommand::UCILimitStrength { .. } => Some(UciOptionKind::UCILimitStrength),
+            SetOptionCommand::UCIElo { .. } => Some(UciOptionKind::UCIElo),
+            SetOptionCommand::SkillLevel { .. } => Some(UciOptionKind::SkillLevel),
+            SetOptionCommand::SyzygyPath { .. } => Some(UciOptionKind::SyzygyPath),
+            SetOptionCommand::SyzygyProbeDepth { .. } => Some(UciOptionKind::SyzygyProbeDepth),
+            SetOptionCommand::Syzygy50MoveRule { .. } => Some(UciOptionKind::Syzygy50MoveRule),
+            SetOptionCommand::SyzygyProbeLimit { .. } => Some(UciOptionKind::SyzygyProbeLimit),
+            SetOptionCommand::MoveOverhead { .. } => Some(UciOptionKind::MoveOverhead),
+            SetOptionCommand::Nodestime { .. } => Some(UciOptionKind::Nodestime),
+            SetOptionCommand::DebugLogFile { .. } => Some(UciOptionKind::DebugLogFile),
+            SetOptionCommand::Custom { .. } => None,
+        }
+    }
+
+    /// The name of the option, e.g. `Skill Level`.
+    pub fn name(&self) -> &str {
+        match self {
+            SetOptionCommand::Custom { name, .. } => name,
+            _ => self
+                .kind()
+                .expect("only custom options have no kind")
+                .name(),
+        }
+    }
+
+    /// Parses the command, splitting the name from the value according to the [`ValueTokenPolicy`].
+    ///
+    /// As per the UCI protocol, the names of the standard options are matched case-insensitively.
+    /// Consecutive whitespace in the name and in the value is collapsed into a single space.
+    pub fn parse_with(
+        s: &str,
+        policy: ValueTokenPolicy,
+    ) -> Result<Self, command::parsing::Error<SetOptionCommandParsingError>> {
+        use command::Command as _;
+
+        let s = Self::parse_cmd_name(s.trim_end())?;
+        let mut tokens = s.split_whitespace();
+        if tokens.next() != Some("name") {
+            return Err(SetOptionCommandParsingError::NameTokenExpected.into());
+        }
+        let tokens: Vec<&str> = tokens.collect();
+
+        let value_token = match policy {
+            ValueTokenPolicy::FirstValueToken => tokens.iter().position(|t| *t == "value"),
+            ValueTokenPolicy::LastValueToken => tokens.iter().rposition(|t| *t == "value"),
+        };
+        let (name, value) = match value_token {
+            Some(i) => (tokens[..i].join(" "), Some(tokens[i + 1..].join(" "))),
+            None => (tokens.join(" "), None),
+        };
+        if name.is_empty() {
+            return Err(SetOptionCommandParsingError::EmptyOptionName.into());
+        }
+
+        let kind = match name.parse::<UciOptionKind>() {
+            Ok(kind) => kind,
+            Err(unknown) => match unknown.case_insensitive_match {
+                Some(kind) => kind,
+                None => return Ok(SetOptionCommand::Custom { name, value }),
+            },
+        };
+        Ok(Self::from_kind_and_value(kind, value)?)
+    }
+
+    fn from_kind_and_value(
+        kind: UciOptionKind,
+        value: Option<String>,
+    ) -> Result<Self, SetOptionCommandParsingError> {
+        let name = kind.name();
+        let invalid = |value: &str| SetOptionCommandParsingError::InvalidValue {
+            name,
+            value: value.to_string(),
+        };
+
+        if kind == UciOptionKind::ClearHash {
+            return match value {
+                None => Ok(SetOptionCommand::ClearHash),
+                Some(_) => Err(SetOptionCommandParsingError::UnexpectedValue(name)),
+            };
+        }
+        let value = value.ok_or(SetOptionCommandParsingError::MissingValue(name))?;
+
+        let spin = |value: &str| value.parse::<u32>().map_err(|_| invalid(value));
+        let check = |value: &str| value.parse::<bool>().map_err(|_| invalid(value));
+
+        let cmd = match kind {
+            UciOptionKind::Threads => SetOptionCommand::Threads {
+                value: spin(&value)?,
+            },
+            UciOptionKind::Hash => SetOptionCommand::Hash {
+                value: spin(&value)?,
+            },
+            UciOptionKind::MultiPV => SetOptionCommand::MultiPV {
+                value: spin(&value)?,
+            },
+            UciOptionKind::NumaPolicy => SetOptionCommand::NumaPolicy {
+                value: model::NumaPolicy::try_from(model::UciString(value.clone()))
+                    .map_err(|_| invalid(&value))?,
+            },
+            UciOptionKind::ClearHash => unreachable!("handled above"),
+            UciOptionKind::Ponder => SetOptionCommand::Ponder {
+                value: check(&value)?,
+            },
+            UciOptionKind::EvalFile => SetOptionCommand::EvalFile {
+                value: model::UciString::from_token(&value).0,
+            },
+            UciOptionKind::EvalFileSmall => SetOptionCommand::EvalFileSmall {
+                value: model::UciString::from_token(&value).0,
+            },
+            UciOptionKind::UCIChess960 => SetOptionCommand::UCIChess960 {
+                value: check(&value)?,
+            },
+            UciOptionKind::UCIShowWDL => SetOptionCommand::UCIShowWDL {
+                value: check(&value)?,
+            },
+            UciOptionKind::UCILimitStrength => SetOptionCommand::UCILimitStrength {
+                value: check(&value)?,
+            },
+            UciOptionKind::UCIElo => SetOptionCommand::UCIElo {
+                value: spin(&value)?,
+            },
+            UciOptionKind::SkillLevel => SetOptionCommand::SkillLevel {
+                value: spin(&value)?,
+            },
+            UciOptionKind::SyzygyPath => SetOptionCommand::SyzygyPath {
+                value: value.parse().map_err(|_| invalid(&value))?,
+            },
+            UciOptionKind::SyzygyProbeDepth => SetOptionCommand::SyzygyProbeDepth {
+                value: spin(&value)?,
+            },
+            UciOptionKind::Syzygy50MoveRule => SetOptionCommand::Syzygy50MoveRule {
+                value: check(&value)?,
+            },
+            UciOptionKind::SyzygyProbeLimit => SetOptionCommand::SyzygyProbeLimit {
+                value: spin(&value)?,
+            },
+            UciOptionKind::MoveOverhead => SetOptionCommand::MoveOverhead {
+                value: spin(&value)?,
+            },
+            UciOptionKind::Nodestime => SetOptionCommand::Nodestime {
+                value: spin(&value)?,
+            },
+            UciOptionKind::DebugLogFile => SetOptionCommand::DebugLogFile {
+                value: model::UciString::from_token(&value).0,
+            },
+        };
+        Ok(cmd)
+    }
+}
+
+impl FromStr for SetOptionCommand {
+    type Err = command::parsing::Error<SetOptionCommandParsingError>;
+
+    /// Parses the command with the default [`ValueTokenPolicy`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, ValueTokenPolicy::default())
+    }
+}
+
 impl UciCommandTrait for SetOptionCommand {
     // TODO: Define a proper response type
     type Response = ();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(name: &str, value: Option<&str>) -> SetOptionCommand {
+        SetOptionCommand::Custom {
+            name: name.to_string(),
+            value: value.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_setoption_name() {
+        assert_eq!(SetOptionCommand::ClearHash.name(), "Clear Hash");
+        assert_eq!(
+            SetOptionCommand::SkillLevel { value: 10 }.kind(),
+            Some(UciOptionKind::SkillLevel)
+        );
+        assert_eq!(custom("Contempt", Some("10")).name(), "Contempt");
+        assert_eq!(custom("Contempt", Some("10")).kind(), None);
+    }
+
+    #[test]
+    fn test_setoption_round_trip() {
+        let commands = [
+            SetOptionCommand::Threads { value: 4 },
+            SetOptionCommand::NumaPolicy {
+                value: model::NumaPolicy::Auto,
+            },
+            SetOptionCommand::ClearHash,
+            SetOptionCommand::UCIShowWDL { value: true },
+            SetOptionCommand::SkillLevel { value: 10 },
+            SetOptionCommand::MoveOverhead { value: 100 },
+            SetOptionCommand::DebugLogFile {
+                value: "sf.log".to_string(),
+            },
+            SetOptionCommand::SyzygyPath {
+                value: model::SyzygyPathSpec::default(),
+            },
+            custom("Contempt", Some("10")),
+            custom("Reset Stats", None),
+        ];
+        for cmd in commands {
+            assert_eq!(cmd.to_string().parse::<SetOptionCommand>().unwrap(), cmd);
+        }
+    }
+
+    #[test]
+    fn test_setoption_string_values() {
+        for (value, line) in [
+            ("", "setoption name EvalFile value <empty>"),
+            ("<empty>", "setoption name EvalFile value \\<empty>"),
+            (
+                "nets/a b.nnue",
+                "setoption name EvalFile value nets/a b.nnue",
+            ),
+        ] {
+            let cmd = SetOptionCommand::EvalFile {
+                value: value.to_string(),
+            };
+            assert_eq!(cmd.to_string(), line);
+            assert_eq!(line.parse::<SetOptionCommand>().unwrap(), cmd);
+        }
+
+        // Whitespace around the value doesn't survive parsing
+        let cmd = SetOptionCommand::DebugLogFile {
+            value: " sf.log ".to_string(),
+        };
+        assert_eq!(
+            cmd.to_string().parse::<SetOptionCommand>().unwrap(),
+            SetOptionCommand::DebugLogFile {
+                value: "sf.log".to_string(),
+            }
+        );
+
+        // The custom values are verbatim
+        assert_eq!(
+            "setoption name WeightsFile value <empty>"
+                .parse::<SetOptionCommand>()
+                .unwrap(),
+            custom("WeightsFile", Some("<empty>"))
+        );
+    }
+
+    #[test]
+    fn test_setoption_value_token_in_value() {
+        let cmd = custom("Book File", Some("my value name value.bin"));
+        let line = cmd.to_string();
+        assert_eq!(
+            line,
+            "setoption name Book File value my value name value.bin"
+        );
+        // The default policy round-trips values containing `value`
+        assert_eq!(line.parse::<SetOptionCommand>().unwrap(), cmd);
+        assert_eq!(
+            SetOptionCommand::parse_with(&line, ValueTokenPolicy::LastValueToken).unwrap(),
+            custom("Book File value my value name", Some("value.bin"))
+        );
+    }
+
+    #[test]
+    fn test_setoption_value_token_in_name() {
+        let line = "setoption name My value option value 3";
+        assert_eq!(
+            line.parse::<SetOptionCommand>().unwrap(),
+            custom("My", Some("option value 3"))
+        );
+        assert_eq!(
+            SetOptionCommand::parse_with(line, ValueTokenPolicy::LastValueToken).unwrap(),
+            custom("My value option", Some("3"))
+        );
+    }
+
+    #[test]
+    fn test_setoption_name_token_in_name_and_value() {
+        let cmd = custom("name", Some("name"));
+        let line = cmd.to_string();
+        assert_eq!(line, "setoption name name value name");
+        assert_eq!(line.parse::<SetOptionCommand>().unwrap(), cmd);
+        assert_eq!(
+            SetOptionCommand::parse_with(&line, ValueTokenPolicy::LastValueToken).unwrap(),
+            cmd
+        );
+    }
+
+    #[test]
+    fn test_setoption_standard_names() {
+        assert_eq!(
+            "setoption name hash value 64"
+                .parse::<SetOptionCommand>()
+                .unwrap(),
+            SetOptionCommand::Hash { value: 64 }
+        );
+        assert_eq!(
+            "setoption name  Skill   Level value 5\n"
+                .parse::<SetOptionCommand>()
+                .unwrap(),
+            SetOptionCommand::SkillLevel { value: 5 }
+        );
+        assert!(matches!(
+            "setoption name Hash value big".parse::<SetOptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                SetOptionCommandParsingError::InvalidValue { name: "Hash", .. }
+            ))
+        ));
+        assert!(matches!(
+            "setoption name Hash".parse::<SetOptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                SetOptionCommandParsingError::MissingValue("Hash")
+            ))
+        ));
+        assert!(matches!(
+            "setoption name Clear Hash value 1".parse::<SetOptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                SetOptionCommandParsingError::UnexpectedValue("Clear Hash")
+            ))
+        ));
+        assert!(matches!(
+            "setoption name value 3".parse::<SetOptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                SetOptionCommandParsingError::EmptyOptionName
+            ))
+        ));
+        assert!(matches!(
+            "setoption Hash value 3".parse::<SetOptionCommand>(),
+            Err(command::parsing::Error::CustomError(
+                SetOptionCommandParsingError::NameTokenExpected
+            ))
+        ));
+    }
+}
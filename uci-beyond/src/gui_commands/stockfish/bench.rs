@@ -0,0 +1,121 @@
+use std::fmt::Display;
+
+use crate::{gui_command_responses::stockfish::BenchResponse, gui_commands::UciCommandTrait};
+
+/// Run a standard benchmark, e.g. `bench 16 1 13 default depth`.
+///
+/// The parameters are positional, so when a parameter is set, the ones before it are sent with their Stockfish defaults.
+/// The output is printed to stderr by Stockfish, so the connection must forward it for [`BenchResponse`] to be read.
+///
+/// See in Stockfish documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html>.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BenchCommand {
+    /// The size of the hash table in MB. Defaults to [`BenchCommand::DEFAULT_HASH`].
+    pub hash: Option<u32>,
+    /// The number of threads. Defaults to [`BenchCommand::DEFAULT_THREADS`].
+    pub threads: Option<u32>,
+    /// The limit of the search, interpreted according to `limit_type`. Defaults to [`BenchCommand::DEFAULT_LIMIT`].
+    pub limit: Option<u32>,
+    /// The file with the positions, or `default` or `current`. Defaults to [`BenchCommand::DEFAULT_FEN_FILE`].
+    pub fen_file: Option<String>,
+    /// Defaults to [`BenchLimitType::Depth`].
+    pub limit_type: Option<BenchLimitType>,
+}
+
+/// How the limit of [`BenchCommand`] is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BenchLimitType {
+    #[default]
+    Depth,
+    Perft,
+    Nodes,
+    Movetime,
+    Eval,
+}
+
+impl Display for BenchLimitType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BenchLimitType::Depth => write!(f, "depth"),
+            BenchLimitType::Perft => write!(f, "perft"),
+            BenchLimitType::Nodes => write!(f, "nodes"),
+            BenchLimitType::Movetime => write!(f, "movetime"),
+            BenchLimitType::Eval => write!(f, "eval"),
+        }
+    }
+}
+
+impl BenchCommand {
+    pub const DEFAULT_HASH: u32 = 16;
+    pub const DEFAULT_THREADS: u32 = 1;
+    pub const DEFAULT_LIMIT: u32 = 13;
+    pub const DEFAULT_FEN_FILE: &'static str = "default";
+}
+
+impl Display for BenchCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            hash,
+            threads,
+            limit,
+            fen_file,
+            limit_type,
+        } = self;
+
+        // The number of leading parameters that have to be sent
+        let len = [
+            hash.is_some(),
+            threads.is_some(),
+            limit.is_some(),
+            fen_file.is_some(),
+            limit_type.is_some(),
+        ]
+        .iter()
+        .rposition(|&is_set| is_set)
+        .map_or(0, |i| i + 1);
+
+        let params = [
+            hash.unwrap_or(Self::DEFAULT_HASH).to_string(),
+            threads.unwrap_or(Self::DEFAULT_THREADS).to_string(),
+            limit.unwrap_or(Self::DEFAULT_LIMIT).to_string(),
+            fen_file
+                .clone()
+                .unwrap_or_else(|| Self::DEFAULT_FEN_FILE.to_string()),
+            limit_type.unwrap_or_default().to_string(),
+        ];
+
+        write!(f, "bench")?;
+        for param in &params[..len] {
+            write!(f, " {param}")?;
+        }
+        Ok(())
+    }
+}
+
+impl UciCommandTrait for BenchCommand {
+    type Response = BenchResponse;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_bench_command() {
+        assert_eq!(BenchCommand::default().to_string(), "bench");
+
+        let cmd = BenchCommand {
+            hash: Some(64),
+            threads: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(cmd.to_string(), "bench 64 4");
+
+        let cmd = BenchCommand {
+            limit: Some(1000),
+            limit_type: Some(BenchLimitType::Movetime),
+            ..Default::default()
+        };
+        assert_eq!(cmd.to_string(), "bench 16 1 1000 default movetime");
+    }
+}
@@ -0,0 +1,18 @@
+use std::fmt::Display;
+
+use crate::{gui_command_responses::stockfish::CompilerResponse, gui_commands::UciCommandTrait};
+
+/// Display the compiler and the compilation settings of the Stockfish binary.
+///
+/// See in Stockfish documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html>.
+pub struct CompilerCommand;
+
+impl Display for CompilerCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "compiler")
+    }
+}
+
+impl UciCommandTrait for CompilerCommand {
+    type Response = CompilerResponse;
+}
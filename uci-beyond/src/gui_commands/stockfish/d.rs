@@ -0,0 +1,18 @@
+use std::fmt::Display;
+
+use crate::{gui_command_responses::stockfish::DisplayResponse, gui_commands::UciCommandTrait};
+
+/// Display the current position, with ASCII art, FEN, the Zobrist key, and the checkers.
+///
+/// See in Stockfish documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html>.
+pub struct DisplayCommand;
+
+impl Display for DisplayCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "d")
+    }
+}
+
+impl UciCommandTrait for DisplayCommand {
+    type Response = DisplayResponse;
+}
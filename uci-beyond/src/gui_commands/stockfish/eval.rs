@@ -0,0 +1,18 @@
+use std::fmt::Display;
+
+use crate::{gui_command_responses::stockfish::EvalResponse, gui_commands::UciCommandTrait};
+
+/// Display the static evaluation of the current position, incl. the contributions of the NNUE buckets.
+///
+/// See in Stockfish documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html>.
+pub struct EvalCommand;
+
+impl Display for EvalCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "eval")
+    }
+}
+
+impl UciCommandTrait for EvalCommand {
+    type Response = EvalResponse;
+}
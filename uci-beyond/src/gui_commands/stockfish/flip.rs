@@ -0,0 +1,18 @@
+use std::fmt::Display;
+
+use crate::gui_commands::UciCommandTrait;
+
+/// Flip the side to move. Stockfish doesn't respond to it.
+///
+/// See in Stockfish documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html>.
+pub struct FlipCommand;
+
+impl Display for FlipCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "flip")
+    }
+}
+
+impl UciCommandTrait for FlipCommand {
+    type Response = ();
+}
@@ -0,0 +1,18 @@
+//! The module for the non-standard commands supported by Stockfish, e.g. [`EvalCommand`] (for `eval` command),
+//! [`DisplayCommand`] (for `d` command), and [`BenchCommand`] (for `bench` command).
+//!
+//! These commands are not part of UCI and are mostly useful for debugging.
+//!
+//! See in Stockfish documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html>.
+
+mod bench;
+mod compiler;
+mod d;
+mod eval;
+mod flip;
+
+pub use bench::{BenchCommand, BenchLimitType};
+pub use compiler::CompilerCommand;
+pub use d::DisplayCommand;
+pub use eval::EvalCommand;
+pub use flip::FlipCommand;
@@ -1,10 +1,14 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use crate::gui_commands::UciCommandTrait;
+use crate::{
+    command,
+    gui_commands::{NoArgumentsCommandParsingError, UciCommandTrait, parse_no_arguments},
+};
 
 /// Stop calculating as soon as possible.
 ///
 /// See in Stockfish UCI documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#stop>.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct StopCommand;
 
 impl Display for StopCommand {
@@ -13,6 +17,20 @@ impl Display for StopCommand {
     }
 }
 
+impl command::Command for StopCommand {
+    type ParsingError = NoArgumentsCommandParsingError;
+
+    const NAME: &'static str = "stop";
+}
+
+impl FromStr for StopCommand {
+    type Err = command::parsing::Error<NoArgumentsCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_no_arguments::<Self>(s).map(|()| StopCommand)
+    }
+}
+
 impl UciCommandTrait for StopCommand {
     type Response = ();
 }
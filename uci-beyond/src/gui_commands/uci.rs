@@ -1,6 +1,10 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use crate::{gui_command_responses::UciCommandResponse, gui_commands::UciCommandTrait};
+use crate::{
+    command,
+    gui_command_responses::UciResponse,
+    gui_commands::{NoArgumentsCommandParsingError, UciCommandTrait, parse_no_arguments},
+};
 
 /// Tell the engine to use the UCI (universal chess interface).
 /// This will be sent once, by a GUI, as a first command after the program boots to tell the engine to switch to UCI mode.
@@ -9,6 +13,7 @@ use crate::{gui_command_responses::UciCommandResponse, gui_commands::UciCommandT
 /// If no `uciok` is sent within a certain time period, the engine task will be killed by the GUI.
 ///
 /// See in Stockfish UCI documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#uci>.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct UciCommand;
 
 impl Display for UciCommand {
@@ -17,7 +22,21 @@ impl Display for UciCommand {
     }
 }
 
+impl command::Command for UciCommand {
+    type ParsingError = NoArgumentsCommandParsingError;
+
+    const NAME: &'static str = "uci";
+}
+
+impl FromStr for UciCommand {
+    type Err = command::parsing::Error<NoArgumentsCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_no_arguments::<Self>(s).map(|()| UciCommand)
+    }
+}
+
 impl UciCommandTrait for UciCommand {
     // TODO: Define a proper response type
-    type Response = UciCommandResponse;
+    type Response = UciResponse;
 }
@@ -1,6 +1,9 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use crate::gui_commands::UciCommandTrait;
+use crate::{
+    command,
+    gui_commands::{NoArgumentsCommandParsingError, UciCommandTrait, parse_no_arguments},
+};
 
 /// This is sent to the engine when the next search (started with `position` and `go`) will be from a different game. This can be a new game the engine should play or a new game it should analyze but also the next position from a test suite with positions only.
 /// If the GUI hasn't sent a `ucinewgame` before the first `position` command, the engine won't expect any further `ucinewgame` commands as the GUI is probably not supporting the `ucinewgame` command.
@@ -8,6 +11,7 @@ use crate::gui_commands::UciCommandTrait;
 /// As the engine's reaction to `ucinewgame` can take some time the GUI should always send `isready` after `ucinewgame` to wait for the engine to finish its operation. The engine will respond with `readyok`.
 ///
 /// See in Stockfish UCI documentation: <https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#ucinewgame>.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct UciNewGameCommand;
 
 impl Display for UciNewGameCommand {
@@ -16,6 +20,20 @@ impl Display for UciNewGameCommand {
     }
 }
 
+impl command::Command for UciNewGameCommand {
+    type ParsingError = NoArgumentsCommandParsingError;
+
+    const NAME: &'static str = "ucinewgame";
+}
+
+impl FromStr for UciNewGameCommand {
+    type Err = command::parsing::Error<NoArgumentsCommandParsingError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_no_arguments::<Self>(s).map(|()| UciNewGameCommand)
+    }
+}
+
 impl UciCommandTrait for UciNewGameCommand {
     type Response = ();
 }
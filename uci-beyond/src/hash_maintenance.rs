@@ -0,0 +1,214 @@
+//! The module for [`HashMaintenance`], i.e. the policy of clearing the hash table of the engine between the searches
+//! once it stays saturated, as reported by the `hashfull` of the `info` lines.
+
+use crate::{
+    engine_commands::{DepthInfoCommand, InfoCommand},
+    gui_command_responses::{GoEvent, GoResponse},
+    gui_commands::SetOptionCommand,
+};
+
+/// When [`HashMaintenance`] suggests clearing the hash table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashMaintenanceConfig {
+    /// The average `hashfull` of a search, in permill, from which the search counts as saturated.
+    pub threshold: u32,
+    /// The number of saturated searches in a row after which clearing is suggested.
+    pub consecutive_searches: usize,
+    /// Whether [`Session`](crate::session::Session) sends `setoption name Clear Hash` by itself
+    /// when clearing is suggested.
+    pub auto_clear: bool,
+}
+
+impl Default for HashMaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 900,
+            consecutive_searches: 3,
+            auto_clear: false,
+        }
+    }
+}
+
+/// Follows the `hashfull` reported by the searches and suggests `setoption name Clear Hash`
+/// when the average of a search exceeds the threshold for a number of searches in a row (see [`HashMaintenanceConfig`]).
+///
+/// A search starts with its first `info` line and ends with `bestmove`. Clearing is never suggested
+/// in the middle of a search, since the engine may ignore or, worse, act on `setoption` while searching.
+/// The searches that don't report `hashfull` leave the count of the saturated searches unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct HashMaintenance {
+    config: HashMaintenanceConfig,
+    in_search: bool,
+    /// The sum and the number of the `hashfull` samples of the search in progress.
+    samples: (u64, u64),
+    last_average: Option<u32>,
+    saturated_searches: usize,
+}
+
+impl HashMaintenance {
+    pub fn new(config: HashMaintenanceConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> HashMaintenanceConfig {
+        self.config
+    }
+
+    /// Takes the event of the search in progress into account, e.g. from the stream of
+    /// [`go_event_channel`](crate::gui_command_responses::go_event_channel).
+    pub fn observe(&mut self, event: &GoEvent) {
+        match event {
+            GoEvent::Info(info) => {
+                self.in_search = true;
+                if let InfoCommand::Depth(DepthInfoCommand {
+                    hashfull: Some(hashfull),
+                    ..
+                }) = info
+                {
+                    self.samples.0 += u64::from(*hashfull);
+                    self.samples.1 += 1;
+                }
+            }
+            GoEvent::Refutation(_) | GoEvent::CurrLine(_) => self.in_search = true,
+            GoEvent::BestMove(_) => self.end_search(),
+        }
+    }
+
+    /// Takes the completed search into account.
+    pub fn observe_response(&mut self, response: &GoResponse) {
+        for event in response.events() {
+            self.observe(event);
+        }
+    }
+
+    fn end_search(&mut self) {
+        let (sum, count) = std::mem::take(&mut self.samples);
+        self.in_search = false;
+        if count == 0 {
+            return;
+        }
+        let average = (sum / count) as u32;
+        self.last_average = Some(average);
+        if average >= self.config.threshold {
+            self.saturated_searches += 1;
+        } else {
+            self.saturated_searches = 0;
+        }
+    }
+
+    /// Whether a search is in progress, i.e. an `info` line was observed since the last `bestmove`.
+    pub fn is_searching(&self) -> bool {
+        self.in_search
+    }
+
+    /// The average `hashfull` of the last search that reported it, in permill.
+    pub fn last_average(&self) -> Option<u32> {
+        self.last_average
+    }
+
+    /// The number of saturated searches in a row, since the last clearing.
+    pub fn saturated_searches(&self) -> usize {
+        self.saturated_searches
+    }
+
+    /// The `setoption name Clear Hash` to send before the next search, if the hash table stayed saturated
+    /// and no search is in progress.
+    pub fn suggestion(&self) -> Option<SetOptionCommand> {
+        let saturated = self.saturated_searches >= self.config.consecutive_searches.max(1);
+        (saturated && !self.in_search).then_some(SetOptionCommand::ClearHash)
+    }
+
+    /// Records that the hash table was cleared, so the saturated searches are counted anew.
+    pub fn record_clear(&mut self) {
+        self.saturated_searches = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{engine_commands::BestMoveCommand, model};
+
+    fn info(hashfull: Option<u32>) -> GoEvent {
+        GoEvent::Info(InfoCommand::Depth(DepthInfoCommand {
+            depth: Some(20),
+            hashfull,
+            ..Default::default()
+        }))
+    }
+
+    fn bestmove() -> GoEvent {
+        GoEvent::BestMove(BestMoveCommand {
+            best_move: model::MoveString::from("e2e4"),
+            ponder: None,
+        })
+    }
+
+    /// Runs a search reporting the `hashfull` samples.
+    fn search(maintenance: &mut HashMaintenance, samples: &[u32]) {
+        for &hashfull in samples {
+            maintenance.observe(&info(Some(hashfull)));
+        }
+        maintenance.observe(&bestmove());
+    }
+
+    fn maintenance() -> HashMaintenance {
+        HashMaintenance::new(HashMaintenanceConfig {
+            threshold: 800,
+            consecutive_searches: 2,
+            auto_clear: false,
+        })
+    }
+
+    #[test]
+    fn test_suggests_after_consecutive_saturated_searches() {
+        let mut maintenance = maintenance();
+        // The average is 750 despite the peak of 950
+        search(&mut maintenance, &[500, 800, 950]);
+        assert_eq!(maintenance.last_average(), Some(750));
+        assert_eq!(maintenance.saturated_searches(), 0);
+
+        search(&mut maintenance, &[850, 900]);
+        assert_eq!(maintenance.suggestion(), None);
+        search(&mut maintenance, &[950, 990]);
+        assert_eq!(maintenance.suggestion(), Some(SetOptionCommand::ClearHash));
+
+        maintenance.record_clear();
+        assert_eq!(maintenance.suggestion(), None);
+    }
+
+    #[test]
+    fn test_unsaturated_search_resets_the_count() {
+        let mut maintenance = maintenance();
+        search(&mut maintenance, &[900]);
+        search(&mut maintenance, &[100]);
+        search(&mut maintenance, &[900]);
+        assert_eq!(maintenance.saturated_searches(), 1);
+        assert_eq!(maintenance.suggestion(), None);
+
+        // A search without `hashfull` changes nothing
+        maintenance.observe(&info(None));
+        maintenance.observe(&bestmove());
+        assert_eq!(maintenance.saturated_searches(), 1);
+        assert_eq!(maintenance.last_average(), Some(900));
+    }
+
+    #[test]
+    fn test_never_suggests_during_a_search() {
+        let mut maintenance = maintenance();
+        search(&mut maintenance, &[900]);
+        search(&mut maintenance, &[900]);
+
+        maintenance.observe(&info(Some(990)));
+        assert!(maintenance.is_searching());
+        assert_eq!(maintenance.suggestion(), None);
+
+        maintenance.observe(&bestmove());
+        assert!(!maintenance.is_searching());
+        assert_eq!(maintenance.suggestion(), Some(SetOptionCommand::ClearHash));
+    }
+}
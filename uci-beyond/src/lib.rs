@@ -1,7 +1,23 @@
+pub mod analysis_cache;
+pub mod capabilities;
 pub mod command;
 pub mod engine_commands;
+pub mod engine_quirks;
+pub mod engine_side;
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
 pub mod gui_command_responses;
 pub mod gui_commands;
+pub mod hash_maintenance;
 pub mod model;
 pub mod options;
+#[cfg(feature = "pgn")]
+pub mod pgn;
+pub mod prelude;
+pub mod search_history;
+pub mod session;
+pub mod test_suite;
+#[cfg(test)]
+mod test_util;
+pub mod transcript;
 pub mod util;
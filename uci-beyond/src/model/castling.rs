@@ -0,0 +1,199 @@
+#[cfg(feature = "board")]
+use shakmaty::{CastlingMode, CastlingSide, Chess, Position as _, Square, fen::Fen};
+
+#[cfg(feature = "board")]
+use crate::model::{FenString, MoveString};
+
+/// How a castling move is spelled in UCI long algebraic notation.
+///
+/// Engines switch between the two with the `UCI_Chess960` option,
+/// see [`Session::castling_notation`](crate::session::Session::castling_notation).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CastlingNotation {
+    /// The king moves to its destination, e.g. `e1g1`.
+    #[default]
+    Standard,
+    /// The king takes its own rook, e.g. `e1h1`.
+    Chess960,
+}
+
+impl CastlingNotation {
+    /// The notation used by an engine with `UCI_Chess960` set to `chess960`.
+    pub fn from_chess960(chess960: bool) -> Self {
+        if chess960 {
+            CastlingNotation::Chess960
+        } else {
+            CastlingNotation::Standard
+        }
+    }
+}
+
+#[cfg(feature = "board")]
+impl MoveString {
+    /// Spells the move in the given notation if it castles in the position, e.g. turns `e1h1` into `e1g1`
+    /// for [`CastlingNotation::Standard`]. Any other move, or a move in an invalid position, is returned as is.
+    ///
+    /// The castling rooks are found from the castling rights of the FEN, either `KQkq` (the outermost rook
+    /// on the side) or the files of Shredder-FEN and X-FEN, e.g. `HAha`. A castling that moves the king
+    /// by one file or not at all, which is possible in Chess960 only, is a plain king move in the standard
+    /// notation, so it stays in the king-takes-rook form.
+    pub fn normalize_castling(&self, notation: CastlingNotation, fen: &FenString) -> MoveString {
+        castling_move(&self.0, fen)
+            .and_then(|castling| castling.spelled(notation))
+            .map_or_else(|| self.clone(), MoveString)
+    }
+}
+
+/// A castling move as found in a position.
+#[cfg(feature = "board")]
+struct Castling {
+    king: Square,
+    rook: Square,
+    /// The destination of the king, the same in standard chess and Chess960.
+    king_to: Square,
+}
+
+#[cfg(feature = "board")]
+impl Castling {
+    fn spelled(&self, notation: CastlingNotation) -> Option<String> {
+        let to = match notation {
+            CastlingNotation::Chess960 => self.rook,
+            CastlingNotation::Standard if self.king.distance(self.king_to) < 2 => return None,
+            CastlingNotation::Standard => self.king_to,
+        };
+        Some(format!("{}{to}", self.king))
+    }
+}
+
+/// Recognizes the castling in either notation, or `None` for any other move or an invalid position.
+#[cfg(feature = "board")]
+fn castling_move(mv: &str, fen: &FenString) -> Option<Castling> {
+    let from = mv.get(..2)?.parse::<Square>().ok()?;
+    let to = mv.get(2..)?.parse::<Square>().ok()?;
+
+    // The Chess960 mode reads the castling rights of the standard FEN as well as of Shredder-FEN and X-FEN
+    let position: Chess = Fen::from_ascii(fen.0.as_bytes())
+        .ok()?
+        .into_position(CastlingMode::Chess960)
+        .ok()?;
+    let color = position.turn();
+    if position.board().king_of(color) != Some(from) {
+        return None;
+    }
+
+    CastlingSide::ALL
+        .into_iter()
+        .filter_map(|side| {
+            let rook = position.castles().rook(color, side)?;
+            Some(Castling {
+                king: from,
+                rook,
+                king_to: side.king_to(color),
+            })
+        })
+        .find(|castling| castling.rook == to || (castling.king_to == to && from.distance(to) >= 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "board")]
+    fn normalize(mv: &str, notation: CastlingNotation, fen: &str) -> String {
+        MoveString(mv.to_string())
+            .normalize_castling(notation, &FenString(fen.to_string()))
+            .0
+    }
+
+    /// Checks that the moves are the same castling in the standard and the Chess960 notation.
+    #[cfg(feature = "board")]
+    fn assert_castling(fen: &str, standard: &str, chess960: &str) {
+        assert_eq!(
+            normalize(chess960, CastlingNotation::Standard, fen),
+            standard
+        );
+        assert_eq!(
+            normalize(standard, CastlingNotation::Standard, fen),
+            standard
+        );
+        assert_eq!(
+            normalize(standard, CastlingNotation::Chess960, fen),
+            chess960
+        );
+        assert_eq!(
+            normalize(chess960, CastlingNotation::Chess960, fen),
+            chess960
+        );
+    }
+
+    #[cfg(feature = "board")]
+    #[test]
+    fn test_normalize_standard_castling() {
+        let white = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1";
+        assert_castling(white, "e1g1", "e1h1");
+        assert_castling(white, "e1c1", "e1a1");
+
+        let black = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R b KQkq - 0 1";
+        assert_castling(black, "e8g8", "e8h8");
+        assert_castling(black, "e8c8", "e8a8");
+    }
+
+    #[cfg(feature = "board")]
+    #[test]
+    fn test_normalize_chess960_start_position() {
+        // The king on b1 between the rooks on a1 and c1
+        let white = "rkrnbbqn/pppppppp/8/8/8/8/PPPPPPPP/RKRNBBQN w KQkq - 0 1";
+        assert_castling(white, "b1g1", "b1c1");
+        // The king moves to c1 by one file, which is a plain king move in the standard notation
+        assert_eq!(normalize("b1a1", CastlingNotation::Standard, white), "b1a1");
+
+        // The same position in Shredder-FEN
+        let black = "rkrnbbqn/pppppppp/8/8/8/8/PPPPPPPP/RKRNBBQN b CAca - 0 1";
+        assert_castling(black, "b8g8", "b8c8");
+        assert_eq!(normalize("b8a8", CastlingNotation::Standard, black), "b8a8");
+    }
+
+    #[cfg(feature = "board")]
+    #[test]
+    fn test_normalize_queenside_with_two_rooks() {
+        // `Q` is the outermost rook, while `B` is the inner one of X-FEN
+        let fen = "4k3/8/8/8/8/8/8/RR2K3 w Q - 0 1";
+        assert_castling(fen, "e1c1", "e1a1");
+        assert_eq!(normalize("e1b1", CastlingNotation::Standard, fen), "e1b1");
+
+        let fen = "4k3/8/8/8/8/8/8/RR2K3 w B - 0 1";
+        assert_castling(fen, "e1c1", "e1b1");
+    }
+
+    #[cfg(feature = "board")]
+    #[test]
+    fn test_normalize_other_moves() {
+        let fen = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w Kk - 0 1";
+        // No castling rights on the queenside
+        assert_eq!(normalize("e1a1", CastlingNotation::Standard, fen), "e1a1");
+        assert_eq!(normalize("e1c1", CastlingNotation::Chess960, fen), "e1c1");
+        // Not the side to move
+        assert_eq!(normalize("e8h8", CastlingNotation::Standard, fen), "e8h8");
+        // Not the king
+        assert_eq!(normalize("a1a2", CastlingNotation::Chess960, fen), "a1a2");
+        assert_eq!(normalize("e1f1", CastlingNotation::Chess960, fen), "e1f1");
+        assert_eq!(normalize("e7e8q", CastlingNotation::Standard, fen), "e7e8q");
+        // An invalid FEN
+        assert_eq!(
+            normalize("e1h1", CastlingNotation::Standard, "r3k2r/8 w KQkq - 0 1"),
+            "e1h1"
+        );
+    }
+
+    #[test]
+    fn test_castling_notation_from_chess960() {
+        assert_eq!(
+            CastlingNotation::from_chess960(true),
+            CastlingNotation::Chess960
+        );
+        assert_eq!(
+            CastlingNotation::from_chess960(false),
+            CastlingNotation::Standard
+        );
+    }
+}
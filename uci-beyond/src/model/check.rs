@@ -2,8 +2,10 @@ use std::fmt::Display;
 
 use crate::command;
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum CheckParsingError {
+    #[error("invalid check value '{0}' (expected one of: true, false)")]
     InvalidCheckValue(String),
 }
 
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use crate::model::Color;
+
+/// The state of a chess clock, which can be turned into a `go` command with
+/// [`GoCommand::from_clock`](crate::gui_commands::GoCommand::from_clock).
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Default)]
+pub struct Clock {
+    /// The time White has left on the clock.
+    pub white: Duration,
+    /// The time Black has left on the clock.
+    pub black: Duration,
+    /// White's increment per move.
+    pub white_inc: Duration,
+    /// Black's increment per move.
+    pub black_inc: Duration,
+    /// The number of moves to the next time control. `None` means sudden death.
+    pub moves_to_go: Option<u32>,
+}
+
+impl Clock {
+    /// The time the given side has left on the clock.
+    pub fn time(&self, color: Color) -> Duration {
+        match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+        }
+    }
+
+    /// The increment per move of the given side.
+    pub fn inc(&self, color: Color) -> Duration {
+        match color {
+            Color::White => self.white_inc,
+            Color::Black => self.black_inc,
+        }
+    }
+}
+
+/// Converts a duration to milliseconds as used in UCI, saturating at [`u32::MAX`].
+pub(crate) fn duration_to_ms(duration: Duration) -> u32 {
+    u32::try_from(duration.as_millis()).unwrap_or(u32::MAX)
+}
@@ -0,0 +1,133 @@
+use std::{fmt::Display, ops::Not, str::FromStr};
+
+/// The side in a chess game.
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    /// The opposite side.
+    pub fn opposite(self) -> Self {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+impl Not for Color {
+    type Output = Color;
+
+    fn not(self) -> Self::Output {
+        self.opposite()
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Color::White => write!(f, "white"),
+            Color::Black => write!(f, "black"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ColorParsingError {
+    #[error("Invalid side to move: {0:?}, expected `w` or `b`")]
+    InvalidSideToMove(String),
+}
+
+/// Parses the side-to-move field of a FEN, i.e. `w` or `b`.
+impl FromStr for Color {
+    type Err = ColorParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "w" => Ok(Color::White),
+            "b" => Ok(Color::Black),
+            _ => Err(ColorParsingError::InvalidSideToMove(s.to_string())),
+        }
+    }
+}
+
+/// The number of half-moves played since the start of the game, e.g. `0` for the starting position
+/// and `1` after `1. e4`.
+#[derive(Eq, Hash, PartialEq, PartialOrd, Ord, Debug, Clone, Copy, Default)]
+pub struct GamePly(pub u32);
+
+impl GamePly {
+    /// The ply of the position with the full-move number (as in a FEN, starting at 1) and the side to move.
+    ///
+    /// A full-move number of 0, which some tools write, is treated as 1.
+    pub fn from_move_number(move_number: u32, side_to_move: Color) -> Self {
+        let white_plies = move_number.max(1).saturating_sub(1).saturating_mul(2);
+        match side_to_move {
+            Color::White => GamePly(white_plies),
+            Color::Black => GamePly(white_plies.saturating_add(1)),
+        }
+    }
+
+    /// The full-move number, which starts at 1 and grows after every move of Black.
+    pub fn move_number(self) -> u32 {
+        self.0 / 2 + 1
+    }
+
+    pub fn side_to_move(self) -> Color {
+        if self.0 % 2 == 0 {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::FenString;
+
+    #[test]
+    fn test_color() {
+        assert_eq!(!Color::White, Color::Black);
+        assert_eq!(Color::Black.opposite(), Color::White);
+        assert_eq!("w".parse(), Ok(Color::White));
+        assert_eq!("b".parse(), Ok(Color::Black));
+        assert!("W".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn test_game_ply_move_numbers() {
+        assert_eq!(GamePly::from_move_number(1, Color::White), GamePly(0));
+        assert_eq!(GamePly::from_move_number(1, Color::Black), GamePly(1));
+        assert_eq!(GamePly::from_move_number(40, Color::White), GamePly(78));
+        assert_eq!(GamePly::from_move_number(0, Color::White), GamePly(0));
+
+        for ply in 0..10 {
+            let ply = GamePly(ply);
+            assert_eq!(
+                GamePly::from_move_number(ply.move_number(), ply.side_to_move()),
+                ply
+            );
+        }
+    }
+
+    #[test]
+    fn test_fen_side_to_move_and_ply() {
+        let fen = FenString::from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_eq!(fen.side_to_move(), Some(Color::Black));
+        assert_eq!(fen.ply(), Some(GamePly(1)));
+
+        let fen = FenString::from("8/8/4k3/8/8/4K3/8/8 w - - 12 57");
+        assert_eq!(fen.side_to_move(), Some(Color::White));
+        assert_eq!(fen.ply(), Some(GamePly(112)));
+
+        let fen = FenString::from("8/8/4k3/8/8/4K3/8/8");
+        assert_eq!(fen.side_to_move(), None);
+        assert_eq!(fen.ply(), None);
+    }
+}
@@ -0,0 +1,134 @@
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
+
+#[derive(thiserror::Error, Debug, Clone)]
+#[non_exhaustive]
+pub enum CpuSetParsingError {
+    #[error("Empty CPU set.")]
+    Empty,
+    #[error("Invalid CPU index: `{0}`.")]
+    InvalidIndex(String),
+    #[error("Invalid CPU range: `{0}`. The start must not exceed the end.")]
+    InvalidRange(String),
+}
+
+/// A set of CPU indices as a comma-separated list of indices and "first-last" ranges,
+/// e.g. `0-15,32-47` or `0,2,4`.
+///
+/// Appears in
+///
+/// ```text
+/// info string Available processors: 0-15,32-47
+/// ```
+///
+/// and in the per-node lists of [`NumaPolicy::Custom`](crate::model::NumaPolicy::Custom).
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+pub struct CpuSet(pub Vec<RangeInclusive<u32>>);
+
+impl CpuSet {
+    /// The total number of CPUs in the set, saturating at [`u32::MAX`].
+    ///
+    /// Overlapping ranges are counted multiple times, and a reversed range, which isn't parsed, is empty.
+    pub fn total(&self) -> u32 {
+        self.0
+            .iter()
+            .map(|range| match range.end().checked_sub(*range.start()) {
+                Some(len) => len.saturating_add(1),
+                None => 0,
+            })
+            .fold(0, u32::saturating_add)
+    }
+}
+
+impl Display for CpuSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, range) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            if range.start() == range.end() {
+                write!(f, "{}", range.start())?;
+            } else {
+                write!(f, "{}-{}", range.start(), range.end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CpuSet {
+    type Err = CpuSetParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(CpuSetParsingError::Empty);
+        }
+
+        let parse_index = |index: &str| -> Result<u32, CpuSetParsingError> {
+            let index = index.trim();
+            index
+                .parse::<u32>()
+                .map_err(|_| CpuSetParsingError::InvalidIndex(index.to_string()))
+        };
+
+        let ranges = s
+            .split(',')
+            .map(|part| match part.split_once('-') {
+                Some((beg, end)) => {
+                    let (beg, end) = (parse_index(beg)?, parse_index(end)?);
+                    if beg > end {
+                        return Err(CpuSetParsingError::InvalidRange(part.trim().to_string()));
+                    }
+                    Ok(beg..=end)
+                }
+                None => {
+                    let index = parse_index(part)?;
+                    Ok(index..=index)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CpuSet(ranges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_set() {
+        let set = "0-7".parse::<CpuSet>().expect("Failed to parse CpuSet");
+        assert_eq!(set, CpuSet(vec![0..=7]));
+        assert_eq!(set.total(), 8);
+
+        let set = "0-15,32-47"
+            .parse::<CpuSet>()
+            .expect("Failed to parse CpuSet");
+        assert_eq!(set, CpuSet(vec![0..=15, 32..=47]));
+        assert_eq!(set.total(), 32);
+
+        let set = "0,2,4-6".parse::<CpuSet>().expect("Failed to parse CpuSet");
+        assert_eq!(set, CpuSet(vec![0..=0, 2..=2, 4..=6]));
+        assert_eq!(set.total(), 5);
+
+        assert_eq!(CpuSet(vec![0..=u32::MAX]).total(), u32::MAX);
+        assert_eq!(CpuSet(vec![0..=u32::MAX - 1, 7..=7]).total(), u32::MAX);
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = CpuSet(vec![7..=0, 1..=2]);
+        assert_eq!(reversed.total(), 2);
+
+        assert!("".parse::<CpuSet>().is_err());
+        assert!("7-0".parse::<CpuSet>().is_err());
+        assert!("0-x".parse::<CpuSet>().is_err());
+        assert!("0,,1".parse::<CpuSet>().is_err());
+    }
+
+    #[test]
+    fn test_display_cpu_set() {
+        for s in ["0-7", "0-15,32-47", "0,2,4", "3"] {
+            let set = s.parse::<CpuSet>().expect("Failed to parse CpuSet");
+            assert_eq!(set.to_string(), s);
+        }
+    }
+}
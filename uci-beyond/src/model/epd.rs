@@ -0,0 +1,245 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::model::FenString;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EpdParsingError {
+    /// The line has fewer than the 4 fields of the position, e.g. because it is a full FEN without operations.
+    #[error("Missing the `{0}` field of the position.")]
+    MissingField(&'static str),
+    /// An operation has no opcode, e.g. `;;`.
+    #[error("Empty operation.")]
+    EmptyOperation,
+    /// A string operand isn't closed with `"`.
+    #[error("Unterminated string operand: `{0}`.")]
+    UnterminatedString(String),
+    #[error("Invalid centipawn evaluation: `{0}`.")]
+    InvalidCentipawnEvaluation(String),
+}
+
+/// A line of an [Extended Position Description (EPD)][EPD] file, i.e. the first 4 fields of a FEN followed by
+/// operations, as used by the test suites such as Win At Chess:
+///
+/// ```text
+/// 2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";
+/// ```
+///
+/// The moves of `bm` and `am` are usually in SAN, see [`TestSuiteRunner`](crate::test_suite::TestSuiteRunner).
+///
+/// [EPD]: https://www.chessprogramming.org/Extended_Position_Description
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Epd {
+    /// The piece placement, the side to move, the castling rights and the en passant square, separated by spaces.
+    pub position: String,
+    /// The best moves (`bm`), any of which solves the position.
+    pub best_moves: Vec<String>,
+    /// The moves to avoid (`am`).
+    pub avoid_moves: Vec<String>,
+    /// The centipawn evaluation (`ce`) from the point of view of the side to move.
+    pub centipawn_evaluation: Option<i32>,
+    /// The identifier (`id`), without the quotes.
+    pub id: Option<String>,
+    /// The other operations in order, as opcodes and their operands as written, e.g. `c0` and `"comment"`.
+    pub other: Vec<(String, String)>,
+}
+
+impl Epd {
+    /// The names of the fields of the position, in order.
+    const POSITION_FIELDS: [&str; 4] =
+        ["piece placement", "side to move", "castling", "en passant"];
+
+    /// The position as a FEN, with the halfmove clock (`hmvc`) and the fullmove number (`fmvn`)
+    /// of the operations or `0 1` without them.
+    pub fn to_fen(&self) -> FenString {
+        let operand = |opcode: &str| {
+            self.other
+                .iter()
+                .find(|(other, _)| other == opcode)
+                .map(|(_, operand)| operand.as_str())
+        };
+        FenString(format!(
+            "{} {} {}",
+            self.position,
+            operand("hmvc").unwrap_or("0"),
+            operand("fmvn").unwrap_or("1")
+        ))
+    }
+}
+
+/// Splits the operations at the `;` outside of the string operands.
+fn split_operations(s: &str) -> Result<Vec<&str>, EpdParsingError> {
+    let mut operations = Vec::new();
+    let mut start = 0;
+    let mut in_string = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ';' if !in_string => {
+                operations.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if in_string {
+        return Err(EpdParsingError::UnterminatedString(
+            s[start..].trim().to_string(),
+        ));
+    }
+    let mut operations = operations
+        .into_iter()
+        .map(str::trim)
+        .map(|operation| {
+            (!operation.is_empty())
+                .then_some(operation)
+                .ok_or(EpdParsingError::EmptyOperation)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // The last operation may lack its `;`
+    let last = s[start..].trim();
+    if !last.is_empty() {
+        operations.push(last);
+    }
+    Ok(operations)
+}
+
+impl FromStr for Epd {
+    type Err = EpdParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rest = s.trim();
+        let mut fields = Vec::with_capacity(Self::POSITION_FIELDS.len());
+        for name in Self::POSITION_FIELDS {
+            let field = rest
+                .split_whitespace()
+                .next()
+                .ok_or(EpdParsingError::MissingField(name))?;
+            fields.push(field);
+            rest = rest[field.len()..].trim_start();
+        }
+
+        let mut epd = Epd {
+            position: fields.join(" "),
+            ..Default::default()
+        };
+        for operation in split_operations(rest)? {
+            let (opcode, operand) = operation
+                .split_once(char::is_whitespace)
+                .map_or((operation, ""), |(opcode, operand)| {
+                    (opcode, operand.trim())
+                });
+            match opcode {
+                "bm" => epd
+                    .best_moves
+                    .extend(operand.split_whitespace().map(str::to_string)),
+                "am" => epd
+                    .avoid_moves
+                    .extend(operand.split_whitespace().map(str::to_string)),
+                "ce" => {
+                    let ce = operand.parse().map_err(|_| {
+                        EpdParsingError::InvalidCentipawnEvaluation(operand.to_string())
+                    })?;
+                    epd.centipawn_evaluation = Some(ce);
+                }
+                "id" => epd.id = Some(operand.trim_matches('"').to_string()),
+                _ => epd.other.push((opcode.to_string(), operand.to_string())),
+            }
+        }
+        Ok(epd)
+    }
+}
+
+/// Writes the operations in the order `bm`, `am`, `ce`, `id` and the other ones, each followed by `;`.
+impl Display for Epd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.position)?;
+        if !self.best_moves.is_empty() {
+            write!(f, " bm {};", self.best_moves.join(" "))?;
+        }
+        if !self.avoid_moves.is_empty() {
+            write!(f, " am {};", self.avoid_moves.join(" "))?;
+        }
+        if let Some(ce) = self.centipawn_evaluation {
+            write!(f, " ce {ce};")?;
+        }
+        if let Some(id) = &self.id {
+            write!(f, " id \"{id}\";")?;
+        }
+        for (opcode, operand) in &self.other {
+            if operand.is_empty() {
+                write!(f, " {opcode};")?;
+            } else {
+                write!(f, " {opcode} {operand};")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAC_001: &str =
+        r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";"#;
+
+    #[test]
+    fn test_parse_wac() {
+        let epd: Epd = WAC_001.parse().unwrap();
+        assert_eq!(
+            epd.position,
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - -"
+        );
+        assert_eq!(epd.best_moves, ["Qg6"]);
+        assert_eq!(epd.id.as_deref(), Some("WAC.001"));
+        assert_eq!(epd.to_string(), WAC_001);
+        assert_eq!(
+            epd.to_fen(),
+            FenString::from("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1")
+        );
+    }
+
+    #[test]
+    fn test_parse_operations() {
+        let line = r#"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - am f3 g4; ce -20; c0 "a; b"; hmvc 3; fmvn 7; noop"#;
+        let epd: Epd = line.parse().unwrap();
+        assert_eq!(epd.avoid_moves, ["f3", "g4"]);
+        assert_eq!(epd.centipawn_evaluation, Some(-20));
+        assert_eq!(
+            epd.other,
+            [
+                ("c0".to_string(), r#""a; b""#.to_string()),
+                ("hmvc".to_string(), "3".to_string()),
+                ("fmvn".to_string(), "7".to_string()),
+                ("noop".to_string(), String::new()),
+            ]
+        );
+        assert_eq!(epd.to_fen().0.split_whitespace().last(), Some("7"));
+        assert_eq!(epd.to_string().parse::<Epd>().unwrap(), epd);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "8/8/8/8/8/8/8/8 w".parse::<Epd>(),
+            Err(EpdParsingError::MissingField("castling"))
+        );
+        assert_eq!(
+            r#"8/8/8/8/8/8/8/8 w - - id "WAC"#.parse::<Epd>(),
+            Err(EpdParsingError::UnterminatedString(
+                r#"id "WAC"#.to_string()
+            ))
+        );
+        assert_eq!(
+            "8/8/8/8/8/8/8/8 w - - ce 1.5;".parse::<Epd>(),
+            Err(EpdParsingError::InvalidCentipawnEvaluation(
+                "1.5".to_string()
+            ))
+        );
+        assert_eq!(
+            "8/8/8/8/8/8/8/8 w - - ; bm e4;".parse::<Epd>(),
+            Err(EpdParsingError::EmptyOperation)
+        );
+    }
+}
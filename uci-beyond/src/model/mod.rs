@@ -4,22 +4,78 @@
 
 use std::fmt::Display;
 
+mod castling;
 mod check;
+mod clock;
+mod color;
+mod cpu_set;
+mod epd;
+mod move_list;
 mod numa_policy;
+#[cfg(feature = "board")]
+pub(crate) mod san;
+mod score;
+mod syzygy_path;
 mod uci_string;
+mod wdl;
 
+pub use castling::CastlingNotation;
 pub use check::{Check, CheckParsingError};
-pub use numa_policy::{NumaPolicy, NumaPolicyParsingError};
-pub use uci_string::UciString;
+pub use clock::Clock;
+pub(crate) use clock::duration_to_ms;
+pub use color::{Color, ColorParsingError, GamePly};
+pub use cpu_set::{CpuSet, CpuSetParsingError};
+pub use epd::{Epd, EpdParsingError};
+pub use move_list::{MoveList, MoveListParsingError};
+pub use numa_policy::{
+    CustomNumaPolicyString, CustomNumaPolicyStringParsingError, NumaPolicy, NumaPolicyParsingError,
+};
+#[cfg(feature = "board")]
+pub use san::SanConversionError;
+pub use score::{Score, ScoreParsingError};
+#[cfg(feature = "fs")]
+pub use syzygy_path::{SyzygyDirectoryError, SyzygyDirectoryFinding};
+pub use syzygy_path::{SyzygyPathSpec, SyzygyPathSpecParsingError};
+pub use uci_string::{EMPTY_SENTINEL, UciString};
+pub use wdl::Wdl;
 
 /// [Forsyth-Edwards Notation (FEN)](https://www.chess.com/terms/fen-chess)
 /// string representing a chess position.
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FenString(pub String);
 
+/// ```
+/// use uci_beyond::model::FenString;
+///
+/// let fen = FenString::from("8/8/8/8/8/8/8/K1k5 w - - 0 1");
+/// assert_eq!(fen, FenString("8/8/8/8/8/8/8/K1k5 w - - 0 1".to_string()));
+/// ```
+impl From<&str> for FenString {
+    fn from(fen: &str) -> Self {
+        FenString(fen.to_string())
+    }
+}
+
+impl FenString {
+    /// The side to move, i.e. the second field. `None` if it is missing or invalid.
+    pub fn side_to_move(&self) -> Option<Color> {
+        self.0.split_whitespace().nth(1)?.parse().ok()
+    }
+
+    /// The ply of the position according to the side to move and the full-move number, i.e. the sixth field.
+    ///
+    /// `None` if either of them is missing or invalid.
+    pub fn ply(&self) -> Option<GamePly> {
+        let move_number = self.0.split_whitespace().nth(5)?.parse().ok()?;
+        Some(GamePly::from_move_number(move_number, self.side_to_move()?))
+    }
+}
+
 /// Either a starting position or a [`FenString`].
 ///
 /// See [`gui_commands::PositionCommand`](crate::gui_commands::PositionCommand).
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Position {
     StartPos,
     Fen(FenString),
@@ -40,7 +96,8 @@ impl Display for Position {
 ///
 /// * <https://en.wikipedia.org/wiki/Algebraic_notation_(chess)#Long_algebraic_notation:~:text=A%20form%20of%20long%20algebraic,)%2C%20e7e8q%20(promotion)>
 /// * <https://en.wikipedia.org/wiki/Universal_Chess_Interface#Design:~:text=long%20algebraic%20notation>
-#[derive(Clone)]
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MoveString(pub String);
 
 impl Display for MoveString {
@@ -48,3 +105,16 @@ impl Display for MoveString {
         write!(f, "{}", self.0)
     }
 }
+
+/// The move isn't checked, see [`MoveList::parse`] for the moves that are.
+///
+/// ```
+/// use uci_beyond::model::MoveString;
+///
+/// assert_eq!(MoveString::from("e7e8q"), MoveString("e7e8q".to_string()));
+/// ```
+impl From<&str> for MoveString {
+    fn from(mv: &str) -> Self {
+        MoveString(mv.to_string())
+    }
+}
@@ -0,0 +1,247 @@
+use std::{fmt::Display, ops::Deref};
+
+use crate::model::MoveString;
+#[cfg(feature = "board")]
+use crate::model::{Position, SanConversionError};
+
+/// Moves in UCI long algebraic notation played or searched one after another, e.g. in
+/// `position startpos moves e2e4 e7e5` or `go searchmoves e2e4 d2d4`.
+///
+/// The moves are displayed separated by single spaces.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveList(pub Vec<MoveString>);
+
+/// The error of [`MoveList::parse`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MoveListParsingError {
+    /// The token starts with a square but isn't a move, e.g. `e2e9` or `e7e8k`.
+    ///
+    /// The index is the 0-based position of the token in the list.
+    #[error("Malformed move `{token}` at index {index}.")]
+    MalformedMove { index: usize, token: String },
+}
+
+impl MoveList {
+    /// Reads the moves up to the first token that doesn't start with a square, e.g. `depth` in
+    /// `e2e4 d2d4 depth 10`, and returns the rest of the line from that token.
+    ///
+    /// A token that starts with a square must be a move, i.e. two squares and an optional promotion
+    /// piece, or the null move `0000`.
+    pub fn parse(s: &str) -> Result<(MoveList, &str), MoveListParsingError> {
+        let mut moves = Vec::new();
+        let mut rest = s.trim_start();
+        while let Some(token) = rest.split_whitespace().next() {
+            if !starts_with_square(token) && token != "0000" {
+                break;
+            }
+            if !is_move(token) {
+                return Err(MoveListParsingError::MalformedMove {
+                    index: moves.len(),
+                    token: token.to_string(),
+                });
+            }
+            moves.push(MoveString(token.to_string()));
+            rest = rest[token.len()..].trim_start();
+        }
+        Ok((MoveList(moves), rest))
+    }
+
+    pub fn push(&mut self, mv: MoveString) {
+        self.0.push(mv);
+    }
+
+    /// Appends the move if it is legal after the moves of the list played from the root position,
+    /// e.g. to check the moves of a game as they arrive.
+    ///
+    /// The moves of the list are replayed every time, so an illegal move already in the list is
+    /// [`SanConversionError::IllegalPositionMove`], while an illegal `mv` is [`SanConversionError::IllegalMove`].
+    #[cfg(feature = "board")]
+    pub fn push_checked(
+        &mut self,
+        root: &Position,
+        mv: MoveString,
+    ) -> Result<(), SanConversionError> {
+        let board = self.play(root)?;
+        if super::san::to_move(&board, &mv).is_none() {
+            return Err(SanConversionError::IllegalMove {
+                ply: self.0.len(),
+                mv,
+            });
+        }
+        self.0.push(mv);
+        Ok(())
+    }
+
+    /// The board after the moves of the list played from the root position.
+    #[cfg(feature = "board")]
+    pub(crate) fn play(&self, root: &Position) -> Result<shakmaty::Chess, SanConversionError> {
+        use shakmaty::Position as _;
+
+        let mut board = root.to_board()?;
+        for (ply, mv) in self.0.iter().enumerate() {
+            let m = super::san::to_move(&board, mv).ok_or_else(|| {
+                SanConversionError::IllegalPositionMove {
+                    ply,
+                    mv: mv.clone(),
+                }
+            })?;
+            board.play_unchecked(&m);
+        }
+        Ok(board)
+    }
+}
+
+fn starts_with_square(token: &str) -> bool {
+    matches!(token.as_bytes(), [b'a'..=b'h', b'1'..=b'8', ..])
+}
+
+fn is_move(token: &str) -> bool {
+    match token.as_bytes() {
+        [b'0', b'0', b'0', b'0'] => true,
+        [from @ .., b'q' | b'r' | b'b' | b'n'] if from.len() == 4 => is_squares(from),
+        squares => is_squares(squares),
+    }
+}
+
+fn is_squares(bytes: &[u8]) -> bool {
+    matches!(bytes, [b'a'..=b'h', b'1'..=b'8', b'a'..=b'h', b'1'..=b'8'])
+}
+
+impl Display for MoveList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, mv) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{mv}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [MoveString];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<MoveString>> for MoveList {
+    fn from(moves: Vec<MoveString>) -> Self {
+        MoveList(moves)
+    }
+}
+
+impl FromIterator<MoveString> for MoveList {
+    fn from_iter<I: IntoIterator<Item = MoveString>>(iter: I) -> Self {
+        MoveList(iter.into_iter().collect())
+    }
+}
+
+/// The moves aren't checked, see [`MoveList::parse`] for the moves that are.
+///
+/// ```
+/// use uci_beyond::model::MoveList;
+///
+/// let moves = "e2e4 e7e5".split_whitespace().collect::<MoveList>();
+/// assert_eq!(moves.to_string(), "e2e4 e7e5");
+/// ```
+impl<'a> FromIterator<&'a str> for MoveList {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        iter.into_iter().map(MoveString::from).collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a MoveString;
+    type IntoIter = std::slice::Iter<'a, MoveString>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(s: &str) -> MoveList {
+        s.split_whitespace().collect()
+    }
+
+    #[test]
+    fn test_parse_move_list() {
+        assert_eq!(MoveList::parse(""), Ok((MoveList::default(), "")));
+        assert_eq!(
+            MoveList::parse("depth 10"),
+            Ok((MoveList::default(), "depth 10"))
+        );
+
+        let (list, rest) = MoveList::parse("e2e4  e7e5 a7a8q 0000").unwrap();
+        assert_eq!(list, moves("e2e4 e7e5 a7a8q 0000"));
+        assert_eq!(rest, "");
+        assert_eq!(list.to_string(), "e2e4 e7e5 a7a8q 0000");
+
+        let (list, rest) = MoveList::parse("e2e4 d2d4 depth 10").unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(rest, "depth 10");
+    }
+
+    #[test]
+    fn test_parse_malformed_move() {
+        assert_eq!(
+            MoveList::parse("e2e4 e7e9 g1f3"),
+            Err(MoveListParsingError::MalformedMove {
+                index: 1,
+                token: "e7e9".to_string(),
+            })
+        );
+        assert_eq!(
+            MoveList::parse("e2e4 e7e5 b7b8k"),
+            Err(MoveListParsingError::MalformedMove {
+                index: 2,
+                token: "b7b8k".to_string(),
+            })
+        );
+        assert_eq!(
+            MoveList::parse("e2"),
+            Err(MoveListParsingError::MalformedMove {
+                index: 0,
+                token: "e2".to_string(),
+            })
+        );
+    }
+
+    #[cfg(feature = "board")]
+    #[test]
+    fn test_push_checked() {
+        let mut list = MoveList::default();
+        for mv in ["e2e4", "e7e5", "g1f3"] {
+            list.push_checked(&Position::StartPos, MoveString(mv.to_string()))
+                .unwrap();
+        }
+        assert_eq!(list, moves("e2e4 e7e5 g1f3"));
+
+        // White has just moved
+        assert_eq!(
+            list.push_checked(&Position::StartPos, MoveString::from("d2d4")),
+            Err(SanConversionError::IllegalMove {
+                ply: 3,
+                mv: MoveString::from("d2d4")
+            })
+        );
+        assert_eq!(list.len(), 3);
+
+        let mut stale = moves("e2e4 e2e4");
+        assert_eq!(
+            stale.push_checked(&Position::StartPos, MoveString::from("e7e5")),
+            Err(SanConversionError::IllegalPositionMove {
+                ply: 1,
+                mv: MoveString::from("e2e4")
+            })
+        );
+    }
+}
@@ -2,13 +2,17 @@ use std::{fmt::Display, str::FromStr};
 
 use crate::model;
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum NumaPolicyParsingError {
+    #[error("empty NUMA policy")]
     EmptyNumaPolicyString,
+    #[error("{0}")]
     CustomNumaPolicyStringParsingError(CustomNumaPolicyStringParsingError),
 }
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid NUMA policy (expected the CPUs of the nodes, e.g. '0-15,32-47:16-31,48-63')")]
 pub struct CustomNumaPolicyStringParsingError;
 
 /// Precisely specify the available CPUs per [NUMA] domain. ':' separates numa nodes; ',' separates cpu indices; supports "first-last" range syntax for cpu indices, for example `0-15,32-47:16-31,48-63`.
@@ -17,6 +21,15 @@ pub struct CustomNumaPolicyStringParsingError;
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
 pub struct CustomNumaPolicyString(pub String);
 
+impl CustomNumaPolicyString {
+    /// The CPU sets of the [NUMA] nodes, in order.
+    ///
+    /// [NUMA]: https://www.chessprogramming.org/NUMA
+    pub fn nodes(&self) -> Result<Vec<model::CpuSet>, model::CpuSetParsingError> {
+        self.0.split(':').map(str::parse).collect()
+    }
+}
+
 /// The type for `NumaPolicy` option (see [`options::UciOption::NumaPolicy`](crate::options::UciOption::NumaPolicy)), which binds threads to a specific [NUMA] node to enhance performance on multi-CPU or multi-[NUMA] domain systems.
 ///
 /// [NUMA]: https://www.chessprogramming.org/NUMA
@@ -85,7 +98,51 @@ impl FromStr for CustomNumaPolicyString {
     type Err = CustomNumaPolicyStringParsingError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // TODO: implement validation of the custom NUMA policy string format
+        for node in s.split(':') {
+            node.parse::<model::CpuSet>()
+                .map_err(|_| CustomNumaPolicyStringParsingError)?;
+        }
         Ok(CustomNumaPolicyString(s.to_string()))
     }
 }
+
+/// Checks the policy like [`FromStr`].
+///
+/// ```
+/// use uci_beyond::model::CustomNumaPolicyString;
+///
+/// let policy = CustomNumaPolicyString::try_from("0-15,32-47:16-31,48-63").unwrap();
+/// assert_eq!(policy.nodes().unwrap().len(), 2);
+/// assert!(CustomNumaPolicyString::try_from("0-15:foo").is_err());
+/// ```
+impl TryFrom<&str> for CustomNumaPolicyString {
+    type Error = CustomNumaPolicyStringParsingError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_custom_numa_policy() {
+        let policy = NumaPolicy::try_from(model::UciString::from("0-15,32-47:16-31,48-63"))
+            .expect("Failed to parse NumaPolicy");
+        let NumaPolicy::Custom(custom) = policy else {
+            panic!("Expected NumaPolicy::Custom, got {policy:?}");
+        };
+
+        assert_eq!(
+            custom.nodes().expect("Failed to parse NUMA nodes"),
+            vec![
+                model::CpuSet(vec![0..=15, 32..=47]),
+                model::CpuSet(vec![16..=31, 48..=63]),
+            ]
+        );
+
+        assert!(NumaPolicy::try_from(model::UciString::from("0-15:foo")).is_err());
+    }
+}
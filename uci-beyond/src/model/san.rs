@@ -0,0 +1,127 @@
+use shakmaty::{CastlingMode, Chess, Move, fen::Fen, san::SanPlus, uci::Uci};
+
+use crate::model::{FenString, MoveString, Position};
+
+/// The error of converting moves in UCI notation to [SAN], e.g. with [`Position::to_san`].
+///
+/// [SAN]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SanConversionError {
+    /// The FEN of the root position can't be set up, e.g. because it is malformed.
+    #[error("Invalid FEN of the root position: `{0}`")]
+    InvalidFen(String),
+    /// A move of the `position` command is illegal, i.e. the root position itself is invalid.
+    ///
+    /// The ply is the 0-based index of the move in the `position` command.
+    #[error("Illegal move `{mv}` at ply {ply} of the position")]
+    IllegalPositionMove { ply: usize, mv: MoveString },
+    /// A move is illegal, e.g. because the principal variation is stale for the root position.
+    ///
+    /// The ply is the 0-based index of the move in the converted moves.
+    #[error("Illegal move `{mv}` at ply {ply} from the root position")]
+    IllegalMove { ply: usize, mv: MoveString },
+}
+
+impl Position {
+    /// Converts the moves played one after another from this position to [SAN], e.g.
+    /// `e2e4 g1f3` to `e4 Nf3`, with disambiguation, captures, promotions, checks and mates.
+    ///
+    /// [SAN]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn to_san(&self, moves: &[MoveString]) -> Result<Vec<String>, SanConversionError> {
+        moves_to_san(self.to_board()?, moves)
+    }
+
+    pub(crate) fn to_board(&self) -> Result<Chess, SanConversionError> {
+        match self {
+            Position::StartPos => Ok(Chess::default()),
+            Position::Fen(FenString(fen)) => Fen::from_ascii(fen.as_bytes())
+                .ok()
+                .and_then(|parsed| parsed.into_position(CastlingMode::Standard).ok())
+                .ok_or_else(|| SanConversionError::InvalidFen(fen.clone())),
+        }
+    }
+}
+
+/// The legal move of the board in UCI notation, if any.
+pub(crate) fn to_move(board: &Chess, mv: &MoveString) -> Option<Move> {
+    Uci::from_ascii(mv.0.as_bytes()).ok()?.to_move(board).ok()
+}
+
+pub(crate) fn moves_to_san(
+    mut board: Chess,
+    moves: &[MoveString],
+) -> Result<Vec<String>, SanConversionError> {
+    moves
+        .iter()
+        .enumerate()
+        .map(|(ply, mv)| {
+            let m = to_move(&board, mv).ok_or_else(|| SanConversionError::IllegalMove {
+                ply,
+                mv: mv.clone(),
+            })?;
+            Ok(SanPlus::from_move_and_play_unchecked(&mut board, &m).to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(s: &str) -> Vec<MoveString> {
+        s.split_whitespace().map(MoveString::from).collect()
+    }
+
+    fn fen(s: &str) -> Position {
+        Position::Fen(FenString(s.to_string()))
+    }
+
+    #[test]
+    fn test_to_san_from_startpos() {
+        let pv = moves("e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5c6 d7c6 e1g1");
+        assert_eq!(
+            Position::StartPos.to_san(&pv).unwrap(),
+            ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6", "O-O"]
+        );
+
+        let fools_mate = moves("f2f3 e7e5 g2g4 d8h4");
+        assert_eq!(
+            Position::StartPos.to_san(&fools_mate).unwrap(),
+            ["f3", "e5", "g4", "Qh4#"]
+        );
+    }
+
+    #[test]
+    fn test_to_san_promotion_and_disambiguation() {
+        let root = fen("7k/P7/8/8/8/8/8/K7 w - - 0 1");
+        assert_eq!(
+            root.to_san(&moves("a7a8q h8h7 a8b7")).unwrap(),
+            ["a8=Q+", "Kh7", "Qb7+"]
+        );
+
+        let root = fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+        assert_eq!(root.to_san(&moves("f1d2")).unwrap(), ["Nfd2"]);
+    }
+
+    #[test]
+    fn test_to_san_errors() {
+        assert_eq!(
+            Position::StartPos.to_san(&moves("e2e4 e2e4")),
+            Err(SanConversionError::IllegalMove {
+                ply: 1,
+                mv: MoveString::from("e2e4")
+            })
+        );
+        assert_eq!(
+            Position::StartPos.to_san(&moves("e2e4 nonsense")),
+            Err(SanConversionError::IllegalMove {
+                ply: 1,
+                mv: MoveString::from("nonsense")
+            })
+        );
+        assert_eq!(
+            fen("not a fen").to_san(&[]),
+            Err(SanConversionError::InvalidFen("not a fen".to_string()))
+        );
+    }
+}
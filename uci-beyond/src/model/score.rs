@@ -0,0 +1,213 @@
+use std::fmt::Display;
+
+use crate::{command, model::Color};
+
+#[derive(thiserror::Error, Debug, Clone)]
+#[non_exhaustive]
+pub enum ScoreParsingError {
+    #[error("Unknown score kind: `{0}`. Expected `cp` or `mate`.")]
+    UnknownScoreKind(String),
+    #[error("Invalid score value: `{0}`.")]
+    InvalidValue(String),
+}
+
+/// The score from the engine's point of view, as reported in the `score` field of the `info` command.
+///
+/// ```text
+/// info depth 5 seldepth 7 multipv 1 score cp 58 nodes 609 nps 87000 hashfull 0 tbhits 0 time 7 pv e2e4
+/// ```
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Score {
+    /// The score from the engine's point of view in centipawns.
+    Cp(i32),
+    /// Mate in y moves, not plies. If the engine is getting mated, negative values are used.
+    Mate(i32),
+}
+
+impl Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Score::Cp(cp) => write!(f, "cp {cp}"),
+            Score::Mate(moves) => write!(f, "mate {moves}"),
+        }
+    }
+}
+
+impl Score {
+    pub fn parse(s: &str) -> Result<(Self, &str), command::parsing::Error<ScoreParsingError>> {
+        debug_assert_eq!(s, s.trim_start());
+
+        let kind_str = s
+            .split_whitespace()
+            .next()
+            .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?;
+
+        let s = s[kind_str.len()..].trim_start();
+
+        let value_str = s
+            .split_whitespace()
+            .next()
+            .ok_or(command::parsing::Error::UnexpectedEndOfTokens)?;
+
+        let s = s[value_str.len()..].trim_start();
+
+        let value: i32 = value_str.parse().map_err(|_| {
+            command::parsing::Error::CustomError(ScoreParsingError::InvalidValue(
+                value_str.to_string(),
+            ))
+        })?;
+
+        let score = match kind_str {
+            "cp" => Score::Cp(value),
+            "mate" => Score::Mate(value),
+            _ => {
+                return Err(command::parsing::Error::CustomError(
+                    ScoreParsingError::UnknownScoreKind(kind_str.to_string()),
+                ));
+            }
+        };
+
+        Ok((score, s))
+    }
+
+    /// The number of plies until the mate, positive if the engine mates and negative if it gets mated,
+    /// or `None` for a centipawn score.
+    ///
+    /// The engine moves first, so `mate 3` is 5 plies away (the third move of the engine),
+    /// while `mate -3` is 6 plies away (the third move of the opponent). `mate 0` is 0 plies away.
+    pub fn mate_in_plies(&self) -> Option<i32> {
+        match *self {
+            Score::Cp(_) => None,
+            Score::Mate(moves) if moves > 0 => Some(moves.saturating_mul(2) - 1),
+            Score::Mate(moves) => Some(moves.saturating_mul(2)),
+        }
+    }
+
+    /// Whether the engine, i.e. the side to move, is getting mated.
+    ///
+    /// This includes `mate 0`, which Stockfish reports when the side to move is already checkmated.
+    pub fn is_engine_getting_mated(&self) -> bool {
+        matches!(*self, Score::Mate(moves) if moves <= 0)
+    }
+
+    /// Whether the score is a mate in at most `moves` moves for either side, i.e. the condition
+    /// on which the search of [`GoCommand::mate`](crate::gui_commands::GoCommand::mate) stops.
+    pub fn is_mate_within(&self, moves: u32) -> bool {
+        matches!(*self, Score::Mate(mate) if mate.unsigned_abs() <= moves)
+    }
+
+    /// The same score from the point of view of the opponent.
+    ///
+    /// `mate 0` has no sign and remains `mate 0`, see [`Score::to_white_pov`].
+    pub fn flipped(self) -> Self {
+        match self {
+            Score::Cp(cp) => Score::Cp(cp.saturating_neg()),
+            Score::Mate(moves) => Score::Mate(moves.saturating_neg()),
+        }
+    }
+
+    /// Converts the score from the point of view of the engine, i.e. of the side to move in the searched position,
+    /// to the point of view of White, e.g. for graphing the evaluation over a game.
+    ///
+    /// Converting a White-POV score with the same side to move returns the engine-POV score.
+    ///
+    /// `mate 0` can't tell the sides apart, so it must be interpreted with [`Score::is_engine_getting_mated`]
+    /// before the conversion: it means that `side_to_move` is checkmated.
+    pub fn to_white_pov(self, side_to_move: Color) -> Self {
+        match side_to_move {
+            Color::White => self,
+            Color::Black => self.flipped(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_score() {
+        let (score, rest) = Score::parse("cp 17 nodes 20").expect("Failed to parse Score");
+        assert_eq!(score, Score::Cp(17));
+        assert_eq!(rest, "nodes 20");
+
+        let (score, rest) = Score::parse("mate -3").expect("Failed to parse Score");
+        assert_eq!(score, Score::Mate(-3));
+        assert_eq!(rest, "");
+
+        assert!(Score::parse("wdl 17").is_err());
+    }
+
+    #[test]
+    fn test_mate_in_plies() {
+        let table = [
+            (Score::Mate(1), Some(1)),
+            (Score::Mate(3), Some(5)),
+            (Score::Mate(-1), Some(-2)),
+            (Score::Mate(-3), Some(-6)),
+            (Score::Mate(0), Some(0)),
+            (Score::Cp(300), None),
+        ];
+        for (score, plies) in table {
+            assert_eq!(score.mate_in_plies(), plies, "{score}");
+        }
+    }
+
+    #[test]
+    fn test_mate_directions() {
+        let table = [
+            (Score::Mate(2), false),
+            (Score::Mate(-2), true),
+            (Score::Mate(0), true),
+            (Score::Cp(-500), false),
+        ];
+        for (score, getting_mated) in table {
+            assert_eq!(score.is_engine_getting_mated(), getting_mated, "{score}");
+        }
+
+        assert!(Score::Mate(3).is_mate_within(3));
+        assert!(Score::Mate(-3).is_mate_within(3));
+        assert!(!Score::Mate(4).is_mate_within(3));
+        assert!(!Score::Mate(-4).is_mate_within(3));
+        assert!(!Score::Cp(1).is_mate_within(3));
+    }
+
+    #[test]
+    fn test_to_white_pov() {
+        // (engine POV, side to move, White POV)
+        let table = [
+            (Score::Cp(35), Color::White, Score::Cp(35)),
+            (Score::Cp(35), Color::Black, Score::Cp(-35)),
+            (Score::Cp(-120), Color::White, Score::Cp(-120)),
+            (Score::Cp(-120), Color::Black, Score::Cp(120)),
+            // White to move mates
+            (Score::Mate(2), Color::White, Score::Mate(2)),
+            // Black to move mates
+            (Score::Mate(2), Color::Black, Score::Mate(-2)),
+            // White to move gets mated
+            (Score::Mate(-2), Color::White, Score::Mate(-2)),
+            // Black to move gets mated
+            (Score::Mate(-2), Color::Black, Score::Mate(2)),
+            (Score::Mate(0), Color::Black, Score::Mate(0)),
+            (Score::Cp(i32::MIN), Color::Black, Score::Cp(i32::MAX)),
+        ];
+        for (engine, side_to_move, white) in table {
+            assert_eq!(
+                engine.to_white_pov(side_to_move),
+                white,
+                "{engine} {side_to_move}"
+            );
+            // The conversion is its own inverse
+            if engine != Score::Cp(i32::MIN) {
+                assert_eq!(white.to_white_pov(side_to_move), engine);
+            }
+        }
+    }
+
+    #[test]
+    fn test_display_score() {
+        assert_eq!(Score::Cp(-25).to_string(), "cp -25");
+        assert_eq!(Score::Mate(2).to_string(), "mate 2");
+    }
+}
@@ -0,0 +1,255 @@
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SyzygyPathSpecParsingError {
+    #[error("Empty directory in `{0}`.")]
+    EmptyDirectory(String),
+    #[error("Whitespace around a separator in `{0}`.")]
+    WhitespaceAroundSeparator(String),
+}
+
+/// The value of the `SyzygyPath` option (see [`options::UciOption::SyzygyPath`](crate::options::UciOption::SyzygyPath)),
+/// i.e. the directories storing the Syzygy tablebase files, separated by `;` on Windows and by `:` elsewhere.
+///
+/// No directories means `<empty>`, i.e. tablebases are disabled.
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Default)]
+pub struct SyzygyPathSpec {
+    directories: Vec<PathBuf>,
+}
+
+impl SyzygyPathSpec {
+    /// The separator of the directories on the current platform.
+    pub const SEPARATOR: char = if cfg!(windows) { ';' } else { ':' };
+
+    pub fn new<I, P>(directories: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Self {
+            directories: directories.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Parses the directories separated by the given separator rather than by [`Self::SEPARATOR`].
+    pub fn parse_with_separator(
+        s: &str,
+        separator: char,
+    ) -> Result<Self, SyzygyPathSpecParsingError> {
+        if s.is_empty() || s == "<empty>" {
+            return Ok(Self::default());
+        }
+
+        let directories = s
+            .split(separator)
+            .map(|dir| {
+                if dir.is_empty() {
+                    Err(SyzygyPathSpecParsingError::EmptyDirectory(s.to_string()))
+                } else if dir.trim() != dir {
+                    Err(SyzygyPathSpecParsingError::WhitespaceAroundSeparator(
+                        s.to_string(),
+                    ))
+                } else {
+                    Ok(PathBuf::from(dir))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { directories })
+    }
+
+    pub fn directories(&self) -> &[PathBuf] {
+        &self.directories
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.directories.is_empty()
+    }
+}
+
+impl FromStr for SyzygyPathSpec {
+    type Err = SyzygyPathSpecParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_separator(s, Self::SEPARATOR)
+    }
+}
+
+impl Display for SyzygyPathSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.directories.is_empty() {
+            return write!(f, "<empty>");
+        }
+
+        for (i, dir) in self.directories.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", Self::SEPARATOR)?;
+            }
+            write!(f, "{}", dir.display())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "fs")]
+mod validation {
+    use std::path::{Path, PathBuf};
+
+    use super::SyzygyPathSpec;
+
+    #[derive(Debug, thiserror::Error)]
+    pub enum SyzygyDirectoryError {
+        #[error("The directory doesn't exist.")]
+        NotFound,
+        #[error("The path is not a directory.")]
+        NotADirectory,
+        #[error("The directory doesn't contain .rtbw files.")]
+        NoRtbwFiles,
+        #[error("I/O error: {0}")]
+        Io(#[from] std::io::Error),
+    }
+
+    /// The result of the validation of a directory of [`SyzygyPathSpec`].
+    #[derive(Debug)]
+    pub struct SyzygyDirectoryFinding {
+        pub directory: PathBuf,
+        /// The number of .rtbw (WDL) files in the directory.
+        pub rtbw_files: Result<usize, SyzygyDirectoryError>,
+    }
+
+    impl SyzygyDirectoryFinding {
+        pub fn is_ok(&self) -> bool {
+            self.rtbw_files.is_ok()
+        }
+    }
+
+    fn count_rtbw_files(dir: &Path) -> Result<usize, SyzygyDirectoryError> {
+        let metadata = match std::fs::metadata(dir) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(SyzygyDirectoryError::NotFound);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if !metadata.is_dir() {
+            return Err(SyzygyDirectoryError::NotADirectory);
+        }
+
+        let mut count = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "rtbw") {
+                count += 1;
+            }
+        }
+
+        match count {
+            0 => Err(SyzygyDirectoryError::NoRtbwFiles),
+            count => Ok(count),
+        }
+    }
+
+    impl SyzygyPathSpec {
+        /// Checks that each directory exists and contains at least one .rtbw file, since otherwise
+        /// the engine silently ignores it.
+        pub fn validate(&self) -> Vec<SyzygyDirectoryFinding> {
+            self.directories
+                .iter()
+                .map(|dir| SyzygyDirectoryFinding {
+                    directory: dir.clone(),
+                    rtbw_files: count_rtbw_files(dir),
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+pub use validation::{SyzygyDirectoryError, SyzygyDirectoryFinding};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_syzygy_path_spec() {
+        let spec = SyzygyPathSpec::parse_with_separator(r"C:\tb\wdl345;D:\tb\dtz345", ';')
+            .expect("Failed to parse SyzygyPathSpec");
+        assert_eq!(
+            spec.directories(),
+            [
+                PathBuf::from(r"C:\tb\wdl345"),
+                PathBuf::from(r"D:\tb\dtz345")
+            ]
+        );
+
+        let spec = SyzygyPathSpec::parse_with_separator("/tb/wdl345:/tb/dtz345", ':')
+            .expect("Failed to parse SyzygyPathSpec");
+        assert_eq!(spec.directories().len(), 2);
+
+        let spec: SyzygyPathSpec = "<empty>".parse().expect("Failed to parse SyzygyPathSpec");
+        assert!(spec.is_empty());
+        assert_eq!(spec.to_string(), "<empty>");
+
+        assert_eq!(
+            SyzygyPathSpec::parse_with_separator("/tb/wdl345::/tb/dtz345", ':'),
+            Err(SyzygyPathSpecParsingError::EmptyDirectory(
+                "/tb/wdl345::/tb/dtz345".to_string()
+            ))
+        );
+        assert_eq!(
+            SyzygyPathSpec::parse_with_separator("/tb/wdl345 : /tb/dtz345", ':'),
+            Err(SyzygyPathSpecParsingError::WhitespaceAroundSeparator(
+                "/tb/wdl345 : /tb/dtz345".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_display_syzygy_path_spec() {
+        let spec = SyzygyPathSpec::new(["tb1", "tb2"]);
+        let expected = format!("tb1{}tb2", SyzygyPathSpec::SEPARATOR);
+        assert_eq!(spec.to_string(), expected);
+        assert_eq!(expected.parse::<SyzygyPathSpec>().unwrap(), spec);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_validate_syzygy_path_spec() {
+        let with_tb = tempfile::tempdir().unwrap();
+        std::fs::write(with_tb.path().join("KQvK.rtbw"), b"").unwrap();
+        std::fs::write(with_tb.path().join("KQvK.rtbz"), b"").unwrap();
+        std::fs::write(with_tb.path().join("KRvK.rtbw"), b"").unwrap();
+
+        let without_tb = tempfile::tempdir().unwrap();
+        std::fs::write(without_tb.path().join("KQvK.rtbz"), b"").unwrap();
+
+        let missing = with_tb.path().join("missing");
+        let file = with_tb.path().join("KQvK.rtbw");
+
+        let spec = SyzygyPathSpec::new([
+            with_tb.path().to_path_buf(),
+            without_tb.path().to_path_buf(),
+            missing,
+            file,
+        ]);
+        let findings = spec.validate();
+
+        assert_eq!(findings.len(), 4);
+        assert_eq!(findings[0].directory, with_tb.path());
+        assert!(matches!(findings[0].rtbw_files, Ok(2)));
+        assert!(matches!(
+            findings[1].rtbw_files,
+            Err(SyzygyDirectoryError::NoRtbwFiles)
+        ));
+        assert!(matches!(
+            findings[2].rtbw_files,
+            Err(SyzygyDirectoryError::NotFound)
+        ));
+        assert!(matches!(
+            findings[3].rtbw_files,
+            Err(SyzygyDirectoryError::NotADirectory)
+        ));
+    }
+}
@@ -1,10 +1,18 @@
-use std::{convert::Infallible, fmt::Display};
+use std::{borrow::Cow, convert::Infallible, fmt::Display};
 
 use crate::command;
 
+/// The token that stands for the empty string, e.g. in `option name Debug Log File type string default <empty>`.
+pub const EMPTY_SENTINEL: &str = "<empty>";
+
 /// String representing a UCI option value.
 ///
-/// When empty, it represents the absence of a value with `<empty>`.
+/// When empty, it represents the absence of a value with [`EMPTY_SENTINEL`], i.e. `<empty>`.
+/// A value that is literally `<empty>` is escaped with a backslash as `\<empty>`, and
+/// every other value consisting of backslashes followed by `<empty>` gets one more backslash,
+/// so that any value round-trips (see [`UciString::to_token`] and [`UciString::from_token`]).
+///
+/// Whitespace around the value isn't representable, since the value is delimited by whitespace.
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
 pub struct UciString(pub String);
 
@@ -12,15 +20,52 @@ impl UciString {
     pub fn parse(s: &str) -> Result<(Self, &str), command::parsing::Error<Infallible>> {
         debug_assert_eq!(s, s.trim_start());
 
-        let value = s.split_whitespace().next().unwrap_or("<empty>");
+        let (token, s) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+
+        Ok((UciString::from_token(token), s.trim_start()))
+    }
 
-        let s = s.trim_start_matches(value).trim_start();
+    /// Decodes the value as written by [`UciString::to_token`].
+    pub fn from_token(token: &str) -> Self {
+        if token == EMPTY_SENTINEL {
+            UciString(String::new())
+        } else if is_escaped_sentinel(token) {
+            // Drop the escaping backslash
+            UciString(token[1..].to_string())
+        } else {
+            UciString(token.to_string())
+        }
+    }
 
-        if value == "<empty>" {
-            return Ok((UciString(String::new()), s));
+    /// Encodes the value for the engine, e.g. `<empty>` for the empty string and `\<empty>` for `<empty>`.
+    pub fn to_token(&self) -> Cow<'_, str> {
+        if self.0.is_empty() {
+            Cow::Borrowed(EMPTY_SENTINEL)
+        } else if self.0 == EMPTY_SENTINEL || is_escaped_sentinel(&self.0) {
+            Cow::Owned(format!("\\{}", self.0))
+        } else {
+            Cow::Borrowed(&self.0)
         }
+    }
+}
+
+/// Whether the string is `<empty>` preceded by at least one backslash.
+fn is_escaped_sentinel(s: &str) -> bool {
+    s.starts_with('\\') && s.trim_start_matches('\\') == EMPTY_SENTINEL
+}
 
-        Ok((UciString(value.to_string()), s))
+/// The value as it is, rather than a token of the protocol (see [`UciString::from_token`]),
+/// so `<empty>` is the literal value.
+///
+/// ```
+/// use uci_beyond::model::UciString;
+///
+/// assert_eq!(UciString::from("book.bin"), UciString("book.bin".to_string()));
+/// assert_eq!(UciString::from("").to_token(), "<empty>");
+/// ```
+impl From<&str> for UciString {
+    fn from(value: &str) -> Self {
+        UciString(value.to_string())
     }
 }
 
@@ -32,11 +77,7 @@ impl Default for UciString {
 
 impl Display for UciString {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.0.is_empty() {
-            write!(f, "<empty>")
-        } else {
-            write!(f, "{}", self.0)
-        }
+        write!(f, "{}", self.to_token())
     }
 }
 
@@ -56,4 +97,28 @@ mod tests {
         assert_eq!(uci_string.0, "");
         assert_eq!(rest, "something");
     }
+
+    #[test]
+    fn test_uci_string_round_trip() {
+        for (value, token) in [
+            ("", "<empty>"),
+            ("<empty>", "\\<empty>"),
+            ("\\<empty>", "\\\\<empty>"),
+            ("\\", "\\"),
+            ("<empty>s", "<empty>s"),
+            ("nn-1c0000000000.nnue", "nn-1c0000000000.nnue"),
+        ] {
+            let uci_string = UciString(value.to_string());
+            assert_eq!(uci_string.to_string(), token);
+
+            let (parsed, rest) = UciString::parse(token).unwrap();
+            assert_eq!(parsed, uci_string, "{token}");
+            assert_eq!(rest, "");
+        }
+
+        // Whitespace around the value isn't representable: it is written as is and lost when parsing
+        let written = UciString::from(" a ").to_string();
+        let (parsed, _) = UciString::parse(written.trim_start()).unwrap();
+        assert_eq!(parsed.0, "a");
+    }
 }
@@ -0,0 +1,111 @@
+use std::fmt::Display;
+
+use crate::model::{Color, GamePly};
+
+/// The win, draw and loss probabilities in permille from the point of view of the side to move,
+/// as reported with `info ... wdl 958 41 1` when `UCI_ShowWDL` is enabled.
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+pub struct Wdl {
+    pub win: u32,
+    pub draw: u32,
+    pub loss: u32,
+}
+
+/// The internal evaluation units of Stockfish that correspond to 100 centipawns.
+const NORMALIZE_TO_PAWN_VALUE: f64 = 328.0;
+
+impl Wdl {
+    /// Estimates the probabilities with the WDL model that Stockfish 16 publishes with `UCI_ShowWDL`,
+    /// given the evaluation in centipawns (as in `score cp`) and the game ply.
+    ///
+    /// The model is fitted to self-play games, so that 100 centipawns mean a 50% chance to win at ply 64.
+    /// Later versions of Stockfish fit the model to the material instead of the ply, so their reported
+    /// probabilities may differ slightly.
+    pub fn from_cp_and_ply(cp: i32, ply: GamePly) -> Self {
+        let value = f64::from(cp) * NORMALIZE_TO_PAWN_VALUE / 100.0;
+        let win = win_rate_model(value, ply);
+        let loss = win_rate_model(-value, ply);
+        Wdl {
+            win,
+            draw: 1000u32.saturating_sub(win + loss),
+            loss,
+        }
+    }
+
+    /// The same probabilities from the point of view of the opponent, i.e. with the win and the loss swapped.
+    pub fn flipped(self) -> Self {
+        Wdl {
+            win: self.loss,
+            draw: self.draw,
+            loss: self.win,
+        }
+    }
+
+    /// Converts the probabilities from the point of view of the side to move to the point of view of White,
+    /// like [`Score::to_white_pov`](crate::model::Score::to_white_pov).
+    pub fn to_white_pov(self, side_to_move: Color) -> Self {
+        match side_to_move {
+            Color::White => self,
+            Color::Black => self.flipped(),
+        }
+    }
+}
+
+/// The win rate in permille for the evaluation in the internal units of Stockfish.
+fn win_rate_model(value: f64, ply: GamePly) -> u32 {
+    const AS: [f64; 4] = [0.38036525, -2.82015070, 23.17882135, 307.36768407];
+    const BS: [f64; 4] = [-2.29434733, 13.27689788, -14.26828904, 63.45318330];
+
+    // The model only captures up to 240 plies, so the input is limited and then rescaled
+    let m = f64::from(ply.0.min(240)) / 64.0;
+    let a = ((AS[0] * m + AS[1]) * m + AS[2]) * m + AS[3];
+    let b = ((BS[0] * m + BS[1]) * m + BS[2]) * m + BS[3];
+
+    let x = value.clamp(-4000.0, 4000.0);
+    (0.5 + 1000.0 / (1.0 + ((a - x) / b).exp())) as u32
+}
+
+impl Display for Wdl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.win, self.draw, self.loss)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wdl(win: u32, draw: u32, loss: u32) -> Wdl {
+        Wdl { win, draw, loss }
+    }
+
+    #[test]
+    fn test_wdl_to_white_pov() {
+        let engine = wdl(958, 41, 1);
+        assert_eq!(engine.to_white_pov(Color::White), engine);
+        assert_eq!(engine.to_white_pov(Color::Black), wdl(1, 41, 958));
+        assert_eq!(engine.flipped().flipped(), engine);
+    }
+
+    #[test]
+    fn test_wdl_model_reference_points() {
+        // A pawn of advantage is a 50% chance to win at ply 64
+        assert_eq!(Wdl::from_cp_and_ply(100, GamePly(64)), wdl(500, 500, 0));
+        assert_eq!(Wdl::from_cp_and_ply(0, GamePly(64)), wdl(4, 992, 4));
+        assert_eq!(Wdl::from_cp_and_ply(0, GamePly(0)), wdl(8, 984, 8));
+        assert_eq!(Wdl::from_cp_and_ply(50, GamePly(20)), wdl(76, 924, 0));
+        assert_eq!(Wdl::from_cp_and_ply(-200, GamePly(120)), wdl(0, 10, 990));
+        assert_eq!(Wdl::from_cp_and_ply(300, GamePly(80)), wdl(1000, 0, 0));
+
+        // The plies beyond 240 are treated as 240
+        assert_eq!(
+            Wdl::from_cp_and_ply(100, GamePly(300)),
+            Wdl::from_cp_and_ply(100, GamePly(240))
+        );
+        assert_eq!(Wdl::from_cp_and_ply(100, GamePly(240)), wdl(350, 650, 0));
+        assert_eq!(
+            Wdl::from_cp_and_ply(100, GamePly(64)).to_string(),
+            "500 500 0"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt::Display;
+
+use crate::{command, model};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ComboParsingError {
+    /// The `default` token is missing before the default of the combo.
+    #[error("missing 'default'")]
+    MissingDefault,
+    /// A `default` or a `var` without a value, e.g. `default Normal var var Risky`.
+    #[error("missing value after '{0}'")]
+    MissingValue(&'static str),
+}
+
+/// A combo box as advertised by the engine, e.g.
+///
+/// ```text
+/// option name Style type combo default Normal var Solid var Normal var Risky
+/// ```
+///
+/// The vars are kept in the order of the engine. A valid combo has its default among the vars,
+/// see [`UciOption::from_parts`](crate::options::UciOption::from_parts).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Combo {
+    pub default: model::UciString,
+    pub vars: Vec<model::UciString>,
+}
+
+impl Combo {
+    /// Whether the value is one of the vars. As the names of options, the vars are case-insensitive.
+    pub fn var(&self, value: &str) -> Option<&model::UciString> {
+        self.vars
+            .iter()
+            .find(|var| var.0.eq_ignore_ascii_case(value))
+    }
+
+    /// Parses `default <value> var <value> ...` up to the end of the line. A value extends to the next `var`
+    /// token, so it may contain spaces, e.g. `default Very Solid var Normal var Very Solid`.
+    pub fn parse(s: &str) -> Result<(Self, &str), command::parsing::Error<ComboParsingError>> {
+        debug_assert_eq!(s, s.trim_start());
+
+        let mut tokens = s.split_whitespace();
+        if tokens.next() != Some("default") {
+            return Err(command::parsing::Error::CustomError(
+                ComboParsingError::MissingDefault,
+            ));
+        }
+
+        let mut values = Vec::new();
+        let mut value = Vec::new();
+        let mut keyword = "default";
+        for token in tokens {
+            if token == "var" {
+                values.push(Self::value(keyword, &value)?);
+                value.clear();
+                keyword = "var";
+            } else {
+                value.push(token);
+            }
+        }
+        values.push(Self::value(keyword, &value)?);
+
+        let default = values.remove(0);
+        Ok((
+            Combo {
+                default,
+                vars: values,
+            },
+            "",
+        ))
+    }
+
+    fn value(
+        keyword: &'static str,
+        tokens: &[&str],
+    ) -> Result<model::UciString, command::parsing::Error<ComboParsingError>> {
+        if tokens.is_empty() {
+            return Err(command::parsing::Error::CustomError(
+                ComboParsingError::MissingValue(keyword),
+            ));
+        }
+        Ok(model::UciString::from_token(&tokens.join(" ")))
+    }
+}
+
+impl Display for Combo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "default {}", self.default)?;
+        for var in &self.vars {
+            write!(f, " var {var}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_combo() {
+        let (combo, rest) = Combo::parse("default Normal var Solid var Normal var Risky")
+            .expect("Failed to parse Combo");
+        assert_eq!(combo.default, model::UciString::from("Normal"));
+        assert_eq!(
+            combo.vars,
+            [
+                model::UciString::from("Solid"),
+                model::UciString::from("Normal"),
+                model::UciString::from("Risky"),
+            ]
+        );
+        assert_eq!(rest, "");
+        assert_eq!(
+            combo.to_string(),
+            "default Normal var Solid var Normal var Risky"
+        );
+        assert_eq!(combo.var("risky"), Some(&model::UciString::from("Risky")));
+        assert_eq!(combo.var("Wild"), None);
+
+        let (combo, _) = Combo::parse("default Very Solid var Normal var Very Solid")
+            .expect("Failed to parse Combo");
+        assert_eq!(combo.default, model::UciString::from("Very Solid"));
+        assert_eq!(combo.vars[1], model::UciString::from("Very Solid"));
+        assert_eq!(
+            combo.to_string(),
+            "default Very Solid var Normal var Very Solid"
+        );
+    }
+
+    #[test]
+    fn test_parse_combo_errors() {
+        assert!(matches!(
+            Combo::parse("var Solid var Normal"),
+            Err(command::parsing::Error::CustomError(
+                ComboParsingError::MissingDefault
+            ))
+        ));
+        assert!(matches!(
+            Combo::parse("default var Solid"),
+            Err(command::parsing::Error::CustomError(
+                ComboParsingError::MissingValue("default")
+            ))
+        ));
+        assert!(matches!(
+            Combo::parse("default Normal var var Normal"),
+            Err(command::parsing::Error::CustomError(
+                ComboParsingError::MissingValue("var")
+            ))
+        ));
+    }
+}
@@ -0,0 +1,108 @@
+use crate::options::TypedUciOptionData;
+
+/// The custom options of a [`UciOptionBlock`](crate::options::UciOptionBlock) by name, in the order
+/// the engine declared them.
+///
+/// Inserting an option with the name of another one replaces its data in place.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CustomOptions {
+    entries: Vec<(String, TypedUciOptionData)>,
+}
+
+impl CustomOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds the option, or replaces the data of the option with the same name, returning the old data.
+    pub fn insert(
+        &mut self,
+        name: String,
+        typed_data: TypedUciOptionData,
+    ) -> Option<TypedUciOptionData> {
+        match self.entries.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, data)) => Some(std::mem::replace(data, typed_data)),
+            None => {
+                self.entries.push((name, typed_data));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TypedUciOptionData> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, data)| data)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// The options in the order of declaration.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&String, &TypedUciOptionData)> {
+        self.entries.iter().map(|(name, data)| (name, data))
+    }
+}
+
+impl Extend<(String, TypedUciOptionData)> for CustomOptions {
+    fn extend<I: IntoIterator<Item = (String, TypedUciOptionData)>>(&mut self, iter: I) {
+        for (name, typed_data) in iter {
+            self.insert(name, typed_data);
+        }
+    }
+}
+
+impl FromIterator<(String, TypedUciOptionData)> for CustomOptions {
+    fn from_iter<I: IntoIterator<Item = (String, TypedUciOptionData)>>(iter: I) -> Self {
+        let mut options = CustomOptions::new();
+        options.extend(iter);
+        options
+    }
+}
+
+impl IntoIterator for CustomOptions {
+    type Item = (String, TypedUciOptionData);
+    type IntoIter = std::vec::IntoIter<(String, TypedUciOptionData)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model;
+
+    #[test]
+    fn test_declaration_order_and_replacement() {
+        let check = |value| TypedUciOptionData::Check(model::Check(value));
+        let mut options = CustomOptions::new();
+        assert_eq!(options.insert("Zeta".to_string(), check(false)), None);
+        assert_eq!(options.insert("Alpha".to_string(), check(false)), None);
+        assert_eq!(
+            options.insert("Zeta".to_string(), check(true)),
+            Some(check(false))
+        );
+
+        assert_eq!(
+            options
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            ["Zeta", "Alpha"]
+        );
+        assert_eq!(options.get("Zeta"), Some(&check(true)));
+        assert!(!options.contains_key("zeta"));
+    }
+}
@@ -2,56 +2,79 @@
 //!
 //! [UCI]: https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html
 
-use std::{collections::HashMap, fmt::Display};
+use std::fmt::Display;
 
 use optional_struct::optional_struct;
 use variants_data_struct::VariantsDataStruct;
 
 use crate::model;
 
+mod combo;
+mod custom_options;
+mod option_state;
+pub mod ordering;
+mod profile;
 mod spin;
+mod stockfish_defaults;
 pub mod typed_uci_option_data;
 mod uci_option_basic_info;
 mod uci_option_kind;
 
-pub use spin::Spin;
+pub use combo::{Combo, ComboParsingError};
+pub use custom_options::CustomOptions;
+pub use option_state::{OptionSnapshot, OptionState, OptionStateError, OptionValue};
+pub use profile::{Profile, ProfileEntry, ProfileWarning};
+pub use spin::{
+    PartialSpin, Spin, SpinBuilder, SpinConversionError, SpinField, SpinFieldKind,
+    SpinFieldParsingError, SpinParsingError, UnknownSpinFieldKind,
+};
 pub use typed_uci_option_data::{TypedUciOptionData, UciOptionType, UnknownUciOptionType};
 pub use uci_option_basic_info::UciOptionBasicInfo;
-pub use uci_option_kind::UciOptionKind;
+pub use uci_option_kind::{UciOptionKind, UnknownUciOptionKind};
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("'{}' must be of type '{}', found '{found}'", option_kind.name(), option_kind.r#type())]
 pub struct UciOptionDataTypeMismatchError {
     pub option_kind: UciOptionKind,
     pub found: UciOptionType,
 }
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
 pub enum UciOptionFromPartsError {
+    #[error("{0}")]
     UciOptionDataTypeMismatchError(UciOptionDataTypeMismatchError),
+    #[error("{0}")]
     NumaPolicyParsingError(model::NumaPolicyParsingError),
+    /// The default of the spin is not within `[min, max]`.
+    #[error("the default of the spin '{0}' is out of its bounds")]
+    InvalidSpinBounds(Spin),
+    /// A standard spin option lacks `min` or `max` or doesn't fit into a [`Spin`].
+    #[error("{0}")]
+    SpinConversionError(SpinConversionError),
+    /// The default of the combo is not one of its vars.
+    #[error("the default '{0}' is not one of the vars of the combo")]
+    InvalidComboDefault(String),
 }
 
 /// The enumeration of known UCI options.
+///
+/// New options are added as the engines add them, e.g. with every Stockfish release,
+/// so the enum is non-exhaustive.
 
-#[derive(
-    VariantsDataStruct,
-    // Kinded,
-    PartialEq,
-    Eq,
-    Debug,
-    Clone,
-)]
+#[derive(VariantsDataStruct, PartialEq, Eq, Debug, Clone)]
 #[variants_data_struct(
     name=UciOptionBlock,
     attrs(
         #[optional_struct(UciOptionBlockBuilder)]
-        #[derive(Debug)]
+        #[derive(Debug, PartialEq, Eq, Clone)]
     ),
     variants_tys_attrs(
         #[derive(Debug, PartialEq, Eq, Clone)]
     )
 )]
-// #[kinded(kind = UciOptionKind, opt_outs=[from_str_impl], derive(EnumIter))]
+// `UciOptionKind` isn't derived with `Kinded` because it has no `Custom` kind,
+// see `standard_uci_options!` instead
+#[non_exhaustive]
 pub enum UciOption {
     /// The number of CPU threads used for searching a position. For best performance, set this equal to the number of CPU cores available.
     #[variants_data_struct_field(field_ty_override = Spin)]
@@ -130,7 +153,7 @@ pub enum UciOption {
     #[variants_data_struct_field(field_ty_override = model::UciString)]
     DebugLogFile { default: model::UciString },
     #[variants_data_struct_field(
-        field_ty_override = HashMap<String, TypedUciOptionData>,
+        field_ty_override = CustomOptions,
         field_attrs(#[optional_skip_wrap])
     )]
     Custom {
@@ -141,40 +164,46 @@ pub enum UciOption {
 
 pub enum UciOptionNameInfo {
     Standard(UciOptionKind),
-    Custom { name: String },
+    /// An option that is not a standard one, along with why it isn't.
+    Custom(UnknownUciOptionKind),
+}
+
+impl From<UciOptionKind> for UciOptionNameInfo {
+    fn from(kind: UciOptionKind) -> Self {
+        UciOptionNameInfo::Standard(kind)
+    }
+}
+
+impl From<&str> for UciOptionNameInfo {
+    /// Recognizes the names of the standard options, e.g. `Hash`.
+    fn from(name: &str) -> Self {
+        match name.parse::<UciOptionKind>() {
+            Ok(kind) => UciOptionNameInfo::Standard(kind),
+            Err(unknown) => UciOptionNameInfo::Custom(unknown),
+        }
+    }
+}
+
+impl From<String> for UciOptionNameInfo {
+    fn from(name: String) -> Self {
+        match name.parse::<UciOptionKind>() {
+            Ok(kind) => UciOptionNameInfo::Standard(kind),
+            Err(unknown) => UciOptionNameInfo::Custom(unknown),
+        }
+    }
 }
 
 impl UciOption {
     pub fn basic_info(&self) -> UciOptionBasicInfo<&str> {
-        let kind = match self {
-            UciOption::Custom { name, typed_data } => {
-                return UciOptionBasicInfo::Custom {
-                    name: &name,
-                    r#type: typed_data.r#type(),
-                };
-            }
-            UciOption::Threads { .. } => UciOptionKind::Threads,
-            UciOption::Hash { .. } => UciOptionKind::Hash,
-            UciOption::MultiPV { .. } => UciOptionKind::MultiPV,
-            UciOption::NumaPolicy { .. } => UciOptionKind::NumaPolicy,
-            UciOption::ClearHash => UciOptionKind::ClearHash,
-            UciOption::Ponder { .. } => UciOptionKind::Ponder,
-            UciOption::EvalFile { .. } => UciOptionKind::EvalFile,
-            UciOption::EvalFileSmall { .. } => UciOptionKind::EvalFileSmall,
-            UciOption::UCIChess960 { .. } => UciOptionKind::UCIChess960,
-            UciOption::UCIShowWDL { .. } => UciOptionKind::UCIShowWDL,
-            UciOption::UCILimitStrength { .. } => UciOptionKind::UCILimitStrength,
-            UciOption::UCIElo { .. } => UciOptionKind::UCIElo,
-            UciOption::SkillLevel { .. } => UciOptionKind::SkillLevel,
-            UciOption::SyzygyPath { .. } => UciOptionKind::SyzygyPath,
-            UciOption::SyzygyProbeDepth { .. } => UciOptionKind::SyzygyProbeDepth,
-            UciOption::Syzygy50MoveRule { .. } => UciOptionKind::Syzygy50MoveRule,
-            UciOption::SyzygyProbeLimit { .. } => UciOptionKind::SyzygyProbeLimit,
-            UciOption::MoveOverhead { .. } => UciOptionKind::MoveOverhead,
-            UciOption::Nodestime { .. } => UciOptionKind::Nodestime,
-            UciOption::DebugLogFile { .. } => UciOptionKind::DebugLogFile,
-        };
-        UciOptionBasicInfo::Standard(kind)
+        match self {
+            UciOption::Custom { name, typed_data } => UciOptionBasicInfo::Custom {
+                name,
+                r#type: typed_data.r#type(),
+            },
+            _ => UciOptionBasicInfo::Standard(
+                self.kind().expect("Only the custom options have no kind"),
+            ),
+        }
     }
 
     pub fn name(&self) -> &str {
@@ -185,173 +214,56 @@ impl UciOption {
         self.basic_info().r#type()
     }
 
+    /// The declaration of the option as advertised by a custom option of its type, e.g.
+    /// a [`TypedUciOptionData::Spin`] for `Hash` or a [`TypedUciOptionData::String`] for `NumaPolicy`.
+    pub fn typed_data(&self) -> TypedUciOptionData {
+        match self {
+            UciOption::Threads(spin)
+            | UciOption::Hash(spin)
+            | UciOption::MultiPV(spin)
+            | UciOption::UCIElo(spin)
+            | UciOption::SkillLevel(spin)
+            | UciOption::SyzygyProbeDepth(spin)
+            | UciOption::SyzygyProbeLimit(spin)
+            | UciOption::MoveOverhead(spin)
+            | UciOption::Nodestime(spin) => TypedUciOptionData::Spin(spin.clone().into()),
+            UciOption::NumaPolicy { default } => {
+                TypedUciOptionData::String(model::UciString(default.to_string()))
+            }
+            UciOption::ClearHash => TypedUciOptionData::Button,
+            UciOption::Ponder { default }
+            | UciOption::UCIChess960 { default }
+            | UciOption::UCIShowWDL { default }
+            | UciOption::UCILimitStrength { default }
+            | UciOption::Syzygy50MoveRule { default } => TypedUciOptionData::Check(default.clone()),
+            UciOption::EvalFile { default }
+            | UciOption::EvalFileSmall { default }
+            | UciOption::SyzygyPath { default }
+            | UciOption::DebugLogFile { default } => TypedUciOptionData::String(default.clone()),
+            UciOption::Custom { typed_data, .. } => typed_data.clone(),
+        }
+    }
+
+    /// Fails if the data doesn't fit the standard option of the name, or if the default of a combo
+    /// is not one of its vars.
     pub fn from_parts(
         name_info: UciOptionNameInfo,
         typed_data: TypedUciOptionData,
     ) -> Result<Self, UciOptionFromPartsError> {
+        if let TypedUciOptionData::Combo(combo) = &typed_data
+            && !combo.vars.contains(&combo.default)
+        {
+            return Err(UciOptionFromPartsError::InvalidComboDefault(
+                combo.default.0.clone(),
+            ));
+        }
         let kind = match name_info {
-            UciOptionNameInfo::Custom { name } => {
+            UciOptionNameInfo::Custom(UnknownUciOptionKind { name, .. }) => {
                 return Ok(UciOption::Custom { name, typed_data });
             }
             UciOptionNameInfo::Standard(kind) => kind,
         };
-        if kind.r#type() != typed_data.r#type() {
-            return Err(UciOptionFromPartsError::UciOptionDataTypeMismatchError(
-                UciOptionDataTypeMismatchError {
-                    option_kind: kind,
-                    found: typed_data.r#type(),
-                },
-            ));
-        };
-
-        match kind {
-            UciOptionKind::Threads => {
-                if let TypedUciOptionData::Spin(spin) = typed_data {
-                    Ok(UciOption::Threads(spin))
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::Hash => {
-                if let TypedUciOptionData::Spin(spin) = typed_data {
-                    Ok(UciOption::Hash(spin))
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::MultiPV => {
-                if let TypedUciOptionData::Spin(spin) = typed_data {
-                    Ok(UciOption::MultiPV(spin))
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::NumaPolicy => {
-                if let TypedUciOptionData::String(uci_string) = typed_data {
-                    let numa_policy = model::NumaPolicy::try_from(uci_string)
-                        .map_err(UciOptionFromPartsError::NumaPolicyParsingError)?;
-                    Ok(UciOption::NumaPolicy {
-                        default: numa_policy,
-                    })
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::ClearHash => Ok(UciOption::ClearHash),
-            UciOptionKind::Ponder => {
-                if let TypedUciOptionData::Check(check) = typed_data {
-                    Ok(UciOption::Ponder { default: check })
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::EvalFile => {
-                if let TypedUciOptionData::String(uci_string) = typed_data {
-                    Ok(UciOption::EvalFile {
-                        default: uci_string,
-                    })
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::EvalFileSmall => {
-                if let TypedUciOptionData::String(uci_string) = typed_data {
-                    Ok(UciOption::EvalFileSmall {
-                        default: uci_string,
-                    })
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::UCIChess960 => {
-                if let TypedUciOptionData::Check(check) = typed_data {
-                    Ok(UciOption::UCIChess960 { default: check })
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::UCIShowWDL => {
-                if let TypedUciOptionData::Check(check) = typed_data {
-                    Ok(UciOption::UCIShowWDL { default: check })
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::UCILimitStrength => {
-                if let TypedUciOptionData::Check(check) = typed_data {
-                    Ok(UciOption::UCILimitStrength { default: check })
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::UCIElo => {
-                if let TypedUciOptionData::Spin(spin) = typed_data {
-                    Ok(UciOption::UCIElo(spin))
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::SkillLevel => {
-                if let TypedUciOptionData::Spin(spin) = typed_data {
-                    Ok(UciOption::SkillLevel(spin))
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::SyzygyPath => {
-                if let TypedUciOptionData::String(uci_string) = typed_data {
-                    Ok(UciOption::SyzygyPath {
-                        default: uci_string,
-                    })
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::SyzygyProbeDepth => {
-                if let TypedUciOptionData::Spin(spin) = typed_data {
-                    Ok(UciOption::SyzygyProbeDepth(spin))
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::Syzygy50MoveRule => {
-                if let TypedUciOptionData::Check(check) = typed_data {
-                    Ok(UciOption::Syzygy50MoveRule { default: check })
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::SyzygyProbeLimit => {
-                if let TypedUciOptionData::Spin(spin) = typed_data {
-                    Ok(UciOption::SyzygyProbeLimit(spin))
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::MoveOverhead => {
-                if let TypedUciOptionData::Spin(spin) = typed_data {
-                    Ok(UciOption::MoveOverhead(spin))
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::Nodestime => {
-                if let TypedUciOptionData::Spin(spin) = typed_data {
-                    Ok(UciOption::Nodestime(spin))
-                } else {
-                    unreachable!()
-                }
-            }
-            UciOptionKind::DebugLogFile => {
-                if let TypedUciOptionData::String(uci_string) = typed_data {
-                    Ok(UciOption::DebugLogFile {
-                        default: uci_string,
-                    })
-                } else {
-                    unreachable!()
-                }
-            }
-        }
+        kind.option_from_typed_data(typed_data)
     }
 }
 
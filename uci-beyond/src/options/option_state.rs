@@ -0,0 +1,520 @@
+use crate::{
+    engine_commands::UciOptionBlockBuilder,
+    gui_commands::SetOptionCommand,
+    model,
+    options::{Combo, TypedUciOptionData, UciOption, UciOptionKind, UciOptionType},
+};
+
+/// The current value of an option tracked by [`OptionState`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OptionValue {
+    Spin(i64),
+    /// The value of a string option, e.g. `NumaPolicy` or `SyzygyPath`, without the `<empty>` escaping.
+    String(String),
+    Check(bool),
+    /// The selected var of a combo, spelled as advertised by the engine.
+    Combo(String),
+    /// A button has no value, so the number of times it was pressed is kept instead.
+    Button {
+        presses: u32,
+    },
+}
+
+/// A problem found by [`OptionState::apply`]. The state is left unchanged.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum OptionStateError {
+    #[error("Unknown option: `{name}`.")]
+    UnknownOption { name: String },
+    #[error("The value doesn't fit the {expected} option `{name}`.")]
+    TypeMismatch {
+        name: String,
+        expected: UciOptionType,
+    },
+    #[error("Invalid value for option `{name}`: `{value}`.")]
+    InvalidValue { name: String, value: String },
+    /// The value is outside the bounds of the spin. An absent bound of a custom spin is [`i64::MIN`] or [`i64::MAX`].
+    #[error("Value {value} for option `{name}` is out of range [{min}, {max}].")]
+    OutOfRange {
+        name: String,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+}
+
+/// The current values of the options advertised by the engine, starting with their defaults
+/// and updated by the `setoption` commands sent since then.
+///
+/// Unlike [`UciOptionBlockBuilder`], which keeps the declarations of the options, the state keeps
+/// what the engine is configured with, e.g. to restore it after a restart or to show it in a GUI.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OptionState {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    option: UciOption,
+    declaration: TypedUciOptionData,
+    value: OptionValue,
+}
+
+/// The options that differ from their defaults at some point, e.g. when a search started,
+/// taken with [`OptionState::snapshot`].
+///
+/// The buttons are left out since they have no value in effect.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OptionSnapshot {
+    entries: Vec<(String, OptionValue)>,
+}
+
+impl OptionSnapshot {
+    /// The value of the option if it differs from the default. As per the UCI protocol, the name is case-insensitive.
+    pub fn get(&self, name: &str) -> Option<&OptionValue> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| entry.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    /// The names of the options with their values, in the order of [`OptionState::iter`].
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &OptionValue)> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Whether every option has its default value.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The names of the options whose values differ between the snapshots, including the ones
+    /// at their defaults in only one of them.
+    pub fn differences<'a>(&'a self, other: &'a OptionSnapshot) -> Vec<&'a str> {
+        let changed = self
+            .iter()
+            .filter(|(name, value)| other.get(name) != Some(value))
+            .map(|(name, _)| name);
+        let reset = other
+            .iter()
+            .filter(|(name, _)| self.get(name).is_none())
+            .map(|(name, _)| name);
+        changed.chain(reset).collect()
+    }
+}
+
+/// The value of a `setoption` command before it is checked against the declaration of the option.
+enum Requested<'a> {
+    Spin(i64),
+    String(String),
+    Check(bool),
+    Press,
+    /// The value of a custom option, whose type is known only from the declaration.
+    Raw(Option<&'a str>),
+}
+
+impl OptionState {
+    /// Starts with the defaults of the options advertised by the engine.
+    pub fn from_defaults(options: &UciOptionBlockBuilder) -> Self {
+        let entries = options
+            .options()
+            .into_iter()
+            .map(|option| {
+                let declaration = option.typed_data();
+                let value = default_value(&declaration);
+                Entry {
+                    option,
+                    declaration,
+                    value,
+                }
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// The current value of the option. As per the UCI protocol, the name is case-insensitive.
+    pub fn get(&self, name: &str) -> Option<&OptionValue> {
+        self.entry(name).map(|entry| &entry.value)
+    }
+
+    /// The current value of the standard option.
+    pub fn get_kind(&self, kind: UciOptionKind) -> Option<&OptionValue> {
+        self.entries
+            .iter()
+            .find(|entry| entry.option.kind() == Some(kind))
+            .map(|entry| &entry.value)
+    }
+
+    /// The options with their current values, in the order of [`UciOptionBlockBuilder::options`].
+    pub fn iter(&self) -> impl Iterator<Item = (&UciOption, &OptionValue)> {
+        self.entries
+            .iter()
+            .map(|entry| (&entry.option, &entry.value))
+    }
+
+    /// Updates the value of the option after checking it against the declaration of the option,
+    /// e.g. that a spin is within its bounds or that a combo has the var.
+    ///
+    /// A button records the press. The values of the custom options are parsed according to their type,
+    /// and the vars of a combo are matched case-insensitively.
+    pub fn apply(&mut self, cmd: &SetOptionCommand) -> Result<(), OptionStateError> {
+        let value = self.check(cmd)?;
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.option.name().eq_ignore_ascii_case(cmd.name()))
+            .expect("the option was checked");
+        entry.value = value;
+        Ok(())
+    }
+
+    /// The value the option would have after [`apply`](Self::apply), without updating it,
+    /// e.g. to hand it over to the engine before committing to it.
+    pub fn check(&self, cmd: &SetOptionCommand) -> Result<OptionValue, OptionStateError> {
+        let name = cmd.name();
+        let Some(entry) = self.entry(name) else {
+            return Err(OptionStateError::UnknownOption {
+                name: name.to_string(),
+            });
+        };
+        checked_value(entry, requested(cmd))
+    }
+
+    /// Adds the option with its default.
+    pub(crate) fn push(&mut self, option: UciOption) {
+        let declaration = option.typed_data();
+        let value = default_value(&declaration);
+        self.entries.push(Entry {
+            option,
+            declaration,
+            value,
+        });
+    }
+
+    /// The options that differ from their defaults, e.g. to record what a search was made with.
+    pub fn snapshot(&self) -> OptionSnapshot {
+        let entries = self
+            .entries
+            .iter()
+            .filter(|entry| !matches!(entry.declaration, TypedUciOptionData::Button))
+            .filter(|entry| default_value(&entry.declaration) != entry.value)
+            .map(|entry| (entry.option.name().to_string(), entry.value.clone()))
+            .collect();
+        OptionSnapshot { entries }
+    }
+
+    fn entry(&self, name: &str) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.option.name().eq_ignore_ascii_case(name))
+    }
+}
+
+fn default_value(declaration: &TypedUciOptionData) -> OptionValue {
+    match declaration {
+        TypedUciOptionData::Spin(spin) => OptionValue::Spin(spin.default),
+        TypedUciOptionData::String(default) => OptionValue::String(default.0.clone()),
+        TypedUciOptionData::Button => OptionValue::Button { presses: 0 },
+        TypedUciOptionData::Check(default) => OptionValue::Check(default.0),
+        TypedUciOptionData::Combo(combo) => OptionValue::Combo(combo.default.0.clone()),
+    }
+}
+
+fn requested(cmd: &SetOptionCommand) -> Requested<'_> {
+    match cmd {
+        SetOptionCommand::Threads { value }
+        | SetOptionCommand::Hash { value }
+        | SetOptionCommand::MultiPV { value }
+        | SetOptionCommand::UCIElo { value }
+        | SetOptionCommand::SkillLevel { value }
+        | SetOptionCommand::SyzygyProbeDepth { value }
+        | SetOptionCommand::SyzygyProbeLimit { value }
+        | SetOptionCommand::MoveOverhead { value }
+        | SetOptionCommand::Nodestime { value } => Requested::Spin(i64::from(*value)),
+        SetOptionCommand::NumaPolicy { value } => Requested::String(value.to_string()),
+        SetOptionCommand::ClearHash => Requested::Press,
+        SetOptionCommand::Ponder { value }
+        | SetOptionCommand::UCIChess960 { value }
+        | SetOptionCommand::UCIShowWDL { value }
+        | SetOptionCommand::UCILimitStrength { value }
+        | SetOptionCommand::Syzygy50MoveRule { value } => Requested::Check(*value),
+        SetOptionCommand::EvalFile { value }
+        | SetOptionCommand::EvalFileSmall { value }
+        | SetOptionCommand::DebugLogFile { value } => Requested::String(value.clone()),
+        SetOptionCommand::SyzygyPath { value } => Requested::String(value.to_string()),
+        SetOptionCommand::Custom { value, .. } => Requested::Raw(value.as_deref()),
+    }
+}
+
+fn checked_value(entry: &Entry, requested: Requested<'_>) -> Result<OptionValue, OptionStateError> {
+    let name = entry.option.name();
+    let invalid_value = |value: &str| OptionStateError::InvalidValue {
+        name: name.to_string(),
+        value: value.to_string(),
+    };
+    let type_mismatch = || OptionStateError::TypeMismatch {
+        name: name.to_string(),
+        expected: entry.declaration.r#type(),
+    };
+
+    let value = match (&entry.declaration, requested) {
+        (TypedUciOptionData::Spin(spin), requested) => {
+            let value = match requested {
+                Requested::Spin(value) => value,
+                Requested::Raw(Some(raw)) => raw.trim().parse().map_err(|_| invalid_value(raw))?,
+                _ => return Err(type_mismatch()),
+            };
+            let (min, max) = (spin.min.unwrap_or(i64::MIN), spin.max.unwrap_or(i64::MAX));
+            if !(min..=max).contains(&value) {
+                return Err(OptionStateError::OutOfRange {
+                    name: name.to_string(),
+                    value,
+                    min,
+                    max,
+                });
+            }
+            OptionValue::Spin(value)
+        }
+        (TypedUciOptionData::String(_), Requested::String(value)) => OptionValue::String(value),
+        (TypedUciOptionData::String(_), Requested::Raw(Some(raw))) => {
+            OptionValue::String(model::UciString::from_token(raw.trim()).0)
+        }
+        (TypedUciOptionData::Check(_), Requested::Check(value)) => OptionValue::Check(value),
+        (TypedUciOptionData::Check(_), Requested::Raw(Some(raw))) => match raw.trim() {
+            "true" => OptionValue::Check(true),
+            "false" => OptionValue::Check(false),
+            _ => return Err(invalid_value(raw)),
+        },
+        (TypedUciOptionData::Combo(combo), Requested::String(value)) => {
+            combo_var(combo, &value).ok_or_else(|| invalid_value(&value))?
+        }
+        (TypedUciOptionData::Combo(combo), Requested::Raw(Some(raw))) => {
+            combo_var(combo, raw).ok_or_else(|| invalid_value(raw))?
+        }
+        (TypedUciOptionData::Button, Requested::Press | Requested::Raw(None)) => {
+            let OptionValue::Button { presses } = entry.value else {
+                unreachable!("a button always has a button value");
+            };
+            OptionValue::Button {
+                presses: presses.saturating_add(1),
+            }
+        }
+        _ => return Err(type_mismatch()),
+    };
+    Ok(value)
+}
+
+fn combo_var(combo: &Combo, value: &str) -> Option<OptionValue> {
+    combo
+        .var(value.trim())
+        .map(|var| OptionValue::Combo(var.0.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::options::{PartialSpin, Spin};
+
+    fn custom(name: &str, value: Option<&str>) -> SetOptionCommand {
+        SetOptionCommand::Custom {
+            name: name.to_string(),
+            value: value.map(str::to_string),
+        }
+    }
+
+    fn options() -> UciOptionBlockBuilder {
+        let mut options = UciOptionBlockBuilder::default();
+        let custom_options = [
+            (
+                "Style",
+                TypedUciOptionData::Combo(Combo {
+                    default: model::UciString::from("Normal"),
+                    vars: vec![
+                        model::UciString::from("Normal"),
+                        model::UciString::from("Solid"),
+                        model::UciString::from("Risky"),
+                    ],
+                }),
+            ),
+            (
+                "Contempt",
+                TypedUciOptionData::Spin(PartialSpin {
+                    default: 0,
+                    min: Some(-100),
+                    max: Some(100),
+                }),
+            ),
+            (
+                "Book File",
+                TypedUciOptionData::String(model::UciString::from("book.bin")),
+            ),
+            ("Reset Learning", TypedUciOptionData::Button),
+        ];
+        for (name, typed_data) in custom_options {
+            options
+                .add(UciOption::Custom {
+                    name: name.to_string(),
+                    typed_data,
+                })
+                .unwrap();
+        }
+        options
+            .add(UciOption::Hash(Spin {
+                default: 16,
+                min: 1,
+                max: 1024,
+            }))
+            .unwrap();
+        options.add(UciOption::ClearHash).unwrap();
+        options
+            .add(UciOption::Ponder {
+                default: model::Check(false),
+            })
+            .unwrap();
+        options
+    }
+
+    #[test]
+    fn test_defaults() {
+        let state = OptionState::from_defaults(&options());
+        assert_eq!(
+            state.get_kind(UciOptionKind::Hash),
+            Some(&OptionValue::Spin(16))
+        );
+        assert_eq!(
+            state.get("style"),
+            Some(&OptionValue::Combo("Normal".to_string()))
+        );
+        assert_eq!(
+            state.get("Clear Hash"),
+            Some(&OptionValue::Button { presses: 0 })
+        );
+        assert_eq!(state.iter().count(), 7);
+        assert_eq!(state.get("Threads"), None);
+    }
+
+    #[test]
+    fn test_apply_setoptions() {
+        let mut state = OptionState::from_defaults(&options());
+        let commands = [
+            SetOptionCommand::Hash { value: 256 },
+            SetOptionCommand::Ponder { value: true },
+            SetOptionCommand::ClearHash,
+            SetOptionCommand::ClearHash,
+            custom("Style", Some("risky")),
+            custom("Contempt", Some("-20")),
+            custom("Book File", Some("<empty>")),
+            custom("Reset Learning", None),
+        ];
+        for cmd in &commands {
+            state.apply(cmd).unwrap();
+        }
+
+        assert_eq!(state.get("Hash"), Some(&OptionValue::Spin(256)));
+        assert_eq!(state.get("Ponder"), Some(&OptionValue::Check(true)));
+        assert_eq!(
+            state.get_kind(UciOptionKind::ClearHash),
+            Some(&OptionValue::Button { presses: 2 })
+        );
+        assert_eq!(
+            state.get("Style"),
+            Some(&OptionValue::Combo("Risky".to_string()))
+        );
+        assert_eq!(state.get("Contempt"), Some(&OptionValue::Spin(-20)));
+        assert_eq!(
+            state.get("Book File"),
+            Some(&OptionValue::String(String::new()))
+        );
+        assert_eq!(
+            state.get("Reset Learning"),
+            Some(&OptionValue::Button { presses: 1 })
+        );
+    }
+
+    #[test]
+    fn test_apply_rejects_invalid_values() {
+        let mut state = OptionState::from_defaults(&options());
+        let before = state.clone();
+
+        assert_eq!(
+            state.apply(&SetOptionCommand::Hash { value: 4096 }),
+            Err(OptionStateError::OutOfRange {
+                name: "Hash".to_string(),
+                value: 4096,
+                min: 1,
+                max: 1024,
+            })
+        );
+        assert_eq!(
+            state.apply(&custom("Style", Some("Reckless"))),
+            Err(OptionStateError::InvalidValue {
+                name: "Style".to_string(),
+                value: "Reckless".to_string(),
+            })
+        );
+        assert_eq!(
+            state.apply(&custom("Contempt", None)),
+            Err(OptionStateError::TypeMismatch {
+                name: "Contempt".to_string(),
+                expected: UciOptionType::Spin,
+            })
+        );
+        assert_eq!(
+            state.apply(&SetOptionCommand::Threads { value: 4 }),
+            Err(OptionStateError::UnknownOption {
+                name: "Threads".to_string(),
+            })
+        );
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut state = OptionState::from_defaults(&options());
+        assert!(state.snapshot().is_empty());
+
+        for cmd in [
+            SetOptionCommand::Hash { value: 256 },
+            SetOptionCommand::ClearHash,
+            custom("Style", Some("solid")),
+            // The default, so it isn't in the snapshot
+            custom("Contempt", Some("0")),
+        ] {
+            state.apply(&cmd).unwrap();
+        }
+        let before = state.snapshot();
+        assert_eq!(
+            before.iter().collect::<Vec<_>>(),
+            [
+                ("Hash", &OptionValue::Spin(256)),
+                ("Style", &OptionValue::Combo("Solid".to_string())),
+            ]
+        );
+
+        state.apply(&SetOptionCommand::Hash { value: 16 }).unwrap();
+        state
+            .apply(&SetOptionCommand::Ponder { value: true })
+            .unwrap();
+        let after = state.snapshot();
+        assert_eq!(after.get("hash"), None);
+        assert_eq!(before.differences(&after), ["Hash", "Ponder"]);
+        assert_eq!(after.differences(&before), ["Ponder", "Hash"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_serde_round_trip() {
+        let mut state = OptionState::from_defaults(&options());
+        state.apply(&custom("Book File", Some("gm.bin"))).unwrap();
+        let snapshot = state.snapshot();
+
+        let json = serde_json::to_string(&snapshot).expect("Failed to serialize OptionSnapshot");
+        let deserialized: OptionSnapshot =
+            serde_json::from_str(&json).expect("Failed to deserialize OptionSnapshot");
+        assert_eq!(deserialized, snapshot);
+    }
+}
@@ -0,0 +1,201 @@
+//! The module for the order in which `setoption` commands should be sent to the engine (see [`sort_setoptions`])
+//! and the order in which the options are advertised (see [`CANONICAL_ORDER`]).
+
+use crate::{gui_commands::SetOptionCommand, options::UciOptionKind};
+
+/// The standard options in the order Stockfish advertises them in response to `uci`, which is
+/// the order the crate renders an option block in, e.g. in [`UciResponse`](crate::gui_command_responses::UciResponse).
+///
+/// Every [`UciOptionKind`] appears exactly once.
+pub const CANONICAL_ORDER: &[UciOptionKind] = &[
+    UciOptionKind::DebugLogFile,
+    UciOptionKind::NumaPolicy,
+    UciOptionKind::Threads,
+    UciOptionKind::Hash,
+    UciOptionKind::ClearHash,
+    UciOptionKind::Ponder,
+    UciOptionKind::MultiPV,
+    UciOptionKind::SkillLevel,
+    UciOptionKind::MoveOverhead,
+    UciOptionKind::Nodestime,
+    UciOptionKind::UCIChess960,
+    UciOptionKind::UCILimitStrength,
+    UciOptionKind::UCIElo,
+    UciOptionKind::UCIShowWDL,
+    UciOptionKind::SyzygyPath,
+    UciOptionKind::SyzygyProbeDepth,
+    UciOptionKind::Syzygy50MoveRule,
+    UciOptionKind::SyzygyProbeLimit,
+    UciOptionKind::EvalFile,
+    UciOptionKind::EvalFileSmall,
+];
+
+/// The position of the kind in [`CANONICAL_ORDER`].
+pub(crate) fn canonical_position(kind: UciOptionKind) -> usize {
+    CANONICAL_ORDER
+        .iter()
+        .position(|&k| k == kind)
+        .expect("every kind is in the canonical order")
+}
+
+/// Reorders `setoption` commands so that the known dependencies between options are respected.
+///
+/// The rules are:
+///
+/// 1. `EvalFile` and `EvalFileSmall` go before `NumaPolicy` and `Threads`. Stockfish replicates the loaded
+///    networks for every NUMA node the threads are bound to, so loading the networks first avoids
+///    replicating the networks which are about to be replaced
+///    (see [`Engine::resize_threads`](https://github.com/official-stockfish/Stockfish/blob/master/src/engine.cpp)).
+/// 2. `NumaPolicy` goes before `Threads`. Changing the NUMA policy recreates the threads according to
+///    the new policy (see the `NumaPolicy` option in
+///    [UCI & Commands](https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#setoption)).
+/// 3. `Threads` goes before `Hash`. "It is recommended to set Hash after setting Threads"
+///    (see the `Hash` option in
+///    [UCI & Commands](https://official-stockfish.github.io/docs/stockfish-wiki/UCI-&-Commands.html#setoption)).
+///
+/// Only the commands covered by the rules are moved, and only among the positions they already occupy.
+/// Every other command stays where it was, and commands with the same rank (e.g. two `Hash` commands)
+/// keep their relative order, so the last one still wins.
+pub fn sort_setoptions(commands: Vec<SetOptionCommand>) -> Vec<SetOptionCommand> {
+    let positions: Vec<usize> = commands
+        .iter()
+        .enumerate()
+        .filter(|(_, cmd)| rank(cmd).is_some())
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut slots: Vec<Option<SetOptionCommand>> = commands.into_iter().map(Some).collect();
+    let mut ranked: Vec<SetOptionCommand> = positions
+        .iter()
+        .map(|&i| slots[i].take().expect("each position is taken once"))
+        .collect();
+    // `sort_by_key` is stable, which keeps the relative order of commands with the same rank.
+    ranked.sort_by_key(|cmd| rank(cmd));
+
+    for (i, cmd) in positions.into_iter().zip(ranked) {
+        slots[i] = Some(cmd);
+    }
+    slots
+        .into_iter()
+        .map(|cmd| cmd.expect("every slot is filled"))
+        .collect()
+}
+
+/// The rank of the command among the ones covered by the rules of [`sort_setoptions`], if any.
+fn rank(cmd: &SetOptionCommand) -> Option<u8> {
+    match cmd {
+        SetOptionCommand::EvalFile { .. } => Some(0),
+        SetOptionCommand::EvalFileSmall { .. } => Some(1),
+        SetOptionCommand::NumaPolicy { .. } => Some(2),
+        SetOptionCommand::Threads { .. } => Some(3),
+        SetOptionCommand::Hash { .. } => Some(4),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator as _;
+
+    use super::*;
+    use crate::model;
+
+    #[test]
+    fn test_canonical_order_has_every_kind_once() {
+        assert_eq!(CANONICAL_ORDER.len(), UciOptionKind::iter().count());
+        for kind in UciOptionKind::iter() {
+            assert_eq!(CANONICAL_ORDER[canonical_position(kind)], kind);
+            assert_eq!(CANONICAL_ORDER.iter().filter(|&&k| k == kind).count(), 1);
+        }
+    }
+
+    fn lines(commands: &[SetOptionCommand]) -> Vec<String> {
+        commands.iter().map(ToString::to_string).collect()
+    }
+
+    fn permutations(items: Vec<SetOptionCommand>) -> Vec<Vec<SetOptionCommand>> {
+        if items.len() <= 1 {
+            return vec![items];
+        }
+        let mut result = Vec::new();
+        for i in 0..items.len() {
+            let mut rest = items.clone();
+            let item = rest.remove(i);
+            for mut permutation in permutations(rest) {
+                permutation.insert(0, item.clone());
+                result.push(permutation);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_sort_setoptions_is_stable_for_permuted_inputs() {
+        let commands = vec![
+            SetOptionCommand::Hash { value: 256 },
+            SetOptionCommand::Threads { value: 8 },
+            SetOptionCommand::NumaPolicy {
+                value: model::NumaPolicy::Auto,
+            },
+            SetOptionCommand::EvalFileSmall {
+                value: "small.nnue".to_string(),
+            },
+            SetOptionCommand::EvalFile {
+                value: "big.nnue".to_string(),
+            },
+        ];
+        let expected = vec![
+            "setoption name EvalFile value big.nnue",
+            "setoption name EvalFileSmall value small.nnue",
+            "setoption name NumaPolicy value auto",
+            "setoption name Threads value 8",
+            "setoption name Hash value 256",
+        ];
+        for permutation in permutations(commands) {
+            assert_eq!(lines(&sort_setoptions(permutation)), expected);
+        }
+    }
+
+    #[test]
+    fn test_sort_setoptions_keeps_unrelated_options_in_place() {
+        let commands = vec![
+            SetOptionCommand::MultiPV { value: 3 },
+            SetOptionCommand::Hash { value: 256 },
+            SetOptionCommand::UCIShowWDL { value: true },
+            SetOptionCommand::Threads { value: 8 },
+            SetOptionCommand::Custom {
+                name: "Contempt".to_string(),
+                value: Some("10".to_string()),
+            },
+            SetOptionCommand::MoveOverhead { value: 100 },
+        ];
+        assert_eq!(
+            lines(&sort_setoptions(commands)),
+            vec![
+                "setoption name MultiPV value 3",
+                "setoption name Threads value 8",
+                "setoption name UCI_ShowWDL value true",
+                "setoption name Hash value 256",
+                "setoption name Contempt value 10",
+                "setoption name Move Overhead value 100",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_setoptions_keeps_repeated_options_in_order() {
+        let commands = vec![
+            SetOptionCommand::Hash { value: 16 },
+            SetOptionCommand::Threads { value: 2 },
+            SetOptionCommand::Hash { value: 32 },
+        ];
+        assert_eq!(
+            lines(&sort_setoptions(commands)),
+            vec![
+                "setoption name Threads value 2",
+                "setoption name Hash value 16",
+                "setoption name Hash value 32",
+            ]
+        );
+    }
+}
@@ -0,0 +1,440 @@
+use crate::{
+    capabilities::EngineCapabilities,
+    engine_commands::UciOptionBlockBuilder,
+    gui_commands::SetOptionCommand,
+    model,
+    options::{PartialSpin, Spin, TypedUciOptionData, UciOption},
+};
+
+/// A single `(option name, value)` pair of a [`Profile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProfileEntry {
+    pub name: String,
+    /// The value as it would be sent in `setoption name <name> value <value>`.
+    ///
+    /// It is ignored for buttons. An empty string or `<empty>` denotes an empty string value.
+    pub value: String,
+}
+
+/// A named engine configuration, e.g. "blitz", "analysis" or "handicap 1500".
+///
+/// The options are kept in order because the order of `setoption` commands can matter,
+/// e.g. `Hash` should be set after `Threads`.
+///
+/// With the `serde` feature, a profile can be saved to and loaded from any serde format, e.g. TOML or JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Profile {
+    pub name: String,
+    pub options: Vec<ProfileEntry>,
+}
+
+/// A problem found by [`Profile::to_setoptions`]. The respective option is not set.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProfileWarning {
+    #[error("Unknown option: `{name}`.")]
+    UnknownOption { name: String },
+    #[error("Invalid value for option `{name}`: `{value}`.")]
+    InvalidValue { name: String, value: String },
+    #[error("Value {value} for option `{name}` is out of range [{min}, {max}].")]
+    /// The value is outside the bounds of the spin. An absent bound of a custom spin is [`i64::MIN`] or [`i64::MAX`].
+    OutOfRange {
+        name: String,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+}
+
+impl Profile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Profile {
+            name: name.into(),
+            options: Vec::new(),
+        }
+    }
+
+    /// Sets the value of the option, replacing the previous value if the option is already in the profile.
+    /// As per the UCI protocol, option names are case-insensitive.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        let (name, value) = (name.into(), value.into());
+        match self.entry_mut(&name) {
+            Some(entry) => entry.value = value,
+            None => self.options.push(ProfileEntry { name, value }),
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+            .map(|entry| entry.value.as_str())
+    }
+
+    fn entry_mut(&mut self, name: &str) -> Option<&mut ProfileEntry> {
+        self.options
+            .iter_mut()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Captures the default values of the options advertised by the engine.
+    ///
+    /// Buttons are skipped since they have no value.
+    pub fn from_current(options: &UciOptionBlockBuilder) -> Self {
+        let options = options
+            .options()
+            .iter()
+            .filter_map(|option| {
+                Some(ProfileEntry {
+                    name: option.name().to_string(),
+                    value: default_value(option)?,
+                })
+            })
+            .collect();
+
+        Profile {
+            name: String::new(),
+            options,
+        }
+    }
+
+    /// Validates the profile against the options advertised by the engine and
+    /// converts it to `setoption` commands, in the order of the profile.
+    ///
+    /// Unknown options, invalid values and out-of-range spins are reported as warnings
+    /// and skipped rather than sent.
+    pub fn to_setoptions(
+        &self,
+        capabilities: &EngineCapabilities,
+    ) -> (Vec<SetOptionCommand>, Vec<ProfileWarning>) {
+        let mut commands = Vec::new();
+        let mut warnings = Vec::new();
+
+        for ProfileEntry { name, value } in &self.options {
+            let Some(option) = capabilities.option(name) else {
+                warnings.push(ProfileWarning::UnknownOption { name: name.clone() });
+                continue;
+            };
+
+            match setoption_command(&option, value) {
+                Ok(cmd) => commands.push(cmd),
+                Err(warning) => warnings.push(warning),
+            }
+        }
+
+        (commands, warnings)
+    }
+}
+
+fn default_value(option: &UciOption) -> Option<String> {
+    let value = match option {
+        UciOption::Threads(spin)
+        | UciOption::Hash(spin)
+        | UciOption::MultiPV(spin)
+        | UciOption::UCIElo(spin)
+        | UciOption::SkillLevel(spin)
+        | UciOption::SyzygyProbeDepth(spin)
+        | UciOption::SyzygyProbeLimit(spin)
+        | UciOption::MoveOverhead(spin)
+        | UciOption::Nodestime(spin) => spin.default.to_string(),
+        UciOption::NumaPolicy { default } => default.to_string(),
+        UciOption::ClearHash => return None,
+        UciOption::Ponder { default }
+        | UciOption::UCIChess960 { default }
+        | UciOption::UCIShowWDL { default }
+        | UciOption::UCILimitStrength { default }
+        | UciOption::Syzygy50MoveRule { default } => default.to_string(),
+        UciOption::EvalFile { default }
+        | UciOption::EvalFileSmall { default }
+        | UciOption::SyzygyPath { default }
+        | UciOption::DebugLogFile { default } => default.to_string(),
+        UciOption::Custom { typed_data, .. } => match typed_data {
+            TypedUciOptionData::Spin(spin) => spin.default.to_string(),
+            TypedUciOptionData::String(default) => default.to_string(),
+            TypedUciOptionData::Button => return None,
+            TypedUciOptionData::Check(default) => default.to_string(),
+            TypedUciOptionData::Combo(combo) => combo.default.to_string(),
+        },
+    };
+    Some(value)
+}
+
+fn setoption_command(option: &UciOption, value: &str) -> Result<SetOptionCommand, ProfileWarning> {
+    let name = option.name();
+    let invalid_value = || ProfileWarning::InvalidValue {
+        name: name.to_string(),
+        value: value.to_string(),
+    };
+
+    let partial_spin = |spin: &PartialSpin| -> Result<i64, ProfileWarning> {
+        let value: i64 = value.trim().parse().map_err(|_| invalid_value())?;
+        let (min, max) = (spin.min.unwrap_or(i64::MIN), spin.max.unwrap_or(i64::MAX));
+        if !(min..=max).contains(&value) {
+            return Err(ProfileWarning::OutOfRange {
+                name: name.to_string(),
+                value,
+                min,
+                max,
+            });
+        }
+        Ok(value)
+    };
+    let spin = |spin: &Spin| -> Result<u32, ProfileWarning> {
+        let value = partial_spin(&spin.clone().into())?;
+        u32::try_from(value).map_err(|_| invalid_value())
+    };
+    let check = || -> Result<bool, ProfileWarning> {
+        match value.trim() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(invalid_value()),
+        }
+    };
+    let string = || -> String { model::UciString::from_token(value.trim()).0 };
+
+    let cmd = match option {
+        UciOption::Threads(s) => SetOptionCommand::Threads { value: spin(s)? },
+        UciOption::Hash(s) => SetOptionCommand::Hash { value: spin(s)? },
+        UciOption::MultiPV(s) => SetOptionCommand::MultiPV { value: spin(s)? },
+        UciOption::NumaPolicy { .. } => SetOptionCommand::NumaPolicy {
+            value: model::NumaPolicy::try_from(model::UciString(string()))
+                .map_err(|_| invalid_value())?,
+        },
+        UciOption::ClearHash => SetOptionCommand::ClearHash,
+        UciOption::Ponder { .. } => SetOptionCommand::Ponder { value: check()? },
+        UciOption::EvalFile { .. } => SetOptionCommand::EvalFile { value: string() },
+        UciOption::EvalFileSmall { .. } => SetOptionCommand::EvalFileSmall { value: string() },
+        UciOption::UCIChess960 { .. } => SetOptionCommand::UCIChess960 { value: check()? },
+        UciOption::UCIShowWDL { .. } => SetOptionCommand::UCIShowWDL { value: check()? },
+        UciOption::UCILimitStrength { .. } => {
+            SetOptionCommand::UCILimitStrength { value: check()? }
+        }
+        UciOption::UCIElo(s) => SetOptionCommand::UCIElo { value: spin(s)? },
+        UciOption::SkillLevel(s) => SetOptionCommand::SkillLevel { value: spin(s)? },
+        UciOption::SyzygyPath { .. } => SetOptionCommand::SyzygyPath {
+            value: string().parse().map_err(|_| invalid_value())?,
+        },
+        UciOption::SyzygyProbeDepth(s) => SetOptionCommand::SyzygyProbeDepth { value: spin(s)? },
+        UciOption::Syzygy50MoveRule { .. } => {
+            SetOptionCommand::Syzygy50MoveRule { value: check()? }
+        }
+        UciOption::SyzygyProbeLimit(s) => SetOptionCommand::SyzygyProbeLimit { value: spin(s)? },
+        UciOption::MoveOverhead(s) => SetOptionCommand::MoveOverhead { value: spin(s)? },
+        UciOption::Nodestime(s) => SetOptionCommand::Nodestime { value: spin(s)? },
+        UciOption::DebugLogFile { .. } => SetOptionCommand::DebugLogFile { value: string() },
+        UciOption::Custom { name, typed_data } => {
+            let value = match typed_data {
+                TypedUciOptionData::Spin(s) => Some(partial_spin(s)?.to_string()),
+                TypedUciOptionData::Check(_) => Some(check()?.to_string()),
+                TypedUciOptionData::String(_) => Some(model::UciString(string()).to_string()),
+                TypedUciOptionData::Combo(combo) => {
+                    let var = combo.var(value.trim()).ok_or_else(invalid_value)?;
+                    Some(var.to_string())
+                }
+                TypedUciOptionData::Button => None,
+            };
+            SetOptionCommand::Custom {
+                name: name.clone(),
+                value,
+            }
+        }
+    };
+
+    Ok(cmd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        fixtures::STOCKFISH_UCI_RESPONSE, gui_command_responses::UciResponse, util::AsyncReadable,
+    };
+
+    async fn stockfish_capabilities() -> EngineCapabilities {
+        let mut reader = tokio::io::BufReader::new(STOCKFISH_UCI_RESPONSE.as_bytes());
+        UciResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn test_profile_from_current() {
+        let capabilities = stockfish_capabilities().await;
+        let profile = Profile::from_current(&capabilities.options);
+
+        // Every option but the `Clear Hash` button
+        assert_eq!(profile.options.len(), 19);
+        assert_eq!(profile.get("Threads"), Some("1"));
+        assert_eq!(profile.get("numapolicy"), Some("auto"));
+        assert_eq!(profile.get("SyzygyPath"), Some("<empty>"));
+        assert_eq!(profile.get("Clear Hash"), None);
+
+        // The defaults are valid
+        let (commands, warnings) = profile.to_setoptions(&capabilities);
+        assert_eq!(commands.len(), 19);
+        assert!(warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_profile_to_setoptions() {
+        let capabilities = stockfish_capabilities().await;
+
+        let mut profile = Profile::new("handicap 1500");
+        profile
+            .set("Threads", "4")
+            .set("Hash", "256")
+            .set("Frobnicate", "3")
+            .set("UCI_LimitStrength", "true")
+            .set("UCI_Elo", "1000")
+            .set("ponder", "maybe")
+            .set("skill level", "5")
+            .set("Clear Hash", "")
+            .set("hash", "128");
+
+        let (commands, warnings) = profile.to_setoptions(&capabilities);
+
+        let commands: Vec<String> = commands.iter().map(|cmd| cmd.to_string()).collect();
+        assert_eq!(
+            commands,
+            [
+                "setoption name Threads value 4",
+                "setoption name Hash value 128",
+                "setoption name UCI_LimitStrength value true",
+                "setoption name Skill Level value 5",
+                "setoption name Clear Hash",
+            ]
+        );
+        assert_eq!(
+            warnings,
+            [
+                ProfileWarning::UnknownOption {
+                    name: "Frobnicate".to_string()
+                },
+                ProfileWarning::OutOfRange {
+                    name: "UCI_Elo".to_string(),
+                    value: 1000,
+                    min: 1320,
+                    max: 3190,
+                },
+                ProfileWarning::InvalidValue {
+                    name: "Ponder".to_string(),
+                    value: "maybe".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profile_set_replaces_in_place() {
+        let mut profile = Profile::new("blitz");
+        profile
+            .set("Threads", "2")
+            .set("Hash", "64")
+            .set("threads", "4");
+
+        // The first spelling of the name and its position are kept
+        assert_eq!(
+            profile.options,
+            [
+                ProfileEntry {
+                    name: "Threads".to_string(),
+                    value: "4".to_string()
+                },
+                ProfileEntry {
+                    name: "Hash".to_string(),
+                    value: "64".to_string()
+                },
+            ]
+        );
+        assert_eq!(profile.get("THREADS"), Some("4"));
+        assert_eq!(profile.get("Ponder"), None);
+    }
+
+    #[tokio::test]
+    async fn test_profile_custom_options() {
+        let response = "id name Toy
+id author The Toy Authors
+option name Contempt type spin default 24 min -100 max 100
+option name Analyse Mode type check default false
+option name Book File type string default <empty>
+uciok
+";
+        let mut reader = tokio::io::BufReader::new(response.as_bytes());
+        let capabilities: EngineCapabilities = UciResponse::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+            .into();
+
+        let defaults = Profile::from_current(&capabilities.options);
+        assert_eq!(defaults.get("Contempt"), Some("24"));
+        assert_eq!(defaults.get("Analyse Mode"), Some("false"));
+        assert_eq!(defaults.get("Book File"), Some("<empty>"));
+
+        let mut profile = Profile::new("analysis");
+        profile
+            .set("contempt", "-50")
+            .set("Analyse Mode", "true")
+            .set("Book File", "books/main line.bin")
+            .set("Contempt", "-101");
+        let (commands, warnings) = profile.to_setoptions(&capabilities);
+
+        let commands: Vec<String> = commands.iter().map(|cmd| cmd.to_string()).collect();
+        assert_eq!(
+            commands,
+            [
+                "setoption name Analyse Mode value true",
+                "setoption name Book File value books/main line.bin",
+            ]
+        );
+        assert_eq!(
+            warnings,
+            [ProfileWarning::OutOfRange {
+                name: "Contempt".to_string(),
+                value: -101,
+                min: -100,
+                max: 100,
+            }]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_profile_serde_round_trip() {
+        let mut profile = Profile::new("analysis");
+        profile
+            .set("Threads", "8")
+            .set("MultiPV", "3")
+            .set("SyzygyPath", "/tb/wdl345:/tb/dtz345");
+
+        let json = serde_json::to_string(&profile).expect("Failed to serialize Profile");
+        let deserialized: Profile =
+            serde_json::from_str(&json).expect("Failed to deserialize Profile");
+
+        assert_eq!(deserialized, profile);
+    }
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_profile_defaults_survive_serde() {
+        let capabilities = stockfish_capabilities().await;
+        let mut profile = Profile::from_current(&capabilities.options);
+        profile.name = "stockfish defaults".to_string();
+
+        let json = serde_json::to_string_pretty(&profile).expect("Failed to serialize Profile");
+        let loaded: Profile = serde_json::from_str(&json).expect("Failed to deserialize Profile");
+        assert_eq!(loaded, profile);
+
+        // The loaded profile configures the engine exactly like the captured one
+        let (commands, warnings) = loaded.to_setoptions(&capabilities);
+        assert_eq!(commands, profile.to_setoptions(&capabilities).0);
+        assert_eq!(commands.len(), 19);
+        assert!(warnings.is_empty());
+    }
+}
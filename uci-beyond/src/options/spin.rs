@@ -1,28 +1,51 @@
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
 
 use kinded::Kinded;
 use optional_struct::optional_struct;
-use strum::{EnumCount, EnumIter};
+use strum::{EnumCount, EnumIter, IntoEnumIterator as _};
 use variants_data_struct::VariantsDataStruct;
 
 use crate::command;
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error(
+    "unknown spin field '{0}' (expected one of: {})",
+    command::parsing::one_of(SpinFieldKind::iter().map(|kind| kind.name()))
+)]
 pub struct UnknownSpinFieldKind(pub String);
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SpinFieldParsingError {
+    #[error("{0}")]
     UnknownSpinFieldKind(UnknownSpinFieldKind),
+    #[error("invalid value '{found}': {err}")]
     InvalidValue {
         found: String,
         err: core::num::ParseIntError,
     },
 }
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SpinParsingError {
+    #[error("{0}")]
     SpinFieldParsingError(SpinFieldParsingError),
-    MissingFields(SpinBuilder),
+    /// The `default` field is missing, e.g. `min 0 max 100`.
+    #[error("missing 'default'")]
+    MissingDefault,
+    /// The spin lacks `min` or `max` or doesn't fit into a [`Spin`], see [`Spin::parse`].
+    #[error("{0}")]
+    SpinConversionError(SpinConversionError),
+}
+
+/// The error of converting a [`PartialSpin`] into a [`Spin`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SpinConversionError {
+    #[error("the spin '{0}' has no 'min' or 'max'")]
+    MissingBounds(PartialSpin),
+    #[error("the spin '{0}' doesn't fit into u32")]
+    OutOfRange(PartialSpin),
 }
 
 #[derive(VariantsDataStruct, Kinded, PartialEq, Eq, Clone, Copy, Debug)]
@@ -53,6 +76,97 @@ impl Display for Spin {
     }
 }
 
+/// A spin as advertised by the engine, where `min` and `max` may be absent, e.g.
+///
+/// ```text
+/// option name Selectivity type spin default 2
+/// ```
+///
+/// Unlike [`Spin`], the values may be negative, e.g. `Contempt` of some engines is within `[-100, 100]`.
+/// The standard options, whose bounds Stockfish always advertises, are [`Spin`]s, see [`Spin::try_from`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PartialSpin {
+    pub default: i64,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+impl PartialSpin {
+    /// Parses 1 to 3 spin fields in any order, e.g. `default 2` or `max 100 default 20 min 0`,
+    /// up to the first token that isn't a spin field. `default` is required.
+    pub fn parse(mut s: &str) -> Result<(Self, &str), command::parsing::Error<SpinParsingError>> {
+        let (mut default, mut min, mut max) = (None, None, None);
+
+        for _ in 0..SpinFieldKind::COUNT {
+            let is_field = s
+                .split_whitespace()
+                .next()
+                .is_some_and(|token| SpinFieldKind::from_str(token).is_ok());
+            if !is_field {
+                break;
+            }
+
+            let (kind, value, rest) = SpinField::parse(s)?;
+            s = rest;
+
+            match kind {
+                SpinFieldKind::Default => default = Some(value),
+                SpinFieldKind::Min => min = Some(value),
+                SpinFieldKind::Max => max = Some(value),
+            }
+        }
+
+        let Some(default) = default else {
+            return Err(command::parsing::Error::CustomError(
+                SpinParsingError::MissingDefault,
+            ));
+        };
+
+        Ok((PartialSpin { default, min, max }, s))
+    }
+}
+
+/// Omits the absent fields, so that a full spin is written as [`Spin`] writes it.
+impl Display for PartialSpin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "default {}", self.default)?;
+        if let Some(min) = self.min {
+            write!(f, " min {min}")?;
+        }
+        if let Some(max) = self.max {
+            write!(f, " max {max}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Spin> for PartialSpin {
+    fn from(spin: Spin) -> Self {
+        PartialSpin {
+            default: spin.default.into(),
+            min: Some(spin.min.into()),
+            max: Some(spin.max.into()),
+        }
+    }
+}
+
+impl TryFrom<PartialSpin> for Spin {
+    type Error = SpinConversionError;
+
+    fn try_from(partial: PartialSpin) -> Result<Self, Self::Error> {
+        let (Some(min), Some(max)) = (partial.min, partial.max) else {
+            return Err(SpinConversionError::MissingBounds(partial));
+        };
+        let to_u32 =
+            |value: i64| u32::try_from(value).map_err(|_| SpinConversionError::OutOfRange(partial));
+        Ok(Spin {
+            default: to_u32(partial.default)?,
+            min: to_u32(min)?,
+            max: to_u32(max)?,
+        })
+    }
+}
+
 impl SpinFieldKind {
     pub fn name(&self) -> &'static str {
         match self {
@@ -67,8 +181,6 @@ impl FromStr for SpinFieldKind {
     type Err = UnknownSpinFieldKind;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use strum::IntoEnumIterator as _;
-
         for kind in SpinFieldKind::iter() {
             if kind.name() == s {
                 return Ok(kind);
@@ -79,14 +191,6 @@ impl FromStr for SpinFieldKind {
 }
 
 impl SpinField {
-    fn from_parts(kind: SpinFieldKind, value: u32) -> Self {
-        match kind {
-            SpinFieldKind::Default => SpinField::Default(value),
-            SpinFieldKind::Min => SpinField::Min(value),
-            SpinFieldKind::Max => SpinField::Max(value),
-        }
-    }
-
     fn parse_spin_field_kind(
         s: &str,
     ) -> Result<(SpinFieldKind, &str), command::parsing::Error<SpinFieldParsingError>> {
@@ -103,7 +207,10 @@ impl SpinField {
         Ok((kind, s))
     }
 
-    fn parse(s: &str) -> Result<(Self, &str), command::parsing::Error<SpinFieldParsingError>> {
+    /// Parses a field into its kind and its value, which may be negative for a [`PartialSpin`].
+    fn parse(
+        s: &str,
+    ) -> Result<(SpinFieldKind, i64, &str), command::parsing::Error<SpinFieldParsingError>> {
         let (kind, s) = SpinField::parse_spin_field_kind(s)?;
 
         let mut parts = s.split_whitespace();
@@ -114,7 +221,7 @@ impl SpinField {
 
         let s = s.trim_start_matches(value_str).trim_start();
 
-        let value: u32 = match value_str.parse() {
+        let value: i64 = match value_str.parse() {
             Ok(v) => v,
             Err(err) => {
                 return Err(SpinFieldParsingError::InvalidValue {
@@ -125,38 +232,68 @@ impl SpinField {
             }
         };
 
-        let field = SpinField::from_parts(kind, value);
-
-        Ok((field, s))
+        Ok((kind, value, s))
     }
 }
 
 impl Spin {
-    pub fn parse(mut s: &str) -> Result<(Self, &str), command::parsing::Error<SpinParsingError>> {
-        let mut builder = SpinBuilder::default();
+    /// Parses the 3 spin fields in any order, failing if `min` or `max` is absent, see [`PartialSpin::parse`].
+    pub fn parse(s: &str) -> Result<(Self, &str), command::parsing::Error<SpinParsingError>> {
+        let (partial, s) = PartialSpin::parse(s)?;
+        let spin = Spin::try_from(partial).map_err(|e| {
+            command::parsing::Error::CustomError(SpinParsingError::SpinConversionError(e))
+        })?;
+        Ok((spin, s))
+    }
+}
 
-        for _ in 0..SpinFieldKind::COUNT {
-            let (field, rest) = SpinField::parse(s)?;
+/// The values for the sliders of a GUI.
+impl Spin {
+    /// The values allowed by the engine, i.e. `min..=max`.
+    pub fn range(&self) -> RangeInclusive<i64> {
+        i64::from(self.min)..=i64::from(self.max)
+    }
 
-            s = rest;
+    /// The nearest value allowed by the engine.
+    pub fn clamp(&self, value: i64) -> i64 {
+        value.clamp(self.min.into(), self.max.into())
+    }
 
-            match field {
-                SpinField::Default(v) => builder.default = Some(v),
-                SpinField::Min(v) => builder.min = Some(v),
-                SpinField::Max(v) => builder.max = Some(v),
-            }
-        }
+    /// `n` evenly spaced values from `min` to `max`, both included, e.g. the marks of a slider.
+    ///
+    /// There are fewer values if the range has fewer than `n`.
+    /// A single value is `min`.
+    pub fn steps(&self, n: usize) -> impl Iterator<Item = i64> + use<> {
+        let (min, max) = (i64::from(self.min), i64::from(self.max));
+        let span = (max - min).max(0) as u64;
+        let n = n.min(usize::try_from(span + 1).unwrap_or(usize::MAX));
+        let last = n.saturating_sub(1).max(1) as u128;
+        (0..n).map(move |i| {
+            // The product doesn't fit into `u64` for the widest ranges
+            min + (u128::from(span) * i as u128 / last) as i64
+        })
+    }
 
-        let spin: Spin = match builder.try_into() {
-            Ok(spin) => spin,
-            Err(e) => {
-                return Err(command::parsing::Error::CustomError(
-                    SpinParsingError::MissingFields(e),
-                ));
-            }
-        };
+    /// `min`, the multiples of `step` between `min` and `max`, and `max`, e.g.
+    /// `1320, 1400, 1600, .., 3000, 3190` for `UCI_Elo` of Stockfish with the step of 200.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` isn't positive.
+    pub fn stepped_by(&self, step: i64) -> impl Iterator<Item = i64> + use<> {
+        assert!(step > 0, "The step of a spin must be positive");
+        let (min, max) = (i64::from(self.min), i64::from(self.max));
+        let first_multiple = (min.div_euclid(step) + 1) * step;
+        let multiples = (first_multiple..max).step_by(step as usize);
+        std::iter::once(min)
+            .chain(multiples)
+            .chain((max > min).then_some(max))
+    }
+}
 
-        Ok((spin, s))
+impl From<Spin> for RangeInclusive<i64> {
+    fn from(spin: Spin) -> Self {
+        spin.range()
     }
 }
 
@@ -200,10 +337,10 @@ mod tests {
 
     #[test]
     fn test_parse_spin_field() {
-        let (field, rest) =
+        let (kind, value, rest) =
             SpinField::parse("min 0 default 20 max 100").expect("Failed to parse spin field");
 
-        assert_eq!(field, SpinField::Min(0));
+        assert_eq!((kind, value), (SpinFieldKind::Min, 0));
         assert_eq!(rest, "default 20 max 100");
     }
 
@@ -222,4 +359,123 @@ mod tests {
         );
         assert_eq!(rest, "extra");
     }
+
+    #[test]
+    fn test_parse_partial_spin() {
+        let (spin, rest) = PartialSpin::parse("default 2").unwrap();
+        assert_eq!(
+            spin,
+            PartialSpin {
+                default: 2,
+                min: None,
+                max: None
+            }
+        );
+        assert_eq!(rest, "");
+        assert_eq!(spin.to_string(), "default 2");
+        assert!(matches!(
+            Spin::try_from(spin),
+            Err(SpinConversionError::MissingBounds(_))
+        ));
+
+        let (spin, rest) = PartialSpin::parse("max 100 default -20 var x").unwrap();
+        assert_eq!(
+            spin,
+            PartialSpin {
+                default: -20,
+                min: None,
+                max: Some(100)
+            }
+        );
+        assert_eq!(rest, "var x");
+        assert_eq!(spin.to_string(), "default -20 max 100");
+
+        assert!(matches!(
+            PartialSpin::parse("min 0 max 100"),
+            Err(command::parsing::Error::CustomError(
+                SpinParsingError::MissingDefault
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_spin_range() {
+        let spin = Spin {
+            default: 16,
+            min: 1,
+            max: 1024,
+        };
+        assert_eq!(spin.range(), 1..=1024);
+        assert_eq!(RangeInclusive::from(spin.clone()), 1..=1024);
+
+        assert_eq!(spin.clamp(0), 1);
+        assert_eq!(spin.clamp(-5), 1);
+        assert_eq!(spin.clamp(1), 1);
+        assert_eq!(spin.clamp(512), 512);
+        assert_eq!(spin.clamp(1024), 1024);
+        assert_eq!(spin.clamp(i64::MAX), 1024);
+    }
+
+    #[test]
+    fn test_spin_steps() {
+        let skill_level = Spin {
+            default: 20,
+            min: 0,
+            max: 20,
+        };
+        assert_eq!(skill_level.steps(5).collect::<Vec<_>>(), [0, 5, 10, 15, 20]);
+        assert_eq!(skill_level.steps(3).collect::<Vec<_>>(), [0, 10, 20]);
+        assert_eq!(skill_level.steps(1).collect::<Vec<_>>(), [0]);
+        assert_eq!(skill_level.steps(0).count(), 0);
+        // No more values than in the range
+        assert_eq!(
+            skill_level.steps(100).collect::<Vec<_>>(),
+            (0..=20).collect::<Vec<_>>()
+        );
+
+        let widest = Spin {
+            default: 0,
+            min: 0,
+            max: u32::MAX,
+        };
+        assert_eq!(
+            widest.steps(3).collect::<Vec<_>>(),
+            [0, i64::from(u32::MAX) / 2, i64::from(u32::MAX)]
+        );
+
+        let fixed = Spin {
+            default: 7,
+            min: 7,
+            max: 7,
+        };
+        assert_eq!(fixed.steps(4).collect::<Vec<_>>(), [7]);
+        assert_eq!(fixed.stepped_by(5).collect::<Vec<_>>(), [7]);
+    }
+
+    #[test]
+    fn test_parse_reordered_spin() {
+        let (spin, rest) = Spin::parse("max 100 min 0 default 20").unwrap();
+        assert_eq!(
+            spin,
+            Spin {
+                default: 20,
+                min: 0,
+                max: 100
+            }
+        );
+        assert_eq!(rest, "");
+        // The full form is written as Stockfish writes it
+        assert_eq!(
+            PartialSpin::from(spin.clone()).to_string(),
+            spin.to_string()
+        );
+        assert_eq!(spin.to_string(), "default 20 min 0 max 100");
+
+        assert!(matches!(
+            Spin::parse("default -1 min -5 max 5"),
+            Err(command::parsing::Error::CustomError(
+                SpinParsingError::SpinConversionError(SpinConversionError::OutOfRange(_))
+            ))
+        ));
+    }
 }
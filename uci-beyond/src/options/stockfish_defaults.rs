@@ -0,0 +1,95 @@
+use crate::{
+    model,
+    options::{CustomOptions, Spin, UciOptionBlock},
+};
+
+impl UciOptionBlock {
+    /// The options advertised by Stockfish 17.1 with their defaults, i.e. the option block of the
+    /// `STOCKFISH_OPTION_BLOCK` transcript in the `fixtures` module (`fixtures` feature).
+    ///
+    /// Useful as a fixture in tests.
+    pub fn stockfish_defaults() -> Self {
+        UciOptionBlock {
+            threads: Spin {
+                default: 1,
+                min: 1,
+                max: 1024,
+            },
+            hash: Spin {
+                default: 16,
+                min: 1,
+                max: 33_554_432,
+            },
+            multi_pv: Spin {
+                default: 1,
+                min: 1,
+                max: 256,
+            },
+            numa_policy: model::NumaPolicy::Auto,
+            clear_hash: (),
+            ponder: model::Check(false),
+            eval_file: model::UciString::from("nn-1c0000000000.nnue"),
+            eval_file_small: model::UciString::from("nn-37f18f62d772.nnue"),
+            uci_chess_960: model::Check(false),
+            uci_show_wdl: model::Check(false),
+            uci_limit_strength: model::Check(false),
+            uci_elo: Spin {
+                default: 1320,
+                min: 1320,
+                max: 3190,
+            },
+            skill_level: Spin {
+                default: 20,
+                min: 0,
+                max: 20,
+            },
+            syzygy_path: model::UciString::default(),
+            syzygy_probe_depth: Spin {
+                default: 1,
+                min: 1,
+                max: 100,
+            },
+            syzygy_50_move_rule: model::Check(true),
+            syzygy_probe_limit: Spin {
+                default: 7,
+                min: 0,
+                max: 7,
+            },
+            move_overhead: Spin {
+                default: 10,
+                min: 0,
+                max: 5000,
+            },
+            nodestime: Spin {
+                default: 0,
+                min: 0,
+                max: 10_000,
+            },
+            debug_log_file: model::UciString::default(),
+            custom: CustomOptions::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        engine_commands::UciOptionBlockBuilder, fixtures::STOCKFISH_OPTION_BLOCK,
+        util::AsyncReadable,
+    };
+
+    #[tokio::test]
+    async fn test_stockfish_defaults_match_stockfish_output() {
+        let mut reader = tokio::io::BufReader::new(STOCKFISH_OPTION_BLOCK.as_bytes());
+        let builder = UciOptionBlockBuilder::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let block: UciOptionBlock = builder.try_into().unwrap();
+
+        assert_eq!(block, UciOptionBlock::stockfish_defaults());
+    }
+}
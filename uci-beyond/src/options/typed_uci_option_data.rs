@@ -1,25 +1,52 @@
 use std::{convert::Infallible, str::FromStr};
 
 use kinded::Kinded;
-use strum::EnumIter;
+use strum::{EnumIter, IntoEnumIterator as _};
 
 use crate::{
     command,
     model::{self, CheckParsingError},
-    options::{Spin, spin::SpinParsingError},
+    options::{Combo, ComboParsingError, PartialSpin, spin::SpinParsingError},
 };
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error(
+    "unknown option type '{0}' (expected one of: {})",
+    command::parsing::one_of(UciOptionType::iter())
+)]
 pub struct UnknownUciOptionType(pub String);
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum KnownUciOptionDataParsingError {
+    #[error("in spin: {0}")]
     SpinParsingError(SpinParsingError),
+    /// The `default` token is missing before the default of a `string` or a `check`.
+    #[error("missing 'default'")]
     StringParsingError,
+    #[error("{0}")]
     CheckParsingError(CheckParsingError),
+    #[error("in combo: {0}")]
+    ComboParsingError(ComboParsingError),
 }
 
 /// The data for the respective [`UciOptionType`] <https://backscattering.de/chess/uci/#engine-option-type>
+///
+/// The enum is non-exhaustive, so the matches outside of the crate need a wildcard arm:
+///
+/// ```compile_fail
+/// use uci_beyond::options::TypedUciOptionData;
+///
+/// fn is_button(data: &TypedUciOptionData) -> bool {
+///     match data {
+///         TypedUciOptionData::Button => true,
+///         TypedUciOptionData::Spin(_)
+///         | TypedUciOptionData::String(_)
+///         | TypedUciOptionData::Check(_)
+///         | TypedUciOptionData::Combo(_) => false,
+///     }
+/// }
+/// ```
 #[derive(Kinded, Debug, PartialEq, Eq, Clone)]
 #[kinded(
     kind = UciOptionType,
@@ -27,9 +54,10 @@ pub enum KnownUciOptionDataParsingError {
     opt_outs=[from_str_impl],
     derive(EnumIter),
 )]
+#[non_exhaustive]
 pub enum TypedUciOptionData {
-    /// a spin wheel that can be an integer in a certain range.
-    Spin(Spin),
+    /// a spin wheel that can be an integer in a certain range. Some engines omit the bounds of the range.
+    Spin(PartialSpin),
     /// a text field that has a string as a value. An empty string is represented as `<empty>`.
     String(model::UciString),
     /// a button that can be pressed to send a command to the engine
@@ -37,7 +65,7 @@ pub enum TypedUciOptionData {
     /// a checkbox that can either be true or false
     Check(model::Check),
     /// a combo box that can have different predefined strings as a value
-    Combo(Vec<model::UciString>),
+    Combo(Combo),
 }
 
 impl std::fmt::Display for TypedUciOptionData {
@@ -47,17 +75,7 @@ impl std::fmt::Display for TypedUciOptionData {
             Self::String(uci_string) => write!(f, "default {uci_string}"),
             Self::Button => write!(f, ""),
             Self::Check(check) => write!(f, "default {check}"),
-            Self::Combo(options) => {
-                write!(
-                    f,
-                    "default {}",
-                    options.first().unwrap_or(&model::UciString(String::new()))
-                )?;
-                for option in options {
-                    write!(f, " var {option}")?;
-                }
-                Ok(())
-            }
+            Self::Combo(combo) => write!(f, "{combo}"),
         }
     }
 }
@@ -66,8 +84,6 @@ impl FromStr for UciOptionType {
     type Err = UnknownUciOptionType;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use strum::IntoEnumIterator as _;
-
         for kind in UciOptionType::iter() {
             if kind.to_string() == s {
                 return Ok(kind);
@@ -106,7 +122,7 @@ impl TypedUciOptionData {
         match uci_option_type {
             UciOptionType::Button => Ok((TypedUciOptionData::Button, s)),
             UciOptionType::Spin => {
-                let (spin, rest) = Spin::parse(s)?;
+                let (spin, rest) = PartialSpin::parse(s)?;
                 Ok((TypedUciOptionData::Spin(spin), rest))
             }
             UciOptionType::String => {
@@ -119,9 +135,25 @@ impl TypedUciOptionData {
                 let (check, rest) = model::Check::parse(s)?;
                 Ok((TypedUciOptionData::Check(check), rest))
             }
-            UciOptionType::Combo => todo!(),
+            UciOptionType::Combo => {
+                let (combo, rest) = Combo::parse(s)?;
+                Ok((TypedUciOptionData::Combo(combo), rest))
+            }
         }
     }
+
+    /// Parses the `default` of a `string` option up to the end of the line rather than up to the next whitespace,
+    /// for the engines whose defaults contain whitespace
+    /// (see [`Quirks::string_defaults_to_eol`](crate::engine_quirks::Quirks::string_defaults_to_eol)).
+    pub fn parse_string_to_end_of_line(
+        s: &str,
+    ) -> Result<(Self, &str), command::parsing::Error<KnownUciOptionDataParsingError>> {
+        let s = Self::parse_default_token(s)?;
+        Ok((
+            TypedUciOptionData::String(model::UciString::from_token(s.trim_end())),
+            "",
+        ))
+    }
 }
 
 impl From<command::parsing::Error<SpinParsingError>>
@@ -132,6 +164,14 @@ impl From<command::parsing::Error<SpinParsingError>>
     }
 }
 
+impl From<command::parsing::Error<ComboParsingError>>
+    for command::parsing::Error<KnownUciOptionDataParsingError>
+{
+    fn from(err: command::parsing::Error<ComboParsingError>) -> Self {
+        err.map_custom(KnownUciOptionDataParsingError::ComboParsingError)
+    }
+}
+
 impl From<command::parsing::Error<Infallible>>
     for command::parsing::Error<KnownUciOptionDataParsingError>
 {
@@ -169,4 +209,24 @@ mod tests {
 
         assert_eq!(rest, "some other tokens");
     }
+
+    #[test]
+    fn test_parse_typed_uci_option_data_combo() {
+        let (data, rest) = TypedUciOptionData::parse_for_type(
+            UciOptionType::Combo,
+            "default Default var Default var Aggressive var Defensive var Human",
+        )
+        .expect("Failed to parse TypedUciOptionData::Combo");
+
+        let TypedUciOptionData::Combo(combo) = &data else {
+            panic!("Expected TypedUciOptionData::Combo");
+        };
+        assert_eq!(combo.default.0, "Default");
+        assert_eq!(combo.vars.len(), 4);
+        assert_eq!(rest, "");
+        assert_eq!(
+            data.to_string(),
+            "default Default var Default var Aggressive var Defensive var Human"
+        );
+    }
 }
@@ -1,91 +1,117 @@
 use strum::EnumIter;
 
-use crate::options::UciOptionType;
+use crate::{
+    model,
+    options::{
+        PartialSpin, Spin, TypedUciOptionData, UciOption, UciOptionDataTypeMismatchError,
+        UciOptionFromPartsError, UciOptionType,
+    },
+};
 
-#[derive(Debug)]
-pub struct UnknownUciOptionKind(pub String);
+/// Converts the spin of a standard option, whose bounds are required.
+fn full_spin(spin: PartialSpin) -> Result<Spin, UciOptionFromPartsError> {
+    Spin::try_from(spin).map_err(UciOptionFromPartsError::SpinConversionError)
+}
 
-/// Represents the standard UCI option kinds.
-///
-/// Any UCI option has a respective [`UciOptionType`].
-///
-/// Some engines may also define custom options that are not part of this enum.
-///
-/// To handle that, there is [`UciOption::Custom`](crate::options::UciOption::Custom).
-#[derive(EnumIter, Debug, PartialEq, Eq, Clone, Copy)]
-pub enum UciOptionKind {
-    Threads,
-    Hash,
-    MultiPV,
-    NumaPolicy,
-    ClearHash,
-    Ponder,
-    EvalFile,
-    EvalFileSmall,
-    UCIChess960,
-    UCIShowWDL,
-    UCILimitStrength,
-    UCIElo,
-    SkillLevel,
-    SyzygyPath,
-    SyzygyProbeDepth,
-    Syzygy50MoveRule,
-    SyzygyProbeLimit,
-    MoveOverhead,
-    Nodestime,
-    DebugLogFile,
+/// The name of an option that is not one of the standard [`UciOptionKind`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownUciOptionKind {
+    pub name: String,
+    /// The standard option whose name differs from `name` only in case, e.g. [`UciOptionKind::Hash`] for `hash`.
+    pub case_insensitive_match: Option<UciOptionKind>,
 }
 
-impl UciOptionKind {
-    pub fn name(self) -> &'static str {
-        match self {
-            UciOptionKind::Threads => "Threads",
-            UciOptionKind::Hash => "Hash",
-            UciOptionKind::MultiPV => "MultiPV",
-            UciOptionKind::NumaPolicy => "NumaPolicy",
-            UciOptionKind::ClearHash => "Clear Hash",
-            UciOptionKind::Ponder => "Ponder",
-            UciOptionKind::EvalFile => "EvalFile",
-            UciOptionKind::EvalFileSmall => "EvalFileSmall",
-            UciOptionKind::UCIChess960 => "UCI_Chess960",
-            UciOptionKind::UCIShowWDL => "UCI_ShowWDL",
-            UciOptionKind::UCILimitStrength => "UCI_LimitStrength",
-            UciOptionKind::UCIElo => "UCI_Elo",
-            UciOptionKind::SkillLevel => "Skill Level",
-            UciOptionKind::SyzygyPath => "SyzygyPath",
-            UciOptionKind::SyzygyProbeDepth => "SyzygyProbeDepth",
-            UciOptionKind::Syzygy50MoveRule => "Syzygy50MoveRule",
-            UciOptionKind::SyzygyProbeLimit => "SyzygyProbeLimit",
-            UciOptionKind::MoveOverhead => "Move Overhead",
-            UciOptionKind::Nodestime => "nodestime",
-            UciOptionKind::DebugLogFile => "Debug Log File",
+/// Generates [`UciOptionKind`] together with its name and type tables, [`UciOption::kind`] and
+/// the conversion used by [`UciOption::from_parts`] from a single table, so that adding a standard option
+/// can't update one of them and forget the others.
+///
+/// Every row is `Kind => "name", Type(binding) => constructor;`, where `Kind` is also the name of
+/// the [`UciOption`] variant, `Type` is the [`TypedUciOptionData`] variant of its data, and the constructor
+/// builds the option from the binding.
+macro_rules! standard_uci_options {
+    ($($kind:ident => $name:literal, $ty:ident $(($data:ident))? => $ctor:expr;)*) => {
+        /// Represents the standard UCI option kinds.
+        ///
+        /// Any UCI option has a respective [`UciOptionType`].
+        ///
+        /// Some engines may also define custom options that are not part of this enum.
+        ///
+        /// To handle that, there is [`UciOption::Custom`].
+        ///
+        /// New kinds are added as the engines add standard options, so the enum is non-exhaustive.
+        #[derive(EnumIter, Debug, PartialEq, Eq, Clone, Copy)]
+        #[non_exhaustive]
+        pub enum UciOptionKind {
+            $($kind,)*
         }
-    }
 
-    pub fn r#type(self) -> UciOptionType {
-        match self {
-            UciOptionKind::Threads => UciOptionType::Spin,
-            UciOptionKind::Hash => UciOptionType::Spin,
-            UciOptionKind::MultiPV => UciOptionType::Spin,
-            UciOptionKind::NumaPolicy => UciOptionType::String,
-            UciOptionKind::ClearHash => UciOptionType::Button,
-            UciOptionKind::Ponder => UciOptionType::Check,
-            UciOptionKind::EvalFile => UciOptionType::String,
-            UciOptionKind::EvalFileSmall => UciOptionType::String,
-            UciOptionKind::UCIChess960 => UciOptionType::Check,
-            UciOptionKind::UCIShowWDL => UciOptionType::Check,
-            UciOptionKind::UCILimitStrength => UciOptionType::Check,
-            UciOptionKind::UCIElo => UciOptionType::Spin,
-            UciOptionKind::SkillLevel => UciOptionType::Spin,
-            UciOptionKind::SyzygyPath => UciOptionType::String,
-            UciOptionKind::SyzygyProbeDepth => UciOptionType::Spin,
-            UciOptionKind::Syzygy50MoveRule => UciOptionType::Check,
-            UciOptionKind::SyzygyProbeLimit => UciOptionType::Spin,
-            UciOptionKind::MoveOverhead => UciOptionType::Spin,
-            UciOptionKind::Nodestime => UciOptionType::Spin,
-            UciOptionKind::DebugLogFile => UciOptionType::String,
+        impl UciOptionKind {
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(UciOptionKind::$kind => $name,)*
+                }
+            }
+
+            pub fn r#type(self) -> UciOptionType {
+                match self {
+                    $(UciOptionKind::$kind => UciOptionType::$ty,)*
+                }
+            }
+
+            /// Builds the option of this kind from its data, failing if the data is of another type.
+            pub(crate) fn option_from_typed_data(
+                self,
+                typed_data: TypedUciOptionData,
+            ) -> Result<UciOption, UciOptionFromPartsError> {
+                match (self, typed_data) {
+                    $((UciOptionKind::$kind, TypedUciOptionData::$ty $(($data))?) => $ctor,)*
+                    (option_kind, typed_data) => Err(
+                        UciOptionFromPartsError::UciOptionDataTypeMismatchError(
+                            UciOptionDataTypeMismatchError {
+                                option_kind,
+                                found: typed_data.r#type(),
+                            },
+                        ),
+                    ),
+                }
+            }
         }
-    }
+
+        impl UciOption {
+            /// The kind of the standard option, or `None` for [`UciOption::Custom`].
+            pub fn kind(&self) -> Option<UciOptionKind> {
+                match self {
+                    $(UciOption::$kind { .. } => Some(UciOptionKind::$kind),)*
+                    UciOption::Custom { .. } => None,
+                }
+            }
+        }
+    };
+}
+
+standard_uci_options! {
+    Threads => "Threads", Spin(spin) => full_spin(spin).map(UciOption::Threads);
+    Hash => "Hash", Spin(spin) => full_spin(spin).map(UciOption::Hash);
+    MultiPV => "MultiPV", Spin(spin) => full_spin(spin).map(UciOption::MultiPV);
+    NumaPolicy => "NumaPolicy", String(uci_string) => model::NumaPolicy::try_from(uci_string)
+        .map(|default| UciOption::NumaPolicy { default })
+        .map_err(UciOptionFromPartsError::NumaPolicyParsingError);
+    ClearHash => "Clear Hash", Button => Ok(UciOption::ClearHash);
+    Ponder => "Ponder", Check(default) => Ok(UciOption::Ponder { default });
+    EvalFile => "EvalFile", String(default) => Ok(UciOption::EvalFile { default });
+    EvalFileSmall => "EvalFileSmall", String(default) => Ok(UciOption::EvalFileSmall { default });
+    UCIChess960 => "UCI_Chess960", Check(default) => Ok(UciOption::UCIChess960 { default });
+    UCIShowWDL => "UCI_ShowWDL", Check(default) => Ok(UciOption::UCIShowWDL { default });
+    UCILimitStrength => "UCI_LimitStrength", Check(default) => Ok(UciOption::UCILimitStrength { default });
+    UCIElo => "UCI_Elo", Spin(spin) => full_spin(spin).map(UciOption::UCIElo);
+    SkillLevel => "Skill Level", Spin(spin) => full_spin(spin).map(UciOption::SkillLevel);
+    SyzygyPath => "SyzygyPath", String(default) => Ok(UciOption::SyzygyPath { default });
+    SyzygyProbeDepth => "SyzygyProbeDepth", Spin(spin) => full_spin(spin).map(UciOption::SyzygyProbeDepth);
+    Syzygy50MoveRule => "Syzygy50MoveRule", Check(default) => Ok(UciOption::Syzygy50MoveRule { default });
+    SyzygyProbeLimit => "SyzygyProbeLimit", Spin(spin) => full_spin(spin).map(UciOption::SyzygyProbeLimit);
+    MoveOverhead => "Move Overhead", Spin(spin) => full_spin(spin).map(UciOption::MoveOverhead);
+    Nodestime => "nodestime", Spin(spin) => full_spin(spin).map(UciOption::Nodestime);
+    DebugLogFile => "Debug Log File", String(default) => Ok(UciOption::DebugLogFile { default });
 }
 
 impl std::str::FromStr for UciOptionKind {
@@ -100,6 +126,63 @@ impl std::str::FromStr for UciOptionKind {
             }
         }
 
-        Err(UnknownUciOptionKind(s.to_string()))
+        Err(UnknownUciOptionKind {
+            name: s.to_string(),
+            case_insensitive_match: UciOptionKind::iter()
+                .find(|kind| kind.name().eq_ignore_ascii_case(s)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator as _;
+
+    use super::*;
+    use crate::options::{Combo, UciOptionNameInfo};
+
+    /// Some data of the type, valid for every standard option of that type.
+    fn sample(r#type: UciOptionType) -> TypedUciOptionData {
+        match r#type {
+            UciOptionType::Spin => TypedUciOptionData::Spin(
+                Spin {
+                    default: 1,
+                    min: 1,
+                    max: 1,
+                }
+                .into(),
+            ),
+            UciOptionType::String => TypedUciOptionData::String(model::UciString::from("auto")),
+            UciOptionType::Button => TypedUciOptionData::Button,
+            UciOptionType::Check => TypedUciOptionData::Check(model::Check(false)),
+            UciOptionType::Combo => TypedUciOptionData::Combo(Combo {
+                default: model::UciString::from("auto"),
+                vars: vec![model::UciString::from("auto")],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_kind_tables_agree() {
+        for kind in UciOptionKind::iter() {
+            assert_eq!(kind.name().parse::<UciOptionKind>().unwrap(), kind);
+
+            let option =
+                UciOption::from_parts(UciOptionNameInfo::Standard(kind), sample(kind.r#type()))
+                    .unwrap_or_else(|e| panic!("{kind:?}: {e:?}"));
+            assert_eq!(option.kind(), Some(kind));
+            assert_eq!(option.name(), kind.name());
+            assert_eq!(option.r#type(), kind.r#type());
+
+            for other in UciOptionType::iter().filter(|&other| other != kind.r#type()) {
+                assert!(
+                    matches!(
+                        kind.option_from_typed_data(sample(other)),
+                        Err(UciOptionFromPartsError::UciOptionDataTypeMismatchError(_))
+                    ),
+                    "{kind:?} from {other:?}"
+                );
+            }
+        }
     }
 }
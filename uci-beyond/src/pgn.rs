@@ -0,0 +1,163 @@
+//! The module for exporting the analysis of a game as [PGN] movetext with the `%eval` and `%clk`
+//! comments of Lichess, e.g.
+//!
+//! ```text
+//! 1. e4 { [%eval 0.38] [%clk 0:05:00] } 1... c5 { [%eval 0.41] [%clk 0:04:58] } 2. Nf3 { [%eval #-3] }
+//! ```
+//!
+//! [PGN]: https://www.chessclub.com/help/PGN-spec
+
+use std::{fmt::Write as _, time::Duration};
+
+use shakmaty::Position as _;
+
+use crate::model::{self, Color, MoveString, Position, SanConversionError, Score};
+
+/// Annotates the moves played from the starting position, see [`annotate_from`].
+pub fn annotate(
+    moves: &[MoveString],
+    evals: &[Score],
+    clock: Option<&[Duration]>,
+) -> Result<String, SanConversionError> {
+    annotate_from(&Position::StartPos, moves, evals, clock)
+}
+
+/// Writes the moves played from the root position as PGN movetext in SAN, with a comment after every move
+/// that has an evaluation or a clock.
+///
+/// * `evals[i]` is the score of the position after `moves[i]`, as reported by the engine, i.e. from the point
+///   of view of the side to move in that position. The comments use the point of view of White, as Lichess does:
+///   `[%eval 0.38]` in pawns or `[%eval #-3]` when Black mates in 3.
+/// * `clock[i]` is the time left to the side that played `moves[i]`, written as `[%clk 0:04:58]`.
+///
+/// The moves without an evaluation or a clock, e.g. because `evals` is shorter than `moves`, have
+/// no such comment. `mate 0` means that the side to move is checkmated, which the move itself already
+/// shows (e.g. `Qh4#`), so it has no `%eval` either.
+///
+/// The movetext has no result, so that it can be put into a PGN with the tags of the game.
+pub fn annotate_from(
+    root: &Position,
+    moves: &[MoveString],
+    evals: &[Score],
+    clock: Option<&[Duration]>,
+) -> Result<String, SanConversionError> {
+    let mut board = root.to_board()?;
+    let mut movetext = String::new();
+    // Black's move needs its number after a comment or at the start of the movetext
+    let mut needs_move_number = true;
+
+    for (ply, mv) in moves.iter().enumerate() {
+        let m = model::san::to_move(&board, mv).ok_or_else(|| SanConversionError::IllegalMove {
+            ply,
+            mv: mv.clone(),
+        })?;
+
+        if !movetext.is_empty() {
+            movetext.push(' ');
+        }
+        let move_number = board.fullmoves();
+        match board.turn() {
+            shakmaty::Color::White => write!(movetext, "{move_number}. ").unwrap(),
+            shakmaty::Color::Black if needs_move_number => {
+                write!(movetext, "{move_number}... ").unwrap()
+            }
+            shakmaty::Color::Black => {}
+        }
+        let san = shakmaty::san::SanPlus::from_move_and_play_unchecked(&mut board, &m);
+        write!(movetext, "{san}").unwrap();
+
+        let side_to_move = match board.turn() {
+            shakmaty::Color::White => Color::White,
+            shakmaty::Color::Black => Color::Black,
+        };
+        let mut comment = Vec::new();
+        if let Some(eval) = evals
+            .get(ply)
+            .copied()
+            .filter(|&eval| eval != Score::Mate(0))
+        {
+            comment.push(format!(
+                "[%eval {}]",
+                format_eval(eval.to_white_pov(side_to_move))
+            ));
+        }
+        if let Some(left) = clock.and_then(|clock| clock.get(ply)) {
+            comment.push(format!("[%clk {}]", format_clock(*left)));
+        }
+        needs_move_number = !comment.is_empty();
+        if !comment.is_empty() {
+            write!(movetext, " {{ {} }}", comment.join(" ")).unwrap();
+        }
+    }
+    Ok(movetext)
+}
+
+/// Formats the White-POV score in pawns with two decimals, e.g. `-0.45`, or as `#3` or `#-3` for a mate.
+fn format_eval(score: Score) -> String {
+    match score {
+        Score::Cp(cp) => format!("{:.2}", f64::from(cp) / 100.0),
+        Score::Mate(moves) => format!("#{moves}"),
+    }
+}
+
+/// Formats the time as `H:MM:SS`, rounding down to whole seconds.
+fn format_clock(left: Duration) -> String {
+    let secs = left.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::FenString;
+
+    /// The hand-verified movetext of the Fool's mate with the analysis below.
+    const FOOLS_MATE: &str = include_str!("pgn/fools_mate.pgn");
+
+    fn moves(s: &str) -> Vec<MoveString> {
+        s.split_whitespace().map(MoveString::from).collect()
+    }
+
+    #[test]
+    fn test_annotate_golden() {
+        let moves = moves("f2f3 e7e5 g2g4 d8h4");
+        // From the point of view of the side to move after each move
+        let evals = [
+            Score::Cp(45),
+            Score::Cp(-60),
+            Score::Mate(1),
+            Score::Mate(0),
+        ];
+        let clock = [
+            Duration::from_secs(300),
+            Duration::from_secs(299),
+            Duration::from_millis(298_500),
+            Duration::from_secs(297),
+        ];
+        assert_eq!(
+            annotate(&moves, &evals, Some(&clock)).unwrap(),
+            FOOLS_MATE.trim_end()
+        );
+    }
+
+    #[test]
+    fn test_annotate_from_black_to_move() {
+        let root = Position::Fen(FenString(
+            "6k1/p4ppp/8/8/8/8/5PPP/3R2K1 b - - 10 40".to_string(),
+        ));
+        // Only the first move has an evaluation
+        let evals = [Score::Mate(1)];
+        assert_eq!(
+            annotate_from(&root, &moves("a7a6 d1d8"), &evals, None).unwrap(),
+            "40... a6 { [%eval #1] } 41. Rd8#"
+        );
+        assert_eq!(
+            annotate_from(&root, &moves("a7a6 a6a5"), &[], None),
+            Err(SanConversionError::IllegalMove {
+                ply: 1,
+                mv: MoveString::from("a6a5")
+            })
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! The commonly used items of the crate, for a glob import:
+//!
+//! ```no_run
+//! use uci_beyond::prelude::*;
+//!
+//! async fn analyse() -> std::io::Result<()> {
+//!     let connection =
+//!         IoConnection::spawn(tokio::process::Command::new("stockfish").kill_on_drop(true))?;
+//!     let mut session = Session::new(connection);
+//!     let response = session.initialize(None).await?.expect("a valid `uci` response");
+//!     let hash = response.option_block.hash.map(|hash| hash.default);
+//!     println!("{} (Hash {hash:?})", response.id_block.name);
+//!
+//!     let connection = session.connection_mut();
+//!     let Ok(()) = connection.send(UciNewGameCommand).await?;
+//!     let Ok(()) = connection
+//!         .send(PositionCommand {
+//!             startpos: Position::StartPos,
+//!             moves: MoveList(vec![MoveString::from("e2e4")]),
+//!         })
+//!         .await?;
+//!
+//!     let go = GoCommand::for_depth(12);
+//!     go.validate().expect("a valid `go` command");
+//!     let search = connection
+//!         .send_expecting_later_response::<_, GoResponse>(go)
+//!         .await?;
+//!     let Ok(()) = connection.send(StopCommand).await?;
+//!     let response = search.await?.expect("a valid `go` response");
+//!     if let Some(Score::Cp(cp)) = response.search_summary.score {
+//!         println!("{}", Wdl::from_cp_and_ply(cp, GamePly(1)));
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The parsing errors, the Stockfish extensions and the less common utilities are
+//! only available from their modules.
+
+pub use crate::{
+    capabilities::EngineCapabilities,
+    engine_commands::{
+        BestMoveCommand, DepthInfoCommand, IdBlock, InfoCommand, OptionCommand, ReadyOkCommand,
+        StringInfoCommand, UciOkCommand, UciOptionBlock, UciOptionBlockBuilder,
+    },
+    engine_quirks::{EngineFamily, Quirks},
+    gui_command_responses::{
+        GoEvent, GoResponse, InfoStringBlock, ReadLimits, ReadOptions, SearchOutcome,
+        SearchSummary, UciResponse,
+    },
+    gui_commands::{
+        AnyGuiCommand, GoCommand, IsReadyCommand, PositionCommand, QuitCommand, SetOptionCommand,
+        StopCommand, UciCommand, UciCommandTrait, UciNewGameCommand,
+    },
+    model::{
+        Check, Clock, Color, CpuSet, FenString, GamePly, MoveList, MoveString, NumaPolicy,
+        Position, Score, SyzygyPathSpec, UciString, Wdl,
+    },
+    options::{Spin, SpinBuilder, UciOption, UciOptionKind},
+    session::Session,
+    util::{
+        AsyncReadable, AsyncWritable, Connection, IoConnection, LineWriter, ResponseHandle,
+        StreamingLineReader,
+    },
+};
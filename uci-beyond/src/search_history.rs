@@ -0,0 +1,161 @@
+//! The module for [`SearchHistory`], i.e. the recent searches of a [`Session`](crate::session::Session),
+//! e.g. for a bot to look back at what it has just played and why.
+
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{
+    engine_commands::BestMoveCommand,
+    gui_command_responses::SearchSummary,
+    gui_commands::{GoCommand, PositionCommand},
+};
+
+/// The default capacity of [`SearchHistory`].
+pub const DEFAULT_SEARCH_HISTORY_CAPACITY: usize = 16;
+
+/// The default number of moves of the principal variation kept per search, see [`SearchHistory::with_max_pv_len`].
+pub const DEFAULT_MAX_PV_LEN: usize = 32;
+
+/// A search that has ended with `bestmove`, as kept by the [`SearchHistory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletedSearch {
+    pub position: PositionCommand,
+    pub go_command: GoCommand,
+    /// The summary of the search, with the principal variation cut to [`SearchHistory::max_pv_len`].
+    pub summary: SearchSummary,
+    /// The time from sending `go` to reading `bestmove`, as measured by the GUI.
+    pub wall_time: Duration,
+}
+
+impl CompletedSearch {
+    /// The best move, unless the engine answered without one, e.g. with `bestmove (none)` in a terminal position.
+    pub fn best_move(&self) -> Option<&BestMoveCommand> {
+        self.summary.best_move.as_ref()
+    }
+}
+
+/// The last [`CompletedSearch`]es, oldest first, evicting the oldest one when full.
+#[derive(Debug, Clone)]
+pub struct SearchHistory {
+    capacity: usize,
+    max_pv_len: usize,
+    searches: VecDeque<CompletedSearch>,
+}
+
+impl Default for SearchHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEARCH_HISTORY_CAPACITY)
+    }
+}
+
+impl SearchHistory {
+    /// Creates a history of at most `capacity` searches, with [`DEFAULT_MAX_PV_LEN`] moves of every principal variation.
+    ///
+    /// A zero capacity keeps nothing.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            max_pv_len: DEFAULT_MAX_PV_LEN,
+            searches: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Keeps at most `max_pv_len` moves of the principal variation of every search, since a long search of
+    /// an engine like Lc0 may report lines of hundreds of moves.
+    pub fn with_max_pv_len(mut self, max_pv_len: usize) -> Self {
+        self.max_pv_len = max_pv_len;
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn max_pv_len(&self) -> usize {
+        self.max_pv_len
+    }
+
+    /// The number of kept searches.
+    pub fn len(&self) -> usize {
+        self.searches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.searches.is_empty()
+    }
+
+    /// Forgets the kept searches.
+    pub fn clear(&mut self) {
+        self.searches.clear();
+    }
+
+    /// Appends the search, evicting the oldest one if the history is full.
+    pub fn push(&mut self, mut search: CompletedSearch) {
+        if self.capacity == 0 {
+            return;
+        }
+        search.summary.pv.truncate(self.max_pv_len);
+        if self.searches.len() == self.capacity {
+            self.searches.pop_front();
+        }
+        self.searches.push_back(search);
+    }
+
+    /// The most recent search.
+    pub fn last(&self) -> Option<&CompletedSearch> {
+        self.searches.back()
+    }
+
+    /// The kept searches, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &CompletedSearch> + ExactSizeIterator {
+        self.searches.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model;
+
+    fn search(depth: u32, pv: &str) -> CompletedSearch {
+        CompletedSearch {
+            position: PositionCommand {
+                startpos: model::Position::StartPos,
+                moves: model::MoveList::default(),
+            },
+            go_command: GoCommand::for_depth(depth),
+            summary: SearchSummary {
+                depth: Some(depth),
+                pv: pv.split_whitespace().map(model::MoveString::from).collect(),
+                ..Default::default()
+            },
+            wall_time: Duration::from_millis(10),
+        }
+    }
+
+    #[test]
+    fn test_eviction_and_pv_cap() {
+        let mut history = SearchHistory::new(2).with_max_pv_len(2);
+        assert!(history.last().is_none());
+
+        history.push(search(1, "e2e4"));
+        history.push(search(2, "e2e4 e7e5"));
+        history.push(search(3, "e2e4 e7e5 g1f3"));
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history
+                .iter()
+                .map(|search| search.summary.depth)
+                .collect::<Vec<_>>(),
+            [Some(2), Some(3)]
+        );
+        assert_eq!(history.last().unwrap().summary.pv.len(), 2);
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut history = SearchHistory::new(0);
+        history.push(search(1, "e2e4"));
+        assert!(history.is_empty());
+    }
+}
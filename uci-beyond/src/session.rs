@@ -0,0 +1,1835 @@
+//! The module for [`Session`], i.e. a [`Connection`] to an engine together with the conveniences
+//! that span several commands, e.g. sending a batch of `setoption` commands in a safe order.
+
+use std::{
+    convert::Infallible,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    analysis_cache::AnalysisCache,
+    capabilities, command,
+    engine_commands::{AnyEngineCommandKind, BestMoveCommand},
+    engine_quirks::Quirks,
+    gui_command_responses::{
+        GoResponse, InfoStringBlock, ReadLimits, ReadOptions, ReadUsage, SearchSummary,
+        UciResponse, UciResponseParsingError,
+    },
+    gui_commands::{
+        GoCommand, GoCommandValidationError, PonderHitCommand, PositionCommand, SetOptionCommand,
+        StopCommand, UciNewGameCommand,
+    },
+    hash_maintenance::HashMaintenance,
+    model::{CastlingNotation, FenString, MoveString, Score},
+    options::{OptionSnapshot, OptionState, OptionValue, UciOptionKind, ordering::sort_setoptions},
+    search_history::{CompletedSearch, SearchHistory},
+    util::{AsyncReadable, CancelToken, Connection, LineCountingReader, ResponseHandle},
+};
+
+/// The order in which [`Session::configure_with`] sends the `setoption` commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SetOptionOrder {
+    /// Reorder the commands according to the known dependencies between options.
+    ///
+    /// See [`sort_setoptions`] for the rules.
+    #[default]
+    Dependencies,
+    /// Send the commands exactly in the given order.
+    AsGiven,
+}
+
+/// The error of a [`Session`] search, e.g. of [`Session::analyze_cached`].
+#[derive(thiserror::Error, Debug)]
+pub enum SessionError<E> {
+    /// The `go` command wasn't sent since it failed [`GoCommand::validate_for`],
+    /// see [`Session::with_go_validation`].
+    #[error("Invalid go command: {0}")]
+    InvalidGo(#[from] GoCommandValidationError),
+    /// The connection to the engine failed.
+    #[error("Connection error: {0:?}")]
+    Connection(E),
+}
+
+/// The evaluation of a position by [`Session::evaluate_positions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionEvaluation {
+    pub fen: FenString,
+    /// The best move, unless the engine answered without one, e.g. with `bestmove (none)` in a terminal position.
+    pub best_move: Option<BestMoveCommand>,
+    /// The final evaluation from the point of view of the side to move.
+    pub score: Option<Score>,
+    /// The maximum search depth reached.
+    pub depth: Option<u32>,
+    /// The elapsed search time, as reported by the engine.
+    pub time: Option<Duration>,
+    /// The options that differed from their defaults when `go` was sent.
+    pub options: Option<OptionSnapshot>,
+}
+
+impl PositionEvaluation {
+    fn from_summary(fen: FenString, summary: SearchSummary) -> Self {
+        Self {
+            fen,
+            best_move: summary.best_move,
+            score: summary.score,
+            depth: summary.depth,
+            time: summary.time.map(Duration::from_millis),
+            options: summary.options,
+        }
+    }
+}
+
+/// The `info string` lines printed by the engine after a `setoption` command and before `readyok`,
+/// see [`Session::set_option_synced`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionChangeFeedback {
+    pub option: SetOptionCommand,
+    pub info: InfoStringBlock,
+}
+
+impl OptionChangeFeedback {
+    /// Whether the lines confirm the new value, i.e. `info string Using N threads` for `Threads`, or a network
+    /// with the file name of the value for `EvalFile` and `EvalFileSmall`, e.g. after the engine reloaded it.
+    ///
+    /// `None` for the other options or if the engine printed nothing about the option.
+    pub fn confirmed(&self) -> Option<bool> {
+        match &self.option {
+            SetOptionCommand::Threads { value } => {
+                self.info.used_threads().map(|used| used.threads == *value)
+            }
+            SetOptionCommand::EvalFile { value } | SetOptionCommand::EvalFileSmall { value } => {
+                let mut nets = self.info.nnue_evaluations().peekable();
+                nets.peek()?;
+                let configured = capabilities::file_name(value);
+                Some(nets.any(|net| capabilities::file_name(&net.name) == configured))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How [`Session::evaluate_positions_with`] goes through the positions.
+#[derive(Default)]
+pub struct EvaluatePositionsOptions<'a> {
+    /// Doesn't send `ucinewgame` before every position, which is faster but lets the hash table
+    /// of the previous searches affect the evaluations.
+    pub skip_new_game: bool,
+    /// Called after every position with the number of the positions evaluated so far.
+    pub on_progress: Option<&'a mut dyn FnMut(usize)>,
+    /// Stops before the next position once cancelled. The search in progress, if any, is completed first.
+    pub cancel: Option<CancelToken>,
+}
+
+/// A [`Connection`] to an engine.
+///
+/// With the `tracing` feature, the handshake, every `setoption` and every search are in their own spans,
+/// i.e. `uci_handshake`, `configure` with a `setoption` span per command, and `search`, with the name of the engine
+/// attached. The parsing errors are reported as warn-level events.
+pub struct Session<C> {
+    connection: C,
+    quirks: Quirks,
+    analysis_cache: Option<AnalysisCache>,
+    hash_maintenance: Option<HashMaintenance>,
+    options: OptionState,
+    engine_name: Option<String>,
+    search_history: SearchHistory,
+    validate_go: bool,
+    last_response_lines: Option<usize>,
+}
+
+impl<C: Connection> Session<C> {
+    pub fn new(connection: C) -> Self {
+        Self {
+            connection,
+            quirks: Quirks::default(),
+            analysis_cache: None,
+            hash_maintenance: None,
+            options: OptionState::default(),
+            engine_name: None,
+            search_history: SearchHistory::default(),
+            validate_go: false,
+            last_response_lines: None,
+        }
+    }
+
+    /// Keeps the results of [`Session::analyze_cached`] in the cache.
+    pub fn with_analysis_cache(mut self, cache: AnalysisCache) -> Self {
+        self.analysis_cache = Some(cache);
+        self
+    }
+
+    pub fn analysis_cache(&self) -> Option<&AnalysisCache> {
+        self.analysis_cache.as_ref()
+    }
+
+    pub fn analysis_cache_mut(&mut self) -> Option<&mut AnalysisCache> {
+        self.analysis_cache.as_mut()
+    }
+
+    /// Follows the `hashfull` of the searches with the [`HashMaintenance`], which sends `setoption name Clear Hash`
+    /// after a search when [`auto_clear`](crate::hash_maintenance::HashMaintenanceConfig::auto_clear) is set
+    /// and the hash table stayed saturated.
+    pub fn with_hash_maintenance(mut self, maintenance: HashMaintenance) -> Self {
+        self.hash_maintenance = Some(maintenance);
+        self
+    }
+
+    pub fn hash_maintenance(&self) -> Option<&HashMaintenance> {
+        self.hash_maintenance.as_ref()
+    }
+
+    pub fn hash_maintenance_mut(&mut self) -> Option<&mut HashMaintenance> {
+        self.hash_maintenance.as_mut()
+    }
+
+    /// Checks the `go` command of every search, e.g. of [`Session::analyze_cached`] or [`Session::start_ponder`], with
+    /// [`GoCommand::validate_for`] before sending it. An invalid search isn't sent and fails with
+    /// [`SessionError::InvalidGo`].
+    pub fn with_go_validation(mut self) -> Self {
+        self.validate_go = true;
+        self
+    }
+
+    /// Keeps the completed searches in the history instead of the default one,
+    /// e.g. with a bigger capacity or without any (see [`SearchHistory::new`]).
+    pub fn with_search_history(mut self, history: SearchHistory) -> Self {
+        self.search_history = history;
+        self
+    }
+
+    /// The searches that have ended with `bestmove`, oldest first, up to the capacity of the [`SearchHistory`].
+    ///
+    /// The searches answered from the [`AnalysisCache`] aren't made, so they aren't there.
+    pub fn searches(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &CompletedSearch> + ExactSizeIterator {
+        self.search_history.iter()
+    }
+
+    /// The most recent search, see [`Session::searches`].
+    pub fn last_search(&self) -> Option<&CompletedSearch> {
+        self.search_history.last()
+    }
+
+    pub fn search_history_mut(&mut self) -> &mut SearchHistory {
+        &mut self.search_history
+    }
+
+    /// The quirks of the engine, selected by [`Session::initialize`].
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    /// The current values of the options advertised in [`Session::initialize`], updated by [`Session::configure`].
+    ///
+    /// The `setoption` commands sent directly through [`Session::connection_mut`] aren't tracked.
+    pub fn current_options(&self) -> &OptionState {
+        &self.options
+    }
+
+    /// Whether `UCI_Chess960` is enabled according to [`Session::current_options`].
+    ///
+    /// `false` if the engine doesn't advertise the option.
+    pub fn chess960(&self) -> bool {
+        self.options.get_kind(UciOptionKind::UCIChess960) == Some(&OptionValue::Check(true))
+    }
+
+    /// The notation of the castling moves of the engine, e.g. to normalize them for a GUI that expects the other one
+    /// with `MoveString::normalize_castling` (`board` feature).
+    pub fn castling_notation(&self) -> CastlingNotation {
+        CastlingNotation::from_chess960(self.chess960())
+    }
+
+    /// The `id name` of the engine, known after [`Session::initialize`].
+    pub fn engine_name(&self) -> Option<&str> {
+        self.engine_name.as_deref()
+    }
+
+    /// The number of lines consumed by the last response read by the session, i.e. of the handshake or
+    /// of a search, incl. the line at which its parsing failed, if it did, e.g. to tell how far the parser got.
+    ///
+    /// The lines of a search are counted by the connection (see [`Connection::last_response_lines`]),
+    /// so they may be unknown. With the `tracing` feature, the count is attached to the warn-level events
+    /// of the parsing errors as `lines_consumed`.
+    pub fn last_response_lines(&self) -> Option<usize> {
+        self.last_response_lines
+    }
+
+    pub fn connection(&self) -> &C {
+        &self.connection
+    }
+
+    pub fn connection_mut(&mut self) -> &mut C {
+        &mut self.connection
+    }
+
+    pub fn into_inner(self) -> C {
+        self.connection
+    }
+
+    /// Sends the `uci` command and reads the response with the [`Quirks`] of the engine.
+    ///
+    /// The quirks are selected from the id block of the response (see [`Quirks::for_id_block`])
+    /// unless they are overridden with `quirks`. Either way, they are kept as [`Session::quirks`].
+    ///
+    /// The response is bounded by the default [`ReadLimits`] except for the deadline.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "uci_handshake", skip_all, fields(engine = tracing::field::Empty))
+    )]
+    pub async fn initialize(
+        &mut self,
+        quirks: Option<Quirks>,
+    ) -> Result<Result<UciResponse, command::parsing::Error<UciResponseParsingError>>, C::Err> {
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+        self.connection.send_raw_line("uci").await?;
+
+        // The quirks are known only after the id block, so the response is read in full first
+        let limits = ReadLimits::default();
+        let mut usage = ReadUsage::default();
+        let mut transcript = String::new();
+        let mut lines_read = 0;
+        while let Some(line) = self.connection.read_raw_line().await? {
+            lines_read += 1;
+            if let Err(e) = usage.read_line(&line, &limits) {
+                self.last_response_lines = Some(lines_read);
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %e, lines_consumed = lines_read, "failed to read the uci handshake");
+                return Ok(Err(command::parsing::Error::CustomError(
+                    UciResponseParsingError::LimitExceeded(e),
+                )));
+            }
+            let is_last = line.trim() == "uciok";
+            transcript.push_str(&line);
+            transcript.push('\n');
+            if is_last {
+                break;
+            }
+        }
+
+        let mut reader = LineCountingReader::new(tokio::io::BufReader::new(transcript.as_bytes()));
+        let options = ReadOptions {
+            quirks,
+            ..Default::default()
+        };
+        #[cfg(feature = "tracing")]
+        let options =
+            options.with_observer(std::sync::Arc::new(crate::util::TracingObserver::default()));
+        let response = UciResponse::read_with_options(&mut reader, options)
+            .await
+            .expect("Reading from memory doesn't fail");
+        let lines_consumed = reader.lines_consumed();
+        self.last_response_lines = Some(lines_consumed);
+        let response = match response {
+            Some(Ok(response)) => response,
+            Some(Err(e)) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = ?e, lines_consumed, "failed to parse the uci handshake");
+                return Ok(Err(e));
+            }
+            None => {
+                return Ok(Err(command::parsing::Error::CustomError(
+                    UciResponseParsingError::IncompleteResponse,
+                )));
+            }
+        };
+
+        self.quirks = quirks.unwrap_or_else(|| Quirks::for_id_block(&response.id_block));
+        self.options = OptionState::from_defaults(&response.option_block);
+        self.engine_name = Some(response.id_block.name.clone());
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Span::current().record("engine", response.id_block.name.as_str());
+            tracing::info!(
+                options = response.option_block.options().len(),
+                elapsed = ?started.elapsed(),
+                "completed the uci handshake"
+            );
+        }
+        Ok(Ok(response))
+    }
+
+    /// Sends the `setoption` commands, reordered according to the known dependencies between options
+    /// (see [`sort_setoptions`]).
+    ///
+    /// Use [`Session::configure_with`] with [`SetOptionOrder::AsGiven`] to send them as they are.
+    pub async fn configure(&mut self, commands: Vec<SetOptionCommand>) -> Result<(), C::Err> {
+        self.configure_with(commands, SetOptionOrder::default())
+            .await
+    }
+
+    /// Sends the `setoption` commands in the given [`SetOptionOrder`].
+    ///
+    /// The options are also recorded in the [`Session::current_options`] and in the [`AnalysisCache`], if any.
+    /// A command that doesn't fit the advertised options is sent anyway but leaves the current options unchanged;
+    /// with the `tracing` feature, it is reported as a warn-level event. A command that fails to be sent
+    /// isn't recorded either.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "configure",
+            skip_all,
+            fields(engine = self.engine_name.as_deref(), commands = commands.len())
+        )
+    )]
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub async fn configure_with(
+        &mut self,
+        commands: Vec<SetOptionCommand>,
+        order: SetOptionOrder,
+    ) -> Result<(), C::Err> {
+        let commands = match order {
+            SetOptionOrder::Dependencies => sort_setoptions(commands),
+            SetOptionOrder::AsGiven => commands,
+        };
+        for cmd in commands {
+            self.send_setoption(cmd.clone()).await?;
+            if let Some(cache) = &mut self.analysis_cache {
+                cache.observe_setoption(&cmd);
+            }
+            if let Err(error) = self.options.apply(&cmd) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(%error, "the current options don't track the option");
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the `setoption` command followed by `isready` and collects the `info string` lines printed before
+    /// `readyok` as the [`OptionChangeFeedback`], e.g. `info string Using 4 threads` after `Threads` or the
+    /// networks after `EvalFile`, which confirms that the engine has reloaded them.
+    ///
+    /// The option is recorded like by [`Session::configure`]. The other lines before `readyok` are skipped,
+    /// and the end of the output before it is [`command::parsing::Error::UnexpectedEof`].
+    pub async fn set_option_synced(
+        &mut self,
+        cmd: SetOptionCommand,
+    ) -> Result<Result<OptionChangeFeedback, command::parsing::Error<Infallible>>, C::Err> {
+        self.configure_with(vec![cmd.clone()], SetOptionOrder::AsGiven)
+            .await?;
+        self.connection.send_raw_line("isready").await?;
+
+        let mut info = InfoStringBlock::default();
+        loop {
+            let Some(line) = self.connection.read_raw_line().await? else {
+                return Ok(Err(command::parsing::Error::UnexpectedEof));
+            };
+            let line = line.trim();
+            if line == "readyok" {
+                break;
+            }
+            if let Some(text) = line.strip_prefix("info string ") {
+                info.push(text);
+            }
+        }
+        Ok(Ok(OptionChangeFeedback { option: cmd, info }))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "setoption", skip_all, fields(option = cmd.name()))
+    )]
+    async fn send_setoption(&mut self, cmd: SetOptionCommand) -> Result<(), C::Err> {
+        let Ok(()) = self.connection.send(cmd).await?;
+        Ok(())
+    }
+
+    /// Analyzes the position, answering from the [`AnalysisCache`] when the same search was made before
+    /// with the same options.
+    ///
+    /// Otherwise, `position` and `go` are sent and the [`SearchSummary`] of the search is cached
+    /// once it has completed with `bestmove`. The search must end by itself, e.g. with `depth` or `movetime`,
+    /// since `stop` isn't sent. Without an analysis cache, the engine is asked every time.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "search",
+            skip_all,
+            fields(engine = self.engine_name.as_deref(), cached = tracing::field::Empty)
+        )
+    )]
+    pub async fn analyze_cached(
+        &mut self,
+        position: PositionCommand,
+        go: GoCommand,
+    ) -> Result<Result<SearchSummary, <GoResponse as AsyncReadable>::Err>, SessionError<C::Err>>
+    {
+        self.check_go(&position, &go)?;
+        let key = self
+            .analysis_cache
+            .as_ref()
+            .map(|cache| cache.key(&position, &go));
+        if let (Some(cache), Some(key)) = (&mut self.analysis_cache, &key)
+            && let Some(summary) = cache.get(key)
+        {
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("cached", true);
+            return Ok(Ok(summary.clone()));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("cached", false);
+
+        let summary = match self.search(position, go).await? {
+            Ok(response) => response.search_summary,
+            Err(e) => return Ok(Err(e)),
+        };
+
+        if let (Some(cache), Some(key)) = (&mut self.analysis_cache, key)
+            && summary.best_move.is_some()
+        {
+            cache.insert(key, summary.clone());
+        }
+        Ok(Ok(summary))
+    }
+
+    /// Starts pondering on the `predicted_move` of the opponent, e.g. the `ponder` move of the last `bestmove`,
+    /// i.e. sends the `position` with the move played and `go ponder` with the time controls of `go`.
+    ///
+    /// The engine doesn't end the search by itself, so once the opponent has moved, the [`PonderHandle`]
+    /// must be resolved with [`PonderHandle::hit`] or [`PonderHandle::miss`].
+    ///
+    /// Like the other searches, `go ponder` isn't sent if it's invalid, see [`Session::with_go_validation`].
+    pub async fn start_ponder(
+        &mut self,
+        mut position: PositionCommand,
+        predicted_move: MoveString,
+        go: GoCommand,
+    ) -> Result<PonderHandle<'_, C>, SessionError<C::Err>> {
+        position.moves.0.push(predicted_move);
+        let go = GoCommand { ponder: true, ..go };
+        self.check_go(&position, &go)?;
+        let search = self
+            .start_search(position, go)
+            .await
+            .map_err(SessionError::Connection)?;
+        Ok(PonderHandle {
+            session: self,
+            search,
+        })
+    }
+
+    /// Evaluates the positions one after another with the same `go` command, e.g. for an EPD test suite,
+    /// sending `ucinewgame`, `position` and `go` for every position.
+    ///
+    /// See [`Session::evaluate_positions_with`].
+    pub async fn evaluate_positions<I>(
+        &mut self,
+        fens: I,
+        go: &GoCommand,
+    ) -> Result<
+        Vec<Result<PositionEvaluation, <GoResponse as AsyncReadable>::Err>>,
+        SessionError<C::Err>,
+    >
+    where
+        I: IntoIterator<Item = FenString>,
+    {
+        self.evaluate_positions_with(fens, go, EvaluatePositionsOptions::default())
+            .await
+    }
+
+    /// Evaluates the positions one after another with the same `go` command and the [`EvaluatePositionsOptions`].
+    ///
+    /// The evaluations are in the order of the positions. A position whose response can't be parsed has an error
+    /// and the next positions are still evaluated, while an error of the connection, or an invalid `go` command
+    /// (see [`Session::with_go_validation`]), ends the batch. The search must end by itself, e.g. with `depth`
+    /// or `movetime`, since `stop` isn't sent.
+    ///
+    /// Once [`EvaluatePositionsOptions::cancel`] is cancelled, the remaining positions are skipped, so there are
+    /// fewer evaluations than positions.
+    pub async fn evaluate_positions_with<I>(
+        &mut self,
+        fens: I,
+        go: &GoCommand,
+        mut options: EvaluatePositionsOptions<'_>,
+    ) -> Result<
+        Vec<Result<PositionEvaluation, <GoResponse as AsyncReadable>::Err>>,
+        SessionError<C::Err>,
+    >
+    where
+        I: IntoIterator<Item = FenString>,
+    {
+        let mut evaluations = Vec::new();
+        for fen in fens {
+            if options
+                .cancel
+                .as_ref()
+                .is_some_and(CancelToken::is_cancelled)
+            {
+                break;
+            }
+            let evaluation = self
+                .evaluate_position(fen, go, options.skip_new_game)
+                .await?;
+            evaluations.push(evaluation);
+
+            if let Some(on_progress) = options.on_progress.as_mut() {
+                on_progress(evaluations.len());
+            }
+        }
+        Ok(evaluations)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "search",
+            skip_all,
+            fields(engine = self.engine_name.as_deref(), fen = fen.0.as_str())
+        )
+    )]
+    async fn evaluate_position(
+        &mut self,
+        fen: FenString,
+        go: &GoCommand,
+        skip_new_game: bool,
+    ) -> Result<Result<PositionEvaluation, <GoResponse as AsyncReadable>::Err>, SessionError<C::Err>>
+    {
+        let position = PositionCommand::from_fen(fen.clone());
+        self.check_go(&position, go)?;
+        if !skip_new_game {
+            let Ok(()) = self
+                .connection
+                .send(UciNewGameCommand)
+                .await
+                .map_err(SessionError::Connection)?;
+        }
+        let evaluation = self
+            .search(position, go.clone())
+            .await?
+            .map(|response| PositionEvaluation::from_summary(fen, response.search_summary));
+        Ok(evaluation)
+    }
+
+    /// Sends `position` and `go` and reads the response, which must end by itself.
+    ///
+    /// The [`SearchSummary::options`] are the ones in effect when `go` is sent, since the session
+    /// can't send `setoption` in the middle of the search.
+    ///
+    /// The completed search is kept in the [`SearchHistory`] and observed by the [`HashMaintenance`], if any,
+    /// which may clear the hash table before the next search.
+    ///
+    /// The `go` command isn't validated, see [`Session::check_go`].
+    async fn search(
+        &mut self,
+        position: PositionCommand,
+        go: GoCommand,
+    ) -> Result<Result<GoResponse, <GoResponse as AsyncReadable>::Err>, SessionError<C::Err>> {
+        let search = self
+            .start_search(position, go)
+            .await
+            .map_err(SessionError::Connection)?;
+        self.complete_search(search)
+            .await
+            .map_err(SessionError::Connection)
+    }
+
+    /// Checks the `go` command with [`GoCommand::validate_for`] if the session validates them,
+    /// see [`Session::with_go_validation`].
+    fn check_go(
+        &self,
+        position: &PositionCommand,
+        go: &GoCommand,
+    ) -> Result<(), GoCommandValidationError> {
+        if self.validate_go {
+            go.validate_for(position)?;
+        }
+        Ok(())
+    }
+
+    /// Sends `position` and `go`, taking the snapshot of the options for the [`SearchSummary`].
+    async fn start_search(
+        &mut self,
+        position: PositionCommand,
+        go: GoCommand,
+    ) -> Result<StartedSearch<C::Err>, C::Err> {
+        let Ok(()) = self.connection.send(position.clone()).await?;
+        let started = Instant::now();
+        let options = self.options.snapshot();
+        let response = self
+            .connection
+            .send_expecting_later_response::<_, GoResponse>(go.clone())
+            .await?;
+        Ok(StartedSearch {
+            position,
+            go,
+            started,
+            options,
+            response,
+        })
+    }
+
+    /// Reads the response of the search up to `bestmove`, keeps it in the [`SearchHistory`]
+    /// and lets the [`HashMaintenance`] observe it.
+    async fn complete_search(
+        &mut self,
+        search: StartedSearch<C::Err>,
+    ) -> Result<Result<GoResponse, <GoResponse as AsyncReadable>::Err>, C::Err> {
+        let StartedSearch {
+            position,
+            go,
+            started,
+            options,
+            response,
+        } = search;
+        let response = response.await;
+        self.last_response_lines = self.connection.last_response_lines();
+        let response = response?.map(|mut response| {
+            response.search_summary.options = Some(options);
+            response
+        });
+        if let Ok(response) = &response {
+            self.search_history.push(CompletedSearch {
+                position,
+                go_command: go,
+                summary: response.search_summary.clone(),
+                wall_time: started.elapsed(),
+            });
+        }
+        #[cfg(feature = "tracing")]
+        match &response {
+            Ok(response) => tracing::info!(
+                depth = response.search_summary.depth,
+                elapsed = ?started.elapsed(),
+                "completed the search"
+            ),
+            Err(error) => tracing::warn!(
+                ?error,
+                lines_consumed = self.last_response_lines,
+                "failed to parse the search"
+            ),
+        }
+
+        let clear_hash = match (&mut self.hash_maintenance, &response) {
+            (Some(maintenance), Ok(response)) => {
+                maintenance.observe_response(response);
+                maintenance
+                    .suggestion()
+                    .filter(|_| maintenance.config().auto_clear)
+            }
+            _ => None,
+        };
+        if let Some(clear_hash) = clear_hash {
+            self.configure_with(vec![clear_hash], SetOptionOrder::AsGiven)
+                .await?;
+            if let Some(maintenance) = &mut self.hash_maintenance {
+                maintenance.record_clear();
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// A search whose `go` has been sent, see [`Session::complete_search`].
+struct StartedSearch<E> {
+    position: PositionCommand,
+    go: GoCommand,
+    started: Instant,
+    options: OptionSnapshot,
+    response: ResponseHandle<GoResponse, E>,
+}
+
+/// A search that has been sent, whose response isn't read yet, e.g. after [`PonderHandle::hit`].
+///
+/// The session is borrowed until the response is read with [`RunningSearch::finish`], since the engine
+/// accepts no other command than `stop` in the meantime.
+#[must_use = "the response of the search is only read by `finish`"]
+pub struct RunningSearch<'a, C: Connection> {
+    session: &'a mut Session<C>,
+    search: StartedSearch<C::Err>,
+}
+
+impl<C: Connection> RunningSearch<'_, C> {
+    /// Sends `stop`, so that the engine ends the search with `bestmove` without waiting for its limits,
+    /// e.g. the time controls of the `go` command.
+    pub async fn stop(&mut self) -> Result<(), C::Err> {
+        let Ok(()) = self.session.connection.send(StopCommand).await?;
+        Ok(())
+    }
+
+    /// Reads the response of the search up to `bestmove`.
+    ///
+    /// Like the other searches of the session, it is kept in the [`SearchHistory`], and its
+    /// [`SearchSummary::options`] are the ones in effect when `go` was sent. After [`PonderHandle::hit`],
+    /// the response is the one of `go ponder`, the `position` in the history ends with the predicted move,
+    /// and the [`CompletedSearch::wall_time`] includes the time spent pondering.
+    pub async fn finish(
+        self,
+    ) -> Result<Result<GoResponse, <GoResponse as AsyncReadable>::Err>, C::Err> {
+        let RunningSearch { session, search } = self;
+        session.complete_search(search).await
+    }
+}
+
+/// A search pondering on the predicted move of the opponent, started by [`Session::start_ponder`].
+///
+/// The session is borrowed until the move of the opponent is known, since the engine accepts
+/// no other command than `ponderhit` and `stop` in the meantime.
+#[must_use = "the engine ponders until the handle is resolved with `hit` or `miss`"]
+pub struct PonderHandle<'a, C: Connection> {
+    session: &'a mut Session<C>,
+    search: StartedSearch<C::Err>,
+}
+
+impl<'a, C: Connection> PonderHandle<'a, C> {
+    /// The opponent has played the predicted move: sends `ponderhit`, so the engine carries on with the same search
+    /// as a normal one, under the time controls of the `go` command, and hands the search over as it runs.
+    ///
+    /// The response of `go ponder` is still unread when `ponderhit` is sent, so the lines before and after it
+    /// are all read by that response, including the events sent while pondering. Nothing else reads the output
+    /// in the meantime, since the session stays borrowed, so the lines don't need a [`Router`](crate::util::Router)
+    /// to reach the search.
+    pub async fn hit(self) -> Result<RunningSearch<'a, C>, C::Err> {
+        let PonderHandle { session, search } = self;
+        let Ok(()) = session.connection.send(PonderHitCommand).await?;
+        Ok(RunningSearch { session, search })
+    }
+
+    /// The opponent has played another move: sends `stop`, skips the output of the stale search up to
+    /// its `bestmove`, which must not be played, and searches the actual `position` with `go`
+    /// like [`Session::analyze_cached`] without the cache.
+    ///
+    /// The stale output is skipped line by line, so neither a malformed line nor an acknowledgment of `stop`
+    /// (see [`Quirks::ack_before_bestmove`]) is taken for the output of the new search. The end of the output
+    /// before the stale `bestmove` is [`command::parsing::Error::UnexpectedEof`], and the new search isn't started.
+    /// The stale search isn't kept in the [`SearchHistory`].
+    ///
+    /// The stale search is stopped even if the new `go` command is invalid (see [`Session::with_go_validation`]).
+    pub async fn miss(
+        self,
+        position: PositionCommand,
+        go: GoCommand,
+    ) -> Result<Result<GoResponse, <GoResponse as AsyncReadable>::Err>, SessionError<C::Err>> {
+        let PonderHandle { session, search } = self;
+        // The stale response is never read, so its lines are left to the loop below
+        drop(search);
+        let Ok(()) = session
+            .connection
+            .send(StopCommand)
+            .await
+            .map_err(SessionError::Connection)?;
+        loop {
+            let Some(line) = session
+                .connection
+                .read_raw_line()
+                .await
+                .map_err(SessionError::Connection)?
+            else {
+                return Ok(Err(command::parsing::Error::UnexpectedEof));
+            };
+            if AnyEngineCommandKind::of_line(&line) == AnyEngineCommandKind::BestMove {
+                break;
+            }
+        }
+        session.check_go(&position, &go)?;
+        session.search(position, go).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::{
+        engine_quirks::EngineFamily,
+        fixtures::{
+            DuplexConnection, FakeEngine, GO_DEPTH5_TRANSCRIPT, LC0_UCI_RESPONSE,
+            STOCKFISH_UCI_RESPONSE,
+        },
+        gui_commands::UciCommandTrait,
+        hash_maintenance::HashMaintenanceConfig,
+        model,
+        options::{TypedUciOptionData, UciOption},
+        util::{AsyncReadable, IoConnection, ResponseHandle},
+    };
+
+    /// Records the sent commands and answers each of them with no output, except for the raw lines.
+    #[derive(Default)]
+    struct RecordingConnection {
+        sent: Vec<String>,
+        /// The lines returned by [`Connection::read_raw_line`].
+        output: VecDeque<String>,
+        /// The output read by every response expected later, e.g. of `go`.
+        later_output: &'static str,
+        /// Fails the commands sent with [`Connection::send`] as if the engine was gone.
+        broken: bool,
+    }
+
+    #[async_trait(?Send)]
+    impl Connection for RecordingConnection {
+        type Err = std::io::Error;
+
+        async fn send<C>(
+            &mut self,
+            cmd: C,
+        ) -> Result<Result<C::Response, <C::Response as AsyncReadable>::Err>, Self::Err>
+        where
+            C: UciCommandTrait,
+            C::Response: AsyncReadable,
+        {
+            if self.broken {
+                return Err(std::io::ErrorKind::BrokenPipe.into());
+            }
+            self.sent.push(cmd.to_string());
+            let mut reader = tokio::io::BufReader::new(&b""[..]);
+            C::Response::read_from(&mut reader)
+                .await?
+                .ok_or_else(|| std::io::ErrorKind::UnexpectedEof.into())
+        }
+
+        async fn send_expecting_later_response<C, T>(
+            &mut self,
+            cmd: C,
+        ) -> Result<ResponseHandle<T, Self::Err>, Self::Err>
+        where
+            C: UciCommandTrait,
+            T: AsyncReadable + 'static,
+        {
+            self.sent.push(cmd.to_string());
+            let output = self.later_output;
+            Ok(ResponseHandle::new(async move {
+                let mut reader = tokio::io::BufReader::new(output.as_bytes());
+                T::read_from(&mut reader).await.and_then(|response| {
+                    response.ok_or_else(|| std::io::ErrorKind::UnexpectedEof.into())
+                })
+            }))
+        }
+
+        async fn send_raw_line(&mut self, line: &str) -> Result<(), Self::Err> {
+            self.sent.push(line.to_string());
+            Ok(())
+        }
+
+        async fn read_raw_line(&mut self) -> Result<Option<String>, Self::Err> {
+            Ok(self.output.pop_front())
+        }
+    }
+
+    fn commands() -> Vec<SetOptionCommand> {
+        vec![
+            SetOptionCommand::Hash { value: 256 },
+            SetOptionCommand::MultiPV { value: 2 },
+            SetOptionCommand::Threads { value: 4 },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_configure_sorts_by_default() {
+        let mut session = Session::new(RecordingConnection::default());
+        session.configure(commands()).await.unwrap();
+        assert_eq!(
+            session.into_inner().sent,
+            vec![
+                "setoption name Threads value 4",
+                "setoption name MultiPV value 2",
+                "setoption name Hash value 256",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_configure_as_given() {
+        let mut session = Session::new(RecordingConnection::default());
+        session
+            .configure_with(commands(), SetOptionOrder::AsGiven)
+            .await
+            .unwrap();
+        assert_eq!(
+            session.into_inner().sent,
+            vec![
+                "setoption name Hash value 256",
+                "setoption name MultiPV value 2",
+                "setoption name Threads value 4",
+            ]
+        );
+    }
+
+    fn lc0() -> RecordingConnection {
+        RecordingConnection {
+            output: LC0_UCI_RESPONSE.lines().map(str::to_string).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lines_consumed_by_the_responses() {
+        let mut session = Session::new(RecordingConnection {
+            output: STOCKFISH_UCI_RESPONSE.lines().map(str::to_string).collect(),
+            ..Default::default()
+        });
+        assert_eq!(session.last_response_lines(), None);
+        session.initialize(None).await.unwrap().unwrap();
+        assert_eq!(session.last_response_lines(), Some(24));
+
+        // The missing empty line after the id block is detected at the third line
+        let output = [
+            "id name Stockfish 17.1",
+            "id author the Stockfish developers (see AUTHORS file)",
+            "option name Threads type spin default 1 min 1 max 1024",
+            "uciok",
+        ];
+        let mut session = Session::new(RecordingConnection {
+            output: output.into_iter().map(str::to_string).collect(),
+            ..Default::default()
+        });
+        assert!(session.initialize(None).await.unwrap().is_err());
+        assert_eq!(session.last_response_lines(), Some(3));
+
+        // The lines of a search are counted by the connection
+        let mut session = Session::new(IoConnection::new(
+            GO_DEPTH5_TRANSCRIPT.as_bytes(),
+            tokio::io::sink(),
+        ));
+        let startpos = PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList::default(),
+        };
+        session
+            .analyze_cached(startpos, GoCommand::for_depth(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            session.last_response_lines(),
+            Some(GO_DEPTH5_TRANSCRIPT.lines().count())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_initialize_selects_quirks() {
+        let mut session = Session::new(lc0());
+        let response = session.initialize(None).await.unwrap().unwrap();
+        assert_eq!(session.quirks(), Quirks::for_family(EngineFamily::Lc0));
+
+        assert_eq!(
+            response.option_block.option("WeightsFile"),
+            Some(UciOption::Custom {
+                name: "WeightsFile".to_string(),
+                typed_data: TypedUciOptionData::String(model::UciString(
+                    "<autodiscover> or a path".to_string()
+                )),
+            })
+        );
+        // The options after the diagnostic line aren't lost
+        assert!(response.option_block.multi_pv.is_some());
+        assert_eq!(session.into_inner().sent, ["uci"]);
+    }
+
+    #[tokio::test]
+    async fn test_current_options() {
+        let mut session = Session::new(lc0());
+        session.initialize(None).await.unwrap().unwrap();
+        assert_eq!(
+            session.current_options().get("MultiPV"),
+            Some(&OptionValue::Spin(1))
+        );
+
+        let commands = vec![
+            SetOptionCommand::MultiPV { value: 3 },
+            SetOptionCommand::Custom {
+                name: "WeightsFile".to_string(),
+                value: Some("/nets/t2.pb.gz".to_string()),
+            },
+            // Not advertised by Lc0, so it is sent but not tracked
+            SetOptionCommand::Hash { value: 64 },
+        ];
+        session.configure(commands).await.unwrap();
+
+        let options = session.current_options();
+        assert_eq!(options.get("MultiPV"), Some(&OptionValue::Spin(3)));
+        assert_eq!(
+            options.get("WeightsFile"),
+            Some(&OptionValue::String("/nets/t2.pb.gz".to_string()))
+        );
+        assert_eq!(options.get("Hash"), None);
+        assert!(
+            session
+                .connection()
+                .sent
+                .contains(&"setoption name Hash value 64".to_string())
+        );
+
+        // The option isn't recorded unless it was sent
+        session.connection_mut().broken = true;
+        assert!(
+            session
+                .configure(vec![SetOptionCommand::MultiPV { value: 4 }])
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            session.current_options().get("MultiPV"),
+            Some(&OptionValue::Spin(3))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_option_synced() {
+        let mut session = Session::new(RecordingConnection {
+            output: [
+                "info string Using 4 threads",
+                "readyok",
+                "info string NNUE evaluation using nn-1c0000000000.nnue (133MiB, (22528, 3072, 15, 32, 1))",
+                "info string Network reloaded",
+                "readyok",
+                // The configured network failed to load, so the engine fell back to the embedded one
+                "info string NNUE evaluation using nn-ad9b42354671.nnue enabled",
+                "readyok",
+                "readyok",
+            ]
+            .map(str::to_string)
+            .into(),
+            ..Default::default()
+        });
+
+        let threads = session
+            .set_option_synced(SetOptionCommand::Threads { value: 4 })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            threads.info.used_threads().map(|used| used.threads),
+            Some(4)
+        );
+        assert_eq!(threads.confirmed(), Some(true));
+
+        let eval_file = session
+            .set_option_synced(SetOptionCommand::EvalFile {
+                value: "/nets/nn-1c0000000000.nnue".to_string(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(eval_file.confirmed(), Some(true));
+        assert_eq!(
+            eval_file.info.other().collect::<Vec<_>>(),
+            ["Network reloaded"]
+        );
+
+        let stale = session
+            .set_option_synced(SetOptionCommand::EvalFile {
+                value: "nn-missing.nnue".to_string(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stale.confirmed(), Some(false));
+
+        // Nothing printed about the option
+        let hash = session
+            .set_option_synced(SetOptionCommand::Hash { value: 64 })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(hash.info.lines().is_empty());
+        assert_eq!(hash.confirmed(), None);
+
+        // The output ends before `readyok`
+        assert!(matches!(
+            session
+                .set_option_synced(SetOptionCommand::Threads { value: 2 })
+                .await
+                .unwrap(),
+            Err(command::parsing::Error::UnexpectedEof)
+        ));
+
+        assert_eq!(
+            session.into_inner().sent[..4],
+            [
+                "setoption name Threads value 4",
+                "isready",
+                "setoption name EvalFile value /nets/nn-1c0000000000.nnue",
+                "isready",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_castling_notation() {
+        // Lc0 doesn't advertise `UCI_Chess960`
+        let mut session = Session::new(lc0());
+        session.initialize(None).await.unwrap().unwrap();
+        assert!(!session.chess960());
+
+        let mut session = Session::new(RecordingConnection {
+            output: STOCKFISH_UCI_RESPONSE.lines().map(str::to_string).collect(),
+            ..Default::default()
+        });
+        session.initialize(None).await.unwrap().unwrap();
+        assert_eq!(session.castling_notation(), CastlingNotation::Standard);
+
+        session
+            .configure(vec![SetOptionCommand::UCIChess960 { value: true }])
+            .await
+            .unwrap();
+        assert!(session.chess960());
+        assert_eq!(session.castling_notation(), CastlingNotation::Chess960);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_with_quirks_override() {
+        let mut session = Session::new(lc0());
+        assert!(
+            session
+                .initialize(Some(Quirks::default()))
+                .await
+                .unwrap()
+                .is_err()
+        );
+        assert_eq!(session.quirks(), Quirks::default());
+    }
+
+    #[tokio::test]
+    async fn test_analyze_cached() {
+        let connection = RecordingConnection {
+            later_output: GO_DEPTH5_TRANSCRIPT,
+            ..Default::default()
+        };
+        let mut session = Session::new(connection).with_analysis_cache(AnalysisCache::new(8));
+        let startpos = || PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList::default(),
+        };
+
+        let summary = session
+            .analyze_cached(startpos(), GoCommand::for_depth(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(summary.depth, Some(5));
+
+        // The same search is answered from the cache
+        let cached = session
+            .analyze_cached(startpos(), GoCommand::for_depth(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cached, summary);
+        assert_eq!(session.connection().sent.len(), 2);
+
+        // Changing an option that affects the results asks the engine again
+        session
+            .configure(vec![SetOptionCommand::MultiPV { value: 2 }])
+            .await
+            .unwrap();
+        session
+            .analyze_cached(startpos(), GoCommand::for_depth(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            session.into_inner().sent,
+            [
+                "position startpos",
+                "go depth 5",
+                "setoption name MultiPV value 2",
+                "position startpos",
+                "go depth 5",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_analysis_cache_ignores_unsent_options() {
+        let mut session =
+            Session::new(RecordingConnection::default()).with_analysis_cache(AnalysisCache::new(8));
+        let startpos = PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList::default(),
+        };
+        let key = |session: &Session<RecordingConnection>| {
+            session
+                .analysis_cache()
+                .unwrap()
+                .key(&startpos, &GoCommand::for_depth(5))
+        };
+        let before = key(&session);
+
+        // The engine never received the option, so its searches are still the same
+        session.connection_mut().broken = true;
+        assert!(
+            session
+                .configure(vec![SetOptionCommand::MultiPV { value: 2 }])
+                .await
+                .is_err()
+        );
+        assert_eq!(key(&session), before);
+    }
+
+    #[tokio::test]
+    async fn test_go_validation() {
+        let connection = RecordingConnection {
+            later_output: GO_DEPTH5_TRANSCRIPT,
+            ..Default::default()
+        };
+        let mut session = Session::new(connection).with_go_validation();
+        let startpos = || PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList::default(),
+        };
+
+        let invalid = GoCommand {
+            indefinite: true,
+            ..GoCommand::for_depth(5)
+        };
+        assert!(matches!(
+            session.analyze_cached(startpos(), invalid.clone()).await,
+            Err(SessionError::InvalidGo(
+                GoCommandValidationError::InfiniteWithLimit("depth")
+            ))
+        ));
+        assert!(session.connection().sent.is_empty());
+
+        // Not even `ucinewgame` is sent
+        let startpos_fen =
+            FenString::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert!(matches!(
+            session.evaluate_positions([startpos_fen], &invalid).await,
+            Err(SessionError::InvalidGo(_))
+        ));
+        assert!(session.connection().sent.is_empty());
+
+        let summary = session
+            .analyze_cached(startpos(), GoCommand::for_depth(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(summary.depth, Some(5));
+        assert_eq!(
+            session.connection().sent,
+            ["position startpos", "go depth 5"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_option_snapshots() {
+        let mut session = Session::new(RecordingConnection {
+            output: STOCKFISH_UCI_RESPONSE.lines().map(str::to_string).collect(),
+            later_output: GO_DEPTH5_TRANSCRIPT,
+            ..Default::default()
+        });
+        session.initialize(None).await.unwrap().unwrap();
+        session
+            .configure(vec![SetOptionCommand::Threads { value: 4 }])
+            .await
+            .unwrap();
+        let startpos = || PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList::default(),
+        };
+
+        let first = session
+            .analyze_cached(startpos(), GoCommand::for_depth(5))
+            .await
+            .unwrap()
+            .unwrap()
+            .options
+            .unwrap();
+        assert_eq!(first.get("Threads"), Some(&OptionValue::Spin(4)));
+        assert_eq!(first.get("MultiPV"), None);
+
+        session
+            .configure(vec![SetOptionCommand::MultiPV { value: 3 }])
+            .await
+            .unwrap();
+        let second = session
+            .analyze_cached(startpos(), GoCommand::for_depth(5))
+            .await
+            .unwrap()
+            .unwrap()
+            .options
+            .unwrap();
+        assert_eq!(second.get("MultiPV"), Some(&OptionValue::Spin(3)));
+        assert_eq!(first.differences(&second), ["MultiPV"]);
+    }
+
+    fn fens() -> Vec<FenString> {
+        [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+        ]
+        .into_iter()
+        .map(|fen| FenString(fen.to_string()))
+        .collect()
+    }
+
+    fn search_connection() -> RecordingConnection {
+        RecordingConnection {
+            later_output: GO_DEPTH5_TRANSCRIPT,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_positions() {
+        let mut session = Session::new(search_connection());
+        let evaluations = session
+            .evaluate_positions(fens(), &GoCommand::for_depth(5))
+            .await
+            .unwrap();
+
+        let evaluated = evaluations
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            evaluated.iter().map(|e| e.fen.clone()).collect::<Vec<_>>(),
+            fens()
+        );
+        assert_eq!(
+            evaluated[0],
+            PositionEvaluation {
+                fen: fens()[0].clone(),
+                best_move: Some(BestMoveCommand {
+                    best_move: model::MoveString::from("e2e4"),
+                    ponder: Some(model::MoveString::from("d7d6")),
+                }),
+                score: Some(Score::Cp(58)),
+                depth: Some(5),
+                time: Some(Duration::from_millis(7)),
+                options: Some(OptionSnapshot::default()),
+            }
+        );
+
+        let sent = session.into_inner().sent;
+        assert_eq!(sent.len(), 15);
+        assert_eq!(
+            sent[3..6],
+            [
+                "ucinewgame".to_string(),
+                format!("position fen {}", fens()[1].0),
+                "go depth 5".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_history() {
+        let mut session =
+            Session::new(search_connection()).with_search_history(SearchHistory::new(2));
+        assert!(session.last_search().is_none());
+
+        for fen in &fens()[..3] {
+            session
+                .analyze_cached(
+                    PositionCommand::from_fen(fen.clone()),
+                    GoCommand::for_depth(5),
+                )
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        // The first search is evicted
+        assert_eq!(
+            session
+                .searches()
+                .map(|search| search.position.clone())
+                .collect::<Vec<_>>(),
+            [
+                PositionCommand::from_fen(fens()[1].clone()),
+                PositionCommand::from_fen(fens()[2].clone()),
+            ]
+        );
+        let last = session.last_search().unwrap();
+        assert_eq!(last.position, PositionCommand::from_fen(fens()[2].clone()));
+        assert_eq!(last.go_command, GoCommand::for_depth(5));
+        assert_eq!(last.summary.depth, Some(5));
+        assert_eq!(last.summary.pv, [model::MoveString::from("e2e4")]);
+        assert_eq!(
+            last.best_move().map(|best_move| &best_move.best_move),
+            Some(&model::MoveString::from("e2e4"))
+        );
+
+        session.search_history_mut().clear();
+        assert_eq!(session.searches().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_positions_cancelled_after_two() {
+        let cancel = CancelToken::new();
+        let mut progress = Vec::new();
+        let mut on_progress = |evaluated: usize| {
+            progress.push(evaluated);
+            if evaluated == 2 {
+                cancel.cancel();
+            }
+        };
+
+        let mut session = Session::new(search_connection());
+        let evaluations = session
+            .evaluate_positions_with(
+                fens(),
+                &GoCommand::for_depth(5),
+                EvaluatePositionsOptions {
+                    skip_new_game: true,
+                    on_progress: Some(&mut on_progress),
+                    cancel: Some(cancel.clone()),
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(progress, [1, 2]);
+        assert_eq!(evaluations.len(), 2);
+        assert_eq!(evaluations[1].as_ref().unwrap().fen, fens()[1]);
+        assert_eq!(
+            session.into_inner().sent,
+            [
+                format!("position fen {}", fens()[0].0),
+                "go depth 5".to_string(),
+                format!("position fen {}", fens()[1].0),
+                "go depth 5".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hash_maintenance_clears_between_searches() {
+        const SATURATED_SEARCH: &str = "info depth 20 seldepth 30 multipv 1 score cp 25 nodes 9000000 nps 3000000 hashfull 950 tbhits 0 time 3000 pv e2e4
+bestmove e2e4
+";
+        let mut session = Session::new(RecordingConnection {
+            later_output: SATURATED_SEARCH,
+            ..Default::default()
+        })
+        .with_hash_maintenance(HashMaintenance::new(HashMaintenanceConfig {
+            threshold: 900,
+            consecutive_searches: 2,
+            auto_clear: true,
+        }));
+        session
+            .evaluate_positions_with(
+                fens().into_iter().take(3),
+                &GoCommand::for_depth(20),
+                EvaluatePositionsOptions {
+                    skip_new_game: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let maintenance = session.hash_maintenance().unwrap();
+        assert_eq!(maintenance.last_average(), Some(950));
+        // The third search is counted anew
+        assert_eq!(maintenance.saturated_searches(), 1);
+        let sent = session.into_inner().sent;
+        assert_eq!(
+            sent.iter()
+                .filter(|line| !line.starts_with("position"))
+                .collect::<Vec<_>>(),
+            [
+                "go depth 20",
+                "go depth 20",
+                "setoption name Clear Hash",
+                "go depth 20"
+            ]
+        );
+    }
+
+    /// A session with an engine that ponders until `ponderhit` or `stop`, acknowledging `stop` before the
+    /// stale `bestmove`; the engine returns the commands it has received.
+    fn pondering_session() -> (
+        Session<DuplexConnection>,
+        tokio::task::JoinHandle<Vec<String>>,
+    ) {
+        let (connection, engine) = FakeEngine::new(|line| {
+            Some(match line {
+                "go ponder wtime 60000 btime 60000" => {
+                    "info depth 10 seldepth 14 multipv 1 score cp 15 nodes 50000 nps 1250000 time 40 pv g1f3 b8c6\n"
+                }
+                "ponderhit" => {
+                    "info depth 14 seldepth 20 multipv 1 score cp 18 nodes 400000 nps 1333333 time 300 pv g1f3 b8c6\n\
+                     bestmove g1f3 ponder b8c6\n"
+                }
+                "stop" => {
+                    "info string stop acknowledged\n\
+                     info depth 11 seldepth 15 multipv 1 score cp 16 nodes 80000 nps 1333333 time 60 pv g1f3 b8c6\n\
+                     bestmove g1f3 ponder b8c6\n"
+                }
+                "go wtime 60000 btime 60000" => {
+                    "info depth 12 seldepth 16 multipv 1 score cp 40 nodes 200000 nps 1333333 time 150 pv e4d5 d8d5\n\
+                     bestmove e4d5 ponder d8d5\n"
+                }
+                _ => "",
+            })
+        })
+        .connect();
+        (Session::new(connection), engine)
+    }
+
+    fn after_moves(moves: &[&str]) -> PositionCommand {
+        PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: moves.iter().copied().collect(),
+        }
+    }
+
+    fn clock() -> GoCommand {
+        GoCommand {
+            wtime: Some(60000),
+            btime: Some(60000),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ponder_hit() {
+        let (mut session, engine) = pondering_session();
+
+        let ponder = session
+            .start_ponder(after_moves(&["e2e4"]), MoveString::from("e7e5"), clock())
+            .await
+            .unwrap();
+        let response = ponder.hit().await.unwrap().finish().await.unwrap().unwrap();
+        // The events sent while pondering belong to the same search
+        assert_eq!(response.events().len(), 3);
+        assert_eq!(response.search_summary.depth, Some(14));
+        assert_eq!(
+            response.search_summary.options,
+            Some(OptionSnapshot::default())
+        );
+        assert_eq!(
+            response.search_summary.best_move.unwrap().best_move,
+            MoveString::from("g1f3")
+        );
+        let last = session.last_search().unwrap();
+        assert_eq!(last.position, after_moves(&["e2e4", "e7e5"]));
+        assert_eq!(
+            last.go_command,
+            GoCommand {
+                ponder: true,
+                ..clock()
+            }
+        );
+        assert_eq!(last.summary.depth, Some(14));
+
+        session
+            .connection_mut()
+            .send_raw_line("quit")
+            .await
+            .unwrap();
+        assert_eq!(
+            engine.await.unwrap(),
+            [
+                "position startpos moves e2e4 e7e5",
+                "go ponder wtime 60000 btime 60000",
+                "ponderhit",
+                "quit",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_after_ponder_hit() {
+        // The engine searches on after `ponderhit` until `stop`
+        let (connection, engine) = FakeEngine::new(|line| {
+            Some(match line {
+                "go ponder wtime 60000 btime 60000" => {
+                    "info depth 10 seldepth 14 multipv 1 score cp 15 nodes 50000 nps 1250000 time 40 pv g1f3 b8c6\n"
+                }
+                "ponderhit" => {
+                    "info depth 14 seldepth 20 multipv 1 score cp 18 nodes 400000 nps 1333333 time 300 pv g1f3 b8c6\n"
+                }
+                "stop" => "bestmove g1f3 ponder b8c6\n",
+                _ => "",
+            })
+        })
+        .connect();
+        let mut session = Session::new(connection);
+
+        let ponder = session
+            .start_ponder(after_moves(&["e2e4"]), MoveString::from("e7e5"), clock())
+            .await
+            .unwrap();
+        // The search is handed over before its `bestmove`, so it can still be stopped
+        let mut search = ponder.hit().await.unwrap();
+        search.stop().await.unwrap();
+        let response = search.finish().await.unwrap().unwrap();
+        assert_eq!(response.events().len(), 3);
+        assert_eq!(response.search_summary.depth, Some(14));
+        assert_eq!(
+            response.search_summary.best_move.unwrap().best_move,
+            MoveString::from("g1f3")
+        );
+        assert_eq!(session.searches().len(), 1);
+
+        session
+            .connection_mut()
+            .send_raw_line("quit")
+            .await
+            .unwrap();
+        assert_eq!(
+            engine.await.unwrap(),
+            [
+                "position startpos moves e2e4 e7e5",
+                "go ponder wtime 60000 btime 60000",
+                "ponderhit",
+                "stop",
+                "quit",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ponder_miss() {
+        let (mut session, engine) = pondering_session();
+
+        let ponder = session
+            .start_ponder(after_moves(&["e2e4"]), MoveString::from("e7e5"), clock())
+            .await
+            .unwrap();
+        let response = ponder
+            .miss(after_moves(&["e2e4", "d7d5"]), clock())
+            .await
+            .unwrap()
+            .unwrap();
+        // Neither the acknowledgment nor the stale `bestmove` is taken for the output of the new search
+        assert_eq!(response.events().len(), 2);
+        assert_eq!(response.search_summary.depth, Some(12));
+        assert_eq!(
+            response.search_summary.best_move.unwrap().best_move,
+            MoveString::from("e4d5")
+        );
+        // Only the actual search is kept
+        assert_eq!(session.searches().len(), 1);
+        let last = session.last_search().unwrap();
+        assert_eq!(last.position, after_moves(&["e2e4", "d7d5"]));
+        assert_eq!(last.go_command, clock());
+
+        session
+            .connection_mut()
+            .send_raw_line("quit")
+            .await
+            .unwrap();
+        assert_eq!(
+            engine.await.unwrap(),
+            [
+                "position startpos moves e2e4 e7e5",
+                "go ponder wtime 60000 btime 60000",
+                "stop",
+                "position startpos moves e2e4 d7d5",
+                "go wtime 60000 btime 60000",
+                "quit",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ponder_validation() {
+        let mut session = Session::new(RecordingConnection::default()).with_go_validation();
+
+        let invalid = GoCommand {
+            depth: Some(0),
+            ..clock()
+        };
+        assert!(matches!(
+            session
+                .start_ponder(after_moves(&["e2e4"]), MoveString::from("e7e5"), invalid)
+                .await,
+            Err(SessionError::InvalidGo(
+                GoCommandValidationError::ZeroLimit("depth")
+            ))
+        ));
+        assert!(session.connection().sent.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ponder_miss_without_stale_bestmove() {
+        // The output ends with the acknowledgment of `stop`, before the stale `bestmove`
+        let mut session = Session::new(RecordingConnection {
+            output: ["info string stop acknowledged".to_string()].into(),
+            ..Default::default()
+        });
+
+        let ponder = session
+            .start_ponder(after_moves(&["e2e4"]), MoveString::from("e7e5"), clock())
+            .await
+            .unwrap();
+        let response = ponder
+            .miss(after_moves(&["e2e4", "d7d5"]), clock())
+            .await
+            .unwrap();
+        assert!(matches!(
+            response,
+            Err(command::parsing::Error::UnexpectedEof)
+        ));
+        assert!(session.last_search().is_none());
+        // The new search isn't started
+        assert_eq!(
+            session.into_inner().sent,
+            [
+                "position startpos moves e2e4 e7e5",
+                "go ponder wtime 60000 btime 60000",
+                "stop",
+            ]
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_spans() {
+        use std::sync::{Arc, Mutex};
+
+        use tracing_subscriber::{
+            Layer,
+            layer::{Context, SubscriberExt as _},
+        };
+
+        /// Records the names of the spans as they are created.
+        #[derive(Clone, Default)]
+        struct CaptureLayer(Arc<Mutex<Vec<&'static str>>>);
+
+        impl<S: tracing::Subscriber> Layer<S> for CaptureLayer {
+            fn on_new_span(
+                &self,
+                attrs: &tracing::span::Attributes<'_>,
+                _id: &tracing::span::Id,
+                _ctx: Context<'_, S>,
+            ) {
+                self.0.lock().unwrap().push(attrs.metadata().name());
+            }
+        }
+
+        let layer = CaptureLayer::default();
+        let subscriber = tracing_subscriber::registry().with(layer.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                let mut session = Session::new(RecordingConnection {
+                    later_output: GO_DEPTH5_TRANSCRIPT,
+                    ..lc0()
+                });
+                session.initialize(None).await.unwrap().unwrap();
+                session.configure(commands()).await.unwrap();
+                let fens = fens().into_iter().take(1);
+                session
+                    .evaluate_positions(fens, &GoCommand::for_depth(5))
+                    .await
+                    .unwrap();
+            });
+        });
+
+        assert_eq!(
+            *layer.0.lock().unwrap(),
+            [
+                "uci_handshake",
+                "configure",
+                "setoption",
+                "setoption",
+                "setoption",
+                "search",
+            ]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! The module for running [EPD](crate::model::Epd) test suites, e.g. Win At Chess, and scoring the engine
+//! by whether its best moves match the `bm` and avoid the `am` operations.
+//!
+//! The suites write the moves in SAN, which can be compared with the best moves of the engine only
+//! with the chess rules of the `board` feature, see [`MoveNotation`].
+
+use crate::{
+    gui_command_responses::GoResponse,
+    gui_commands::GoCommand,
+    model::{Epd, EpdParsingError, MoveString},
+    session::{EvaluatePositionsOptions, PositionEvaluation, Session, SessionError},
+    util::{AsyncReadable, Connection},
+};
+
+/// The error of parsing a line of a test suite with [`parse_suite`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid EPD at line {line_number}: {error}")]
+pub struct TestSuiteParsingError {
+    /// The 1-based number of the line.
+    pub line_number: usize,
+    #[source]
+    pub error: EpdParsingError,
+}
+
+/// Parses an EPD file line by line, skipping the blank lines.
+pub fn parse_suite(s: &str) -> Result<Vec<Epd>, TestSuiteParsingError> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            line.parse().map_err(|error| TestSuiteParsingError {
+                line_number: i + 1,
+                error,
+            })
+        })
+        .collect()
+}
+
+/// The notation of the moves of the `bm` and `am` operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveNotation {
+    /// SAN, e.g. `Qg6`, as in most suites. The best move of the engine is converted to SAN in the position
+    /// of the EPD, and the check, mate and annotation suffixes (`+`, `#`, `!`, `?`) are ignored.
+    #[cfg(feature = "board")]
+    San,
+    /// UCI long algebraic notation, e.g. `g3g6`, compared with the best move of the engine as is.
+    Uci,
+}
+
+/// Whether the engine solved a position of the suite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestOutcome {
+    /// The best move is one of the `bm` moves, if any, and none of the `am` moves.
+    Solved,
+    /// The best move is wrong, or the engine answered without a best move.
+    Failed,
+    /// The EPD has neither `bm` nor `am`.
+    Unscored,
+}
+
+/// A position of the suite, its evaluation and whether it was solved.
+#[derive(Debug)]
+pub struct TestResult {
+    pub epd: Epd,
+    pub evaluation: Result<PositionEvaluation, <GoResponse as AsyncReadable>::Err>,
+    pub outcome: TestOutcome,
+}
+
+/// The results of [`TestSuiteRunner::run`], in the order of the suite.
+#[derive(Debug, Default)]
+pub struct TestSuiteReport {
+    pub results: Vec<TestResult>,
+}
+
+impl TestSuiteReport {
+    /// The number of the solved positions.
+    pub fn solved(&self) -> usize {
+        self.count(TestOutcome::Solved)
+    }
+
+    /// The number of the positions with `bm` or `am`, whether solved or not.
+    pub fn scored(&self) -> usize {
+        self.results.len() - self.count(TestOutcome::Unscored)
+    }
+
+    fn count(&self, outcome: TestOutcome) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.outcome == outcome)
+            .count()
+    }
+}
+
+/// Runs a test suite with [`Session::evaluate_positions_with`], searching every position with the same `go` command.
+pub struct TestSuiteRunner {
+    go: GoCommand,
+    notation: MoveNotation,
+}
+
+impl TestSuiteRunner {
+    pub fn new(go: GoCommand, notation: MoveNotation) -> Self {
+        Self { go, notation }
+    }
+
+    pub async fn run<C: Connection>(
+        &self,
+        session: &mut Session<C>,
+        suite: Vec<Epd>,
+    ) -> Result<TestSuiteReport, SessionError<C::Err>> {
+        self.run_with(session, suite, EvaluatePositionsOptions::default())
+            .await
+    }
+
+    /// Runs the suite with the [`EvaluatePositionsOptions`], e.g. for reporting the progress.
+    ///
+    /// If the run is cancelled, the report has only the positions evaluated before.
+    pub async fn run_with<C: Connection>(
+        &self,
+        session: &mut Session<C>,
+        suite: Vec<Epd>,
+        options: EvaluatePositionsOptions<'_>,
+    ) -> Result<TestSuiteReport, SessionError<C::Err>> {
+        let evaluations = session
+            .evaluate_positions_with(suite.iter().map(Epd::to_fen), &self.go, options)
+            .await?;
+        let results = suite
+            .into_iter()
+            .zip(evaluations)
+            .map(|(epd, evaluation)| {
+                let best_move = evaluation
+                    .as_ref()
+                    .ok()
+                    .and_then(|evaluation| evaluation.best_move.as_ref())
+                    .map(|best_move| &best_move.best_move);
+                let outcome = self.outcome(&epd, best_move);
+                TestResult {
+                    epd,
+                    evaluation,
+                    outcome,
+                }
+            })
+            .collect();
+        Ok(TestSuiteReport { results })
+    }
+
+    /// Scores the best move of the engine, if any, against the `bm` and `am` operations of the EPD.
+    pub fn outcome(&self, epd: &Epd, best_move: Option<&MoveString>) -> TestOutcome {
+        if epd.best_moves.is_empty() && epd.avoid_moves.is_empty() {
+            return TestOutcome::Unscored;
+        }
+        let Some(best_move) = best_move.and_then(|best_move| self.notate(epd, best_move)) else {
+            return TestOutcome::Failed;
+        };
+        let matches = |moves: &[String]| {
+            moves
+                .iter()
+                .any(|mv| without_suffixes(mv) == without_suffixes(&best_move))
+        };
+        if (epd.best_moves.is_empty() || matches(&epd.best_moves)) && !matches(&epd.avoid_moves) {
+            TestOutcome::Solved
+        } else {
+            TestOutcome::Failed
+        }
+    }
+
+    /// The move in the notation of the suite, or `None` if it is illegal in the position.
+    #[cfg_attr(not(feature = "board"), allow(unused_variables))]
+    fn notate(&self, epd: &Epd, mv: &MoveString) -> Option<String> {
+        match self.notation {
+            #[cfg(feature = "board")]
+            MoveNotation::San => crate::model::Position::Fen(epd.to_fen())
+                .to_san(std::slice::from_ref(mv))
+                .ok()?
+                .pop(),
+            MoveNotation::Uci => Some(mv.0.clone()),
+        }
+    }
+}
+
+fn without_suffixes(mv: &str) -> &str {
+    mv.trim_end_matches(['+', '#', '!', '?'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::IoConnection;
+
+    /// The first positions of Win At Chess and a position with an `am` operation.
+    const SUITE: &str = r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";
+8/7p/5k2/5p2/p1p2P2/Pr1pPK2/1P1R3P/8 b - - bm Rxb2; id "WAC.002";
+5rk1/1ppb3p/p1pb4/6q1/3P1p1r/2P1R2P/PP1BQ1P1/5RKN w - - bm Rg3; id "WAC.003";
+
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - am f3; id "opening.001";
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - id "unscored.001";
+"#;
+
+    /// The best moves of the scripted engine for the positions of the suite, in order.
+    const ENGINE_OUTPUT: &str = "bestmove g3g6
+bestmove b3a3
+bestmove e3g3
+bestmove f2f3
+bestmove e2e4
+";
+
+    fn session() -> Session<IoConnection<&'static [u8], tokio::io::Sink>> {
+        Session::new(IoConnection::new(
+            ENGINE_OUTPUT.as_bytes(),
+            tokio::io::sink(),
+        ))
+    }
+
+    #[test]
+    fn test_parse_suite() {
+        let suite = parse_suite(SUITE).unwrap();
+        assert_eq!(suite.len(), 5);
+        assert_eq!(suite[1].best_moves, ["Rxb2"]);
+        assert_eq!(suite[3].avoid_moves, ["f3"]);
+
+        assert_eq!(
+            parse_suite("8/8/8/8/8/8/8/8 w - - bm Kb1;\n\n8/8/8/8/8/8/8/8 w"),
+            Err(TestSuiteParsingError {
+                line_number: 3,
+                error: EpdParsingError::MissingField("castling"),
+            })
+        );
+    }
+
+    #[cfg(feature = "board")]
+    #[tokio::test]
+    async fn test_run_san_suite() {
+        let runner = TestSuiteRunner::new(GoCommand::for_depth(10), MoveNotation::San);
+        let report = runner
+            .run(&mut session(), parse_suite(SUITE).unwrap())
+            .await
+            .unwrap();
+
+        let outcomes = report
+            .results
+            .iter()
+            .map(|result| result.outcome)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            [
+                TestOutcome::Solved,
+                TestOutcome::Failed,
+                TestOutcome::Solved,
+                TestOutcome::Failed,
+                TestOutcome::Unscored,
+            ]
+        );
+        assert_eq!(report.solved(), 2);
+        assert_eq!(report.scored(), 4);
+        assert_eq!(report.results[1].epd.id.as_deref(), Some("WAC.002"));
+    }
+
+    #[tokio::test]
+    async fn test_run_uci_suite() {
+        let suite = parse_suite(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm g3g6 f6h7;\n\
+            8/7p/5k2/5p2/p1p2P2/Pr1pPK2/1P1R3P/8 b - - bm b3b2;",
+        )
+        .unwrap();
+        let runner = TestSuiteRunner::new(GoCommand::for_depth(10), MoveNotation::Uci);
+        let report = runner.run(&mut session(), suite).await.unwrap();
+        assert_eq!(report.solved(), 1);
+        assert_eq!(report.results[1].outcome, TestOutcome::Failed);
+    }
+}
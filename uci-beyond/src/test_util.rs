@@ -0,0 +1,4 @@
+//! The helpers shared by the tests of this crate.
+
+/// Compiles only if the values can be sent to other tasks and kept in the state of an app.
+pub(crate) fn assert_data<T: Send + Sync + Clone + 'static>() {}
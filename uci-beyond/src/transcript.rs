@@ -0,0 +1,292 @@
+//! The module for replaying the transcripts of the `Debug Log File` option of Stockfish, e.g.
+//! for debugging the interaction of a GUI and an engine offline with the parsers of this crate.
+//!
+//! Stockfish logs every line read from the GUI with the `>> ` prefix and every line written
+//! to the GUI with the `<< ` prefix:
+//!
+//! ```text
+//! >> position startpos
+//! >> go depth 3
+//! << info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 6666 hashfull 0 tbhits 0 time 3 pv e2e4
+//! << bestmove e2e4 ponder e7e5
+//! ```
+
+use std::str::FromStr;
+
+use crate::{
+    command::Command as _,
+    engine_commands::{BestMoveCommand, IdCommand, OptionCommand, ReadyOkCommand, UciOkCommand},
+    gui_command_responses::{GoEvent, SearchSummary},
+    gui_commands::AnyGuiCommand,
+};
+
+/// The prefix of the lines sent to the engine.
+pub(crate) const TO_ENGINE_MARKER: &str = ">>";
+/// The prefix of the lines sent by the engine.
+pub(crate) const FROM_ENGINE_MARKER: &str = "<<";
+
+/// Who sent a line of the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The line was sent by the GUI to the engine, i.e. `>> `.
+    ToEngine,
+    /// The line was sent by the engine to the GUI, i.e. `<< `.
+    FromEngine,
+}
+
+/// A line of the transcript parsed with the dispatcher of its [`Direction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnyCommand {
+    /// A command sent to the engine. The commands that aren't parsed yet are [`AnyGuiCommand::Other`].
+    Gui(AnyGuiCommand),
+    /// An `info` or a `bestmove` line of a search.
+    Go(GoEvent),
+    Id(IdCommand),
+    Option(OptionCommand),
+    UciOk(UciOkCommand),
+    ReadyOk(ReadyOkCommand),
+}
+
+/// A line of the transcript, without the direction marker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    pub direction: Direction,
+    pub line: String,
+    /// The parsed line, if it could be parsed, e.g. not for the banner of the engine or a malformed line.
+    pub parsed: Option<AnyCommand>,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TranscriptParsingError {
+    /// The line starts with neither `>>` nor `<<`, e.g. because the file isn't a `Debug Log File`.
+    ///
+    /// The line number is 1-based.
+    #[error("Line {line_number} has no direction marker: `{line}`")]
+    MissingDirection { line_number: usize, line: String },
+}
+
+/// Parses the transcript line by line, in order.
+///
+/// The lines that can't be parsed as commands are kept with `parsed: None`, since the parsing
+/// is best-effort. Only the blank lines of the file are skipped.
+pub fn parse_transcript(s: &str) -> Result<Vec<TranscriptEntry>, TranscriptParsingError> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let (direction, rest) = if let Some(rest) = line.strip_prefix(TO_ENGINE_MARKER) {
+                (Direction::ToEngine, rest)
+            } else if let Some(rest) = line.strip_prefix(FROM_ENGINE_MARKER) {
+                (Direction::FromEngine, rest)
+            } else {
+                return Err(TranscriptParsingError::MissingDirection {
+                    line_number: i + 1,
+                    line: line.to_string(),
+                });
+            };
+            let line = rest
+                .strip_prefix(' ')
+                .unwrap_or(rest)
+                .trim_end_matches('\r');
+            Ok(TranscriptEntry {
+                direction,
+                line: line.to_string(),
+                parsed: parse_line(direction, line),
+            })
+        })
+        .collect()
+}
+
+fn parse_line(direction: Direction, line: &str) -> Option<AnyCommand> {
+    match direction {
+        Direction::ToEngine => line.parse().ok().map(AnyCommand::Gui),
+        Direction::FromEngine => {
+            if let Ok(event) = line.parse::<GoEvent>() {
+                Some(AnyCommand::Go(event))
+            } else if IdCommand::matches_line(line) {
+                IdCommand::from_str(line).ok().map(AnyCommand::Id)
+            } else if OptionCommand::matches_line(line) {
+                OptionCommand::from_str(line).ok().map(AnyCommand::Option)
+            } else {
+                match line.trim() {
+                    "uciok" => Some(AnyCommand::UciOk(UciOkCommand)),
+                    "readyok" => Some(AnyCommand::ReadyOk(ReadyOkCommand)),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// A search of the transcript, i.e. a `go` command and the `info` and `bestmove` lines that followed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptSearch {
+    /// The index of the `go` command in the entries.
+    pub start: usize,
+    /// The last `position` command sent before the `go` command, if any.
+    pub position: Option<String>,
+    /// The `go` command, as sent.
+    pub go: String,
+    /// The events of the search, up to and including `bestmove` unless the transcript ends before it.
+    pub events: Vec<GoEvent>,
+}
+
+impl TranscriptSearch {
+    /// The `bestmove` of the search, if it is in the transcript.
+    pub fn best_move(&self) -> Option<&BestMoveCommand> {
+        self.events.iter().find_map(|event| match event {
+            GoEvent::BestMove(best_move) => Some(best_move),
+            GoEvent::Info(_) | GoEvent::Refutation(_) | GoEvent::CurrLine(_) => None,
+        })
+    }
+
+    pub fn summary(&self) -> SearchSummary {
+        SearchSummary::from_events(self.events.iter().cloned())
+    }
+}
+
+/// Extracts the searches of the transcript, in order.
+///
+/// A search starts with a `go` command and ends with `bestmove`. A search without `bestmove`
+/// (e.g. because the engine crashed or the log was cut) ends at the next `go` command.
+pub fn searches(entries: &[TranscriptEntry]) -> Vec<TranscriptSearch> {
+    let mut searches = Vec::new();
+    let mut position = None;
+    let mut current: Option<TranscriptSearch> = None;
+
+    for (i, entry) in entries.iter().enumerate() {
+        match entry.direction {
+            Direction::ToEngine => {
+                let name = entry.line.split_whitespace().next();
+                if name == Some("position") {
+                    position = Some(entry.line.clone());
+                } else if name == Some("go") {
+                    searches.extend(current.take());
+                    current = Some(TranscriptSearch {
+                        start: i,
+                        position: position.clone(),
+                        go: entry.line.clone(),
+                        events: Vec::new(),
+                    });
+                }
+            }
+            Direction::FromEngine => {
+                let Some(search) = current.as_mut() else {
+                    continue;
+                };
+                if let Some(AnyCommand::Go(event)) = &entry.parsed {
+                    search.events.push(event.clone());
+                    if matches!(event, GoEvent::BestMove(_)) {
+                        searches.extend(current.take());
+                    }
+                }
+            }
+        }
+    }
+    searches.extend(current);
+    searches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{fixtures::STOCKFISH_DEBUG_LOG, model::MoveString};
+
+    #[test]
+    fn test_parse_debug_log() {
+        let entries = parse_transcript(STOCKFISH_DEBUG_LOG).unwrap();
+        assert_eq!(entries.len(), 26);
+
+        assert_eq!(entries[0].direction, Direction::ToEngine);
+        assert_eq!(entries[0].line, "uci");
+        assert!(matches!(
+            entries[0].parsed,
+            Some(AnyCommand::Gui(AnyGuiCommand::Uci(_)))
+        ));
+
+        // The banner isn't a command
+        assert_eq!(entries[1].direction, Direction::FromEngine);
+        assert_eq!(entries[1].parsed, None);
+        assert!(matches!(
+            entries[2].parsed,
+            Some(AnyCommand::Id(IdCommand::Name(ref name))) if name == "Stockfish 17.1"
+        ));
+        // The empty line after the id block is kept
+        assert_eq!(entries[4].line, "");
+        assert!(matches!(entries[5].parsed, Some(AnyCommand::Option(_))));
+        assert_eq!(entries[8].parsed, Some(AnyCommand::UciOk(UciOkCommand)));
+        assert!(matches!(
+            entries[9].parsed,
+            Some(AnyCommand::Gui(AnyGuiCommand::SetOption(_)))
+        ));
+        assert_eq!(
+            entries[11].parsed,
+            Some(AnyCommand::ReadyOk(ReadyOkCommand))
+        );
+        assert!(matches!(
+            entries[14].parsed,
+            Some(AnyCommand::Gui(AnyGuiCommand::Other(ref line))) if line == "go depth 3"
+        ));
+    }
+
+    #[test]
+    fn test_searches_of_debug_log() {
+        let entries = parse_transcript(STOCKFISH_DEBUG_LOG).unwrap();
+        let searches = searches(&entries);
+        assert_eq!(searches.len(), 2);
+
+        assert_eq!(searches[0].start, 14);
+        assert_eq!(searches[0].position.as_deref(), Some("position startpos"));
+        assert_eq!(searches[0].go, "go depth 3");
+        assert_eq!(searches[0].events.len(), 5);
+        assert_eq!(searches[0].summary().depth, Some(3));
+
+        assert_eq!(
+            searches[1].position.as_deref(),
+            Some("position startpos moves e2e4 e7e5")
+        );
+        assert_eq!(searches[1].go, "go movetime 100");
+
+        let best_moves = searches
+            .iter()
+            .map(|search| search.best_move().map(|b| b.best_move.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            best_moves,
+            [
+                Some(MoveString::from("e2e4")),
+                Some(MoveString::from("g1f3"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unfinished_searches() {
+        let log = "\
+>> go infinite\r
+<< info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 6666 hashfull 0 tbhits 0 time 3 pv e2e4\r
+>> go depth 1\r
+<< info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 6666 hashfull 0 tbhits 0 time 3 pv e2e4\r
+";
+        let searches = searches(&parse_transcript(log).unwrap());
+        assert_eq!(searches.len(), 2);
+        assert_eq!(searches[0].go, "go infinite");
+        assert_eq!(searches[0].position, None);
+        assert_eq!(searches[0].events.len(), 1);
+        assert_eq!(searches[0].best_move(), None);
+        assert_eq!(searches[1].start, 2);
+    }
+
+    #[test]
+    fn test_missing_direction() {
+        assert_eq!(
+            parse_transcript(">> uci\n\nuciok\n"),
+            Err(TranscriptParsingError::MissingDirection {
+                line_number: 3,
+                line: "uciok".to_string()
+            })
+        );
+    }
+}
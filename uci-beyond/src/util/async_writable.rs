@@ -0,0 +1,290 @@
+use std::fmt::Display;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncWrite, AsyncWriteExt as _};
+
+use crate::util::{NoRetry, RetryPolicy};
+
+/// The write counterpart of [`AsyncReadable`](crate::util::AsyncReadable): a sink of UCI lines.
+///
+/// The implementors decide how a line is terminated, when it is flushed, and how the
+/// partial and failed writes are retried, so that the callers only provide the text.
+#[async_trait(?Send)]
+pub trait AsyncWritable {
+    type Error;
+
+    /// Writes each line of the text, e.g. `"readyok"` or a multi-line response, as a whole line.
+    ///
+    /// The line terminators of the text (`\n` or `\r\n`) are replaced with the ones of the writer,
+    /// and a missing terminator after the last line is added. An empty text is written as an empty line.
+    async fn write_lines(&mut self, text: &str) -> Result<(), Self::Error>;
+
+    /// Writes the command (or response) the way it is displayed, e.g. `isready` or `bestmove e2e4`.
+    async fn write_command<C>(&mut self, cmd: &C) -> Result<(), Self::Error>
+    where
+        C: Display + ?Sized,
+    {
+        self.write_lines(&cmd.to_string()).await
+    }
+}
+
+/// When a [`LineWriter`] flushes the underlying writer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// After every [`write_lines`](AsyncWritable::write_lines), which is what the other end,
+    /// waiting for the line, expects.
+    #[default]
+    Always,
+    /// Only on [`LineWriter::flush`], e.g. to write many lines in a row to a file.
+    Manual,
+}
+
+/// The [`AsyncWritable`] over an [`AsyncWrite`], e.g. the stdin of an engine or the stdout of an engine
+/// talking to a GUI, that terminates the lines with `\n`.
+///
+/// The lines written together are written as a single buffer, so that the readers that wait for `\n`
+/// never see a partial line. A partial write is continued after the bytes already written, and
+/// a failed one is retried according to the [`RetryPolicy`], so a line is never written twice.
+pub struct LineWriter<W> {
+    writer: W,
+    flush_policy: FlushPolicy,
+    retry_policy: Box<dyn RetryPolicy<std::io::Error> + Send + Sync>,
+}
+
+impl<W> LineWriter<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            flush_policy: FlushPolicy::default(),
+            retry_policy: Box::new(NoRetry),
+        }
+    }
+
+    /// Retries the failed writes according to the policy, e.g. [`ExponentialRetry`](crate::util::ExponentialRetry)
+    /// with [`is_transient_io_error`](crate::util::is_transient_io_error). The default is [`NoRetry`].
+    pub fn with_retry_policy<P>(mut self, policy: P) -> Self
+    where
+        P: RetryPolicy<std::io::Error> + Send + Sync + 'static,
+    {
+        self.set_retry_policy(policy);
+        self
+    }
+
+    pub(crate) fn set_retry_policy<P>(&mut self, policy: P)
+    where
+        P: RetryPolicy<std::io::Error> + Send + Sync + 'static,
+    {
+        self.retry_policy = Box::new(policy);
+    }
+
+    pub fn with_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+
+    /// Flushes the underlying writer, retrying according to the retry policy.
+    pub async fn flush(&mut self) -> std::io::Result<()> {
+        self.write_all_with_retries(&[], true).await
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes the bytes and flushes them if asked to, retrying the failed writes according to the retry policy.
+    async fn write_all_with_retries(&mut self, bytes: &[u8], flush: bool) -> std::io::Result<()> {
+        let mut written = 0;
+        let mut failures = 0;
+        loop {
+            let res = if written < bytes.len() {
+                match self.writer.write(&bytes[written..]).await {
+                    Ok(0) => Err(std::io::ErrorKind::WriteZero.into()),
+                    Ok(n) => {
+                        written += n;
+                        failures = 0;
+                        continue;
+                    }
+                    Err(e) => Err(e),
+                }
+            } else if flush {
+                self.writer.flush().await
+            } else {
+                Ok(())
+            };
+            match res {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    failures += 1;
+                    let Some(delay) = self.retry_policy.retry_after(&e, failures) else {
+                        return Err(e);
+                    };
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<W> AsyncWritable for LineWriter<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    type Error = std::io::Error;
+
+    async fn write_lines(&mut self, text: &str) -> Result<(), Self::Error> {
+        let mut buf = String::with_capacity(text.len() + 1);
+        for line in text.lines() {
+            buf.push_str(line);
+            buf.push('\n');
+        }
+        if buf.is_empty() {
+            buf.push('\n');
+        }
+        let flush = self.flush_policy == FlushPolicy::Always;
+        self.write_all_with_retries(buf.as_bytes(), flush).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::{
+        engine_commands::{BestMoveCommand, ReadyOkCommand},
+        util::{ExponentialRetry, is_transient_io_error},
+    };
+
+    /// A writer that accepts at most `chunk` bytes per poll, returning `Pending` every other poll,
+    /// and fails the first `failures` writes with `WouldBlock`.
+    #[derive(Default)]
+    struct TrickleWriter {
+        written: Vec<u8>,
+        chunk: usize,
+        failures: usize,
+        pending: bool,
+        flushes: usize,
+    }
+
+    impl AsyncWrite for TrickleWriter {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Poll::Ready(Err(std::io::ErrorKind::WouldBlock.into()));
+            }
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let n = buf.len().min(self.chunk);
+            self.written.extend_from_slice(&buf[..n]);
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            self.flushes += 1;
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn trickle(chunk: usize) -> TrickleWriter {
+        TrickleWriter {
+            chunk,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_command_in_small_chunks() {
+        let mut writer = LineWriter::new(trickle(3));
+        writer.write_command(&ReadyOkCommand).await.unwrap();
+        writer
+            .write_command(
+                &"bestmove e2e4 ponder e7e5"
+                    .parse::<BestMoveCommand>()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let writer = writer.into_inner();
+        assert_eq!(writer.written, b"readyok\nbestmove e2e4 ponder e7e5\n");
+        assert_eq!(writer.flushes, 2);
+    }
+
+    #[tokio::test]
+    async fn test_write_lines_normalizes_the_terminators() {
+        let mut writer = LineWriter::new(trickle(1));
+        writer
+            .write_lines("id name Fake\r\nid author Nobody\n\nuciok")
+            .await
+            .unwrap();
+        assert_eq!(
+            writer.get_ref().written,
+            b"id name Fake\nid author Nobody\n\nuciok\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_manual_flush() {
+        let mut writer = LineWriter::new(trickle(4)).with_flush_policy(FlushPolicy::Manual);
+        writer.write_lines("isready").await.unwrap();
+        writer.write_lines("quit").await.unwrap();
+        assert_eq!(writer.get_ref().flushes, 0);
+        writer.flush().await.unwrap();
+        assert_eq!(writer.get_ref().written, b"isready\nquit\n");
+        assert_eq!(writer.get_ref().flushes, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries() {
+        let mut writer = LineWriter::new(TrickleWriter {
+            failures: 1,
+            ..trickle(2)
+        });
+        let err = writer.write_lines("isready").await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+        assert!(writer.get_ref().written.is_empty());
+
+        let policy = ExponentialRetry::new(
+            2,
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+            is_transient_io_error,
+        );
+        let mut writer = LineWriter::new(TrickleWriter {
+            failures: 1,
+            ..trickle(2)
+        })
+        .with_retry_policy(policy);
+        writer.write_lines("isready").await.unwrap();
+        assert_eq!(writer.get_ref().written, b"isready\n");
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use crate::util::{Clock, SystemClock};
+
+/// An exponential backoff schedule, e.g. for reconnecting to an engine.
+///
+/// The delay starts at `initial`, doubles after every consecutive failure and is capped at `max`.
+/// The schedule doesn't sleep by itself: the caller waits for [`Backoff::remaining`] before
+/// the next attempt, which keeps the schedule testable with a fake [`Clock`].
+#[derive(Debug, Clone)]
+pub struct Backoff<C = SystemClock> {
+    clock: C,
+    initial: Duration,
+    max: Duration,
+    failures: u32,
+    retry_at: Option<Duration>,
+}
+
+impl<C: Clock> Backoff<C> {
+    pub fn new(clock: C, initial: Duration, max: Duration) -> Self {
+        Self {
+            clock,
+            initial,
+            max,
+            failures: 0,
+            retry_at: None,
+        }
+    }
+
+    /// The number of consecutive failures.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// The delay before the next attempt after the next failure.
+    pub fn next_delay(&self) -> Duration {
+        exponential_delay(self.initial, self.max, self.failures)
+    }
+
+    /// Records a failed attempt and schedules the next one.
+    pub fn failed(&mut self) {
+        let delay = self.next_delay();
+        self.retry_at = Some(self.clock.now().since_origin() + delay);
+        self.failures = self.failures.saturating_add(1);
+    }
+
+    /// Records a successful attempt, resetting the schedule.
+    pub fn succeeded(&mut self) {
+        self.failures = 0;
+        self.retry_at = None;
+    }
+
+    /// The time left until the next attempt is allowed.
+    pub fn remaining(&self) -> Duration {
+        match self.retry_at {
+            Some(retry_at) => retry_at.saturating_sub(self.clock.now().since_origin()),
+            None => Duration::ZERO,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.remaining().is_zero()
+    }
+}
+
+/// The delay after `failures` consecutive failures, starting at `initial`, doubling and capped at `max`.
+pub(crate) fn exponential_delay(initial: Duration, max: Duration, failures: u32) -> Duration {
+    let factor = 2u32.saturating_pow(failures);
+    initial.saturating_mul(factor).min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::util::Timestamp;
+
+    /// A clock that only moves when told to.
+    #[derive(Clone, Default)]
+    struct FakeClock(Rc<Cell<Duration>>);
+
+    impl FakeClock {
+        fn advance(&self, by: Duration) {
+            self.0.set(self.0.get() + by);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Timestamp {
+            Timestamp::from_monotonic(self.0.get())
+        }
+    }
+
+    #[test]
+    fn test_backoff_with_fake_clock() {
+        let clock = FakeClock::default();
+        let mut backoff = Backoff::new(
+            clock.clone(),
+            Duration::from_millis(100),
+            Duration::from_millis(500),
+        );
+        assert!(backoff.is_ready());
+
+        backoff.failed();
+        assert_eq!(backoff.remaining(), Duration::from_millis(100));
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(backoff.remaining(), Duration::from_millis(40));
+        assert!(!backoff.is_ready());
+        clock.advance(Duration::from_millis(40));
+        assert!(backoff.is_ready());
+
+        backoff.failed();
+        assert_eq!(backoff.remaining(), Duration::from_millis(200));
+        backoff.failed();
+        assert_eq!(backoff.remaining(), Duration::from_millis(400));
+        backoff.failed();
+        assert_eq!(backoff.remaining(), Duration::from_millis(500));
+        assert_eq!(backoff.failures(), 4);
+
+        backoff.succeeded();
+        assert!(backoff.is_ready());
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    }
+}
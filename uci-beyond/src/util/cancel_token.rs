@@ -0,0 +1,145 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    task::Waker,
+};
+
+/// A flag for cancelling a long-running operation, e.g. [`Session::evaluate_positions_with`](crate::session::Session::evaluate_positions_with),
+/// from another task or thread.
+///
+/// The clones share the flag, so the operation is cancelled with any of them. The operation checks the flag
+/// at the points where stopping leaves the connection usable, e.g. between the positions of a batch.
+/// To cancel a read, see [`CancellableReader`](crate::util::CancellableReader).
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<Inner>);
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    /// The tasks waiting for a line, by the key of their [`WakerSlot`], woken on cancellation.
+    wakers: Mutex<HashMap<u64, Waker>>,
+    next_key: AtomicU64,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the operation. Cancelling again has no effect.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        let wakers = std::mem::take(&mut *self.0.wakers.lock().unwrap());
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// The slot for the waker of a reader, e.g. of a [`CancellableReader`](crate::util::CancellableReader).
+    pub(crate) fn waker_slot(&self) -> WakerSlot {
+        WakerSlot {
+            token: self.clone(),
+            key: self.0.next_key.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+/// The waker of the task waiting on behalf of one reader, woken on cancellation.
+///
+/// A waker replaces the previous one of the slot, and is forgotten once the reader stops waiting
+/// or is dropped, so the wakers don't pile up with the tasks that used a long-lived token.
+#[derive(Debug)]
+pub(crate) struct WakerSlot {
+    token: CancelToken,
+    key: u64,
+}
+
+impl WakerSlot {
+    pub(crate) fn token(&self) -> &CancelToken {
+        &self.token
+    }
+
+    /// Wakes the task of the waker on cancellation. The waker is woken at most once.
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut wakers = self.token.0.wakers.lock().unwrap();
+        match wakers.get(&self.key) {
+            Some(registered) if registered.will_wake(waker) => {}
+            _ => {
+                wakers.insert(self.key, waker.clone());
+            }
+        }
+    }
+
+    /// Forgets the waker, e.g. once the line is read.
+    pub(crate) fn clear(&self) {
+        self.token
+            .0
+            .wakers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.key);
+    }
+}
+
+impl Drop for WakerSlot {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_the_flag() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_cancel_wakes_registered_tasks() {
+        let token = CancelToken::new();
+        let waker = futures::task::noop_waker();
+        let slot = token.waker_slot();
+        slot.register(&waker);
+        slot.register(&waker);
+        assert_eq!(token.0.wakers.lock().unwrap().len(), 1);
+
+        token.cancel();
+        assert!(token.0.wakers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_wakers_of_finished_readers_are_forgotten() {
+        let token = CancelToken::new();
+        let waker = futures::task::noop_waker();
+        // Many reads of a long-lived token, one after another
+        for _ in 0..100 {
+            let slot = token.waker_slot();
+            slot.register(&waker);
+        }
+        assert!(token.0.wakers.lock().unwrap().is_empty());
+
+        let first = token.waker_slot();
+        let second = token.waker_slot();
+        first.register(&waker);
+        second.register(&waker);
+        assert_eq!(token.0.wakers.lock().unwrap().len(), 2);
+        // The line was read
+        first.clear();
+        assert_eq!(token.0.wakers.lock().unwrap().len(), 1);
+        drop(second);
+        assert!(token.0.wakers.lock().unwrap().is_empty());
+    }
+}
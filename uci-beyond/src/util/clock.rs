@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+/// A source of [`Timestamp`]s.
+///
+/// Injecting a clock instead of calling [`Instant::now`] directly lets the tests drive
+/// the time-dependent logic (e.g. [`Backoff`](crate::util::Backoff)) without sleeping.
+pub trait Clock {
+    fn now(&self) -> Timestamp;
+}
+
+impl<C: Clock + ?Sized> Clock for Box<C> {
+    fn now(&self) -> Timestamp {
+        (**self).now()
+    }
+}
+
+/// A point in time as seen by a [`Clock`].
+///
+/// The monotonic part is measured from an origin chosen by the clock, so only the timestamps
+/// of the same clock can be compared. With the `time` feature, the timestamp may also carry
+/// the wall-clock time for transcripts and reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    since_origin: Duration,
+    #[cfg(feature = "time")]
+    wall_time: Option<time::OffsetDateTime>,
+}
+
+impl Timestamp {
+    /// Creates a timestamp without the wall-clock time.
+    pub fn from_monotonic(since_origin: Duration) -> Self {
+        Self {
+            since_origin,
+            #[cfg(feature = "time")]
+            wall_time: None,
+        }
+    }
+
+    #[cfg(feature = "time")]
+    pub fn with_wall_time(self, wall_time: time::OffsetDateTime) -> Self {
+        Self {
+            wall_time: Some(wall_time),
+            ..self
+        }
+    }
+
+    /// The monotonic time elapsed since the origin of the clock.
+    pub fn since_origin(&self) -> Duration {
+        self.since_origin
+    }
+
+    /// The monotonic time elapsed since the `earlier` timestamp, or zero if it is in fact later.
+    pub fn duration_since(&self, earlier: Timestamp) -> Duration {
+        self.since_origin.saturating_sub(earlier.since_origin)
+    }
+
+    #[cfg(feature = "time")]
+    pub fn wall_time(&self) -> Option<time::OffsetDateTime> {
+        self.wall_time
+    }
+}
+
+/// The [`Clock`] backed by [`Instant`], without the wall-clock time.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::from_monotonic(self.origin.elapsed())
+    }
+}
+
+/// The [`Clock`] backed by [`Instant`] and [`time::OffsetDateTime::now_utc`].
+#[cfg(feature = "time")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WallClock {
+    monotonic: SystemClock,
+}
+
+#[cfg(feature = "time")]
+impl Clock for WallClock {
+    fn now(&self) -> Timestamp {
+        self.monotonic
+            .now()
+            .with_wall_time(time::OffsetDateTime::now_utc())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_clock_is_monotonic() {
+        let clock = SystemClock::default();
+        let earlier = clock.now();
+        let later = clock.now();
+        assert!(later.since_origin() >= earlier.since_origin());
+        assert_eq!(earlier.duration_since(later), Duration::ZERO);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_wall_clock_has_wall_time() {
+        assert!(WallClock::default().now().wall_time().is_some());
+        assert!(SystemClock::default().now().wall_time().is_none());
+    }
+}
@@ -0,0 +1,280 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    gui_commands::{SetOptionCommand, UciCommandTrait},
+    util::{AsyncReadable, Clock, Connection, ResponseHandle, SystemClock},
+};
+
+/// A `setoption` command waiting for the end of the burst of changes of its option.
+#[derive(Debug)]
+struct PendingOption {
+    cmd: SetOptionCommand,
+    /// When the option was last changed, since the origin of the clock.
+    changed_at: Duration,
+}
+
+/// A [`Connection`] that debounces the `setoption` commands, e.g. while the user drags a slider
+/// of the GUI, so that the engine doesn't reload a network or reallocate the hash for every value.
+///
+/// The options set with [`CoalescingConfigurator::set_option`] are queued by name, and only the
+/// last value of an option is sent once it hasn't changed for the window. Like [`Backoff`](crate::util::Backoff),
+/// the configurator doesn't sleep by itself: the caller sends the options that are due with
+/// [`CoalescingConfigurator::flush_due`], e.g. after waiting for [`CoalescingConfigurator::remaining`].
+///
+/// Every other command, including `go` and `isready`, first flushes the whole queue, so the engine
+/// always searches with the options set before. The commands sent with [`Connection::send`] are never
+/// queued, even `setoption` ones.
+pub struct CoalescingConfigurator<C, K = SystemClock> {
+    connection: C,
+    clock: K,
+    window: Duration,
+    /// In the order the options were first changed.
+    pending: Vec<PendingOption>,
+}
+
+impl<C: Connection, K: Clock> CoalescingConfigurator<C, K> {
+    pub fn new(connection: C, clock: K, window: Duration) -> Self {
+        Self {
+            connection,
+            clock,
+            window,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// The number of options waiting to be sent.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// The time left until the next pending option is due, if any is pending.
+    pub fn remaining(&self) -> Option<Duration> {
+        let now = self.clock.now().since_origin();
+        self.pending
+            .iter()
+            .map(|pending| (pending.changed_at + self.window).saturating_sub(now))
+            .min()
+    }
+
+    /// Queues the option, replacing its pending value if any, and sends the options that are due.
+    ///
+    /// The option names are compared case-insensitively, as per the UCI protocol.
+    pub async fn set_option(&mut self, cmd: SetOptionCommand) -> Result<(), C::Err> {
+        let changed_at = self.clock.now().since_origin();
+        match self
+            .pending
+            .iter_mut()
+            .find(|pending| pending.cmd.name().eq_ignore_ascii_case(cmd.name()))
+        {
+            Some(pending) => {
+                pending.cmd = cmd;
+                pending.changed_at = changed_at;
+            }
+            None => self.pending.push(PendingOption { cmd, changed_at }),
+        }
+        self.flush_due().await
+    }
+
+    /// Sends the options that haven't changed for the window.
+    pub async fn flush_due(&mut self) -> Result<(), C::Err> {
+        let now = self.clock.now().since_origin();
+        let window = self.window;
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|pending| now.saturating_sub(pending.changed_at) >= window);
+        self.pending = pending;
+        self.send_all(due).await
+    }
+
+    /// Sends all pending options at once, regardless of the window.
+    pub async fn flush(&mut self) -> Result<(), C::Err> {
+        let pending = std::mem::take(&mut self.pending);
+        self.send_all(pending).await
+    }
+
+    async fn send_all(&mut self, options: Vec<PendingOption>) -> Result<(), C::Err> {
+        let mut options = options.into_iter();
+        while let Some(option) = options.next() {
+            if let Err(e) = self.connection.send(option.cmd).await {
+                // The options that weren't sent are still pending
+                let mut unsent: Vec<PendingOption> = options.collect();
+                unsent.append(&mut self.pending);
+                self.pending = unsent;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the connection **without** sending the pending options.
+    pub fn into_inner(self) -> C {
+        self.connection
+    }
+}
+
+#[async_trait(?Send)]
+impl<C: Connection, K: Clock> Connection for CoalescingConfigurator<C, K> {
+    type Err = C::Err;
+
+    async fn send<Cmd>(
+        &mut self,
+        cmd: Cmd,
+    ) -> Result<Result<Cmd::Response, <Cmd::Response as AsyncReadable>::Err>, Self::Err>
+    where
+        Cmd: UciCommandTrait,
+        Cmd::Response: AsyncReadable,
+    {
+        self.flush().await?;
+        self.connection.send(cmd).await
+    }
+
+    async fn send_expecting_later_response<Cmd, T>(
+        &mut self,
+        cmd: Cmd,
+    ) -> Result<ResponseHandle<T, Self::Err>, Self::Err>
+    where
+        Cmd: UciCommandTrait,
+        T: AsyncReadable + 'static,
+    {
+        self.flush().await?;
+        self.connection
+            .send_expecting_later_response::<_, T>(cmd)
+            .await
+    }
+
+    async fn send_raw_line(&mut self, line: &str) -> Result<(), Self::Err> {
+        self.flush().await?;
+        self.connection.send_raw_line(line).await
+    }
+
+    async fn read_raw_line(&mut self) -> Result<Option<String>, Self::Err> {
+        self.connection.read_raw_line().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use tokio::io::{AsyncReadExt as _, DuplexStream};
+
+    use super::*;
+    use crate::{
+        gui_command_responses::GoResponse,
+        gui_commands::{GoCommand, IsReadyCommand},
+        util::{IoConnection, Timestamp},
+    };
+
+    /// A clock that only moves when told to.
+    #[derive(Clone, Default)]
+    struct FakeClock(Rc<Cell<Duration>>);
+
+    impl FakeClock {
+        fn advance(&self, by: Duration) {
+            self.0.set(self.0.get() + by);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Timestamp {
+            Timestamp::from_monotonic(self.0.get())
+        }
+    }
+
+    type TestConfigurator =
+        CoalescingConfigurator<IoConnection<&'static [u8], DuplexStream>, FakeClock>;
+
+    /// A configurator over an engine with the given output, and the other end of its input.
+    fn configurator(output: &'static str, clock: &FakeClock) -> (TestConfigurator, DuplexStream) {
+        let (writer, received) = tokio::io::duplex(4096);
+        let connection = IoConnection::new(output.as_bytes(), writer);
+        let configurator =
+            CoalescingConfigurator::new(connection, clock.clone(), Duration::from_millis(100));
+        (configurator, received)
+    }
+
+    async fn received_lines(configurator: TestConfigurator, mut received: DuplexStream) -> String {
+        // Dropping the writer ends the input of the engine
+        drop(configurator);
+        let mut s = String::new();
+        received.read_to_string(&mut s).await.unwrap();
+        s
+    }
+
+    #[tokio::test]
+    async fn test_rapid_changes_are_coalesced() {
+        let clock = FakeClock::default();
+        let (mut configurator, received) = configurator("", &clock);
+
+        for value in 1..=10 {
+            configurator
+                .set_option(SetOptionCommand::Hash { value: value * 16 })
+                .await
+                .unwrap();
+            clock.advance(Duration::from_millis(10));
+        }
+        assert_eq!(configurator.pending_len(), 1);
+        assert_eq!(configurator.remaining(), Some(Duration::from_millis(90)));
+
+        // Not due yet
+        configurator.flush_due().await.unwrap();
+        assert_eq!(configurator.pending_len(), 1);
+
+        clock.advance(Duration::from_millis(90));
+        assert_eq!(configurator.remaining(), Some(Duration::ZERO));
+        configurator.flush_due().await.unwrap();
+        assert_eq!(configurator.pending_len(), 0);
+        assert_eq!(configurator.remaining(), None);
+
+        assert_eq!(
+            received_lines(configurator, received).await,
+            "setoption name Hash value 160\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_go_and_isready_flush_first() {
+        let clock = FakeClock::default();
+        let (mut configurator, received) = configurator("bestmove e2e4\n", &clock);
+
+        configurator
+            .set_option(SetOptionCommand::MultiPV { value: 3 })
+            .await
+            .unwrap();
+        configurator
+            .set_option(SetOptionCommand::Hash { value: 64 })
+            .await
+            .unwrap();
+        configurator
+            .set_option(SetOptionCommand::MultiPV { value: 2 })
+            .await
+            .unwrap();
+        let Ok(()) = configurator.send(IsReadyCommand).await.unwrap();
+        assert_eq!(configurator.pending_len(), 0);
+
+        configurator
+            .set_option(SetOptionCommand::Threads { value: 4 })
+            .await
+            .unwrap();
+        let search = configurator
+            .send_expecting_later_response::<_, GoResponse>(GoCommand::for_depth(1))
+            .await
+            .unwrap();
+        search.await.unwrap().unwrap();
+
+        assert_eq!(
+            received_lines(configurator, received).await,
+            "setoption name MultiPV value 2\n\
+            setoption name Hash value 64\n\
+            isready\n\
+            setoption name Threads value 4\n\
+            go depth 1\n"
+        );
+    }
+}
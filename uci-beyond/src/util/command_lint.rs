@@ -0,0 +1,205 @@
+use std::fmt::Display;
+
+use crate::{command::Command as _, gui_commands::SetOptionCommand};
+
+/// The length of the longest line that [`lint_command`] accepts without a [`CommandLint::LineTooLong`],
+/// e.g. for the fixed-size input buffers of older engines.
+///
+/// The `position` command of a game of 400 plies is about 2000 bytes long.
+pub const MAX_COMMAND_LINE_LEN: usize = 4096;
+
+/// How bad a [`CommandLint`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintSeverity {
+    /// Some engines may misread the command.
+    Warning,
+    /// The command must not be sent, e.g. since it would smuggle in another one.
+    Error,
+}
+
+/// A problem with the wire format of a command about to be sent, found by [`lint_command`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CommandLint {
+    /// A line break in the command, e.g. in the value of a `setoption`, which ends the command there,
+    /// so the rest of it is read as another command, e.g. `quit`.
+    #[error("line break at byte {index} would start another command")]
+    LineBreak { index: usize },
+    /// A tab, which some engines don't take for whitespace.
+    #[error("tab at byte {index} (expected a space)")]
+    Tab { index: usize },
+    /// A name of an option with characters other than ASCII, which some engines compare byte by byte.
+    #[error("non-ASCII option name '{name}'")]
+    NonAsciiOptionName { name: String },
+    /// A line longer than [`MAX_COMMAND_LINE_LEN`].
+    #[error("line of {len} bytes is longer than {max} bytes")]
+    LineTooLong { len: usize, max: usize },
+}
+
+impl CommandLint {
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            CommandLint::LineBreak { .. } => LintSeverity::Error,
+            CommandLint::Tab { .. }
+            | CommandLint::NonAsciiOptionName { .. }
+            | CommandLint::LineTooLong { .. } => LintSeverity::Warning,
+        }
+    }
+}
+
+/// Whether a [`Connection`](crate::util::Connection) checks the commands with [`lint_command`] before writing them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LintPolicy {
+    /// The commands are written as they are.
+    #[default]
+    Off,
+    /// A command with a [`LintSeverity::Error`] lint isn't written and fails with
+    /// [`std::io::ErrorKind::InvalidInput`], whose inner error is the lint.
+    RejectErrors,
+}
+
+impl LintPolicy {
+    /// The first lint of the line that the policy rejects, if any.
+    pub(crate) fn rejected(self, line: &str) -> Option<CommandLint> {
+        match self {
+            LintPolicy::Off => None,
+            LintPolicy::RejectErrors => lint_command(line)
+                .into_iter()
+                .find(|lint| lint.severity() == LintSeverity::Error),
+        }
+    }
+}
+
+/// Checks the command, as it is displayed, for what engines choke on, e.g. a line break in the value
+/// of a `setoption`. The lints are in the order of the line, followed by [`CommandLint::LineTooLong`].
+///
+/// The command is expected without a line terminator, like the ones of [`gui_commands`](crate::gui_commands),
+/// so a trailing one is a [`CommandLint::LineBreak`] too.
+///
+/// ```
+/// use uci_beyond::{
+///     gui_commands::SetOptionCommand,
+///     model::SyzygyPathSpec,
+///     util::{CommandLint, LintSeverity, lint_command},
+/// };
+///
+/// let cmd = SetOptionCommand::SyzygyPath {
+///     value: SyzygyPathSpec::new(["/tb\nquit"]),
+/// };
+/// let lints = lint_command(&cmd);
+/// assert_eq!(lints, [CommandLint::LineBreak { index: 35 }]);
+/// assert_eq!(lints[0].severity(), LintSeverity::Error);
+/// ```
+pub fn lint_command(cmd: &(impl Display + ?Sized)) -> Vec<CommandLint> {
+    let line = cmd.to_string();
+    let mut lints = Vec::new();
+    for (index, c) in line.char_indices() {
+        match c {
+            '\n' | '\r' => lints.push(CommandLint::LineBreak { index }),
+            '\t' => lints.push(CommandLint::Tab { index }),
+            _ => {}
+        }
+    }
+    if let Some(name) = option_name(&line)
+        && !name.is_ascii()
+    {
+        lints.push(CommandLint::NonAsciiOptionName {
+            name: name.to_string(),
+        });
+    }
+    if line.len() > MAX_COMMAND_LINE_LEN {
+        lints.push(CommandLint::LineTooLong {
+            len: line.len(),
+            max: MAX_COMMAND_LINE_LEN,
+        });
+    }
+    lints
+}
+
+/// The name of the option of a `setoption` line, i.e. the tokens between `name` and `value`, if any.
+fn option_name(line: &str) -> Option<&str> {
+    let rest = line
+        .strip_prefix(SetOptionCommand::NAME)?
+        .trim_start()
+        .strip_prefix("name")?;
+    let name = match rest.find(" value") {
+        Some(end) => &rest[..end],
+        None => rest,
+    };
+    Some(name.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gui_commands::GoCommand, model};
+
+    #[test]
+    fn test_lint_commands() {
+        assert!(lint_command(&GoCommand::for_depth(20)).is_empty());
+        assert!(lint_command("setoption name Hash value 64").is_empty());
+
+        assert_eq!(
+            lint_command("setoption\tname Hash value 64"),
+            [CommandLint::Tab { index: 9 }]
+        );
+        assert_eq!(
+            lint_command("setoption name Pondération value true"),
+            [CommandLint::NonAsciiOptionName {
+                name: "Pondération".to_string()
+            }]
+        );
+        let custom = SetOptionCommand::Custom {
+            name: "Clear Hash".to_string(),
+            value: None,
+        };
+        assert!(lint_command(&custom).is_empty());
+        assert_eq!(
+            lint_command("isready\r\n"),
+            [
+                CommandLint::LineBreak { index: 7 },
+                CommandLint::LineBreak { index: 8 }
+            ]
+        );
+
+        let moves = vec![model::MoveString::from("g1f3"); 1000];
+        let position = crate::gui_commands::PositionCommand {
+            startpos: model::Position::StartPos,
+            moves: model::MoveList(moves),
+        };
+        let lints = lint_command(&position);
+        assert_eq!(
+            lints,
+            [CommandLint::LineTooLong {
+                len: 5023,
+                max: MAX_COMMAND_LINE_LEN
+            }]
+        );
+        assert_eq!(lints[0].severity(), LintSeverity::Warning);
+    }
+
+    #[test]
+    fn test_injection_through_setoption_value() {
+        let cmd = SetOptionCommand::SyzygyPath {
+            value: model::SyzygyPathSpec::new(["/tb\nquit"]),
+        };
+        let lints = lint_command(&cmd);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].severity(), LintSeverity::Error);
+        assert_eq!(
+            lints[0].to_string(),
+            "line break at byte 35 would start another command"
+        );
+
+        assert_eq!(
+            LintPolicy::RejectErrors.rejected(&cmd.to_string()),
+            Some(lints[0].clone())
+        );
+        assert_eq!(LintPolicy::Off.rejected(&cmd.to_string()), None);
+        // A warning doesn't reject the command
+        assert_eq!(
+            LintPolicy::RejectErrors.rejected("setoption\tname Hash value 64"),
+            None
+        );
+    }
+}
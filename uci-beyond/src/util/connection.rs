@@ -1,11 +1,30 @@
 use async_trait::async_trait;
 
-use crate::{gui_commands::UciCommandTrait, util::AsyncReadable};
+use crate::{
+    gui_commands::UciCommandTrait,
+    util::{AsyncReadable, ResponseHandle},
+};
 
+/// The transport of the commands and their responses, e.g. [`IoConnection`](crate::util::IoConnection)
+/// over the stdio of an engine.
+///
+/// # Stability
+///
+/// The trait is meant to be implemented outside of the crate, e.g. by the WebSocket connection of
+/// `remote-stockfish-client` and by the test doubles. New methods only come with default implementations,
+/// so the implementations keep compiling.
 #[async_trait(?Send)]
 pub trait Connection {
     type Err: std::fmt::Debug + Send;
 
+    /// Sends the command and reads its response.
+    ///
+    /// # Warning
+    ///
+    /// The connection is borrowed until the **whole** response is read. If the response only
+    /// arrives after another command, e.g. the `bestmove` of `go infinite` after `stop`, waiting
+    /// for it here deadlocks because the other command can't be sent in the meantime.
+    /// Use [`Connection::send_expecting_later_response`] for such commands instead.
     async fn send<C>(
         &mut self,
         cmd: C,
@@ -13,4 +32,48 @@ pub trait Connection {
     where
         C: UciCommandTrait,
         C::Response: AsyncReadable;
+
+    /// Sends the command and returns a [`ResponseHandle`] that reads the response `T` when awaited.
+    ///
+    /// Unlike [`Connection::send`], the connection remains usable until the response is read,
+    /// e.g. for sending `stop` after `go infinite`:
+    ///
+    /// ```ignore
+    /// let search = connection
+    ///     .send_expecting_later_response::<_, GoResponse>(go_infinite)
+    ///     .await?;
+    /// connection.send(StopCommand).await??;
+    /// let response = search.await??;
+    /// ```
+    ///
+    /// Only the commands without a response should be sent before the handle is awaited,
+    /// otherwise their responses and `T` are read from the same lines.
+    async fn send_expecting_later_response<C, T>(
+        &mut self,
+        cmd: C,
+    ) -> Result<ResponseHandle<T, Self::Err>, Self::Err>
+    where
+        C: UciCommandTrait,
+        T: AsyncReadable + 'static;
+
+    /// Sends an arbitrary line that the crate doesn't model, e.g. a vendor extension.
+    ///
+    /// The line goes through the same writer as the commands sent with [`Connection::send`],
+    /// so it must **NOT** include a newline character at the end.
+    async fn send_raw_line(&mut self, line: &str) -> Result<(), Self::Err>;
+
+    /// Reads the next line sent by the engine without parsing it.
+    ///
+    /// The line is read from the same reader as the responses to [`Connection::send`],
+    /// so the lines left over by the previous response are returned first. Returns `None`
+    /// when the engine closed the connection.
+    async fn read_raw_line(&mut self) -> Result<Option<String>, Self::Err>;
+
+    /// The number of lines consumed by the last response read by [`Connection::send`] or a [`ResponseHandle`],
+    /// incl. the line at which its parsing failed, if it did, e.g. to tell how far a misbehaving parser got.
+    ///
+    /// `None` until a response has been read, or if the connection doesn't count the lines.
+    fn last_response_lines(&self) -> Option<usize> {
+        None
+    }
 }
@@ -0,0 +1,287 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    transcript::{Direction, FROM_ENGINE_MARKER, TO_ENGINE_MARKER},
+    util::Timestamp,
+};
+
+/// The default number of lines kept by [`ConnectionDiagnostics`].
+pub const DEFAULT_RECENT_LINES_CAPACITY: usize = 64;
+
+/// The state of a connection as seen by its send and read paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConnectionState {
+    /// Nothing went wrong so far.
+    #[default]
+    Open,
+    /// The engine closed the connection, i.e. its output ended.
+    Closed,
+    /// Sending or reading failed, see [`ConnectionDiagnostics::last_error`].
+    Failed,
+}
+
+/// A line sent or received by a connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentLine {
+    pub direction: Direction,
+    /// The line without the line terminator.
+    pub line: String,
+    pub at: Timestamp,
+}
+
+/// A complete log of the lines of a connection in the format of the `Debug Log File` of Stockfish, i.e. `>> `
+/// before the lines sent to the engine and `<< ` before the lines received, so that it can be replayed with
+/// [`parse_transcript`](crate::transcript::parse_transcript).
+///
+/// Unlike the recent lines of [`ConnectionDiagnostics`], nothing is dropped, incl. the lines of
+/// [`Connection::send_raw_line`](crate::util::Connection::send_raw_line) and
+/// [`Connection::read_raw_line`](crate::util::Connection::read_raw_line).
+/// A clone of the log writes to the same writer.
+#[derive(Clone)]
+pub struct TranscriptLog {
+    writer: Arc<Mutex<dyn Write + Send>>,
+}
+
+impl TranscriptLog {
+    /// Logs to the writer, e.g. a [`std::fs::File`]. Each line is written with a single call.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Arc::new(Mutex::new(writer)),
+        }
+    }
+
+    fn record(&self, direction: Direction, line: &str) {
+        let marker = match direction {
+            Direction::ToEngine => TO_ENGINE_MARKER,
+            Direction::FromEngine => FROM_ENGINE_MARKER,
+        };
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        // The log is a side channel, so failing to write it doesn't fail the connection
+        let _ = writer.write_all(format!("{marker} {line}\n").as_bytes());
+    }
+}
+
+impl std::fmt::Debug for TranscriptLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TranscriptLog")
+    }
+}
+
+/// What a connection has been doing lately, e.g. for finding out why a long-running bot misbehaves.
+///
+/// The connections record every line they send or receive, in a ring buffer of the most recent lines,
+/// and count the sent commands by name, e.g. `go` or `setoption`. The diagnostics of a connection are
+/// a snapshot: they don't change after they were taken.
+///
+/// The lines are recorded as they are written and read, so a line received while a response
+/// is read later (see [`Connection::send_expecting_later_response`](crate::util::Connection::send_expecting_later_response))
+/// is recorded when the response reads it.
+#[derive(Debug, Clone)]
+pub struct ConnectionDiagnostics {
+    state: ConnectionState,
+    capacity: usize,
+    recent_lines: VecDeque<RecentLine>,
+    commands_sent: BTreeMap<String, u64>,
+    lines_received: u64,
+    last_response_lines: Option<usize>,
+    last_sent: Option<RecentLine>,
+    last_received: Option<RecentLine>,
+    last_error: Option<(String, Timestamp)>,
+    transcript: Option<TranscriptLog>,
+}
+
+impl Default for ConnectionDiagnostics {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECENT_LINES_CAPACITY)
+    }
+}
+
+impl ConnectionDiagnostics {
+    /// Creates the diagnostics keeping at most `capacity` recent lines.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: ConnectionState::Open,
+            capacity,
+            recent_lines: VecDeque::with_capacity(capacity),
+            commands_sent: BTreeMap::new(),
+            lines_received: 0,
+            last_response_lines: None,
+            last_sent: None,
+            last_received: None,
+            last_error: None,
+            transcript: None,
+        }
+    }
+
+    /// Writes every line recorded from now on to the log too.
+    pub fn set_transcript(&mut self, transcript: TranscriptLog) {
+        self.transcript = Some(transcript);
+    }
+
+    /// Records the line written to the engine, counting it by its first token.
+    pub fn record_sent(&mut self, line: &str, at: Timestamp) {
+        if let Some(name) = line.split_whitespace().next() {
+            *self.commands_sent.entry(name.to_string()).or_default() += 1;
+        }
+        if let Some(transcript) = &self.transcript {
+            transcript.record(Direction::ToEngine, line);
+        }
+        let line = RecentLine {
+            direction: Direction::ToEngine,
+            line: line.to_string(),
+            at,
+        };
+        self.last_sent = Some(line.clone());
+        self.push(line);
+    }
+
+    /// Records the line read from the engine.
+    pub fn record_received(&mut self, line: &str, at: Timestamp) {
+        self.lines_received += 1;
+        if let Some(transcript) = &self.transcript {
+            transcript.record(Direction::FromEngine, line);
+        }
+        let line = RecentLine {
+            direction: Direction::FromEngine,
+            line: line.to_string(),
+            at,
+        };
+        self.last_received = Some(line.clone());
+        self.push(line);
+    }
+
+    /// Records the number of lines consumed by the response just read, as counted by
+    /// a [`LineCountingReader`](crate::util::LineCountingReader).
+    pub fn record_response_lines(&mut self, lines: usize) {
+        self.last_response_lines = Some(lines);
+    }
+
+    /// Records that the engine closed the connection, unless the connection failed before.
+    pub fn record_closed(&mut self) {
+        if self.state == ConnectionState::Open {
+            self.state = ConnectionState::Closed;
+        }
+    }
+
+    /// Records the error of sending or reading, after which the connection is [`ConnectionState::Failed`].
+    pub fn record_error(&mut self, error: &impl std::fmt::Debug, at: Timestamp) {
+        self.state = ConnectionState::Failed;
+        self.last_error = Some((format!("{error:?}"), at));
+    }
+
+    fn push(&mut self, line: RecentLine) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.recent_lines.len() == self.capacity {
+            self.recent_lines.pop_front();
+        }
+        self.recent_lines.push_back(line);
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// The most recent lines in both directions, from the oldest to the newest.
+    pub fn recent_lines(&self) -> impl Iterator<Item = &RecentLine> {
+        self.recent_lines.iter()
+    }
+
+    /// The number of the sent commands with the name, e.g. `go`.
+    pub fn commands_sent(&self, name: &str) -> u64 {
+        self.commands_sent.get(name).copied().unwrap_or(0)
+    }
+
+    /// The number of the sent commands by name.
+    pub fn commands_sent_by_name(&self) -> &BTreeMap<String, u64> {
+        &self.commands_sent
+    }
+
+    pub fn lines_received(&self) -> u64 {
+        self.lines_received
+    }
+
+    /// The number of lines consumed by the last response, incl. the line at which its parsing failed, if it did,
+    /// e.g. to tell how far a misbehaving parser got. `None` until a response has been read.
+    pub fn last_response_lines(&self) -> Option<usize> {
+        self.last_response_lines
+    }
+
+    pub fn last_sent(&self) -> Option<&RecentLine> {
+        self.last_sent.as_ref()
+    }
+
+    pub fn last_received(&self) -> Option<&RecentLine> {
+        self.last_received.as_ref()
+    }
+
+    /// The last error as formatted with [`Debug`](std::fmt::Debug), since the errors may not be [`Clone`].
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_ref().map(|(error, _)| error.as_str())
+    }
+
+    pub fn last_error_at(&self) -> Option<Timestamp> {
+        self.last_error.as_ref().map(|(_, at)| *at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn at(ms: u64) -> Timestamp {
+        Timestamp::from_monotonic(Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn test_ring_buffer_and_counters() {
+        let mut diagnostics = ConnectionDiagnostics::new(3);
+        diagnostics.record_sent("setoption name Hash value 64", at(1));
+        diagnostics.record_sent("setoption name Threads value 2", at(2));
+        diagnostics.record_sent("isready", at(3));
+        diagnostics.record_received("readyok", at(4));
+
+        let recent = diagnostics
+            .recent_lines()
+            .map(|line| line.line.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            recent,
+            ["setoption name Threads value 2", "isready", "readyok"]
+        );
+        assert_eq!(diagnostics.commands_sent("setoption"), 2);
+        assert_eq!(diagnostics.commands_sent("isready"), 1);
+        assert_eq!(diagnostics.commands_sent("go"), 0);
+        assert_eq!(diagnostics.lines_received(), 1);
+        assert_eq!(diagnostics.last_response_lines(), None);
+        diagnostics.record_response_lines(1);
+        assert_eq!(diagnostics.last_response_lines(), Some(1));
+        assert_eq!(diagnostics.last_sent().unwrap().line, "isready");
+        assert_eq!(diagnostics.last_received().unwrap().at, at(4));
+        assert_eq!(diagnostics.state(), ConnectionState::Open);
+    }
+
+    #[test]
+    fn test_state_transitions() {
+        let mut diagnostics = ConnectionDiagnostics::default();
+        diagnostics.record_closed();
+        assert_eq!(diagnostics.state(), ConnectionState::Closed);
+
+        let error = std::io::Error::from(std::io::ErrorKind::BrokenPipe);
+        diagnostics.record_error(&error, at(5));
+        assert_eq!(diagnostics.state(), ConnectionState::Failed);
+        assert!(diagnostics.last_error().unwrap().contains("BrokenPipe"));
+        assert_eq!(diagnostics.last_error_at(), Some(at(5)));
+
+        // A failed connection stays failed
+        diagnostics.record_closed();
+        assert_eq!(diagnostics.state(), ConnectionState::Failed);
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    collections::VecDeque,
+    process::ExitStatus,
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncBufReadExt as _, BufReader},
+    process::{Child, ChildStderr},
+    sync::Mutex,
+    task::JoinHandle,
+};
+
+/// The default number of the last lines of the stderr of an engine kept by [`IoConnection::spawn`](crate::util::IoConnection::spawn).
+pub const DEFAULT_STDERR_TAIL_CAPACITY: usize = 64;
+
+/// How long an error of the connection waits for the engine to exit and for the rest of its stderr.
+const EXIT_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// The error of a connection to an engine that has exited, e.g. after failing to load its NNUE network,
+/// wrapped in the [`std::io::Error`] of the connection.
+///
+/// The error is found with [`std::io::Error::get_ref`] and [`downcast_ref`](std::error::Error::downcast_ref).
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("The engine exited with {}. The last lines of its stderr: {stderr_tail:?}", status.map_or_else(|| "an unknown status".to_string(), |status| status.to_string()))]
+pub struct EngineExited {
+    /// The exit status, unless the engine closed its stdio without exiting in time.
+    pub status: Option<ExitStatus>,
+    pub stderr_tail: Vec<String>,
+}
+
+/// The process of an engine spawned by [`IoConnection::spawn`](crate::util::IoConnection::spawn),
+/// with the last lines of its stderr.
+pub(super) struct EngineProcess {
+    child: Mutex<Child>,
+    stderr_tail: Arc<StdMutex<VecDeque<String>>>,
+    /// Reads the stderr until the engine closes it.
+    stderr_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl EngineProcess {
+    pub(super) fn new(child: Child, stderr: ChildStderr, capacity: usize) -> Self {
+        let stderr_tail = Arc::new(StdMutex::new(VecDeque::with_capacity(capacity)));
+        let stderr_task = tokio::spawn(read_stderr(stderr, Arc::clone(&stderr_tail), capacity));
+        Self {
+            child: Mutex::new(child),
+            stderr_tail,
+            stderr_task: Mutex::new(Some(stderr_task)),
+        }
+    }
+
+    pub(super) fn stderr_tail(&self) -> Vec<String> {
+        self.stderr_tail.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Turns the error of the connection into [`EngineExited`] if the engine has exited, keeping the kind of the error.
+    pub(super) async fn check_exit(&self, error: std::io::Error) -> std::io::Error {
+        let exited = async {
+            let status = self.child.lock().await.wait().await.ok();
+            // The rest of the stderr, e.g. the reason of a crash, is usually written right before exiting
+            if let Some(task) = self.stderr_task.lock().await.take() {
+                let _ = task.await;
+            }
+            status
+        };
+        match tokio::time::timeout(EXIT_GRACE_PERIOD, exited).await {
+            Ok(status) => std::io::Error::new(
+                error.kind(),
+                EngineExited {
+                    status,
+                    stderr_tail: self.stderr_tail(),
+                },
+            ),
+            // Still running, so the error is about the stdio only
+            Err(_elapsed) => error,
+        }
+    }
+}
+
+async fn read_stderr(
+    stderr: ChildStderr,
+    stderr_tail: Arc<StdMutex<VecDeque<String>>>,
+    capacity: usize,
+) {
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(line, "stderr");
+        let mut stderr_tail = stderr_tail.lock().unwrap();
+        if stderr_tail.len() == capacity {
+            stderr_tail.pop_front();
+        }
+        if capacity > 0 {
+            stderr_tail.push_back(line);
+        }
+    }
+}
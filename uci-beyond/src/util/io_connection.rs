@@ -0,0 +1,871 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex as StdMutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, BufReader},
+    sync::{Mutex, Notify},
+};
+
+use crate::{
+    command::Command as _,
+    engine_commands::ReadyOkCommand,
+    gui_commands::{IsReadyCommand, UciCommandTrait},
+    util::{
+        AsyncReadable, AsyncWritable, Clock, Connection, ConnectionDiagnostics,
+        DEFAULT_STDERR_TAIL_CAPACITY, LineCountingReader, LineWriter, LintPolicy, ResponseHandle,
+        RetryPolicy, StringStreamReader, SystemClock, TranscriptLog, Watchdog, WatchdogConfig,
+        engine_process::EngineProcess, read_line, watchdog::ConnectionPinger,
+    },
+};
+
+/// A [`Connection`] over any pair of byte streams, e.g. the stdio of a child process,
+/// a TCP or Unix socket, or a [`tokio::io::duplex`] in the tests.
+///
+/// Each command is written as a line terminated with `\n`, and the responses are read line by line.
+pub struct IoConnection<R, W> {
+    /// Shared with the [`ResponseHandle`]s of the responses expected later and with the [`Watchdog`]s.
+    incoming: Arc<Mutex<Incoming<R>>>,
+    /// Shared with the [`Watchdog`]s.
+    outgoing: Arc<Mutex<Outgoing<W>>>,
+    /// Shared with the read half and the [`Watchdog`]s.
+    pings: Arc<Pings>,
+    /// Shared with both halves, which record the lines.
+    diagnostics: Arc<StdMutex<ConnectionDiagnostics>>,
+    /// The engine spawned by [`IoConnection::spawn`], shared with the [`ResponseHandle`]s.
+    process: Option<Arc<EngineProcess>>,
+}
+
+/// The read half of an [`IoConnection`].
+pub(super) struct Incoming<R> {
+    reader: BufReader<R>,
+    /// The lines peeked at by the previous responses, or read by a [`Watchdog`], that haven't been read yet.
+    pub(super) pending: VecDeque<String>,
+    /// Counts the answers it takes out, for the [`Watchdog`]s.
+    pings: Arc<Pings>,
+    diagnostics: Arc<StdMutex<ConnectionDiagnostics>>,
+    clock: SystemClock,
+}
+
+impl<R> Incoming<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    /// Reads the next line of the responses, i.e. a pending line or else a line from the engine
+    /// that doesn't answer a ping.
+    pub(super) async fn next_line(&mut self) -> std::io::Result<Option<String>> {
+        if let Some(line) = self.pending.pop_front() {
+            return Ok(Some(line));
+        }
+        loop {
+            match self.read_from_engine().await? {
+                Some(line) if self.take_pong(&line) => continue,
+                line => return Ok(line),
+            }
+        }
+    }
+
+    /// Reads the next line from the engine, bypassing the pending lines.
+    pub(super) async fn read_from_engine(&mut self) -> std::io::Result<Option<String>> {
+        let line = read_line(&mut self.reader).await;
+        let mut diagnostics = self.diagnostics.lock().unwrap();
+        match &line {
+            Ok(Some(line)) => {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(line, bytes = line.len() + 1, "received");
+                diagnostics.record_received(line, self.clock.now());
+            }
+            Ok(None) => diagnostics.record_closed(),
+            Err(e) => diagnostics.record_error(e, self.clock.now()),
+        }
+        line
+    }
+
+    /// Whether the line answers a ping, in which case it's counted and doesn't reach the responses.
+    pub(super) fn take_pong(&mut self, line: &str) -> bool {
+        self.pings.take_pong(line)
+    }
+}
+
+/// The `isready` pings of the [`Watchdog`]s, counted outside of the read half, so that a watchdog
+/// learns of the answer even while a response holds the read half.
+///
+/// The `isready` commands sent through the connection are counted as well. The engine answers every
+/// `isready` in order and the answers are alike, so the first answers go to the commands and only
+/// the extra ones to the pings.
+#[derive(Debug, Default)]
+pub(super) struct Pings {
+    /// The `isready` commands that haven't been answered yet, whose answers reach the responses.
+    commands_in_flight: AtomicUsize,
+    /// The pings that haven't been answered yet.
+    in_flight: AtomicUsize,
+    /// The `readyok` answers to the pings so far.
+    pongs: AtomicU64,
+    answered: Notify,
+}
+
+impl Pings {
+    /// Puts a ping in flight and returns the count of the answers so far.
+    pub(super) fn start(&self) -> u64 {
+        let pongs = self.pongs();
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        pongs
+    }
+
+    /// Takes back a ping that wasn't sent, so that its answer isn't awaited.
+    pub(super) fn cancel(&self) {
+        let _ = self
+            .in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+    }
+
+    pub(super) fn pongs(&self) -> u64 {
+        self.pongs.load(Ordering::SeqCst)
+    }
+
+    /// Counts an `isready` command about to be written, before the engine can answer it.
+    fn start_command(&self) {
+        self.commands_in_flight.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Takes back an `isready` command that wasn't written.
+    fn cancel_command(&self) {
+        let _ = self
+            .commands_in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+    }
+
+    /// Waits for the next answer. The future is registered once created, so an answer counted
+    /// after the creation isn't missed.
+    pub(super) fn answered(&self) -> tokio::sync::futures::Notified<'_> {
+        self.answered.notified()
+    }
+
+    fn take_pong(&self, line: &str) -> bool {
+        if line.trim() != ReadyOkCommand::NAME
+            || self
+                .commands_in_flight
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            || self
+                .in_flight
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_err()
+        {
+            return false;
+        }
+        self.pongs.fetch_add(1, Ordering::SeqCst);
+        self.answered.notify_waiters();
+        true
+    }
+}
+
+/// The write half of an [`IoConnection`].
+pub(super) struct Outgoing<W> {
+    writer: LineWriter<W>,
+    lint_policy: LintPolicy,
+    /// Counts the `isready` commands, so that their answers aren't taken for the answers to the pings.
+    pings: Arc<Pings>,
+    diagnostics: Arc<StdMutex<ConnectionDiagnostics>>,
+    clock: SystemClock,
+}
+
+impl<W> Outgoing<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// Writes the line followed by `\n`, unless the [`LintPolicy`] rejects it.
+    ///
+    /// The answer to an `isready` reaches the responses, even while a [`Watchdog`] ping is in flight.
+    pub(super) async fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let is_ready = line.trim() == IsReadyCommand::NAME;
+        if is_ready {
+            self.pings.start_command();
+        }
+        let res = self.write(line).await;
+        if is_ready && res.is_err() {
+            self.pings.cancel_command();
+        }
+        res
+    }
+
+    /// Writes the `isready` ping of a [`Watchdog`], whose answer doesn't reach the responses.
+    pub(super) async fn write_ping(&mut self) -> std::io::Result<()> {
+        self.write(IsReadyCommand::NAME).await
+    }
+
+    async fn write(&mut self, line: &str) -> std::io::Result<()> {
+        // The connection is still usable, so the rejection isn't recorded as its error
+        if let Some(lint) = self.lint_policy.rejected(line) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, lint));
+        }
+        let res = self.writer.write_lines(line).await;
+        let mut diagnostics = self.diagnostics.lock().unwrap();
+        match &res {
+            Ok(()) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    command = line.split_whitespace().next().unwrap_or_default(),
+                    bytes = line.len() + 1,
+                    "sent"
+                );
+                diagnostics.record_sent(line, self.clock.now());
+            }
+            Err(e) => diagnostics.record_error(e, self.clock.now()),
+        }
+        res
+    }
+}
+
+impl<R, W> IoConnection<R, W>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    pub fn new(reader: R, writer: W) -> Self {
+        let diagnostics = Arc::new(StdMutex::new(ConnectionDiagnostics::default()));
+        let clock = SystemClock::default();
+        let pings = Arc::new(Pings::default());
+        Self {
+            incoming: Arc::new(Mutex::new(Incoming {
+                reader: BufReader::new(reader),
+                pending: VecDeque::new(),
+                pings: Arc::clone(&pings),
+                diagnostics: Arc::clone(&diagnostics),
+                clock,
+            })),
+            outgoing: Arc::new(Mutex::new(Outgoing {
+                writer: LineWriter::new(writer),
+                lint_policy: LintPolicy::default(),
+                pings: Arc::clone(&pings),
+                diagnostics: Arc::clone(&diagnostics),
+                clock,
+            })),
+            pings,
+            diagnostics,
+            process: None,
+        }
+    }
+
+    /// Retries the failed writes according to the policy, e.g. [`ExponentialRetry`](crate::util::ExponentialRetry)
+    /// with [`is_transient_io_error`](crate::util::is_transient_io_error). The default is [`NoRetry`](crate::util::NoRetry).
+    ///
+    /// A line is never written twice: the retried write continues after the bytes already written.
+    ///
+    /// # Panics
+    ///
+    /// If a [`Watchdog`] of the connection is writing at the same time.
+    pub fn with_retry_policy<P>(self, policy: P) -> Self
+    where
+        P: RetryPolicy<std::io::Error> + Send + Sync + 'static,
+    {
+        self.outgoing
+            .try_lock()
+            .expect("The connection is being built, so nothing else writes")
+            .writer
+            .set_retry_policy(policy);
+        self
+    }
+
+    /// Checks every line with [`lint_command`](crate::util::lint_command) before writing it, e.g. to reject
+    /// a `setoption` whose value has a line break, which would smuggle in another command.
+    /// The default is [`LintPolicy::Off`].
+    ///
+    /// # Panics
+    ///
+    /// If a [`Watchdog`] of the connection is writing at the same time.
+    pub fn with_lint_policy(self, policy: LintPolicy) -> Self {
+        self.outgoing
+            .try_lock()
+            .expect("The connection is being built, so nothing else writes")
+            .lint_policy = policy;
+        self
+    }
+
+    /// Writes every line sent or received from now on to the log, incl. the raw lines and the lines read
+    /// by a [`Router`] or a [`Watchdog`].
+    pub fn with_transcript(self, transcript: TranscriptLog) -> Self {
+        self.diagnostics.lock().unwrap().set_transcript(transcript);
+        self
+    }
+
+    /// The snapshot of what the connection has been doing lately, e.g. the last lines and the last error.
+    pub fn diagnostics(&self) -> ConnectionDiagnostics {
+        self.diagnostics.lock().unwrap().clone()
+    }
+
+    /// The [`Watchdog`] that pings the engine with `isready` over this connection, e.g. during a search.
+    ///
+    /// Once the connection is handed over to a [`Router`], use [`Router::watchdog`] instead.
+    pub fn watchdog(&self, config: WatchdogConfig) -> Watchdog
+    where
+        W: Send + 'static,
+    {
+        let pinger = ConnectionPinger {
+            incoming: Arc::clone(&self.incoming),
+            outgoing: Arc::clone(&self.outgoing),
+            pings: Arc::clone(&self.pings),
+        };
+        Watchdog::new(pinger, config)
+    }
+
+    /// Hands the connection over to a [`Router`], which reads the lines in the background and routes them
+    /// to the pending requests, so several commands can be in flight at once.
+    pub fn into_router(self) -> Router<W> {
+        Router::new(self.incoming, self.outgoing, self.diagnostics, self.process)
+    }
+
+    /// The last lines that the engine spawned by [`IoConnection::spawn`] wrote to its stderr, oldest first.
+    ///
+    /// At most [`DEFAULT_STDERR_TAIL_CAPACITY`] lines are kept. Empty for the other connections.
+    pub fn stderr_tail(&self) -> Vec<String> {
+        self.process
+            .as_ref()
+            .map(|process| process.stderr_tail())
+            .unwrap_or_default()
+    }
+
+    async fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let res = self.outgoing.lock().await.write_line(line).await;
+        match res {
+            Err(e) => Err(check_exit(self.process.as_deref(), e).await),
+            Ok(()) => Ok(()),
+        }
+    }
+}
+
+/// The error inside the [`std::io::ErrorKind::UnexpectedEof`] of an [`IoConnection`] or a [`Router`] when
+/// the engine closed its output in the middle of a response.
+///
+/// The error is found with [`std::io::Error::get_ref`] and [`downcast_ref`](std::error::Error::downcast_ref).
+/// The count is also kept as [`ConnectionDiagnostics::last_response_lines`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("The output of the engine ended after {lines_consumed} lines of the response")]
+pub struct TruncatedResponse {
+    /// The lines consumed by the response before the end of the output.
+    pub lines_consumed: usize,
+}
+
+/// Records the number of lines consumed by the response, e.g. for a parsing error, and turns the end
+/// of the output before the response into [`TruncatedResponse`].
+pub(super) fn finish_response<T>(
+    response: std::io::Result<Option<T>>,
+    lines_consumed: usize,
+    diagnostics: &StdMutex<ConnectionDiagnostics>,
+) -> std::io::Result<T> {
+    diagnostics
+        .lock()
+        .unwrap()
+        .record_response_lines(lines_consumed);
+    response?.ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            TruncatedResponse { lines_consumed },
+        )
+    })
+}
+
+/// Turns the error into [`EngineExited`](crate::util::EngineExited) if the engine spawned by
+/// [`IoConnection::spawn`] has exited.
+pub(super) async fn check_exit(
+    process: Option<&EngineProcess>,
+    error: std::io::Error,
+) -> std::io::Error {
+    match process {
+        Some(process) => process.check_exit(error).await,
+        None => error,
+    }
+}
+
+impl IoConnection<tokio::process::ChildStdout, tokio::process::ChildStdin> {
+    /// Spawns the engine, e.g. `tokio::process::Command::new("stockfish")`, and connects to its stdio.
+    ///
+    /// The stderr of the engine is kept in a bounded buffer (see [`IoConnection::stderr_tail`]) and, with
+    /// the `tracing` feature, reported as debug-level events. Once the engine has exited, the errors of
+    /// the connection carry [`EngineExited`](crate::util::EngineExited) with the exit status and the stderr tail.
+    ///
+    /// The connection owns the process, which is killed when the connection is dropped only if
+    /// [`kill_on_drop`](tokio::process::Command::kill_on_drop) was set on the command.
+    pub fn spawn(command: &mut tokio::process::Command) -> std::io::Result<Self> {
+        use std::process::Stdio;
+
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+
+        let mut connection = Self::new(stdout, stdin);
+        connection.process = Some(Arc::new(EngineProcess::new(
+            child,
+            stderr,
+            DEFAULT_STDERR_TAIL_CAPACITY,
+        )));
+        Ok(connection)
+    }
+}
+
+/// Reads the response from the shared read half, counting the lines it consumes.
+///
+/// The read half is locked one line at a time, so a response that never reads
+/// (e.g. `()` of `stop`) doesn't wait for a response being read elsewhere.
+async fn read_response<R, T>(
+    incoming: &Arc<Mutex<Incoming<R>>>,
+    diagnostics: &StdMutex<ConnectionDiagnostics>,
+) -> std::io::Result<Result<T, T::Err>>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    T: AsyncReadable,
+{
+    let lines = futures::stream::unfold(Arc::clone(incoming), |incoming| async move {
+        let line = incoming.lock().await.next_line().await.transpose()?;
+        Some((line, incoming))
+    });
+
+    let mut reader = LineCountingReader::new(StringStreamReader::new(Box::pin(lines)));
+    let response = T::read_from(&mut reader).await;
+    let lines_consumed = reader.lines_consumed();
+    // The line the parser peeked at belongs to whatever is read next
+    if let Some(line) = reader.into_inner().into_peeked_line() {
+        incoming.lock().await.pending.push_front(line);
+    }
+    #[cfg(feature = "tracing")]
+    if let Ok(Some(Err(error))) = &response {
+        tracing::warn!(
+            response = std::any::type_name::<T>(),
+            ?error,
+            lines_consumed,
+            "failed to parse the response"
+        );
+    }
+    finish_response(response, lines_consumed, diagnostics)
+}
+
+#[async_trait(?Send)]
+impl<R, W> Connection for IoConnection<R, W>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    type Err = std::io::Error;
+
+    async fn send<C>(
+        &mut self,
+        cmd: C,
+    ) -> Result<Result<C::Response, <C::Response as AsyncReadable>::Err>, Self::Err>
+    where
+        C: UciCommandTrait,
+        C::Response: AsyncReadable,
+    {
+        self.write_line(&cmd.to_string()).await?;
+        match read_response::<R, C::Response>(&self.incoming, &self.diagnostics).await {
+            Err(e) => Err(check_exit(self.process.as_deref(), e).await),
+            response => response,
+        }
+    }
+
+    async fn send_expecting_later_response<C, T>(
+        &mut self,
+        cmd: C,
+    ) -> Result<ResponseHandle<T, Self::Err>, Self::Err>
+    where
+        C: UciCommandTrait,
+        T: AsyncReadable + 'static,
+    {
+        self.write_line(&cmd.to_string()).await?;
+
+        let incoming = Arc::clone(&self.incoming);
+        let diagnostics = Arc::clone(&self.diagnostics);
+        let process = self.process.clone();
+        Ok(ResponseHandle::new(async move {
+            match read_response::<R, T>(&incoming, &diagnostics).await {
+                Err(e) => Err(check_exit(process.as_deref(), e).await),
+                response => response,
+            }
+        }))
+    }
+
+    async fn send_raw_line(&mut self, line: &str) -> Result<(), Self::Err> {
+        self.write_line(line).await
+    }
+
+    async fn read_raw_line(&mut self) -> Result<Option<String>, Self::Err> {
+        self.incoming.lock().await.next_line().await
+    }
+
+    fn last_response_lines(&self) -> Option<usize> {
+        self.diagnostics.lock().unwrap().last_response_lines()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        fixtures::{FakeEngine, STOCKFISH_UCI_RESPONSE},
+        gui_command_responses::GoResponse,
+        gui_commands::{GoCommand, IsReadyCommand, SetOptionCommand, StopCommand, UciCommand},
+        model::SyzygyPathSpec,
+        transcript::Direction,
+        util::{CommandLint, ConnectionState, ExponentialRetry, is_transient_io_error},
+    };
+
+    /// A minimal engine, which prints its banner in reply to `uci`.
+    fn fake_engine() -> FakeEngine {
+        FakeEngine::new(|line| {
+            Some(match line {
+                "uci" => {
+                    "Fake 1.0 by Nobody\nid name Fake 1.0\nid author Nobody\n\n\
+                    option name Hash type spin default 16 min 1 max 1024\nuciok\n"
+                }
+                "isready" => "readyok\n",
+                "go infinite" => {
+                    "info depth 1 seldepth 1 multipv 1 score cp 20 nodes 20 nps 20000 time 1 pv e2e4\n"
+                }
+                "stop" => "bestmove e2e4\n",
+                _ => "",
+            })
+        })
+    }
+
+    async fn talk<R, W>(connection: &mut IoConnection<R, W>)
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin,
+    {
+        let response = connection.send(UciCommand).await.unwrap().unwrap();
+        assert_eq!(response.id_block.name, "Fake 1.0");
+        assert!(response.option_block.hash.is_some());
+
+        let Ok(()) = connection.send(IsReadyCommand).await.unwrap();
+        // `isready` doesn't read its response
+        assert_eq!(
+            connection.read_raw_line().await.unwrap().as_deref(),
+            Some("readyok")
+        );
+
+        let go_infinite = GoCommand {
+            indefinite: true,
+            ..Default::default()
+        };
+        let search = connection
+            .send_expecting_later_response::<_, GoResponse>(go_infinite)
+            .await
+            .unwrap();
+        let Ok(()) = connection.send(StopCommand).await.unwrap();
+        let response = search.await.unwrap().unwrap();
+        assert_eq!(response.search_summary.depth, Some(1));
+
+        connection.send_raw_line("quit").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_lint_policy_rejects_injection() {
+        let (connection, engine) = fake_engine().connect();
+        let mut connection = connection.with_lint_policy(LintPolicy::RejectErrors);
+
+        // The value would end the command and make the engine quit
+        let injection = SetOptionCommand::SyzygyPath {
+            value: SyzygyPathSpec::new(["/tb\nquit"]),
+        };
+        let error = connection.send(injection).await.unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(matches!(
+            error
+                .get_ref()
+                .and_then(|e| e.downcast_ref::<CommandLint>()),
+            Some(CommandLint::LineBreak { index: 35 })
+        ));
+
+        // Nothing was written, so the connection is still usable
+        let Ok(()) = connection.send(IsReadyCommand).await.unwrap();
+        assert_eq!(
+            connection.read_raw_line().await.unwrap().as_deref(),
+            Some("readyok")
+        );
+        assert_eq!(connection.diagnostics().state(), ConnectionState::Open);
+        connection.send_raw_line("quit").await.unwrap();
+        assert_eq!(engine.await.unwrap(), ["isready", "quit"]);
+    }
+
+    #[tokio::test]
+    async fn test_io_connection_over_duplex() {
+        let (gui, engine) = tokio::io::duplex(1024);
+        let (gui_read, gui_write) = tokio::io::split(gui);
+        let (engine_read, engine_write) = tokio::io::split(engine);
+
+        let mut connection = IoConnection::new(gui_read, gui_write);
+        let (received, ()) = futures::future::join(
+            fake_engine().serve(engine_read, engine_write),
+            talk(&mut connection),
+        )
+        .await;
+        assert_eq!(received, ["uci", "isready", "go infinite", "stop", "quit"]);
+
+        let diagnostics = connection.diagnostics();
+        assert_eq!(diagnostics.state(), ConnectionState::Open);
+        for name in ["uci", "isready", "go", "stop", "quit"] {
+            assert_eq!(diagnostics.commands_sent(name), 1, "{name}");
+        }
+        assert_eq!(diagnostics.last_sent().unwrap().line, "quit");
+        // The greeting, the id and option blocks, `uciok`, `readyok`, the `info` line and `bestmove`
+        assert_eq!(diagnostics.lines_received(), 9);
+        assert_eq!(diagnostics.last_received().unwrap().line, "bestmove e2e4");
+        let tail = diagnostics
+            .recent_lines()
+            .skip(9)
+            .map(|line| (line.direction, line.line.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tail,
+            [
+                (Direction::ToEngine, "go infinite"),
+                (Direction::ToEngine, "stop"),
+                // Read when the search is awaited, i.e. after `stop`
+                (
+                    Direction::FromEngine,
+                    "info depth 1 seldepth 1 multipv 1 score cp 20 nodes 20 nps 20000 time 1 pv e2e4"
+                ),
+                (Direction::FromEngine, "bestmove e2e4"),
+                (Direction::ToEngine, "quit"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lines_consumed_by_the_responses() {
+        let mut connection =
+            IoConnection::new(STOCKFISH_UCI_RESPONSE.as_bytes(), tokio::io::sink());
+        assert_eq!(connection.last_response_lines(), None);
+        let response = connection.send(UciCommand).await.unwrap();
+        assert!(response.is_ok());
+        assert_eq!(connection.last_response_lines(), Some(24));
+        assert_eq!(connection.diagnostics().last_response_lines(), Some(24));
+
+        // The missing empty line after the id block is detected at the third line
+        let output = "id name Stockfish 17.1\n\
+id author the Stockfish developers (see AUTHORS file)\n\
+option name Threads type spin default 1 min 1 max 1024\n\
+uciok\n";
+        let mut connection = IoConnection::new(output.as_bytes(), tokio::io::sink());
+        let response = connection.send(UciCommand).await.unwrap();
+        assert!(response.is_err());
+        assert_eq!(connection.last_response_lines(), Some(3));
+
+        // The output ends before `bestmove`
+        let output = "info depth 1 seldepth 1 multipv 1 score cp 20 nodes 20 nps 20000 time 1 pv e2e4\n\
+info depth 2 seldepth 2 multipv 1 score cp 25 nodes 60 nps 30000 time 2 pv e2e4 e7e5\n";
+        let mut connection = IoConnection::new(output.as_bytes(), tokio::io::sink());
+        let search = connection
+            .send_expecting_later_response::<_, GoResponse>(GoCommand::for_depth(5))
+            .await
+            .unwrap();
+        let error = search.await.unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            error
+                .get_ref()
+                .and_then(|e| e.downcast_ref::<TruncatedResponse>()),
+            Some(&TruncatedResponse { lines_consumed: 2 })
+        );
+        assert_eq!(connection.last_response_lines(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_transcript_log() {
+        let (gui, engine) = tokio::io::duplex(1024);
+        let (gui_read, gui_write) = tokio::io::split(gui);
+        let (engine_read, engine_write) = tokio::io::split(engine);
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut connection = IoConnection::new(gui_read, gui_write)
+            .with_transcript(TranscriptLog::new(file.reopen().unwrap()));
+        futures::future::join(
+            fake_engine().serve(engine_read, engine_write),
+            talk(&mut connection),
+        )
+        .await;
+
+        let log = std::fs::read_to_string(file.path()).unwrap();
+        let entries = crate::transcript::parse_transcript(&log).unwrap();
+        // Every line, unlike the bounded recent lines of the diagnostics
+        assert_eq!(entries.len(), 5 + 9);
+        let lines = entries
+            .iter()
+            .map(|entry| (entry.direction, entry.line.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(lines[0], (Direction::ToEngine, "uci"));
+        assert_eq!(lines[1], (Direction::FromEngine, "Fake 1.0 by Nobody"));
+        // The raw lines are logged like the typed ones
+        assert!(lines.contains(&(Direction::FromEngine, "readyok")));
+        assert_eq!(lines.last(), Some(&(Direction::ToEngine, "quit")));
+        assert!(matches!(
+            entries[entries.len() - 2].parsed,
+            Some(crate::transcript::AnyCommand::Go(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_after_the_engine_exits() {
+        let (gui, engine) = tokio::io::duplex(1024);
+        let (gui_read, gui_write) = tokio::io::split(gui);
+        drop(engine);
+
+        let mut connection = IoConnection::new(gui_read, gui_write);
+        assert!(connection.send(IsReadyCommand).await.is_err());
+        let diagnostics = connection.diagnostics();
+        assert_eq!(diagnostics.state(), ConnectionState::Failed);
+        assert!(diagnostics.last_error().is_some());
+        assert_eq!(diagnostics.last_sent(), None);
+    }
+
+    /// A writer that fails the first `failures` writes with `WouldBlock`, then accepts at most 4 bytes per write.
+    struct FlakyWriter {
+        written: Arc<StdMutex<Vec<u8>>>,
+        failures: usize,
+    }
+
+    impl AsyncWrite for FlakyWriter {
+        fn poll_write(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return std::task::Poll::Ready(Err(std::io::ErrorKind::WouldBlock.into()));
+            }
+            let n = buf.len().min(4);
+            self.written.lock().unwrap().extend_from_slice(&buf[..n]);
+            std::task::Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    fn flaky_connection(
+        failures: usize,
+    ) -> (
+        IoConnection<&'static [u8], FlakyWriter>,
+        Arc<StdMutex<Vec<u8>>>,
+    ) {
+        let written = Arc::new(StdMutex::new(Vec::new()));
+        let writer = FlakyWriter {
+            written: Arc::clone(&written),
+            failures,
+        };
+        (IoConnection::new(&b""[..], writer), written)
+    }
+
+    #[tokio::test]
+    async fn test_no_retries_by_default() {
+        let (mut connection, written) = flaky_connection(1);
+        let err = connection.send(IsReadyCommand).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+        assert!(written.lock().unwrap().is_empty());
+        assert_eq!(connection.diagnostics().state(), ConnectionState::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy() {
+        let policy = ExponentialRetry::new(
+            2,
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+            is_transient_io_error,
+        );
+        let (connection, written) = flaky_connection(1);
+        let mut connection = connection.with_retry_policy(policy);
+        let Ok(()) = connection.send(IsReadyCommand).await.unwrap();
+        // The line is written whole and once, although it takes several writes
+        assert_eq!(written.lock().unwrap().as_slice(), b"isready\n");
+        assert_eq!(connection.diagnostics().commands_sent("isready"), 1);
+
+        // Two failures in a row exceed the attempts
+        let (connection, _) = flaky_connection(2);
+        let mut connection = connection.with_retry_policy(policy);
+        assert!(connection.send(IsReadyCommand).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_io_connection_over_unix_stream() {
+        let (gui, engine) = tokio::net::UnixStream::pair().unwrap();
+        let (gui_read, gui_write) = gui.into_split();
+        let (engine_read, engine_write) = engine.into_split();
+
+        let mut connection = IoConnection::new(gui_read, gui_write);
+        let (received, ()) = futures::future::join(
+            fake_engine().serve(engine_read, engine_write),
+            talk(&mut connection),
+        )
+        .await;
+        assert_eq!(received, ["uci", "isready", "go infinite", "stop", "quit"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawned_engine_exits() {
+        use crate::util::EngineExited;
+
+        // Fails to load its network when searching and exits, like a misconfigured engine
+        let mut command = tokio::process::Command::new("sh");
+        command.args([
+            "-c",
+            "read line; \
+             echo 'ERROR: Network evaluation parameters compatible with the engine must be available.' >&2; \
+             echo 'ERROR: The network file nn-missing.nnue was not loaded successfully.' >&2; \
+             exit 1",
+        ]);
+        let mut connection = IoConnection::spawn(&mut command).unwrap();
+
+        let search = connection
+            .send_expecting_later_response::<_, GoResponse>(GoCommand::for_depth(1))
+            .await
+            .unwrap();
+        let err = search.await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        let exited = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<EngineExited>())
+            .unwrap();
+        assert_eq!(exited.status.and_then(|status| status.code()), Some(1));
+        assert_eq!(
+            exited.stderr_tail,
+            [
+                "ERROR: Network evaluation parameters compatible with the engine must be available.",
+                "ERROR: The network file nn-missing.nnue was not loaded successfully.",
+            ]
+        );
+        assert_eq!(connection.stderr_tail(), exited.stderr_tail);
+
+        // The following commands fail the same way
+        let err = connection.send(IsReadyCommand).await.unwrap_err();
+        assert!(
+            err.get_ref()
+                .is_some_and(|e| e.downcast_ref::<EngineExited>().is_some())
+        );
+    }
+}
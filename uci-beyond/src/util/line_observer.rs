@@ -0,0 +1,144 @@
+#[cfg(feature = "tracing")]
+use std::sync::Mutex;
+use std::{
+    fmt::Debug,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// The callbacks for the lines read by the responses, e.g. to collect metrics without wrapping every reader.
+///
+/// An observer is attached to a reader with [`ObservedReader`](crate::util::ObservedReader) or
+/// [`ReadOptions::with_observer`](crate::gui_command_responses::ReadOptions::with_observer). The lines are reported
+/// by [`handle_next_line`](crate::util::handle_next_line) and the parsed commands by the block readers,
+/// e.g. [`read_block`](crate::util::read_block). The observer only looks at what is read,
+/// so it can't change which lines are consumed or peeked.
+pub trait LineObserver: Send + Sync {
+    /// Called for every consumed line, without the line terminator.
+    ///
+    /// A peeked line is reported once, when it is consumed by the next reader.
+    fn on_line(&self, _line: &str) {}
+
+    /// Called for every line of a block parsed into a command, e.g. an `option` line.
+    fn on_parsed(&self, _parsed: &dyn Debug) {}
+
+    /// Called for every line of a block that failed to parse.
+    fn on_error(&self, _error: &dyn Debug) {}
+}
+
+impl Debug for dyn LineObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LineObserver")
+    }
+}
+
+/// The [`LineObserver`] that reports to several observers in turn, e.g. to the one of the reader
+/// and to the one of [`ReadOptions::with_observer`](crate::gui_command_responses::ReadOptions::with_observer).
+#[derive(Debug, Default)]
+pub struct FanOutObserver {
+    observers: Vec<Arc<dyn LineObserver>>,
+}
+
+impl FanOutObserver {
+    pub fn new(observers: impl IntoIterator<Item = Arc<dyn LineObserver>>) -> Self {
+        Self {
+            observers: observers.into_iter().collect(),
+        }
+    }
+
+    /// The observer that reports to both `first` and `second`, or to the one that is present.
+    pub(crate) fn combine(
+        first: Option<Arc<dyn LineObserver>>,
+        second: Option<Arc<dyn LineObserver>>,
+    ) -> Option<Arc<dyn LineObserver>> {
+        match (first, second) {
+            (Some(first), Some(second)) => Some(Arc::new(Self::new([first, second]))),
+            (first, second) => first.or(second),
+        }
+    }
+}
+
+impl LineObserver for FanOutObserver {
+    fn on_line(&self, line: &str) {
+        for observer in &self.observers {
+            observer.on_line(line);
+        }
+    }
+
+    fn on_parsed(&self, parsed: &dyn Debug) {
+        for observer in &self.observers {
+            observer.on_parsed(parsed);
+        }
+    }
+
+    fn on_error(&self, error: &dyn Debug) {
+        for observer in &self.observers {
+            observer.on_error(error);
+        }
+    }
+}
+
+/// The [`LineObserver`] that counts the lines, the parsed commands and the parsing errors.
+#[derive(Debug, Default)]
+pub struct CountingObserver {
+    lines: AtomicUsize,
+    parsed: AtomicUsize,
+    errors: AtomicUsize,
+}
+
+impl CountingObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines.load(Ordering::Relaxed)
+    }
+
+    pub fn parsed(&self) -> usize {
+        self.parsed.load(Ordering::Relaxed)
+    }
+
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::Relaxed)
+    }
+}
+
+impl LineObserver for CountingObserver {
+    fn on_line(&self, _line: &str) {
+        self.lines.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_parsed(&self, _parsed: &dyn Debug) {
+        self.parsed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_error(&self, _error: &dyn Debug) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// The [`LineObserver`] that turns the parsing errors into warn-level [`tracing`] events
+/// with the offending line.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Default)]
+pub struct TracingObserver {
+    /// The last consumed line, which is the offending one when an error is reported.
+    last_line: Mutex<String>,
+}
+
+#[cfg(feature = "tracing")]
+impl LineObserver for TracingObserver {
+    fn on_line(&self, line: &str) {
+        let mut last_line = self.last_line.lock().unwrap();
+        last_line.clear();
+        last_line.push_str(line);
+    }
+
+    fn on_error(&self, error: &dyn Debug) {
+        let line = self.last_line.lock().unwrap();
+        tracing::warn!(line = line.as_str(), ?error, "failed to parse the line");
+    }
+}
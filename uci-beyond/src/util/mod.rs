@@ -1,9 +1,54 @@
 mod async_readable;
+mod async_writable;
+mod backoff;
+mod cancel_token;
+mod clock;
+mod coalescing_configurator;
+mod command_lint;
 mod connection;
-mod streaming_line_reader;
+mod diagnostics;
+mod engine_process;
+mod io_connection;
+mod line_observer;
+mod read_block;
+mod read_block_until;
+mod read_until;
+mod response_handle;
+mod retry;
+mod router;
+pub(crate) mod streaming_line_reader;
+mod watchdog;
 
 pub use async_readable::AsyncReadable;
+pub use async_writable::{AsyncWritable, FlushPolicy, LineWriter};
+pub use backoff::Backoff;
+pub use cancel_token::CancelToken;
+#[cfg(feature = "time")]
+pub use clock::WallClock;
+pub use clock::{Clock, SystemClock, Timestamp};
+pub use coalescing_configurator::CoalescingConfigurator;
+pub use command_lint::{CommandLint, LintPolicy, LintSeverity, MAX_COMMAND_LINE_LEN, lint_command};
 pub use connection::Connection;
+pub use diagnostics::{
+    ConnectionDiagnostics, ConnectionState, DEFAULT_RECENT_LINES_CAPACITY, RecentLine,
+    TranscriptLog,
+};
+pub use engine_process::{DEFAULT_STDERR_TAIL_CAPACITY, EngineExited};
+pub use io_connection::{IoConnection, TruncatedResponse};
+#[cfg(feature = "tracing")]
+pub use line_observer::TracingObserver;
+pub use line_observer::{CountingObserver, FanOutObserver, LineObserver};
+pub use read_block::{ReadBlockError, read_block, read_block_with};
+pub use read_block_until::{IncompleteBlock, read_block_until, read_line};
+pub use read_until::{
+    DEFAULT_READ_UNTIL_CAP, ReadUntilError, read_until, read_until_with_cap, wait_for_bestmove,
+    wait_for_readyok,
+};
+pub use response_handle::ResponseHandle;
+pub use retry::{ExponentialRetry, NoRetry, RetryPolicy, is_transient_io_error};
+pub use router::{Route, Router};
 pub use streaming_line_reader::{
-    LineHandlerOutcome, StreamingLineReader, StringStreamReader, handle_next_line,
+    CancellableReadError, CancellableReader, LineCountingReader, LineHandlerOutcome, LinesStream,
+    ObservedReader, StreamingLineReader, StringStreamReader, handle_next_line, lines_stream,
 };
+pub use watchdog::{Watchdog, WatchdogConfig};
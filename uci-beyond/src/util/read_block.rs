@@ -0,0 +1,176 @@
+use std::{fmt::Debug, str::FromStr};
+
+use crate::{
+    command::Command,
+    util::{LineHandlerOutcome, StreamingLineReader, handle_next_line},
+};
+
+/// The error returned by [`read_block`].
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum ReadBlockError<P, F> {
+    /// A line of the block couldn't be parsed.
+    #[error("{0}")]
+    Parsing(P),
+    /// A parsed line was rejected by the fold, e.g. because it repeats an earlier one.
+    #[error("{0}")]
+    Fold(F),
+}
+
+/// Reads the consecutive lines of the command `C` (e.g. `option`, see [`Command::matches_line`]),
+/// folding each of them into the block `B`.
+///
+/// The block ends at the first line of another command, which is peeked and left for the next reader,
+/// or at the end of the input. Returns `Ok(None)` if the block ends before its first line.
+/// Reading stops at the first line that fails to parse or to fold; that line is consumed.
+///
+/// The leading whitespace of the lines is skipped.
+pub async fn read_block<R, B, C, E, F>(
+    reader: &mut R,
+    fold: F,
+) -> Result<Option<Result<B, ReadBlockError<C::Err, E>>>, R::Error>
+where
+    R: StreamingLineReader,
+    B: Default,
+    C: Command + FromStr + Send + Debug,
+    C::Err: Debug,
+    F: FnMut(&mut B, C) -> Result<(), E>,
+{
+    read_block_with(reader, |line: &str| line.parse::<C>(), fold).await
+}
+
+/// The same as [`read_block`], but the lines are parsed with `parse` rather than with [`FromStr`],
+/// e.g. to parse them with the parser options of a particular engine.
+///
+/// The parsed commands and the parsing errors are reported to the [`observer`](StreamingLineReader::observer) of the reader.
+pub async fn read_block_with<R, B, C, PE, P, E, F>(
+    reader: &mut R,
+    mut parse: P,
+    mut fold: F,
+) -> Result<Option<Result<B, ReadBlockError<PE, E>>>, R::Error>
+where
+    R: StreamingLineReader,
+    B: Default,
+    C: Command + Send + Debug,
+    PE: Debug,
+    P: FnMut(&str) -> Result<C, PE> + Send,
+    F: FnMut(&mut B, C) -> Result<(), E>,
+{
+    let mut block = B::default();
+    let mut is_empty = true;
+    let observer = reader.observer();
+
+    loop {
+        let f = |line: &str| -> LineHandlerOutcome<C, PE> {
+            if !C::matches_line(line) {
+                return LineHandlerOutcome::Peeked;
+            }
+            match parse(line.trim_start()) {
+                Ok(cmd) => LineHandlerOutcome::Read(cmd),
+                Err(e) => LineHandlerOutcome::Error(e),
+            }
+        };
+
+        let cmd = match handle_next_line(reader, f).await? {
+            Some(LineHandlerOutcome::Read(cmd)) => {
+                if let Some(observer) = &observer {
+                    observer.on_parsed(&cmd);
+                }
+                cmd
+            }
+            Some(LineHandlerOutcome::Error(e)) => {
+                if let Some(observer) = &observer {
+                    observer.on_error(&e);
+                }
+                return Ok(Some(Err(ReadBlockError::Parsing(e))));
+            }
+            Some(LineHandlerOutcome::Peeked) | None => {
+                return Ok((!is_empty).then_some(Ok(block)));
+            }
+        };
+
+        if let Err(e) = fold(&mut block, cmd) {
+            return Ok(Some(Err(ReadBlockError::Fold(e))));
+        }
+        is_empty = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::read_line;
+
+    /// Sums the numbers of the `n <number>` lines.
+    async fn read_sum(input: &str) -> Option<Result<u32, ReadBlockError<String, String>>> {
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        read_block(&mut reader, |sum: &mut u32, n: Number| {
+            *sum = sum.checked_add(n.0).ok_or_else(|| "overflow".to_string())?;
+            Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[derive(Debug)]
+    struct Number(u32);
+
+    impl Command for Number {
+        type ParsingError = String;
+
+        const NAME: &'static str = "n";
+    }
+
+    impl FromStr for Number {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let value = s.trim_start_matches("n").trim();
+            value.parse().map(Number).map_err(|_| value.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_block_stops_at_other_line() {
+        let mut reader = tokio::io::BufReader::new("n 1\nn 2\n\nn 3\n".as_bytes());
+        let sum = read_block(&mut reader, |sum: &mut u32, n: Number| {
+            *sum += n.0;
+            Ok::<_, ()>(())
+        })
+        .await
+        .unwrap();
+        assert!(matches!(sum, Some(Ok(3))));
+
+        // The line that ended the block is left for the next reader
+        assert_eq!(read_line(&mut reader).await.unwrap().as_deref(), Some(""));
+        assert_eq!(
+            read_line(&mut reader).await.unwrap().as_deref(),
+            Some("n 3")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_block_eof() {
+        assert!(matches!(read_sum("n 1\nn 2\n").await, Some(Ok(3))));
+        assert!(read_sum("").await.is_none());
+        assert!(read_sum("uciok\n").await.is_none());
+        // The name is a whole token
+        assert!(read_sum("nodes 1\n").await.is_none());
+        assert!(matches!(
+            read_sum("\tn 1\n  n 2\nnodes 3\n").await,
+            Some(Ok(3))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_block_errors() {
+        assert!(matches!(
+            read_sum("n 1\nn x\n").await,
+            Some(Err(ReadBlockError::Parsing(value))) if value == "x"
+        ));
+        assert!(matches!(
+            read_sum("n 4294967295\nn 1\n").await,
+            Some(Err(ReadBlockError::Fold(e))) if e == "overflow"
+        ));
+    }
+}
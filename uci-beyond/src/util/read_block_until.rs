@@ -0,0 +1,98 @@
+use crate::util::{LineHandlerOutcome, StreamingLineReader, handle_next_line};
+
+/// The error returned by [`read_block_until`] when the input ends before the last line of the block.
+#[derive(Debug, thiserror::Error)]
+#[error("The input ended after {} lines of the block.", .0.len())]
+pub struct IncompleteBlock(pub Vec<String>);
+
+/// Reads the next line without the line terminator.
+pub async fn read_line<R>(reader: &mut R) -> Result<Option<String>, R::Error>
+where
+    R: StreamingLineReader,
+{
+    let f = |line: &str| -> LineHandlerOutcome<String, ()> {
+        LineHandlerOutcome::Read(line.trim_end_matches(['\r', '\n']).to_string())
+    };
+
+    match handle_next_line(reader, f).await? {
+        Some(LineHandlerOutcome::Read(line)) => Ok(Some(line)),
+        Some(LineHandlerOutcome::Error(())) | Some(LineHandlerOutcome::Peeked) => {
+            unreachable!("The handler always reads the line")
+        }
+        None => Ok(None),
+    }
+}
+
+/// Reads the lines of a block up to and including the first one for which `is_last` returns `true`.
+///
+/// This is the framing of the non-UCI responses, e.g. of Stockfish's `eval` that ends with the `Final evaluation` line.
+/// The lines are returned without line terminators. Returns `Ok(None)` if the input ends before the first line.
+pub async fn read_block_until<R, P>(
+    reader: &mut R,
+    mut is_last: P,
+) -> Result<Option<Result<Vec<String>, IncompleteBlock>>, R::Error>
+where
+    R: StreamingLineReader,
+    P: FnMut(&str) -> bool,
+{
+    let mut lines = Vec::new();
+
+    loop {
+        let Some(line) = read_line(reader).await? else {
+            if lines.is_empty() {
+                return Ok(None);
+            }
+            return Ok(Some(Err(IncompleteBlock(lines))));
+        };
+
+        let is_last_line = is_last(&line);
+        lines.push(line);
+        if is_last_line {
+            return Ok(Some(Ok(lines)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_block_until() {
+        let input = "Compiled by : g++\r\nCompilation architecture : x86-64\n\nreadyok\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let block = read_block_until(&mut reader, str::is_empty)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            block,
+            ["Compiled by : g++", "Compilation architecture : x86-64", ""]
+        );
+
+        // The lines after the block are left in the reader
+        let line = read_line(&mut reader).await.unwrap();
+        assert_eq!(line.as_deref(), Some("readyok"));
+        assert!(read_line(&mut reader).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_incomplete_block() {
+        let input = "Position: 1/1\nbestmove e2e4\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let err = read_block_until(&mut reader, |line| line.starts_with("Nodes/second"))
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_err();
+
+        assert_eq!(err.0, ["Position: 1/1", "bestmove e2e4"]);
+
+        let res = read_block_until(&mut reader, |_| true).await.unwrap();
+        assert!(res.is_none());
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{
+    command,
+    engine_commands::{
+        BestMoveCommand, BestMoveCommandParsingError, ReadyOkCommand, ReadyOkCommandParsingError,
+    },
+    util::{AsyncReadable, LineHandlerOutcome, StreamingLineReader, handle_next_line},
+};
+
+/// The default maximum number of lines [`read_until`] buffers before giving up.
+pub const DEFAULT_READ_UNTIL_CAP: usize = 1024;
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ReadUntilError<E> {
+    /// The target didn't appear within `cap` lines.
+    #[error("Gave up after draining {cap} lines.")]
+    CapExceeded { cap: usize, drained: Vec<String> },
+    /// A line was recognized as the target but failed to parse.
+    #[error("Parsing error after draining {} lines: {error:?}", drained.len())]
+    ParsingError {
+        drained: Vec<String>,
+        error: command::parsing::Error<E>,
+    },
+}
+
+impl<E> ReadUntilError<E> {
+    /// The lines drained before the error occurred.
+    pub fn drained(&self) -> &[String] {
+        match self {
+            ReadUntilError::CapExceeded { drained, .. } => drained,
+            ReadUntilError::ParsingError { drained, .. } => drained,
+        }
+    }
+}
+
+/// Reads lines until `T` parses, collecting the lines that were drained on the way,
+/// e.g. the `info` lines interleaved with `readyok`.
+///
+/// A line is drained only if parsing `T` fails with [`command::parsing::Error::UnexpectedCommand`].
+/// Any other parsing error is returned as [`ReadUntilError::ParsingError`]. At most
+/// [`DEFAULT_READ_UNTIL_CAP`] lines are drained; see [`read_until_with_cap`] to configure the cap.
+///
+/// `T` is expected to consume the line it failed to parse, as the single-line commands of this crate do.
+pub async fn read_until<R, T, E>(
+    reader: &mut R,
+) -> Result<Option<Result<(Vec<String>, T), ReadUntilError<E>>>, R::Error>
+where
+    R: StreamingLineReader,
+    T: AsyncReadable<Err = command::parsing::Error<E>>,
+    E: Send + std::fmt::Debug,
+{
+    read_until_with_cap(reader, DEFAULT_READ_UNTIL_CAP).await
+}
+
+/// Same as [`read_until`] but with a configurable maximum number of drained lines.
+pub async fn read_until_with_cap<R, T, E>(
+    reader: &mut R,
+    cap: usize,
+) -> Result<Option<Result<(Vec<String>, T), ReadUntilError<E>>>, R::Error>
+where
+    R: StreamingLineReader,
+    T: AsyncReadable<Err = command::parsing::Error<E>>,
+    E: Send + std::fmt::Debug,
+{
+    let mut drained = Vec::new();
+
+    loop {
+        // Peek at the line first to keep its text in case `T` rejects it
+        let mut line_text = None;
+        let f = |line: &str| -> LineHandlerOutcome<(), ()> {
+            line_text = Some(line.trim_end_matches(['\r', '\n']).to_string());
+            LineHandlerOutcome::Peeked
+        };
+        if handle_next_line(reader, f).await?.is_none() {
+            return Ok(None);
+        }
+
+        match T::read_from(reader).await? {
+            Some(Ok(value)) => return Ok(Some(Ok((drained, value)))),
+            Some(Err(command::parsing::Error::UnexpectedCommand(_))) => {
+                if drained.len() == cap {
+                    return Ok(Some(Err(ReadUntilError::CapExceeded { cap, drained })));
+                }
+                drained.extend(line_text);
+            }
+            Some(Err(error)) => {
+                return Ok(Some(Err(ReadUntilError::ParsingError { drained, error })));
+            }
+            None => return Ok(None),
+        }
+    }
+}
+
+/// Waits for `readyok`, draining the output that precedes it (e.g. `info` lines of a running search).
+pub async fn wait_for_readyok<R>(
+    reader: &mut R,
+) -> Result<
+    Option<Result<(Vec<String>, ReadyOkCommand), ReadUntilError<ReadyOkCommandParsingError>>>,
+    R::Error,
+>
+where
+    R: StreamingLineReader,
+{
+    read_until(reader).await
+}
+
+/// Waits for `bestmove`, draining the output that precedes it (e.g. `info` lines of the search).
+pub async fn wait_for_bestmove<R>(
+    reader: &mut R,
+) -> Result<
+    Option<Result<(Vec<String>, BestMoveCommand), ReadUntilError<BestMoveCommandParsingError>>>,
+    R::Error,
+>
+where
+    R: StreamingLineReader,
+{
+    read_until(reader).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model;
+
+    #[tokio::test]
+    async fn test_wait_for_readyok() {
+        let input = "info string NNUE evaluation using nn-1c0000000000.nnue\n\
+info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 20000 time 1 pv e2e4\n\
+readyok\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let (drained, readyok) = wait_for_readyok(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(readyok, ReadyOkCommand);
+        assert_eq!(
+            drained,
+            [
+                "info string NNUE evaluation using nn-1c0000000000.nnue",
+                "info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 20000 time 1 pv e2e4",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_bestmove() {
+        let input = "readyok\n\
+info depth 1 seldepth 2 multipv 1 score cp 17 nodes 20 nps 20000 time 1 pv e2e4\n\
+bestmove e2e4 ponder e7e5\n\
+readyok\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let (drained, bestmove) = wait_for_bestmove(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(drained.len(), 2);
+        assert_eq!(bestmove.best_move, model::MoveString::from("e2e4"));
+        assert_eq!(bestmove.ponder, Some(model::MoveString::from("e7e5")));
+
+        // The line after the target is left in the reader
+        let readyok = ReadyOkCommand::read_from(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(readyok, ReadyOkCommand);
+    }
+
+    #[tokio::test]
+    async fn test_read_until_cap_exceeded() {
+        let input = "info string one\ninfo string two\ninfo string three\nreadyok\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let err = read_until_with_cap::<_, ReadyOkCommand, _>(&mut reader, 2)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_err();
+
+        assert!(matches!(err, ReadUntilError::CapExceeded { cap: 2, .. }));
+        assert_eq!(err.drained(), ["info string one", "info string two"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_until_parsing_error() {
+        let input = "info string one\nbestmove e2e4 e7e5\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let err = wait_for_bestmove(&mut reader)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ReadUntilError::ParsingError {
+                error: command::parsing::Error::CustomError(
+                    BestMoveCommandParsingError::PonderTokenExpected(_)
+                ),
+                ..
+            }
+        ));
+        assert_eq!(err.drained(), ["info string one"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_until_eof() {
+        let input = "info string one\n";
+
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let res = wait_for_readyok(&mut reader).await.unwrap();
+
+        assert!(res.is_none());
+    }
+}
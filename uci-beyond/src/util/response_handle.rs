@@ -0,0 +1,46 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::future::LocalBoxFuture;
+
+use crate::util::AsyncReadable;
+
+/// The response to a command sent with
+/// [`Connection::send_expecting_later_response`](crate::util::Connection::send_expecting_later_response).
+///
+/// The handle doesn't borrow the connection, so the connection remains usable for the commands
+/// that don't expect a response (e.g. `stop`) until the handle is awaited.
+pub struct ResponseHandle<T, E>
+where
+    T: AsyncReadable,
+{
+    future: LocalBoxFuture<'static, Result<Result<T, T::Err>, E>>,
+}
+
+impl<T, E> ResponseHandle<T, E>
+where
+    T: AsyncReadable,
+{
+    pub fn new<F>(future: F) -> Self
+    where
+        F: Future<Output = Result<Result<T, T::Err>, E>> + 'static,
+    {
+        Self {
+            future: Box::pin(future),
+        }
+    }
+}
+
+impl<T, E> Future for ResponseHandle<T, E>
+where
+    T: AsyncReadable,
+{
+    type Output = Result<Result<T, T::Err>, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.future.as_mut().poll(cx)
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use crate::util::backoff::exponential_delay;
+
+/// Whether and when a connection retries a failed write of a line, e.g. because of backpressure.
+///
+/// The policy is applied only to writing: once a line was written, its response is never read twice.
+/// The default policy of the connections is [`NoRetry`].
+pub trait RetryPolicy<E> {
+    /// The maximum number of attempts to write a line, including the first one.
+    fn max_attempts(&self) -> u32;
+
+    /// The delay before the next attempt after `failures` consecutive failures, starting at 1.
+    fn delay(&self, failures: u32) -> Duration;
+
+    /// Whether the error is transient, i.e. the write may succeed if retried.
+    fn is_retryable(&self, error: &E) -> bool;
+
+    /// The delay before retrying the write that failed with the error for the `failures`-th time in a row,
+    /// or `None` if the error must be returned.
+    fn retry_after(&self, error: &E, failures: u32) -> Option<Duration> {
+        (failures < self.max_attempts() && self.is_retryable(error)).then(|| self.delay(failures))
+    }
+}
+
+/// The [`RetryPolicy`] that returns the first error, i.e. doesn't retry.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoRetry;
+
+impl<E> RetryPolicy<E> for NoRetry {
+    fn max_attempts(&self) -> u32 {
+        1
+    }
+
+    fn delay(&self, _failures: u32) -> Duration {
+        Duration::ZERO
+    }
+
+    fn is_retryable(&self, _error: &E) -> bool {
+        false
+    }
+}
+
+/// The [`RetryPolicy`] that retries the errors accepted by `is_retryable` with an exponential backoff,
+/// like [`Backoff`](crate::util::Backoff): the delay starts at `initial`, doubles after every
+/// failure and is capped at `max`.
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialRetry<F> {
+    pub max_attempts: u32,
+    pub initial: Duration,
+    pub max: Duration,
+    pub is_retryable: F,
+}
+
+impl<F> ExponentialRetry<F> {
+    pub fn new(max_attempts: u32, initial: Duration, max: Duration, is_retryable: F) -> Self {
+        Self {
+            max_attempts,
+            initial,
+            max,
+            is_retryable,
+        }
+    }
+}
+
+impl<E, F> RetryPolicy<E> for ExponentialRetry<F>
+where
+    F: Fn(&E) -> bool,
+{
+    fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    fn delay(&self, failures: u32) -> Duration {
+        exponential_delay(self.initial, self.max, failures.saturating_sub(1))
+    }
+
+    fn is_retryable(&self, error: &E) -> bool {
+        (self.is_retryable)(error)
+    }
+}
+
+/// Whether the I/O error is transient, i.e. [`WouldBlock`](std::io::ErrorKind::WouldBlock),
+/// [`Interrupted`](std::io::ErrorKind::Interrupted) or [`TimedOut`](std::io::ErrorKind::TimedOut).
+///
+/// The errors of a closed pipe or a reset connection aren't transient.
+pub fn is_transient_io_error(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+
+    matches!(
+        error.kind(),
+        ErrorKind::WouldBlock | ErrorKind::Interrupted | ErrorKind::TimedOut
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_after() {
+        let error = std::io::Error::from(std::io::ErrorKind::WouldBlock);
+        assert_eq!(NoRetry.retry_after(&error, 1), None);
+
+        let policy = ExponentialRetry::new(
+            3,
+            Duration::from_millis(10),
+            Duration::from_millis(15),
+            is_transient_io_error,
+        );
+        assert_eq!(
+            policy.retry_after(&error, 1),
+            Some(Duration::from_millis(10))
+        );
+        assert_eq!(
+            policy.retry_after(&error, 2),
+            Some(Duration::from_millis(15))
+        );
+        // The third attempt was the last one
+        assert_eq!(policy.retry_after(&error, 3), None);
+
+        let broken_pipe = std::io::Error::from(std::io::ErrorKind::BrokenPipe);
+        assert_eq!(policy.retry_after(&broken_pipe, 1), None);
+    }
+}
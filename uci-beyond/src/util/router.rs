@@ -0,0 +1,639 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::{Mutex, mpsc},
+    task::JoinHandle,
+};
+
+use crate::{
+    command::Command as _,
+    engine_commands::{AnyEngineCommandKind, ReadyOkCommand},
+    engine_quirks::Quirks,
+    gui_command_responses::{GoResponse, StopResponse, UciResponse},
+    gui_commands::{GoCommand, IsReadyCommand, StopCommand, UciCommand},
+    util::{
+        AsyncReadable, ConnectionDiagnostics, LineCountingReader, ResponseHandle,
+        StringStreamReader, Watchdog, WatchdogConfig,
+        engine_process::EngineProcess,
+        io_connection::{Incoming, Outgoing, check_exit, finish_response},
+        watchdog::Pinger,
+    },
+};
+
+/// The response that a request sent through a [`Router`] waits for, i.e. which lines it accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Route {
+    /// `readyok`, the answer to `isready`.
+    ReadyOk,
+    /// The `info` lines and `bestmove`, the answer to `go`.
+    Search,
+    /// The `id` and `option` lines and `uciok`, the answer to `uci`.
+    Handshake,
+}
+
+impl Route {
+    /// Whether the request accepts a line of the kind.
+    ///
+    /// The lines of no known command, e.g. the banner of the engine, are accepted by the handshake
+    /// and by the search, whose readers skip them or report them.
+    fn accepts(self, kind: AnyEngineCommandKind) -> bool {
+        match self {
+            Route::ReadyOk => kind == AnyEngineCommandKind::ReadyOk,
+            Route::Search => matches!(
+                kind,
+                AnyEngineCommandKind::Info
+                    | AnyEngineCommandKind::BestMove
+                    | AnyEngineCommandKind::Other
+            ),
+            Route::Handshake => matches!(
+                kind,
+                AnyEngineCommandKind::Id
+                    | AnyEngineCommandKind::Option
+                    | AnyEngineCommandKind::UciOk
+                    | AnyEngineCommandKind::Other
+            ),
+        }
+    }
+
+    /// Whether the line of the kind is the last line of the response.
+    fn completes(self, kind: AnyEngineCommandKind) -> bool {
+        match self {
+            Route::ReadyOk => kind == AnyEngineCommandKind::ReadyOk,
+            Route::Search => kind == AnyEngineCommandKind::BestMove,
+            Route::Handshake => kind == AnyEngineCommandKind::UciOk,
+        }
+    }
+}
+
+/// A request waiting for its response, which receives the routed lines over the channel.
+struct PendingRequest {
+    id: u64,
+    route: Route,
+    lines: mpsc::UnboundedSender<std::io::Result<String>>,
+}
+
+#[derive(Default)]
+struct PendingRequests {
+    /// Oldest first.
+    requests: VecDeque<PendingRequest>,
+    next_id: u64,
+    /// Whether the read loop has ended, so no line will be routed anymore.
+    closed: bool,
+    quirks: Quirks,
+    /// Whether a `stop` was sent whose acknowledgment is still awaited.
+    acknowledgment_awaited: bool,
+    /// The search pending when `stop` was last sent, if any.
+    stopped_search: Option<u64>,
+}
+
+impl PendingRequests {
+    /// Registers a request waiting for the response of the route, which receives its lines over the channel.
+    fn register(
+        &mut self,
+        route: Route,
+    ) -> std::io::Result<(u64, mpsc::UnboundedReceiver<std::io::Result<String>>)> {
+        if self.closed {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let id = self.next_id;
+        self.next_id += 1;
+        self.requests.push_back(PendingRequest {
+            id,
+            route,
+            lines: sender,
+        });
+        Ok((id, receiver))
+    }
+
+    /// Forgets the request, e.g. whose command wasn't written.
+    fn forget(&mut self, id: u64) {
+        self.requests.retain(|request| request.id != id);
+    }
+
+    /// Sends the line to the oldest request that accepts it, and forgets the request if the line completes it.
+    fn route(&mut self, line: String) {
+        // Only while a `stop` is awaiting its acknowledgment, so the `info string` lines
+        // of the searches reach them otherwise
+        if self.quirks.ack_before_bestmove
+            && self.acknowledgment_awaited
+            && StopResponse::is_acknowledgment(&line)
+        {
+            return self.route_acknowledgment(line);
+        }
+        let kind = AnyEngineCommandKind::of_line(&line);
+        let Some(index) = self
+            .requests
+            .iter()
+            .position(|request| request.route.accepts(kind))
+        else {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(line, "no pending request accepts the line");
+            return;
+        };
+        let request = &self.requests[index];
+        let completes = request.route.completes(kind);
+        // The response may have been dropped, e.g. a search that isn't awaited, but its lines still belong to it
+        let _ = request.lines.send(Ok(line));
+        if completes {
+            let request = self.requests.remove(index);
+            if request.is_some_and(|request| self.stopped_search == Some(request.id)) {
+                // The stopped search ended without acknowledging the `stop`
+                self.stopped_search = None;
+                self.acknowledgment_awaited = false;
+            }
+        }
+    }
+
+    /// Sends the acknowledgment of `stop` to the stopped search if it is still pending, so a late one
+    /// isn't taken for a line of the next search. Only an `info string` acknowledgment is sent, since
+    /// the readers of the search know no other.
+    fn route_acknowledgment(&mut self, line: String) {
+        self.acknowledgment_awaited = false;
+        let stopped_search = self.stopped_search.take();
+        let request = stopped_search.and_then(|id| self.requests.iter().find(|r| r.id == id));
+        match request {
+            Some(request) if AnyEngineCommandKind::of_line(&line) == AnyEngineCommandKind::Info => {
+                let _ = request.lines.send(Ok(line));
+            }
+            _ => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(line, "dropped the acknowledgment of stop");
+            }
+        }
+    }
+
+    /// Fails every pending request with the error of the read loop, or with the end of the input.
+    fn close(&mut self, error: Option<&std::io::Error>) {
+        self.closed = true;
+        for request in self.requests.drain(..) {
+            if let Some(e) = error {
+                let _ = request
+                    .lines
+                    .send(Err(std::io::Error::new(e.kind(), e.to_string())));
+            }
+        }
+    }
+}
+
+/// Owns the read loop of an [`IoConnection`](crate::util::IoConnection) and routes every line to the oldest
+/// pending request whose response accepts it, so several commands can be in flight at once, e.g. an `isready`
+/// ping during a search:
+///
+/// * `readyok` goes to the oldest `isready`;
+/// * the `info` lines and `bestmove` go to the search;
+/// * the `id` and `option` lines and `uciok` go to the handshake.
+///
+/// The lines are classified with [`AnyEngineCommandKind::of_line`]. A line that no pending request accepts
+/// is dropped. Every request is registered before its command is written, so its response can't be missed.
+///
+/// The router is created with [`IoConnection::into_router`](crate::util::IoConnection::into_router).
+/// Dropping it stops the read loop.
+pub struct Router<W> {
+    outgoing: Arc<Mutex<Outgoing<W>>>,
+    pending: Arc<StdMutex<PendingRequests>>,
+    diagnostics: Arc<StdMutex<ConnectionDiagnostics>>,
+    process: Option<Arc<EngineProcess>>,
+    read_loop: JoinHandle<()>,
+}
+
+impl<W> Router<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    pub(super) fn new<R>(
+        incoming: Arc<Mutex<Incoming<R>>>,
+        outgoing: Arc<Mutex<Outgoing<W>>>,
+        diagnostics: Arc<StdMutex<ConnectionDiagnostics>>,
+        process: Option<Arc<EngineProcess>>,
+    ) -> Self
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let pending = Arc::new(StdMutex::new(PendingRequests::default()));
+        let read_loop = tokio::spawn(read_loop(incoming, Arc::clone(&pending)));
+        Self {
+            outgoing,
+            pending,
+            diagnostics,
+            process,
+            read_loop,
+        }
+    }
+
+    /// Sends the command and returns its response, read from the lines routed to it.
+    ///
+    /// The response is read by `T`, e.g. [`GoResponse`] for [`Route::Search`].
+    pub async fn request<C, T>(
+        &self,
+        cmd: C,
+        route: Route,
+    ) -> std::io::Result<ResponseHandle<T, std::io::Error>>
+    where
+        C: Display,
+        T: AsyncReadable + 'static,
+        T::Err: 'static,
+    {
+        let (id, mut receiver) = self.pending.lock().unwrap().register(route)?;
+
+        if let Err(e) = self.send_raw_line(&cmd.to_string()).await {
+            self.pending.lock().unwrap().forget(id);
+            return Err(e);
+        }
+
+        let diagnostics = Arc::clone(&self.diagnostics);
+        let process = self.process.clone();
+        Ok(ResponseHandle::new(async move {
+            let lines = futures::stream::poll_fn(move |cx| receiver.poll_recv(cx));
+            let mut reader = LineCountingReader::new(StringStreamReader::new(lines));
+            let response = match T::read_from(&mut reader).await {
+                Err(e) => Err(check_exit(process.as_deref(), e).await),
+                response => response,
+            };
+            finish_response(response, reader.lines_consumed(), &diagnostics)
+        }))
+    }
+
+    /// Sends `uci` and returns the handshake.
+    pub async fn uci(&self) -> std::io::Result<ResponseHandle<UciResponse, std::io::Error>> {
+        self.request(UciCommand, Route::Handshake).await
+    }
+
+    /// Sends `isready` and returns its `readyok`.
+    pub async fn is_ready(
+        &self,
+    ) -> std::io::Result<ResponseHandle<ReadyOkCommand, std::io::Error>> {
+        self.request(IsReadyCommand, Route::ReadyOk).await
+    }
+
+    /// Starts the search and returns its response, which ends with `bestmove`.
+    pub async fn go(
+        &self,
+        cmd: GoCommand,
+    ) -> std::io::Result<ResponseHandle<GoResponse, std::io::Error>> {
+        self.request(cmd, Route::Search).await
+    }
+
+    /// Sends `stop` to end the oldest pending search, whose response then ends with `bestmove`.
+    ///
+    /// With [`Quirks::ack_before_bestmove`], the next acknowledgment of `stop` goes to that search,
+    /// or is dropped if the search has already ended.
+    pub async fn stop(&self) -> std::io::Result<()> {
+        {
+            let mut pending = self.pending.lock().unwrap();
+            pending.acknowledgment_awaited = true;
+            pending.stopped_search = pending
+                .requests
+                .iter()
+                .find(|request| request.route == Route::Search)
+                .map(|request| request.id);
+        }
+        let res = self.send_raw_line(&StopCommand.to_string()).await;
+        if res.is_err() {
+            // The engine won't acknowledge the `stop` it didn't get
+            let mut pending = self.pending.lock().unwrap();
+            pending.acknowledgment_awaited = false;
+            pending.stopped_search = None;
+        }
+        res
+    }
+
+    /// The [`Watchdog`] that pings the engine with `isready` through the router, e.g. during a search.
+    ///
+    /// Each ping is a [`Route::ReadyOk`] request, so the `readyok` goes to the watchdog and the `info` lines
+    /// to the search. A ping that isn't answered in time stays pending, so its late `readyok` doesn't reach
+    /// the next `isready`.
+    pub fn watchdog(&self, config: WatchdogConfig) -> Watchdog
+    where
+        W: Send + 'static,
+    {
+        let pinger = RouterPinger {
+            outgoing: Arc::clone(&self.outgoing),
+            pending: Arc::clone(&self.pending),
+        };
+        Watchdog::new(pinger, config)
+    }
+
+    /// Sets the leniencies of the engine used to route its lines, e.g. [`Quirks::ack_before_bestmove`].
+    pub fn with_quirks(self, quirks: Quirks) -> Self {
+        self.pending.lock().unwrap().quirks = quirks;
+        self
+    }
+
+    /// Sends a line that expects no response, e.g. `position`.
+    pub async fn send_raw_line(&self, line: &str) -> std::io::Result<()> {
+        let res = self.outgoing.lock().await.write_line(line).await;
+        match res {
+            Err(e) => Err(check_exit(self.process.as_deref(), e).await),
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// The number of requests waiting for their responses.
+    pub fn pending_requests(&self) -> usize {
+        self.pending.lock().unwrap().requests.len()
+    }
+
+    /// The snapshot of what the connection has been doing lately, e.g. the last lines and the last error.
+    pub fn diagnostics(&self) -> ConnectionDiagnostics {
+        self.diagnostics.lock().unwrap().clone()
+    }
+}
+
+impl<W> Drop for Router<W> {
+    fn drop(&mut self) {
+        self.read_loop.abort();
+    }
+}
+
+/// Pings through a [`Router`], whose read loop routes the `readyok` to the ping.
+struct RouterPinger<W> {
+    outgoing: Arc<Mutex<Outgoing<W>>>,
+    pending: Arc<StdMutex<PendingRequests>>,
+}
+
+#[async_trait]
+impl<W> Pinger for RouterPinger<W>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    async fn ping(&self, deadline: Duration) -> std::io::Result<bool> {
+        let (id, mut lines) = self.pending.lock().unwrap().register(Route::ReadyOk)?;
+        let mut sent = false;
+        let answered = tokio::time::timeout(deadline, async {
+            self.outgoing
+                .lock()
+                .await
+                .write_line(IsReadyCommand::NAME)
+                .await?;
+            sent = true;
+            match lines.recv().await {
+                Some(Ok(_readyok)) => Ok(true),
+                Some(Err(e)) => Err(e),
+                None => Ok(false),
+            }
+        })
+        .await;
+        if !sent {
+            self.pending.lock().unwrap().forget(id);
+        }
+        match answered {
+            Ok(answered) => answered,
+            Err(_elapsed) => Ok(false),
+        }
+    }
+}
+
+async fn read_loop<R>(incoming: Arc<Mutex<Incoming<R>>>, pending: Arc<StdMutex<PendingRequests>>)
+where
+    R: AsyncRead + Unpin + Send,
+{
+    loop {
+        let line = incoming.lock().await.next_line().await;
+        match line {
+            Ok(Some(line)) => pending.lock().unwrap().route(line),
+            Ok(None) => return pending.lock().unwrap().close(None),
+            Err(e) => return pending.lock().unwrap().close(Some(&e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::FakeEngine, gui_commands::StopCommand, util::IoConnection};
+
+    const INFO: &str =
+        "info depth 1 seldepth 1 multipv 1 score cp 20 nodes 20 nps 20000 time 1 pv e2e4\n";
+
+    /// An engine that answers `isready` at once, even during a search, with an `info` line of the search first.
+    fn connect() -> (
+        Router<tokio::io::WriteHalf<tokio::io::DuplexStream>>,
+        JoinHandle<Vec<String>>,
+    ) {
+        let mut searching = false;
+        let (connection, engine) = FakeEngine::new(move |line| {
+            Some(match line {
+                "uci" => "id name Fake 1.0\n\
+                    id author Somebody\n\
+                    option name Hash type spin default 16 min 1 max 1024\n\
+                    uciok\n"
+                    .to_string(),
+                "isready" if searching => format!("{INFO}readyok\n"),
+                "isready" => "readyok\n".to_string(),
+                "go infinite" => {
+                    searching = true;
+                    INFO.to_string()
+                }
+                "stop" => {
+                    searching = false;
+                    "bestmove e2e4\n".to_string()
+                }
+                _ => String::new(),
+            })
+        })
+        .connect();
+        (connection.into_router(), engine)
+    }
+
+    #[tokio::test]
+    async fn test_isready_during_search() {
+        let (router, engine) = connect();
+
+        // The handshake and the ping are in flight together
+        let handshake = router.uci().await.unwrap();
+        let ping = router.is_ready().await.unwrap();
+        let ready = ping.await.unwrap().unwrap();
+        assert_eq!(ready, ReadyOkCommand);
+        let handshake = handshake.await.unwrap().unwrap();
+        assert_eq!(handshake.id_block.name, "Fake 1.0");
+
+        router
+            .send_raw_line("position startpos moves e2e4")
+            .await
+            .unwrap();
+        let search = router
+            .go(GoCommand {
+                indefinite: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        // The `info` line sent before `readyok` goes to the search
+        let ping = router.is_ready().await.unwrap();
+        assert_eq!(ping.await.unwrap().unwrap(), ReadyOkCommand);
+        assert_eq!(router.pending_requests(), 1);
+
+        router
+            .send_raw_line(&StopCommand.to_string())
+            .await
+            .unwrap();
+        let response = search.await.unwrap().unwrap();
+        assert_eq!(response.search_summary.depth, Some(1));
+        assert!(response.search_summary.best_move.is_some());
+        assert_eq!(
+            response
+                .events()
+                .iter()
+                .filter(|event| matches!(event, crate::gui_command_responses::GoEvent::Info(_)))
+                .count(),
+            2
+        );
+        assert_eq!(router.pending_requests(), 0);
+
+        router.send_raw_line("quit").await.unwrap();
+        engine.await.unwrap();
+        // The end of the input fails the requests sent afterwards
+        let late = async { router.is_ready().await?.await }.await;
+        assert!(late.is_err());
+    }
+
+    #[test]
+    fn test_routes() {
+        let mut pending = PendingRequests::default();
+        let (search, mut search_lines) = mpsc::unbounded_channel();
+        let (ping, mut ping_lines) = mpsc::unbounded_channel();
+        pending.requests.push_back(PendingRequest {
+            id: 0,
+            route: Route::Search,
+            lines: search,
+        });
+        pending.requests.push_back(PendingRequest {
+            id: 1,
+            route: Route::ReadyOk,
+            lines: ping,
+        });
+
+        pending.route("readyok".to_string());
+        pending.route(INFO.trim_end().to_string());
+        // No pending request accepts it
+        pending.route("uciok".to_string());
+        pending.route("bestmove e2e4".to_string());
+        assert!(pending.requests.is_empty());
+
+        assert_eq!(ping_lines.try_recv().unwrap().unwrap(), "readyok");
+        assert!(ping_lines.try_recv().is_err());
+        assert_eq!(search_lines.try_recv().unwrap().unwrap(), INFO.trim_end());
+        assert_eq!(search_lines.try_recv().unwrap().unwrap(), "bestmove e2e4");
+        assert!(search_lines.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_route_info_strings_without_stop() {
+        let mut pending = PendingRequests {
+            quirks: Quirks {
+                ack_before_bestmove: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (search, mut search_lines) = mpsc::unbounded_channel();
+        pending.requests.push_back(PendingRequest {
+            id: 0,
+            route: Route::Search,
+            lines: search,
+        });
+
+        // With no `stop` sent, a line that mentions it belongs to the search
+        pending.route("info string stop time extended".to_string());
+        assert_eq!(
+            search_lines.try_recv().unwrap().unwrap(),
+            "info string stop time extended"
+        );
+
+        // The stopped search ends without acknowledging the `stop`, which is then no longer awaited
+        pending.acknowledgment_awaited = true;
+        pending.stopped_search = Some(0);
+        pending.route("bestmove e2e4".to_string());
+        assert!(!pending.acknowledgment_awaited);
+        assert_eq!(pending.stopped_search, None);
+    }
+
+    #[tokio::test]
+    async fn test_failed_stop_awaits_no_acknowledgment() {
+        // The engine keeps writing, but its input is closed
+        let (gui, _engine) = tokio::io::duplex(1024);
+        let (gui_read, _) = tokio::io::split(gui);
+        let (gui_input, engine_input) = tokio::io::duplex(1024);
+        drop(engine_input);
+        let router = IoConnection::new(gui_read, gui_input).into_router();
+        let _search = router.pending.lock().unwrap().register(Route::Search);
+
+        assert!(router.stop().await.is_err());
+        let pending = router.pending.lock().unwrap();
+        assert!(!pending.acknowledgment_awaited);
+        assert_eq!(pending.stopped_search, None);
+    }
+
+    /// A hosted service multiplexing clients on one engine, which acknowledges `stop` before `bestmove`,
+    /// and also when the search has already ended.
+    fn multiplexed_service() -> FakeEngine {
+        let mut searching = false;
+        FakeEngine::new(move |line| {
+            Some(match line {
+                "go infinite" => {
+                    searching = true;
+                    INFO.to_string()
+                }
+                "go depth 1" => format!("{INFO}bestmove d2d4\n"),
+                "stop" if searching => {
+                    searching = false;
+                    format!("info string stop acknowledged\n{INFO}bestmove e2e4 ponder e7e5\n")
+                }
+                "stop" => "info string stop acknowledged\n".to_string(),
+                _ => String::new(),
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_stop_acknowledged_by_multiplexed_service() {
+        let (connection, engine) = multiplexed_service().connect();
+        let router = connection.into_router().with_quirks(Quirks {
+            ack_before_bestmove: true,
+            ..Default::default()
+        });
+
+        let search = router
+            .go(GoCommand {
+                indefinite: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        router.stop().await.unwrap();
+        let response = search.await.unwrap().unwrap();
+        assert_eq!(
+            response.search_summary.best_move.unwrap().best_move,
+            crate::model::MoveString::from("e2e4")
+        );
+        assert_eq!(response.raw_lines()[1], "info string stop acknowledged");
+
+        // The late acknowledgment of a search that has already ended isn't taken for a line of the next one
+        router.stop().await.unwrap();
+        let search = router
+            .go(GoCommand {
+                depth: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let response = search.await.unwrap().unwrap();
+        assert_eq!(response.events().len(), 2);
+        assert_eq!(
+            response.search_summary.best_move.unwrap().best_move,
+            crate::model::MoveString::from("d2d4")
+        );
+
+        router.send_raw_line("quit").await.unwrap();
+        engine.await.unwrap();
+    }
+}
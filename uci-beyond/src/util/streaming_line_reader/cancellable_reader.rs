@@ -0,0 +1,169 @@
+use core::task::{Context, Poll};
+use std::sync::Arc;
+
+use crate::util::{
+    CancelToken, LineObserver, StreamingLineReader, cancel_token::WakerSlot,
+    streaming_line_reader::sealed,
+};
+
+/// The error of a [`CancellableReader`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum CancellableReadError<E> {
+    /// The token was cancelled before the next line was read.
+    #[error("The read was cancelled")]
+    Cancelled,
+    #[error("Failed to read the line: {0}")]
+    Read(E),
+}
+
+/// A [`StreamingLineReader`] wrapper that stops reading once its [`CancelToken`] is cancelled.
+///
+/// The token is checked before each line, so any read, e.g. [`AsyncReadable::read_from`](crate::util::AsyncReadable::read_from)
+/// or [`forward_go_events`](crate::gui_command_responses::forward_go_events), fails with
+/// [`CancellableReadError::Cancelled`] between two lines, leaving the next line unread. A read waiting for
+/// a line is woken by the cancellation. The lines read before are lost with the partial response,
+/// but the reader can be used again for the rest of the output, e.g. through [`get_mut`](Self::get_mut).
+pub struct CancellableReader<R>
+where
+    R: StreamingLineReader,
+{
+    inner: R,
+    waker_slot: WakerSlot,
+}
+
+impl<R> CancellableReader<R>
+where
+    R: StreamingLineReader,
+{
+    pub fn new(inner: R, token: CancelToken) -> Self {
+        Self {
+            inner,
+            waker_slot: token.waker_slot(),
+        }
+    }
+
+    pub fn token(&self) -> &CancelToken {
+        self.waker_slot.token()
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> sealed::Sealed for CancellableReader<R> where R: StreamingLineReader {}
+
+impl<R> StreamingLineReader for CancellableReader<R>
+where
+    R: StreamingLineReader,
+{
+    type Error = CancellableReadError<R::Error>;
+
+    const AUTO_CONSUMING: bool = R::AUTO_CONSUMING;
+
+    type Line<'a>
+        = R::Line<'a>
+    where
+        Self: 'a;
+
+    fn next_line<'a>(
+        self: &'a mut Self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<Self::Line<'a>>, Self::Error>> {
+        if self.token().is_cancelled() {
+            return Poll::Ready(Err(CancellableReadError::Cancelled));
+        }
+        match self.inner.next_line(cx) {
+            Poll::Ready(result) => {
+                self.waker_slot.clear();
+                Poll::Ready(result.map_err(CancellableReadError::Read))
+            }
+            Poll::Pending => {
+                self.waker_slot.register(cx.waker());
+                // The token may have been cancelled before the registration
+                if self.waker_slot.token().is_cancelled() {
+                    cx.waker().wake_by_ref();
+                }
+                Poll::Pending
+            }
+        }
+    }
+
+    fn consume_line_manually(&mut self, line_len: usize) {
+        self.inner.consume_line_manually(line_len);
+    }
+
+    fn observer(&self) -> Option<Arc<dyn LineObserver>> {
+        self.inner.observer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::io::{AsyncWriteExt, BufReader};
+
+    use crate::{engine_commands::UciOptionBlockBuilder, util::AsyncReadable};
+
+    #[tokio::test]
+    async fn test_cancel_block_read_and_resume() {
+        let (mut engine, gui) = tokio::io::duplex(1024);
+        let token = CancelToken::new();
+        let mut reader = CancellableReader::new(BufReader::new(gui), token.clone());
+
+        engine
+            .write_all(b"option name Threads type spin default 1 min 1 max 1024\n")
+            .await
+            .unwrap();
+        let read = UciOptionBlockBuilder::read_from(&mut reader);
+        let cancel = async {
+            tokio::task::yield_now().await;
+            token.cancel();
+        };
+        let (result, ()) = tokio::join!(read, cancel);
+        assert!(matches!(result, Err(CancellableReadError::Cancelled)));
+
+        // The read stopped at a line boundary, so the rest of the block is read as a whole
+        engine
+            .write_all(b"option name Hash type spin default 16 min 1 max 33554432\n\n")
+            .await
+            .unwrap();
+        let block = UciOptionBlockBuilder::read_from(reader.get_mut())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(block.threads.is_none());
+        assert_eq!(block.hash.unwrap().default, 16);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_token_leaves_line_unread() {
+        let output = "option name Threads type spin default 1 min 1 max 1024\n\n";
+        let token = CancelToken::new();
+        token.cancel();
+        let mut reader = CancellableReader::new(BufReader::new(output.as_bytes()), token);
+
+        assert!(matches!(
+            UciOptionBlockBuilder::read_from(&mut reader).await,
+            Err(CancellableReadError::Cancelled)
+        ));
+
+        let mut inner = reader.into_inner();
+        let block = UciOptionBlockBuilder::read_from(&mut inner)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(block.threads.is_some());
+    }
+}
@@ -0,0 +1,122 @@
+use core::task::{Context, Poll};
+use std::sync::Arc;
+
+use crate::util::{LineObserver, StreamingLineReader, streaming_line_reader::sealed};
+
+/// A [`StreamingLineReader`] wrapper that counts the lines consumed from the inner reader.
+///
+/// Peeked lines are not counted until they are consumed. This is useful for diagnostics
+/// when a response parser misbehaves, since the count tells how far it got.
+pub struct LineCountingReader<R>
+where
+    R: StreamingLineReader,
+{
+    inner: R,
+    lines_consumed: usize,
+}
+
+impl<R> LineCountingReader<R>
+where
+    R: StreamingLineReader,
+{
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            lines_consumed: 0,
+        }
+    }
+
+    /// The number of lines consumed since the creation of the reader or the last [`reset`](Self::reset).
+    pub fn lines_consumed(&self) -> usize {
+        self.lines_consumed
+    }
+
+    /// Resets the count of consumed lines to zero.
+    pub fn reset(&mut self) {
+        self.lines_consumed = 0;
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> sealed::Sealed for LineCountingReader<R> where R: StreamingLineReader {}
+
+impl<R> StreamingLineReader for LineCountingReader<R>
+where
+    R: StreamingLineReader,
+{
+    type Error = R::Error;
+
+    const AUTO_CONSUMING: bool = R::AUTO_CONSUMING;
+
+    type Line<'a>
+        = R::Line<'a>
+    where
+        Self: 'a;
+
+    fn next_line<'a>(
+        self: &'a mut Self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<Self::Line<'a>>, Self::Error>> {
+        let poll = self.inner.next_line(cx);
+        // Auto-consuming readers consume the line as soon as it is returned
+        if R::AUTO_CONSUMING && matches!(poll, Poll::Ready(Ok(Some(_)))) {
+            self.lines_consumed += 1;
+        }
+        poll
+    }
+
+    fn consume_line_manually(&mut self, line_len: usize) {
+        self.inner.consume_line_manually(line_len);
+        self.lines_consumed += 1;
+    }
+
+    fn observer(&self) -> Option<Arc<dyn LineObserver>> {
+        self.inner.observer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        fixtures::STOCKFISH_UCI_RESPONSE, gui_command_responses::UciResponse, util::AsyncReadable,
+    };
+
+    #[tokio::test]
+    async fn test_count_lines_of_uci_command_response() {
+        let input = format!("{STOCKFISH_UCI_RESPONSE}readyok\n");
+
+        let mut reader = LineCountingReader::new(tokio::io::BufReader::new(input.as_bytes()));
+        let response = UciResponse::read_from(&mut reader).await.unwrap().unwrap();
+
+        assert!(response.is_ok());
+        assert_eq!(reader.lines_consumed(), 24);
+    }
+
+    #[tokio::test]
+    async fn test_count_lines_until_failure() {
+        let input = "id name Stockfish 17.1\n\
+id author the Stockfish developers (see AUTHORS file)\n\
+option name Threads type spin default 1 min 1 max 1024\n\
+uciok\n";
+
+        let mut reader = LineCountingReader::new(tokio::io::BufReader::new(input.as_bytes()));
+        let response = UciResponse::read_from(&mut reader).await.unwrap().unwrap();
+
+        // The missing empty line after the id block is detected at the third line
+        assert!(response.is_err());
+        assert_eq!(reader.lines_consumed(), 3);
+    }
+}
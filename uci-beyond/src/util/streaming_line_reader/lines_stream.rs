@@ -0,0 +1,119 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::sync::Arc;
+
+use futures::stream::Stream;
+
+use crate::util::{LineObserver, StreamingLineReader};
+
+/// The [`Stream`] of the lines of a [`StreamingLineReader`], see [`lines_stream`].
+pub struct LinesStream<'r, R>
+where
+    R: StreamingLineReader,
+{
+    reader: &'r mut R,
+    observer: Option<Arc<dyn LineObserver>>,
+}
+
+/// Streams the lines of the reader without line terminators, e.g. to filter them with the
+/// combinators of [`StreamExt`](futures::StreamExt). This is the inverse of [`StringStreamReader`](crate::util::StringStreamReader).
+///
+/// A line is consumed only when it is yielded, so the stream can be dropped at any point, e.g. after
+/// [`take`](futures::StreamExt::take), and the reader goes on with the next line that wasn't yielded.
+pub fn lines_stream<R>(reader: &mut R) -> LinesStream<'_, R>
+where
+    R: StreamingLineReader,
+{
+    LinesStream {
+        observer: reader.observer(),
+        reader,
+    }
+}
+
+impl<R> Stream for LinesStream<'_, R>
+where
+    R: StreamingLineReader,
+{
+    type Item = Result<String, R::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let (line, len) = match this.reader.next_line(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+            Poll::Ready(Ok(None)) => return Poll::Ready(None),
+            Poll::Ready(Ok(Some(line))) => {
+                let line: &str = line.as_ref();
+                (line.trim_end_matches(['\r', '\n']).to_string(), line.len())
+            }
+        };
+
+        // The line is consumed in the same poll that yields it, so that no line is lost on drop
+        if !R::AUTO_CONSUMING {
+            this.reader.consume_line_manually(len);
+        }
+        if let Some(observer) = &this.observer {
+            observer.on_line(&line);
+        }
+        Poll::Ready(Some(Ok(line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt as _;
+
+    use crate::{
+        fixtures::STOCKFISH_UCI_RESPONSE,
+        gui_command_responses::{GreetingPolicy, UciResponse},
+        util::StringStreamReader,
+    };
+
+    const INPUT: &str = "info string first\r\ninfo string second\n";
+
+    async fn assert_resumes_with_uci_response<R>(reader: &mut R)
+    where
+        R: StreamingLineReader,
+    {
+        let lines: Vec<String> = lines_stream(reader)
+            .take(2)
+            .map(|line| line.ok().unwrap())
+            .collect()
+            .await;
+        assert_eq!(lines, ["info string first", "info string second"]);
+
+        // Strictly, a lost `id name` line would fail the response
+        let response = UciResponse::read_with(reader, GreetingPolicy::Strict)
+            .await
+            .ok()
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.option_block.options().len(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_lines_stream_then_bufreader() {
+        let input = format!("{INPUT}{STOCKFISH_UCI_RESPONSE}");
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        assert_resumes_with_uci_response(&mut reader).await;
+
+        let rest: Vec<_> = lines_stream(&mut reader).collect().await;
+        assert!(rest.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lines_stream_then_string_stream_reader() {
+        let input = format!("{INPUT}{STOCKFISH_UCI_RESPONSE}");
+        let lines = input
+            .lines()
+            .map(|line| Ok::<_, std::io::Error>(line.to_string()))
+            .collect::<Vec<_>>();
+        let mut reader = StringStreamReader::new(futures::stream::iter(lines));
+        assert_resumes_with_uci_response(&mut reader).await;
+    }
+}
@@ -1,11 +1,57 @@
 use core::task::{Context, Poll};
+use std::sync::Arc;
 
+use crate::util::LineObserver;
+
+mod cancellable_reader;
+mod line_counting_reader;
+mod lines_stream;
+mod observed_reader;
 mod string_stream_reader;
 mod tokio_io_bufreader_impl;
 
+pub use cancellable_reader::{CancellableReadError, CancellableReader};
+pub use line_counting_reader::LineCountingReader;
+pub use lines_stream::{LinesStream, lines_stream};
+pub use observed_reader::ObservedReader;
 pub use string_stream_reader::StringStreamReader;
 
-pub trait StreamingLineReader: Unpin + Send {
+/// The keeper of the [`StreamingLineReader`] implementations.
+pub(crate) mod sealed {
+    pub trait Sealed {}
+}
+
+/// The source of the lines read by the parsers of the responses, e.g. [`AsyncReadable::read_from`](crate::util::AsyncReadable::read_from).
+///
+/// # Stability
+///
+/// The trait is sealed: the parsers rely on how a line is peeked at and consumed (see [`StreamingLineReader::AUTO_CONSUMING`]),
+/// which is easy to get subtly wrong, and the trait may gain items. Any source of lines is read
+/// through [`StringStreamReader`], i.e. a [`Stream`](futures::Stream) of lines, or a [`tokio::io::BufReader`]:
+///
+/// ```compile_fail
+/// use std::task::{Context, Poll};
+///
+/// use uci_beyond::util::StreamingLineReader;
+///
+/// struct Lines(Vec<String>);
+///
+/// impl StreamingLineReader for Lines {
+///     type Error = std::convert::Infallible;
+///     const AUTO_CONSUMING: bool = true;
+///     type Line<'a> = String;
+///
+///     fn next_line<'a>(
+///         self: &'a mut Self,
+///         _cx: &mut Context<'_>,
+///     ) -> Poll<Result<Option<String>, Self::Error>> {
+///         Poll::Ready(Ok(self.0.pop()))
+///     }
+///
+///     fn consume_line_manually(&mut self, _line_len: usize) {}
+/// }
+/// ```
+pub trait StreamingLineReader: sealed::Sealed + Unpin + Send {
     /// The type of error that can occur while reading a line.
     type Error: Send;
 
@@ -28,6 +74,13 @@ pub trait StreamingLineReader: Unpin + Send {
     ) -> Poll<Result<Option<Self::Line<'a>>, Self::Error>>;
 
     fn consume_line_manually(&mut self, line_len: usize);
+
+    /// The observer of the lines read from the reader, see [`ObservedReader`].
+    ///
+    /// The wrappers forward the observer of the inner reader.
+    fn observer(&self) -> Option<Arc<dyn LineObserver>> {
+        None
+    }
 }
 
 pub enum LineHandlerOutcome<O, E> {
@@ -47,6 +100,9 @@ where
 {
     use core::future::poll_fn;
 
+    // Fetched once, so that a reader without an observer only pays for the check
+    let observer = reader.observer();
+
     poll_fn(|cx| {
         let (poll, len, should_consume) = match reader.next_line(cx) {
             Poll::Pending => return Poll::Pending,
@@ -59,6 +115,9 @@ where
 
                 // Only consume if we actually read the line (not if we peeked)
                 let should_consume = !matches!(o, LineHandlerOutcome::Peeked);
+                if let Some(observer) = observer.as_deref().filter(|_| should_consume) {
+                    observer.on_line(line_str.trim_end_matches(['\r', '\n']));
+                }
 
                 (Poll::Ready(Ok(Some(o))), len, should_consume)
             }
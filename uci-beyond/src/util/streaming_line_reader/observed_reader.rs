@@ -0,0 +1,111 @@
+use core::task::{Context, Poll};
+use std::sync::Arc;
+
+use crate::util::{
+    FanOutObserver, LineObserver, StreamingLineReader, streaming_line_reader::sealed,
+};
+
+/// A [`StreamingLineReader`] wrapper that reports the lines read through it to the [`LineObserver`].
+///
+/// The observer is attached to the reader rather than to every response, so the whole read path,
+/// e.g. [`AsyncReadable::read_from`](crate::util::AsyncReadable::read_from), reports to it.
+/// The observer of the wrapped reader, if any, keeps being reported to.
+pub struct ObservedReader<R>
+where
+    R: StreamingLineReader,
+{
+    inner: R,
+    observer: Arc<dyn LineObserver>,
+}
+
+impl<R> ObservedReader<R>
+where
+    R: StreamingLineReader,
+{
+    pub fn new(inner: R, observer: Arc<dyn LineObserver>) -> Self {
+        let observer = match inner.observer() {
+            Some(of_inner) => Arc::new(FanOutObserver::new([of_inner, observer])),
+            None => observer,
+        };
+        Self { inner, observer }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> sealed::Sealed for ObservedReader<R> where R: StreamingLineReader {}
+
+impl<R> StreamingLineReader for ObservedReader<R>
+where
+    R: StreamingLineReader,
+{
+    type Error = R::Error;
+
+    const AUTO_CONSUMING: bool = R::AUTO_CONSUMING;
+
+    type Line<'a>
+        = R::Line<'a>
+    where
+        Self: 'a;
+
+    fn next_line<'a>(
+        self: &'a mut Self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<Self::Line<'a>>, Self::Error>> {
+        self.inner.next_line(cx)
+    }
+
+    fn consume_line_manually(&mut self, line_len: usize) {
+        self.inner.consume_line_manually(line_len);
+    }
+
+    fn observer(&self) -> Option<Arc<dyn LineObserver>> {
+        Some(Arc::clone(&self.observer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        engine_commands::UciOptionBlockBuilder,
+        util::{AsyncReadable, CountingObserver, read_line},
+    };
+
+    #[tokio::test]
+    async fn test_peeked_lines_are_reported_once() {
+        let input = "option name Hash type spin default 16 min 1 max 1024\n\
+            option nonsense\n\
+            uciok\n";
+        let observer = Arc::new(CountingObserver::new());
+        let mut reader = ObservedReader::new(
+            tokio::io::BufReader::new(input.as_bytes()),
+            observer.clone(),
+        );
+
+        // The block stops at the line that fails to parse
+        let block = UciOptionBlockBuilder::read_from(&mut reader).await.unwrap();
+        assert!(matches!(block, Some(Err(_))));
+        assert_eq!(
+            (observer.lines(), observer.parsed(), observer.errors()),
+            (2, 1, 1)
+        );
+
+        assert_eq!(
+            read_line(&mut reader).await.unwrap().as_deref(),
+            Some("uciok")
+        );
+        assert_eq!(observer.lines(), 3);
+    }
+}
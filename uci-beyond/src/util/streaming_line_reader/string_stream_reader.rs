@@ -1,6 +1,6 @@
 use futures::stream::Stream;
 
-use crate::util::StreamingLineReader;
+use crate::util::{StreamingLineReader, streaming_line_reader::sealed};
 
 pub struct StringStreamReader<E, S>
 where
@@ -24,6 +24,19 @@ where
             current_line: None,
         }
     }
+
+    /// Returns the line that was peeked at but not consumed, if any, e.g. to put it back
+    /// into the buffer that the stream reads from.
+    pub fn into_peeked_line(self) -> Option<String> {
+        self.current_line
+    }
+}
+
+impl<E, S> sealed::Sealed for StringStreamReader<E, S>
+where
+    S: Stream<Item = Result<String, E>> + Unpin + Send,
+    E: Send,
+{
 }
 
 impl<E, S> StreamingLineReader for StringStreamReader<E, S>
@@ -3,7 +3,9 @@ use std::{
     task::{Context, Poll},
 };
 
-use crate::util::StreamingLineReader;
+use crate::util::{StreamingLineReader, streaming_line_reader::sealed};
+
+impl<R> sealed::Sealed for tokio::io::BufReader<R> where R: tokio::io::AsyncRead + Unpin + Send {}
 
 impl<R> StreamingLineReader for tokio::io::BufReader<R>
 where
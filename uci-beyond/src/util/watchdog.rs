@@ -0,0 +1,372 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::Mutex,
+};
+
+use crate::util::{
+    CancelToken,
+    io_connection::{Incoming, Outgoing, Pings},
+};
+
+/// How often a [`Watchdog`] pings the engine and how long it waits for the answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchdogConfig {
+    /// The time between the answer to a ping and the next ping.
+    pub interval: Duration,
+    /// The time the engine has to answer a ping with `readyok`.
+    pub deadline: Duration,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            deadline: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Pings the engine with `isready` over an [`IoConnection`](crate::util::IoConnection) or a
+/// [`Router`](crate::util::Router), e.g. during a search, and raises the alarm when the engine doesn't answer
+/// with `readyok` in time.
+///
+/// The `readyok` answers to the pings never reach the responses, e.g. the [`ResponseHandle`](crate::util::ResponseHandle)
+/// of `go`. Over a connection, they are taken out of the incoming lines: when nothing else is reading, the watchdog
+/// reads the lines itself and keeps the other ones, e.g. the `info` lines, for the responses in order.
+/// A `readyok` is only taken while a ping is in flight, and only once the `isready` commands sent
+/// with [`Connection::send`](crate::util::Connection::send) have been answered, so their answers still reach them.
+/// Over a router, each ping is a request of its own, to which the `readyok` is routed.
+pub struct Watchdog {
+    pinger: Box<dyn Pinger>,
+    config: WatchdogConfig,
+    token: Option<CancelToken>,
+    on_unresponsive: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl Watchdog {
+    pub(super) fn new(pinger: impl Pinger + 'static, config: WatchdogConfig) -> Self {
+        Self {
+            pinger: Box::new(pinger),
+            config,
+            token: None,
+            on_unresponsive: None,
+        }
+    }
+
+    /// Cancels the token when the engine stops responding, e.g. the one of
+    /// [`Session::evaluate_positions_with`](crate::session::Session::evaluate_positions_with).
+    pub fn cancelling(mut self, token: CancelToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Calls the callback when the engine stops responding.
+    pub fn on_unresponsive<F>(mut self, callback: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.on_unresponsive = Some(Box::new(callback));
+        self
+    }
+
+    /// Sends `isready` and waits for the `readyok` until the deadline, which includes the wait
+    /// for the connection, e.g. while another command is being written.
+    ///
+    /// Returns whether the engine answered in time. The end of the input counts as no answer.
+    pub async fn ping(&self) -> std::io::Result<bool> {
+        self.pinger.ping(self.config.deadline).await
+    }
+
+    /// Pings the engine every [`interval`](WatchdogConfig::interval) until it stops responding,
+    /// then calls the callback and cancels the token.
+    ///
+    /// The future never completes while the engine is responsive, so it's meant to be raced
+    /// against the search, e.g. with `tokio::select!`. Dropping it stops the pings.
+    pub async fn run(mut self) -> std::io::Result<()> {
+        loop {
+            tokio::time::sleep(self.config.interval).await;
+            if self.ping().await? {
+                continue;
+            }
+
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                deadline = ?self.config.deadline,
+                "the engine didn't answer isready in time"
+            );
+            if let Some(callback) = self.on_unresponsive.take() {
+                callback();
+            }
+            if let Some(token) = &self.token {
+                token.cancel();
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// Sends `isready` and waits for its `readyok`.
+#[async_trait]
+pub(super) trait Pinger: Send + Sync {
+    /// Whether the engine answered before the deadline. The end of the input counts as no answer.
+    async fn ping(&self, deadline: Duration) -> std::io::Result<bool>;
+}
+
+/// Pings over an [`IoConnection`](crate::util::IoConnection), reading the lines itself when nothing else does.
+pub(super) struct ConnectionPinger<R, W> {
+    pub(super) incoming: Arc<Mutex<Incoming<R>>>,
+    pub(super) outgoing: Arc<Mutex<Outgoing<W>>>,
+    pub(super) pings: Arc<Pings>,
+}
+
+impl<R, W> ConnectionPinger<R, W>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    /// Waits until the count of the answers exceeds `pongs`, reading the lines unless a response does.
+    ///
+    /// A response that holds the read half takes the answer out itself, so the count is awaited meanwhile.
+    async fn wait_for_pong(&self, pongs: u64) -> std::io::Result<bool> {
+        loop {
+            let answered = self.pings.answered();
+            if self.pings.pongs() > pongs {
+                return Ok(true);
+            }
+            let mut incoming = tokio::select! {
+                () = answered => continue,
+                incoming = self.incoming.lock() => incoming,
+            };
+            if self.pings.pongs() > pongs {
+                return Ok(true);
+            }
+            let Some(line) = incoming.read_from_engine().await? else {
+                return Ok(false);
+            };
+            if !incoming.take_pong(&line) {
+                incoming.pending.push_back(line);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<R, W> Pinger for ConnectionPinger<R, W>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    async fn ping(&self, deadline: Duration) -> std::io::Result<bool> {
+        let pongs = self.pings.start();
+        let mut sent = false;
+        let answered = tokio::time::timeout(deadline, async {
+            self.outgoing.lock().await.write_ping().await?;
+            sent = true;
+            self.wait_for_pong(pongs).await
+        })
+        .await;
+        // A sent ping stays in flight, so a late `readyok` is still taken out
+        if !sent {
+            self.pings.cancel();
+        }
+        match answered {
+            Ok(answered) => answered,
+            Err(_elapsed) => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::task::JoinHandle;
+
+    use super::*;
+    use crate::{
+        fixtures::{DuplexConnection, FakeEngine},
+        gui_command_responses::GoResponse,
+        gui_commands::{GoCommand, IsReadyCommand, StopCommand},
+        util::{Connection, ResponseHandle},
+    };
+
+    const CONFIG: WatchdogConfig = WatchdogConfig {
+        interval: Duration::from_millis(10),
+        deadline: Duration::from_millis(100),
+    };
+
+    /// Connects to an engine that answers only the first `answered_pings` of `isready`, e.g. because its
+    /// search hangs.
+    fn connect(answered_pings: usize) -> (DuplexConnection, JoinHandle<Vec<String>>) {
+        FakeEngine::new(|line| {
+            Some(match line {
+                "isready" => "readyok\n",
+                "go infinite" => {
+                    "info depth 1 seldepth 1 multipv 1 score cp 20 nodes 20 nps 20000 time 1 pv e2e4\n"
+                }
+                "stop" => "bestmove e2e4\n",
+                _ => "",
+            })
+        })
+        .answering_pings(answered_pings)
+        .connect()
+    }
+
+    async fn go_infinite(
+        connection: &mut DuplexConnection,
+    ) -> ResponseHandle<GoResponse, std::io::Error> {
+        let go_infinite = GoCommand {
+            indefinite: true,
+            ..Default::default()
+        };
+        connection
+            .send_expecting_later_response::<_, GoResponse>(go_infinite)
+            .await
+            .unwrap()
+    }
+
+    async fn stop_and_quit(
+        mut connection: DuplexConnection,
+        search: ResponseHandle<GoResponse, std::io::Error>,
+        engine: JoinHandle<Vec<String>>,
+    ) {
+        let Ok(()) = connection.send(StopCommand).await.unwrap();
+        // Neither the `readyok` lines nor the lines read by the watchdog are lost to the search
+        let response = search.await.unwrap().unwrap();
+        assert_eq!(response.search_summary.depth, Some(1));
+        assert_eq!(response.search_summary.pv.len(), 1);
+        assert!(response.search_summary.best_move.is_some());
+
+        connection.send_raw_line("quit").await.unwrap();
+        engine.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_detects_unresponsive_engine() {
+        let (mut connection, engine) = connect(1);
+        let search = go_infinite(&mut connection).await;
+
+        let token = CancelToken::new();
+        let alarms = Arc::new(AtomicUsize::new(0));
+        let watchdog = connection
+            .watchdog(CONFIG)
+            .cancelling(token.clone())
+            .on_unresponsive({
+                let alarms = Arc::clone(&alarms);
+                move || {
+                    alarms.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        // The first ping is answered, the second one isn't
+        watchdog.run().await.unwrap();
+        assert!(token.is_cancelled());
+        assert_eq!(alarms.load(Ordering::Relaxed), 1);
+        assert_eq!(connection.diagnostics().commands_sent("isready"), 2);
+
+        stop_and_quit(connection, search, engine).await;
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_keeps_quiet_while_engine_responds() {
+        let (mut connection, engine) = connect(usize::MAX);
+        let search = go_infinite(&mut connection).await;
+
+        let token = CancelToken::new();
+        let watchdog = connection.watchdog(CONFIG).cancelling(token.clone());
+        tokio::select! {
+            res = watchdog.run() => panic!("The watchdog raised the alarm: {res:?}"),
+            () = tokio::time::sleep(Duration::from_millis(100)) => {}
+        }
+        assert!(!token.is_cancelled());
+        assert!(connection.diagnostics().commands_sent("isready") >= 2);
+
+        // With no ping in flight, `readyok` is the response of `isready`
+        let Ok(()) = connection.send(IsReadyCommand).await.unwrap();
+        assert_eq!(
+            connection.read_raw_line().await.unwrap().as_deref(),
+            Some("readyok")
+        );
+
+        stop_and_quit(connection, search, engine).await;
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_leaves_the_answer_to_isready() {
+        let (mut connection, engine) = connect(1);
+        // The `readyok` isn't read before the watchdog pings, so both are in flight together
+        let Ok(()) = connection.send(IsReadyCommand).await.unwrap();
+
+        let token = CancelToken::new();
+        connection
+            .watchdog(CONFIG)
+            .cancelling(token.clone())
+            .run()
+            .await
+            .unwrap();
+        // The only `readyok` answers the command, so the first ping is already unanswered
+        assert!(token.is_cancelled());
+        assert_eq!(connection.diagnostics().commands_sent("isready"), 2);
+        assert_eq!(
+            connection.read_raw_line().await.unwrap().as_deref(),
+            Some("readyok")
+        );
+
+        connection.send_raw_line("quit").await.unwrap();
+        assert_eq!(engine.await.unwrap(), ["isready", "isready", "quit"]);
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_while_search_is_read() {
+        // The search holds the read half while it waits for the next line
+        for (answered_pings, alarm) in [(usize::MAX, false), (0, true)] {
+            let (mut connection, engine) = connect(answered_pings);
+            let mut search = go_infinite(&mut connection).await;
+
+            let token = CancelToken::new();
+            let watchdog = connection.watchdog(CONFIG).cancelling(token.clone());
+            tokio::select! {
+                biased;
+                res = &mut search => panic!("The search ended: {res:?}"),
+                res = watchdog.run() => res.unwrap(),
+                () = tokio::time::sleep(Duration::from_millis(300)) => {}
+            }
+            assert_eq!(token.is_cancelled(), alarm);
+
+            stop_and_quit(connection, search, engine).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_through_router() {
+        let (connection, engine) = connect(1);
+        let router = connection.into_router();
+        let search = router
+            .go(GoCommand {
+                indefinite: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let token = CancelToken::new();
+        // The router reads all the time, so a watchdog of the connection would never get the read half
+        let watchdog = router.watchdog(CONFIG).cancelling(token.clone());
+        assert!(watchdog.ping().await.unwrap());
+        // The first ping is answered, the second one isn't
+        watchdog.run().await.unwrap();
+        assert!(token.is_cancelled());
+        assert_eq!(router.diagnostics().commands_sent("isready"), 2);
+
+        // The `readyok` went to the watchdog and the `info` line to the search
+        router.stop().await.unwrap();
+        let response = search.await.unwrap().unwrap();
+        assert_eq!(response.search_summary.depth, Some(1));
+        assert!(response.search_summary.best_move.is_some());
+
+        router.send_raw_line("quit").await.unwrap();
+        engine.await.unwrap();
+    }
+}